@@ -11,7 +11,13 @@
 //     command's functionality.
 // - Add the command to the `Command` enum in this module.
 
+pub(crate) mod convert;
 pub(crate) mod format;
+pub(crate) mod matcher;
+#[cfg(feature = "review")]
+pub(crate) mod publish;
+pub(crate) mod selftest;
+pub(crate) mod trend;
 pub(crate) mod version;
 
 use anyhow::Result;
@@ -19,8 +25,26 @@ use anyhow::Result;
 /// Available subcommands for the CLI.
 #[derive(Debug, clap::Subcommand)]
 pub enum Command {
+    /// Convert tool output to a report in another format.
+    Convert(convert::Args),
+
     /// Format tool output for CI platforms.
-    Format(format::Args),
+    Format(Box<format::Args>),
+
+    /// Register a GitHub problem matcher for a tool's plain-text output.
+    Matcher(matcher::Args),
+
+    /// Publish the annotations of formatted output to a code review service.
+    #[cfg(feature = "review")]
+    Publish(publish::Args),
+
+    /// Write a sample of each kind of message, to check how the platform
+    /// renders them.
+    Selftest(selftest::Args),
+
+    /// Compare the latest run recorded in a history against the previous
+    /// runs.
+    Trend(trend::Args),
 
     /// Show version information.
     Version(version::Args),
@@ -28,10 +52,67 @@ pub enum Command {
 
 impl Default for Command {
     fn default() -> Self {
-        Command::Format(format::Args {
+        Command::Format(Box::new(format::Args {
             tool: None,
             detect: true,
-        })
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            trace: None,
+            junit: None,
+            rdjson: None,
+            rdjsonl: None,
+            codeclimate: None,
+            checkstyle: None,
+            emits: Vec::new(),
+            max_output: None,
+            max_annotations: None,
+            explain: false,
+            debug_raw: None,
+            artifact_dir: None,
+            parse: format::ParseArgs {
+                max_line_length: None,
+                strip_ansi: false,
+                lossy_utf8: false,
+                passthrough: format::PassthroughPolicy::Drop,
+                test_output: format::TestOutputPolicy::Always,
+            },
+            expand_failures: Vec::new(),
+            debug_messages: format::DebugPolicy::Auto,
+            fold_children: false,
+            deny: Vec::new(),
+            read_sources: false,
+            catalog: None,
+            checkout_root: None,
+            metadata: false,
+            suite_budget: None,
+            run_budget: None,
+            coverage_threshold: None,
+            stable_order: false,
+            group_by_crate: false,
+            idle_timeout: None,
+            buildkite_annotate: false,
+            summary: false,
+            report_dir: None,
+            #[cfg(feature = "checks")]
+            check_run: None,
+            #[cfg(feature = "issues")]
+            file_issues: None,
+            #[cfg(feature = "issues")]
+            issue_pattern: Vec::new(),
+            #[cfg(feature = "issues")]
+            issue_label: cifmt::issues::IssueFiler::DEFAULT_LABEL.to_owned(),
+            #[cfg(feature = "notify")]
+            notify: Vec::new(),
+            #[cfg(feature = "notify")]
+            notify_max_failures: cifmt::notify::Notifier::DEFAULT_MAX_FAILURES,
+            code_owners: None,
+            binary_sizes: false,
+            size_baseline: None,
+            size_threshold: 5,
+            save_sizes: None,
+            history: None,
+            check: false,
+        }))
     }
 }
 
@@ -49,7 +130,13 @@ impl Command {
     /// Execute the command.
     pub(crate) fn execute(self) -> Result<()> {
         match self {
-            Command::Format(args) => format::execute(args),
+            Command::Convert(args) => convert::execute(args),
+            Command::Format(args) => format::execute(*args),
+            Command::Matcher(args) => matcher::execute(args),
+            #[cfg(feature = "review")]
+            Command::Publish(args) => publish::execute(args),
+            Command::Selftest(args) => selftest::execute(args),
+            Command::Trend(args) => trend::execute(args),
             Command::Version(args) => version::execute(args),
         }
     }
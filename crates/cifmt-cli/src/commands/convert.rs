@@ -0,0 +1,96 @@
+//! Convert command implementation.
+//!
+//! This module handles the convert command, which parses the output of a tool
+//! and writes it as a report in another format (e.g., a JUnit XML report as
+//! Code Climate issues), without formatting it for a CI platform.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use cifmt::pipeline::Pipeline;
+use cifmt::tool::{self, AnyTool, ToolOptions};
+
+use crate::commands::format::{
+    CHUNK_SIZE, EmitSpec, ParseArgs, ReportFormat, ToolFormat, configure_input, configure_tool,
+    detection_sample,
+};
+
+/// Arguments for the convert command.
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// The tool format of the input.
+    ///
+    /// If not specified, the tool is detected from the input. Any tool which
+    /// `format` supports can be converted.
+    #[arg(value_enum)]
+    tool: Option<ToolFormat>,
+
+    /// Read the tool's output from this file, rather than from stdin.
+    #[arg(long, value_name = "PATH")]
+    input: Option<PathBuf>,
+
+    /// The format of the report written.
+    ///
+    /// Any report which `format --emit` writes can be written.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    to: ReportFormat,
+
+    /// Write the report to this file, rather than to stdout.
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// How the tool's output is read and parsed.
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+/// Execute the convert command.
+///
+/// The input is parsed as for the format command, but only the requested
+/// report is written. Except for `jsonl`, whose records are written as the
+/// input is parsed, the report is written once all of the input is parsed.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Reading from stdin, or from the input file, fails
+/// - No tool is given and none could be detected
+/// - The report cannot be written
+#[tracing::instrument]
+pub(crate) fn execute(
+    Args {
+        tool: format,
+        input,
+        to,
+        output,
+        parse,
+    }: Args,
+) -> Result<()> {
+    let mut reader: Box<dyn Read + Send> = match &input {
+        Some(path) => Box::new(
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+        ),
+        None => Box::new(io::stdin()),
+    };
+    let mut buffer = Vec::new();
+    let mut tool: Box<dyn AnyTool> = if let Some(tool_format) = format {
+        tool_format.into_any_tool()
+    } else {
+        buffer.resize(CHUNK_SIZE, 0);
+        let n = reader.read(&mut buffer)?;
+        buffer.truncate(n);
+        tool::detect_any(&detection_sample(&buffer, &parse))?
+    };
+    tracing::info!("Using tool: {}", tool.name());
+    configure_tool(tool.as_mut(), &parse, &ToolOptions::default());
+
+    let report = EmitSpec::new(to, output);
+    let mut pipeline = report.enable(configure_input(Pipeline::new(tool, Vec::new()), &parse))?;
+    pipeline.process(&buffer)?;
+    pipeline.run(reader)?;
+    pipeline.finish()?;
+
+    report.write(&pipeline)
+}
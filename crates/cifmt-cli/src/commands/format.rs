@@ -2,16 +2,46 @@
 //!
 //! This module handles the formatting of tool output for CI platforms.
 
-use anyhow::Result;
-use cifmt::ci::{GitHub, Plain, Platform};
-use cifmt::tool::{self, DynTool};
+use anyhow::{Context as _, Result};
+use cifmt::ansi;
+use cifmt::capture::{OutputPolicy, TestGrouping};
+use cifmt::catalog::Catalog;
+#[cfg(feature = "checks")]
+use cifmt::checks::CheckRun;
+use cifmt::ci::{self, GitHub, PlatformKind};
+use cifmt::exit_code;
+use cifmt::history::History;
+#[cfg(feature = "issues")]
+use cifmt::issues::IssueFiler;
+use cifmt::matcher::ProblemMatcher;
+use cifmt::metadata::RunMetadata;
+#[cfg(feature = "notify")]
+use cifmt::notify::{Notifier, Webhook};
+use cifmt::owners::CodeOwners;
+use cifmt::pipeline::{DebugMessages, Pipeline, Target};
+use cifmt::report::ReportArtifact;
+use cifmt::sizes::SizeStats;
+use cifmt::timeline::TimeBudget;
+use cifmt::timeout::IdleReader;
+use cifmt::tool::{self, AnyTool, Passthrough, ToolOption, ToolOptions};
+use cifmt::utf8;
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+use std::env;
+use std::fs::File;
 use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 
-/// Size of each read chunk from stdin.
-const CHUNK_SIZE: usize = 16 * 1024;
+/// Size of the initial read from stdin used for detection.
+pub(crate) const CHUNK_SIZE: usize = 16 * 1024;
 
 /// Arguments for the format command.
 #[derive(Debug, clap::Args)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each bool is an independent command-line flag"
+)]
 pub struct Args {
     /// The tool format to use.
     ///
@@ -23,6 +53,643 @@ pub struct Args {
     /// Automatically detect the tool format from the input.
     #[arg(long, group = "tool_selection")]
     pub detect: bool,
+
+    /// Read the tool's output from this file, rather than from stdin.
+    ///
+    /// May be specified multiple times to consolidate the output of several
+    /// runs (e.g., one for each shard of a matrix build). The files are
+    /// parsed in parallel, and their messages written in the order in which
+    /// the files are given. With `--detect`, the tool is detected from the
+    /// first file.
+    #[arg(long = "input", value_name = "PATH", conflicts_with = "idle_timeout")]
+    pub inputs: Vec<PathBuf>,
+
+    /// An output target, as `PLATFORM` or `PLATFORM=PATH`.
+    ///
+    /// May be specified multiple times to format the input for several
+    /// platforms in a single pass. Targets without a path are written to
+    /// stdout. If no output is specified, the platform is detected from the
+    /// environment and written to stdout.
+    ///
+    /// Supported platforms: `plain`, `github`, `azure`, `buildkite`,
+    /// `codebuild`, `cloudbuild`, `terminal`. Colored `terminal` output is
+    /// detected when stdout is a terminal and `NO_COLOR` is unset.
+    #[arg(long = "output", value_name = "PLATFORM[=PATH]")]
+    pub outputs: Vec<OutputSpec>,
+
+    /// Alias of `--emit trace=PATH`.
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub trace: Option<PathBuf>,
+
+    /// Alias of `--emit junit=PATH`.
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub junit: Option<PathBuf>,
+
+    /// Alias of `--emit rdjson=PATH`.
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub rdjson: Option<PathBuf>,
+
+    /// Alias of `--emit rdjsonl=PATH`.
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub rdjsonl: Option<PathBuf>,
+
+    /// Alias of `--emit codeclimate=PATH`.
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub codeclimate: Option<PathBuf>,
+
+    /// Alias of `--emit checkstyle=PATH`.
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub checkstyle: Option<PathBuf>,
+
+    /// A report to write, as `FORMAT` or `FORMAT=PATH`.
+    ///
+    /// May be specified multiple times to write several reports. Reports
+    /// without a path are written to stdout, in which case the formatted
+    /// output is only written to the targets given with `--output`.
+    ///
+    /// Supported formats: `junit`, the results of the tests in the JUnit XML
+    /// format, for platforms which display test results from such a report
+    /// (e.g., AWS CodeBuild's test reports); `rdjson` and `rdjsonl`, the
+    /// annotations in the Reviewdog Diagnostic Format, as a single JSON object
+    /// or as one diagnostic per line, to be read by reviewdog; `codeclimate`,
+    /// the annotations as a JSON array of Code Climate issues, for dashboards
+    /// such as GitLab's code quality reports; `checkstyle`, the annotations in
+    /// the Checkstyle XML format, for plugins such as the Warnings Next
+    /// Generation plugin of Jenkins; `sarif`, the annotations in the SARIF
+    /// format, with the fixes which the tool considers safe to apply
+    /// automatically, for code scanning services such as GitHub's code
+    /// scanning alerts; `github-annotations`, a JSON array of the
+    /// annotations as the arguments of the functions of `@actions/core`
+    /// creating them, to create them from another step through
+    /// `actions/github-script`; `html`, a self-contained page of the
+    /// annotations and the results of the tests, which can be filtered by
+    /// severity, outcome and text, to be uploaded as an artifact of the job;
+    /// `jsonl`, a record of each parsed message as a line of JSON, in a schema
+    /// which is the same for all tools (its severity, locations, code, title,
+    /// body and timing), written as the input is processed; `trace`, the timing
+    /// information in the Chrome trace-event format, for a trace viewer such as
+    /// Perfetto or `chrome://tracing`.
+    #[arg(long = "emit", value_name = "FORMAT[=PATH]")]
+    pub emits: Vec<EmitSpec>,
+
+    /// The maximum size of captured output (e.g., a failed test's stdout)
+    /// written to each output, in bytes.
+    ///
+    /// Longer output is truncated, keeping its head and tail. Defaults to a
+    /// limit suited to each output's platform.
+    #[arg(long, value_name = "BYTES")]
+    pub max_output: Option<usize>,
+
+    /// The maximum number of annotations created on GitHub Actions.
+    ///
+    /// Errors take priority over warnings and notices. Annotations beyond the
+    /// limit are written to the log instead, and counted in a single warning,
+    /// which is not given if the limit is 0. GitHub's own limit of 10 annotations of each type per step always
+    /// applies.
+    #[arg(long, value_name = "COUNT")]
+    pub max_annotations: Option<usize>,
+
+    /// Explain each parsed message instead of writing it: its kind,
+    /// severity, the filters applied to it, and the output it would produce
+    /// for each platform.
+    ///
+    /// Use this to debug the configuration of the outputs and filters.
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Write the raw record of each parsed message as a line of JSON,
+    /// alongside the formatted output: its kind, severity, classification,
+    /// fingerprint and plain text.
+    ///
+    /// The records are written to stderr, or to the given file
+    /// (`--debug-raw=PATH`). Use this to develop the output for a new
+    /// platform, or to report a bug in a parser.
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-"
+    )]
+    pub debug_raw: Option<PathBuf>,
+
+    /// Write captured output which is truncated in full to a file within
+    /// this directory.
+    #[arg(long, value_name = "DIR")]
+    pub artifact_dir: Option<PathBuf>,
+
+    /// How the tool's output is read and parsed.
+    #[command(flatten)]
+    pub parse: ParseArgs,
+
+    /// On these platforms, only group the output of passing tests, and write
+    /// the output of failing tests outside of any group, so that it is
+    /// visible without expanding a group.
+    ///
+    /// May be specified multiple times, or as a comma-separated list. Only
+    /// affects platforms with collapsible groups (`github` and `azure`).
+    #[arg(long, value_name = "PLATFORM", value_delimiter = ',')]
+    pub expand_failures: Vec<PlatformKind>,
+
+    /// When to write debug messages on GitHub Actions, which hides them
+    /// unless debug logging is enabled.
+    ///
+    /// By default (`auto`), debug messages are only written if `RUNNER_DEBUG`
+    /// or `ACTIONS_STEP_DEBUG` is set. Omitted debug messages are counted in a
+    /// single line at the end of the output. With `downgrade`, they are
+    /// written as plain lines instead, so that they are always shown.
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t)]
+    pub debug_messages: DebugPolicy,
+
+    /// Fold the notes and help attached to a compiler diagnostic into its
+    /// annotation, rather than annotating each separately.
+    ///
+    /// The full diagnostic is written to a collapsed group of the log.
+    #[arg(long)]
+    pub fold_children: bool,
+
+    /// Report the diagnostics of this lint as errors, whichever level the
+    /// compiler reported (e.g., `unsafe_code` or `clippy::unwrap_used`).
+    ///
+    /// May be specified multiple times, or as a comma-separated list. The
+    /// title of their annotations is prefixed with `denied`, and they fail
+    /// the step with `--check`.
+    #[arg(long, value_name = "LINT", value_delimiter = ',')]
+    pub deny: Vec<String>,
+
+    /// Read the source of a diagnostic from disk when the compiler does not
+    /// include it, to show a snippet of the code in the terminal and in the
+    /// Buildkite annotation.
+    ///
+    /// Paths are resolved relative to the current directory, which should be
+    /// the root of the workspace.
+    #[arg(long)]
+    pub read_sources: bool,
+
+    /// Read the titles of annotations and groups from this file, rather
+    /// than writing them in English.
+    ///
+    /// The file is a JSON object mapping the key of each title to override
+    /// to its text (e.g., `{"test-failed": "Test fehlgeschlagen"}`). Keys
+    /// are the titles in kebab case, and titles not in the file are written
+    /// in English.
+    #[arg(long, value_name = "PATH")]
+    pub catalog: Option<PathBuf>,
+
+    /// The root of the checkout, to which the paths of files within it are
+    /// made relative, so that annotations link to the source.
+    ///
+    /// Defaults to the root given by the CI platform (e.g.,
+    /// `GITHUB_WORKSPACE`), or else the current directory.
+    #[arg(long, value_name = "PATH")]
+    pub checkout_root: Option<PathBuf>,
+
+    /// Start each output with a group describing the run: the tool, the
+    /// version of cifmt, the platforms, the command line, the host and the
+    /// time at which it started.
+    ///
+    /// The same metadata is included in the `--junit` report and the
+    /// `--trace`.
+    #[arg(long)]
+    pub metadata: bool,
+
+    /// Warn about each test suite which passes, but takes longer than this
+    /// many seconds.
+    ///
+    /// The suite's completion is annotated with a warning rather than a
+    /// notice, so that a slow increase in the duration of the tests is
+    /// noticed.
+    #[arg(long, value_name = "SECONDS")]
+    pub suite_budget: Option<u64>,
+
+    /// Warn once the test suites run take longer than this many seconds in
+    /// total.
+    ///
+    /// The completion of the suite exceeding the budget is annotated with a
+    /// warning rather than a notice.
+    #[arg(long, value_name = "SECONDS")]
+    pub run_budget: Option<u64>,
+
+    /// Warn about each source file whose line coverage is below this
+    /// percentage, and about the total coverage if it is below it.
+    ///
+    /// Only applies to coverage reports (e.g., `lcov`).
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub coverage_threshold: Option<u8>,
+
+    /// Sort the messages of concurrently running tests or compilations
+    /// (e.g., by test name), so that the output is identical across runs.
+    ///
+    /// Messages are held back until the end of each test suite or build,
+    /// rather than being streamed.
+    #[arg(long)]
+    pub stable_order: bool,
+
+    /// Group the messages of each crate of a workspace in the log.
+    ///
+    /// GitHub Actions and Azure Pipelines do not nest groups, so that this
+    /// is best not combined with `--fold-children`.
+    #[arg(long)]
+    pub group_by_crate: bool,
+
+    /// Stop reading once no input has been received for this many seconds.
+    ///
+    /// This guards against a tool which hangs. The input read so far is
+    /// formatted as usual, followed by a timeout error, and the command
+    /// exits with a non-zero status.
+    #[arg(long, value_name = "SECONDS")]
+    pub idle_timeout: Option<u64>,
+
+    /// Summarize the errors in a Buildkite annotation once the input has
+    /// been processed.
+    ///
+    /// This runs `buildkite-agent annotate`, which must be available.
+    #[arg(long)]
+    pub buildkite_annotate: bool,
+
+    /// Write a Markdown summary of the results (e.g., test totals, failed and
+    /// slowest tests) to the job summary of the GitHub Actions step.
+    ///
+    /// The summary is appended to the file given by `GITHUB_STEP_SUMMARY`,
+    /// which must be set.
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Write the full report of the run (the summary of the results and the
+    /// annotations beyond GitHub's limit) to `cifmt-report.md` within this
+    /// directory, and link to it from the job summary.
+    ///
+    /// The job summary is truncated if it would exceed GitHub's limit. On
+    /// Buildkite, the report is uploaded as an artifact of the job. On GitHub
+    /// Actions, its path is set as the `report-path` output of the step, for
+    /// `actions/upload-artifact` to upload; on GitLab CI, the directory must
+    /// be listed under `artifacts:paths`.
+    #[arg(long, value_name = "DIR")]
+    pub report_dir: Option<PathBuf>,
+
+    /// Create the annotations beyond GitHub's limit per step on a check run
+    /// of this name, through the Checks API.
+    ///
+    /// The annotations are still written to the log. This requires
+    /// `GITHUB_TOKEN` to be set, with the `checks: write` permission.
+    #[cfg(feature = "checks")]
+    #[arg(long, value_name = "NAME")]
+    pub check_run: Option<String>,
+
+    /// Open an issue on this service for each error and failed test, or
+    /// comment on the issue already open for it.
+    ///
+    /// Issues are matched to failures by a fingerprint recorded in their
+    /// body. On GitHub, this requires `GITHUB_TOKEN` to be set with the
+    /// `issues: write` permission; on GitLab, `GITLAB_TOKEN` must be set to a
+    /// token with the `api` scope.
+    #[cfg(feature = "issues")]
+    #[arg(long, value_name = "SERVICE")]
+    pub file_issues: Option<IssueService>,
+
+    /// Only file issues for the failures of a job whose name, or for the
+    /// failures whose title, matches this pattern (e.g., `nightly-*`).
+    ///
+    /// May be specified multiple times. `*` matches any sequence of
+    /// characters, and `?` any single character.
+    #[cfg(feature = "issues")]
+    #[arg(long, value_name = "PATTERN", requires = "file_issues")]
+    pub issue_pattern: Vec<String>,
+
+    /// The label of the issues filed with `--file-issues`, by which the
+    /// issues already open are found.
+    #[cfg(feature = "issues")]
+    #[arg(long, value_name = "LABEL", default_value = IssueFiler::DEFAULT_LABEL, requires = "file_issues")]
+    pub issue_label: String,
+
+    /// Post a summary of the run, with its first failures, to a chat webhook
+    /// on this service once the input is processed. Can be repeated to post
+    /// to several services.
+    ///
+    /// The URL of the webhook is read from `SLACK_WEBHOOK_URL` for Slack,
+    /// `TEAMS_WEBHOOK_URL` for Microsoft Teams and `DISCORD_WEBHOOK_URL` for
+    /// Discord. The job and the links to the failures are taken from the
+    /// environment of GitHub Actions, GitLab CI or Buildkite.
+    #[cfg(feature = "notify")]
+    #[arg(long, value_name = "SERVICE")]
+    pub notify: Vec<NotifyService>,
+
+    /// The maximum number of failures listed in the summary posted with
+    /// `--notify`.
+    #[cfg(feature = "notify")]
+    #[arg(long, value_name = "COUNT", default_value_t = Notifier::DEFAULT_MAX_FAILURES, requires = "notify")]
+    pub notify_max_failures: usize,
+
+    /// Attribute the errors and warnings to the owners of the file in which
+    /// they lie, as assigned by this `CODEOWNERS` file, and summarize the
+    /// issues of each owner.
+    #[arg(long, value_name = "PATH")]
+    pub code_owners: Option<PathBuf>,
+
+    /// Report the sizes of the binaries built (e.g., the executables built
+    /// by cargo) in a table once the input has been processed.
+    ///
+    /// Executables built for tests are not included.
+    #[arg(long)]
+    pub binary_sizes: bool,
+
+    /// Compare the sizes of the binaries built against those stored in this
+    /// file, warning about each binary which grew beyond `--size-threshold`.
+    ///
+    /// The file is a JSON object mapping the name of each binary to its size
+    /// in bytes, as written by `--save-sizes`. This implies
+    /// `--binary-sizes`.
+    #[arg(long, value_name = "PATH")]
+    pub size_baseline: Option<PathBuf>,
+
+    /// The growth of a binary beyond which a warning is written, in percent
+    /// of its size in the `--size-baseline`.
+    #[arg(long, value_name = "PERCENT", default_value_t = 5)]
+    pub size_threshold: u64,
+
+    /// Write the sizes of the binaries built to this file, for use as a
+    /// `--size-baseline` by later builds.
+    ///
+    /// This implies `--binary-sizes`.
+    #[arg(long, value_name = "PATH")]
+    pub save_sizes: Option<PathBuf>,
+
+    /// Record a summary of this run (e.g., the errors, warnings, failed
+    /// tests and duration) in the history stored in this file, and compare
+    /// it against the previous runs in the job summary.
+    ///
+    /// The file is created if missing, and should be kept between runs
+    /// (e.g., in a cache, or committed to an artifacts branch). See also
+    /// `cifmt trend`.
+    #[arg(long, value_name = "PATH")]
+    pub history: Option<PathBuf>,
+
+    /// Exit with a non-zero status if the tool reported a failed build or
+    /// any errors, or if its output is incomplete (e.g., it ended before
+    /// cargo reported the result of the build).
+    ///
+    /// Without this, the exit status only reflects whether the input could
+    /// be formatted. A failed build or errors exit with code 3, and an
+    /// incomplete output with code 4 (see `cifmt::exit_code`).
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// Arguments for how the tool's output is read and parsed, shared by the
+/// commands which parse it.
+#[derive(Debug, clap::Args)]
+pub struct ParseArgs {
+    /// The maximum length of a single line of input, in bytes.
+    ///
+    /// Longer lines are truncated to this length and reported with a warning,
+    /// which bounds the memory used when a tool emits very long lines or
+    /// binary output.
+    /// Defaults to 16 MiB.
+    #[arg(long, value_name = "BYTES")]
+    pub max_line_length: Option<usize>,
+
+    /// Remove ANSI escape sequences (e.g., colour codes) from the input
+    /// before it is detected and parsed.
+    #[arg(long)]
+    pub strip_ansi: bool,
+
+    /// Replace invalid UTF-8 in the input (e.g., raw bytes printed by a
+    /// test) before it is detected and parsed.
+    ///
+    /// Lines containing invalid UTF-8 are otherwise skipped. A notice is
+    /// written if any replacement occurred.
+    #[arg(long)]
+    pub lossy_utf8: bool,
+
+    /// How to handle lines of input which are not structured messages
+    /// (e.g., a plain-text warning printed by cargo).
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t)]
+    pub passthrough: PassthroughPolicy,
+
+    /// When to include the output captured from tests (their stdout) and
+    /// the reasons given for ignored tests.
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t)]
+    pub test_output: TestOutputPolicy,
+}
+
+/// Specification of an output target.
+#[derive(Debug, Clone)]
+pub struct OutputSpec {
+    /// The platform used to format messages.
+    platform: PlatformKind,
+    /// The file to which messages are written, or `None` for stdout.
+    path: Option<PathBuf>,
+}
+
+impl FromStr for OutputSpec {
+    type Err = cifmt::ci::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (platform, path) = match s.split_once('=') {
+            Some((platform, path)) => (platform, Some(PathBuf::from(path))),
+            None => (s, None),
+        };
+        Ok(Self {
+            platform: platform.parse()?,
+            path,
+        })
+    }
+}
+
+impl OutputSpec {
+    /// Open the output target.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output file cannot be created.
+    fn open(&self) -> Result<Target<'static>> {
+        tracing::info!("Using platform: {}", self.platform);
+        match &self.path {
+            Some(path) => {
+                let file = File::create(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                Ok(Target::new(self.platform, file))
+            }
+            None => Ok(Target::new(self.platform, io::stdout().lock())),
+        }
+    }
+}
+
+/// Supported report formats of `--emit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum ReportFormat {
+    /// JUnit XML report of the results of the tests.
+    Junit,
+    /// Reviewdog Diagnostic Format, as a single JSON object.
+    Rdjson,
+    /// Reviewdog Diagnostic Format, as one diagnostic per line.
+    Rdjsonl,
+    /// JSON array of Code Climate issues.
+    Codeclimate,
+    /// Checkstyle XML report of the annotations.
+    Checkstyle,
+    /// SARIF report of the annotations, with the fixes which can be applied
+    /// automatically.
+    Sarif,
+    /// Annotations for the annotation functions of `@actions/core`, as a
+    /// JSON array.
+    GithubAnnotations,
+    /// Self-contained HTML report of the annotations and the tests.
+    Html,
+    /// Normalized records of the parsed messages, as lines of JSON.
+    Jsonl,
+    /// Timing information in the Chrome trace-event format.
+    Trace,
+}
+
+/// Specification of a report written with `--emit`.
+#[derive(Debug, Clone)]
+pub struct EmitSpec {
+    /// The format of the report.
+    format: ReportFormat,
+    /// The file to which the report is written, or `None` for stdout.
+    path: Option<PathBuf>,
+}
+
+impl FromStr for EmitSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = match s.split_once('=') {
+            Some((format, path)) => (format, Some(PathBuf::from(path))),
+            None => (s, None),
+        };
+        Ok(Self {
+            format: clap::ValueEnum::from_str(format, false)?,
+            path,
+        })
+    }
+}
+
+impl fmt::Display for EmitSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}", path.display()),
+            None => write!(f, "stdout"),
+        }
+    }
+}
+
+impl EmitSpec {
+    /// Create the specification of a report.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format of the report.
+    /// * `path` - The file to which the report is written, or `None` for
+    ///   stdout.
+    pub(crate) const fn new(format: ReportFormat, path: Option<PathBuf>) -> Self {
+        Self { format, path }
+    }
+
+    /// Open the destination of the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file of the report cannot be created.
+    fn open(&self) -> Result<Box<dyn Write>> {
+        match &self.path {
+            Some(path) => {
+                let file = File::create(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                Ok(Box::new(io::BufWriter::new(file)))
+            }
+            None => Ok(Box::new(io::stdout().lock())),
+        }
+    }
+
+    /// Enable the collection of the report by a pipeline (see
+    /// [`EmitSpec::write`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file of the normalized records cannot be
+    /// created.
+    pub(crate) fn enable<'a>(&self, pipeline: Pipeline<'a>) -> Result<Pipeline<'a>> {
+        Ok(match self.format {
+            ReportFormat::Junit => pipeline.with_junit_report(),
+            ReportFormat::Rdjson | ReportFormat::Rdjsonl => pipeline.with_rdjson_diagnostics(),
+            ReportFormat::Codeclimate => pipeline.with_codeclimate_issues(),
+            ReportFormat::Checkstyle => pipeline.with_checkstyle_report(),
+            ReportFormat::Sarif => pipeline.with_sarif_report(),
+            ReportFormat::GithubAnnotations => pipeline.with_github_annotations(),
+            ReportFormat::Html => pipeline.with_html_report(),
+            ReportFormat::Jsonl => pipeline.with_normalized_messages(self.open()?),
+            // The timeline is always recorded.
+            ReportFormat::Trace => pipeline,
+        })
+    }
+
+    /// Write the report, once the pipeline is finished.
+    ///
+    /// Reports which the pipeline did not collect (see [`EmitSpec::enable`])
+    /// are left empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be written.
+    pub(crate) fn write(&self, pipeline: &Pipeline<'_>) -> Result<()> {
+        if self.format == ReportFormat::Jsonl {
+            // The records are written as the input is processed.
+            return Ok(());
+        }
+        let mut writer = self.open()?;
+        let context = || format!("Failed to write the report to {self}");
+        match self.format {
+            ReportFormat::Junit => {
+                if let Some(report) = pipeline.junit_report() {
+                    report.write_xml(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Rdjson => {
+                if let Some(diagnostics) = pipeline.rdjson_diagnostics() {
+                    diagnostics.write_json(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Rdjsonl => {
+                if let Some(diagnostics) = pipeline.rdjson_diagnostics() {
+                    diagnostics.write_jsonl(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Codeclimate => {
+                if let Some(issues) = pipeline.codeclimate_issues() {
+                    issues.write_json(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Checkstyle => {
+                if let Some(report) = pipeline.checkstyle_report() {
+                    report.write_xml(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Sarif => {
+                if let Some(report) = pipeline.sarif_report() {
+                    report.write_json(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::GithubAnnotations => {
+                if let Some(annotations) = pipeline.github_annotations() {
+                    annotations.write_json(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Html => {
+                if let Some(report) = pipeline.html_report() {
+                    report.write_html(&mut writer).with_context(context)?;
+                }
+            }
+            ReportFormat::Trace => pipeline
+                .timeline()
+                .write_chrome_trace(&mut writer)
+                .with_context(context)?,
+            ReportFormat::Jsonl => {}
+        }
+        writer.flush().with_context(context)
+    }
 }
 
 /// Supported tool formats.
@@ -33,22 +700,216 @@ pub enum ToolFormat {
     CargoLibtest,
     /// Cargo check/build JSON format.
     CargoCheck,
+    /// Cargo fmt check output (`cargo fmt --check`).
+    CargoFmt,
+    /// Cargo doc JSON format (`cargo doc --message-format json`).
+    CargoDoc,
+    /// LCOV coverage tracefile (`cargo llvm-cov --lcov`).
+    Lcov,
+    /// Cargo udeps JSON format (`cargo udeps --output json`).
+    CargoUdeps,
+    /// Miri output (`cargo miri test --message-format json 2>&1`).
+    Miri,
+    /// Human-readable rustc diagnostics (`cargo build 2>&1`, without
+    /// `--message-format json`).
+    RustcHuman,
+    /// JUnit XML report (e.g., `pytest --junitxml report.xml`).
+    Junit,
+    /// GCC or Clang diagnostics (e.g., `make 2>&1`).
+    GccDiagnostics,
+    /// cppcheck XML results (`cppcheck --xml 2>&1`).
+    Cppcheck,
+    /// CMake errors and warnings (`cmake -B build 2>&1`).
+    Cmake,
+    /// pytest report log (`pytest --report-log report.jsonl`).
+    Pytest,
+    /// black check output (`black --check --diff . 2>&1`).
+    Black,
+    /// bandit JSON report (`bandit -r . -f json`).
+    Bandit,
+    /// Maven Surefire test output (`mvn test`).
+    Surefire,
+    /// PMD report (`pmd check -f xml` or `-f json`).
+    Pmd,
+    /// Psalm JSON report (`psalm --output-format=json`).
+    Psalm,
+    /// ESLint JSON report (`eslint -f json`).
+    Eslint,
+    /// PHPUnit TeamCity output (`--teamcity`) or JUnit XML report (`--log-junit`).
+    Phpunit,
+    /// xcodebuild output, or JSON summary of a result bundle (`xcresulttool get --legacy`).
+    Xcodebuild,
+    /// OSV-Scanner JSON report (`--format json`).
+    OsvScanner,
+    /// JSONL stream of LSP diagnostics (`textDocument/publishDiagnostics` parameters or notifications).
+    LspDiagnostics,
 }
 
 impl ToolFormat {
-    /// Convert the tool format to a dynamic tool instance for the specified platform.
+    /// Convert the tool format to a dynamic tool instance.
     ///
     /// # Returns
     ///
-    /// A boxed dynamic tool that can parse and format messages for the platform.
-    fn into_dyn_tool<P: Platform + 'static>(self) -> Box<dyn DynTool<P>>
-    where
-        tool::CargoCheck: DynTool<P>,
-        tool::CargoLibtest: DynTool<P>,
-    {
+    /// A boxed dynamic tool that can parse messages for any platform.
+    pub(crate) fn into_any_tool(self) -> Box<dyn AnyTool> {
         match self {
             Self::CargoLibtest => Box::new(tool::CargoLibtest::default()),
             Self::CargoCheck => Box::new(tool::CargoCheck::default()),
+            Self::CargoFmt => Box::new(tool::CargoFmt::default()),
+            Self::CargoDoc => Box::new(tool::CargoDoc::default()),
+            Self::Lcov => Box::new(tool::Lcov::default()),
+            Self::CargoUdeps => Box::new(tool::CargoUdeps::default()),
+            Self::Miri => Box::new(tool::Miri::default()),
+            Self::RustcHuman => Box::new(tool::RustcHuman::default()),
+            Self::Junit => Box::new(tool::Junit::default()),
+            Self::GccDiagnostics => Box::new(tool::GccDiagnostics::default()),
+            Self::Cppcheck => Box::new(tool::Cppcheck::default()),
+            Self::Cmake => Box::new(tool::Cmake::default()),
+            Self::Pytest => Box::new(tool::Pytest::default()),
+            Self::Black => Box::new(tool::Black::default()),
+            Self::Bandit => Box::new(tool::Bandit::default()),
+            Self::Surefire => Box::new(tool::Surefire::default()),
+            Self::Pmd => Box::new(tool::Pmd::default()),
+            Self::Psalm => Box::new(tool::Psalm::default()),
+            Self::Eslint => Box::new(tool::Eslint::default()),
+            Self::Phpunit => Box::new(tool::Phpunit::default()),
+            Self::Xcodebuild => Box::new(tool::Xcodebuild::default()),
+            Self::OsvScanner => Box::new(tool::OsvScanner::default()),
+            Self::LspDiagnostics => Box::new(tool::LspDiagnostics::default()),
+        }
+    }
+
+    /// The GitHub problem matcher for the tool's plain-text output.
+    pub(crate) fn problem_matcher(self) -> ProblemMatcher {
+        match self {
+            Self::CargoLibtest => tool::CargoLibtest::problem_matcher(),
+            Self::CargoCheck => tool::CargoCheck::problem_matcher(),
+            Self::CargoFmt => tool::CargoFmt::problem_matcher(),
+            Self::CargoDoc => tool::CargoDoc::problem_matcher(),
+            Self::Lcov => tool::Lcov::problem_matcher(),
+            Self::CargoUdeps => tool::CargoUdeps::problem_matcher(),
+            Self::Miri => tool::Miri::problem_matcher(),
+            Self::RustcHuman => tool::RustcHuman::problem_matcher(),
+            Self::Junit => tool::Junit::problem_matcher(),
+            Self::GccDiagnostics => tool::GccDiagnostics::problem_matcher(),
+            Self::Cppcheck => tool::Cppcheck::problem_matcher(),
+            Self::Cmake => tool::Cmake::problem_matcher(),
+            Self::Pytest => tool::Pytest::problem_matcher(),
+            Self::Black => tool::Black::problem_matcher(),
+            Self::Bandit => tool::Bandit::problem_matcher(),
+            Self::Surefire => tool::Surefire::problem_matcher(),
+            Self::Pmd => tool::Pmd::problem_matcher(),
+            Self::Psalm => tool::Psalm::problem_matcher(),
+            Self::Eslint => tool::Eslint::problem_matcher(),
+            Self::Phpunit => tool::Phpunit::problem_matcher(),
+            Self::Xcodebuild => tool::Xcodebuild::problem_matcher(),
+            Self::OsvScanner => tool::OsvScanner::problem_matcher(),
+            Self::LspDiagnostics => tool::LspDiagnostics::problem_matcher(),
+        }
+    }
+}
+
+/// Services on which issues are filed.
+#[cfg(feature = "issues")]
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum IssueService {
+    /// The issues of the GitHub repository of the workflow.
+    Github,
+    /// The issues of the GitLab project of the job.
+    Gitlab,
+}
+
+/// Services to which a summary of the run is posted.
+#[cfg(feature = "notify")]
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum NotifyService {
+    /// An incoming webhook of a Slack channel.
+    Slack,
+    /// A workflow webhook of a Microsoft Teams channel.
+    Teams,
+    /// A webhook of a Discord channel.
+    Discord,
+}
+
+/// Policies for lines of input which are not structured messages.
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum PassthroughPolicy {
+    /// Drop the lines.
+    #[default]
+    Drop,
+    /// Print each line as is.
+    Raw,
+    /// Print each line as a debug message, which is typically hidden.
+    Debug,
+    /// Collect the lines, and print them in a group at the end of the output.
+    Collect,
+}
+
+impl From<PassthroughPolicy> for Passthrough {
+    #[inline]
+    fn from(policy: PassthroughPolicy) -> Self {
+        match policy {
+            PassthroughPolicy::Drop => Self::Drop,
+            PassthroughPolicy::Raw => Self::Raw,
+            PassthroughPolicy::Debug => Self::Debug,
+            PassthroughPolicy::Collect => Self::Collect,
+        }
+    }
+}
+
+/// Policies for debug messages.
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum DebugPolicy {
+    /// Always write debug messages.
+    Always,
+    /// Only write debug messages if debug logging is enabled.
+    #[default]
+    Auto,
+    /// Write debug messages as plain lines unless debug logging is enabled.
+    Downgrade,
+    /// Never write debug messages.
+    Never,
+}
+
+impl DebugPolicy {
+    /// How debug messages are written.
+    fn handling(self) -> DebugMessages {
+        match self {
+            Self::Always => DebugMessages::Write,
+            Self::Auto | Self::Downgrade if GitHub::debug_enabled() => DebugMessages::Write,
+            Self::Auto | Self::Never => DebugMessages::Omit,
+            Self::Downgrade => DebugMessages::Downgrade,
+        }
+    }
+}
+
+/// Policies for the output captured from tests.
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum TestOutputPolicy {
+    /// Always include the output.
+    #[default]
+    Always,
+    /// Only include the output of failed tests.
+    OnFailure,
+    /// Never include the output.
+    Never,
+    /// Always include the output, truncated to a short excerpt.
+    Truncated,
+}
+
+impl From<TestOutputPolicy> for OutputPolicy {
+    #[inline]
+    fn from(policy: TestOutputPolicy) -> Self {
+        match policy {
+            TestOutputPolicy::Always => Self::Always,
+            TestOutputPolicy::OnFailure => Self::OnFailure,
+            TestOutputPolicy::Never => Self::Never,
+            TestOutputPolicy::Truncated => Self::Truncated,
         }
     }
 }
@@ -57,7 +918,8 @@ impl ToolFormat {
 ///
 /// This function reads from stdin as a stream, parses the input according to
 /// the specified or detected tool format, and writes the formatted output to
-/// stdout.
+/// each of the output targets. Reading, parsing and writing happen on separate
+/// threads. If input files are given instead, they are parsed in parallel.
 ///
 /// # Arguments
 ///
@@ -71,80 +933,538 @@ impl ToolFormat {
 /// # Errors
 ///
 /// This function will return an error if:
-/// - Reading from stdin fails
+/// - The catalog cannot be read
+/// - Reading from stdin, or from an input file, fails
 /// - Auto-detection is enabled but no tool format could be detected
-/// - Parsing the input fails
-/// - Writing to stdout fails
+/// - An option is given which the tool does not support
+/// - An output file, or the file of the raw records, cannot be created
+/// - A summary is requested but `GITHUB_STEP_SUMMARY` is not set, or the
+///   summary cannot be written
+/// - The code owners or the size baseline cannot be read, or the sizes
+///   cannot be saved
+/// - The history cannot be read or written
+/// - Writing to an output fails
+/// - A truncated output cannot be written to the artifact directory
+/// - No input is received within the idle timeout
+/// - Checking is enabled, and the build failed, errors were reported or the
+///   input is incomplete
 #[tracing::instrument(skip(args))]
 #[expect(
     clippy::needless_pass_by_value,
     reason = "follows common pattern for command execution functions"
 )]
 pub(crate) fn execute(args: Args) -> Result<()> {
-    let mut reader = io::stdin().lock();
-    let mut writer = io::stdout().lock();
-    let mut buffer = Vec::with_capacity(CHUNK_SIZE);
-
-    // Detect platform and dispatch to the appropriate typed handler
-    if GitHub::from_env().is_some() {
-        execute_with_platform::<GitHub>(&args, &mut reader, &mut writer, &mut buffer)
-    } else {
-        execute_with_platform::<Plain>(&args, &mut reader, &mut writer, &mut buffer)
-    }
-}
+    configure_paths_and_titles(&args)?;
 
-/// Execute the format command with a specific platform type.
-fn execute_with_platform<P: Platform + 'static>(
-    args: &Args,
-    reader: &mut impl Read,
-    writer: &mut impl Write,
-    buffer: &mut Vec<u8>,
-) -> Result<()>
-where
-    tool::CargoCheck: DynTool<P>,
-    tool::CargoLibtest: DynTool<P>,
-{
-    let platform = P::from_env().ok_or_else(|| anyhow::anyhow!("Failed to detect platform"))?;
-    tracing::info!("Using platform: {}", platform);
+    let mut input: Box<dyn Read + Send> = match (args.inputs.first(), args.idle_timeout) {
+        (Some(path), _) => Box::new(
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+        ),
+        (None, Some(secs)) => Box::new(IdleReader::new(io::stdin(), Duration::from_secs(secs))),
+        (None, None) => Box::new(io::stdin()),
+    };
+    let mut buffer = Vec::with_capacity(CHUNK_SIZE);
 
     // Get tool (either detected or specified)
-    let mut tool: Box<dyn DynTool<P>> = if args.detect {
+    let mut tool: Box<dyn AnyTool> = if args.detect {
         // Read initial buffer for detection
         buffer.resize(CHUNK_SIZE, 0);
-        let n = reader.read(buffer)?;
+        let n = input.read(&mut buffer)?;
         buffer.truncate(n);
-        tool::detect::<P>(buffer)?
+        tool::detect_any(&detection_sample(&buffer, &args.parse))?
     } else if let Some(tool_format) = args.tool {
-        tool_format.into_dyn_tool::<P>()
+        tool_format.into_any_tool()
     } else {
         anyhow::bail!("Either --detect or a tool format must be specified");
     };
 
     tracing::info!("Using tool: {}", tool.name());
+    check_options(tool.as_ref(), &args)?;
+    configure_tool(tool.as_mut(), &args.parse, &tool_options(&args));
+    let format = <ToolFormat as clap::ValueEnum>::from_str(tool.name(), false)
+        .map_err(anyhow::Error::msg)?;
+
+    let targets = open_targets(&args)?;
+    let mut pipeline = configure(Pipeline::new(tool, targets), &args)?;
 
-    // Process the initial buffer if we read it for detection
-    if args.detect && !buffer.is_empty() {
-        for output in tool.parse_and_format(buffer) {
-            writeln!(writer, "{output}")?;
+    if args.inputs.is_empty() {
+        // Process the initial buffer if we read it for detection
+        if args.detect && !buffer.is_empty() {
+            pipeline.process(&buffer)?;
         }
+
+        // Stream remaining input
+        pipeline.run(input)?;
+    } else {
+        pipeline.run_files(&args.inputs, || {
+            let mut shard_tool = format.into_any_tool();
+            configure_tool(shard_tool.as_mut(), &args.parse, &tool_options(&args));
+            shard_tool
+        })?;
     }
+    pipeline.finish()?;
 
-    // Stream remaining input
-    loop {
-        buffer.clear();
-        buffer.resize(CHUNK_SIZE, 0);
-        let n = reader.read(buffer)?;
+    write_reports(&pipeline, &args)?;
+
+    if pipeline.timed_out() {
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "No input received for {} second(s)",
+                args.idle_timeout.unwrap_or_default()
+            ),
+        )
+        .into());
+    }
+    if args.check {
+        check(&pipeline)?;
+    }
+
+    Ok(())
+}
 
-        if n == 0 {
-            break;
+/// Open the output targets, configured according to the command-line
+/// arguments.
+///
+/// # Errors
+///
+/// Returns an error if an output file cannot be created.
+fn open_targets(args: &Args) -> Result<Vec<Target<'static>>> {
+    let mut targets =
+        if args.outputs.is_empty() && args.emits.iter().any(|spec| spec.path.is_none()) {
+            Vec::new()
+        } else if args.outputs.is_empty() {
+            let platform = PlatformKind::from_env();
+            tracing::info!("Using platform: {}", platform);
+            vec![Target::new(platform, io::stdout().lock())]
+        } else {
+            args.outputs
+                .iter()
+                .map(OutputSpec::open)
+                .collect::<Result<Vec<_>>>()?
+        };
+    if let Some(max) = args.max_output {
+        targets = targets
+            .into_iter()
+            .map(|target| target.with_output_limit(Some(max)))
+            .collect();
+    }
+    if let Some(max) = args.max_annotations {
+        targets = targets
+            .into_iter()
+            .map(|target| target.with_annotation_limit(Some(max)))
+            .collect();
+    }
+    if !args.expand_failures.is_empty() {
+        targets = targets
+            .into_iter()
+            .map(|target| {
+                if args.expand_failures.contains(&target.platform()) {
+                    target.with_test_grouping(TestGrouping::Passing)
+                } else {
+                    target
+                }
+            })
+            .collect();
+    }
+    let debug_messages = args.debug_messages.handling();
+    if debug_messages != DebugMessages::Write {
+        targets = targets
+            .into_iter()
+            .map(|target| target.with_debug_messages(debug_messages))
+            .collect();
+    }
+    if args.explain {
+        targets = targets
+            .into_iter()
+            .map(|target| target.with_explanations(true))
+            .collect();
+    }
+    Ok(targets)
+}
+
+/// Configure the parsing of the tool's output according to the command-line
+/// arguments.
+pub(crate) fn configure_tool(tool: &mut dyn AnyTool, parse: &ParseArgs, options: &ToolOptions) {
+    if let Some(max) = parse.max_line_length {
+        tool.set_max_line_length(max);
+    }
+    tool.set_passthrough(parse.passthrough.into());
+    tool.set_output_policy(parse.test_output.into());
+    tool.set_options(options);
+}
+
+/// Clean the sample of the input from which the tool is detected, as the
+/// input is cleaned before it is parsed (see [`configure_input`]).
+pub(crate) fn detection_sample(buffer: &[u8], parse: &ParseArgs) -> Vec<u8> {
+    let mut sample = buffer.to_vec();
+    if parse.strip_ansi {
+        sample = ansi::strip(&sample);
+    }
+    if parse.lossy_utf8 {
+        sample = utf8::replace_invalid(&sample);
+    }
+    sample
+}
+
+/// Configure the cleaning of the input by the pipeline, before it is parsed,
+/// according to the command-line arguments.
+pub(crate) fn configure_input<'a>(mut pipeline: Pipeline<'a>, parse: &ParseArgs) -> Pipeline<'a> {
+    if parse.strip_ansi {
+        pipeline = pipeline.with_ansi_stripping();
+    }
+    if parse.lossy_utf8 {
+        pipeline = pipeline.with_lossy_utf8();
+    }
+    pipeline
+}
+
+/// The options of the tool given on the command line.
+fn tool_options(args: &Args) -> ToolOptions {
+    ToolOptions::default()
+        .with_fold_children(args.fold_children)
+        .with_denied_lints(args.deny.clone())
+        .with_read_sources(args.read_sources)
+        .with_time_budget(TimeBudget::new(
+            args.suite_budget.map(Duration::from_secs),
+            args.run_budget.map(Duration::from_secs),
+        ))
+        .with_coverage_threshold(args.coverage_threshold)
+}
+
+/// Check that the tool supports each of its options given on the command
+/// line, which it would otherwise ignore.
+///
+/// # Errors
+///
+/// Returns an [`UnsupportedOption`] for the first flag of an option which the
+/// tool does not support.
+fn check_options(tool: &dyn AnyTool, args: &Args) -> Result<(), UnsupportedOption> {
+    let unsupported = tool_options(args).unsupported(tool.supported_options());
+    let flags = [
+        (
+            ToolOption::FoldChildren,
+            "--fold-children",
+            args.fold_children,
+        ),
+        (ToolOption::DeniedLints, "--deny", !args.deny.is_empty()),
+        (ToolOption::ReadSources, "--read-sources", args.read_sources),
+        (
+            ToolOption::TimeBudget,
+            "--suite-budget",
+            args.suite_budget.is_some(),
+        ),
+        (
+            ToolOption::TimeBudget,
+            "--run-budget",
+            args.run_budget.is_some(),
+        ),
+        (
+            ToolOption::CoverageThreshold,
+            "--coverage-threshold",
+            args.coverage_threshold.is_some(),
+        ),
+    ];
+    match flags
+        .into_iter()
+        .find(|&(option, _, given)| given && unsupported.contains(&option))
+    {
+        Some((_, flag, _)) => Err(UnsupportedOption {
+            flag,
+            tool: tool.name(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// A flag given on the command line for an option which the tool does not
+/// support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct UnsupportedOption {
+    /// The flag (e.g., `--deny`).
+    flag: &'static str,
+    /// The name of the tool.
+    tool: &'static str,
+}
+
+impl fmt::Display for UnsupportedOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not supported by {}", self.flag, self.tool)
+    }
+}
+
+impl core::error::Error for UnsupportedOption {}
+
+/// Set the root of the checkout and install the catalog of titles, which
+/// apply to the whole process, before any message is formatted.
+///
+/// # Errors
+///
+/// Returns an error if the catalog cannot be opened, or is not a catalog.
+fn configure_paths_and_titles(args: &Args) -> Result<()> {
+    if let Some(root) = &args.checkout_root
+        && let Err(ignored) = ci::set_checkout_root(root)
+    {
+        tracing::warn!(
+            "The checkout root is already set, ignoring {}",
+            ignored.display()
+        );
+    }
+    if let Some(path) = &args.catalog {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let catalog = Catalog::read_json(io::BufReader::new(file))
+            .with_context(|| format!("Failed to read catalog from {}", path.display()))?;
+        if catalog.install().is_err() {
+            tracing::warn!(
+                "A catalog is already installed, ignoring {}",
+                path.display()
+            );
         }
+    }
+    Ok(())
+}
 
-        buffer.truncate(n);
+/// Configure the pipeline according to the command-line arguments.
+///
+/// # Errors
+///
+/// Returns an error if the file of the raw or normalized records cannot be
+/// created, if a summary is requested but `GITHUB_STEP_SUMMARY` is not set, or if the code
+/// owners, the size baseline or the history cannot be read.
+fn configure<'a>(mut pipeline: Pipeline<'a>, args: &Args) -> Result<Pipeline<'a>> {
+    if args.metadata {
+        let metadata = RunMetadata::new(pipeline.tool_name())
+            .with_platforms(pipeline.targets().iter().map(Target::platform))
+            .with_command_line(env::args());
+        pipeline = pipeline.with_metadata(metadata);
+    }
+    if let Some(dir) = &args.artifact_dir {
+        pipeline = pipeline.with_artifact_dir(dir);
+    }
+    if let Some(path) = &args.debug_raw {
+        pipeline = if path.as_os_str() == "-" {
+            pipeline.with_raw_messages(io::stderr())
+        } else {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            pipeline.with_raw_messages(file)
+        };
+    }
+    pipeline = configure_input(pipeline, &args.parse);
+    if args.stable_order {
+        pipeline = pipeline.with_stable_ordering();
+    }
+    if args.group_by_crate {
+        pipeline = pipeline.with_crate_groups();
+    }
+    if args.buildkite_annotate {
+        pipeline = pipeline.with_buildkite_annotations();
+    }
+    pipeline = configure_reports(pipeline, args)?;
+    if args.summary {
+        let path = env::var_os(GitHub::STEP_SUMMARY_ENV).with_context(|| {
+            format!("--summary requires {} to be set", GitHub::STEP_SUMMARY_ENV)
+        })?;
+        pipeline = pipeline.with_step_summary(path);
+    }
+    if let Some(dir) = &args.report_dir {
+        pipeline = pipeline.with_report_artifact(ReportArtifact::from_env(dir));
+    }
+    #[cfg(feature = "issues")]
+    if let Some(service) = args.file_issues {
+        let filer = match service {
+            IssueService::Github => IssueFiler::github_from_env()?,
+            IssueService::Gitlab => IssueFiler::gitlab_from_env()?,
+        };
+        pipeline = pipeline.with_issue_filer(
+            filer
+                .with_label(args.issue_label.as_str())
+                .with_patterns(&args.issue_pattern),
+        );
+    }
+    #[cfg(feature = "notify")]
+    if let Some(notifier) = notifier(args)? {
+        pipeline = pipeline.with_notifier(notifier);
+    }
+    #[cfg(feature = "checks")]
+    if let Some(name) = &args.check_run {
+        pipeline = pipeline.with_check_run(CheckRun::from_env(name.as_str())?);
+    }
+    if let Some(path) = &args.code_owners {
+        pipeline = pipeline.with_code_owners(read_code_owners(path)?);
+    }
+    if args.binary_sizes || args.save_sizes.is_some() {
+        pipeline = pipeline.with_binary_sizes();
+    }
+    if let Some(path) = &args.size_baseline {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let baseline = SizeStats::read_json(io::BufReader::new(file))
+            .with_context(|| format!("Failed to read size baseline from {}", path.display()))?;
+        pipeline = pipeline.with_size_baseline(baseline, args.size_threshold);
+    }
+    if let Some(path) = &args.history {
+        let history = if path.exists() {
+            let file =
+                File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+            History::read_json(io::BufReader::new(file))
+                .with_context(|| format!("Failed to read history from {}", path.display()))?
+        } else {
+            History::default()
+        };
+        pipeline = pipeline.with_history(history);
+    }
+    Ok(pipeline)
+}
 
-        for output in tool.parse_and_format(buffer) {
-            writeln!(writer, "{output}")?;
+/// Build the notifier posting to the services requested with `--notify`, if
+/// any.
+///
+/// # Errors
+///
+/// Returns an error if the URL of a service's webhook is not set.
+#[cfg(feature = "notify")]
+fn notifier(args: &Args) -> Result<Option<Notifier>> {
+    let webhook = |service: &NotifyService| match service {
+        NotifyService::Slack => Webhook::slack_from_env(),
+        NotifyService::Teams => Webhook::teams_from_env(),
+        NotifyService::Discord => Webhook::discord_from_env(),
+    };
+    let Some((first, rest)) = args.notify.split_first() else {
+        return Ok(None);
+    };
+    let mut notifier = Notifier::new(webhook(first)?);
+    for service in rest {
+        notifier = notifier.with_webhook(webhook(service)?);
+    }
+    Ok(Some(
+        notifier
+            .with_run_from_env()
+            .with_max_failures(args.notify_max_failures),
+    ))
+}
+
+/// Enable the collection of the reports requested by the command-line
+/// arguments (see [`write_reports`]).
+///
+/// # Errors
+///
+/// Returns an error if the file of the normalized records cannot be created.
+fn configure_reports<'a>(mut pipeline: Pipeline<'a>, args: &Args) -> Result<Pipeline<'a>> {
+    for spec in reports(args) {
+        pipeline = spec.enable(pipeline)?;
+    }
+    Ok(pipeline)
+}
+
+/// The reports requested by the command-line arguments, with `--emit` or
+/// one of its aliases (e.g., `--junit PATH`).
+fn reports(args: &Args) -> Vec<EmitSpec> {
+    [
+        (ReportFormat::Trace, &args.trace),
+        (ReportFormat::Junit, &args.junit),
+        (ReportFormat::Rdjson, &args.rdjson),
+        (ReportFormat::Rdjsonl, &args.rdjsonl),
+        (ReportFormat::Codeclimate, &args.codeclimate),
+        (ReportFormat::Checkstyle, &args.checkstyle),
+    ]
+    .into_iter()
+    .filter_map(|(format, path)| Some(EmitSpec::new(format, Some(path.clone()?))))
+    .chain(args.emits.iter().cloned())
+    .collect()
+}
+
+/// Read a `CODEOWNERS` file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub(crate) fn read_code_owners(path: &Path) -> Result<CodeOwners> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    CodeOwners::read(io::BufReader::new(file))
+        .with_context(|| format!("Failed to read code owners from {}", path.display()))
+}
+
+/// Write the reports requested by the command-line arguments, once the
+/// pipeline is finished.
+///
+/// # Errors
+///
+/// Returns an error if any of the reports cannot be written.
+fn write_reports(pipeline: &Pipeline<'_>, args: &Args) -> Result<()> {
+    for spec in reports(args) {
+        spec.write(pipeline)?;
+    }
+    if let Some(path) = &args.save_sizes
+        && let Some(sizes) = pipeline.binary_sizes()
+    {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        sizes
+            .write_json(io::BufWriter::new(file))
+            .with_context(|| format!("Failed to write binary sizes to {}", path.display()))?;
+    }
+    if let Some(path) = &args.history
+        && let Some(history) = pipeline.history()
+    {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        history
+            .write_json(io::BufWriter::new(file))
+            .with_context(|| format!("Failed to write history to {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The reason for which the check of the tool's outcome failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CheckFailure {
+    /// The tool reported a failed build.
+    BuildFailed,
+    /// The tool reported errors.
+    Errors(usize),
+    /// The tool's output is incomplete.
+    Incomplete,
+}
+
+impl CheckFailure {
+    /// The code with which cifmt exits when the check fails.
+    pub(crate) const fn exit_code(self) -> u8 {
+        match self {
+            Self::BuildFailed | Self::Errors(_) => exit_code::GATING_FAILURE,
+            Self::Incomplete => exit_code::PARSE_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuildFailed => f.write_str("The build failed"),
+            Self::Errors(count) => write!(f, "{count} error(s) reported"),
+            Self::Incomplete => f.write_str("The tool's output is incomplete"),
         }
     }
+}
+
+impl core::error::Error for CheckFailure {}
 
+/// Check the outcome of the tool, once the pipeline is finished.
+///
+/// # Errors
+///
+/// Returns a [`CheckFailure`] if the tool reported a failed build or any
+/// errors, or if its output is incomplete.
+fn check(pipeline: &Pipeline<'_>) -> Result<(), CheckFailure> {
+    if pipeline.build_succeeded() == Some(false) {
+        return Err(CheckFailure::BuildFailed);
+    }
+    if pipeline.errors() > 0 {
+        return Err(CheckFailure::Errors(pipeline.errors()));
+    }
+    if pipeline.is_incomplete() {
+        return Err(CheckFailure::Incomplete);
+    }
     Ok(())
 }
@@ -0,0 +1,65 @@
+//! Matcher command implementation.
+//!
+//! This module handles the matcher command, which registers a GitHub problem
+//! matcher for a tool's plain-text output, as an alternative to streaming the
+//! output through cifmt.
+
+use std::env;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use cifmt::ci::GitHub;
+
+use crate::commands::format::ToolFormat;
+
+/// Environment variable holding the temporary directory of the GitHub runner.
+const RUNNER_TEMP_ENV: &str = "RUNNER_TEMP";
+
+/// Arguments for the matcher command.
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// The tool whose plain-text output is matched.
+    #[arg(value_enum)]
+    tool: ToolFormat,
+
+    /// Where the definition of the problem matcher is written.
+    ///
+    /// Defaults to a file named after the matcher's owner in `RUNNER_TEMP`,
+    /// or in the system's temporary directory if unset.
+    #[arg(long, value_name = "PATH")]
+    path: Option<PathBuf>,
+
+    /// Print the definition of the problem matcher, rather than writing it
+    /// and registering it.
+    #[arg(long, conflicts_with = "path")]
+    json: bool,
+}
+
+/// Execute the matcher command.
+///
+/// Unless `--json` is given, the definition is written to a file and the
+/// `::add-matcher::` command registering it is printed, so that running the
+/// command in a step of a GitHub Actions workflow enables the matcher for the
+/// subsequent steps.
+#[tracing::instrument]
+pub(crate) fn execute(Args { tool, path, json }: Args) -> Result<()> {
+    let matcher = tool.problem_matcher();
+    let definition = matcher.to_json();
+    let mut stdout = io::stdout().lock();
+    if json {
+        writeln!(stdout, "{definition}")?;
+        return Ok(());
+    }
+
+    let file = path.unwrap_or_else(|| {
+        env::var_os(RUNNER_TEMP_ENV)
+            .map_or_else(env::temp_dir, PathBuf::from)
+            .join(format!("{}.json", matcher.owner))
+    });
+    tracing::info!("Writing problem matcher to: {}", file.display());
+    fs::write(&file, definition).with_context(|| format!("failed to write {}", file.display()))?;
+    write!(stdout, "{}", GitHub::add_matcher(file.to_string_lossy()))?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Publish command implementation.
+//!
+//! This module handles the publish command, which posts the annotations of
+//! output already formatted for a CI platform to a code review service.
+
+use std::fs;
+use std::io::{self, Read as _};
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use cifmt::review::PullRequest;
+
+use crate::commands::format::read_code_owners;
+
+/// Arguments for the publish command.
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// Where the annotations are published.
+    #[command(subcommand)]
+    target: Target,
+}
+
+/// Services to which annotations can be published.
+#[derive(Debug, clap::Subcommand)]
+enum Target {
+    /// Post annotations as review comments on the GitHub pull request which
+    /// triggered the workflow.
+    ///
+    /// The input is output formatted for GitHub Actions (e.g., written with
+    /// `cifmt format --output github=FILE`). Annotations on lines outside the
+    /// pull request's diff, and comments already posted, are skipped.
+    /// Requires `GITHUB_TOKEN` to be set with the `pull-requests: write`
+    /// permission.
+    GithubPr {
+        /// The file containing the formatted output.
+        ///
+        /// Defaults to reading from stdin.
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Mention the owners of the file commented on in each comment, as
+        /// assigned by this `CODEOWNERS` file.
+        #[arg(long, value_name = "PATH")]
+        code_owners: Option<PathBuf>,
+    },
+}
+
+/// Execute the publish command.
+#[tracing::instrument]
+pub(crate) fn execute(Args { target }: Args) -> Result<()> {
+    match target {
+        Target::GithubPr { file, code_owners } => {
+            let output = read_input(file)?;
+            let mut pull = PullRequest::from_env()?;
+            if let Some(path) = code_owners {
+                pull = pull.with_code_owners(read_code_owners(&path)?);
+            }
+            tracing::info!("Publishing to: {:?}", pull);
+            let posted = pull.publish(&output)?;
+            tracing::info!("Posted {} review comment(s)", posted);
+        }
+    }
+    Ok(())
+}
+
+/// Read the formatted output from a file, or from stdin.
+fn read_input(file: Option<PathBuf>) -> Result<String> {
+    if let Some(path) = file {
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))
+    } else {
+        let mut output = String::new();
+        io::stdin()
+            .lock()
+            .read_to_string(&mut output)
+            .context("failed to read stdin")?;
+        Ok(output)
+    }
+}
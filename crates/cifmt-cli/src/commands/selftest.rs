@@ -0,0 +1,53 @@
+//! Self-test command implementation.
+//!
+//! This module handles the selftest command, which writes a sample of each
+//! kind of message so that their rendering by a CI platform can be checked
+//! when setting up a new integration.
+
+use std::env;
+use std::io::{self, Write as _};
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use cifmt::ci::{GitHub, PlatformKind};
+use cifmt::ci_message::DynCiMessage as _;
+use cifmt::selftest::SelfTest;
+
+/// Arguments for the selftest command.
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// The platform for which the sample is formatted.
+    ///
+    /// Defaults to the platform detected from the environment.
+    #[arg(long, value_name = "PLATFORM")]
+    platform: Option<PlatformKind>,
+
+    /// Also write a sample job summary to the GitHub Actions step.
+    ///
+    /// The summary is appended to the file given by `GITHUB_STEP_SUMMARY`,
+    /// which must be set.
+    #[arg(long)]
+    summary: bool,
+}
+
+/// Execute the selftest command.
+#[tracing::instrument]
+pub(crate) fn execute(Args { platform, summary }: Args) -> Result<()> {
+    let kind = platform.unwrap_or_else(PlatformKind::from_env);
+    tracing::info!("Using platform: {}", kind);
+    writeln!(
+        io::stdout().lock(),
+        "{}",
+        SelfTest::default().format_for(kind)
+    )?;
+
+    if summary {
+        let path = env::var_os(GitHub::STEP_SUMMARY_ENV).with_context(|| {
+            format!("--summary requires {} to be set", GitHub::STEP_SUMMARY_ENV)
+        })?;
+        GitHub::summary(SelfTest::JOB_SUMMARY)
+            .path(Path::new(&path))
+            .write()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Trend command implementation.
+//!
+//! This module handles the trend command, which compares the latest run
+//! recorded in a history (as written by `cifmt format --history`) against the
+//! previous runs.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use cifmt::ci::GitHub;
+use cifmt::history::History;
+
+/// Arguments for the trend command.
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// The history of the runs, as written by `cifmt format --history`.
+    #[arg(value_name = "PATH")]
+    history: PathBuf,
+
+    /// Append the comparison to the job summary of the GitHub Actions step,
+    /// rather than printing it.
+    ///
+    /// The summary is appended to the file given by `GITHUB_STEP_SUMMARY`,
+    /// which must be set.
+    #[arg(long)]
+    summary: bool,
+}
+
+/// Execute the trend command.
+///
+/// The comparison is rendered in Markdown, under a heading naming the tool
+/// of the latest run.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The history cannot be read, or records no runs
+/// - A summary is requested but `GITHUB_STEP_SUMMARY` is not set, or the
+///   summary cannot be written
+#[tracing::instrument]
+pub(crate) fn execute(Args { history, summary }: Args) -> Result<()> {
+    let file =
+        File::open(&history).with_context(|| format!("Failed to open {}", history.display()))?;
+    let runs = History::read_json(io::BufReader::new(file))
+        .with_context(|| format!("Failed to read history from {}", history.display()))?;
+    let trend = runs
+        .trend()
+        .with_context(|| format!("No runs are recorded in {}", history.display()))?;
+    let markdown = format!("### {}\n\n{trend}", trend.current().tool);
+
+    if summary {
+        let path = env::var_os(GitHub::STEP_SUMMARY_ENV).with_context(|| {
+            format!("--summary requires {} to be set", GitHub::STEP_SUMMARY_ENV)
+        })?;
+        GitHub::summary(&markdown)
+            .path(Path::new(&path))
+            .write()
+            .context("Failed to write the job summary")?;
+    } else {
+        io::stdout().lock().write_all(markdown.as_bytes())?;
+    }
+    Ok(())
+}
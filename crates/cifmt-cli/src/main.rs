@@ -1,6 +1,8 @@
 //! CI message formatter CLI.
 
+use cifmt::{exit_code, tool};
 use clap::Parser;
+use std::io;
 use std::process::ExitCode;
 
 pub(crate) mod commands;
@@ -33,7 +35,27 @@ fn main() -> ExitCode {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             tracing::error!("Error executing command: {}", e);
-            ExitCode::FAILURE
+            ExitCode::from(failure_code(&e))
         }
     }
 }
+
+/// The code with which to exit after a command failed, after the cause of
+/// the failure (see [`cifmt::exit_code`]).
+fn failure_code(error: &anyhow::Error) -> u8 {
+    if let Some(failure) = error.downcast_ref::<commands::format::CheckFailure>() {
+        return failure.exit_code();
+    }
+    if error.is::<commands::format::UnsupportedOption>() {
+        return exit_code::USAGE;
+    }
+    for cause in error.chain() {
+        if cause.is::<tool::Error>() {
+            return exit_code::DETECTION_FAILURE;
+        }
+        if cause.is::<io::Error>() {
+            return exit_code::IO_ERROR;
+        }
+    }
+    exit_code::FAILURE
+}
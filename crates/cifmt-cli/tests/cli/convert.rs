@@ -0,0 +1,174 @@
+use crate::TestCommand;
+
+/// A JUnit XML report with a failed test.
+const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="pytest" tests="2" failures="1">
+  <testcase classname="tests.test_math" name="test_add" file="tests/test_math.py" line="3" time="0.001"/>
+  <testcase classname="tests.test_math" name="test_divide" file="tests/test_math.py" line="9" time="0.002">
+    <failure message="assert (1 / 2) == 0">boom</failure>
+  </testcase>
+</testsuite>
+"#;
+
+#[test]
+fn convert_junit_to_checkstyle() {
+    let cmd = TestCommand::default().args([
+        "convert",
+        "junit",
+        "--input",
+        "report.xml",
+        "--to",
+        "checkstyle",
+    ]);
+    std::fs::write(cmd.cwd().join("report.xml"), REPORT).expect("Failed to write report");
+    insta::assert_snapshot!(cmd.run_and_format(), @r#"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    <?xml version="1.0" encoding="UTF-8"?>
+    <checkstyle version="4.3">
+      <file name="tests/test_math.py">
+        <error line="9" severity="error" message="Test Failed: tests.test_math.test_divide: assert (1 / 2) == 0" source="junit"/>
+      </file>
+    </checkstyle>
+
+    --- STDERR ---
+    "#);
+}
+
+#[test]
+fn convert_junit_to_sarif() {
+    let cmd = TestCommand::default().args(["convert", "junit", "--to", "sarif"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(REPORT)), @r#"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    {
+      "$schema": "https://json.schemastore.org/sarif-[VERSION].json",
+      "version": "[VERSION]",
+      "runs": [
+        {
+          "tool": {
+            "driver": {
+              "name": "junit"
+            }
+          },
+          "columnKind": "unicodeCodePoints",
+          "results": [
+            {
+              "level": "error",
+              "message": {
+                "text": "Test Failed: tests.test_math.test_divide: assert (1 / 2) == 0"
+              },
+              "locations": [
+                {
+                  "physicalLocation": {
+                    "artifactLocation": {
+                      "uri": "tests/test_math.py"
+                    },
+                    "region": {
+                      "startLine": 9
+                    }
+                  }
+                }
+              ]
+            }
+          ]
+        }
+      ]
+    }
+    --- STDERR ---
+    "#);
+}
+
+#[test]
+fn convert_eslint_to_checkstyle() {
+    let report = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../cifmt/corpora/eslint.in"
+    ))
+    .expect("Failed to read test data file");
+    let cmd = TestCommand::default().args(["convert", "eslint", "--to", "checkstyle"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&report)), @r#"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    <?xml version="1.0" encoding="UTF-8"?>
+    <checkstyle version="4.3">
+      <file name="/home/user/app/src/broken.js">
+        <error line="2" column="17" severity="error" message="Scan Error: Parsing error: Unexpected token )" source="eslint"/>
+      </file>
+      <file name="/home/user/app/src/index.js">
+        <error line="1" column="7" severity="error" message="&apos;unused&apos; is assigned a value but never used." source="eslint.no-unused-vars"/>
+        <error line="4" column="13" severity="error" message="Expected &apos;===&apos; and instead saw &apos;==&apos;." source="eslint.eqeqeq"/>
+        <error line="7" column="3" severity="warning" message="Unexpected console statement." source="eslint.no-console"/>
+      </file>
+    </checkstyle>
+
+    --- STDERR ---
+    "#);
+}
+
+#[test]
+fn convert_detect_to_jsonl() {
+    let cmd = TestCommand::default().args(["convert", "--to", "jsonl", "--output", "out.jsonl"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(REPORT)), @"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+
+    --- STDERR ---
+    ");
+    let records =
+        std::fs::read_to_string(cmd.cwd().join("out.jsonl")).expect("Failed to read records");
+    insta::assert_snapshot!(records, @r#"
+    {"tool":"junit","kind":"TestPassed","severity":"notice","code":null,"package":null,"title":"Test Passed: tests.test_math.test_add","body":"Executed in 0.00s","locations":[],"fingerprint":null,"test":{"name":"tests.test_math.test_add","outcome":"passed","duration":0.001},"timing":{"event":"elapsed","track":"tests.test_math.test_add","name":"tests.test_math.test_add","category":"test","seconds":0.001}}
+    {"tool":"junit","kind":"TestFailed","severity":"error","code":null,"package":null,"title":"Test Failed: tests.test_math.test_divide","body":"assert (1 / 2) == 0","locations":[{"file":"tests/test_math.py","line":9,"column":null,"end_line":null,"end_column":null}],"fingerprint":"32e7688c63f5e820","test":{"name":"tests.test_math.test_divide","outcome":"failed","duration":0.002},"timing":{"event":"elapsed","track":"tests.test_math.test_divide","name":"tests.test_math.test_divide","category":"test","seconds":0.002}}
+    {"tool":"junit","kind":"SuiteFailed","severity":"error","code":null,"package":null,"title":"Test Suite Failed: pytest","body":"1 passed, 1 failed, 0 ignored","locations":[],"fingerprint":null,"test":null,"timing":null}
+    "#);
+}
+
+#[test]
+fn convert_unknown_format() {
+    let cmd = TestCommand::default().args(["convert", "junit", "--to", "pdf"]);
+    insta::assert_snapshot!(cmd.run_and_format(), @"
+    Success: false
+    Exit Code: 2
+    --- STDOUT ---
+
+    --- STDERR ---
+    error: invalid value 'pdf' for '--to <FORMAT>'
+      [possible values: junit, rdjson, rdjsonl, codeclimate, checkstyle, sarif, github-annotations, html, jsonl, trace]
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn convert_strip_ansi_and_passthrough() {
+    let input = [
+        "\x1b[1;32mCompiling example v0.1.0\x1b[0m",
+        "\x1b[1m{ \"type\": \"test\", \"event\": \"started\", \"name\": \"tests::a\" }\x1b[0m",
+        "\x1b[1m{ \"type\": \"test\", \"name\": \"tests::a\", \"event\": \"ok\" }\x1b[0m",
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args([
+        "convert",
+        "--strip-ansi",
+        "--passthrough",
+        "debug",
+        "--to",
+        "jsonl",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)), @r#"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    {"tool":"cargo-libtest","kind":"TextOutput","severity":"debug","code":null,"package":null,"title":null,"body":"debug:   Compiling example v[VERSION]","locations":[],"fingerprint":null,"test":null,"timing":null}
+    {"tool":"cargo-libtest","kind":"TestStarted","severity":"debug","code":null,"package":null,"title":null,"body":"Test: tests::a","locations":[],"fingerprint":null,"test":null,"timing":null}
+    {"tool":"cargo-libtest","kind":"TestPassed","severity":"notice","code":null,"package":null,"title":"Test Passed: tests::a","body":"notice:  Test Passed: tests::a","locations":[],"fingerprint":null,"test":{"name":"tests::a","outcome":"passed","duration":null},"timing":null}
+
+    --- STDERR ---
+    "#);
+}
@@ -2,5 +2,26 @@
 
 #![cfg(test)]
 
+mod bandit;
+mod black;
 mod cargo_check;
+mod cargo_doc;
+mod cargo_fmt;
 mod cargo_libtest;
+mod cargo_udeps;
+mod cmake;
+mod cppcheck;
+mod eslint;
+mod gcc_diagnostics;
+mod junit;
+mod lcov;
+mod lsp_diagnostics;
+mod miri;
+mod osv_scanner;
+mod phpunit;
+mod pmd;
+mod psalm;
+mod pytest;
+mod rustc_human;
+mod surefire;
+mod xcodebuild;
@@ -1,5 +1,6 @@
 #![cfg(test)]
 
+use pretty_assertions::assert_eq;
 use rstest::{fixture, rstest};
 
 use crate::{TestCommand, set_snapshot_suffix};
@@ -22,16 +23,16 @@ use crate::{TestCommand, set_snapshot_suffix};
 /// To regenerate this test data, run:
 ///
 /// ```bash
-/// cd crates/cifmt-cli/tests/cli/test_data
+/// cd crates/cifmt/corpora
 /// ./generate cargo-check.in
 /// ```
 ///
-/// See `test_data/README.md` for more details.
+/// See `crates/cifmt/corpora/README.md` for more details.
 #[fixture]
 fn output() -> String {
     std::fs::read_to_string(concat!(
         env!("CARGO_MANIFEST_DIR"),
-        "/tests/cli/test_data/cargo-check.in"
+        "/../cifmt/corpora/cargo-check.in"
     ))
     .expect("Failed to read test data file")
 }
@@ -50,17 +51,21 @@ fn format_detect(output: String) {
 
 #[rstest]
 #[case("plain", None)]
-#[case("github", Some("true"))]
+#[case("github", Some(("GITHUB_ACTIONS", "true")))]
+#[case("azure", Some(("TF_BUILD", "True")))]
+#[case("buildkite", Some(("BUILDKITE", "true")))]
+#[case("codebuild", Some(("CODEBUILD_BUILD_ID", "build:1")))]
+#[case("cloudbuild", Some(("BUILDER_OUTPUT", "/builder/outputs")))]
 fn format_platform(
     #[case] platform_name: &str,
-    #[case] github_actions_env: Option<&str>,
+    #[case] platform_env: Option<(&str, &str)>,
     output: String,
 ) {
-    set_snapshot_suffix!(platform_name, github_actions_env.is_some());
+    set_snapshot_suffix!(platform_name);
 
     let mut cmd = TestCommand::default().arg("format").arg("--detect");
-    if let Some(val) = github_actions_env {
-        cmd = cmd.env("GITHUB_ACTIONS", val);
+    if let Some((key, val)) = platform_env {
+        cmd = cmd.env(key, val);
     }
 
     insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
@@ -71,3 +76,436 @@ fn format_no_tool_specified(output: String) {
     let cmd = TestCommand::default().arg("format");
     insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
 }
+
+#[rstest]
+#[case("raw")]
+#[case("debug")]
+#[case("collect")]
+fn format_passthrough(#[case] policy: &str, output: String) {
+    set_snapshot_suffix!(policy);
+
+    let cmd = TestCommand::default().args([
+        "format",
+        "--detect",
+        "--passthrough",
+        policy,
+        "--output",
+        "github",
+        // Passthrough lines are written as debug messages with `debug`.
+        "--debug-messages",
+        "always",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_fold_children(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--fold-children",
+        "--output",
+        "github",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_explain(output: String) {
+    let cmd =
+        TestCommand::default().args(["format", "cargo-check", "--explain", "--output", "github"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_debug_raw(output: String) {
+    let cmd =
+        TestCommand::default().args(["format", "cargo-check", "--debug-raw", "--output", "plain"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_group_by_crate(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--group-by-crate",
+        "--output",
+        "github",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_code_owners(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--code-owners",
+        "CODEOWNERS",
+        "--output",
+        "plain=log.txt",
+    ]);
+    std::fs::write(
+        cmd.cwd().join("CODEOWNERS"),
+        "* @org/core\n/src/ @org/lib\n",
+    )
+    .expect("Failed to write CODEOWNERS");
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let log = std::fs::read_to_string(cmd.cwd().join("log.txt")).expect("Failed to read log");
+    let summary = log
+        .split_once("Issues by owner\n")
+        .map(|(_, rest)| rest.lines().take(2).collect::<Vec<_>>().join("\n"));
+    insta::assert_snapshot!(summary.unwrap_or_default(), @"
+    Errors  Warnings  Owner
+         1         0  @org/lib
+    ");
+}
+
+#[rstest]
+fn format_platform_override(output: String) {
+    let detected = TestCommand::default()
+        .args(["format", "--detect"])
+        .env("TF_BUILD", "True");
+    let overridden = TestCommand::default()
+        .args(["format", "--detect"])
+        .env("GITHUB_ACTIONS", "true")
+        .env("CIFMT_PLATFORM", "azure");
+    assert_eq!(
+        overridden.run_and_format_with_stdin(Some(&output)),
+        detected.run_and_format_with_stdin(Some(&output))
+    );
+}
+
+#[rstest]
+fn format_check(output: String) {
+    let cmd = TestCommand::default().args(["format", "cargo-check", "--check"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_check_incomplete(output: String) {
+    let unfinished: String = output
+        .lines()
+        .filter(|line| !line.contains("\"build-finished\""))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    let cmd = TestCommand::default().args(["format", "cargo-check", "--check"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&unfinished)));
+}
+
+#[test]
+fn format_check_truncated() {
+    let input = concat!(
+        r#"{"reason":"build-finished","success":true}"#,
+        "\n",
+        r#"{"reason":"compiler-message","package_id":"p""#,
+    );
+    let cmd = TestCommand::default().args(["format", "cargo-check", "--check"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(input)), @"
+    Success: false
+    Exit Code: 4
+    --- STDOUT ---
+    warning: Truncated Output: Output ended within line 2 (at byte offset 43), discarding 45 bytes of an incomplete message: EOF while parsing an object at line 1 column 45
+    notice:  Build Complete: Build finished successfully with 0 error(s) and 1 warning(s)
+
+    --- STDERR ---
+    [2m[TIMESTAMP][0m [31mERROR[0m [2mcifmt[0m[2m:[0m [2m37:[0m Error executing command: The tool's output is incomplete
+    ");
+}
+
+#[test]
+fn format_detect_failure() {
+    let cmd = TestCommand::default().args(["format", "--detect"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some("Hello, world!\n")), @"
+    Success: false
+    Exit Code: 5
+    --- STDOUT ---
+
+    --- STDERR ---
+    [2m[TIMESTAMP][0m [31mERROR[0m [2mcifmt[0m[2m:[0m [2m37:[0m Error executing command: No tool format detected
+    ");
+}
+
+#[test]
+fn format_binary_sizes() {
+    let artifact = |name: &str| {
+        concat!(
+            r#"{"reason":"compiler-artifact","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+            r#""target":{"kind":["bin"],"crate_types":["bin"],"name":"{name}","src_path":"/p/src/main.rs","#,
+            r#""edition":"2024","doc":true,"doctest":false,"test":true},"#,
+            r#""profile":{"opt_level":"3","debuginfo":0,"debug_assertions":false,"#,
+            r#""overflow_checks":false,"test":false},"features":[],"#,
+            r#""filenames":["{name}"],"executable":"{name}","fresh":false}"#,
+            "\n",
+        )
+        .replace("{name}", name)
+    };
+    let input = [
+        artifact("app"),
+        artifact("cli"),
+        r#"{"reason":"build-finished","success":true}"#.to_owned(),
+    ]
+    .join("");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "plain",
+        "--size-baseline",
+        "baseline.json",
+        "--size-threshold",
+        "10",
+        "--save-sizes",
+        "sizes.json",
+    ]);
+    std::fs::write(cmd.cwd().join("app"), vec![0; 1500]).expect("Failed to write binary");
+    std::fs::write(cmd.cwd().join("cli"), vec![0; 200]).expect("Failed to write binary");
+    std::fs::write(
+        cmd.cwd().join("baseline.json"),
+        r#"{"app": 1000, "cli": 200}"#,
+    )
+    .expect("Failed to write baseline");
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+
+    let sizes =
+        std::fs::read_to_string(cmd.cwd().join("sizes.json")).expect("Failed to read sizes");
+    assert_eq!(sizes, "{\n  \"app\": 1500,\n  \"cli\": 200\n}");
+}
+
+#[rstest]
+fn format_inputs(output: String) {
+    let shard = concat!(
+        r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+        r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+        r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+        r#""message":{"$message_type":"diagnostic","message":"unused variable: `shard`","#,
+        r#""code":null,"level":"warning","spans":[],"children":[],"rendered":null}}"#,
+        "\n",
+        r#"{"reason":"build-finished","success":true}"#,
+        "\n",
+    );
+    let cmd = TestCommand::default().args([
+        "format",
+        "--detect",
+        "--input",
+        "shard-1.json",
+        "--input",
+        "shard-2.json",
+        "--output",
+        "plain",
+    ]);
+    std::fs::write(cmd.cwd().join("shard-1.json"), shard).expect("Failed to write shard");
+    std::fs::write(cmd.cwd().join("shard-2.json"), &output).expect("Failed to write shard");
+    insta::assert_snapshot!(cmd.run_and_format());
+}
+
+#[test]
+fn format_inputs_missing() {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--input",
+        "missing.json",
+        "--output",
+        "plain",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format());
+}
+
+#[rstest]
+#[case("3")]
+#[case("0")]
+fn format_max_annotations(#[case] max: &str) {
+    let diagnostic = |level: &str, message: &str| {
+        concat!(
+            r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+            r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+            r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+            r#""message":{"$message_type":"diagnostic","message":"{message}","code":null,"#,
+            r#""level":"{level}","spans":[],"children":[],"rendered":null}}"#,
+            "\n",
+        )
+        .replace("{level}", level)
+        .replace("{message}", message)
+    };
+    let input = [
+        diagnostic("warning", "unused a"),
+        diagnostic("warning", "unused b"),
+        diagnostic("error", "mismatched types"),
+        r#"{"reason":"build-finished","success":false}"#.to_owned(),
+    ]
+    .join("");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "github",
+        "--max-annotations",
+        max,
+    ]);
+    set_snapshot_suffix!(max);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[test]
+fn format_deny() {
+    let diagnostic = |code: &str, message: &str| {
+        concat!(
+            r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+            r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+            r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+            r#""message":{"$message_type":"diagnostic","message":"{message}","#,
+            r#""code":{"code":"{code}","explanation":null},"#,
+            r#""level":"warning","spans":[],"children":[],"rendered":null}}"#,
+            "\n",
+        )
+        .replace("{code}", code)
+        .replace("{message}", message)
+    };
+    let input = [
+        diagnostic("unsafe_code", "usage of an `unsafe` block"),
+        diagnostic(
+            "clippy::unwrap_used",
+            "used `unwrap()` on an `Option` value",
+        ),
+        diagnostic("unused_variables", "unused variable: `x`"),
+        r#"{"reason":"build-finished","success":true}"#.to_owned(),
+    ]
+    .join("");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "github",
+        "--deny",
+        "unsafe_code,clippy::unwrap_used",
+        "--check",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[test]
+fn format_checkout_root() {
+    let input = concat!(
+        r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/work/repo/Cargo.toml","#,
+        r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/work/repo/src/lib.rs","#,
+        r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+        r#""message":{"$message_type":"diagnostic","message":"unused variable: `x`","#,
+        r#""code":{"code":"unused_variables","explanation":null},"level":"warning","#,
+        r#""spans":[{"file_name":"/work/repo/src/lib.rs","byte_start":0,"byte_end":1,"#,
+        r#""line_start":3,"line_end":3,"column_start":9,"column_end":10,"is_primary":true,"#,
+        r#""text":[{"text":"    let x = 5;","highlight_start":9,"highlight_end":10}],"#,
+        r#""label":null,"suggested_replacement":"_x","suggestion_applicability":"MachineApplicable","#,
+        r#""expansion":null}],"children":[],"rendered":null}}"#,
+        "\n",
+    );
+    for platform in ["github", "plain"] {
+        let cmd = TestCommand::default().args([
+            "format",
+            "cargo-check",
+            "--output",
+            platform,
+            "--checkout-root",
+            "/work/repo",
+        ]);
+        insta::assert_snapshot!(
+            format!("format_checkout_root_{platform}"),
+            cmd.run_and_format_with_stdin(Some(input))
+        );
+    }
+}
+
+#[rstest]
+fn format_rdjsonl(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "plain=log.txt",
+        "--rdjsonl",
+        "diagnostics.jsonl",
+    ]);
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let diagnostics = std::fs::read_to_string(cmd.cwd().join("diagnostics.jsonl"))
+        .expect("Failed to read diagnostics");
+    insta::assert_snapshot!(diagnostics);
+}
+
+#[rstest]
+fn format_codeclimate(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "plain=log.txt",
+        "--codeclimate",
+        "gl-code-quality-report.json",
+    ]);
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let issues = std::fs::read_to_string(cmd.cwd().join("gl-code-quality-report.json"))
+        .expect("Failed to read issues");
+    insta::assert_snapshot!(issues);
+}
+
+#[rstest]
+fn format_checkstyle(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "plain=log.txt",
+        "--checkstyle",
+        "checkstyle.xml",
+    ]);
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let report =
+        std::fs::read_to_string(cmd.cwd().join("checkstyle.xml")).expect("Failed to read report");
+    insta::assert_snapshot!(report);
+}
+
+#[rstest]
+fn format_emit_sarif(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-check",
+        "--output",
+        "plain=log.txt",
+        "--emit",
+        "sarif=results.sarif",
+    ]);
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let report =
+        std::fs::read_to_string(cmd.cwd().join("results.sarif")).expect("Failed to read report");
+    insta::assert_snapshot!(report);
+}
+
+#[rstest]
+#[case::rdjson("rdjson")]
+#[case::rdjsonl("rdjsonl")]
+#[case::codeclimate("codeclimate")]
+#[case::checkstyle("checkstyle")]
+fn format_emit_alias(output: String, #[case] format: &str) {
+    let read = |args: [&str; 2]| {
+        let cmd = TestCommand::default()
+            .args(["format", "cargo-check", "--output", "plain=log.txt"])
+            .args(args);
+        let formatted = cmd.run_and_format_with_stdin(Some(&output));
+        assert!(formatted.starts_with("Success: true"), "{formatted}");
+        std::fs::read_to_string(cmd.cwd().join("report")).expect("Failed to read report")
+    };
+
+    let emitted = read(["--emit", &format!("{format}=report")]);
+    assert!(!emitted.is_empty());
+    assert_eq!(emitted, read([&format!("--{format}"), "report"]));
+}
@@ -0,0 +1,65 @@
+#![cfg(test)]
+
+use rstest::{fixture, rstest};
+
+use crate::{TestCommand, set_snapshot_suffix};
+
+/// Get cargo doc output for testing from static test data.
+///
+/// This uses pre-generated test data instead of running `cargo doc`
+/// dynamically to ensure test stability across code changes.
+///
+/// # Returns
+///
+/// Static JSON output representing `cargo doc --message-format json`
+///
+/// # Panics
+///
+/// Panics if the test data file cannot be read
+///
+/// # Regeneration
+///
+/// To regenerate this test data, run:
+///
+/// ```bash
+/// cd crates/cifmt/corpora
+/// ./generate cargo-doc.in
+/// ```
+///
+/// See `crates/cifmt/corpora/README.md` for more details.
+#[fixture]
+fn output() -> String {
+    std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../cifmt/corpora/cargo-doc.in"
+    ))
+    .expect("Failed to read test data file")
+}
+
+#[rstest]
+fn format_manual(output: String) {
+    let cmd = TestCommand::default().arg("format").arg("cargo-doc");
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_detect(output: String) {
+    let cmd = TestCommand::default().arg("format").arg("--detect");
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+#[case("plain", None)]
+#[case("github", Some(("GITHUB_ACTIONS", "true")))]
+fn format_platform(
+    #[case] platform_name: &str,
+    #[case] env_var: Option<(&str, &str)>,
+    output: String,
+) {
+    set_snapshot_suffix!(platform_name);
+    let mut cmd = TestCommand::default().arg("format").arg("cargo-doc");
+    if let Some((key, value)) = env_var {
+        cmd = cmd.env(key, value);
+    }
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
@@ -22,16 +22,16 @@ use crate::{TestCommand, set_snapshot_suffix};
 /// To regenerate this test data, run:
 ///
 /// ```bash
-/// cd crates/cifmt-cli/tests/cli/test_data
+/// cd crates/cifmt/corpora
 /// ./generate cargo-libtest.in
 /// ```
 ///
-/// See `test_data/README.md` for more details.
+/// See `crates/cifmt/corpora/README.md` for more details.
 #[fixture]
 fn output() -> String {
     std::fs::read_to_string(concat!(
         env!("CARGO_MANIFEST_DIR"),
-        "/tests/cli/test_data/cargo-libtest.in"
+        "/../cifmt/corpora/cargo-libtest.in"
     ))
     .expect("Failed to read test data file")
 }
@@ -50,18 +50,453 @@ fn format_detect(output: String) {
 
 #[rstest]
 #[case("plain", None)]
-#[case("github", Some("true"))]
+#[case("github", Some(("GITHUB_ACTIONS", "true")))]
+#[case("azure", Some(("TF_BUILD", "True")))]
+#[case("buildkite", Some(("BUILDKITE", "true")))]
+#[case("codebuild", Some(("CODEBUILD_BUILD_ID", "build:1")))]
+#[case("cloudbuild", Some(("BUILDER_OUTPUT", "/builder/outputs")))]
 fn format_platform(
     #[case] platform_name: &str,
-    #[case] github_actions_env: Option<&str>,
+    #[case] platform_env: Option<(&str, &str)>,
     output: String,
 ) {
-    set_snapshot_suffix!(platform_name, github_actions_env.is_some());
+    set_snapshot_suffix!(platform_name);
 
     let mut cmd = TestCommand::default().arg("format").arg("--detect");
-    if let Some(val) = github_actions_env {
-        cmd = cmd.env("GITHUB_ACTIONS", val);
+    if let Some((key, val)) = platform_env {
+        cmd = cmd.env(key, val);
     }
 
     insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
 }
+
+#[rstest]
+fn format_multiple_outputs(output: String) {
+    let cmd = TestCommand::default().args([
+        "format", "--detect", "--output", "github", "--output", "plain",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_unknown_output() {
+    let cmd = TestCommand::default().args(["format", "--detect", "--output", "jenkins"]);
+    insta::assert_snapshot!(cmd.run_and_format());
+}
+
+#[rstest]
+fn format_max_line_length(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "plain",
+        "--max-line-length",
+        "80",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_max_output(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "plain",
+        "--max-output",
+        "64",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_strip_ansi(output: String) {
+    let coloured = output.lines().fold(String::new(), |mut acc, line| {
+        acc.push_str("\x1b[1;32m");
+        acc.push_str(line);
+        acc.push_str("\x1b[0m\n");
+        acc
+    });
+    let cmd =
+        TestCommand::default().args(["format", "--detect", "--strip-ansi", "--output", "plain"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&coloured)));
+}
+
+#[rstest]
+fn format_crlf_bom(output: String) {
+    let windows = output
+        .lines()
+        .fold(String::from('\u{feff}'), |mut acc, line| {
+            acc.push_str(line);
+            acc.push_str("\r\n");
+            acc
+        });
+    let cmd = TestCommand::default().args(["format", "--detect", "--output", "plain"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&windows)));
+}
+
+#[rstest]
+fn format_unterminated(output: String) {
+    let cmd = TestCommand::default().args(["format", "cargo-libtest", "--output", "plain"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(output.trim_end())));
+}
+
+#[rstest]
+fn format_truncated(output: String) {
+    // Cut the output part way through the failed test's message, as if the
+    // test binary had been killed.
+    let start = output.find(r#""event": "failed""#).unwrap_or_default();
+    let truncated = output.get(..start.saturating_add(40)).unwrap_or(&output);
+    let cmd = TestCommand::default().args(["format", "cargo-libtest", "--output", "plain"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(truncated)));
+}
+
+#[rstest]
+fn format_stable_order(output: String) {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--stable-order",
+        "--output",
+        "github",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+#[case("on-failure")]
+#[case("never")]
+fn format_test_output(#[case] policy: &str, output: String) {
+    set_snapshot_suffix!(policy);
+
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--test-output",
+        policy,
+        "--output",
+        "plain",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[rstest]
+fn format_summary(output: String) {
+    let cmd = TestCommand::default()
+        .args(["format", "cargo-libtest", "--summary", "--output", "plain"])
+        .env("GITHUB_STEP_SUMMARY", "summary.md");
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let summary = std::fs::read_to_string(cmd.cwd().join("summary.md"))
+        .expect("Failed to read the job summary");
+    insta::assert_snapshot!(summary);
+}
+
+#[rstest]
+fn format_report_dir(output: String) {
+    let cmd = TestCommand::default()
+        .args([
+            "format",
+            "cargo-libtest",
+            "--summary",
+            "--report-dir",
+            "reports",
+            "--output",
+            "plain",
+        ])
+        .env("GITHUB_STEP_SUMMARY", "summary.md");
+    let formatted = cmd.run_and_format_with_stdin(Some(&output));
+    assert!(
+        formatted.contains("Full report: reports/cifmt-report.md"),
+        "{formatted}"
+    );
+
+    let report = std::fs::read_to_string(cmd.cwd().join("reports/cifmt-report.md"))
+        .expect("Failed to read the report");
+    let summary = std::fs::read_to_string(cmd.cwd().join("summary.md"))
+        .expect("Failed to read the job summary");
+    assert!(summary.starts_with(&report), "{summary}");
+    insta::assert_snapshot!(summary.get(report.len()..).unwrap_or_default(), @"
+
+    > [!NOTE]
+    > The full report is available in `reports/cifmt-report.md`.
+    ");
+}
+
+#[rstest]
+fn format_summary_unset(output: String) {
+    let cmd = TestCommand::default().args(["format", "cargo-libtest", "--summary"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&output)));
+}
+
+#[test]
+fn format_suite_budget() {
+    let input = [
+        r#"{ "type": "suite", "event": "started", "test_count": 1 }"#,
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok" }"#,
+        r#"{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 2.5 }"#,
+        r#"{ "type": "suite", "event": "started", "test_count": 1 }"#,
+        r#"{ "type": "test", "event": "started", "name": "tests::b" }"#,
+        r#"{ "type": "test", "name": "tests::b", "event": "ok" }"#,
+        r#"{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.5 }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "github",
+        "--suite-budget",
+        "2",
+        "--run-budget",
+        "2",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[test]
+fn format_unsupported_option() {
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "github",
+        "--deny",
+        "unsafe_code",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some("")));
+}
+
+#[test]
+fn format_expand_failures() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "event": "started", "name": "tests::b" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok", "stdout": "setting up a" }"#,
+        r#"{ "type": "test", "name": "tests::b", "event": "failed", "stdout": "panicked in b" }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "github",
+        "--output",
+        "azure",
+        "--expand-failures",
+        "github",
+    ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[rstest]
+#[case(None, false)]
+#[case(None, true)]
+#[case(Some("downgrade"), false)]
+#[case(Some("downgrade"), true)]
+#[case(Some("always"), false)]
+fn format_debug_messages(#[case] policy: Option<&str>, #[case] debug: bool) {
+    // Without a policy, the default (`auto`) applies.
+    let mode = policy.unwrap_or("auto");
+    set_snapshot_suffix!(mode, debug);
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok" }"#,
+        "",
+    ]
+    .join("\n");
+    let mut cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "github",
+        "--expand-failures",
+        "github",
+    ]);
+    if let Some(name) = policy {
+        cmd = cmd.args(["--debug-messages", name]);
+    }
+    if debug {
+        cmd = cmd.env("RUNNER_DEBUG", "1");
+    }
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[test]
+fn format_catalog() {
+    let input = [
+        r#"{ "type": "suite", "event": "started", "test_count": 2 }"#,
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok" }"#,
+        r#"{ "type": "test", "event": "started", "name": "tests::b" }"#,
+        r#"{ "type": "test", "name": "tests::b", "event": "failed" }"#,
+        r#"{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0 }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "github",
+        "--catalog",
+        "de.json",
+    ]);
+    std::fs::write(
+        cmd.cwd().join("de.json"),
+        r#"{"test-passed": "Test bestanden", "test-failed": "Test fehlgeschlagen", "test-suite-failed": "Testsuite fehlgeschlagen"}"#,
+    )
+    .expect("Failed to write catalog");
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[test]
+fn format_metadata() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok" }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default()
+        .args([
+            "format",
+            "cargo-libtest",
+            "--output",
+            "github",
+            "--output",
+            "plain=log.txt",
+            "--metadata",
+            "--junit",
+            "junit.xml",
+        ])
+        .filters([
+            (r"command-line: \S*cifmt(\.exe)?", "command-line: cifmt"),
+            (r"cifmt-version: \S+", "cifmt-version: [VERSION]"),
+            (r"os: \S+", "os: [OS]"),
+            (r"arch: \S+", "arch: [ARCH]"),
+            (r"timestamp: \S+", "timestamp: [TIMESTAMP]"),
+        ]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+
+    let junit =
+        std::fs::read_to_string(cmd.cwd().join("junit.xml")).expect("Failed to read report");
+    assert!(junit.contains(r#"<property name="platform" value="github, plain"/>"#));
+}
+
+#[rstest]
+fn format_emit_html() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok" }"#,
+        r#"{ "type": "test", "event": "started", "name": "tests::b" }"#,
+        r#"{ "type": "test", "name": "tests::b", "event": "failed", "stdout": "left: <1>\n" }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "plain=log.txt",
+        "--emit",
+        "html=report.html",
+    ]);
+    let formatted = cmd.run_and_format_with_stdin(Some(&input));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let report =
+        std::fs::read_to_string(cmd.cwd().join("report.html")).expect("Failed to read report");
+    assert!(report.starts_with("<!DOCTYPE html>"), "{report}");
+    assert!(
+        report.contains(r#"<details data-kind="passed">"#),
+        "{report}"
+    );
+    assert!(
+        report.contains(r#"<details data-kind="failed" open>"#),
+        "{report}"
+    );
+    assert!(report.contains("left: &lt;1&gt;"), "{report}");
+}
+
+#[rstest]
+fn format_emit_junit_and_trace() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.5 }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args([
+        "format",
+        "cargo-libtest",
+        "--output",
+        "plain=log.txt",
+        "--emit",
+        "junit=junit.xml",
+        "--emit",
+        "trace=trace.json",
+    ]);
+    let formatted = cmd.run_and_format_with_stdin(Some(&input));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let junit =
+        std::fs::read_to_string(cmd.cwd().join("junit.xml")).expect("Failed to read report");
+    assert!(
+        junit.contains(r#"<testcase classname="tests" name="a""#),
+        "{junit}"
+    );
+    let trace =
+        std::fs::read_to_string(cmd.cwd().join("trace.json")).expect("Failed to read trace");
+    assert!(trace.contains(r#""traceEvents""#), "{trace}");
+}
+
+#[rstest]
+fn format_emit_unknown() {
+    let cmd = TestCommand::default().args(["format", "--detect", "--emit", "pdf=report.pdf"]);
+    insta::assert_snapshot!(cmd.run_and_format());
+}
+
+#[rstest]
+fn format_emit_jsonl() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.5 }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default().args(["format", "cargo-libtest", "--emit", "jsonl"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)));
+}
+
+#[test]
+fn format_emit_github_annotations() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "failed", "stdout": "boom" }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd =
+        TestCommand::default().args(["format", "cargo-libtest", "--emit", "github-annotations"]);
+    insta::assert_snapshot!(cmd.run_and_format_with_stdin(Some(&input)), @r#"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    [
+      {
+        "level": "error",
+        "message": "",
+        "properties": {
+          "title": "Test Failed: tests::a"
+        }
+      }
+    ]
+    --- STDERR ---
+    "#);
+}
@@ -4,9 +4,17 @@
 // https://github.com/rust-lang/rust-clippy/issues/15764
 #![cfg(test)]
 
-use std::{fmt, fmt::Write as _, path::PathBuf};
+use std::{
+    fmt,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
 
+mod convert;
 mod format;
+mod matcher;
+mod selftest;
+mod trend;
 mod version;
 
 /// Default replacements when formatting command output.
@@ -97,6 +105,13 @@ impl TestCommand {
         self
     }
 
+    /// The working directory in which the command is run.
+    #[must_use]
+    #[inline]
+    pub fn cwd(&self) -> &Path {
+        self.cwd.path()
+    }
+
     /// Add an insta filter to the command.
     #[must_use]
     #[inline]
@@ -171,9 +186,14 @@ impl TestCommand {
             });
 
             if let Some(mut stdin) = process.stdin.take() {
-                stdin
-                    .write_all(input.as_bytes())
-                    .expect("Failed to write to stdin");
+                // The command may exit before consuming all of its input (for
+                // example, on invalid arguments), so a broken pipe is expected.
+                match stdin.write_all(input.as_bytes()) {
+                    Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+                        panic!("Failed to write to stdin: {e}")
+                    }
+                    _ => {}
+                }
             }
 
             process.wait_with_output().unwrap_or_else(|e| {
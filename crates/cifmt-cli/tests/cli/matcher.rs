@@ -0,0 +1,59 @@
+use rstest::rstest;
+
+use crate::{TestCommand, set_snapshot_suffix};
+
+#[rstest]
+fn matcher_json(
+    #[values(
+        "cargo-check",
+        "cargo-libtest",
+        "cargo-fmt",
+        "cargo-doc",
+        "lcov",
+        "cargo-udeps",
+        "miri",
+        "rustc-human",
+        "junit",
+        "gcc-diagnostics",
+        "cppcheck",
+        "cmake",
+        "pytest",
+        "black",
+        "bandit",
+        "surefire",
+        "pmd",
+        "psalm",
+        "eslint",
+        "phpunit",
+        "xcodebuild",
+        "osv-scanner",
+        "lsp-diagnostics"
+    )]
+    tool: &str,
+) {
+    set_snapshot_suffix!(tool);
+    let cmd = TestCommand::default().args(["matcher", tool, "--json"]);
+    insta::assert_snapshot!(cmd.run_and_format());
+}
+
+#[test]
+fn matcher_register() {
+    let cmd = TestCommand::default()
+        .args(["matcher", "cargo-check"])
+        .env("RUNNER_TEMP", ".");
+    insta::assert_snapshot!(cmd.run_and_format(), @"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    ::add-matcher::./cifmt-cargo-check.json
+
+    --- STDERR ---
+    ");
+
+    let definition = std::fs::read_to_string(cmd.cwd().join("cifmt-cargo-check.json"))
+        .expect("Failed to read the problem matcher");
+    assert!(
+        definition.contains(r#""owner": "cifmt-cargo-check""#),
+        "{definition}"
+    );
+}
@@ -0,0 +1,23 @@
+use rstest::rstest;
+
+use crate::{TestCommand, set_snapshot_suffix};
+
+#[rstest]
+fn selftest(#[values("plain", "github", "azure")] platform: &str) {
+    set_snapshot_suffix!(platform);
+    let cmd = TestCommand::default().args(["selftest", "--platform", platform]);
+    insta::assert_snapshot!(cmd.run_and_format());
+}
+
+#[test]
+fn selftest_summary() {
+    let cmd = TestCommand::default()
+        .args(["selftest", "--platform", "github", "--summary"])
+        .env("GITHUB_STEP_SUMMARY", "summary.md");
+    let formatted = cmd.run_and_format();
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let summary = std::fs::read_to_string(cmd.cwd().join("summary.md"))
+        .expect("Failed to read the job summary");
+    insta::assert_snapshot!(summary);
+}
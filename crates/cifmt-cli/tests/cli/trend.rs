@@ -0,0 +1,115 @@
+use pretty_assertions::assert_eq;
+
+use crate::TestCommand;
+
+/// A history of two runs of cargo-libtest.
+const HISTORY: &str = r#"[
+  {
+    "tool": "cargo-libtest",
+    "timestamp": "2026-10-14T12:00:00Z",
+    "errors": 0,
+    "warnings": 3,
+    "tests": 2,
+    "failed_tests": ["tests::a"],
+    "duration_ms": 1500
+  },
+  {
+    "tool": "cargo-libtest",
+    "timestamp": "2026-10-15T12:00:00Z",
+    "errors": 1,
+    "warnings": 1,
+    "tests": 2,
+    "failed_tests": ["tests::b"],
+    "duration_ms": 1250
+  }
+]"#;
+
+#[test]
+fn trend() {
+    let cmd = TestCommand::default().args(["trend", "history.json"]);
+    std::fs::write(cmd.cwd().join("history.json"), HISTORY).expect("Failed to write history");
+    insta::assert_snapshot!(cmd.run_and_format(), @"
+    Success: true
+    Exit Code: 0
+    --- STDOUT ---
+    ### cargo-libtest
+
+    #### Trend
+
+    Compared with 1 previous run(s), the latest on 2026-10-14T12:00:00Z.
+
+    | Measure | This run | Change | Recent runs |
+    | :------ | -------: | -----: | :---------- |
+    | Errors | 1 | +1 | 0 → 1 |
+    | Warnings | 1 | -2 | 3 → 1 |
+    | Tests | 2 | 0 | 2 → 2 |
+    | Failed tests | 1 | 0 | 1 → 1 |
+    | Duration | 1.2s | | 1.5s → 1.2s |
+
+    #### New failures
+
+    - `tests::b`
+
+    #### Fixed tests
+
+    - `tests::a`
+
+    --- STDERR ---
+    ");
+}
+
+#[test]
+fn trend_empty() {
+    let cmd = TestCommand::default().args(["trend", "history.json"]);
+    std::fs::write(cmd.cwd().join("history.json"), "[]").expect("Failed to write history");
+    insta::assert_snapshot!(cmd.run_and_format(), @"
+    Success: false
+    Exit Code: 1
+    --- STDOUT ---
+
+    --- STDERR ---
+    [2m[TIMESTAMP][0m [31mERROR[0m [2mcifmt[0m[2m:[0m [2m37:[0m Error executing command: No runs are recorded in history.json
+    ");
+}
+
+#[test]
+fn format_history() {
+    let input = [
+        r#"{ "type": "test", "event": "started", "name": "tests::a" }"#,
+        r#"{ "type": "test", "name": "tests::a", "event": "failed", "stdout": "boom" }"#,
+        "",
+    ]
+    .join("\n");
+    let cmd = TestCommand::default()
+        .args([
+            "format",
+            "cargo-libtest",
+            "--output",
+            "plain=log.txt",
+            "--history",
+            "history.json",
+            "--summary",
+        ])
+        .env("GITHUB_STEP_SUMMARY", "summary.md");
+    std::fs::write(cmd.cwd().join("history.json"), HISTORY).expect("Failed to write history");
+    let formatted = cmd.run_and_format_with_stdin(Some(&input));
+    assert!(formatted.starts_with("Success: true"), "{formatted}");
+
+    let history =
+        std::fs::read_to_string(cmd.cwd().join("history.json")).expect("Failed to read history");
+    assert_eq!(history.matches(r#""tool": "cargo-libtest""#).count(), 3);
+    let summary = std::fs::read_to_string(cmd.cwd().join("summary.md"))
+        .expect("Failed to read the job summary");
+    assert!(
+        summary.contains("Compared with 2 previous run(s), the latest on 2026-10-15T12:00:00Z."),
+        "{summary}"
+    );
+    assert!(
+        summary.contains("#### New failures\n\n- `tests::a`\n"),
+        "{summary}"
+    );
+    assert!(
+        summary.contains("#### Fixed tests\n\n- `tests::b`\n"),
+        "{summary}"
+    );
+}
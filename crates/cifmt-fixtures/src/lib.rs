@@ -0,0 +1,113 @@
+//! Fixtures for benchmarking cifmt.
+//!
+//! The fixtures are built from output recorded from the real tools (the same
+//! output used by the CLI tests), repeated until the fixture reaches the
+//! requested size. Each repetition is given distinct names (test names, source
+//! files) so that the fixture resembles the output of a large project rather
+//! than the same few lines over and over.
+//!
+//! The fixtures can be generated from the benchmarks directly, or written to a
+//! file with the `cifmt-fixtures` binary.
+
+use core::fmt;
+
+/// Recorded `cargo test` output (libtest JSON format).
+const CARGO_LIBTEST: &str = include_str!("../../cifmt/corpora/cargo-libtest.in");
+
+/// Recorded `cargo check` output (JSON format).
+const CARGO_CHECK: &str = include_str!("../../cifmt/corpora/cargo-check.in");
+
+/// A fixture which can be generated at any size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Fixture {
+    /// Cargo test (libtest) JSON output.
+    CargoLibtest,
+    /// Cargo check/build JSON output.
+    CargoCheck,
+}
+
+impl Fixture {
+    /// All available fixtures.
+    pub const ALL: [Self; 2] = [Self::CargoLibtest, Self::CargoCheck];
+
+    /// The recorded output from which this fixture is built.
+    const fn recording(self) -> &'static str {
+        match self {
+            Self::CargoLibtest => CARGO_LIBTEST,
+            Self::CargoCheck => CARGO_CHECK,
+        }
+    }
+
+    /// The text replaced in a repetition of the recording to make it
+    /// distinct, and its replacement.
+    fn rename(self, repetition: usize) -> (&'static str, String) {
+        match self {
+            Self::CargoLibtest => ("tests::", format!("tests_{repetition}::")),
+            Self::CargoCheck => ("src/lib.rs", format!("src/module_{repetition}.rs")),
+        }
+    }
+
+    /// Generate this fixture.
+    ///
+    /// The recording is repeated until the fixture is at least `min_bytes`
+    /// long, so the fixture always contains at least one full recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_bytes` - The minimum size of the fixture, in bytes.
+    #[must_use]
+    #[inline]
+    pub fn generate(self, min_bytes: usize) -> String {
+        let recording = self.recording();
+
+        let mut fixture = String::with_capacity(min_bytes.saturating_add(recording.len()));
+        let mut repetition = 0_usize;
+        loop {
+            let (from, to) = self.rename(repetition);
+            fixture.push_str(&recording.replace(from, &to));
+            repetition = repetition.saturating_add(1);
+            if fixture.len() >= min_bytes {
+                return fixture;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Fixture {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CargoLibtest => write!(f, "cargo-libtest"),
+            Self::CargoCheck => write!(f, "cargo-check"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::Fixture;
+
+    #[rstest]
+    fn contains_recording(#[values(Fixture::CargoLibtest, Fixture::CargoCheck)] fixture: Fixture) {
+        let generated = fixture.generate(0);
+        assert_eq!(
+            generated.lines().count(),
+            fixture.recording().lines().count()
+        );
+    }
+
+    #[test]
+    fn distinct_repetitions() {
+        let recording = Fixture::CargoLibtest.recording();
+        let generated = Fixture::CargoLibtest.generate(recording.len().saturating_mul(3));
+        assert!(generated.len() >= recording.len().saturating_mul(3));
+        assert!(generated.contains("tests_0::test_failing"));
+        assert!(generated.contains("tests_2::test_failing"));
+        assert!(!generated.contains("tests::"));
+    }
+}
@@ -0,0 +1,52 @@
+//! Generate fixtures for benchmarking cifmt.
+//!
+//! This writes a fixture to a file (or stdout), so that it can be used to
+//! profile the CLI or to benchmark it against other formatters:
+//!
+//! ```bash
+//! cargo run -p cifmt-fixtures -- cargo-libtest --size 16777216 > libtest.json
+//! cifmt format cargo-libtest < libtest.json > /dev/null
+//! ```
+
+use std::{
+    fs::File,
+    io::{self, Write as _},
+    path::PathBuf,
+};
+
+use anyhow::{Context as _, Result};
+use cifmt_fixtures::Fixture;
+use clap::Parser as _;
+
+/// Arguments for the fixture generator.
+#[derive(clap::Parser, Debug)]
+#[command(name = "cifmt-fixtures")]
+#[command(about = "Generate fixtures for benchmarking cifmt")]
+struct Args {
+    /// The fixture to generate.
+    #[arg(value_enum)]
+    fixture: Fixture,
+
+    /// The minimum size of the fixture, in bytes.
+    #[arg(long, default_value_t = 1024 * 1024)]
+    size: usize,
+
+    /// The file to which the fixture is written, or stdout if not specified.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let fixture = args.fixture.generate(args.size);
+
+    match &args.output {
+        Some(path) => File::create(path)
+            .and_then(|mut file| file.write_all(fixture.as_bytes()))
+            .with_context(|| format!("Failed to write {}", path.display())),
+        None => io::stdout()
+            .lock()
+            .write_all(fixture.as_bytes())
+            .context("Failed to write to stdout"),
+    }
+}
@@ -0,0 +1,90 @@
+//! Formatting throughput benchmarks.
+//!
+//! These benchmarks measure how quickly parsed messages are formatted for
+//! each platform, both by allocating a new string per message and by writing
+//! into a single reused buffer (as the pipeline does). The messages are parsed
+//! from fixtures generated from recorded tool output by [`cifmt_fixtures`].
+#![expect(
+    missing_docs,
+    reason = "`criterion_group!` generates undocumented items"
+)]
+
+use core::hint::black_box;
+
+use cifmt::{
+    ci::PlatformKind,
+    ci_message::DynCiMessage,
+    tool::{AnyTool, CargoCheck, CargoLibtest},
+};
+use cifmt_fixtures::Fixture;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+/// Minimum size of each fixture, in bytes.
+const FIXTURE_SIZE: usize = 1024 * 1024;
+
+/// Parse the input into messages.
+fn parse(mut tool: impl AnyTool, input: &str) -> Vec<Box<dyn DynCiMessage>> {
+    let messages = tool.parse_dyn(input.as_bytes());
+    assert!(!messages.is_empty(), "no messages parsed");
+    messages
+}
+
+/// Benchmark formatting the given messages for each platform.
+fn bench_messages(c: &mut Criterion, name: &str, messages: &[Box<dyn DynCiMessage>]) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(
+        u64::try_from(messages.len()).unwrap_or(u64::MAX),
+    ));
+    for platform in [PlatformKind::Plain, PlatformKind::GitHub] {
+        group.bench_with_input(
+            BenchmarkId::new("format_for", platform),
+            messages,
+            |b, msgs| {
+                b.iter(|| {
+                    msgs.iter()
+                        .map(|message| message.format_for(black_box(platform)).len())
+                        .fold(0_usize, usize::saturating_add)
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("write_for", platform),
+            messages,
+            |b, msgs| {
+                let mut buffer = String::new();
+                b.iter(|| {
+                    msgs.iter()
+                        .map(|message| {
+                            buffer.clear();
+                            message
+                                .write_for(black_box(platform), &mut buffer)
+                                .map_or(0, |()| buffer.len())
+                        })
+                        .fold(0_usize, usize::saturating_add)
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Benchmark formatting libtest messages.
+fn cargo_libtest(c: &mut Criterion) {
+    let messages = parse(
+        CargoLibtest::default(),
+        &Fixture::CargoLibtest.generate(FIXTURE_SIZE),
+    );
+    bench_messages(c, "format_cargo_libtest", &messages);
+}
+
+/// Benchmark formatting cargo messages.
+fn cargo_check(c: &mut Criterion) {
+    let messages = parse(
+        CargoCheck::default(),
+        &Fixture::CargoCheck.generate(FIXTURE_SIZE),
+    );
+    bench_messages(c, "format_cargo_check", &messages);
+}
+
+criterion_group!(benches, cargo_libtest, cargo_check);
+criterion_main!(benches);
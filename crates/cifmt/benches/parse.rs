@@ -0,0 +1,64 @@
+//! Parser throughput benchmarks.
+//!
+//! These benchmarks measure how quickly each tool splits and parses its
+//! output, both when the output arrives in a single large chunk (e.g., when
+//! reading a saved log) and when it arrives in small chunks (e.g., when
+//! streaming from a pipe). The inputs are generated from recorded tool output
+//! by [`cifmt_fixtures`].
+#![expect(
+    missing_docs,
+    reason = "`criterion_group!` generates undocumented items"
+)]
+
+use core::hint::black_box;
+
+use cifmt::tool::{CargoCheck, CargoLibtest, Tool};
+use cifmt_fixtures::Fixture;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+/// Minimum size of each fixture, in bytes.
+const FIXTURE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Chunk sizes with which the input is provided to the parser.
+const CHUNK_SIZES: [usize; 2] = [4096, usize::MAX];
+
+/// Benchmark a tool against an input, split into chunks of various sizes.
+fn bench_tool<T: Tool + Default>(c: &mut Criterion, name: &str, input: &[u8]) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(
+        u64::try_from(input.len()).unwrap_or(u64::MAX),
+    ));
+    for chunk_size in CHUNK_SIZES {
+        let id = if chunk_size == usize::MAX {
+            "whole".to_owned()
+        } else {
+            chunk_size.to_string()
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(id), input, |b, data| {
+            b.iter(|| {
+                let mut tool = T::default();
+                let mut count = 0_usize;
+                for chunk in data.chunks(chunk_size) {
+                    count = count.saturating_add(tool.parse(black_box(chunk)).len());
+                }
+                count
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark the cargo libtest parser.
+fn cargo_libtest(c: &mut Criterion) {
+    let input = Fixture::CargoLibtest.generate(FIXTURE_SIZE);
+    bench_tool::<CargoLibtest>(c, "cargo_libtest", input.as_bytes());
+}
+
+/// Benchmark the cargo check parser.
+fn cargo_check(c: &mut Criterion) {
+    let input = Fixture::CargoCheck.generate(FIXTURE_SIZE);
+    bench_tool::<CargoCheck>(c, "cargo_check", input.as_bytes());
+}
+
+criterion_group!(benches, cargo_libtest, cargo_check);
+criterion_main!(benches);
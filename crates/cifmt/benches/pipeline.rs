@@ -0,0 +1,61 @@
+//! End-to-end pipeline benchmarks.
+//!
+//! These benchmarks measure the throughput of the whole pipeline (framing,
+//! parsing, formatting, truncation and buffered writing) for each tool and
+//! platform, writing to a sink so that only cifmt itself is measured.
+#![expect(
+    missing_docs,
+    reason = "`criterion_group!` generates undocumented items"
+)]
+
+use core::hint::black_box;
+use std::io;
+
+use cifmt::{
+    ci::PlatformKind,
+    pipeline::{Pipeline, Target},
+    tool::{AnyTool, CargoCheck, CargoLibtest},
+};
+use cifmt_fixtures::Fixture;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+/// Minimum size of each fixture, in bytes.
+const FIXTURE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Benchmark running a fixture through the pipeline for each platform.
+fn bench_pipeline<T: AnyTool + Default + 'static>(c: &mut Criterion, name: &str, fixture: Fixture) {
+    let input = fixture.generate(FIXTURE_SIZE);
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(
+        u64::try_from(input.len()).unwrap_or(u64::MAX),
+    ));
+    for platform in [PlatformKind::Plain, PlatformKind::GitHub] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(platform),
+            input.as_bytes(),
+            |b, data| {
+                b.iter(|| {
+                    let mut pipeline = Pipeline::new(
+                        Box::new(T::default()),
+                        vec![Target::new(platform, io::sink())],
+                    );
+                    pipeline.run(black_box(data)).map(|()| pipeline.errors())
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Benchmark the pipeline on libtest output.
+fn cargo_libtest(c: &mut Criterion) {
+    bench_pipeline::<CargoLibtest>(c, "pipeline_cargo_libtest", Fixture::CargoLibtest);
+}
+
+/// Benchmark the pipeline on cargo output.
+fn cargo_check(c: &mut Criterion) {
+    bench_pipeline::<CargoCheck>(c, "pipeline_cargo_check", Fixture::CargoCheck);
+}
+
+criterion_group!(benches, cargo_libtest, cargo_check);
+criterion_main!(benches);
@@ -0,0 +1,216 @@
+//! Stripping of ANSI escape sequences.
+//!
+//! Many tools emit colour codes even when their output is piped, and some
+//! wrap entire JSON lines in them. Since these sequences are not valid JSON,
+//! they cause every affected line to be skipped. The [`AnsiStripper`] removes
+//! them from the tool's output before it is detected and parsed.
+//!
+//! The following sequences are removed:
+//!
+//! - Control Sequence Introducer (CSI) sequences, such as `ESC [ 3 1 m`, which
+//!   are used for colours and cursor movement.
+//! - Operating System Command (OSC) sequences, such as hyperlinks and window
+//!   titles, terminated by either `BEL` or `ESC \`.
+//! - Device control, privacy and application program command strings, which
+//!   are terminated in the same way as OSC sequences.
+//! - Any other two-byte escape sequence (e.g., `ESC c`).
+//!
+//! Only the 7-bit forms of these sequences are recognized, since the 8-bit
+//! forms would clash with UTF-8 encoded text.
+
+/// The escape character, which introduces every escape sequence.
+const ESC: u8 = 0x1b;
+
+/// The bell character, which may terminate an OSC sequence.
+const BEL: u8 = 0x07;
+
+/// The state of the [`AnsiStripper`] between bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum State {
+    /// Outside of any escape sequence.
+    #[default]
+    Ground,
+    /// After an `ESC`.
+    Escape,
+    /// Within the intermediate bytes of a non-CSI escape sequence.
+    EscapeIntermediate,
+    /// Within a CSI sequence.
+    Csi,
+    /// Within an OSC sequence or other control string.
+    String,
+    /// After an `ESC` within a control string, which may be the start of the
+    /// string terminator.
+    StringEscape,
+}
+
+/// Removes ANSI escape sequences from a stream of bytes.
+///
+/// The input may be provided in arbitrary chunks, as the stripper retains its
+/// state between calls to [`AnsiStripper::strip`]. A sequence split across
+/// two chunks is therefore still removed in its entirety.
+///
+/// To avoid discarding the remainder of the output when a sequence is
+/// malformed or never terminated, a newline always ends the current sequence
+/// and is preserved.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::ansi::AnsiStripper;
+///
+/// let mut stripper = AnsiStripper::default();
+/// assert_eq!(stripper.strip(b"\x1b[1m{\"a\":"), b"{\"a\":");
+/// assert_eq!(stripper.strip(b"1}\x1b[0"), b"1}");
+/// assert_eq!(stripper.strip(b"m\n"), b"\n");
+/// ```
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "AnsiStripper names what is stripped, unlike Stripper"
+)]
+#[derive(Debug, Clone, Default)]
+pub struct AnsiStripper {
+    /// The current state.
+    state: State,
+    /// Buffer holding the stripped output, reused across calls.
+    buffer: Vec<u8>,
+}
+
+impl AnsiStripper {
+    /// Remove escape sequences from the next chunk of input.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The next chunk of the tool's output.
+    ///
+    /// # Returns
+    ///
+    /// The chunk with all escape sequences removed. This borrows the input
+    /// directly if it does not contain any escape sequences.
+    #[inline]
+    pub fn strip<'a>(&'a mut self, input: &'a [u8]) -> &'a [u8] {
+        if self.state == State::Ground && memchr::memchr(ESC, input).is_none() {
+            return input;
+        }
+
+        self.buffer.clear();
+        let mut rest = input;
+        while !rest.is_empty() {
+            if self.state == State::Ground {
+                // Copy everything up to the next escape sequence at once.
+                let Some(pos) = memchr::memchr(ESC, rest) else {
+                    self.buffer.extend_from_slice(rest);
+                    break;
+                };
+                let (text, escape) = rest.split_at(pos);
+                self.buffer.extend_from_slice(text);
+                rest = escape;
+            }
+
+            let Some((&byte, tail)) = rest.split_first() else {
+                break;
+            };
+            rest = tail;
+            self.state = self.next_state(byte);
+            if self.state == State::Ground && byte == b'\n' {
+                self.buffer.push(byte);
+            }
+        }
+        &self.buffer
+    }
+
+    /// The state following the given byte, which is consumed.
+    ///
+    /// A newline always returns to [`State::Ground`], and must then be
+    /// preserved by the caller.
+    fn next_state(&self, byte: u8) -> State {
+        if byte == b'\n' {
+            return State::Ground;
+        }
+        match self.state {
+            State::Ground if byte == ESC => State::Escape,
+            State::Ground => State::Ground,
+            State::Escape => match byte {
+                b'[' => State::Csi,
+                b']' | b'P' | b'X' | b'^' | b'_' => State::String,
+                ESC => State::Escape,
+                0x20..=0x2f => State::EscapeIntermediate,
+                _ => State::Ground,
+            },
+            State::EscapeIntermediate => match byte {
+                0x20..=0x2f => State::EscapeIntermediate,
+                _ => State::Ground,
+            },
+            State::Csi => match byte {
+                0x20..=0x3f => State::Csi,
+                ESC => State::Escape,
+                _ => State::Ground,
+            },
+            State::String => match byte {
+                BEL => State::Ground,
+                ESC => State::StringEscape,
+                _ => State::String,
+            },
+            State::StringEscape => match byte {
+                b'\\' => State::Ground,
+                ESC => State::StringEscape,
+                _ => State::String,
+            },
+        }
+    }
+}
+
+/// Remove all escape sequences from the given input.
+///
+/// This is a convenience for input which is available in its entirety; use
+/// an [`AnsiStripper`] for streamed input.
+///
+/// # Arguments
+///
+/// * `input` - The input to strip.
+#[must_use]
+#[inline]
+pub fn strip(input: &[u8]) -> Vec<u8> {
+    AnsiStripper::default().strip(input).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{AnsiStripper, strip};
+
+    #[rstest]
+    #[case::plain(b"no escapes\n", b"no escapes\n")]
+    #[case::colour(b"\x1b[1;31merror\x1b[0m: failed\n", b"error: failed\n")]
+    #[case::cursor(b"\x1b[2K\x1b[1Gprogress\n", b"progress\n")]
+    #[case::private_mode(b"\x1b[?25lhidden\x1b[?25h\n", b"hidden\n")]
+    #[case::osc_bel(b"\x1b]0;title\x07text\n", b"text\n")]
+    #[case::osc_st(b"\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\\n", b"link\n")]
+    #[case::dcs(b"\x1bPq#0;2;0;0;0\x1b\\text\n", b"text\n")]
+    #[case::two_byte(b"\x1bctext\x1b(B\n", b"text\n")]
+    #[case::json(
+        b"\x1b[32m{\"type\":\"test\",\"event\":\"ok\"}\x1b[0m\n",
+        b"{\"type\":\"test\",\"event\":\"ok\"}\n"
+    )]
+    #[case::utf8("\x1b[1mé\x1b[0m→\n".as_bytes(), "é→\n".as_bytes())]
+    #[case::unterminated_osc(b"\x1b]0;title\nnext\n", b"\nnext\n")]
+    #[case::unterminated_csi(b"\x1b[31\nnext\n", b"\nnext\n")]
+    fn strips(#[case] input: &[u8], #[case] expected: &[u8]) {
+        assert_eq!(strip(input), expected);
+    }
+
+    #[test]
+    fn split_across_chunks() {
+        let input = b"\x1b[1;31merror\x1b]8;;file:///a\x1b\\link\x1b]8;;\x07\x1b[0m\n";
+
+        // Every possible split point must yield the same output.
+        for split in 0..=input.len() {
+            let (first, second) = input.split_at(split);
+            let mut stripper = AnsiStripper::default();
+            let mut output = stripper.strip(first).to_vec();
+            output.extend_from_slice(stripper.strip(second));
+            assert_eq!(output, b"errorlink\n", "split at {split}");
+        }
+    }
+}
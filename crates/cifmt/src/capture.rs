@@ -0,0 +1,274 @@
+//! Handling of output captured by tools.
+//!
+//! Some messages carry output captured by the tool, such as the stdout of a
+//! failed test. This output can be arbitrarily large, which is a problem for
+//! platforms whose logs are not designed to hold megabytes of output for a
+//! single message. This module provides the means to truncate such output,
+//! keeping its head and tail, and optionally to write the full output to an
+//! artifact file instead.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Trait for messages which may carry output captured by the tool.
+pub trait Captured {
+    /// The output captured by the tool (e.g., a test's stdout), along with
+    /// the name of its source (e.g., the test's name).
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn captured_output_mut(&mut self) -> Option<(&str, &mut String)> {
+        None
+    }
+
+    /// Set how the captured output is grouped, for the target the message is
+    /// about to be formatted for.
+    ///
+    /// Defaults to ignoring the grouping, for messages whose output is not
+    /// grouped.
+    ///
+    /// # Arguments
+    ///
+    /// * `grouping` - The grouping of the target.
+    #[inline]
+    fn set_test_grouping(&mut self, _grouping: TestGrouping) {}
+}
+
+/// How the output of each test is grouped, on platforms with collapsible
+/// groups (i.e., GitHub Actions and Azure Pipelines).
+///
+/// Buildkite always expands the section of a failed test, and the other
+/// platforms have no groups, so this has no effect on them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TestGrouping {
+    /// Group the output of every test, from the moment it starts.
+    #[default]
+    All,
+    /// Only group the output of passing tests, and write the output of
+    /// failing tests outside of any group, so that it is visible without
+    /// expanding a group.
+    ///
+    /// Each group is written once the test has finished, so the groups of
+    /// tests running in parallel do not interleave.
+    Passing,
+}
+
+/// Policy for the inclusion of output captured by the tool, and of other
+/// free-form text accompanying a result (e.g., the reason a test was
+/// ignored).
+///
+/// The policy is applied by the tool as its output is parsed, so that it
+/// affects all platforms alike.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutputPolicy {
+    /// Always include the output.
+    #[default]
+    Always,
+    /// Only include the output of failures (e.g., of a failed test).
+    OnFailure,
+    /// Never include the output.
+    Never,
+    /// Always include the output, truncated to
+    /// [`OutputPolicy::TRUNCATED_LIMIT`].
+    Truncated,
+}
+
+impl OutputPolicy {
+    /// The maximum size of the output included under
+    /// [`OutputPolicy::Truncated`], in bytes.
+    pub const TRUNCATED_LIMIT: usize = 2 * 1024;
+
+    /// Apply the policy to the output accompanying a result.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output, which is removed or truncated as required.
+    /// * `failure` - Whether the result is a failure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::capture::OutputPolicy;
+    ///
+    /// let mut output = Some("running...\n".to_owned());
+    /// OutputPolicy::OnFailure.apply(&mut output, false);
+    /// assert_eq!(output, None);
+    /// ```
+    #[inline]
+    pub fn apply(self, output: &mut Option<String>, failure: bool) {
+        match self {
+            Self::Always => {}
+            Self::OnFailure if failure => {}
+            Self::OnFailure | Self::Never => *output = None,
+            Self::Truncated => {
+                if let Some(text) = output.as_mut() {
+                    *text = truncate(text, Self::TRUNCATED_LIMIT, None);
+                }
+            }
+        }
+    }
+}
+
+/// Truncate captured output to the given number of bytes.
+///
+/// The head and tail of the output are kept, as these typically contain the
+/// most relevant information (e.g., how a test started, and the panic which
+/// ended it). The omitted middle is replaced by a marker on its own line,
+/// which also references the artifact holding the full output, if any.
+///
+/// Output which does not exceed the limit is returned unchanged.
+///
+/// # Arguments
+///
+/// * `output` - The captured output.
+/// * `limit` - The maximum number of bytes of the output to keep.
+/// * `artifact` - The file holding the full output, if any.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::capture::truncate;
+///
+/// let output = "first line\n".repeat(100);
+/// let truncated = truncate(&output, 22, None);
+/// assert_eq!(
+///     truncated,
+///     "first line\n[... 1078 bytes omitted ...]\nfirst line\n"
+/// );
+/// ```
+#[must_use]
+#[inline]
+pub fn truncate(output: &str, limit: usize, artifact: Option<&Path>) -> String {
+    if output.len() <= limit {
+        return output.to_owned();
+    }
+
+    let head_len = limit.div_euclid(2);
+    let head_end = output.floor_char_boundary(head_len);
+    let tail_start =
+        output.ceil_char_boundary(output.len().saturating_sub(limit.saturating_sub(head_len)));
+    let (head, rest) = output.split_at(head_end);
+    let (omitted, tail) = rest.split_at(tail_start.saturating_sub(head_end));
+
+    let mut truncated = String::with_capacity(limit.saturating_add(128));
+    truncated.push_str(head);
+    if !head.is_empty() && !head.ends_with('\n') {
+        truncated.push('\n');
+    }
+    truncated.push_str("[... ");
+    truncated.push_str(&omitted.len().to_string());
+    truncated.push_str(" bytes omitted");
+    if let Some(path) = artifact {
+        truncated.push_str(", full output in ");
+        truncated.push_str(&path.display().to_string());
+    }
+    truncated.push_str(" ...]\n");
+    truncated.push_str(tail);
+    truncated
+}
+
+/// Write captured output to an artifact file.
+///
+/// The file is created within the given directory (which is created if
+/// necessary), and named after the source of the output. Since names need
+/// not be unique (e.g., tests with the same name in different test
+/// binaries), each file is prefixed with a sequence number.
+///
+/// # Arguments
+///
+/// * `dir` - The directory in which to create the artifact.
+/// * `sequence` - A number unique to this artifact.
+/// * `name` - The name of the source of the output.
+/// * `output` - The full output.
+///
+/// # Errors
+///
+/// Returns an error if the directory or file cannot be created or written.
+#[inline]
+pub fn write_artifact(
+    dir: &Path,
+    sequence: usize,
+    name: &str,
+    output: &str,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let file_name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let path = dir.join(format!("{sequence:04}-{file_name}.log"));
+    fs::File::create(&path)?.write_all(output.as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use rstest::rstest;
+
+    use super::{OutputPolicy, truncate};
+
+    #[test]
+    fn within_limit() {
+        assert_eq!(truncate("short", 5, None), "short");
+    }
+
+    #[test]
+    fn head_and_tail() {
+        let output = format!("{}{}", "a".repeat(100), "b".repeat(100));
+        insta::assert_snapshot!(
+            truncate(&output, 10, Some(Path::new("out/0000-test.log"))),
+            @"
+        aaaaa
+        [... 190 bytes omitted, full output in out/0000-test.log ...]
+        bbbbb
+        "
+        );
+    }
+
+    #[test]
+    fn char_boundaries() {
+        // Each character is two bytes, so the head (3 bytes) is shortened to
+        // avoid splitting a character.
+        let output = "é".repeat(10);
+        assert_eq!(
+            truncate(&output, 7, None),
+            "é\n[... 14 bytes omitted ...]\néé"
+        );
+    }
+
+    #[rstest]
+    #[case::always(OutputPolicy::Always, false, Some("output"))]
+    #[case::on_failure_passed(OutputPolicy::OnFailure, false, None)]
+    #[case::on_failure_failed(OutputPolicy::OnFailure, true, Some("output"))]
+    #[case::never(OutputPolicy::Never, true, None)]
+    #[case::truncated_short(OutputPolicy::Truncated, false, Some("output"))]
+    fn apply(#[case] policy: OutputPolicy, #[case] failure: bool, #[case] expected: Option<&str>) {
+        let mut output = Some("output".to_owned());
+        policy.apply(&mut output, failure);
+        assert_eq!(output.as_deref(), expected);
+    }
+
+    #[test]
+    fn apply_truncated() {
+        let mut output = Some("a".repeat(OutputPolicy::TRUNCATED_LIMIT * 2));
+        OutputPolicy::Truncated.apply(&mut output, false);
+        let truncated = output.expect("output kept");
+        assert!(truncated.len() < OutputPolicy::TRUNCATED_LIMIT + 64);
+        assert!(truncated.contains("bytes omitted"));
+    }
+}
@@ -0,0 +1,289 @@
+//! Catalog of the titles of annotations and groups.
+//!
+//! The titles shown by the CI platform (e.g., `Test Failed` or `Build
+//! Complete`) default to English. A [`Catalog`] overrides some or all of them
+//! (e.g., with a translation), and is installed once for the whole process
+//! with [`Catalog::install`], before any message is formatted.
+//!
+//! Catalogs are typically read from a JSON object mapping the key of each
+//! [`Title`] to its text:
+//!
+//! ```
+//! use cifmt::catalog::{Catalog, Title};
+//!
+//! let json = r#"{"test-failed": "Test fehlgeschlagen"}"#;
+//! let catalog = Catalog::read_json(json.as_bytes()).expect("valid catalog");
+//! assert_eq!(catalog.get(Title::TestFailed), Some("Test fehlgeschlagen"));
+//! assert_eq!(catalog.get(Title::TestPassed), None);
+//! ```
+
+use core::fmt;
+use std::{collections::HashMap, io::Read, sync::OnceLock};
+
+use serde::Deserialize;
+
+/// The catalog installed for the process, if any.
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// A title of an annotation or a group.
+///
+/// Each title is identified in a catalog by its key, the kebab-case form of
+/// its name (e.g., `test-failed` for [`Title::TestFailed`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Title {
+    /// The group of a single test.
+    Test,
+    /// A test which started.
+    TestStarted,
+    /// A test which passed.
+    TestPassed,
+    /// A test which failed.
+    TestFailed,
+    /// A test which timed out.
+    TestTimeout,
+    /// A test which was ignored.
+    TestIgnored,
+    /// The discovery of the tests of a suite.
+    TestDiscovery,
+    /// A test suite which started.
+    TestSuiteStarted,
+    /// A test suite which passed.
+    TestSuitePassed,
+    /// A test suite which failed.
+    TestSuiteFailed,
+    /// The results of a test suite.
+    TestResults,
+    /// A test suite which exceeded its time budget.
+    SlowTestSuite,
+    /// A test suite which did not finish.
+    IncompleteTestSuite,
+    /// The report of the doctests run.
+    DoctestReport,
+    /// The result of a benchmark.
+    BenchmarkResult,
+    /// A build which succeeded.
+    BuildComplete,
+    /// A build which failed.
+    BuildFailed,
+    /// A build which did not finish.
+    IncompleteBuild,
+    /// A report of future incompatibilities.
+    FutureIncompatibilityReport,
+    /// Dependencies which are not used.
+    UnusedDependencies,
+    /// A line of input exceeding the maximum length.
+    LineTooLong,
+    /// A line of input which ended prematurely.
+    TruncatedOutput,
+    /// The lines of input which are not structured messages.
+    OtherOutput,
+    /// A file which is not formatted.
+    UnformattedFile,
+    /// The number of files which are not formatted.
+    FormatCheck,
+    /// A file which the formatter cannot format.
+    FormatError,
+    /// A file which the analyser could not scan.
+    ScanError,
+    /// A rule which the analyser could not configure.
+    ConfigError,
+    /// A link in the documentation which does not resolve.
+    BrokenDocLink,
+    /// An item without documentation.
+    MissingDocs,
+    /// Another warning of rustdoc about the documentation.
+    Documentation,
+    /// A file whose line coverage is below the threshold.
+    LowCoverage,
+    /// The total coverage of the source files.
+    Coverage,
+    /// The number of dependencies which are not used.
+    DependencyCheck,
+    /// The number of known vulnerabilities of the dependencies.
+    VulnerabilityScan,
+    /// Undefined behavior detected by Miri.
+    UndefinedBehavior,
+    /// A binary which grew beyond the allowed threshold.
+    BinarySizeRegression,
+    /// Input which stopped arriving.
+    Timeout,
+    /// A parser which panicked on its input.
+    ParserPanic,
+    /// Input which is not valid UTF-8.
+    InvalidUtf8,
+    /// Issues not shown as annotations.
+    AnnotationsOmitted,
+    /// The group of the messages of a crate.
+    Crate,
+    /// The group of the messages of a target (e.g., of an Xcode project).
+    Target,
+    /// The group listing the skipped tests.
+    SkippedTests,
+    /// The group listing the sizes of the binaries.
+    BinarySizes,
+    /// The group listing the most frequent lints.
+    TopLintOffenders,
+    /// The group listing the issues of each crate.
+    IssuesByCrate,
+    /// The group listing the issues of each owner.
+    IssuesByOwner,
+    /// The group describing the run.
+    RunMetadata,
+}
+
+impl Title {
+    /// The title in English, unless overridden by the installed catalog.
+    #[must_use]
+    #[inline]
+    pub const fn default_text(self) -> &'static str {
+        match self {
+            Self::Test => "Test",
+            Self::TestStarted => "Test Started",
+            Self::TestPassed => "Test Passed",
+            Self::TestFailed => "Test Failed",
+            Self::TestTimeout => "Test Timeout",
+            Self::TestIgnored => "Test Ignored",
+            Self::TestDiscovery => "Test Discovery",
+            Self::TestSuiteStarted => "Test Suite Started",
+            Self::TestSuitePassed => "Test Suite Passed",
+            Self::TestSuiteFailed => "Test Suite Failed",
+            Self::TestResults => "Test Results",
+            Self::SlowTestSuite => "Slow Test Suite",
+            Self::IncompleteTestSuite => "Incomplete Test Suite",
+            Self::DoctestReport => "Doctest Report",
+            Self::BenchmarkResult => "Benchmark Result",
+            Self::BuildComplete => "Build Complete",
+            Self::BuildFailed => "Build Failed",
+            Self::IncompleteBuild => "Incomplete Build",
+            Self::FutureIncompatibilityReport => "Future Incompatibility Report",
+            Self::UnusedDependencies => "Unused Dependencies",
+            Self::LineTooLong => "Line Too Long",
+            Self::TruncatedOutput => "Truncated Output",
+            Self::OtherOutput => "Other Output",
+            Self::UnformattedFile => "Unformatted File",
+            Self::FormatCheck => "Format Check",
+            Self::FormatError => "Format Error",
+            Self::ScanError => "Scan Error",
+            Self::ConfigError => "Configuration Error",
+            Self::BrokenDocLink => "Broken Doc Link",
+            Self::MissingDocs => "Missing Docs",
+            Self::Documentation => "Documentation",
+            Self::LowCoverage => "Low Coverage",
+            Self::Coverage => "Coverage",
+            Self::DependencyCheck => "Dependency Check",
+            Self::VulnerabilityScan => "Vulnerability Scan",
+            Self::UndefinedBehavior => "Undefined Behavior",
+            Self::BinarySizeRegression => "Binary Size Regression",
+            Self::Timeout => "Timeout",
+            Self::ParserPanic => "Parser Panic",
+            Self::InvalidUtf8 => "Invalid UTF-8",
+            Self::AnnotationsOmitted => "Annotations Omitted",
+            Self::Crate => "Crate",
+            Self::Target => "Target",
+            Self::SkippedTests => "Skipped tests",
+            Self::BinarySizes => "Binary sizes",
+            Self::TopLintOffenders => "Top lint offenders",
+            Self::IssuesByCrate => "Issues by crate",
+            Self::IssuesByOwner => "Issues by owner",
+            Self::RunMetadata => "Run Metadata",
+        }
+    }
+
+    /// The title, as overridden by the installed catalog, or else in
+    /// English.
+    #[must_use]
+    #[inline]
+    pub fn text(self) -> &'static str {
+        CATALOG
+            .get()
+            .and_then(|catalog| catalog.get(self))
+            .unwrap_or(self.default_text())
+    }
+}
+
+impl fmt::Display for Title {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+/// Overrides of the titles of annotations and groups.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Catalog {
+    /// The text of each overridden title.
+    titles: HashMap<Title, String>,
+}
+
+impl Catalog {
+    /// Read a catalog stored as a JSON object mapping the key of each
+    /// overridden title to its text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read, or is not such an
+    /// object (e.g., a key does not identify a [`Title`]).
+    #[inline]
+    pub fn read_json(reader: impl Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Override a title.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title to override.
+    /// * `text` - The text of the title.
+    #[must_use]
+    #[inline]
+    pub fn with(mut self, title: Title, text: impl Into<String>) -> Self {
+        self.titles.insert(title, text.into());
+        self
+    }
+
+    /// The text of a title, if overridden by this catalog.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, title: Title) -> Option<&str> {
+        self.titles.get(&title).map(String::as_str)
+    }
+
+    /// Install this catalog for the whole process.
+    ///
+    /// Only the first catalog installed takes effect, so this should be
+    /// called before any message is formatted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the catalog if one was installed already.
+    #[inline]
+    pub fn install(self) -> Result<(), Self> {
+        CATALOG.set(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Catalog, Title};
+
+    #[test]
+    fn read_json() {
+        let json = r#"{"test-failed": "Test fehlgeschlagen", "invalid-utf8": "Ungültiges UTF-8"}"#;
+        let catalog = Catalog::read_json(json.as_bytes()).expect("valid catalog");
+        assert_eq!(
+            catalog,
+            Catalog::default()
+                .with(Title::TestFailed, "Test fehlgeschlagen")
+                .with(Title::InvalidUtf8, "Ungültiges UTF-8")
+        );
+
+        Catalog::read_json(r#"{"test-exploded": "Boom"}"#.as_bytes()).expect_err("unknown title");
+    }
+}
@@ -0,0 +1,427 @@
+//! Annotations created through the GitHub Checks API.
+//!
+//! GitHub creates at most [`GitHub::MAX_ANNOTATIONS_PER_STEP`] annotations of
+//! each type from the workflow commands of a step, so the annotations beyond
+//! the limit are written to the log instead (see
+//! [`Target::with_annotation_limit`]). With a [`CheckRun`] (see
+//! [`Pipeline::with_check_run`]), these annotations are also created on a
+//! separate check run through the [Checks
+//! API](https://docs.github.com/en/rest/checks/runs), which accepts any number
+//! of annotations in batches of [`CheckRun::MAX_ANNOTATIONS_PER_REQUEST`].
+//!
+//! This module requires the `checks` feature.
+//!
+//! [`GitHub::MAX_ANNOTATIONS_PER_STEP`]: crate::ci::GitHub::MAX_ANNOTATIONS_PER_STEP
+//! [`Target::with_annotation_limit`]: crate::pipeline::Target::with_annotation_limit
+//! [`Pipeline::with_check_run`]: crate::pipeline::Pipeline::with_check_run
+
+use core::fmt;
+use std::{borrow::Cow, env};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{ci::WorkflowAnnotation, ci_message::Severity, http};
+
+/// The level of an annotation of a check run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AnnotationLevel {
+    /// An informational annotation.
+    Notice,
+    /// A warning.
+    Warning,
+    /// An error.
+    Failure,
+}
+
+impl From<Severity> for AnnotationLevel {
+    #[inline]
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => Self::Failure,
+            Severity::Warning => Self::Warning,
+            Severity::Notice | Severity::Debug => Self::Notice,
+        }
+    }
+}
+
+/// An annotation of a check run.
+///
+/// Unlike workflow commands, annotations of a check run must refer to a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct CheckAnnotation {
+    /// The path of the annotated file, relative to the root of the
+    /// repository.
+    pub path: String,
+    /// The first annotated line.
+    pub start_line: u32,
+    /// The last annotated line.
+    pub end_line: u32,
+    /// The first annotated column, if the annotation spans a single line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    /// The last annotated column, if the annotation spans a single line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+    /// The level of the annotation.
+    pub annotation_level: AnnotationLevel,
+    /// The message of the annotation.
+    pub message: String,
+    /// The title of the annotation, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl CheckAnnotation {
+    /// Convert an annotation workflow command.
+    ///
+    /// # Returns
+    ///
+    /// The annotation, or `None` if it does not refer to a file.
+    pub(crate) fn from_workflow(annotation: &WorkflowAnnotation) -> Option<Self> {
+        let path = annotation.param("file")?.into_owned();
+        let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+        let start_line = number("line").unwrap_or(1);
+        let end_line = number("endLine").unwrap_or(start_line).max(start_line);
+        // Columns are only accepted for annotations spanning a single line.
+        let start_column = number("col").filter(|_| end_line == start_line);
+        Some(Self {
+            path,
+            start_line,
+            end_line,
+            start_column,
+            end_column: number("endColumn").filter(|_| start_column.is_some()),
+            annotation_level: annotation.severity().into(),
+            message: annotation.message().into_owned(),
+            title: annotation.param("title").map(Cow::into_owned),
+        })
+    }
+}
+
+/// A check run on which annotations are created through the Checks API.
+#[derive(Clone)]
+pub struct CheckRun {
+    /// The name of the check run.
+    name: String,
+    /// The URL of the GitHub API.
+    api_url: String,
+    /// The repository, as `owner/name`.
+    repository: String,
+    /// The commit to which the check run belongs.
+    head_sha: String,
+    /// The token used to authenticate with the API.
+    token: String,
+}
+
+/// The response to the creation of a check run.
+#[derive(Deserialize)]
+struct Created {
+    /// The identifier of the check run.
+    id: u64,
+}
+
+impl CheckRun {
+    /// The maximum number of annotations accepted by a single request.
+    pub const MAX_ANNOTATIONS_PER_REQUEST: usize = 50;
+
+    /// Environment variable holding the URL of the GitHub API.
+    pub const API_URL_ENV: &str = "GITHUB_API_URL";
+
+    /// Environment variable holding the repository, as `owner/name`.
+    pub const REPOSITORY_ENV: &str = "GITHUB_REPOSITORY";
+
+    /// Environment variable holding the commit being built.
+    pub const SHA_ENV: &str = "GITHUB_SHA";
+
+    /// Environment variable holding the token used to authenticate with the
+    /// API, which must be granted the `checks: write` permission.
+    pub const TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+    /// The URL of the GitHub API, if not set by [`CheckRun::API_URL_ENV`].
+    pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+    /// Create a check run for the given commit.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the check run.
+    /// * `api_url` - The URL of the GitHub API.
+    /// * `repository` - The repository, as `owner/name`.
+    /// * `head_sha` - The commit to which the check run belongs.
+    /// * `token` - The token used to authenticate with the API.
+    #[must_use]
+    #[inline]
+    pub fn new(
+        name: impl Into<String>,
+        api_url: impl Into<String>,
+        repository: impl Into<String>,
+        head_sha: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            api_url: api_url.into(),
+            repository: repository.into(),
+            head_sha: head_sha.into(),
+            token: token.into(),
+        }
+    }
+
+    /// Create a check run for the commit being built by GitHub Actions.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the check run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository, the commit or the token is not
+    /// set in the environment.
+    #[inline]
+    pub fn from_env(name: impl Into<String>) -> Result<Self, Error> {
+        let var = |key: &'static str| env::var(key).map_err(|_err| Error::MissingEnv(key));
+        Ok(Self::new(
+            name,
+            env::var(Self::API_URL_ENV).unwrap_or_else(|_err| Self::DEFAULT_API_URL.to_owned()),
+            var(Self::REPOSITORY_ENV)?,
+            var(Self::SHA_ENV)?,
+            var(Self::TOKEN_ENV)?,
+        ))
+    }
+
+    /// Create the check run with the given annotations, and complete it.
+    ///
+    /// The check run is created with the first batch of annotations, and
+    /// updated with each further batch. It concludes as failed if any of the
+    /// annotations is an error, and as neutral otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `annotations` - The annotations of the check run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request to the API fails.
+    #[inline]
+    pub fn create(&self, annotations: &[CheckAnnotation]) -> Result<(), Error> {
+        let agent = http::agent();
+        let runs = format!(
+            "{}/repos/{}/check-runs",
+            self.api_url.trim_end_matches('/'),
+            self.repository
+        );
+        let authorization = format!("Bearer {}", self.token);
+        let mut bodies = self.bodies(annotations).into_iter();
+        let Some(create) = bodies.next() else {
+            return Ok(());
+        };
+        let Created { id } = agent
+            .post(&runs)
+            .header("Accept", "application/vnd.github+json")
+            .header("Authorization", &authorization)
+            .header("User-Agent", "cifmt")
+            .send_json(&create)?
+            .body_mut()
+            .read_json()?;
+        let run = format!("{runs}/{id}");
+        for update in bodies {
+            agent
+                .patch(&run)
+                .header("Accept", "application/vnd.github+json")
+                .header("Authorization", &authorization)
+                .header("User-Agent", "cifmt")
+                .send_json(&update)?;
+        }
+        Ok(())
+    }
+
+    /// The bodies of the requests creating the check run, then updating it
+    /// with each further batch of annotations.
+    ///
+    /// The last request completes the check run.
+    fn bodies(&self, annotations: &[CheckAnnotation]) -> Vec<Value> {
+        let conclusion = if annotations
+            .iter()
+            .any(|annotation| annotation.annotation_level == AnnotationLevel::Failure)
+        {
+            "failure"
+        } else {
+            "neutral"
+        };
+        let title = format!("{} annotation(s)", annotations.len());
+        let summary = "Annotations exceeding the limit of a workflow step, which are also written \
+                       to the log of the step.";
+
+        let mut bodies: Vec<Value> = annotations
+            .chunks(Self::MAX_ANNOTATIONS_PER_REQUEST)
+            .map(|batch| {
+                json!({
+                    "output": {
+                        "title": title,
+                        "summary": summary,
+                        "annotations": batch,
+                    },
+                })
+            })
+            .collect();
+        if bodies.is_empty() {
+            bodies.push(json!({ "output": { "title": title, "summary": summary } }));
+        }
+        if let Some(Value::Object(first)) = bodies.first_mut() {
+            first.insert("name".to_owned(), json!(self.name));
+            first.insert("head_sha".to_owned(), json!(self.head_sha));
+            first.insert("status".to_owned(), json!("in_progress"));
+        }
+        if let Some(Value::Object(last)) = bodies.last_mut() {
+            last.insert("status".to_owned(), json!("completed"));
+            last.insert("conclusion".to_owned(), json!(conclusion));
+        }
+        bodies
+    }
+}
+
+impl fmt::Debug for CheckRun {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckRun")
+            .field("name", &self.name)
+            .field("api_url", &self.api_url)
+            .field("repository", &self.repository)
+            .field("head_sha", &self.head_sha)
+            .field("token", &"[redacted]")
+            .finish()
+    }
+}
+
+/// Errors relating to the Checks API.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A required environment variable is not set.
+    #[error("The {0} environment variable is not set")]
+    MissingEnv(&'static str),
+    /// A request to the API failed.
+    #[error("Request to the GitHub Checks API failed: {0}")]
+    Request(#[from] ureq::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use serde_json::Value;
+
+    use super::{AnnotationLevel, CheckAnnotation, CheckRun};
+    use crate::ci::{AnnotationLimiter, GitHub};
+    use crate::ci_message::Severity;
+
+    #[test]
+    fn from_workflow() {
+        let mut limiter = AnnotationLimiter::default()
+            .with_limit(Some(1))
+            .collecting();
+        let output = [
+            GitHub::error("cannot find `y`")
+                .file("src/lib.rs")
+                .line(3)
+                .col(5)
+                .title("E0425")
+                .format(),
+            GitHub::warning("unused").file("src/main.rs").format(),
+            GitHub::notice("no file").format(),
+        ]
+        .concat();
+        assert!(
+            limiter.apply(Severity::Error, &output).is_some(),
+            "expected omissions"
+        );
+
+        let annotations: Vec<_> = limiter
+            .collected()
+            .iter()
+            .filter_map(CheckAnnotation::from_workflow)
+            .collect();
+        assert_eq!(
+            annotations,
+            [
+                CheckAnnotation {
+                    path: "src/lib.rs".to_owned(),
+                    start_line: 3,
+                    end_line: 3,
+                    start_column: Some(5),
+                    end_column: None,
+                    annotation_level: AnnotationLevel::Failure,
+                    message: "cannot find `y`".to_owned(),
+                    title: Some("E0425".to_owned()),
+                },
+                CheckAnnotation {
+                    path: "src/main.rs".to_owned(),
+                    start_line: 1,
+                    end_line: 1,
+                    start_column: None,
+                    end_column: None,
+                    annotation_level: AnnotationLevel::Warning,
+                    message: "unused".to_owned(),
+                    title: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bodies() {
+        let run = CheckRun::new("cifmt", "https://api", "owner/repo", "abc123", "secret");
+        let annotation = CheckAnnotation {
+            path: "src/lib.rs".to_owned(),
+            start_line: 1,
+            end_line: 1,
+            start_column: None,
+            end_column: None,
+            annotation_level: AnnotationLevel::Warning,
+            message: "unused".to_owned(),
+            title: None,
+        };
+        let annotations = vec![annotation; CheckRun::MAX_ANNOTATIONS_PER_REQUEST + 1];
+        let bodies = run.bodies(&annotations);
+        let [create, update] = bodies.as_slice() else {
+            panic!("expected two requests, got {bodies:?}");
+        };
+        assert_eq!(create["name"], "cifmt");
+        assert_eq!(create["status"], "in_progress");
+        assert_eq!(
+            create
+                .pointer("/output/annotations")
+                .and_then(Value::as_array)
+                .map(Vec::len),
+            Some(CheckRun::MAX_ANNOTATIONS_PER_REQUEST)
+        );
+        assert_eq!(update["status"], "completed");
+        assert_eq!(update["conclusion"], "neutral");
+        assert_eq!(
+            update
+                .pointer("/output/annotations")
+                .and_then(Value::as_array)
+                .map(Vec::len),
+            Some(1)
+        );
+
+        insta::assert_json_snapshot!(run.bodies(&[]), @r#"
+        [
+          {
+            "conclusion": "neutral",
+            "head_sha": "abc123",
+            "name": "cifmt",
+            "output": {
+              "summary": "Annotations exceeding the limit of a workflow step, which are also written to the log of the step.",
+              "title": "0 annotation(s)"
+            },
+            "status": "completed"
+          }
+        ]
+        "#);
+        assert!(!format!("{run:?}").contains("secret"), "token not redacted");
+    }
+}
@@ -0,0 +1,230 @@
+//! Checkstyle XML reports.
+//!
+//! Many plugins of CI servers and code hosts (e.g., the Warnings Next
+//! Generation plugin of Jenkins, or the code insights of Bitbucket) read the
+//! issues found by an analyser from a report in the XML format of
+//! [Checkstyle](https://checkstyle.org), rather than from the log. This
+//! module collects the annotations of the messages parsed into a [`Report`],
+//! which can be written in this format.
+//!
+//! Each annotation of a message (as formatted for GitHub Actions, so that the
+//! paths are relative to the root of the checkout) becomes an `error` of its
+//! file, at its line and column, with the severity `error`, `warning` or
+//! `info`. Its source is the tool, followed by the message's code if any
+//! (e.g., `cargo-check.unused_variables`). The files are written in the order
+//! of their paths, and the errors of each file in the order in which they
+//! were recorded. Annotations which do not refer to a file are skipped, as
+//! each error belongs to a file.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::{
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+    junit::Escaped,
+};
+
+/// An error of a file within a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// The line of the error, if known.
+    line: Option<u32>,
+    /// The column of the error, if known.
+    column: Option<u32>,
+    /// The severity of the error.
+    severity: Severity,
+    /// The message of the error, preceded by the title of its annotation
+    /// unless it only restates the code.
+    message: String,
+    /// The check which reported the error.
+    source: String,
+}
+
+impl Entry {
+    /// The severity of the error, as named by Checkstyle.
+    const fn severity(&self) -> &'static str {
+        match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Notice | Severity::Debug => "info",
+        }
+    }
+}
+
+/// A Checkstyle report of the annotations of the messages of a tool.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::checkstyle::Report;
+///
+/// let report = Report::new("cargo-check");
+///
+/// let mut xml = Vec::new();
+/// report.write_xml(&mut xml).expect("writing to a Vec cannot fail");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// The name of the tool which reported the annotations.
+    tool: String,
+    /// The errors of each file, by the path of the file.
+    files: BTreeMap<String, Vec<Entry>>,
+}
+
+impl Report {
+    /// Create an empty report.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool reporting the annotations.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Record the annotations of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, whose annotations when formatted for
+    ///   GitHub Actions are recorded.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        let formatted = message.format_for(PlatformKind::GitHub);
+        let code = message.code();
+        let source = match code {
+            Some(value) => format!("{}.{value}", self.tool),
+            None => self.tool.clone(),
+        };
+        for annotation in formatted.lines().filter_map(WorkflowAnnotation::parse) {
+            let Some(path) = annotation.param("file") else {
+                continue;
+            };
+            let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+            // The title is omitted when it only restates the code (e.g.,
+            // `warning: unused_variables`).
+            let text = match annotation.param("title") {
+                Some(title) if code.is_none_or(|value| !title.contains(value)) => {
+                    format!("{title}: {}", annotation.message())
+                }
+                Some(_) | None => annotation.message().into_owned(),
+            };
+            self.files
+                .entry(path.into_owned())
+                .or_default()
+                .push(Entry {
+                    line: number("line"),
+                    column: number("col"),
+                    severity: annotation.severity(),
+                    message: text,
+                    source: source.clone(),
+                });
+        }
+    }
+
+    /// The number of errors recorded, in all files.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.files.values().map(Vec::len).sum()
+    }
+
+    /// Whether no errors have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Write the report in the Checkstyle XML format.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    #[inline]
+    pub fn write_xml(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+        for (path, entries) in &self.files {
+            writeln!(writer, r#"  <file name="{}">"#, Escaped(path))?;
+            for entry in entries {
+                write_entry(&mut writer, entry)?;
+            }
+            writeln!(writer, "  </file>")?;
+        }
+        writeln!(writer, "</checkstyle>")?;
+        writer.flush()
+    }
+}
+
+/// Write a single error of a file.
+fn write_entry(writer: &mut impl Write, entry: &Entry) -> io::Result<()> {
+    write!(writer, "    <error")?;
+    if let Some(line) = entry.line {
+        write!(writer, r#" line="{line}""#)?;
+    }
+    if let Some(column) = entry.column {
+        write!(writer, r#" column="{column}""#)?;
+    }
+    writeln!(
+        writer,
+        r#" severity="{}" message="{}" source="{}"/>"#,
+        entry.severity(),
+        Escaped(&entry.message),
+        Escaped(&entry.source)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Report;
+    use crate::tool::{CargoCheck, CargoFmt, Tool as _};
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+"#;
+
+    #[test]
+    fn write_xml() {
+        let mut report = Report::new("cargo-check");
+        let mut check = CargoCheck::default();
+        let mut messages = check.parse(WARNING.as_bytes());
+        messages.extend(check.finish());
+        for message in messages {
+            report.record(&message.expect("valid message"));
+        }
+        let mut fmt = CargoFmt::default();
+        let mut unformatted = fmt.parse(b"Diff in /w/src/main.rs:2:\n-    a+b\n+    a + b\n");
+        unformatted.extend(fmt.finish());
+        for message in unformatted {
+            report.record(&message.expect("valid message"));
+        }
+        assert_eq!(report.len(), 2);
+
+        let mut xml = Vec::new();
+        report.write_xml(&mut xml).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&xml), @r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <checkstyle version="4.3">
+          <file name="/w/src/main.rs">
+            <error line="2" severity="error" message="Unformatted File: File is not formatted (1 change(s)); run `cargo fmt` to format it" source="cargo-check"/>
+          </file>
+          <file name="src/lib.rs">
+            <error line="2" column="9" severity="warning" message="unused variable: `x`" source="cargo-check.unused_variables"/>
+          </file>
+        </checkstyle>
+        "#);
+    }
+}
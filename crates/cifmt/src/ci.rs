@@ -9,17 +9,74 @@
     reason = "Keeping a flat module structure for CI platforms"
 )]
 
+mod azure;
+mod buildkite;
+mod cloudbuild;
+mod codebuild;
 mod github;
+mod path;
 mod plain;
+mod registry;
+mod terminal;
 
-use core::fmt;
+use core::{fmt, str::FromStr};
 
-use tracing::debug;
+use crate::ci_message::Severity;
 
+pub(crate) use azure::{AZURE_ENDGROUP, AzureGroup, Issue, IssueParams};
+pub use azure::{AzureDevOps, TaskResult};
+pub(crate) use buildkite::CodeBlock;
+pub use buildkite::{AnnotationStyle, Buildkite};
+pub use cloudbuild::CloudBuild;
+pub use codebuild::{CodeBuild, Phase};
 pub use github::GitHub;
+pub(crate) use github::{
+    Annotation, AnnotationLimiter, AnnotationParams, ENDGROUP, Group, WorkflowAnnotation,
+    plain_debug,
+};
+pub use path::set_checkout_root;
+pub(crate) use path::{NormalizedPath, is_in_checkout, relative_to_checkout};
 pub use plain::Plain;
+pub use registry::{Detector, PLATFORM_ENV, Registry};
+pub use terminal::Terminal;
 
 /// Platform trait.
+///
+/// Besides detecting the platform, this provides the basic building blocks of
+/// CI output, such that a message can be formatted for any platform with a
+/// single generic [`CiMessage`](crate::ci_message::CiMessage) implementation.
+///
+/// None of the building blocks ends with a newline, such that they can be
+/// joined with newlines, or returned as a formatted message (which is written
+/// as a line). Building blocks which write nothing on a platform (e.g., the
+/// end of a group where groups need not be ended) are empty.
+///
+/// ```
+/// use cifmt::ci::{GitHub, Platform, Plain};
+/// use cifmt::ci_message::Severity;
+///
+/// fn deprecated<P: Platform>(file: &str, line: u32) -> String {
+///     P::annotate(
+///         Severity::Warning,
+///         "This function is deprecated",
+///         Some((file, line, None)),
+///         Some("Deprecation"),
+///     )
+/// }
+///
+/// assert_eq!(
+///     deprecated::<GitHub>("src/lib.rs", 3),
+///     "::warning file=src/lib.rs,line=3,title=Deprecation::This function is deprecated",
+/// );
+/// assert_eq!(
+///     deprecated::<Plain>("src/lib.rs", 3),
+///     "src/lib.rs:3: warning: Deprecation: This function is deprecated",
+/// );
+/// ```
+///
+/// Platforms may offer more than these building blocks (for example, the
+/// expanded sections of Buildkite), which are available through their
+/// inherent methods.
 pub trait Platform: fmt::Display {
     /// Infer the CI platform from environment variables.
     ///
@@ -28,19 +85,388 @@ pub trait Platform: fmt::Display {
     fn from_env() -> Option<Self>
     where
         Self: Sized;
+
+    /// Formats an annotation, highlighting a message in the platform's
+    /// interface where supported, or otherwise logging it as plain text.
+    ///
+    /// # Arguments
+    ///
+    /// * `severity` - The severity of the message. Debug messages are only
+    ///   logged, and only where the platform shows them.
+    /// * `message` - The message.
+    /// * `location` - The file, line and column to which the message relates,
+    ///   if any.
+    /// * `title` - A title for the message, if any.
+    ///
+    /// # Returns
+    ///
+    /// The formatted annotation, without a trailing newline.
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String
+    where
+        Self: Sized;
+
+    /// Formats an annotation spanning a range of lines and columns.
+    ///
+    /// Platforms which cannot highlight a range annotate its start, which is
+    /// the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `severity` - The severity of the message.
+    /// * `message` - The message.
+    /// * `file` - The file to which the message relates.
+    /// * `span` - The lines and columns at which the range starts and ends.
+    /// * `title` - A title for the message, if any.
+    ///
+    /// # Returns
+    ///
+    /// The formatted annotation, without a trailing newline.
+    #[inline]
+    fn annotate_span(
+        severity: Severity,
+        message: impl AsRef<str>,
+        file: &str,
+        span: ((u32, u32), (u32, u32)),
+        title: Option<&str>,
+    ) -> String
+    where
+        Self: Sized,
+    {
+        let ((line, column), _) = span;
+        Self::annotate(severity, message, Some((file, line, Some(column))), title)
+    }
+
+    /// Formats the start of a group of log lines, which the platform may
+    /// collapse.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the group.
+    ///
+    /// # Returns
+    ///
+    /// The formatted start of the group, without a trailing newline.
+    fn group(title: impl AsRef<str>) -> String
+    where
+        Self: Sized;
+
+    /// Formats the end of a group started with [`Platform::group`].
+    ///
+    /// # Returns
+    ///
+    /// The formatted end of the group, without a trailing newline. This is
+    /// empty where groups need not be ended.
+    fn endgroup() -> String
+    where
+        Self: Sized;
+
+    /// Formats a debug message, which the platform may hide by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message.
+    ///
+    /// # Returns
+    ///
+    /// The formatted message, without a trailing newline.
+    fn debug(message: impl AsRef<str>) -> String
+    where
+        Self: Sized;
 }
 
 /// Detect the CI platform from environment variables.
 ///
-/// Returns a boxed platform implementation. Falls back to `Plain` when no
-/// specific platform is detected.
+/// Returns a boxed platform implementation, as detected by the default
+/// [`Registry`]. Falls back to `Plain` when no specific platform is detected.
+#[must_use]
 #[inline]
 pub fn from_env() -> Box<dyn Platform> {
-    debug!("Detecting CI platform from environment variables");
-    if let Some(env) = GitHub::from_env() {
-        Box::new(env)
-    } else {
-        // Fall back to the plain formatter when detection fails.
-        Box::new(Plain)
+    match Registry::default().detect() {
+        PlatformKind::Plain => Box::new(Plain),
+        PlatformKind::GitHub => Box::new(GitHub),
+        PlatformKind::AzureDevOps => Box::new(AzureDevOps),
+        PlatformKind::Buildkite => Box::new(Buildkite),
+        PlatformKind::CodeBuild => Box::new(CodeBuild),
+        PlatformKind::CloudBuild => Box::new(CloudBuild),
+        PlatformKind::Terminal => Box::new(Terminal),
+    }
+}
+
+/// Prefix a message with its title, for platforms which have no separate
+/// title.
+///
+/// A title without a message is used on its own.
+pub(crate) fn titled(message: &str, title: Option<&str>) -> String {
+    match title {
+        Some(heading) if message.is_empty() => heading.to_owned(),
+        Some(heading) => format!("{heading}: {message}"),
+        None => message.to_owned(),
+    }
+}
+
+/// Runtime identifier for a supported CI platform.
+///
+/// The [`Platform`] implementations are type-level markers, which makes them
+/// unsuitable when the platform is only known at runtime (for example, when it
+/// is provided on the command line), or when the same messages must be
+/// formatted for several platforms at once. This enum fills that gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlatformKind {
+    /// The [`Plain`] text platform.
+    Plain,
+    /// The [`GitHub`] Actions platform.
+    GitHub,
+    /// The [`AzureDevOps`] Pipelines platform.
+    AzureDevOps,
+    /// The [`Buildkite`] platform.
+    Buildkite,
+    /// The AWS [`CodeBuild`] platform.
+    CodeBuild,
+    /// The Google [`CloudBuild`] platform.
+    CloudBuild,
+    /// The colored [`Terminal`] platform, for local usage.
+    Terminal,
+}
+
+impl PlatformKind {
+    /// All platforms.
+    pub const ALL: [Self; 7] = [
+        Self::Plain,
+        Self::GitHub,
+        Self::AzureDevOps,
+        Self::Buildkite,
+        Self::CodeBuild,
+        Self::CloudBuild,
+        Self::Terminal,
+    ];
+
+    /// Detect the CI platform from environment variables, using the default
+    /// [`Registry`].
+    ///
+    /// The platform can be forced with the [`PLATFORM_ENV`] environment
+    /// variable. Falls back to [`PlatformKind::Plain`] when no specific
+    /// platform is detected.
+    #[must_use]
+    #[inline]
+    pub fn from_env() -> Self {
+        Registry::default().detect()
+    }
+
+    /// The default maximum size of captured output (e.g., a test's stdout)
+    /// for this platform, in bytes.
+    ///
+    /// Output exceeding this limit is truncated (see
+    /// [`capture::truncate`](crate::capture::truncate)). `None` indicates
+    /// that output is never truncated.
+    #[must_use]
+    #[inline]
+    pub fn default_output_limit(self) -> Option<usize> {
+        match self {
+            Self::Plain | Self::Buildkite | Self::CodeBuild | Self::CloudBuild | Self::Terminal => {
+                None
+            }
+            Self::GitHub | Self::AzureDevOps => Some(64 * 1024),
+        }
+    }
+
+    /// Start a group of log lines on this platform (see [`Platform::group`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the group.
+    #[must_use]
+    #[inline]
+    pub fn group(self, title: &str) -> String {
+        match self {
+            Self::Plain => Plain::group(title),
+            Self::GitHub => GitHub::group(title),
+            Self::AzureDevOps => AzureDevOps::group(title),
+            Self::Buildkite => Buildkite::group(title),
+            Self::CodeBuild => CodeBuild::group(title),
+            Self::CloudBuild => CloudBuild::group(title),
+            Self::Terminal => Terminal::group(title),
+        }
+    }
+
+    /// End the current group of log lines on this platform (see
+    /// [`Platform::endgroup`]).
+    #[must_use]
+    #[inline]
+    pub fn endgroup(self) -> String {
+        match self {
+            Self::Plain => Plain::endgroup(),
+            Self::GitHub => GitHub::endgroup(),
+            Self::AzureDevOps => AzureDevOps::endgroup(),
+            Self::Buildkite => Buildkite::endgroup(),
+            Self::CodeBuild => CodeBuild::endgroup(),
+            Self::CloudBuild => CloudBuild::endgroup(),
+            Self::Terminal => Terminal::endgroup(),
+        }
+    }
+}
+
+impl fmt::Display for PlatformKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plain => write!(f, "plain"),
+            Self::GitHub => write!(f, "github"),
+            Self::AzureDevOps => write!(f, "azure"),
+            Self::Buildkite => write!(f, "buildkite"),
+            Self::CodeBuild => write!(f, "codebuild"),
+            Self::CloudBuild => write!(f, "cloudbuild"),
+            Self::Terminal => write!(f, "terminal"),
+        }
+    }
+}
+
+impl FromStr for PlatformKind {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "github" => Ok(Self::GitHub),
+            "azure" => Ok(Self::AzureDevOps),
+            "buildkite" => Ok(Self::Buildkite),
+            "codebuild" => Ok(Self::CodeBuild),
+            "cloudbuild" => Ok(Self::CloudBuild),
+            "terminal" => Ok(Self::Terminal),
+            _ => Err(Error::UnknownPlatform(s.to_owned())),
+        }
+    }
+}
+
+/// Errors relating to CI platforms.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The platform name is not recognised.
+    #[error("Unknown platform: {0}")]
+    UnknownPlatform(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{
+        AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, PlatformKind,
+        Terminal,
+    };
+    use crate::ci_message::Severity;
+
+    /// Format a group containing an annotation of each severity.
+    fn building_blocks<P: Platform>() -> String {
+        [
+            P::group("Checks"),
+            P::annotate(
+                Severity::Error,
+                "mismatched types",
+                Some(("src/lib.rs", 3, Some(9))),
+                Some("E0308"),
+            ),
+            P::annotate(Severity::Warning, "unused import", None, None),
+            P::annotate(Severity::Notice, "2 checks run", None, Some("Summary")),
+            P::annotate(Severity::Debug, "took 1s", None, Some("Timing")),
+            P::debug("done"),
+            P::endgroup(),
+        ]
+        .into_iter()
+        .filter(|block| !block.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    #[test]
+    fn platform_building_blocks() {
+        insta::assert_snapshot!(building_blocks::<Plain>(), @"
+        Checks
+        src/lib.rs:3:9: error:   E0308: mismatched types
+        warning: unused import
+        notice:  Summary: 2 checks run
+        debug:   Timing: took 1s
+        done
+        ");
+        insta::assert_snapshot!(building_blocks::<GitHub>(), @"
+        ::group::Checks
+        ::error file=src/lib.rs,line=3,col=9,title=E0308::mismatched types
+        ::warning ::unused import
+        ::notice title=Summary::2 checks run
+        ::debug::Timing: took 1s
+        ::debug::done
+        ::endgroup::
+        ");
+        insta::assert_snapshot!(building_blocks::<AzureDevOps>(), @"
+        ##[group]Checks
+        ##vso[task.logissue type=error;sourcepath=src/lib.rs;linenumber=3;columnnumber=9]E0308: mismatched types
+        ##vso[task.logissue type=warning]unused import
+        notice:  Summary: 2 checks run
+        ##[debug]Timing: took 1s
+        ##[debug]done
+        ##[endgroup]
+        ");
+        insta::assert_snapshot!(building_blocks::<Buildkite>(), @"
+        --- Checks
+        ^^^ +++
+        src/lib.rs:3:9: error:   E0308: mismatched types
+        warning: unused import
+        notice:  Summary: 2 checks run
+        debug:   Timing: took 1s
+        done
+        ");
+        insta::assert_snapshot!(building_blocks::<CodeBuild>(), @"
+        Checks
+        src/lib.rs:3:9: error:   E0308: mismatched types
+        warning: unused import
+        notice:  Summary: 2 checks run
+        debug:   Timing: took 1s
+        done
+        ");
+        insta::assert_snapshot!(building_blocks::<CloudBuild>(), @r#"
+        {"severity":"NOTICE","message":"Checks"}
+        {"severity":"ERROR","message":"E0308: mismatched types","logging.googleapis.com/sourceLocation":{"file":"src/lib.rs","line":"3"}}
+        {"severity":"WARNING","message":"unused import"}
+        {"severity":"NOTICE","message":"Summary: 2 checks run"}
+        {"severity":"DEBUG","message":"Timing: took 1s"}
+        {"severity":"DEBUG","message":"done"}
+        "#);
+        insta::assert_snapshot!(building_blocks::<Terminal>().replace('\x1b', "\\e"), @r"
+        \e[1mChecks\e[0m
+        \e[1msrc/lib.rs:3:9\e[0m: \e[1;31mERROR\e[0m: \e[1mE0308\e[0m: mismatched types
+        \e[1;33mWARNING\e[0m: unused import
+        \e[36mNOTICE\e[0m: \e[1mSummary\e[0m: 2 checks run
+        \e[2mDEBUG\e[0m: \e[1mTiming\e[0m: took 1s
+        \e[2mdone\e[0m
+        ");
+    }
+
+    #[rstest]
+    #[case("plain", PlatformKind::Plain)]
+    #[case("github", PlatformKind::GitHub)]
+    #[case("azure", PlatformKind::AzureDevOps)]
+    #[case("buildkite", PlatformKind::Buildkite)]
+    #[case("codebuild", PlatformKind::CodeBuild)]
+    #[case("cloudbuild", PlatformKind::CloudBuild)]
+    #[case("terminal", PlatformKind::Terminal)]
+    fn platform_kind_round_trip(#[case] name: &str, #[case] kind: PlatformKind) {
+        assert_eq!(name.parse::<PlatformKind>().ok(), Some(kind));
+        assert_eq!(kind.to_string(), name);
+    }
+
+    #[rstest]
+    fn platform_kind_unknown() {
+        let err = "jenkins"
+            .parse::<PlatformKind>()
+            .expect_err("unknown platform should fail to parse");
+        insta::assert_snapshot!(err, @"Unknown platform: jenkins");
     }
 }
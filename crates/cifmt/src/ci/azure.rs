@@ -0,0 +1,543 @@
+//! Azure DevOps platform support.
+//!
+//! This module defines the Azure DevOps platform marker and implements
+//! formatting of CI messages through Azure Pipelines logging commands.
+
+use bon::bon;
+use core::fmt;
+use tracing::debug;
+
+use crate::ci::{NormalizedPath, Plain, Platform, titled};
+use crate::ci_message::Severity;
+
+/// Azure DevOps platform marker.
+///
+/// Azure Pipelines supports logging commands for reporting issues against
+/// files, folding output into groups, and setting the result of the current
+/// task.
+///
+/// For more information, see:
+/// <https://learn.microsoft.com/en-us/azure/devops/pipelines/scripts/logging-commands>.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct AzureDevOps;
+
+impl Platform for AzureDevOps {
+    #[inline]
+    fn from_env() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        (std::env::var("TF_BUILD").is_ok()
+            || std::env::var("SYSTEM_TEAMFOUNDATIONCOLLECTIONURI").is_ok())
+        .then(|| {
+            debug!("Detected Azure DevOps environment");
+            AzureDevOps
+        })
+    }
+
+    /// Errors and warnings are reported as issues, which have no title, so the
+    /// title prefixes the message. Notices are logged as plain text, as Azure
+    /// has no equivalent.
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        let kind = match severity {
+            Severity::Debug => {
+                return <Self as Platform>::debug(titled(message.as_ref(), title));
+            }
+            Severity::Notice => return Plain::annotate(severity, message, location, title),
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let text = titled(message.as_ref(), title);
+        let mut issue = Issue {
+            kind,
+            params: IssueParams {
+                source_path: location.map(|(file, _, _)| file),
+                line_number: location.map(|(_, line, _)| line),
+                column_number: location.and_then(|(_, _, column)| column),
+                code: None,
+            },
+            message: &text,
+        }
+        .to_string();
+        // The command is written as a line, whose newline is left to the caller.
+        issue.pop();
+        issue
+    }
+
+    /// Starts a collapsible group in the pipeline log.
+    ///
+    /// All output between this command and `endgroup()` will be folded into
+    /// an expandable group in the logs.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the group to display.
+    ///
+    /// # Returns
+    ///
+    /// A formatted group command string, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{AzureDevOps, Platform};
+    ///
+    /// println!("{}", AzureDevOps::group("Build Steps"));
+    /// println!("Running build...");
+    /// println!("{}", AzureDevOps::endgroup());
+    /// ```
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        format!("##[group]{}", title.as_ref())
+    }
+
+    /// Ends a collapsible group in the pipeline log.
+    ///
+    /// # Returns
+    ///
+    /// A formatted endgroup command string, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{AzureDevOps, Platform};
+    ///
+    /// println!("{}", AzureDevOps::group("Test Results"));
+    /// println!("Running tests...");
+    /// println!("{}", AzureDevOps::endgroup());
+    /// ```
+    #[inline]
+    fn endgroup() -> String {
+        "##[endgroup]".to_owned()
+    }
+
+    /// Formats a debug message for Azure Pipelines.
+    ///
+    /// These messages are only visible when the pipeline is run with
+    /// `system.debug` set to `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The debug message to format.
+    ///
+    /// # Returns
+    ///
+    /// A formatted debug message string, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{AzureDevOps, Platform};
+    ///
+    /// let debug_message = AzureDevOps::debug("This is a debug message.");
+    /// ```
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        format!("##[debug]{}", message.as_ref())
+    }
+}
+
+impl fmt::Display for AzureDevOps {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Azure DevOps")
+    }
+}
+
+/// The result of a task, as reported by [`AzureDevOps::complete`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TaskResult {
+    /// The task succeeded.
+    Succeeded,
+    /// The task succeeded, but reported warnings.
+    SucceededWithIssues,
+    /// The task failed.
+    Failed,
+}
+
+impl fmt::Display for TaskResult {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Succeeded => write!(f, "Succeeded"),
+            Self::SucceededWithIssues => write!(f, "SucceededWithIssues"),
+            Self::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// Writer which escapes the data of a logging command.
+///
+/// Logging commands are terminated by a newline, so newlines (and the `%`
+/// used to escape them) must be escaped within the message. Property values
+/// additionally escape the `;` and `]` characters which delimit properties.
+struct Escaper<'a, 'b> {
+    /// The underlying formatter.
+    f: &'a mut fmt::Formatter<'b>,
+    /// Whether a property value (rather than the message) is being written.
+    property: bool,
+}
+
+impl fmt::Write for Escaper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(pos) = rest
+            .find(|c| matches!(c, '%' | '\r' | '\n') || (self.property && matches!(c, ';' | ']')))
+        {
+            let (text, tail) = rest.split_at(pos);
+            self.f.write_str(text)?;
+            let mut chars = tail.chars();
+            self.f.write_str(match chars.next() {
+                Some('%') => "%AZP25",
+                Some('\r') => "%0D",
+                Some('\n') => "%0A",
+                Some(';') => "%3B",
+                _ => "%5D",
+            })?;
+            rest = chars.as_str();
+        }
+        self.f.write_str(rest)
+    }
+}
+
+/// Display a value, escaped for use within a logging command.
+struct Escaped<T> {
+    /// The value to escape.
+    value: T,
+    /// Whether the value is a property value (rather than the message).
+    property: bool,
+}
+
+impl<T: fmt::Display> fmt::Display for Escaped<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let property = self.property;
+        fmt::write(&mut Escaper { f, property }, format_args!("{}", self.value))
+    }
+}
+
+/// Location and metadata of an issue (error or warning).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IssueParams<'a> {
+    /// The source file in which the issue occurs, which is normalized when
+    /// displayed (see [`NormalizedPath`]).
+    pub source_path: Option<&'a str>,
+    /// The line number (1-indexed).
+    pub line_number: Option<u32>,
+    /// The column number (1-indexed).
+    pub column_number: Option<u32>,
+    /// The code identifying the issue.
+    pub code: Option<&'a str>,
+}
+
+impl fmt::Display for IssueParams<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.source_path {
+            write!(
+                f,
+                ";sourcepath={}",
+                Escaped {
+                    value: NormalizedPath(path),
+                    property: true
+                }
+            )?;
+        }
+        if let Some(line) = self.line_number {
+            write!(f, ";linenumber={line}")?;
+        }
+        if let Some(column) = self.column_number {
+            write!(f, ";columnnumber={column}")?;
+        }
+        if let Some(code) = self.code {
+            write!(
+                f,
+                ";code={}",
+                Escaped {
+                    value: code,
+                    property: true
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A `task.logissue` command (error or warning).
+///
+/// Displays as the full logging command, including the trailing newline.
+pub(crate) struct Issue<'a, M> {
+    /// The type of issue, either `error` or `warning`.
+    pub kind: &'static str,
+    /// The location and metadata of the issue.
+    pub params: IssueParams<'a>,
+    /// The issue message.
+    pub message: M,
+}
+
+impl<M: fmt::Display> fmt::Display for Issue<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "##vso[task.logissue type={}{}]{}",
+            self.kind,
+            self.params,
+            Escaped {
+                value: &self.message,
+                property: false,
+            }
+        )
+    }
+}
+
+/// A command starting a collapsible group.
+///
+/// Displays as the full formatting command, including the trailing newline.
+pub(crate) struct AzureGroup<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for AzureGroup<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "##[group]{}", self.0)
+    }
+}
+
+/// The command ending a collapsible group, including the trailing newline.
+pub(crate) const AZURE_ENDGROUP: &str = "##[endgroup]\n";
+
+#[bon]
+impl AzureDevOps {
+    /// Creates a builder for a warning issue.
+    ///
+    /// Warnings are listed in the build summary, and can optionally be
+    /// associated with a specific file location.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The warning message to display.
+    /// * `file` - Optional file path for the issue.
+    /// * `line` - Optional line number (1-indexed).
+    /// * `col` - Optional column number (1-indexed).
+    /// * `code` - Optional code identifying the warning.
+    ///
+    /// # Returns
+    ///
+    /// A builder that can be used to set optional parameters and format the
+    /// warning.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::AzureDevOps;
+    ///
+    /// // Simple warning
+    /// let warning = AzureDevOps::warning("Deprecated function used").format();
+    ///
+    /// // Warning with file location
+    /// let warning = AzureDevOps::warning("This function will be removed")
+    ///     .file("src/lib.rs")
+    ///     .line(100)
+    ///     .col(5)
+    ///     .format();
+    /// ```
+    #[builder(finish_fn = format)]
+    pub fn warning(
+        #[builder(start_fn)] message: impl AsRef<str>,
+        file: Option<&str>,
+        line: Option<u32>,
+        col: Option<u32>,
+        code: Option<&str>,
+    ) -> String {
+        Issue {
+            kind: "warning",
+            params: IssueParams {
+                source_path: file,
+                line_number: line,
+                column_number: col,
+                code,
+            },
+            message: message.as_ref(),
+        }
+        .to_string()
+    }
+
+    /// Creates a builder for an error issue.
+    ///
+    /// Errors are listed in the build summary, and can optionally be
+    /// associated with a specific file location.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The error message to display.
+    /// * `file` - Optional file path for the issue.
+    /// * `line` - Optional line number (1-indexed).
+    /// * `col` - Optional column number (1-indexed).
+    /// * `code` - Optional code identifying the error.
+    ///
+    /// # Returns
+    ///
+    /// A builder that can be used to set optional parameters and format the
+    /// error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::AzureDevOps;
+    ///
+    /// // Simple error
+    /// let error = AzureDevOps::error("Build failed").format();
+    ///
+    /// // Error with file location
+    /// let error = AzureDevOps::error("Expected semicolon")
+    ///     .file("src/main.rs")
+    ///     .line(50)
+    ///     .col(10)
+    ///     .code("E0425")
+    ///     .format();
+    /// ```
+    #[builder(finish_fn = format)]
+    pub fn error(
+        #[builder(start_fn)] message: impl AsRef<str>,
+        file: Option<&str>,
+        line: Option<u32>,
+        col: Option<u32>,
+        code: Option<&str>,
+    ) -> String {
+        Issue {
+            kind: "error",
+            params: IssueParams {
+                source_path: file,
+                line_number: line,
+                column_number: col,
+                code,
+            },
+            message: message.as_ref(),
+        }
+        .to_string()
+    }
+
+    /// Creates a builder for a command completing the current task.
+    ///
+    /// This sets the result of the current task, which is shown in the
+    /// pipeline summary.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the task.
+    /// * `message` - Optional message describing the result.
+    ///
+    /// # Returns
+    ///
+    /// A builder that can be used to set the message and format the command.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{AzureDevOps, TaskResult};
+    ///
+    /// let complete = AzureDevOps::complete(TaskResult::Failed)
+    ///     .message("2 errors")
+    ///     .format();
+    /// assert_eq!(complete, "##vso[task.complete result=Failed;]2 errors\n");
+    /// ```
+    #[builder(finish_fn = format)]
+    pub fn complete(#[builder(start_fn)] result: TaskResult, message: Option<&str>) -> String {
+        format!(
+            "##vso[task.complete result={result};]{}\n",
+            Escaped {
+                value: message.unwrap_or_default(),
+                property: false,
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::ci::{AzureDevOps, Platform, TaskResult};
+
+    #[rstest]
+    fn debug() {
+        insta::assert_snapshot!(
+            AzureDevOps::debug("This is a debug message"),
+            @"##[debug]This is a debug message"
+        );
+    }
+
+    #[rstest]
+    fn warning_simple() {
+        insta::assert_snapshot!(
+            AzureDevOps::warning("Deprecated API").format(),
+            @"##vso[task.logissue type=warning]Deprecated API\n"
+        );
+    }
+
+    #[rstest]
+    fn error_with_params() {
+        let result = AzureDevOps::error("Unsupported syntax")
+            .file("src/main.rs")
+            .line(10)
+            .col(1)
+            .code("E0425")
+            .format();
+        insta::assert_snapshot!(
+            result,
+            @"##vso[task.logissue type=error;sourcepath=src/main.rs;linenumber=10;columnnumber=1;code=E0425]Unsupported syntax\n"
+        );
+    }
+
+    #[rstest]
+    fn escaping() {
+        let result = AzureDevOps::error("100% broken\r\nsee below")
+            .file("src/a;b].rs")
+            .format();
+        insta::assert_snapshot!(
+            result,
+            @"##vso[task.logissue type=error;sourcepath=src/a%3Bb%5D.rs]100%AZP25 broken%0D%0Asee below\n"
+        );
+    }
+
+    #[rstest]
+    fn group() {
+        insta::assert_snapshot!(AzureDevOps::group("Build Steps"), @"##[group]Build Steps");
+        insta::assert_snapshot!(AzureDevOps::endgroup(), @"##[endgroup]");
+    }
+
+    #[rstest]
+    #[case(TaskResult::Succeeded)]
+    #[case(TaskResult::SucceededWithIssues)]
+    #[case(TaskResult::Failed)]
+    fn complete(#[case] result: TaskResult) {
+        assert_eq!(
+            AzureDevOps::complete(result).format(),
+            format!("##vso[task.complete result={result};]\n")
+        );
+    }
+
+    #[rstest]
+    #[case("TF_BUILD")]
+    #[case("SYSTEM_TEAMFOUNDATIONCOLLECTIONURI")]
+    fn azure_from_env_present(#[case] var: &str) {
+        // SAFETY: Safe within a single-threaded test context
+        unsafe {
+            std::env::set_var(var, "True");
+        }
+        let result = AzureDevOps::from_env();
+        assert!(result.is_some());
+        // SAFETY: Safe within a single-threaded test context
+        unsafe {
+            std::env::remove_var(var);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Buildkite platform support.
+//!
+//! This module defines the Buildkite platform marker and implements
+//! formatting of CI messages for the Buildkite log, as well as annotations
+//! through the Buildkite agent.
+
+use bon::bon;
+use core::fmt;
+use std::{
+    io::{self, Write as _},
+    path::Path,
+    process::{Command, Stdio},
+};
+use tracing::debug;
+
+use crate::ci::{Plain, Platform};
+use crate::ci_message::Severity;
+
+/// Buildkite platform marker.
+///
+/// The Buildkite log is folded into sections, each of which starts with a
+/// header line and extends until the next header. Buildkite has no equivalent
+/// of inline annotations, so messages are otherwise logged as plain text.
+/// Failures instead expand the section in which they occur, and can be
+/// summarized in an annotation displayed above the log (see
+/// [`Buildkite::annotate`]).
+///
+/// For more information, see:
+/// <https://buildkite.com/docs/pipelines/configure/managing-log-output>.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Buildkite;
+
+impl Platform for Buildkite {
+    #[inline]
+    fn from_env() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        std::env::var("BUILDKITE")
+            .is_ok_and(|v| v == "true")
+            .then(|| {
+                debug!("Detected Buildkite environment");
+                Buildkite
+            })
+    }
+
+    /// Messages are logged as plain text, as Buildkite has no inline
+    /// annotations. Errors expand the section in which they are logged.
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        let line = Plain::annotate(severity, message, location, title);
+        match severity {
+            Severity::Error => format!("{}{line}", Self::expand_previous()),
+            Severity::Debug | Severity::Notice | Severity::Warning => line,
+        }
+    }
+
+    /// Formats the header of a collapsed log section.
+    ///
+    /// The section extends until the next section header.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the section.
+    ///
+    /// # Returns
+    ///
+    /// A formatted section header, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{Buildkite, Platform};
+    ///
+    /// println!("{}", Buildkite::group("Test Results"));
+    /// println!("Running tests...");
+    /// ```
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        format!("--- {}", title.as_ref())
+    }
+
+    /// Sections extend until the next section header, so this is empty.
+    #[inline]
+    fn endgroup() -> String {
+        String::new()
+    }
+
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        message.as_ref().to_owned()
+    }
+}
+
+impl fmt::Display for Buildkite {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Buildkite")
+    }
+}
+
+/// The style of an annotation, which determines its colour and icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AnnotationStyle {
+    /// A successful outcome.
+    Success,
+    /// Informational content.
+    Info,
+    /// A warning.
+    Warning,
+    /// An error.
+    Error,
+}
+
+impl fmt::Display for AnnotationStyle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Success => write!(f, "success"),
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A Markdown code block containing the given text verbatim.
+///
+/// The fence is chosen to be longer than any run of backticks within the
+/// text, so that the text cannot terminate the block early.
+pub(crate) struct CodeBlock<'a>(pub &'a str);
+
+impl fmt::Display for CodeBlock<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let longest = self
+            .0
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default();
+        let fence = "`".repeat(longest.saturating_add(1).max(3));
+        writeln!(f, "{fence}text")?;
+        writeln!(f, "{}", self.0.trim_end_matches('\n'))?;
+        writeln!(f, "{fence}")
+    }
+}
+
+/// The command used to create an annotation.
+fn annotate_command(
+    style: Option<AnnotationStyle>,
+    context: Option<&str>,
+    append: bool,
+) -> Command {
+    let mut command = Command::new("buildkite-agent");
+    command.arg("annotate");
+    if let Some(value) = style {
+        command.arg("--style").arg(value.to_string());
+    }
+    if let Some(value) = context {
+        command.arg("--context").arg(value);
+    }
+    if append {
+        command.arg("--append");
+    }
+    command
+}
+
+/// The command used to upload an artifact.
+fn upload_command(path: &Path) -> Command {
+    let mut command = Command::new("buildkite-agent");
+    command.arg("artifact").arg("upload").arg(path);
+    command
+}
+
+#[allow(
+    clippy::allow_attributes,
+    clippy::same_name_method,
+    reason = "Annotations above the log differ from those of the Platform trait, and the \
+              lint is not reported for every target through the #[bon] expansion"
+)]
+#[bon]
+impl Buildkite {
+    /// Formats the header of an expanded log section.
+    ///
+    /// The section extends until the next section header.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the section.
+    ///
+    /// # Returns
+    ///
+    /// A formatted section header, suitable for printing to stdout. The
+    /// string includes a trailing newline.
+    #[must_use]
+    #[inline]
+    pub fn expanded_group(title: impl AsRef<str>) -> String {
+        format!("+++ {}\n", title.as_ref())
+    }
+
+    /// Formats the command expanding the current log section.
+    ///
+    /// This is used to reveal a failure within a section which was started
+    /// collapsed.
+    ///
+    /// # Returns
+    ///
+    /// The formatted command, suitable for printing to stdout. The string
+    /// includes a trailing newline.
+    #[must_use]
+    #[inline]
+    pub fn expand_previous() -> String {
+        "^^^ +++\n".to_owned()
+    }
+
+    /// Creates a builder for an annotation of the current build.
+    ///
+    /// Annotations are rendered as Markdown above the build's log. They are
+    /// created by running `buildkite-agent annotate`, which must be available
+    /// on the `PATH`.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The Markdown content of the annotation.
+    /// * `style` - Optional style of the annotation.
+    /// * `context` - Optional context identifying the annotation. Creating
+    ///   another annotation with the same context replaces it.
+    /// * `append` - Whether to append the body to the existing annotation
+    ///   with the same context, rather than replacing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the agent cannot be run, or if it fails to create
+    /// the annotation.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cifmt::ci::{AnnotationStyle, Buildkite};
+    ///
+    /// Buildkite::annotate("**2** tests failed")
+    ///     .style(AnnotationStyle::Error)
+    ///     .context("tests")
+    ///     .run()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[builder(finish_fn = run)]
+    pub fn annotate(
+        #[builder(start_fn)] body: &str,
+        style: Option<AnnotationStyle>,
+        context: Option<&str>,
+        #[builder(default)] append: bool,
+    ) -> io::Result<()> {
+        let mut child = annotate_command(style, context, append)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(body.as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "buildkite-agent annotate failed: {status}"
+            )))
+        }
+    }
+
+    /// Uploads a file as an artifact of the current job.
+    ///
+    /// The artifact is uploaded by running `buildkite-agent artifact upload`,
+    /// which must be available on the `PATH`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to upload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the agent cannot be run, or if it fails to upload
+    /// the artifact.
+    #[inline]
+    pub fn upload_artifact(path: &Path) -> io::Result<()> {
+        let status = upload_command(path).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "buildkite-agent artifact upload failed: {status}"
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{AnnotationStyle, CodeBlock, annotate_command, upload_command};
+    use crate::ci::{Buildkite, Platform};
+
+    #[rstest]
+    fn group() {
+        insta::assert_snapshot!(Buildkite::group("Build Steps"), @"--- Build Steps");
+        insta::assert_snapshot!(Buildkite::expanded_group("Failures"), @"+++ Failures\n");
+        insta::assert_snapshot!(Buildkite::expand_previous(), @"^^^ +++\n");
+    }
+
+    #[rstest]
+    #[case(None, None, false, &["annotate"])]
+    #[case(
+        Some(AnnotationStyle::Error),
+        Some("cifmt"),
+        true,
+        &["annotate", "--style", "error", "--context", "cifmt", "--append"]
+    )]
+    fn annotate_args(
+        #[case] style: Option<AnnotationStyle>,
+        #[case] context: Option<&str>,
+        #[case] append: bool,
+        #[case] expected: &[&str],
+    ) {
+        let command = annotate_command(style, context, append);
+        assert_eq!(command.get_program(), "buildkite-agent");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), expected);
+    }
+
+    #[rstest]
+    fn upload_args() {
+        let command = upload_command(Path::new("reports/cifmt-report.md"));
+        assert_eq!(command.get_program(), "buildkite-agent");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["artifact", "upload", "reports/cifmt-report.md"]
+        );
+    }
+
+    #[rstest]
+    #[case::plain("failed\n", "```text\nfailed\n```\n")]
+    #[case::backticks("a ``` b", "````text\na ``` b\n````\n")]
+    fn code_block(#[case] content: &str, #[case] expected: &str) {
+        assert_eq!(CodeBlock(content).to_string(), expected);
+    }
+
+    #[rstest]
+    #[case(Some("true"), true)]
+    #[case(Some("false"), false)]
+    #[case(None, false)]
+    fn buildkite_from_env(#[case] value: Option<&str>, #[case] expected: bool) {
+        match value {
+            // SAFETY: Safe within a single-threaded test context
+            Some(v) => unsafe { std::env::set_var("BUILDKITE", v) },
+            // SAFETY: Safe within a single-threaded test context
+            None => unsafe { std::env::remove_var("BUILDKITE") },
+        }
+        assert_eq!(Buildkite::from_env().is_some(), expected);
+        // SAFETY: Safe within a single-threaded test context
+        unsafe {
+            std::env::remove_var("BUILDKITE");
+        }
+    }
+}
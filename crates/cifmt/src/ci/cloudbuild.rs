@@ -0,0 +1,187 @@
+//! Google Cloud Build platform support.
+//!
+//! This module defines the Google Cloud Build platform marker and implements
+//! formatting of CI messages for Cloud Logging.
+
+use core::fmt;
+
+use serde::Serialize;
+use tracing::debug;
+
+use crate::ci::{NormalizedPath, Platform, titled};
+use crate::ci_message::Severity;
+
+/// Google Cloud Build platform marker.
+///
+/// The output of each build step is sent to Cloud Logging, which parses
+/// lines containing a JSON object as [structured
+/// logs](https://cloud.google.com/logging/docs/structured-logging). Messages
+/// are therefore logged as single-line JSON objects (see
+/// [`CloudBuild::entry`]), such that their severity and source location are
+/// shown by the Logs Explorer, and errors can be filtered for.
+///
+/// For more information, see:
+/// <https://cloud.google.com/build/docs/view-build-results>.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct CloudBuild;
+
+impl Platform for CloudBuild {
+    #[inline]
+    fn from_env() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        // `BUILDER_OUTPUT` is set for every build step. `PROJECT_ID` is also
+        // available, but is commonly set in other Google Cloud environments.
+        std::env::var("BUILDER_OUTPUT")
+            .is_ok_and(|v| !v.is_empty())
+            .then(|| {
+                debug!("Detected Google Cloud Build environment");
+                CloudBuild
+            })
+    }
+
+    /// Messages are logged as [entries](CloudBuild::entry), with the title
+    /// preceding the message.
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        Self::entry(
+            severity,
+            titled(message.as_ref(), title),
+            location.map(|(file, line, _)| (file, line)),
+        )
+    }
+
+    /// Groups are logged as a notice of their title, as Cloud Logging has no
+    /// grouping.
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        Self::entry(Severity::Notice, title, None)
+    }
+
+    #[inline]
+    fn endgroup() -> String {
+        String::new()
+    }
+
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        Self::entry(Severity::Debug, message, None)
+    }
+}
+
+impl fmt::Display for CloudBuild {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Google Cloud Build")
+    }
+}
+
+impl CloudBuild {
+    /// Formats a message as a structured log entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `severity` - The severity of the message.
+    /// * `message` - The message, which may span several lines.
+    /// * `location` - The file and line to which the message relates, if any.
+    ///   The file is relative to the root of the checkout where possible.
+    ///
+    /// # Returns
+    ///
+    /// The entry, as a JSON object on a single line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::CloudBuild;
+    /// use cifmt::ci_message::Severity;
+    ///
+    /// assert_eq!(
+    ///     CloudBuild::entry(Severity::Error, "error: oops", Some(("src/lib.rs", 3))),
+    ///     r#"{"severity":"ERROR","message":"error: oops","logging.googleapis.com/sourceLocation":{"file":"src/lib.rs","line":"3"}}"#,
+    /// );
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn entry(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32)>,
+    ) -> String {
+        let entry = LogEntry {
+            severity: match severity {
+                Severity::Debug => "DEBUG",
+                Severity::Notice => "NOTICE",
+                Severity::Warning => "WARNING",
+                Severity::Error => "ERROR",
+            },
+            message: message.as_ref().trim_matches('\n'),
+            source_location: location.map(|(file, line)| SourceLocation {
+                file: NormalizedPath(file).to_string(),
+                // Cloud Logging expects the line as a string.
+                line: line.to_string(),
+            }),
+        };
+        serde_json::to_string(&entry).unwrap_or_else(|_| entry.message.to_owned())
+    }
+}
+
+/// A structured Cloud Logging entry.
+#[derive(Serialize)]
+struct LogEntry<'a> {
+    /// The severity of the entry.
+    severity: &'a str,
+    /// The text of the entry.
+    message: &'a str,
+    /// The location in the source to which the entry relates.
+    #[serde(
+        rename = "logging.googleapis.com/sourceLocation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    source_location: Option<SourceLocation>,
+}
+
+/// The source location of a structured Cloud Logging entry.
+#[derive(Serialize)]
+struct SourceLocation {
+    /// The path of the file.
+    file: String,
+    /// The line within the file.
+    line: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::CloudBuild;
+    use crate::ci_message::Severity;
+
+    #[rstest]
+    #[case::debug(Severity::Debug, "DEBUG")]
+    #[case::notice(Severity::Notice, "NOTICE")]
+    #[case::warning(Severity::Warning, "WARNING")]
+    #[case::error(Severity::Error, "ERROR")]
+    fn entry_severity(#[case] severity: Severity, #[case] expected: &str) {
+        assert_eq!(
+            CloudBuild::entry(severity, "message", None),
+            format!(r#"{{"severity":"{expected}","message":"message"}}"#)
+        );
+    }
+
+    #[test]
+    fn entry_multiline() {
+        assert_eq!(
+            CloudBuild::entry(Severity::Error, "error: \"a\"\n  --> b\n", None),
+            r#"{"severity":"ERROR","message":"error: \"a\"\n  --> b"}"#
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! AWS CodeBuild platform support.
+//!
+//! This module defines the AWS CodeBuild platform marker and implements
+//! formatting of CI messages for the CodeBuild log.
+
+use core::fmt;
+
+use tracing::debug;
+
+use crate::ci::{Plain, Platform};
+use crate::ci_message::Severity;
+
+/// AWS CodeBuild platform marker.
+///
+/// The CodeBuild log is plain text, without any form of grouping or inline
+/// annotations, and is typically read through CloudWatch Logs. Messages are
+/// therefore logged as plain text. A [`Pipeline`](crate::pipeline::Pipeline)
+/// prefixes each line it writes with the [`Phase`] of the tool's run (see
+/// [`Tool::phase`](crate::tool::Tool::phase)), mirroring the `[Container]`
+/// prefix of the CodeBuild agent, so that the log can be filtered by phase.
+///
+/// Test results are best reported through CodeBuild's test reports, which
+/// are read from a JUnit report (see
+/// [`Pipeline::with_junit_report`](crate::pipeline::Pipeline::with_junit_report)).
+///
+/// For more information, see:
+/// <https://docs.aws.amazon.com/codebuild/latest/userguide/test-reporting.html>.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct CodeBuild;
+
+impl Platform for CodeBuild {
+    #[inline]
+    fn from_env() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        std::env::var("CODEBUILD_BUILD_ID")
+            .is_ok_and(|v| !v.is_empty())
+            .then(|| {
+                debug!("Detected AWS CodeBuild environment");
+                CodeBuild
+            })
+    }
+
+    /// Messages are logged as plain text, as CodeBuild has no annotations.
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        Plain::annotate(severity, message, location, title)
+    }
+
+    /// Groups are logged as a header line, as CodeBuild has no grouping.
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        Plain::group(title)
+    }
+
+    #[inline]
+    fn endgroup() -> String {
+        String::new()
+    }
+
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        Plain::debug(message)
+    }
+}
+
+impl fmt::Display for CodeBuild {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AWS CodeBuild")
+    }
+}
+
+/// The phase of a CI job in which a tool runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Phase {
+    /// Compilation of the code.
+    Build,
+    /// Execution of the tests.
+    Test,
+}
+
+impl fmt::Display for Phase {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Build => write!(f, "BUILD"),
+            Self::Test => write!(f, "TEST"),
+        }
+    }
+}
+
+impl CodeBuild {
+    /// Prefixes each line of a message with the phase in which it occurred.
+    ///
+    /// Blank lines are left as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The phase in which the message occurred.
+    /// * `message` - The message, which may span several lines.
+    ///
+    /// # Returns
+    ///
+    /// The prefixed message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{CodeBuild, Phase};
+    ///
+    /// assert_eq!(
+    ///     CodeBuild::prefixed(Phase::Test, "TEST FAILED: a\n\nreason\n"),
+    ///     "[TEST] TEST FAILED: a\n\n[TEST] reason\n",
+    /// );
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn prefixed(phase: Phase, message: impl AsRef<str>) -> String {
+        message
+            .as_ref()
+            .split_inclusive('\n')
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.to_owned()
+                } else {
+                    format!("[{phase}] {line}")
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{CodeBuild, Phase};
+
+    #[rstest]
+    #[case::single("Build failed", "[BUILD] Build failed")]
+    #[case::trailing_newline("Build failed\n", "[BUILD] Build failed\n")]
+    #[case::multiple("error: a\nhelp: b", "[BUILD] error: a\n[BUILD] help: b")]
+    #[case::blank("error: a\n\nhelp: b\n", "[BUILD] error: a\n\n[BUILD] help: b\n")]
+    #[case::empty("", "")]
+    fn prefixed(#[case] message: &str, #[case] expected: &str) {
+        assert_eq!(CodeBuild::prefixed(Phase::Build, message), expected);
+    }
+}
@@ -1,13 +1,25 @@
 //! GitHub CI platform support.
 //!
 //! This module defines the GitHub platform marker and implements formatting of
-//! CI messages for GitHub Actions.
+//! CI messages for GitHub Actions. The data and property values of workflow
+//! commands are escaped as specified by the Actions runner (see
+//! [`GitHub::escape_data`] and [`GitHub::escape_property`]), so that messages
+//! spanning several lines or containing colons are not cut short.
 
 use bon::bon;
 use core::fmt;
+use std::borrow::Cow;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::debug;
 
-use crate::ci::Platform;
+use crate::catalog::Title;
+use crate::ci::{NormalizedPath, Platform, titled};
+use crate::ci_message::Severity;
 
 /// GitHub Action platform marker.
 ///
@@ -31,6 +43,150 @@ impl Platform for GitHub {
             GitHub
         })
     }
+
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        let command = match severity {
+            Severity::Debug => {
+                return <Self as Platform>::debug(titled(message.as_ref(), title));
+            }
+            Severity::Notice => "notice",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let mut annotation = Annotation {
+            command,
+            params: AnnotationParams {
+                file: location.map(|(file, _, _)| file),
+                line: location.map(|(_, line, _)| line),
+                col: location.and_then(|(_, _, column)| column),
+                title,
+                ..AnnotationParams::default()
+            },
+            message: message.as_ref(),
+        }
+        .to_string();
+        // The command is written as a line, whose newline is left to the caller.
+        annotation.pop();
+        annotation
+    }
+
+    /// The annotation is highlighted over the lines and columns which it
+    /// spans.
+    #[inline]
+    fn annotate_span(
+        severity: Severity,
+        message: impl AsRef<str>,
+        file: &str,
+        span: ((u32, u32), (u32, u32)),
+        title: Option<&str>,
+    ) -> String {
+        let ((line, column), (end_line, end_column)) = span;
+        let command = match severity {
+            Severity::Debug => {
+                return Self::annotate(severity, message, Some((file, line, Some(column))), title);
+            }
+            Severity::Notice => "notice",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let mut annotation = Annotation {
+            command,
+            params: AnnotationParams {
+                file: Some(file),
+                line: Some(line),
+                col: Some(column),
+                end_line: Some(end_line),
+                end_column: Some(end_column),
+                title,
+            },
+            message: message.as_ref(),
+        }
+        .to_string();
+        // The command is written as a line, whose newline is left to the caller.
+        annotation.pop();
+        annotation
+    }
+
+    /// Starts a collapsible group in the workflow log.
+    ///
+    /// All output between this command and `endgroup()` will be nested inside
+    /// an expandable group in the logs.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the group to display.
+    ///
+    /// # Returns
+    ///
+    /// A formatted group command string, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{GitHub, Platform};
+    ///
+    /// println!("{}", GitHub::group("Build Steps"));
+    /// println!("Running build...");
+    /// println!("Compiling...");
+    /// println!("{}", GitHub::endgroup());
+    /// ```
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        format!("::group::{}", Data(title.as_ref()))
+    }
+
+    /// Ends a collapsible group in the workflow log.
+    ///
+    /// # Returns
+    ///
+    /// A formatted endgroup command string, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{GitHub, Platform};
+    ///
+    /// println!("{}", GitHub::group("Test Results"));
+    /// println!("Running tests...");
+    /// println!("{}", GitHub::endgroup());
+    /// ```
+    #[inline]
+    fn endgroup() -> String {
+        "::endgroup::".to_owned()
+    }
+
+    /// Formats a debug message for GitHub Actions.
+    ///
+    /// These messages are only visible when the workflow is run in debug mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The debug message to format.
+    ///
+    /// # Returns
+    ///
+    /// A formatted debug message string, suitable for printing as a
+    /// line to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::{GitHub, Platform};
+    ///
+    /// let debug_message = GitHub::debug("This is a debug message.");
+    /// ```
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        format!("::debug::{}", Data(message.as_ref()))
+    }
 }
 
 impl fmt::Display for GitHub {
@@ -44,19 +200,20 @@ impl fmt::Display for GitHub {
 ///
 /// Used to specify optional location and metadata for annotations.
 #[derive(Debug, Clone, Default)]
-struct AnnotationParams<'a> {
-    /// The file path to annotate.
-    file: Option<&'a str>,
+pub(crate) struct AnnotationParams<'a> {
+    /// The file path to annotate, which is normalized when displayed (see
+    /// [`NormalizedPath`]).
+    pub file: Option<&'a str>,
     /// The starting line number (1-indexed).
-    line: Option<u32>,
+    pub line: Option<u32>,
     /// The starting column number (1-indexed).
-    col: Option<u32>,
+    pub col: Option<u32>,
     /// The ending line number.
-    end_line: Option<u32>,
+    pub end_line: Option<u32>,
     /// The ending column number.
-    end_column: Option<u32>,
+    pub end_column: Option<u32>,
     /// Custom title for the annotation.
-    title: Option<&'a str>,
+    pub title: Option<&'a str>,
 }
 
 impl fmt::Display for AnnotationParams<'_> {
@@ -79,41 +236,535 @@ impl fmt::Display for AnnotationParams<'_> {
             };
         }
 
-        write_param!("file={}", self.file);
+        write_param!(
+            "file={}",
+            self.file.map(|file| Property(NormalizedPath(file)))
+        );
         write_param!("line={}", self.line);
         write_param!("col={}", self.col);
         write_param!("endLine={}", self.end_line);
         write_param!("endColumn={}", self.end_column);
-        write_param!("title={}", self.title);
+        write_param!(
+            "title={}",
+            self.title
+                .map(|title| Property(truncate_chars(title, GitHub::MAX_TITLE_LENGTH)))
+        );
         Ok(())
     }
 }
 
-#[bon]
-impl GitHub {
-    /// Formats a debug message for GitHub Actions.
+/// An annotation command (error, warning, notice).
+///
+/// Displays as the full workflow command, including the trailing newline.
+/// This allows annotations to be written directly into an existing buffer.
+pub(crate) struct Annotation<'a, M> {
+    /// The command, one of `error`, `warning` or `notice`.
+    pub command: &'static str,
+    /// The location and metadata of the annotation.
+    pub params: AnnotationParams<'a>,
+    /// The annotation message.
+    pub message: M,
+}
+
+impl<M: fmt::Display> fmt::Display for Annotation<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = self.message.to_string();
+        writeln!(
+            f,
+            "::{} {}::{}",
+            self.command,
+            self.params,
+            Data(truncate_chars(&message, GitHub::MAX_MESSAGE_LENGTH))
+        )
+    }
+}
+
+/// Data of a workflow command (e.g., the message of an annotation), which is
+/// escaped when displayed.
+///
+/// Percent signs and line breaks are percent-encoded, so that the data
+/// cannot end the command early, as specified by the Actions runner.
+pub(crate) struct Data<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Data<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Write::write_fmt(
+            &mut Escaper { f, property: false },
+            format_args!("{}", self.0),
+        )
+    }
+}
+
+/// A property value of a workflow command (e.g., the title of an
+/// annotation), which is escaped when displayed.
+///
+/// In addition to the escaping of [`Data`], colons and commas are
+/// percent-encoded, as they would otherwise end the property.
+pub(crate) struct Property<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Property<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Write::write_fmt(
+            &mut Escaper { f, property: true },
+            format_args!("{}", self.0),
+        )
+    }
+}
+
+/// Writer percent-encoding the characters which are special within
+/// workflow commands.
+struct Escaper<'a, 'b> {
+    /// The formatter to which the escaped text is written.
+    f: &'a mut fmt::Formatter<'b>,
+    /// Whether the text is a property value, whose colons and commas are
+    /// also escaped.
+    property: bool,
+}
+
+impl fmt::Write for Escaper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(index) = rest.find(|c: char| {
+            matches!(c, '%' | '\r' | '\n') || (self.property && matches!(c, ':' | ','))
+        }) {
+            let (plain, special) = rest.split_at(index);
+            self.f.write_str(plain)?;
+            let mut chars = special.chars();
+            self.f.write_str(match chars.next() {
+                Some('%') => "%25",
+                Some('\r') => "%0D",
+                Some('\n') => "%0A",
+                Some(':') => "%3A",
+                Some(',') => "%2C",
+                Some(_) | None => "",
+            })?;
+            rest = chars.as_str();
+        }
+        self.f.write_str(rest)
+    }
+}
+
+/// Reverse the escaping of [`Data`] and [`Property`].
+///
+/// Unrecognized escape sequences are kept as is.
+fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('%') {
+        return Cow::Borrowed(text);
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('%') {
+        let (plain, escaped) = rest.split_at(index);
+        unescaped.push_str(plain);
+        let decoded = escaped.get(..3).and_then(|sequence| match sequence {
+            "%25" => Some('%'),
+            "%0D" => Some('\r'),
+            "%0A" => Some('\n'),
+            "%3A" => Some(':'),
+            "%2C" => Some(','),
+            _ => None,
+        });
+        if let Some(c) = decoded {
+            unescaped.push(c);
+            rest = escaped.get(3..).unwrap_or_default();
+        } else {
+            unescaped.push('%');
+            rest = escaped.get(1..).unwrap_or_default();
+        }
+    }
+    unescaped.push_str(rest);
+    Cow::Owned(unescaped)
+}
+
+/// Truncate text to the given number of characters.
+///
+/// Truncated text ends with an ellipsis, which counts towards the limit.
+fn truncate_chars(text: &str, max: usize) -> Cow<'_, str> {
+    if text.chars().nth(max).is_none() {
+        return Cow::Borrowed(text);
+    }
+    let end = text
+        .char_indices()
+        .nth(max.saturating_sub(1))
+        .map_or(text.len(), |(index, _)| index);
+    Cow::Owned(format!("{}…", text.get(..end).unwrap_or_default()))
+}
+
+/// The annotation commands, along with the label used when an annotation is
+/// written to the log as plain text instead.
+const ANNOTATION_COMMANDS: [(&str, &str); 3] = [
+    ("::error ", "Error"),
+    ("::warning ", "Warning"),
+    ("::notice ", "Notice"),
+];
+
+/// Limits the number of annotations of each type created for a single step.
+///
+/// GitHub displays only [`GitHub::MAX_ANNOTATIONS_PER_STEP`] annotations of
+/// each type for a step and discards the rest. The limiter instead counts the
+/// messages annotated, by their severity, and rewrites the annotations of the
+/// messages beyond the limit as plain log lines, so that they are not
+/// mistaken for annotations, and keeps count of them. One annotation of each
+/// type is held back, so that the [summary](AnnotationLimiter::summary) of
+/// the omitted annotations is always displayed.
+///
+/// The total number of annotations can be limited further (see
+/// [`AnnotationLimiter::with_limit`]), in which case errors take priority:
+/// warnings and notices only use the annotations which are not needed for
+/// errors, so that errors are never crowded out by less severe annotations.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AnnotationLimiter {
+    /// Number of messages annotated, by index into [`ANNOTATION_COMMANDS`] of
+    /// the type given by their severity.
+    created: [usize; 3],
+    /// The maximum number of annotations of all types, if limited further
+    /// than by the limit on each type.
+    limit: Option<usize>,
+    /// Number of messages whose annotations were written as plain log lines
+    /// instead.
+    omitted: usize,
+    /// The annotations written as plain log lines instead, if collected.
+    collected: Option<Vec<WorkflowAnnotation>>,
+}
+
+impl AnnotationLimiter {
+    /// Limit the total number of annotations of all types, including the
+    /// summary of the omitted annotations, which is not given if the limit is
+    /// zero.
     ///
-    /// These messages are only visible when the workflow is run in debug mode.
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of annotations, or `None` to only limit
+    ///   the number of each type.
+    pub(crate) fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Collect the annotations beyond the limit, so that they can be created
+    /// by other means (see [`AnnotationLimiter::collected`]).
+    pub(crate) fn collecting(mut self) -> Self {
+        self.collected = Some(Vec::new());
+        self
+    }
+
+    /// The annotations beyond the limit, if collected.
+    pub(crate) fn collected(&self) -> &[WorkflowAnnotation] {
+        self.collected.as_deref().unwrap_or_default()
+    }
+
+    /// Whether another annotation of the given type can be created.
     ///
     /// # Arguments
     ///
-    /// * `message` - The debug message to format.
+    /// * `index` - The index of the annotation's type into
+    ///   [`ANNOTATION_COMMANDS`].
+    fn allows(&self, index: usize) -> bool {
+        let per_type = GitHub::MAX_ANNOTATIONS_PER_STEP.saturating_sub(1);
+        let [errors, ..] = self.created;
+        if self
+            .created
+            .get(index)
+            .is_none_or(|&created| created >= per_type)
+        {
+            return false;
+        }
+        let Some(limit) = self.limit else {
+            return true;
+        };
+        let created: usize = self.created.iter().sum();
+        let reserved = if index == 0 {
+            0
+        } else {
+            per_type.saturating_sub(errors)
+        };
+        created.saturating_add(reserved) < limit.saturating_sub(1)
+    }
+
+    /// Apply the limit to the output formatted for a message.
+    ///
+    /// Each message counts as a single annotation of the type given by its
+    /// severity, however many annotation commands its output holds, so that
+    /// the annotations of a message are either all created or all written to
+    /// the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `severity` - The severity of the message.
+    /// * `formatted` - The output formatted for GitHub Actions.
     ///
     /// # Returns
     ///
-    /// A formatted debug message string, suitable for printing to stdout. The
-    /// string includes a trailing newline.
+    /// The output with its annotations rewritten, or `None` if the output is
+    /// unchanged.
+    pub(crate) fn apply(&mut self, severity: Severity, formatted: &str) -> Option<String> {
+        if !formatted
+            .split_inclusive('\n')
+            .any(|line| annotation_command(line).is_some())
+        {
+            return None;
+        }
+        let index = match severity {
+            Severity::Error => 0,
+            Severity::Warning => 1,
+            Severity::Notice | Severity::Debug => 2,
+        };
+        if self.allows(index) {
+            if let Some(created) = self.created.get_mut(index) {
+                *created = created.saturating_add(1);
+            }
+            return None;
+        }
+
+        self.omitted = self.omitted.saturating_add(1);
+        let mut rewritten = String::with_capacity(formatted.len());
+        for line in formatted.split_inclusive('\n') {
+            match annotation_command(line) {
+                Some((command_index, rest, label)) => {
+                    if let Some(collected) = &mut self.collected {
+                        collected.push(WorkflowAnnotation::new(command_index, rest));
+                    }
+                    rewritten.push_str(&plain_annotation(label, rest));
+                }
+                None => rewritten.push_str(line),
+            }
+        }
+        Some(rewritten)
+    }
+
+    /// A warning summarizing the omitted annotations, if any.
     ///
-    /// # Example
+    /// No warning is given if the limit allows no annotation at all.
     ///
-    /// ```
-    /// use cifmt::ci::GitHub;
+    /// # Arguments
     ///
-    /// let debug_message = GitHub::debug("This is a debug message.");
-    /// ```
+    /// * `job_summary` - Whether a job summary is written, to which the
+    ///   warning refers alongside the log.
+    pub(crate) fn summary(&self, job_summary: bool) -> Option<String> {
+        (self.omitted > 0 && self.limit != Some(0)).then(|| {
+            GitHub::warning(format!(
+                "{} additional issue(s) not shown as annotations, see the log{}",
+                self.omitted,
+                if job_summary {
+                    " and the job summary"
+                } else {
+                    ""
+                }
+            ))
+            .title(Title::AnnotationsOmitted.text())
+            .format()
+        })
+    }
+}
+
+/// The annotation command on a line of output, if any.
+///
+/// # Returns
+///
+/// The index of the annotation's type into [`ANNOTATION_COMMANDS`], the
+/// command following the command name, and the label of the type.
+fn annotation_command(line: &str) -> Option<(usize, &str, &'static str)> {
+    ANNOTATION_COMMANDS
+        .iter()
+        .enumerate()
+        .find_map(|(index, &(prefix, label))| Some((index, line.strip_prefix(prefix)?, label)))
+}
+
+/// Rewrite an annotation as a plain log line.
+///
+/// The parameters and message are separated in the same way as by GitHub.
+///
+/// # Arguments
+///
+/// * `label` - The label of the annotation's type.
+/// * `command` - The annotation command, following the command name.
+fn plain_annotation(label: &str, command: &str) -> String {
+    let (params, message) = split_command(command);
+    let param = |name: &str| command_param(params, name);
+
+    let mut line = format!("{label}: ");
+    if let Some(file) = param("file") {
+        line.push_str(&unescape(file));
+        for position in [param("line"), param("col")].into_iter().flatten() {
+            line.push(':');
+            line.push_str(position);
+        }
+        line.push_str(": ");
+    }
+    if let Some(title) = param("title") {
+        line.push_str(&unescape(title));
+        line.push_str(": ");
+    }
+    line.push_str(&unescape(message));
+    line.push('\n');
+    line
+}
+
+/// Rewrite a debug message as a plain log line, which is shown whether or
+/// not debug logging is enabled.
+///
+/// # Arguments
+///
+/// * `message` - The debug command, following the command name.
+pub(crate) fn plain_debug(message: &str) -> String {
+    let mut line = format!("Debug: {}", unescape(message.trim_end_matches('\n')));
+    line.push('\n');
+    line
+}
+
+/// Split an annotation command into its parameters and message.
+///
+/// The parameters and message are separated in the same way as by GitHub, and
+/// are returned as escaped.
+///
+/// # Arguments
+///
+/// * `command` - The annotation command, following the command name.
+fn split_command(command: &str) -> (&str, &str) {
+    let trimmed = command.trim_end_matches('\n');
+    trimmed.split_once("::").unwrap_or((trimmed, ""))
+}
+
+/// The value of a parameter of an annotation command, as escaped.
+///
+/// # Arguments
+///
+/// * `params` - The parameters of the command (see [`split_command`]).
+/// * `name` - The name of the parameter.
+fn command_param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params
+        .split(',')
+        .find_map(|param| param.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// An annotation workflow command, as formatted for GitHub Actions.
+///
+/// This is used to create the annotations elsewhere (e.g., those omitted by
+/// the [`AnnotationLimiter`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WorkflowAnnotation {
+    /// The index of the annotation's type into [`ANNOTATION_COMMANDS`].
+    index: usize,
+    /// The annotation command, following the command name.
+    command: String,
+}
+
+impl WorkflowAnnotation {
+    /// Record an annotation command.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the annotation's type into
+    ///   [`ANNOTATION_COMMANDS`].
+    /// * `command` - The annotation command, following the command name.
+    fn new(index: usize, command: &str) -> Self {
+        Self {
+            index,
+            command: command.to_owned(),
+        }
+    }
+
+    /// Parse a line of output formatted for GitHub Actions.
+    ///
+    /// # Returns
+    ///
+    /// The annotation, or `None` if the line is not an annotation command.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        ANNOTATION_COMMANDS
+            .iter()
+            .enumerate()
+            .find_map(|(index, &(prefix, _))| Some(Self::new(index, line.strip_prefix(prefix)?)))
+    }
+
+    /// The severity of the annotation.
+    pub(crate) fn severity(&self) -> Severity {
+        match self.index {
+            0 => Severity::Error,
+            1 => Severity::Warning,
+            _ => Severity::Notice,
+        }
+    }
+
+    /// The value of a parameter of the annotation (e.g., `file`), if given.
+    pub(crate) fn param(&self, name: &str) -> Option<Cow<'_, str>> {
+        let (params, _) = split_command(&self.command);
+        command_param(params, name).map(unescape)
+    }
+
+    /// The message of the annotation.
+    pub(crate) fn message(&self) -> Cow<'_, str> {
+        let (_, message) = split_command(&self.command);
+        unescape(message)
+    }
+}
+
+/// A command starting a collapsible group.
+///
+/// Displays as the full workflow command, including the trailing newline.
+pub(crate) struct Group<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Group<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "::group::{}", Data(&self.0))
+    }
+}
+
+/// The command ending a collapsible group, including the trailing newline.
+pub(crate) const ENDGROUP: &str = "::endgroup::\n";
+
+#[bon]
+impl GitHub {
+    /// Maximum length of an annotation message, in characters.
+    ///
+    /// Longer messages are truncated.
+    pub const MAX_MESSAGE_LENGTH: usize = 4096;
+
+    /// Maximum length of an annotation title, in characters.
+    ///
+    /// Longer titles are truncated.
+    pub const MAX_TITLE_LENGTH: usize = 255;
+
+    /// Maximum number of annotations of each type (error, warning and notice)
+    /// displayed for a single step.
+    pub const MAX_ANNOTATIONS_PER_STEP: usize = 10;
+
+    /// Environment variable holding the path to the job summary of the
+    /// current step.
+    pub const STEP_SUMMARY_ENV: &str = "GITHUB_STEP_SUMMARY";
+
+    /// Maximum size of the job summary of a single step, in bytes.
+    ///
+    /// Larger summaries are not displayed.
+    pub const MAX_STEP_SUMMARY_SIZE: usize = 1024 * 1024;
+
+    /// Environment variable holding the path to the file setting the outputs
+    /// of the current step.
+    pub const OUTPUT_ENV: &str = "GITHUB_OUTPUT";
+
+    /// Environment variable holding the path to the file setting environment
+    /// variables for the following steps of the job.
+    pub const ENV_FILE_ENV: &str = "GITHUB_ENV";
+
+    /// Environment variable holding the path to the file adding directories
+    /// to the `PATH` of the following steps of the job.
+    pub const PATH_ENV: &str = "GITHUB_PATH";
+
+    /// Environment variables enabling debug logging: `RUNNER_DEBUG` is set
+    /// when a job is re-run with debug logging, and `ACTIONS_STEP_DEBUG`
+    /// when the secret or variable of the same name is exposed to the step.
+    pub const DEBUG_ENV: [&str; 2] = ["RUNNER_DEBUG", "ACTIONS_STEP_DEBUG"];
+
+    /// Whether debug logging is enabled, without which debug messages are
+    /// hidden from the log.
+    ///
+    /// Debug logging is enabled if any of [`GitHub::DEBUG_ENV`] is set to
+    /// `1` or `true`.
+    #[must_use]
     #[inline]
-    pub fn debug(message: impl AsRef<str>) -> String {
-        format!("::debug::{}\n", message.as_ref())
+    pub fn debug_enabled() -> bool {
+        Self::DEBUG_ENV.iter().any(|var| {
+            env::var(var).is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        })
     }
 
     /// Creates a builder for a notice message.
@@ -161,15 +812,19 @@ impl GitHub {
         end_column: Option<u32>,
         title: Option<&str>,
     ) -> String {
-        let params = AnnotationParams {
-            file,
-            line,
-            col,
-            end_line,
-            end_column,
-            title,
-        };
-        format!("::notice {params}::{}\n", message.as_ref())
+        Annotation {
+            command: "notice",
+            params: AnnotationParams {
+                file,
+                line,
+                col,
+                end_line,
+                end_column,
+                title,
+            },
+            message: message.as_ref(),
+        }
+        .to_string()
     }
 
     /// Creates a builder for a warning message.
@@ -218,15 +873,19 @@ impl GitHub {
         end_column: Option<u32>,
         title: Option<&str>,
     ) -> String {
-        let params = AnnotationParams {
-            file,
-            line,
-            col,
-            end_line,
-            end_column,
-            title,
-        };
-        format!("::warning {params}::{}\n", message.as_ref())
+        Annotation {
+            command: "warning",
+            params: AnnotationParams {
+                file,
+                line,
+                col,
+                end_line,
+                end_column,
+                title,
+            },
+            message: message.as_ref(),
+        }
+        .to_string()
     }
 
     /// Creates a builder for an error message.
@@ -266,76 +925,29 @@ impl GitHub {
     ///     .title("Compilation Error")
     ///     .format();
     /// ```
-    #[builder(finish_fn = format)]
-    pub fn error(
-        #[builder(start_fn)] message: impl AsRef<str>,
-        file: Option<&str>,
-        line: Option<u32>,
-        col: Option<u32>,
-        end_line: Option<u32>,
-        end_column: Option<u32>,
-        title: Option<&str>,
-    ) -> String {
-        let params = AnnotationParams {
-            file,
-            line,
-            col,
-            end_line,
-            end_column,
-            title,
-        };
-        format!("::error {params}::{}\n", message.as_ref())
-    }
-
-    /// Starts a collapsible group in the workflow log.
-    ///
-    /// All output between this command and `endgroup()` will be nested inside
-    /// an expandable group in the logs.
-    ///
-    /// # Arguments
-    ///
-    /// * `title` - The title of the group to display.
-    ///
-    /// # Returns
-    ///
-    /// A formatted group command string, suitable for printing to stdout. The
-    /// string includes a trailing newline.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use cifmt::ci::GitHub;
-    ///
-    /// print!("{}", GitHub::group("Build Steps"));
-    /// println!("Running build...");
-    /// println!("Compiling...");
-    /// print!("{}", GitHub::endgroup());
-    /// ```
-    #[inline]
-    pub fn group(title: impl AsRef<str>) -> String {
-        format!("::group::{}\n", title.as_ref())
-    }
-
-    /// Ends a collapsible group in the workflow log.
-    ///
-    /// # Returns
-    ///
-    /// A formatted endgroup command string, suitable for printing to stdout.
-    /// The string includes a trailing newline.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use cifmt::ci::GitHub;
-    ///
-    /// print!("{}", GitHub::group("Test Results"));
-    /// println!("Running tests...");
-    /// print!("{}", GitHub::endgroup());
-    /// ```
-    #[must_use]
-    #[inline]
-    pub fn endgroup() -> String {
-        "::endgroup::\n".to_owned()
+    #[builder(finish_fn = format)]
+    pub fn error(
+        #[builder(start_fn)] message: impl AsRef<str>,
+        file: Option<&str>,
+        line: Option<u32>,
+        col: Option<u32>,
+        end_line: Option<u32>,
+        end_column: Option<u32>,
+        title: Option<&str>,
+    ) -> String {
+        Annotation {
+            command: "error",
+            params: AnnotationParams {
+                file,
+                line,
+                col,
+                end_line,
+                end_column,
+                title,
+            },
+            message: message.as_ref(),
+        }
+        .to_string()
     }
 
     /// Masks a value in the workflow logs.
@@ -364,7 +976,7 @@ impl GitHub {
     /// ```
     #[inline]
     pub fn add_mask(value: impl AsRef<str>) -> String {
-        format!("::add-mask::{}\n", value.as_ref())
+        format!("::add-mask::{}\n", Data(value.as_ref()))
     }
 
     /// Stops processing workflow commands.
@@ -461,21 +1073,368 @@ impl GitHub {
         let value = if enable { "on" } else { "off" };
         format!("::echo::{value}\n")
     }
+
+    /// Registers a problem matcher.
+    ///
+    /// Problem matchers scan the log of the subsequent steps for lines matching
+    /// their patterns, and create an annotation for each match. The matcher is
+    /// read from a JSON file on the runner (see
+    /// [`ProblemMatcher`](crate::matcher::ProblemMatcher)).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the JSON file defining the problem matcher.
+    ///
+    /// # Returns
+    ///
+    /// A formatted add-matcher command string, suitable for printing to
+    /// stdout. The string includes a trailing newline.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::GitHub;
+    ///
+    /// print!("{}", GitHub::add_matcher(".github/rustc.json"));
+    /// ```
+    #[inline]
+    pub fn add_matcher(path: impl AsRef<str>) -> String {
+        format!("::add-matcher::{}\n", Data(path.as_ref()))
+    }
+
+    /// Unregisters a problem matcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The owner of the problem matcher, as given in its
+    ///   definition.
+    ///
+    /// # Returns
+    ///
+    /// A formatted remove-matcher command string, suitable for printing to
+    /// stdout. The string includes a trailing newline.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::GitHub;
+    ///
+    /// print!("{}", GitHub::remove_matcher("rustc"));
+    /// ```
+    #[inline]
+    pub fn remove_matcher(owner: impl AsRef<str>) -> String {
+        format!("::remove-matcher owner={}::\n", Property(owner.as_ref()))
+    }
+
+    /// Escapes the data of a workflow command (e.g., the message of an
+    /// annotation), percent-encoding `%`, `\r` and `\n`.
+    ///
+    /// This is applied by all commands formatted by this platform, and need
+    /// only be used for commands written by hand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::GitHub;
+    ///
+    /// assert_eq!(GitHub::escape_data("100%\ndone"), "100%25%0Adone");
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn escape_data(data: &str) -> String {
+        Data(data).to_string()
+    }
+
+    /// Escapes a property value of a workflow command (e.g., the title of an
+    /// annotation), percent-encoding `:` and `,` in addition to the
+    /// characters escaped by [`GitHub::escape_data`].
+    ///
+    /// This is applied by all commands formatted by this platform, and need
+    /// only be used for commands written by hand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::GitHub;
+    ///
+    /// assert_eq!(GitHub::escape_property("Test: a, b"), "Test%3A a%2C b");
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn escape_property(value: &str) -> String {
+        Property(value).to_string()
+    }
+
+    /// Creates a builder writing Markdown to the job summary.
+    ///
+    /// The job summary is displayed on the summary page of the workflow run,
+    /// and is read from the file given by the [`GitHub::STEP_SUMMARY_ENV`]
+    /// environment variable once the step completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The Markdown content to write.
+    /// * `path` - Optional path to the job summary. Defaults to the path
+    ///   given by [`GitHub::STEP_SUMMARY_ENV`].
+    /// * `overwrite` - Whether to replace the content written to the job
+    ///   summary by the step so far, rather than appending to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no path is given and the environment variable is
+    /// not set, or if the job summary cannot be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cifmt::ci::GitHub;
+    ///
+    /// GitHub::summary("### Tests\n\n**2** tests failed\n").write()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[builder(finish_fn = write)]
+    pub fn summary(
+        #[builder(start_fn)] body: &str,
+        path: Option<&Path>,
+        #[builder(default)] overwrite: bool,
+    ) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!overwrite)
+            .truncate(overwrite)
+            .open(env_file(path, Self::STEP_SUMMARY_ENV)?)?;
+        file.write_all(body.as_bytes())?;
+        if !body.is_empty() && !body.ends_with('\n') {
+            file.write_all(b"\n")?;
+        }
+        file.flush()
+    }
+
+    /// Creates a builder setting an output of the current step.
+    ///
+    /// Outputs can be referenced by the following steps of the job through
+    /// the `steps.<id>.outputs.<name>` context. The output is appended to the
+    /// file given by the [`GitHub::OUTPUT_ENV`] environment variable, with
+    /// values spanning several lines written with a delimiter (see
+    /// [`GitHub::file_command`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the output.
+    /// * `value` - The value of the output.
+    /// * `path` - Optional path to the file of outputs. Defaults to the path
+    ///   given by [`GitHub::OUTPUT_ENV`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid, if no path is given and the
+    /// environment variable is not set, or if the file cannot be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cifmt::ci::GitHub;
+    ///
+    /// GitHub::set_output("failed-tests", "3").write()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[builder(finish_fn = write)]
+    pub fn set_output(
+        #[builder(start_fn)] name: &str,
+        #[builder(start_fn)] value: &str,
+        path: Option<&Path>,
+    ) -> io::Result<()> {
+        append(
+            &env_file(path, Self::OUTPUT_ENV)?,
+            &Self::file_command(name, value)?,
+        )
+    }
+
+    /// Creates a builder setting an environment variable for the following
+    /// steps of the job.
+    ///
+    /// The variable is appended to the file given by the
+    /// [`GitHub::ENV_FILE_ENV`] environment variable, with values spanning
+    /// several lines written with a delimiter (see [`GitHub::file_command`]).
+    /// The variable is not set for the current step.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the environment variable.
+    /// * `value` - The value of the environment variable.
+    /// * `path` - Optional path to the file of environment variables.
+    ///   Defaults to the path given by [`GitHub::ENV_FILE_ENV`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid, if no path is given and the
+    /// environment variable is not set, or if the file cannot be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cifmt::ci::GitHub;
+    ///
+    /// GitHub::export_variable("RUST_BACKTRACE", "1").write()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[builder(finish_fn = write)]
+    pub fn export_variable(
+        #[builder(start_fn)] name: &str,
+        #[builder(start_fn)] value: &str,
+        path: Option<&Path>,
+    ) -> io::Result<()> {
+        append(
+            &env_file(path, Self::ENV_FILE_ENV)?,
+            &Self::file_command(name, value)?,
+        )
+    }
+
+    /// Creates a builder adding a directory to the `PATH` of the following
+    /// steps of the job.
+    ///
+    /// The directory is appended to the file given by the
+    /// [`GitHub::PATH_ENV`] environment variable, and is prepended to the
+    /// `PATH` of the following steps. The `PATH` of the current step is not
+    /// modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to add.
+    /// * `path` - Optional path to the file of directories. Defaults to the
+    ///   path given by [`GitHub::PATH_ENV`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory is empty or spans several lines, if
+    /// no path is given and the environment variable is not set, or if the
+    /// file cannot be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cifmt::ci::GitHub;
+    ///
+    /// GitHub::add_path("/opt/tools/bin").write()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[builder(finish_fn = write)]
+    pub fn add_path(#[builder(start_fn)] dir: &str, path: Option<&Path>) -> io::Result<()> {
+        if dir.is_empty() || dir.contains(['\n', '\r']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid directory for {}: {dir:?}", Self::PATH_ENV),
+            ));
+        }
+        append(&env_file(path, Self::PATH_ENV)?, &format!("{dir}\n"))
+    }
+
+    /// Formats a name and value for an environment file (e.g., the file of
+    /// step outputs given by [`GitHub::OUTPUT_ENV`]).
+    ///
+    /// Values on a single line are formatted as `name=value`. Values spanning
+    /// several lines are written between delimiters, as `name<<DELIMITER`,
+    /// followed by the value and the delimiter on their own lines. The
+    /// delimiter is chosen such that it does not occur within the value, so
+    /// that the value cannot end the entry early and inject other entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the entry.
+    /// * `value` - The value of the entry.
+    ///
+    /// # Returns
+    ///
+    /// The formatted entry, including a trailing newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty, or contains `=`, `<<` or a line
+    /// break, as it would otherwise be misread.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::GitHub;
+    ///
+    /// assert_eq!(GitHub::file_command("result", "ok")?, "result=ok\n");
+    /// assert!(GitHub::file_command("report", "a\nb")?.starts_with("report<<ghadelimiter_"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    pub fn file_command(name: &str, value: &str) -> io::Result<String> {
+        if name.is_empty() || name.contains(['=', '\n', '\r']) || name.contains("<<") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid name for an environment file: {name:?}"),
+            ));
+        }
+        if !value.contains(['\n', '\r']) {
+            return Ok(format!("{name}={value}\n"));
+        }
+        let delimiter = delimiter(value);
+        Ok(format!("{name}<<{delimiter}\n{value}\n{delimiter}\n"))
+    }
+}
+
+/// The path to an environment file, either as given or else from the
+/// environment variable holding it.
+///
+/// Returns an error if no path is given and the environment variable is not
+/// set.
+fn env_file(path: Option<&Path>, var: &str) -> io::Result<PathBuf> {
+    if let Some(file) = path {
+        return Ok(file.to_path_buf());
+    }
+    env::var_os(var)
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{var} is not set")))
+}
+
+/// Append content to a file, creating it if needed.
+fn append(path: &Path, content: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(content.as_bytes())?;
+    file.flush()
+}
+
+/// A delimiter for a value spanning several lines of an environment file,
+/// which does not occur within the value.
+///
+/// The delimiter is derived from the current time and process, so that it
+/// cannot be predicted by whoever controls the value.
+fn delimiter(value: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut seed = nanos ^ u128::from(process::id());
+    loop {
+        let delimiter = format!("ghadelimiter_{seed:032x}");
+        if !value.contains(&delimiter) {
+            return delimiter;
+        }
+        seed = seed.wrapping_add(1);
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use pretty_assertions::assert_eq;
     use rstest::rstest;
 
+    use super::{AnnotationLimiter, plain_annotation, plain_debug, truncate_chars, unescape};
     use crate::ci::GitHub;
     use crate::ci::Platform;
+    use crate::ci_message::Severity;
 
     #[rstest]
     fn debug() {
         let result = GitHub::debug("This is a debug message");
         insta::assert_snapshot!(
             result,
-            @"::debug::This is a debug message\n"
+            @"::debug::This is a debug message"
         );
     }
 
@@ -552,12 +1511,51 @@ pub(crate) mod tests {
         );
     }
 
+    #[rstest]
+    fn windows_path() {
+        let result = GitHub::error("Unsupported syntax")
+            .file(r".\src\main.rs")
+            .line(10)
+            .format();
+        insta::assert_snapshot!(
+            result,
+            @"::error file=src/main.rs,line=10::Unsupported syntax"
+        );
+    }
+
+    #[rstest]
+    #[case::plain("Build failed", "Build failed", "Build failed")]
+    #[case::percent("100% done", "100%25 done", "100%25 done")]
+    #[case::line_breaks("a\r\nb", "a%0D%0Ab", "a%0D%0Ab")]
+    #[case::separators("tests::a, b", "tests::a, b", "tests%3A%3Aa%2C b")]
+    #[case::escaped("%0A", "%250A", "%250A")]
+    fn escape(#[case] text: &str, #[case] data: &str, #[case] property: &str) {
+        assert_eq!(GitHub::escape_data(text), data);
+        assert_eq!(GitHub::escape_property(text), property);
+        assert_eq!(unescape(data), text);
+        assert_eq!(unescape(property), text);
+    }
+
+    #[rstest]
+    fn escaped_annotation() {
+        let result = GitHub::warning("50% of\noutput")
+            .file("src/a,b.rs")
+            .line(3)
+            .title("Test Failed: tests::a")
+            .format();
+        insta::assert_snapshot!(
+            result,
+            @"::warning file=src/a%2Cb.rs,line=3,title=Test Failed%3A tests%3A%3Aa::50%25 of%0Aoutput"
+        );
+        insta::assert_snapshot!(GitHub::group("Test: a\nb"), @"::group::Test: a%0Ab");
+    }
+
     #[rstest]
     fn group() {
         let result = GitHub::group("Build Steps");
         insta::assert_snapshot!(
             result,
-            @"::group::Build Steps\n"
+            @"::group::Build Steps"
         );
     }
 
@@ -566,7 +1564,7 @@ pub(crate) mod tests {
         let result = GitHub::endgroup();
         insta::assert_snapshot!(
             result,
-            @"::endgroup::\n"
+            @"::endgroup::"
         );
     }
 
@@ -615,6 +1613,24 @@ pub(crate) mod tests {
         );
     }
 
+    #[rstest]
+    fn add_matcher() {
+        let result = GitHub::add_matcher("/tmp/cifmt-cargo-check.json");
+        insta::assert_snapshot!(
+            result,
+            @"::add-matcher::/tmp/cifmt-cargo-check.json\n"
+        );
+    }
+
+    #[rstest]
+    fn remove_matcher() {
+        let result = GitHub::remove_matcher("cifmt-cargo-check");
+        insta::assert_snapshot!(
+            result,
+            @"::remove-matcher owner=cifmt-cargo-check::\n"
+        );
+    }
+
     #[rstest]
     fn github_from_env_present() {
         // SAFETY: Safe within a single-threaded test context
@@ -638,4 +1654,224 @@ pub(crate) mod tests {
         let result = GitHub::from_env();
         assert!(result.is_none());
     }
+
+    #[rstest]
+    #[case::short("abc", 3, "abc")]
+    #[case::long("abcdef", 4, "abc…")]
+    #[case::multibyte("ééééé", 3, "éé…")]
+    fn truncate(#[case] text: &str, #[case] max: usize, #[case] expected: &str) {
+        let truncated = truncate_chars(text, max);
+        assert_eq!(truncated, expected);
+        assert!(truncated.chars().count() <= max);
+    }
+
+    #[rstest]
+    fn length_limits() {
+        let title = "t".repeat(300);
+        let result = GitHub::error("m".repeat(5000)).title(&title).format();
+        let (params, message) = result
+            .trim_end()
+            .trim_start_matches("::error ")
+            .split_once("::")
+            .expect("annotation");
+        let title_length = params.strip_prefix("title=").map(|t| t.chars().count());
+        assert_eq!(title_length, Some(GitHub::MAX_TITLE_LENGTH));
+        assert_eq!(message.chars().count(), GitHub::MAX_MESSAGE_LENGTH);
+    }
+
+    #[rstest]
+    #[case::location(
+        "file=src/lib.rs,line=3,col=5,title=error%3A E0425::cannot find `y`\n",
+        "Error: src/lib.rs:3:5: error: E0425: cannot find `y`\n"
+    )]
+    #[case::escaped(
+        "title=Test Failed%3A a%2C b::50%25 of%0Aoutput\n",
+        "Error: Test Failed: a, b: 50% of\noutput\n"
+    )]
+    #[case::title_only(
+        "title=Build Failed::Build failed\n",
+        "Error: Build Failed: Build failed\n"
+    )]
+    #[case::message_only("::Build failed\n", "Error: Build failed\n")]
+    fn plain(#[case] command: &str, #[case] expected: &str) {
+        assert_eq!(plain_annotation("Error", command), expected);
+    }
+
+    #[test]
+    fn plain_debug_message() {
+        assert_eq!(
+            plain_debug("Artifact: target/debug/a%0Abuilt\n"),
+            "Debug: Artifact: target/debug/a\nbuilt\n"
+        );
+    }
+
+    #[rstest]
+    fn annotation_limit() {
+        let mut limiter = AnnotationLimiter::default();
+        let error = GitHub::error("failed").line(1).file("src/lib.rs").format();
+        let notice = GitHub::notice("note").format();
+
+        // One annotation of each type is held back for the summary.
+        for _ in 1..GitHub::MAX_ANNOTATIONS_PER_STEP {
+            assert_eq!(limiter.apply(Severity::Error, &error), None);
+        }
+        assert_eq!(limiter.summary(false), None);
+        assert_eq!(limiter.apply(Severity::Notice, &notice), None);
+        let formatted = format!("::group::Test\n{error}{notice}::endgroup::\n");
+        insta::assert_snapshot!(
+            limiter.apply(Severity::Error, &formatted).unwrap_or_default(),
+            @"
+        ::group::Test
+        Error: src/lib.rs:1: failed
+        Notice: note
+        ::endgroup::
+        "
+        );
+        insta::assert_snapshot!(
+            limiter.summary(false).unwrap_or_default(),
+            @"::warning title=Annotations Omitted::1 additional issue(s) not shown as annotations, see the log"
+        );
+    }
+
+    #[rstest]
+    fn annotation_limit_priority() {
+        let mut limiter = AnnotationLimiter::default().with_limit(Some(12));
+        let error = GitHub::error("failed").format();
+        let warning = GitHub::warning("unused").format();
+
+        // Warnings leave room for the errors which may follow.
+        assert_eq!(limiter.apply(Severity::Warning, &warning), None);
+        assert_eq!(limiter.apply(Severity::Warning, &warning), None);
+        assert_eq!(
+            limiter.apply(Severity::Warning, &warning).as_deref(),
+            Some("Warning: unused\n")
+        );
+        for _ in 1..GitHub::MAX_ANNOTATIONS_PER_STEP {
+            assert_eq!(limiter.apply(Severity::Error, &error), None);
+        }
+        assert_eq!(
+            limiter.apply(Severity::Error, &error).as_deref(),
+            Some("Error: failed\n")
+        );
+        assert_eq!(
+            limiter.apply(Severity::Warning, &warning).as_deref(),
+            Some("Warning: unused\n")
+        );
+        insta::assert_snapshot!(
+            limiter.summary(true).unwrap_or_default(),
+            @"::warning title=Annotations Omitted::3 additional issue(s) not shown as annotations, see the log and the job summary"
+        );
+    }
+
+    #[rstest]
+    fn annotation_limit_zero() {
+        let mut limiter = AnnotationLimiter::default().with_limit(Some(0));
+        let formatted = format!(
+            "{}{}",
+            GitHub::error("failed").file("src/lib.rs").format(),
+            GitHub::notice("note").format()
+        );
+        insta::assert_snapshot!(
+            limiter.apply(Severity::Error, &formatted).unwrap_or_default(),
+            @"
+        Error: src/lib.rs: failed
+        Notice: note
+        "
+        );
+        assert_eq!(
+            limiter.apply(Severity::Notice, "Test passed\n").as_deref(),
+            None
+        );
+        assert_eq!(limiter.summary(true), None);
+    }
+
+    #[rstest]
+    fn summary() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let path = dir.path().join("summary.md");
+        GitHub::summary("### First")
+            .path(&path)
+            .write()
+            .expect("write failed");
+        GitHub::summary("### Second\n")
+            .path(&path)
+            .write()
+            .expect("write failed");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read failed"),
+            "### First\n### Second\n"
+        );
+
+        GitHub::summary("### Third\n")
+            .path(&path)
+            .overwrite(true)
+            .write()
+            .expect("write failed");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read failed"),
+            "### Third\n"
+        );
+    }
+
+    #[rstest]
+    #[case::single_line("result", "ok", "result=ok\n")]
+    #[case::equals("expr", "a=b", "expr=a=b\n")]
+    #[case::empty("empty", "", "empty=\n")]
+    fn file_command(#[case] name: &str, #[case] value: &str, #[case] expected: &str) {
+        assert_eq!(
+            GitHub::file_command(name, value).expect("valid name"),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::multi_line("line 1\nline 2")]
+    #[case::injection("x\nghadelimiter_0\nINJECTED=1")]
+    fn file_command_delimited(#[case] value: &str) {
+        let command = GitHub::file_command("report", value).expect("valid name");
+        let (header, rest) = command.split_once('\n').expect("header line");
+        let delimiter = header.strip_prefix("report<<").expect("delimited entry");
+        assert!(
+            !value.contains(delimiter),
+            "{delimiter} occurs in {value:?}"
+        );
+        assert_eq!(rest, format!("{value}\n{delimiter}\n"));
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::equals("a=b")]
+    #[case::heredoc("a<<b")]
+    #[case::newline("a\nb")]
+    fn file_command_invalid(#[case] name: &str) {
+        let err = GitHub::file_command(name, "value").expect_err("invalid name");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[rstest]
+    fn env_files() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let output = dir.path().join("output");
+        GitHub::set_output("count", "2")
+            .path(&output)
+            .write()
+            .expect("write failed");
+        GitHub::export_variable("RUST_LOG", "debug")
+            .path(&output)
+            .write()
+            .expect("write failed");
+        GitHub::add_path("/opt/bin")
+            .path(&output)
+            .write()
+            .expect("write failed");
+        assert_eq!(
+            std::fs::read_to_string(&output).expect("read failed"),
+            "count=2\nRUST_LOG=debug\n/opt/bin\n"
+        );
+
+        GitHub::add_path("/opt/bin\nINJECTED=1")
+            .path(&output)
+            .write()
+            .expect_err("invalid directory");
+    }
 }
@@ -0,0 +1,209 @@
+//! Normalization of file paths within annotations.
+//!
+//! Annotations only attach to a file if their path matches the path of the
+//! file within the repository. Tools running on Windows report paths with
+//! backslashes, and often as absolute paths including a drive letter (e.g.,
+//! `D:\a\repo\repo\src\lib.rs`), neither of which match. Paths are therefore
+//! normalized to use forward slashes, and made relative to the root of the
+//! checkout if they lie within it.
+//!
+//! The root is taken from the environment of the CI platform (e.g.,
+//! `GITHUB_WORKSPACE`), unless set explicitly with [`set_checkout_root`].
+//! Normalization applies to every platform, so that the paths in plain logs
+//! match those in annotations.
+
+use core::fmt;
+use std::{
+    env,
+    path::{self, Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// Environment variables holding the root of the checkout, in order of
+/// preference.
+const ROOT_VARS: &[&str] = &[
+    "GITHUB_WORKSPACE",
+    "BUILD_SOURCESDIRECTORY",
+    "BUILDKITE_BUILD_CHECKOUT_PATH",
+    "CODEBUILD_SRC_DIR",
+];
+
+/// The root of the checkout, normalized with [`normalize_root`], once known.
+static ROOT: OnceLock<Option<String>> = OnceLock::new();
+
+/// A file path which is normalized when displayed.
+///
+/// See the [module documentation](self) for details.
+pub(crate) struct NormalizedPath<'a>(pub &'a str);
+
+impl fmt::Display for NormalizedPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&normalize(self.0, checkout_root()))
+    }
+}
+
+/// Whether a file lies within the checkout, rather than (for example) in the
+/// standard library or in a dependency from the Cargo registry.
+///
+/// Relative paths are assumed to be relative to the root of the checkout.
+pub(crate) fn is_in_checkout(path: &str) -> bool {
+    is_relative(&normalize(path, checkout_root()))
+}
+
+/// The normalized path of a file relative to the root of the checkout, or
+/// `None` if the file lies outside of it.
+///
+/// Relative paths are assumed to be relative to the root of the checkout.
+pub(crate) fn relative_to_checkout(path: &str) -> Option<String> {
+    let normalized = normalize(path, checkout_root());
+    is_relative(&normalized).then_some(normalized)
+}
+
+/// Whether a normalized path is relative to the root of the checkout.
+fn is_relative(normalized: &str) -> bool {
+    !(normalized.starts_with('/') || normalized.starts_with("../") || has_drive_letter(normalized))
+}
+
+/// Set the root of the checkout, to which paths within it are made relative,
+/// rather than taking it from the environment.
+///
+/// Only the first root set takes effect, so this should be called before any
+/// message is formatted. A relative root is resolved against the current
+/// directory.
+///
+/// # Arguments
+///
+/// * `root` - The root of the checkout.
+///
+/// # Errors
+///
+/// Returns the root if the root of the checkout is already known.
+#[inline]
+pub fn set_checkout_root(root: impl AsRef<Path>) -> Result<(), PathBuf> {
+    let given = root.as_ref();
+    let absolute = path::absolute(given).unwrap_or_else(|_err| given.to_owned());
+    let normalized = normalize_root(&absolute.to_string_lossy());
+    ROOT.set(Some(normalized).filter(|prefix| !prefix.is_empty()))
+        .map_err(|_rejected| absolute)
+}
+
+/// The root of the checkout, normalized with [`normalize_root`].
+///
+/// Unless set with [`set_checkout_root`], this is taken from the environment
+/// of the CI platform if available, and is otherwise the current directory.
+fn checkout_root() -> Option<&'static str> {
+    ROOT.get_or_init(|| {
+        ROOT_VARS
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+            .or_else(|| env::current_dir().ok()?.into_os_string().into_string().ok())
+            .map(|root| normalize_root(&root))
+            .filter(|root| !root.is_empty())
+    })
+    .as_deref()
+}
+
+/// Use forward slashes throughout a path, and remove the verbatim prefix
+/// (`\\?\`) of Windows paths.
+fn to_forward_slashes(path: &str) -> String {
+    let slashed = path.replace('\\', "/");
+    match slashed.strip_prefix("//?/") {
+        Some(rest) => rest.to_owned(),
+        None => slashed,
+    }
+}
+
+/// Normalize the root of the checkout for comparison with paths.
+fn normalize_root(root: &str) -> String {
+    to_forward_slashes(root).trim_end_matches('/').to_owned()
+}
+
+/// Whether a path starts with a drive letter (e.g., `C:`).
+fn has_drive_letter(path: &str) -> bool {
+    matches!(path.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic())
+}
+
+/// Normalize a path.
+///
+/// # Arguments
+///
+/// * `path` - The path to normalize.
+/// * `root` - The root of the checkout, normalized with [`normalize_root`].
+///   Paths within it are made relative to it.
+fn normalize(path: &str, root: Option<&str>) -> String {
+    let slashed = to_forward_slashes(path);
+    let relative = root
+        .and_then(|prefix| {
+            let head = slashed.get(..prefix.len())?;
+            // Windows paths are case-insensitive.
+            let matches = if has_drive_letter(prefix) {
+                head.eq_ignore_ascii_case(prefix)
+            } else {
+                head == prefix
+            };
+            if !matches {
+                return None;
+            }
+            slashed.get(prefix.len()..)?.strip_prefix('/')
+        })
+        .unwrap_or(&slashed);
+    relative.trim_start_matches("./").to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{is_relative, normalize, normalize_root};
+
+    #[rstest]
+    #[case::relative("src/lib.rs", None, "src/lib.rs")]
+    #[case::backslashes(r"src\ci\path.rs", None, "src/ci/path.rs")]
+    #[case::dot_relative(r".\src\lib.rs", None, "src/lib.rs")]
+    #[case::unix_root(
+        "/home/runner/work/repo/src/lib.rs",
+        Some("/home/runner/work/repo/"),
+        "src/lib.rs"
+    )]
+    #[case::drive_root(r"D:\a\repo\repo\src\lib.rs", Some(r"D:\a\repo\repo"), "src/lib.rs")]
+    #[case::drive_case(r"d:\a\repo\repo\src\lib.rs", Some(r"D:\a\repo\repo"), "src/lib.rs")]
+    #[case::verbatim(r"\\?\D:\a\repo\repo\src\lib.rs", Some("D:/a/repo/repo"), "src/lib.rs")]
+    #[case::outside_root(
+        r"C:\Users\me\.cargo\registry\src\lib.rs",
+        Some(r"D:\a\repo\repo"),
+        "C:/Users/me/.cargo/registry/src/lib.rs"
+    )]
+    #[case::sibling(
+        "/work/repo-other/src/lib.rs",
+        Some("/work/repo"),
+        "/work/repo-other/src/lib.rs"
+    )]
+    #[case::case_sensitive("/Work/repo/src/lib.rs", Some("/work/repo"), "/Work/repo/src/lib.rs")]
+    fn normalizes(#[case] path: &str, #[case] root: Option<&str>, #[case] expected: &str) {
+        let normalized_root = root.map(normalize_root);
+        assert_eq!(normalize(path, normalized_root.as_deref()), expected);
+    }
+
+    #[rstest]
+    #[case::relative("src/lib.rs", None, true)]
+    #[case::within_root("/work/repo/src/lib.rs", Some("/work/repo"), true)]
+    #[case::std(
+        "/rustc/1234/library/core/src/macros/mod.rs",
+        Some("/work/repo"),
+        false
+    )]
+    #[case::registry(
+        r"C:\Users\me\.cargo\registry\src\lib.rs",
+        Some(r"D:\a\repo\repo"),
+        false
+    )]
+    #[case::parent("../other/src/lib.rs", None, false)]
+    fn in_checkout(#[case] path: &str, #[case] root: Option<&str>, #[case] expected: bool) {
+        let normalized_root = root.map(normalize_root);
+        assert_eq!(
+            is_relative(&normalize(path, normalized_root.as_deref())),
+            expected
+        );
+    }
+}
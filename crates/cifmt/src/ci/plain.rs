@@ -1,10 +1,14 @@
 //! Fallback plain text formatter.
 //!
 //! This formatter is used when no other formatter matches the CI environment.
+//! Each annotation takes a single line with a consistent layout (see
+//! [`Plain::line`]), such that the output can be consumed by tools matching
+//! lines (e.g., `grep` or an editor's error format).
 
 use std::fmt;
 
-use crate::ci::Platform;
+use crate::ci::{NormalizedPath, Platform, titled};
+use crate::ci_message::Severity;
 
 /// Plain text formatter.
 #[derive(Debug, Clone, Copy)]
@@ -19,6 +23,100 @@ impl Platform for Plain {
     {
         Some(Plain)
     }
+
+    /// Formats the message as a single line (see [`Plain::line`]).
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        Self::line(severity, message.as_ref(), location, title)
+    }
+
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        title.as_ref().to_owned()
+    }
+
+    #[inline]
+    fn endgroup() -> String {
+        String::new()
+    }
+
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        message.as_ref().to_owned()
+    }
+}
+
+impl Plain {
+    /// The width of the severity prefixes, that of the longest (`warning:`).
+    const LABEL_WIDTH: usize = 8;
+
+    /// Formats a message as a single line, which downstream tools can match
+    /// with a regular expression (e.g., with `grep`).
+    ///
+    /// The line is `file:line:col: severity: title: message`, omitting the
+    /// location (or only its column) and the title if absent. The severity is
+    /// `error:`, `warning:`, `notice:` or `debug:`, padded such that the text
+    /// following it is aligned across messages with the same location prefix.
+    /// Line breaks within the message are escaped as `\n`, such that each
+    /// message takes a single line.
+    ///
+    /// # Arguments
+    ///
+    /// * `severity` - The severity of the message.
+    /// * `message` - The message.
+    /// * `location` - The file, line and column to which the message relates,
+    ///   if any.
+    /// * `title` - A title for the message, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::Plain;
+    /// use cifmt::ci_message::Severity;
+    ///
+    /// assert_eq!(
+    ///     Plain::line(Severity::Error, "mismatched types", Some(("src/lib.rs", 3, Some(9))), Some("E0308")),
+    ///     "src/lib.rs:3:9: error:   E0308: mismatched types",
+    /// );
+    /// assert_eq!(
+    ///     Plain::line(Severity::Warning, "first\nsecond\n", None, None),
+    ///     "warning: first\\nsecond",
+    /// );
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn line(
+        severity: Severity,
+        message: &str,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        let label = match severity {
+            Severity::Debug => "debug:",
+            Severity::Notice => "notice:",
+            Severity::Warning => "warning:",
+            Severity::Error => "error:",
+        };
+        let text = titled(message.trim_end_matches('\n'), title).replace('\n', "\\n");
+        let width = Self::LABEL_WIDTH;
+        match location {
+            Some((file, line, Some(column))) => {
+                format!(
+                    "{}:{line}:{column}: {label:<width$} {text}",
+                    NormalizedPath(file)
+                )
+            }
+            Some((file, line, None)) => {
+                format!("{}:{line}: {label:<width$} {text}", NormalizedPath(file))
+            }
+            None => format!("{label:<width$} {text}"),
+        }
+    }
 }
 
 impl fmt::Display for Plain {
@@ -27,3 +125,24 @@ impl fmt::Display for Plain {
         write!(f, "Plain Text Formatter")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::Plain;
+    use crate::ci_message::Severity;
+
+    #[rstest]
+    #[case::debug(Severity::Debug, "src/lib.rs:3:9: debug:   message")]
+    #[case::notice(Severity::Notice, "src/lib.rs:3:9: notice:  message")]
+    #[case::warning(Severity::Warning, "src/lib.rs:3:9: warning: message")]
+    #[case::error(Severity::Error, "src/lib.rs:3:9: error:   message")]
+    fn line_severity(#[case] severity: Severity, #[case] expected: &str) {
+        assert_eq!(
+            Plain::line(severity, "message", Some(("src/lib.rs", 3, Some(9))), None),
+            expected
+        );
+    }
+}
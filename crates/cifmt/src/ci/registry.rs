@@ -0,0 +1,249 @@
+//! Detection of the CI platform from the environment.
+//!
+//! Each platform is detected by a [`Detector`], and a [`Registry`] evaluates
+//! its detectors in order of decreasing priority, returning the first platform
+//! detected. Detection can be bypassed by forcing a platform, either through
+//! the [`PLATFORM_ENV`] environment variable or with
+//! [`Registry::with_platform`].
+
+use std::env;
+
+use tracing::{debug, warn};
+
+use super::{
+    AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Platform, PlatformKind, Terminal,
+};
+
+/// Environment variable forcing the platform (e.g., `CIFMT_PLATFORM=github`),
+/// overriding detection.
+///
+/// The value is the name of a platform as accepted by [`PlatformKind`]'s
+/// [`FromStr`](core::str::FromStr) implementation. Empty values are ignored,
+/// and unknown values are ignored with a warning.
+pub const PLATFORM_ENV: &str = "CIFMT_PLATFORM";
+
+/// A detector for a CI platform.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Detector {
+    /// The platform detected.
+    pub kind: PlatformKind,
+    /// The priority of the detector. Detectors with a higher priority are
+    /// evaluated first.
+    pub priority: i32,
+    /// Whether the current environment matches the platform.
+    pub detect: fn() -> bool,
+}
+
+/// A registry of platform detectors.
+///
+/// The default registry detects each supported platform, in the following
+/// order (with their priority):
+///
+/// 1. [`GitHub`] Actions (50).
+/// 2. [`AzureDevOps`] Pipelines (40).
+/// 3. [`Buildkite`] (30).
+/// 4. AWS [`CodeBuild`] (20).
+/// 5. Google [`CloudBuild`] (10).
+/// 6. An interactive [`Terminal`] (0), for local usage.
+///
+/// Falls back to [`PlatformKind::Plain`] when no platform is detected.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::ci::{PlatformKind, Registry};
+///
+/// // Prefer Buildkite on agents which also set the variables of another
+/// // platform.
+/// let registry = Registry::default().with_detector(PlatformKind::Buildkite, 100, || {
+///     std::env::var_os("BUILDKITE").is_some()
+/// });
+/// let platform = registry.detect();
+///
+/// // Force a platform, regardless of the environment.
+/// let forced = Registry::default().with_platform(PlatformKind::GitHub);
+/// assert_eq!(forced.detect(), PlatformKind::GitHub);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Registry {
+    /// The detectors, in order of decreasing priority.
+    detectors: Vec<Detector>,
+    /// The platform forced through the library, if any.
+    forced: Option<PlatformKind>,
+}
+
+impl Registry {
+    /// Create a registry without any detectors, which detects
+    /// [`PlatformKind::Plain`] unless a platform is forced.
+    #[must_use]
+    #[inline]
+    pub fn empty() -> Self {
+        Self {
+            detectors: Vec::new(),
+            forced: None,
+        }
+    }
+
+    /// Register a detector for a platform.
+    ///
+    /// Detectors with equal priority are evaluated in the order in which they
+    /// are registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The platform detected.
+    /// * `priority` - The priority of the detector. Detectors with a higher
+    ///   priority are evaluated first.
+    /// * `detect` - Whether the current environment matches the platform.
+    #[must_use]
+    #[inline]
+    pub fn with_detector(
+        mut self,
+        kind: PlatformKind,
+        priority: i32,
+        detect: fn() -> bool,
+    ) -> Self {
+        let index = self
+            .detectors
+            .partition_point(|detector| detector.priority >= priority);
+        self.detectors.insert(
+            index,
+            Detector {
+                kind,
+                priority,
+                detect,
+            },
+        );
+        self
+    }
+
+    /// Force a platform, regardless of the environment.
+    ///
+    /// This takes precedence over the [`PLATFORM_ENV`] environment variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The platform to use.
+    #[must_use]
+    #[inline]
+    pub fn with_platform(mut self, kind: PlatformKind) -> Self {
+        self.forced = Some(kind);
+        self
+    }
+
+    /// The registered detectors, in order of evaluation.
+    #[must_use]
+    #[inline]
+    pub fn detectors(&self) -> &[Detector] {
+        &self.detectors
+    }
+
+    /// Detect the CI platform.
+    ///
+    /// A platform forced through [`Registry::with_platform`] takes precedence,
+    /// followed by the [`PLATFORM_ENV`] environment variable, and then the
+    /// detectors in order of decreasing priority.
+    #[must_use]
+    #[inline]
+    pub fn detect(&self) -> PlatformKind {
+        self.resolve(env::var(PLATFORM_ENV).ok().as_deref())
+    }
+
+    /// Detect the CI platform, given the value of the [`PLATFORM_ENV`]
+    /// environment variable.
+    fn resolve(&self, overridden: Option<&str>) -> PlatformKind {
+        if let Some(kind) = self.forced {
+            debug!("Using forced platform: {kind}");
+            return kind;
+        }
+        if let Some(name) = overridden.map(str::trim).filter(|name| !name.is_empty()) {
+            match name.parse() {
+                Ok(kind) => {
+                    debug!("Using platform from {PLATFORM_ENV}: {kind}");
+                    return kind;
+                }
+                Err(err) => warn!("Ignoring {PLATFORM_ENV}: {err}"),
+            }
+        }
+        debug!("Detecting CI platform from environment variables");
+        self.detectors
+            .iter()
+            .find(|detector| (detector.detect)())
+            .map_or(PlatformKind::Plain, |detector| detector.kind)
+    }
+}
+
+impl Default for Registry {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+            .with_detector(PlatformKind::GitHub, 50, || GitHub::from_env().is_some())
+            .with_detector(PlatformKind::AzureDevOps, 40, || {
+                AzureDevOps::from_env().is_some()
+            })
+            .with_detector(PlatformKind::Buildkite, 30, || {
+                Buildkite::from_env().is_some()
+            })
+            .with_detector(PlatformKind::CodeBuild, 20, || {
+                CodeBuild::from_env().is_some()
+            })
+            .with_detector(PlatformKind::CloudBuild, 10, || {
+                CloudBuild::from_env().is_some()
+            })
+            .with_detector(PlatformKind::Terminal, 0, || Terminal::from_env().is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::Registry;
+    use crate::ci::PlatformKind;
+
+    fn registry() -> Registry {
+        Registry::empty()
+            .with_detector(PlatformKind::CodeBuild, 10, || true)
+            .with_detector(PlatformKind::GitHub, 20, || false)
+            .with_detector(PlatformKind::Buildkite, 10, || true)
+            .with_detector(PlatformKind::AzureDevOps, 20, || true)
+    }
+
+    #[test]
+    fn precedence() {
+        let kinds: Vec<_> = registry()
+            .detectors()
+            .iter()
+            .map(|detector| detector.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                PlatformKind::GitHub,
+                PlatformKind::AzureDevOps,
+                PlatformKind::CodeBuild,
+                PlatformKind::Buildkite,
+            ]
+        );
+        assert_eq!(registry().resolve(None), PlatformKind::AzureDevOps);
+        assert_eq!(Registry::empty().resolve(None), PlatformKind::Plain);
+    }
+
+    #[rstest]
+    #[case(Some("cloudbuild"), PlatformKind::CloudBuild)]
+    #[case(Some(" plain "), PlatformKind::Plain)]
+    #[case(Some(""), PlatformKind::AzureDevOps)]
+    #[case(Some("jenkins"), PlatformKind::AzureDevOps)]
+    #[case(None, PlatformKind::AzureDevOps)]
+    fn env_override(#[case] value: Option<&str>, #[case] expected: PlatformKind) {
+        assert_eq!(registry().resolve(value), expected);
+    }
+
+    #[test]
+    fn forced() {
+        let registry = registry().with_platform(PlatformKind::Buildkite);
+        assert_eq!(registry.resolve(Some("github")), PlatformKind::Buildkite);
+    }
+}
@@ -0,0 +1,238 @@
+//! Colored terminal formatter.
+//!
+//! This module defines a variant of the [`Plain`](crate::ci::Plain) text
+//! formatter for local usage, which highlights messages with ANSI escape
+//! sequences.
+
+use core::fmt;
+use std::{
+    env,
+    io::{self, IsTerminal as _},
+};
+
+use tracing::debug;
+
+use crate::ci::{NormalizedPath, Platform};
+use crate::ci_message::Severity;
+
+/// Colored terminal formatter.
+///
+/// Messages are formatted as for [`Plain`](crate::ci::Plain), with their
+/// severity highlighted in color (see [`Terminal::styled`]), the names of
+/// tests in bold, and code frames (e.g., the source excerpts of a compiler
+/// diagnostic, or the output of a failed test) indented below the message
+/// they belong to (see [`Terminal::message`]).
+///
+/// The platform is detected when stdout is a terminal, unless the
+/// [`NO_COLOR`](https://no-color.org) environment variable is set.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Terminal;
+
+impl Platform for Terminal {
+    #[inline]
+    fn from_env() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        (!no_color && io::stdout().is_terminal()).then(|| {
+            debug!("Detected an interactive terminal");
+            Terminal
+        })
+    }
+
+    /// Formats the message as `file:line:col: SEVERITY: title: message`,
+    /// omitting the location (or only its column) and title if absent, with
+    /// the location and title in bold and the severity in color.
+    #[inline]
+    fn annotate(
+        severity: Severity,
+        message: impl AsRef<str>,
+        location: Option<(&str, u32, Option<u32>)>,
+        title: Option<&str>,
+    ) -> String {
+        let label = Self::styled(
+            severity,
+            match severity {
+                Severity::Debug => "DEBUG",
+                Severity::Notice => "NOTICE",
+                Severity::Warning => "WARNING",
+                Severity::Error => "ERROR",
+            },
+        );
+        let text = match title {
+            Some(heading) => format!("{}: {}", Self::bold(heading), message.as_ref()),
+            None => message.as_ref().to_owned(),
+        };
+        match location {
+            Some((file, line, Some(column))) => {
+                format!(
+                    "{}: {label}: {text}",
+                    Self::bold(format!("{}:{line}:{column}", NormalizedPath(file)))
+                )
+            }
+            Some((file, line, None)) => {
+                format!(
+                    "{}: {label}: {text}",
+                    Self::bold(format!("{}:{line}", NormalizedPath(file)))
+                )
+            }
+            None => format!("{label}: {text}"),
+        }
+    }
+
+    #[inline]
+    fn group(title: impl AsRef<str>) -> String {
+        Self::bold(title)
+    }
+
+    #[inline]
+    fn endgroup() -> String {
+        String::new()
+    }
+
+    #[inline]
+    fn debug(message: impl AsRef<str>) -> String {
+        Self::styled(Severity::Debug, message)
+    }
+}
+
+impl fmt::Display for Terminal {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Terminal")
+    }
+}
+
+impl Terminal {
+    /// The indentation of the lines of a code frame.
+    pub const INDENT: &str = "    ";
+
+    /// Highlights text according to a severity: errors in bold red, warnings
+    /// in bold yellow, notices in cyan, and debug messages dimmed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::Terminal;
+    /// use cifmt::ci_message::Severity;
+    ///
+    /// assert_eq!(
+    ///     Terminal::styled(Severity::Error, "error: oops"),
+    ///     "\x1b[1;31merror: oops\x1b[0m",
+    /// );
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn styled(severity: Severity, text: impl AsRef<str>) -> String {
+        let style = match severity {
+            Severity::Debug => "2",
+            Severity::Notice => "36",
+            Severity::Warning => "1;33",
+            Severity::Error => "1;31",
+        };
+        paint(style, text.as_ref())
+    }
+
+    /// Highlights text in bold (e.g., the name of a test).
+    #[must_use]
+    #[inline]
+    pub fn bold(text: impl AsRef<str>) -> String {
+        paint("1", text.as_ref())
+    }
+
+    /// Highlights text in green, reporting a success (e.g., a passing test).
+    #[must_use]
+    #[inline]
+    pub fn success(text: impl AsRef<str>) -> String {
+        paint("32", text.as_ref())
+    }
+
+    /// Indents each line of a code frame by [`Terminal::INDENT`].
+    ///
+    /// Empty lines are kept as is, as is a trailing newline.
+    #[must_use]
+    #[inline]
+    pub fn code_frame(text: impl AsRef<str>) -> String {
+        text.as_ref()
+            .split_inclusive('\n')
+            .map(|line| {
+                if line.trim_end_matches('\n').is_empty() {
+                    line.to_owned()
+                } else {
+                    format!("{}{line}", Self::INDENT)
+                }
+            })
+            .collect()
+    }
+
+    /// Highlights the first line of a message according to its severity (see
+    /// [`Terminal::styled`]), and indents the following lines as a code frame
+    /// (see [`Terminal::code_frame`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::ci::Terminal;
+    /// use cifmt::ci_message::Severity;
+    ///
+    /// assert_eq!(
+    ///     Terminal::message(Severity::Warning, "warning: unused\n --> src/lib.rs:3:5\n"),
+    ///     "\x1b[1;33mwarning: unused\x1b[0m\n     --> src/lib.rs:3:5\n",
+    /// );
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn message(severity: Severity, text: impl AsRef<str>) -> String {
+        match text.as_ref().split_once('\n') {
+            Some((first, rest)) => format!(
+                "{}\n{}",
+                Self::styled(severity, first),
+                Self::code_frame(rest)
+            ),
+            None => Self::styled(severity, text),
+        }
+    }
+}
+
+/// Wraps text in an ANSI select graphic rendition sequence, resetting the
+/// style at its end.
+///
+/// Empty text is left empty, rather than being wrapped.
+fn paint(style: &str, text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{style}m{text}\x1b[0m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Terminal;
+    use crate::ci_message::Severity;
+
+    #[test]
+    fn code_frame() {
+        assert_eq!(
+            Terminal::code_frame("  |\n3 | let x;\n\n  |\n"),
+            "      |\n    3 | let x;\n\n      |\n"
+        );
+        assert_eq!(Terminal::code_frame(""), "");
+    }
+
+    #[test]
+    fn message() {
+        assert_eq!(
+            Terminal::message(Severity::Debug, "Built artifact"),
+            "\x1b[2mBuilt artifact\x1b[0m"
+        );
+        assert_eq!(
+            Terminal::message(Severity::Error, "error: oops\n"),
+            "\x1b[1;31merror: oops\x1b[0m\n"
+        );
+    }
+}
@@ -3,7 +3,26 @@
 //! This module defines the fundamental types and traits for working with
 //! messages from different tools and formatting them for CI platforms.
 
-use crate::ci::Platform;
+use core::fmt;
+
+use crate::{
+    capture::Captured,
+    ci::{
+        AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, PlatformKind,
+        Terminal,
+    },
+    junit::TestResult,
+    sarif::Fix,
+    suites::SuiteResult,
+    timeline::Timed,
+};
+
+/// Insert a snippet of source code (see [`Classify::snippet`]) after the
+/// first line of a formatted message, which typically holds its summary.
+pub(crate) fn insert_snippet(formatted: &str, snippet: &str) -> String {
+    let (first, rest) = formatted.split_once('\n').unwrap_or((formatted, ""));
+    format!("{first}\n{snippet}{rest}")
+}
 
 /// Trait for types that can be formatted as CI messages.
 ///
@@ -37,4 +56,308 @@ pub trait CiMessage<T: Platform> {
     ///
     /// A formatted string suitable for the associated CI platform.
     fn format(&self) -> String;
+
+    /// Writes this message to a writer.
+    ///
+    /// This allows the message to be formatted into an existing buffer,
+    /// avoiding the allocation of a new string for each message. The default
+    /// implementation writes the result of [`CiMessage::format`].
+    ///
+    /// Messages which are built up from several parts should override this
+    /// method, and implement [`CiMessage::format`] in terms of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The writer to which the formatted message is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `out` fails.
+    #[inline]
+    fn write_to(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(&self.format())
+    }
+}
+
+/// Severity of a message.
+///
+/// Severities are ordered from least to most severe, such that gating logic
+/// can be expressed as a comparison (e.g., `severity >= Severity::Warning`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Severity {
+    /// Diagnostic information which is typically hidden.
+    Debug,
+    /// Informational message.
+    Notice,
+    /// Possible problem which does not cause a failure.
+    Warning,
+    /// Problem which causes a failure.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Debug => write!(f, "debug"),
+            Self::Notice => write!(f, "notice"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Trait for classifying messages independently of any platform.
+///
+/// This is used to aggregate statistics about the messages produced by a tool
+/// (e.g., the number of errors), without having to re-parse the formatted
+/// output.
+pub trait Classify {
+    /// The severity of this message.
+    fn severity(&self) -> Severity;
+
+    /// A short name for the kind of this message (e.g., `Diagnostic`), by
+    /// which it is described when the output is explained (see
+    /// [`Target::with_explanations`](crate::pipeline::Target::with_explanations)).
+    ///
+    /// Defaults to the name of the message's type, and need only be
+    /// implemented by messages which wrap others or report several events.
+    #[inline]
+    fn kind(&self) -> &'static str {
+        let name = core::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Whether this message reports a failed test.
+    ///
+    /// Defaults to `false`, and need only be implemented by tools which run
+    /// tests.
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        false
+    }
+
+    /// Whether this message reports that the tool's output is incomplete
+    /// (e.g., because it ended in the middle of a message).
+    ///
+    /// Defaults to `false`.
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        false
+    }
+
+    /// The key by which this message is ordered relative to the messages of
+    /// concurrent producers (e.g., the name of a test, or the source file of
+    /// the target being compiled).
+    ///
+    /// This is used to order the output deterministically when enabled (see
+    /// [`Pipeline::with_stable_ordering`](crate::pipeline::Pipeline::with_stable_ordering)).
+    /// Defaults to `None`, in which case the message keeps its position.
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        None
+    }
+
+    /// The code identifying the kind of issue reported by this message
+    /// (e.g., a lint such as `clippy::needless_borrow`, or an error code such
+    /// as `E0308`).
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        None
+    }
+
+    /// The lint reported by this message (e.g., `clippy::needless_borrow`),
+    /// which is counted in the [lint
+    /// statistics](crate::pipeline::Pipeline::lint_stats).
+    ///
+    /// Defaults to the [code](Classify::code) of the message.
+    #[inline]
+    fn lint(&self) -> Option<&str> {
+        self.code()
+    }
+
+    /// The fixes of the issue reported by this message which the tool
+    /// considers safe to apply automatically (e.g., rustc's machine-applicable
+    /// suggestions), for inclusion in a SARIF report (see
+    /// [`Pipeline::with_sarif_report`](crate::pipeline::Pipeline::with_sarif_report)).
+    ///
+    /// Defaults to no fixes.
+    #[inline]
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        Vec::new()
+    }
+
+    /// The name of the package (e.g., the crate within a workspace) to which
+    /// the issue reported by this message belongs, by which issues are
+    /// counted and optionally grouped (see
+    /// [`Pipeline::with_crate_groups`](crate::pipeline::Pipeline::with_crate_groups)).
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        None
+    }
+
+    /// The path of the file in which the issue reported by this message lies
+    /// (e.g., the file annotated by a diagnostic), by which issues are
+    /// attributed to the owners of the file (see
+    /// [`Pipeline::with_code_owners`](crate::pipeline::Pipeline::with_code_owners)).
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        None
+    }
+
+    /// The message of the diagnostic reported by this message (e.g., ``unused
+    /// variable: `x` ``), without its location, from which its
+    /// [fingerprint](crate::fingerprint) is computed.
+    ///
+    /// Defaults to `None`, and need only be implemented by messages which
+    /// report diagnostics.
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the build reported by this message succeeded, for messages
+    /// reporting the end of a build (e.g., cargo's `build-finished`), which
+    /// determine the outcome summarized by the
+    /// [pipeline](crate::pipeline::Pipeline::build_succeeded).
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn build_result(&self) -> Option<bool> {
+        None
+    }
+
+    /// The name and path of the binary produced by this message's build step
+    /// (e.g., the executable of a binary target), whose size is tracked if
+    /// enabled (see
+    /// [`Pipeline::with_binary_sizes`](crate::pipeline::Pipeline::with_binary_sizes)).
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn binary(&self) -> Option<(&str, &str)> {
+        None
+    }
+
+    /// The result of the test reported by this message, for inclusion in a
+    /// JUnit report (see
+    /// [`Pipeline::with_junit_report`](crate::pipeline::Pipeline::with_junit_report)).
+    ///
+    /// Defaults to `None`, and need only be implemented by tools which run
+    /// tests.
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        None
+    }
+
+    /// The result of the test suite reported by this message, for inclusion
+    /// in the job summary (see
+    /// [`Pipeline::with_step_summary`](crate::pipeline::Pipeline::with_step_summary)).
+    ///
+    /// Defaults to `None`, and need only be implemented by tools which run
+    /// tests in suites.
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        None
+    }
+
+    /// A short excerpt of the source code to which this message refers, with
+    /// the location highlighted, for outputs which have room for it (e.g., the
+    /// terminal, or the Markdown annotation of
+    /// [`Pipeline::with_buildkite_annotations`](crate::pipeline::Pipeline::with_buildkite_annotations)).
+    ///
+    /// The excerpt spans several lines, each ending with a newline. Defaults
+    /// to `None`, and need only be implemented by messages with a location in
+    /// the source code.
+    #[inline]
+    fn snippet(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Object-safe counterpart to [`CiMessage`].
+///
+/// Whereas [`CiMessage`] selects the platform at compile time, this trait
+/// selects it at runtime through a [`PlatformKind`]. This allows a single
+/// parsed message to be formatted for several platforms without having to
+/// parse the tool output more than once.
+///
+/// This trait is implemented automatically for every type which implements
+/// [`CiMessage`] for all supported platforms, and which implements
+/// [`Classify`], [`Timed`] and [`Captured`]. Messages must be [`Send`], so that
+/// they can be parsed and formatted on separate threads.
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "DynCiMessage mirrors the name of the CiMessage trait"
+)]
+pub trait DynCiMessage: Classify + Timed + Captured + Send {
+    /// Formats this message for the given platform.
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform to format the message for.
+    ///
+    /// # Returns
+    ///
+    /// A formatted string suitable for the given CI platform.
+    fn format_for(&self, platform: PlatformKind) -> String;
+
+    /// Writes this message, formatted for the given platform, to a writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform to format the message for.
+    /// * `out` - The writer to which the formatted message is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `out` fails.
+    fn write_for(&self, platform: PlatformKind, out: &mut dyn fmt::Write) -> fmt::Result;
+}
+
+impl<T> DynCiMessage for T
+where
+    T: CiMessage<Plain>
+        + CiMessage<GitHub>
+        + CiMessage<AzureDevOps>
+        + CiMessage<Buildkite>
+        + CiMessage<CodeBuild>
+        + CiMessage<CloudBuild>
+        + CiMessage<Terminal>
+        + Classify
+        + Timed
+        + Captured
+        + Send,
+{
+    #[inline]
+    fn format_for(&self, platform: PlatformKind) -> String {
+        match platform {
+            PlatformKind::Plain => <T as CiMessage<Plain>>::format(self),
+            PlatformKind::GitHub => <T as CiMessage<GitHub>>::format(self),
+            PlatformKind::AzureDevOps => <T as CiMessage<AzureDevOps>>::format(self),
+            PlatformKind::Buildkite => <T as CiMessage<Buildkite>>::format(self),
+            PlatformKind::CodeBuild => <T as CiMessage<CodeBuild>>::format(self),
+            PlatformKind::CloudBuild => <T as CiMessage<CloudBuild>>::format(self),
+            PlatformKind::Terminal => <T as CiMessage<Terminal>>::format(self),
+        }
+    }
+
+    #[inline]
+    fn write_for(&self, platform: PlatformKind, out: &mut dyn fmt::Write) -> fmt::Result {
+        match platform {
+            PlatformKind::Plain => <T as CiMessage<Plain>>::write_to(self, out),
+            PlatformKind::GitHub => <T as CiMessage<GitHub>>::write_to(self, out),
+            PlatformKind::AzureDevOps => <T as CiMessage<AzureDevOps>>::write_to(self, out),
+            PlatformKind::Buildkite => <T as CiMessage<Buildkite>>::write_to(self, out),
+            PlatformKind::CodeBuild => <T as CiMessage<CodeBuild>>::write_to(self, out),
+            PlatformKind::CloudBuild => <T as CiMessage<CloudBuild>>::write_to(self, out),
+            PlatformKind::Terminal => <T as CiMessage<Terminal>>::write_to(self, out),
+        }
+    }
 }
@@ -0,0 +1,421 @@
+//! Issues in the format of Code Climate.
+//!
+//! Code Climate, and dashboards compatible with its [engine
+//! specification](https://github.com/codeclimate/platform/blob/master/spec/analyzers/SPEC.md)
+//! (e.g., GitLab's code quality reports), read the issues found by an
+//! analyser as JSON objects. This module collects the annotations of the
+//! messages parsed into [`Issues`], which can be written as a JSON array (as
+//! read by GitLab through `artifacts:reports:codequality`), or as the stream
+//! of issues written by an engine, each followed by a null character.
+//!
+//! Each annotation of a message (as formatted for GitHub Actions, so that the
+//! paths are relative to the root of the checkout) becomes an issue at its
+//! location, checked by the message's code (or else the title of the
+//! annotation, or the tool). Its description is the first line of its
+//! message, with the rest of the message as its body. Errors are `major`
+//! issues, warnings `minor` issues, and notices `info` issues. Annotations
+//! which do not refer to a file are skipped, as an issue must have a
+//! location.
+//!
+//! The fingerprint of each issue is the [`Fingerprint`] of the tool, the
+//! code, the file and the message of its annotation, so that an issue keeps
+//! its fingerprint when lines are added before it. As the dashboards expect
+//! each fingerprint to be unique within a report, further occurrences of the
+//! same issue within the same file are distinguished by their number (e.g.,
+//! `0f4c2b5a1d3e6f78-2`).
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use serde::Serialize;
+
+use crate::{
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+    fingerprint::Fingerprint,
+};
+
+/// The severity of an issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum IssueSeverity {
+    /// An informational issue.
+    Info,
+    /// A minor issue.
+    Minor,
+    /// A major issue.
+    Major,
+}
+
+impl From<Severity> for IssueSeverity {
+    #[inline]
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => Self::Major,
+            Severity::Warning => Self::Minor,
+            Severity::Notice | Severity::Debug => Self::Info,
+        }
+    }
+}
+
+/// The extended description of an issue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Content {
+    /// The description, in Markdown.
+    pub body: String,
+}
+
+/// A position within a file, counting lines and columns from one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Position {
+    /// The line of the position.
+    pub line: u32,
+    /// The column of the position.
+    pub column: u32,
+}
+
+/// The span of an issue within its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum Span {
+    /// The lines of the issue, if its columns are not known.
+    Lines {
+        /// The range of lines.
+        lines: LineRange,
+    },
+    /// The positions at which the issue starts and ends.
+    Positions {
+        /// The range of positions.
+        positions: PositionRange,
+    },
+}
+
+/// A range of lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct LineRange {
+    /// The first line.
+    pub begin: u32,
+    /// The last line.
+    pub end: u32,
+}
+
+/// A range of positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct PositionRange {
+    /// The first position.
+    pub begin: Position,
+    /// The last position.
+    pub end: Position,
+}
+
+/// The location of an issue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Location {
+    /// The path of the file, relative to the root of the checkout.
+    pub path: String,
+    /// The span of the issue within the file.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+/// An issue in the format of Code Climate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Issue {
+    /// The type of the object, which is always `issue`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// The name of the check which found the issue (e.g.,
+    /// `unused_variables`).
+    pub check_name: String,
+    /// The description of the issue: the first line of the message of its
+    /// annotation, preceded by its title unless it only restates the check.
+    pub description: String,
+    /// The rest of the message of the annotation, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Content>,
+    /// The categories of the issue, which are always `Bug Risk`.
+    pub categories: [&'static str; 1],
+    /// The location of the issue.
+    pub location: Location,
+    /// The severity of the issue.
+    pub severity: IssueSeverity,
+    /// The fingerprint identifying the issue across runs.
+    pub fingerprint: String,
+}
+
+impl Issue {
+    /// Convert an annotation workflow command.
+    ///
+    /// # Arguments
+    ///
+    /// * `annotation` - The annotation.
+    /// * `tool` - The name of the tool which reported the annotation.
+    /// * `code` - The code of the message annotated, if any.
+    ///
+    /// # Returns
+    ///
+    /// The issue, without its fingerprint, or `None` if the annotation does
+    /// not refer to a file.
+    fn from_workflow(
+        annotation: &WorkflowAnnotation,
+        tool: &str,
+        code: Option<&str>,
+    ) -> Option<Self> {
+        let path = annotation.param("file")?.into_owned();
+        let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+        let begin = number("line").unwrap_or(1);
+        let end = number("endLine").unwrap_or(begin).max(begin);
+        let span = match number("col") {
+            Some(column) => Span::Positions {
+                positions: PositionRange {
+                    begin: Position {
+                        line: begin,
+                        column,
+                    },
+                    end: Position {
+                        line: end,
+                        column: number("endColumn").unwrap_or(column),
+                    },
+                },
+            },
+            None => Span::Lines {
+                lines: LineRange { begin, end },
+            },
+        };
+
+        let title = annotation.param("title");
+        let check_name = code.or(title.as_deref()).unwrap_or(tool).to_owned();
+        let message = annotation.message();
+        let (first, rest) = message.split_once('\n').unwrap_or((&message, ""));
+        // The title is omitted when it only restates the check (e.g.,
+        // `warning: unused_variables`).
+        let description = match &title {
+            Some(text) if !text.contains(&check_name) => format!("{text}: {first}"),
+            Some(_) | None => first.to_owned(),
+        };
+        let body = rest.trim();
+        Some(Self {
+            kind: "issue",
+            check_name,
+            description,
+            content: (!body.is_empty()).then(|| Content {
+                body: body.to_owned(),
+            }),
+            categories: ["Bug Risk"],
+            location: Location { path, span },
+            severity: annotation.severity().into(),
+            fingerprint: String::new(),
+        })
+    }
+}
+
+/// The issues collected from the messages of a tool.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::codeclimate::Issues;
+///
+/// let issues = Issues::new("cargo-check");
+///
+/// let mut json = Vec::new();
+/// issues.write_json(&mut json).expect("writing to a Vec cannot fail");
+/// assert_eq!(json, b"[]");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Issues {
+    /// The name of the tool which reported the issues.
+    tool: String,
+    /// The issues, in the order in which they were recorded.
+    recorded: Vec<Issue>,
+    /// The number of occurrences of each fingerprint so far.
+    occurrences: HashMap<Fingerprint, usize>,
+}
+
+impl Issues {
+    /// Create an empty collection of issues.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool reporting the issues.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            recorded: Vec::new(),
+            occurrences: HashMap::new(),
+        }
+    }
+
+    /// Record the annotations of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, whose annotations when formatted for
+    ///   GitHub Actions are recorded.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        let formatted = message.format_for(PlatformKind::GitHub);
+        let rule = message.code().unwrap_or_else(|| message.kind());
+        for annotation in formatted.lines().filter_map(WorkflowAnnotation::parse) {
+            let Some(mut issue) = Issue::from_workflow(&annotation, &self.tool, message.code())
+            else {
+                continue;
+            };
+            let fingerprint = Fingerprint::new(
+                &self.tool,
+                rule,
+                Some(&issue.location.path),
+                &annotation.message(),
+            );
+            let count = self.occurrences.entry(fingerprint).or_default();
+            *count = count.saturating_add(1);
+            issue.fingerprint = if *count == 1 {
+                fingerprint.to_string()
+            } else {
+                format!("{fingerprint}-{count}")
+            };
+            self.recorded.push(issue);
+        }
+    }
+
+    /// The number of issues recorded.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.recorded.len()
+    }
+
+    /// Whether no issues have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.recorded.is_empty()
+    }
+
+    /// The issues recorded, in the order in which they were recorded.
+    #[must_use]
+    #[inline]
+    pub fn issues(&self) -> &[Issue] {
+        &self.recorded
+    }
+
+    /// Write the issues as a JSON array, as read by GitLab's code quality
+    /// reports.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be written.
+    #[inline]
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, &self.recorded)
+    }
+
+    /// Write the issues as the output of a Code Climate engine: each issue
+    /// as JSON, followed by a null character.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `writer` fails.
+    #[inline]
+    pub fn write_engine(&self, mut writer: impl Write) -> io::Result<()> {
+        for issue in &self.recorded {
+            serde_json::to_writer(&mut writer, issue)?;
+            writer.write_all(b"\0")?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Issues;
+    use crate::tool::{CargoCheck, Tool as _};
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+"#;
+
+    #[test]
+    fn write_json() {
+        let mut tool = CargoCheck::default();
+        let mut messages = tool.parse(WARNING.as_bytes());
+        messages.extend(tool.finish());
+        let mut issues = Issues::new("cargo-check");
+        for message in messages {
+            issues.record(&message.expect("valid message"));
+        }
+        assert_eq!(issues.len(), 1);
+
+        let mut json = Vec::new();
+        issues.write_json(&mut json).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&json), @r#"
+        [
+          {
+            "type": "issue",
+            "check_name": "unused_variables",
+            "description": "unused variable: `x`",
+            "categories": [
+              "Bug Risk"
+            ],
+            "location": {
+              "path": "src/lib.rs",
+              "positions": {
+                "begin": {
+                  "line": 2,
+                  "column": 9
+                },
+                "end": {
+                  "line": 2,
+                  "column": 10
+                }
+              }
+            },
+            "severity": "minor",
+            "fingerprint": "8ceeb276274fe423"
+          }
+        ]
+        "#);
+    }
+
+    #[test]
+    fn write_engine() {
+        // The same warning, on another line of the same file
+        let input = [
+            WARNING,
+            &WARNING.replace(
+                r#""line_end":2,"line_start":2"#,
+                r#""line_end":5,"line_start":5"#,
+            ),
+        ]
+        .concat();
+        let mut tool = CargoCheck::default();
+        let mut messages = tool.parse(input.as_bytes());
+        messages.extend(tool.finish());
+        let mut issues = Issues::new("cargo-check");
+        for message in messages {
+            issues.record(&message.expect("valid message"));
+        }
+
+        let mut stream = Vec::new();
+        issues.write_engine(&mut stream).expect("write failed");
+        let output = String::from_utf8(stream).expect("valid UTF-8");
+        let lines: Vec<_> = output.split_terminator('\0').collect();
+        insta::assert_snapshot!(lines.join("\n"), @r#"
+        {"type":"issue","check_name":"unused_variables","description":"unused variable: `x`","categories":["Bug Risk"],"location":{"path":"src/lib.rs","positions":{"begin":{"line":2,"column":9},"end":{"line":2,"column":10}}},"severity":"minor","fingerprint":"8ceeb276274fe423"}
+        {"type":"issue","check_name":"unused_variables","description":"unused variable: `x`","categories":["Bug Risk"],"location":{"path":"src/lib.rs","positions":{"begin":{"line":5,"column":9},"end":{"line":5,"column":10}}},"severity":"minor","fingerprint":"8ceeb276274fe423-2"}
+        "#);
+    }
+}
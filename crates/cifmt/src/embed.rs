@@ -0,0 +1,183 @@
+//! Formatting of output captured in full.
+//!
+//! A [`Pipeline`] formats the output of a tool as it is produced, which suits
+//! a tool whose output is piped through `cifmt`. An embedder which has
+//! already captured the output (e.g., a test harness or a build system
+//! running the tool itself) can instead format it in one call, through
+//! [`format_str`] or [`format_reader`], and get back both the formatted text
+//! and a summary of the run:
+//!
+//! ```
+//! use cifmt::{ci::PlatformKind, tool::ToolKind};
+//!
+//! let input = r#"{ "type": "test", "event": "failed", "name": "tests::broken" }"#;
+//! let formatted = cifmt::format_str(input, ToolKind::CargoLibtest, PlatformKind::Plain);
+//! assert!(formatted.output.contains("Test Failed: tests::broken"));
+//! assert_eq!(formatted.summary.failed_tests, ["tests::broken"]);
+//! ```
+//!
+//! The output is formatted with the default settings of the tool and of the
+//! platform. Embedders needing more control (e.g., over the handling of
+//! captured output, or several platforms at once) should drive a
+//! [`Pipeline`] themselves.
+
+use std::io::{self, Read};
+
+use crate::{
+    ci::PlatformKind,
+    history::RunSummary,
+    pipeline::{Pipeline, Target},
+    tool::ToolKind,
+};
+
+/// The output of a tool, formatted for a platform, along with a summary of
+/// the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FormattedOutput {
+    /// The formatted output, as it would be printed by `cifmt format`.
+    pub output: String,
+    /// A summary of the run: the errors and warnings reported, and the tests
+    /// run and failed.
+    pub summary: RunSummary,
+    /// Whether the output of the tool ended prematurely (e.g., a test suite
+    /// which never reported its result).
+    pub incomplete: bool,
+    /// Whether the build succeeded, if the tool reported it.
+    pub build_succeeded: Option<bool>,
+}
+
+/// Format the output of a tool captured in full.
+///
+/// See the [module documentation](self) for details.
+///
+/// # Arguments
+///
+/// * `input` - The output of the tool.
+/// * `tool` - The tool which produced the output.
+/// * `platform` - The platform to format the output for.
+#[must_use]
+#[inline]
+pub fn format_str(input: &str, tool: ToolKind, platform: PlatformKind) -> FormattedOutput {
+    // Reading from and writing to memory cannot fail, and the pipeline
+    // writes no files
+    format_reader(input.as_bytes(), tool, platform)
+        .unwrap_or_else(|error| unreachable!("formatting in memory failed: {error}"))
+}
+
+/// Format the output of a tool read to completion.
+///
+/// This is the counterpart to [`format_str`] for output which is still to be
+/// read (e.g., from a file, or from the pipe of a child process). The output
+/// is read on a separate thread, as with [`Pipeline::run`].
+///
+/// # Arguments
+///
+/// * `reader` - The source of the tool's output.
+/// * `tool` - The tool which produced the output.
+/// * `platform` - The platform to format the output for.
+///
+/// # Errors
+///
+/// Returns an error if reading the output fails.
+#[inline]
+pub fn format_reader(
+    reader: impl Read + Send,
+    tool: ToolKind,
+    platform: PlatformKind,
+) -> io::Result<FormattedOutput> {
+    let mut output = Vec::new();
+    // The results of the tests are collected for the summary
+    let mut pipeline = Pipeline::new(
+        tool.into_any_tool(),
+        vec![Target::new(platform, &mut output)],
+    )
+    .with_junit_report();
+    pipeline.run(reader)?;
+    pipeline.finish()?;
+    let summary = pipeline.run_summary();
+    let incomplete = pipeline.is_incomplete();
+    let build_succeeded = pipeline.build_succeeded();
+    drop(pipeline);
+
+    Ok(FormattedOutput {
+        output: String::from_utf8_lossy(&output).into_owned(),
+        summary,
+        incomplete,
+        build_succeeded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{format_reader, format_str};
+    use crate::{
+        ci::PlatformKind,
+        test_util::{CORPORA, Corpus, render},
+        tool::{Error, ToolKind},
+    };
+
+    /// The recorded output of a tool.
+    fn corpus(tool: ToolKind) -> Corpus {
+        CORPORA
+            .into_iter()
+            .find(|corpus| corpus.tool == tool.name())
+            .expect("no corpus for the tool")
+    }
+
+    #[test]
+    fn kinds() {
+        for kind in ToolKind::ALL {
+            assert_eq!(kind.into_any_tool().name(), kind.name());
+            assert_eq!(kind.to_string().parse::<ToolKind>().ok(), Some(kind));
+        }
+        assert!(matches!(
+            "cargo-nextest".parse::<ToolKind>(),
+            Err(Error::UnknownTool(name)) if name == "cargo-nextest"
+        ));
+    }
+
+    #[test]
+    fn messages() {
+        let miri = corpus(ToolKind::Miri);
+        let formatted = format_str(miri.input, ToolKind::Miri, PlatformKind::GitHub);
+        let rendered = render(&miri, PlatformKind::GitHub).expect("tool not detected");
+        for line in rendered.lines().filter(|line| line.starts_with("::error")) {
+            assert!(formatted.output.contains(line), "missing: {line}");
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let formatted = format_str(
+            corpus(ToolKind::CargoLibtest).input,
+            ToolKind::CargoLibtest,
+            PlatformKind::Plain,
+        );
+        assert_eq!(formatted.summary.tool, "cargo-libtest");
+        assert_eq!(formatted.summary.tests, 4);
+        assert_eq!(formatted.summary.failed_tests, ["tests::test_failing"]);
+        assert!(!formatted.incomplete);
+        assert_eq!(formatted.build_succeeded, None);
+
+        let build = format_str(
+            corpus(ToolKind::CargoCheck).input,
+            ToolKind::CargoCheck,
+            PlatformKind::Plain,
+        );
+        assert!(build.summary.errors > 0);
+        assert_eq!(build.build_succeeded, Some(false));
+    }
+
+    #[test]
+    fn reader() {
+        let input = corpus(ToolKind::Junit).input;
+        let read = format_reader(input.as_bytes(), ToolKind::Junit, PlatformKind::Plain)
+            .expect("reading failed");
+        let formatted = format_str(input, ToolKind::Junit, PlatformKind::Plain);
+        assert_eq!(read.output, formatted.output);
+        assert_eq!(read.summary.failed_tests, formatted.summary.failed_tests);
+    }
+}
@@ -0,0 +1,50 @@
+//! Exit codes of the command-line interface.
+//!
+//! The `cifmt` binary exits with a distinct code for each cause of failure,
+//! so that a CI pipeline can branch on the cause (e.g., retry a step whose
+//! output could not be read, but not one whose build failed):
+//!
+//! | Code | Constant              | Cause                                                        |
+//! | ---- | --------------------- | ------------------------------------------------------------ |
+//! | 0    | [`SUCCESS`]           | The input was formatted (and passed the check, if enabled).  |
+//! | 1    | [`FAILURE`]           | Any other failure (e.g., a catalog which is not valid).      |
+//! | 2    | [`USAGE`]             | The command-line arguments are not valid.                    |
+//! | 3    | [`GATING_FAILURE`]    | The build failed or errors were reported (with `--check`).   |
+//! | 4    | [`PARSE_FAILURE`]     | The tool's output is incomplete (with `--check`).            |
+//! | 5    | [`DETECTION_FAILURE`] | The tool could not be detected from its output.              |
+//! | 6    | [`IO_ERROR`]          | Reading the input, or reading or writing a file, failed.     |
+//! | 7    | [`CHILD_FAILURE`]     | A child process run by cifmt failed.                         |
+//!
+//! The codes are stable: a cause keeps its code across releases, and new
+//! causes are given new codes.
+
+/// The input was formatted, and passed the check if enabled.
+pub const SUCCESS: u8 = 0;
+
+/// A failure with no more specific code (e.g., a catalog or history which is
+/// not valid, or a request to a service which failed).
+pub const FAILURE: u8 = 1;
+
+/// The command-line arguments are not valid, as reported by the argument
+/// parser, or give an option which the tool does not support.
+pub const USAGE: u8 = 2;
+
+/// The check failed because of the diagnostics reported by the tool: the
+/// build failed, or errors were reported.
+pub const GATING_FAILURE: u8 = 3;
+
+/// The check failed because the tool's output could not be parsed entirely
+/// (e.g., it ended in the middle of a message, or the parser panicked).
+pub const PARSE_FAILURE: u8 = 4;
+
+/// No tool format could be detected from the tool's output.
+pub const DETECTION_FAILURE: u8 = 5;
+
+/// Reading the input, or reading or writing a file, failed (including when
+/// no input was received within the idle timeout).
+pub const IO_ERROR: u8 = 6;
+
+/// A child process run by cifmt failed.
+///
+/// Reserved for running a tool as a child process; no command does so yet.
+pub const CHILD_FAILURE: u8 = 7;
@@ -0,0 +1,185 @@
+//! Stable fingerprints of diagnostics.
+//!
+//! A [`Fingerprint`] identifies a diagnostic (e.g., a warning of rustc, or a
+//! file which is not formatted) across runs, so that the same issue can be
+//! recognized in the output of a later run (e.g., to file a single issue for a
+//! persistent failure), or a diagnostic reported several times within a run
+//! can be reported once.
+//!
+//! The fingerprint is the 64-bit [FNV-1a] hash, written as 16 hexadecimal
+//! digits, of:
+//!
+//! 1. the name of the tool (e.g., `cargo-check`);
+//! 2. the rule which reported the diagnostic: its [code] (e.g.,
+//!    `clippy::needless_borrow`), or else the [kind] of the message;
+//! 3. the [path of its file], normalized as in annotations (with forward
+//!    slashes, and relative to the root of the checkout), if any;
+//! 4. the skeleton of its [message]: the message without digits, and with
+//!    each run of whitespace collapsed to a single space.
+//!
+//! # Stability
+//!
+//! The fingerprint of a diagnostic does not depend on its position within
+//! the file, on its severity (e.g., a warning denied by policy), on the
+//! numbers within its message (e.g., a count or a duration), on the platform
+//! on which the tool ran, or on the location of the checkout. It changes if
+//! the file is renamed, or if the wording of the message changes (e.g., with
+//! a new version of the tool). Two diagnostics of the same rule with the same
+//! message in the same file share a fingerprint.
+//!
+//! The inputs and the hash are part of the public interface of cifmt:
+//! fingerprints recorded by one release (e.g., in the body of an issue)
+//! remain valid for later releases, and any change to their computation is a
+//! breaking change.
+//!
+//! [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+//! [code]: crate::ci_message::Classify::code
+//! [kind]: crate::ci_message::Classify::kind
+//! [path of its file]: crate::ci_message::Classify::file
+//! [message]: crate::ci_message::Classify::message
+
+use core::fmt;
+
+use crate::{ci::NormalizedPath, ci_message::Classify};
+
+/// The offset basis of the 64-bit FNV-1a hash.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The prime of the 64-bit FNV-1a hash.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A fingerprint identifying a diagnostic across runs.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Compute the fingerprint of a diagnostic from its parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool which reported the diagnostic.
+    /// * `rule` - The rule which reported the diagnostic (e.g., a lint).
+    /// * `path` - The path of the file of the diagnostic, if any.
+    /// * `message` - The message of the diagnostic.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: &str, rule: &str, path: Option<&str>, message: &str) -> Self {
+        let normalized = path.map(|file| NormalizedPath(file).to_string());
+        let skeleton = skeleton(message);
+        let fields = [tool, rule, normalized.as_deref().unwrap_or_default()];
+        let bytes = fields
+            .into_iter()
+            .flat_map(|field| field.bytes().chain([0]))
+            .chain(skeleton.bytes());
+        Self(hash(bytes))
+    }
+
+    /// Compute the fingerprint of the diagnostic reported by a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool which reported the message.
+    /// * `message` - The message.
+    ///
+    /// # Returns
+    ///
+    /// The fingerprint, or `None` if the message reports no diagnostic (i.e.,
+    /// it has no [message](Classify::message)).
+    #[must_use]
+    #[inline]
+    pub fn of<M: Classify + ?Sized>(tool: &str, message: &M) -> Option<Self> {
+        let text = message.message()?;
+        let rule = message.code().unwrap_or_else(|| message.kind());
+        Some(Self::new(tool, rule, message.file(), text))
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// The 64-bit FNV-1a hash of some bytes.
+pub(crate) fn hash(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The skeleton of a message: the message without digits, and with each run
+/// of whitespace collapsed to a single space.
+fn skeleton(message: &str) -> String {
+    message
+        .split_whitespace()
+        .map(|word| word.replace(|c: char| c.is_ascii_digit(), ""))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::{Fingerprint, hash, skeleton};
+
+    #[test]
+    fn fnv1a() {
+        assert_eq!(hash(*b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(*b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn skeletons() {
+        assert_eq!(
+            skeleton("expected  2 arguments,\nfound 13"),
+            "expected arguments, found"
+        );
+    }
+
+    #[test]
+    fn stable() {
+        let fingerprint = Fingerprint::new(
+            "cargo-check",
+            "unused_variables",
+            Some("src/lib.rs"),
+            "unused variable: `x`",
+        );
+        assert_eq!(fingerprint.to_string(), "8ceeb276274fe423");
+        assert_eq!(
+            fingerprint,
+            Fingerprint::new(
+                "cargo-check",
+                "unused_variables",
+                Some(r".\src\lib.rs"),
+                "unused variable:  `x`"
+            )
+        );
+        assert_ne!(
+            fingerprint,
+            Fingerprint::new(
+                "cargo-check",
+                "unused_variables",
+                Some("src/main.rs"),
+                "unused variable: `x`"
+            )
+        );
+        assert_ne!(
+            fingerprint,
+            Fingerprint::new(
+                "cargo-clippy",
+                "unused_variables",
+                Some("src/lib.rs"),
+                "unused variable: `x`"
+            )
+        );
+        assert_ne!(
+            Fingerprint::new("cargo-check", "a", None, "bc"),
+            Fingerprint::new("cargo-check", "ab", None, "c")
+        );
+    }
+}
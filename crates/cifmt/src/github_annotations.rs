@@ -0,0 +1,242 @@
+//! Annotations for the toolkit of GitHub Actions.
+//!
+//! A step can only create annotations through workflow commands written to
+//! its own log. To create them in another step (e.g., one which is given a
+//! token, or which runs after the job's output has been checked), this module
+//! collects the annotations of the messages parsed into [`Annotations`],
+//! which can be written as a JSON array in the shape of the arguments of the
+//! annotation functions of [`@actions/core`](https://github.com/actions/toolkit/tree/main/packages/core),
+//! as available to the scripts of
+//! [`actions/github-script`](https://github.com/actions/github-script):
+//!
+//! ```yaml
+//! - uses: actions/github-script@v7
+//!   with:
+//!     script: |
+//!       const annotations = require('./annotations.json');
+//!       for (const { level, message, properties } of annotations) {
+//!         core[level](message, properties);
+//!       }
+//! ```
+//!
+//! Each annotation of a message (as formatted for GitHub Actions, so that the
+//! paths are relative to the root of the checkout) becomes an entry with its
+//! `level` (`error`, `warning` or `notice`, the name of the function creating
+//! it), its `message`, and its `properties`: the `title`, `file`,
+//! `startLine`, `endLine`, `startColumn` and `endColumn` of the annotation,
+//! each omitted if unknown. Unlike the other reports, annotations which do
+//! not refer to a file are kept, as GitHub shows them on the run.
+
+use std::{borrow::Cow, io::Write};
+
+use serde::Serialize;
+
+use crate::{
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+};
+
+/// The level of an annotation, named after the function of `@actions/core`
+/// which creates it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Level {
+    /// An error (`core.error`).
+    Error,
+    /// A warning (`core.warning`).
+    Warning,
+    /// A notice (`core.notice`).
+    Notice,
+}
+
+impl From<Severity> for Level {
+    #[inline]
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => Self::Error,
+            Severity::Warning => Self::Warning,
+            Severity::Notice | Severity::Debug => Self::Notice,
+        }
+    }
+}
+
+/// The properties of an annotation, as the `AnnotationProperties` of
+/// `@actions/core`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Properties {
+    /// The title of the annotation, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The path of the annotated file, relative to the root of the checkout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// The first line annotated, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// The last line annotated, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// The first column annotated, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    /// The last column annotated, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+}
+
+/// An annotation, as the arguments of the function of `@actions/core`
+/// creating it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Annotation {
+    /// The level of the annotation.
+    pub level: Level,
+    /// The message of the annotation.
+    pub message: String,
+    /// The properties of the annotation.
+    pub properties: Properties,
+}
+
+impl Annotation {
+    /// Convert an annotation workflow command.
+    fn from_workflow(annotation: &WorkflowAnnotation) -> Self {
+        let param = |name: &str| annotation.param(name).map(Cow::into_owned);
+        let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+        Self {
+            level: annotation.severity().into(),
+            message: annotation.message().into_owned(),
+            properties: Properties {
+                title: param("title"),
+                file: param("file"),
+                start_line: number("line"),
+                end_line: number("endLine"),
+                start_column: number("col"),
+                end_column: number("endColumn"),
+            },
+        }
+    }
+}
+
+/// The annotations of the messages of a tool, for `@actions/core`.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::github_annotations::Annotations;
+///
+/// let annotations = Annotations::default();
+///
+/// let mut json = Vec::new();
+/// annotations
+///     .write_json(&mut json)
+///     .expect("writing to a Vec cannot fail");
+/// assert_eq!(json, b"[]");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotations {
+    /// The annotations, in the order in which they were recorded.
+    recorded: Vec<Annotation>,
+}
+
+impl Annotations {
+    /// Record the annotations of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, whose annotations when formatted for
+    ///   GitHub Actions are recorded.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        let formatted = message.format_for(PlatformKind::GitHub);
+        self.recorded.extend(
+            formatted
+                .lines()
+                .filter_map(WorkflowAnnotation::parse)
+                .map(|annotation| Annotation::from_workflow(&annotation)),
+        );
+    }
+
+    /// The number of annotations recorded.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.recorded.len()
+    }
+
+    /// Whether no annotations have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.recorded.is_empty()
+    }
+
+    /// The annotations recorded, in the order in which they were recorded.
+    #[must_use]
+    #[inline]
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.recorded
+    }
+
+    /// Write the annotations as a JSON array.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be written.
+    #[inline]
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, &self.recorded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Annotations;
+    use crate::tool::{CargoCheck, Tool as _};
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+"#;
+
+    #[test]
+    fn write_json() {
+        let mut tool = CargoCheck::default();
+        let mut messages = tool.parse(WARNING.as_bytes());
+        messages.extend(tool.finish());
+        let mut annotations = Annotations::default();
+        for message in messages {
+            annotations.record(&message.expect("valid message"));
+        }
+        // The warning, and the incomplete build, which has no file.
+        assert_eq!(annotations.len(), 2);
+
+        let mut json = Vec::new();
+        annotations.write_json(&mut json).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&json), @r#"
+        [
+          {
+            "level": "warning",
+            "message": "unused variable: `x`",
+            "properties": {
+              "title": "warning: unused_variables",
+              "file": "src/lib.rs",
+              "startLine": 2,
+              "endLine": 2,
+              "startColumn": 9,
+              "endColumn": 10
+            }
+          },
+          {
+            "level": "warning",
+            "message": "Cargo's output ended without reporting the result of the build",
+            "properties": {
+              "title": "Incomplete Build"
+            }
+          }
+        ]
+        "#);
+    }
+}
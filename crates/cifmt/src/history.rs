@@ -0,0 +1,409 @@
+//! History of the runs of a tool.
+//!
+//! To follow how a project evolves across CI runs, a [`RunSummary`] of each
+//! run (its errors, warnings, failed tests and duration) can be recorded in a
+//! [`History`], stored as JSON between runs (e.g., in a cache or committed
+//! to an artifacts branch, see [`History::write_json`]). A [`Trend`]
+//! compares the latest run against the previous ones, and renders as a
+//! Markdown section listing the new failures, the tests fixed since the
+//! previous run, and the counts and durations of the recent runs.
+//!
+//! ```
+//! use cifmt::history::{History, RunSummary};
+//!
+//! let mut history = History::default();
+//! let mut run = RunSummary::new("cargo-libtest");
+//! run.failed_tests = vec!["tests::flaky".to_owned()];
+//! history.record(run.clone());
+//! run.failed_tests = vec!["tests::broken".to_owned()];
+//! history.record(run);
+//! let trend = history.trend().expect("a run is recorded");
+//! assert_eq!(trend.new_failures(), ["tests::broken"]);
+//! assert_eq!(trend.fixed_tests(), ["tests::flaky"]);
+//! ```
+
+use core::{fmt, time::Duration};
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{markdown::Cell, metadata};
+
+/// A summary of a run of a tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RunSummary {
+    /// The name of the tool.
+    pub tool: String,
+    /// The time at which the run finished, in RFC 3339 format (UTC).
+    pub timestamp: String,
+    /// Number of errors reported.
+    pub errors: usize,
+    /// Number of warnings reported.
+    pub warnings: usize,
+    /// Number of tests run.
+    #[serde(default)]
+    pub tests: usize,
+    /// The names of the tests which failed.
+    #[serde(default)]
+    pub failed_tests: Vec<String>,
+    /// How long the run took, in milliseconds.
+    #[serde(default)]
+    pub duration_ms: u64,
+}
+
+impl RunSummary {
+    /// A summary of a run of a tool which finished now, without any issues
+    /// or tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            timestamp: metadata::now(),
+            errors: 0,
+            warnings: 0,
+            tests: 0,
+            failed_tests: Vec::new(),
+            duration_ms: 0,
+        }
+    }
+
+    /// How long the run took.
+    #[must_use]
+    #[inline]
+    pub const fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+/// The summaries of the runs of a tool, from the oldest to the latest.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct History {
+    /// The summary of each run, from the oldest to the latest.
+    runs: Vec<RunSummary>,
+}
+
+impl History {
+    /// The maximum number of runs kept; older runs are dropped as new ones
+    /// are recorded.
+    pub const MAX_RUNS: usize = 100;
+
+    /// Record the summary of a run, dropping the oldest runs beyond
+    /// [`History::MAX_RUNS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The summary of the latest run.
+    #[inline]
+    pub fn record(&mut self, run: RunSummary) {
+        self.runs.push(run);
+        let excess = self.runs.len().saturating_sub(Self::MAX_RUNS);
+        self.runs.drain(..excess);
+    }
+
+    /// The summaries of the runs recorded, from the oldest to the latest.
+    #[must_use]
+    #[inline]
+    pub fn runs(&self) -> &[RunSummary] {
+        &self.runs
+    }
+
+    /// The latest run compared against the previous ones, if any run is
+    /// recorded.
+    #[must_use]
+    #[inline]
+    pub fn trend(&self) -> Option<Trend<'_>> {
+        let (current, previous) = self.runs.split_last()?;
+        Some(Trend { current, previous })
+    }
+
+    /// Read a history stored as a JSON array of run summaries, from the
+    /// oldest to the latest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read, or is not such an
+    /// array.
+    #[inline]
+    pub fn read_json(reader: impl Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Write the history as a JSON array of run summaries, as read by
+    /// [`History::read_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be written.
+    #[inline]
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+}
+
+/// The latest run of a tool, compared against the previous runs.
+///
+/// The comparison is rendered as a Markdown section through its
+/// [`Display`](fmt::Display) implementation.
+#[derive(Debug, Clone, Copy)]
+pub struct Trend<'a> {
+    /// The latest run.
+    current: &'a RunSummary,
+    /// The previous runs, from the oldest to the latest.
+    previous: &'a [RunSummary],
+}
+
+impl<'a> Trend<'a> {
+    /// The maximum number of recent runs whose counts and durations are
+    /// listed.
+    pub const MAX_RECENT_RUNS: usize = 10;
+
+    /// The maximum number of new failures or fixed tests listed.
+    pub const MAX_TESTS: usize = 50;
+
+    /// The latest run.
+    #[must_use]
+    #[inline]
+    pub const fn current(&self) -> &'a RunSummary {
+        self.current
+    }
+
+    /// The run preceding the latest, if any.
+    #[must_use]
+    #[inline]
+    pub fn previous(&self) -> Option<&'a RunSummary> {
+        self.previous.last()
+    }
+
+    /// The tests which failed in the latest run but not in the previous
+    /// one, in the order in which they failed.
+    ///
+    /// Without a previous run, every failed test is new.
+    #[must_use]
+    #[inline]
+    pub fn new_failures(&self) -> Vec<&'a str> {
+        let before = self.previous().map_or(&[][..], |run| &run.failed_tests);
+        self.current
+            .failed_tests
+            .iter()
+            .filter(|name| !before.contains(name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The tests which failed in the previous run but not in the latest
+    /// one, in the order in which they failed.
+    #[must_use]
+    #[inline]
+    pub fn fixed_tests(&self) -> Vec<&'a str> {
+        let after = &self.current.failed_tests;
+        self.previous()
+            .map(|run| {
+                run.failed_tests
+                    .iter()
+                    .filter(|name| !after.contains(name))
+                    .map(String::as_str)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The recent runs, from the oldest to the latest.
+    fn recent(&self) -> impl Iterator<Item = &'a RunSummary> {
+        let skipped = self
+            .previous
+            .len()
+            .saturating_sub(Self::MAX_RECENT_RUNS.saturating_sub(1));
+        self.previous
+            .iter()
+            .skip(skipped)
+            .chain(core::iter::once(self.current))
+    }
+
+    /// Write a row of the table of counts, with the change since the
+    /// previous run and the values of the recent runs.
+    fn write_count(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        label: &str,
+        count: impl Fn(&RunSummary) -> usize,
+    ) -> fmt::Result {
+        let current = count(self.current);
+        write!(f, "| {label} | {current} | ")?;
+        match self.previous().map(&count) {
+            Some(previous) if current > previous => {
+                write!(f, "+{}", current.saturating_sub(previous))?;
+            }
+            Some(previous) if current < previous => {
+                write!(f, "-{}", previous.saturating_sub(current))?;
+            }
+            Some(_) => write!(f, "0")?,
+            None => write!(f, "-")?,
+        }
+        let recent: Vec<_> = self.recent().map(|run| count(run).to_string()).collect();
+        writeln!(f, " | {} |", recent.join(" → "))
+    }
+
+    /// Write a list of tests, under a heading.
+    fn write_tests(f: &mut fmt::Formatter<'_>, heading: &str, tests: &[&str]) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "#### {heading}")?;
+        writeln!(f)?;
+        for name in tests.iter().take(Self::MAX_TESTS) {
+            writeln!(f, "- `{}`", Cell(name))?;
+        }
+        let omitted = tests.len().saturating_sub(Self::MAX_TESTS);
+        if omitted > 0 {
+            writeln!(f)?;
+            writeln!(f, "{omitted} more test(s) omitted.")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Trend<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#### Trend")?;
+        writeln!(f)?;
+        if self.previous.is_empty() {
+            writeln!(f, "No previous run to compare with.")?;
+            return Ok(());
+        }
+        writeln!(
+            f,
+            "Compared with {} previous run(s), the latest on {}.",
+            self.previous.len(),
+            self.previous().map_or("", |run| run.timestamp.as_str())
+        )?;
+        writeln!(f)?;
+        writeln!(f, "| Measure | This run | Change | Recent runs |")?;
+        writeln!(f, "| :------ | -------: | -----: | :---------- |")?;
+        self.write_count(f, "Errors", |run| run.errors)?;
+        self.write_count(f, "Warnings", |run| run.warnings)?;
+        self.write_count(f, "Tests", |run| run.tests)?;
+        self.write_count(f, "Failed tests", |run| run.failed_tests.len())?;
+        let durations: Vec<_> = self
+            .recent()
+            .map(|run| format!("{:.1}s", run.duration().as_secs_f64()))
+            .collect();
+        writeln!(
+            f,
+            "| Duration | {:.1}s | | {} |",
+            self.current.duration().as_secs_f64(),
+            durations.join(" → ")
+        )?;
+
+        let new_failures = self.new_failures();
+        if !new_failures.is_empty() {
+            Self::write_tests(f, "New failures", &new_failures)?;
+        }
+        let fixed = self.fixed_tests();
+        if !fixed.is_empty() {
+            Self::write_tests(f, "Fixed tests", &fixed)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{History, RunSummary};
+
+    /// A summary of a run at the given time.
+    fn run(timestamp: &str, errors: usize, failed: &[&str], duration_ms: u64) -> RunSummary {
+        RunSummary {
+            timestamp: timestamp.to_owned(),
+            errors,
+            warnings: 2,
+            tests: 10,
+            failed_tests: failed.iter().map(|&name| name.to_owned()).collect(),
+            duration_ms,
+            ..RunSummary::new("cargo-libtest")
+        }
+    }
+
+    #[test]
+    fn record() {
+        let mut history = History::default();
+        for _ in 0..History::MAX_RUNS {
+            history.record(run("2026-10-14T12:00:00Z", 0, &[], 0));
+        }
+        history.record(run("2026-10-15T12:00:00Z", 1, &[], 0));
+        assert_eq!(history.runs().len(), History::MAX_RUNS);
+        assert_eq!(
+            history.runs().last().map(|run| run.timestamp.as_str()),
+            Some("2026-10-15T12:00:00Z")
+        );
+
+        let mut json = Vec::new();
+        history.write_json(&mut json).expect("written");
+        assert_eq!(
+            History::read_json(json.as_slice()).expect("valid history"),
+            history
+        );
+        History::read_json(r#"{"runs": 1}"#.as_bytes()).expect_err("not an array");
+    }
+
+    #[test]
+    fn render() {
+        let mut history = History::default();
+        history.record(run("2026-10-13T12:00:00Z", 0, &[], 1200));
+        history.record(run(
+            "2026-10-14T12:00:00Z",
+            2,
+            &["tests::a", "tests::b"],
+            1500,
+        ));
+        history.record(run(
+            "2026-10-15T12:00:00Z",
+            1,
+            &["tests::b", "tests::c"],
+            1350,
+        ));
+        let trend = history.trend().expect("a run is recorded");
+        insta::assert_snapshot!(trend.to_string(), @"
+        #### Trend
+
+        Compared with 2 previous run(s), the latest on 2026-10-14T12:00:00Z.
+
+        | Measure | This run | Change | Recent runs |
+        | :------ | -------: | -----: | :---------- |
+        | Errors | 1 | -1 | 0 → 2 → 1 |
+        | Warnings | 2 | 0 | 2 → 2 → 2 |
+        | Tests | 10 | 0 | 10 → 10 → 10 |
+        | Failed tests | 2 | 0 | 0 → 2 → 2 |
+        | Duration | 1.4s | | 1.2s → 1.5s → 1.4s |
+
+        #### New failures
+
+        - `tests::c`
+
+        #### Fixed tests
+
+        - `tests::a`
+        ");
+
+        let mut first = History::default();
+        first.record(run("2026-10-13T12:00:00Z", 0, &["tests::a"], 1200));
+        let alone = first.trend().expect("a run is recorded");
+        assert_eq!(alone.new_failures(), ["tests::a"]);
+        insta::assert_snapshot!(alone.to_string(), @"
+        #### Trend
+
+        No previous run to compare with.
+        ");
+
+        assert!(History::default().trend().is_none());
+    }
+}
@@ -0,0 +1,473 @@
+//! Self-contained HTML reports.
+//!
+//! The log of a CI job is hard to browse once a run reports many issues or
+//! tests. This module collects the annotations and the test results of the
+//! messages parsed into a [`Report`], which can be written as a single HTML
+//! file, without any external resource, to be uploaded as an artifact of the
+//! job and opened in a browser.
+//!
+//! The report starts with the number of issues of each severity and of tests
+//! of each outcome, followed by the annotations grouped by file (with those
+//! which do not refer to a file last), and the tests in the order in which
+//! they were run. Each file and each test is a collapsible section, and the
+//! report can be filtered by severity, by outcome and by text. Failed tests
+//! are expanded, with their output in full.
+//!
+//! Each annotation is taken from the message as formatted for GitHub Actions,
+//! so that the paths are relative to the root of the checkout.
+
+use core::time::Duration;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::{
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+    junit::{Escaped, TestOutcome},
+};
+
+/// The style of the report.
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em; color: #1f2328; }
+h1 { font-size: 1.5em; }
+.counts span { margin-right: 1.5em; }
+.filters { margin: 1em 0; display: flex; gap: 1em; flex-wrap: wrap; align-items: center; }
+details { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.5em 0; padding: 0.5em 1em; }
+summary { cursor: pointer; font-weight: 600; }
+table { border-collapse: collapse; width: 100%; margin-top: 0.5em; }
+td { border-top: 1px solid #d0d7de; padding: 0.25em 0.5em; vertical-align: top; }
+td.position { white-space: nowrap; font-family: monospace; }
+pre { white-space: pre-wrap; margin: 0; font-family: monospace; }
+.badge { border-radius: 1em; padding: 0 0.6em; color: #fff; font-size: 0.85em; }
+.error, .failed { background: #cf222e; }
+.warning { background: #9a6700; }
+.notice, .skipped { background: #57606a; }
+.passed { background: #1a7f37; }
+[hidden] { display: none; }
+";
+
+/// The script filtering the report.
+const SCRIPT: &str = r#"
+const filters = document.querySelectorAll(".filters input");
+function apply() {
+  const shown = new Set([...filters].filter((box) => box.checked).map((box) => box.value));
+  const text = document.getElementById("search").value.toLowerCase();
+  for (const item of document.querySelectorAll("[data-kind]")) {
+    item.hidden = !shown.has(item.dataset.kind) || !item.textContent.toLowerCase().includes(text);
+  }
+  for (const file of document.querySelectorAll("details.file")) {
+    file.hidden = !file.querySelector("tr:not([hidden])");
+  }
+}
+for (const input of filters) {
+  input.addEventListener("input", apply);
+}
+"#;
+
+/// An annotation within a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Annotation {
+    /// The line of the annotation, if known.
+    line: Option<u32>,
+    /// The column of the annotation, if known.
+    column: Option<u32>,
+    /// The severity of the annotation.
+    severity: Severity,
+    /// The title of the annotation, if any.
+    title: Option<String>,
+    /// The message of the annotation.
+    message: String,
+}
+
+/// A test within a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Test {
+    /// The full name of the test.
+    name: String,
+    /// The outcome of the test.
+    outcome: TestOutcome,
+    /// How long the test took to run, if known.
+    duration: Option<Duration>,
+    /// A message explaining the outcome, if any.
+    message: Option<String>,
+    /// The output captured while the test ran, if any.
+    output: Option<String>,
+}
+
+/// The name of a severity, as the class and filter of its annotations.
+const fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Notice | Severity::Debug => "notice",
+    }
+}
+
+/// The name of an outcome, as the class and filter of its tests.
+const fn outcome_name(outcome: TestOutcome) -> &'static str {
+    match outcome {
+        TestOutcome::Passed => "passed",
+        TestOutcome::Failed => "failed",
+        TestOutcome::Skipped => "skipped",
+    }
+}
+
+/// An HTML report of the annotations and test results of a tool.
+///
+/// See the [module documentation](self) for details.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::html::Report;
+///
+/// let report = Report::new("cargo-libtest");
+///
+/// let mut html = Vec::new();
+/// report.write_html(&mut html).expect("writing to a Vec cannot fail");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// The name of the tool which reported the messages.
+    tool: String,
+    /// The annotations of each file, by the path of the file.
+    files: BTreeMap<String, Vec<Annotation>>,
+    /// The annotations which do not refer to a file.
+    general: Vec<Annotation>,
+    /// The tests, in the order in which they were recorded.
+    tests: Vec<Test>,
+}
+
+impl Report {
+    /// Create an empty report.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool reporting the messages.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            files: BTreeMap::new(),
+            general: Vec::new(),
+            tests: Vec::new(),
+        }
+    }
+
+    /// Record the annotations of a message, and the result of the test it
+    /// reports, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, whose annotations when formatted for
+    ///   GitHub Actions are recorded.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        if let Some(result) = message.test_result() {
+            self.tests.push(Test {
+                name: result.name.to_owned(),
+                outcome: result.outcome,
+                duration: result.duration,
+                message: result.message.map(str::to_owned),
+                output: result.output.map(str::to_owned),
+            });
+            // The annotations of a test only restate its result.
+            return;
+        }
+        let formatted = message.format_for(PlatformKind::GitHub);
+        for annotation in formatted.lines().filter_map(WorkflowAnnotation::parse) {
+            let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+            let recorded = Annotation {
+                line: number("line"),
+                column: number("col"),
+                severity: annotation.severity(),
+                title: annotation.param("title").map(Cow::into_owned),
+                message: annotation.message().into_owned(),
+            };
+            match annotation.param("file") {
+                Some(path) => self
+                    .files
+                    .entry(path.into_owned())
+                    .or_default()
+                    .push(recorded),
+                None => self.general.push(recorded),
+            }
+        }
+    }
+
+    /// The number of annotations recorded.
+    #[must_use]
+    #[inline]
+    pub fn annotations(&self) -> usize {
+        self.files
+            .values()
+            .map(Vec::len)
+            .sum::<usize>()
+            .saturating_add(self.general.len())
+    }
+
+    /// The number of tests recorded.
+    #[must_use]
+    #[inline]
+    pub fn tests(&self) -> usize {
+        self.tests.len()
+    }
+
+    /// Write the report as a self-contained HTML document.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    #[inline]
+    pub fn write_html(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, r#"<html lang="en">"#)?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, r#"<meta charset="utf-8">"#)?;
+        writeln!(
+            writer,
+            "<title>cifmt report: {}</title>",
+            Escaped(&self.tool)
+        )?;
+        writeln!(writer, "<style>{STYLE}</style>")?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<h1>cifmt report: {}</h1>", Escaped(&self.tool))?;
+        self.write_counts(&mut writer)?;
+        self.write_filters(&mut writer)?;
+
+        if !self.files.is_empty() || !self.general.is_empty() {
+            writeln!(writer, "<h2>Issues</h2>")?;
+            for (path, annotations) in &self.files {
+                write_file(&mut writer, path, annotations)?;
+            }
+            if !self.general.is_empty() {
+                write_file(&mut writer, "Other", &self.general)?;
+            }
+        }
+        if !self.tests.is_empty() {
+            writeln!(writer, "<h2>Tests</h2>")?;
+            for test in &self.tests {
+                write_test(&mut writer, test)?;
+            }
+        }
+
+        writeln!(writer, "<script>{SCRIPT}</script>")?;
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
+        writer.flush()
+    }
+
+    /// Write the number of annotations of each severity and of tests of each
+    /// outcome.
+    fn write_counts(&self, writer: &mut impl Write) -> io::Result<()> {
+        let all = || self.files.values().flatten().chain(&self.general);
+        let severities = [Severity::Error, Severity::Warning, Severity::Notice].map(|severity| {
+            let count = all()
+                .filter(|annotation| severity_name(annotation.severity) == severity_name(severity))
+                .count();
+            (severity_name(severity), count)
+        });
+        let outcomes = [
+            TestOutcome::Passed,
+            TestOutcome::Failed,
+            TestOutcome::Skipped,
+        ]
+        .map(|outcome| {
+            let count = self
+                .tests
+                .iter()
+                .filter(|test| test.outcome == outcome)
+                .count();
+            (outcome_name(outcome), count)
+        });
+
+        write!(writer, r#"<p class="counts">"#)?;
+        for (name, count) in severities.into_iter().chain(outcomes) {
+            if count > 0 {
+                write!(
+                    writer,
+                    r#"<span><span class="badge {name}">{name}</span> {count}</span>"#
+                )?;
+            }
+        }
+        writeln!(writer, "</p>")
+    }
+
+    /// Write the controls filtering the report.
+    fn write_filters(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut kinds = Vec::new();
+        if !self.files.is_empty() || !self.general.is_empty() {
+            kinds.extend(["error", "warning", "notice"]);
+        }
+        if !self.tests.is_empty() {
+            kinds.extend(["passed", "failed", "skipped"]);
+        }
+        write!(writer, r#"<div class="filters">"#)?;
+        for kind in kinds {
+            write!(
+                writer,
+                r#"<label><input type="checkbox" value="{kind}" checked> {kind}</label>"#
+            )?;
+        }
+        writeln!(
+            writer,
+            r#"<input id="search" type="search" placeholder="Filter"></div>"#
+        )
+    }
+}
+
+/// Write the annotations of a file, as a collapsible section.
+fn write_file(writer: &mut impl Write, path: &str, annotations: &[Annotation]) -> io::Result<()> {
+    writeln!(writer, r#"<details class="file" open>"#)?;
+    writeln!(
+        writer,
+        "<summary>{} ({})</summary>",
+        Escaped(path),
+        annotations.len()
+    )?;
+    writeln!(writer, "<table>")?;
+    for annotation in annotations {
+        let severity = severity_name(annotation.severity);
+        let position = match (annotation.line, annotation.column) {
+            (Some(line), Some(column)) => format!("{line}:{column}"),
+            (Some(line), None) => line.to_string(),
+            (None, _) => String::new(),
+        };
+        write!(
+            writer,
+            r#"<tr data-kind="{severity}"><td><span class="badge {severity}">{severity}</span></td><td class="position">{position}</td><td>"#
+        )?;
+        if let Some(title) = &annotation.title {
+            write!(writer, "<strong>{}</strong>", Escaped(title))?;
+        }
+        writeln!(
+            writer,
+            "<pre>{}</pre></td></tr>",
+            Escaped(&annotation.message)
+        )?;
+    }
+    writeln!(writer, "</table>")?;
+    writeln!(writer, "</details>")
+}
+
+/// Write a test, as a collapsible section expanded if it failed.
+fn write_test(writer: &mut impl Write, test: &Test) -> io::Result<()> {
+    let outcome = outcome_name(test.outcome);
+    let open = if test.outcome == TestOutcome::Failed {
+        " open"
+    } else {
+        ""
+    };
+    writeln!(writer, r#"<details data-kind="{outcome}"{open}>"#)?;
+    write!(
+        writer,
+        r#"<summary><span class="badge {outcome}">{outcome}</span> {}"#,
+        Escaped(&test.name)
+    )?;
+    if let Some(duration) = test.duration {
+        write!(writer, " ({:.3}s)", duration.as_secs_f64())?;
+    }
+    writeln!(writer, "</summary>")?;
+    if let Some(message) = &test.message {
+        writeln!(writer, "<p>{}</p>", Escaped(message))?;
+    }
+    if let Some(output) = test.output.as_deref().filter(|output| !output.is_empty()) {
+        writeln!(writer, "<pre>{}</pre>", Escaped(output))?;
+    }
+    writeln!(writer, "</details>")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Report;
+    use crate::tool::{CargoCheck, CargoLibtest, Tool as _};
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x` <T>","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+"#;
+
+    const TESTS: &str = concat!(
+        r#"{"type":"test","event":"ok","name":"tests::a","exec_time":0.5}"#,
+        "\n",
+        r#"{"type":"test","event":"failed","name":"tests::b","stdout":"assertion failed: 1 < 2\n"}"#,
+        "\n",
+    );
+
+    /// The body of the report, without its style and script.
+    fn body(report: &Report) -> String {
+        let mut html = Vec::new();
+        report.write_html(&mut html).expect("write failed");
+        let document = String::from_utf8(html).expect("valid UTF-8");
+        let start = document.find("<body>").expect("body");
+        let end = document.find("<script>").expect("script");
+        document.get(start..end).expect("valid range").to_owned()
+    }
+
+    #[test]
+    fn annotations() {
+        let mut tool = CargoCheck::default();
+        let mut messages = tool.parse(WARNING.as_bytes());
+        messages.extend(tool.finish());
+        let mut report = Report::new("cargo-check");
+        for message in messages {
+            report.record(&message.expect("valid message"));
+        }
+        assert_eq!(report.annotations(), 2);
+        insta::assert_snapshot!(body(&report), @r#"
+        <body>
+        <h1>cifmt report: cargo-check</h1>
+        <p class="counts"><span><span class="badge warning">warning</span> 2</span></p>
+        <div class="filters"><label><input type="checkbox" value="error" checked> error</label><label><input type="checkbox" value="warning" checked> warning</label><label><input type="checkbox" value="notice" checked> notice</label><input id="search" type="search" placeholder="Filter"></div>
+        <h2>Issues</h2>
+        <details class="file" open>
+        <summary>src/lib.rs (1)</summary>
+        <table>
+        <tr data-kind="warning"><td><span class="badge warning">warning</span></td><td class="position">2:9</td><td><strong>warning: unused_variables</strong><pre>unused variable: `x` &lt;T&gt;</pre></td></tr>
+        </table>
+        </details>
+        <details class="file" open>
+        <summary>Other (1)</summary>
+        <table>
+        <tr data-kind="warning"><td><span class="badge warning">warning</span></td><td class="position"></td><td><strong>Incomplete Build</strong><pre>Cargo&apos;s output ended without reporting the result of the build</pre></td></tr>
+        </table>
+        </details>
+        "#);
+    }
+
+    #[test]
+    fn tests() {
+        let mut tool = CargoLibtest::default();
+        let mut messages = tool.parse(TESTS.as_bytes());
+        messages.extend(tool.finish());
+        let mut report = Report::new("cargo-libtest");
+        for message in messages {
+            report.record(&message.expect("valid message"));
+        }
+        assert_eq!(report.tests(), 2);
+        insta::assert_snapshot!(body(&report), @r#"
+        <body>
+        <h1>cifmt report: cargo-libtest</h1>
+        <p class="counts"><span><span class="badge passed">passed</span> 1</span><span><span class="badge failed">failed</span> 1</span></p>
+        <div class="filters"><label><input type="checkbox" value="passed" checked> passed</label><label><input type="checkbox" value="failed" checked> failed</label><label><input type="checkbox" value="skipped" checked> skipped</label><input id="search" type="search" placeholder="Filter"></div>
+        <h2>Tests</h2>
+        <details data-kind="passed">
+        <summary><span class="badge passed">passed</span> tests::a (0.500s)</summary>
+        </details>
+        <details data-kind="failed" open>
+        <summary><span class="badge failed">failed</span> tests::b</summary>
+        <pre>assertion failed: 1 &lt; 2
+        </pre>
+        </details>
+        "#);
+    }
+}
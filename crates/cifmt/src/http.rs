@@ -0,0 +1,35 @@
+//! The HTTP agent shared by the integrations with web services (the check
+//! runs, issues, notifications and review comments).
+//!
+//! These services are called once the tool's output is formatted, so a
+//! service which does not respond must not hold up the job until the CI
+//! platform cancels it. Every request made through [`agent`] therefore fails
+//! after [`TIMEOUT`].
+
+use core::time::Duration;
+
+use ureq::Agent;
+
+/// The longest a request may take in total, from resolving the host to
+/// reading the body of the response.
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Create an agent whose requests fail after [`TIMEOUT`].
+pub(crate) fn agent() -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .new_agent()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{TIMEOUT, agent};
+
+    #[test]
+    fn timeout() {
+        assert_eq!(agent().config().timeouts().global, Some(TIMEOUT));
+    }
+}
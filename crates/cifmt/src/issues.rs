@@ -0,0 +1,740 @@
+//! Issues filed for failures on GitHub or GitLab.
+//!
+//! Failures of scheduled jobs (e.g., a nightly build against the latest
+//! toolchain) are easily lost in logs nobody reads. An [`IssueFiler`] (see
+//! [`Pipeline::with_issue_filer`]) opens an issue for each error and failed
+//! test of the run, with its details formatted as plain text, through the
+//! REST API of [GitHub](https://docs.github.com/en/rest/issues) or
+//! [GitLab](https://docs.gitlab.com/api/issues/).
+//!
+//! Each [`Failure`] is identified by a fingerprint, which is recorded in the
+//! body of its issue. A failure whose fingerprint is found in an open issue
+//! with the filer's label is added to that issue as a comment instead, so that
+//! a persistent failure is tracked by a single issue. The fingerprint of an
+//! error is that of its diagnostic (see [`fingerprint`](crate::fingerprint)),
+//! and otherwise ignores digits (e.g., line numbers and durations), which
+//! change between runs of the same failure.
+//!
+//! Filing is opt-in, and may be restricted to failures matching some patterns
+//! (see [`IssueFiler::with_patterns`]).
+//!
+//! This module requires the `issues` feature.
+//!
+//! [`Pipeline::with_issue_filer`]: crate::pipeline::Pipeline::with_issue_filer
+
+use core::fmt;
+use std::{collections::HashSet, env};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use ureq::{Agent, RequestBuilder};
+
+use crate::{
+    catalog::Title,
+    ci::PlatformKind,
+    ci_message::{DynCiMessage, Severity},
+    fingerprint::Fingerprint,
+    http,
+    owners::matches_glob,
+};
+
+/// A failure of the run, for which an issue is filed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Failure {
+    /// The fingerprint identifying the failure across runs.
+    pub fingerprint: String,
+    /// The title of the failure (e.g., `Test Failed: tests::it_works`).
+    pub title: String,
+    /// The details of the failure, formatted as plain text.
+    pub details: String,
+}
+
+impl Failure {
+    /// The maximum length of the title, in bytes.
+    pub const MAX_TITLE: usize = 200;
+
+    /// The maximum length of the details, in bytes.
+    pub const MAX_DETAILS: usize = 16 * 1024;
+
+    /// Describe a failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The text identifying the failure, from which the fingerprint
+    ///   is computed. Digits are ignored.
+    /// * `title` - The title of the failure.
+    /// * `details` - The details of the failure.
+    #[must_use]
+    #[inline]
+    pub fn new(key: &str, title: &str, details: &str) -> Self {
+        Self {
+            fingerprint: fingerprint(key),
+            title: truncate(title.trim(), Self::MAX_TITLE),
+            details: truncate(details.trim_end(), Self::MAX_DETAILS),
+        }
+    }
+
+    /// Describe the failure reported by a message, if any.
+    ///
+    /// Failed tests are identified by their name, and errors by the
+    /// [fingerprint](crate::fingerprint) of their diagnostic, or else by
+    /// their file and the first line of their plain-text formatting. The
+    /// results of test suites are not failures of their own, since their
+    /// failed tests are.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool which reported the message.
+    /// * `message` - The message.
+    pub(crate) fn from_message(tool: &str, message: &dyn DynCiMessage) -> Option<Self> {
+        if message.suite_result().is_some() {
+            return None;
+        }
+        let details = message.format_for(PlatformKind::Plain);
+        if message.is_test_failure() {
+            let name = message.test_result()?.name;
+            let title = format!("{}: {name}", Title::TestFailed.text());
+            return Some(Self::new(&format!("test\0{name}"), &title, &details));
+        }
+        if message.severity() < Severity::Error {
+            return None;
+        }
+        let summary = details.lines().find(|line| !line.trim().is_empty())?;
+        let key = format!("error\0{}\0{summary}", message.file().unwrap_or_default());
+        let failure = Self::new(&key, summary, &details);
+        Some(match Fingerprint::of(tool, message) {
+            Some(fingerprint) => Self {
+                fingerprint: fingerprint.to_string(),
+                ..failure
+            },
+            None => failure,
+        })
+    }
+
+    /// The marker recording the fingerprint in the body of an issue.
+    fn marker(&self) -> String {
+        format!("<!-- cifmt-fingerprint: {} -->", self.fingerprint)
+    }
+}
+
+/// The fingerprint of a failure: the 64-bit FNV-1a hash of its key, ignoring
+/// digits, in hexadecimal.
+fn fingerprint(key: &str) -> String {
+    let hash = crate::fingerprint::hash(key.bytes().filter(|byte| !byte.is_ascii_digit()));
+    format!("{hash:016x}")
+}
+
+/// Truncate text to at most `max` bytes, at a character boundary.
+fn truncate(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_owned();
+    }
+    let end = (0..=max)
+        .rev()
+        .find(|&end| text.is_char_boundary(end))
+        .unwrap_or_default();
+    format!("{}…", text.get(..end).unwrap_or_default())
+}
+
+/// The service on which issues are filed.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Tracker {
+    /// The issues of a GitHub repository.
+    GitHub {
+        /// The URL of the GitHub API.
+        api_url: String,
+        /// The repository, as `owner/name`.
+        repository: String,
+        /// The token used to authenticate with the API, which must be granted
+        /// the `issues: write` permission.
+        token: String,
+    },
+    /// The issues of a GitLab project.
+    GitLab {
+        /// The URL of the GitLab API (e.g., `https://gitlab.com/api/v4`).
+        api_url: String,
+        /// The ID of the project.
+        project: String,
+        /// The token used to authenticate with the API, which must be granted
+        /// the `api` scope.
+        token: String,
+    },
+}
+
+/// An open issue, as listed by GitHub or GitLab.
+#[derive(Debug, Deserialize)]
+struct OpenIssue {
+    /// The number of the issue within its repository or project.
+    #[serde(alias = "iid")]
+    number: u64,
+    /// The body of the issue, if any.
+    #[serde(alias = "description")]
+    body: Option<String>,
+}
+
+impl Tracker {
+    /// The number of issues requested per page of a listing.
+    const PER_PAGE: usize = 100;
+
+    /// The URL of a resource of the repository or project.
+    fn url(&self, resource: &str) -> String {
+        match self {
+            Self::GitHub {
+                api_url,
+                repository,
+                ..
+            } => format!(
+                "{}/repos/{repository}/{resource}",
+                api_url.trim_end_matches('/')
+            ),
+            Self::GitLab {
+                api_url, project, ..
+            } => format!(
+                "{}/projects/{project}/{resource}",
+                api_url.trim_end_matches('/')
+            ),
+        }
+    }
+
+    /// Add the headers of the API to a request.
+    fn headers<B>(&self, request: RequestBuilder<B>) -> RequestBuilder<B> {
+        match self {
+            Self::GitHub { token, .. } => request
+                .header("Accept", "application/vnd.github+json")
+                .header("Authorization", &format!("Bearer {token}"))
+                .header("User-Agent", "cifmt"),
+            Self::GitLab { token, .. } => request
+                .header("PRIVATE-TOKEN", token)
+                .header("User-Agent", "cifmt"),
+        }
+    }
+
+    /// List the open issues with the given label, page by page.
+    fn open_issues(&self, agent: &Agent, label: &str) -> Result<Vec<OpenIssue>, Error> {
+        let url = self.url("issues");
+        let state = match self {
+            Self::GitHub { .. } => "open",
+            Self::GitLab { .. } => "opened",
+        };
+        let mut issues = Vec::new();
+        for page in 1_u32.. {
+            let batch: Vec<OpenIssue> = self
+                .headers(agent.get(&url))
+                .query("state", state)
+                .query("labels", label)
+                .query("per_page", Self::PER_PAGE.to_string())
+                .query("page", page.to_string())
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let last = batch.len() < Self::PER_PAGE;
+            issues.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    /// The body of the request opening an issue.
+    fn issue_request(&self, title: &str, body: &str, label: &str) -> Value {
+        match self {
+            Self::GitHub { .. } => json!({ "title": title, "body": body, "labels": [label] }),
+            Self::GitLab { .. } => json!({ "title": title, "description": body, "labels": label }),
+        }
+    }
+
+    /// Open an issue.
+    fn open(&self, agent: &Agent, title: &str, body: &str, label: &str) -> Result<(), Error> {
+        self.headers(agent.post(self.url("issues")))
+            .send_json(self.issue_request(title, body, label))?;
+        Ok(())
+    }
+
+    /// Comment on an open issue.
+    fn comment(&self, agent: &Agent, number: u64, body: &str) -> Result<(), Error> {
+        let resource = match self {
+            Self::GitHub { .. } => format!("issues/{number}/comments"),
+            Self::GitLab { .. } => format!("issues/{number}/notes"),
+        };
+        self.headers(agent.post(self.url(&resource)))
+            .send_json(json!({ "body": body }))?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Tracker {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GitHub {
+                api_url,
+                repository,
+                ..
+            } => f
+                .debug_struct("GitHub")
+                .field("api_url", api_url)
+                .field("repository", repository)
+                .field("token", &"[redacted]")
+                .finish(),
+            Self::GitLab {
+                api_url, project, ..
+            } => f
+                .debug_struct("GitLab")
+                .field("api_url", api_url)
+                .field("project", project)
+                .field("token", &"[redacted]")
+                .finish(),
+        }
+    }
+}
+
+/// The number of issues opened and updated by an [`IssueFiler`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Filed {
+    /// The number of issues opened.
+    pub opened: usize,
+    /// The number of open issues commented on.
+    pub updated: usize,
+}
+
+/// Files issues for the failures of a run.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct IssueFiler {
+    /// The service on which issues are filed.
+    tracker: Tracker,
+    /// The label of the issues filed, by which open issues are found.
+    label: String,
+    /// Patterns restricting the failures filed, if any.
+    patterns: Vec<String>,
+    /// The name of the job which failed, if known.
+    job: Option<String>,
+    /// The URL of the job's log, if known.
+    run_url: Option<String>,
+}
+
+impl IssueFiler {
+    /// The label of the issues filed, unless set otherwise.
+    pub const DEFAULT_LABEL: &str = "cifmt";
+
+    /// Environment variable holding the URL of the GitHub API.
+    pub const GITHUB_API_URL_ENV: &str = "GITHUB_API_URL";
+
+    /// Environment variable holding the GitHub repository, as `owner/name`.
+    pub const GITHUB_REPOSITORY_ENV: &str = "GITHUB_REPOSITORY";
+
+    /// Environment variable holding the token used to authenticate with the
+    /// GitHub API.
+    pub const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+    /// The URL of the GitHub API, if not set by
+    /// [`IssueFiler::GITHUB_API_URL_ENV`].
+    pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+    /// Environment variable holding the URL of the GitLab API.
+    pub const GITLAB_API_URL_ENV: &str = "CI_API_V4_URL";
+
+    /// Environment variable holding the ID of the GitLab project.
+    pub const GITLAB_PROJECT_ENV: &str = "CI_PROJECT_ID";
+
+    /// Environment variable holding the token used to authenticate with the
+    /// GitLab API. The job token of GitLab CI cannot create issues, so this
+    /// must be set to a project or personal access token.
+    pub const GITLAB_TOKEN_ENV: &str = "GITLAB_TOKEN";
+
+    /// File issues on the given service.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - The service on which issues are filed.
+    #[must_use]
+    #[inline]
+    pub fn new(tracker: Tracker) -> Self {
+        Self {
+            tracker,
+            label: Self::DEFAULT_LABEL.to_owned(),
+            patterns: Vec::new(),
+            job: None,
+            run_url: None,
+        }
+    }
+
+    /// File issues on the repository of the GitHub Actions workflow.
+    ///
+    /// The job and the URL of the workflow run are taken from the
+    /// environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository or the token is not set in the
+    /// environment.
+    #[inline]
+    pub fn github_from_env() -> Result<Self, Error> {
+        let tracker = Tracker::GitHub {
+            api_url: env::var(Self::GITHUB_API_URL_ENV)
+                .unwrap_or_else(|_err| Self::DEFAULT_GITHUB_API_URL.to_owned()),
+            repository: required(Self::GITHUB_REPOSITORY_ENV)?,
+            token: required(Self::GITHUB_TOKEN_ENV)?,
+        };
+        let run_url = match (
+            env::var("GITHUB_SERVER_URL"),
+            env::var(Self::GITHUB_REPOSITORY_ENV),
+            env::var("GITHUB_RUN_ID"),
+        ) {
+            (Ok(server), Ok(repository), Ok(run)) => {
+                Some(format!("{server}/{repository}/actions/runs/{run}"))
+            }
+            _ => None,
+        };
+        Ok(Self::new(tracker).with_run(env::var("GITHUB_JOB").ok(), run_url))
+    }
+
+    /// File issues on the project of the GitLab CI job.
+    ///
+    /// The job and the URL of its log are taken from the environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API, the project or the token is not set in
+    /// the environment.
+    #[inline]
+    pub fn gitlab_from_env() -> Result<Self, Error> {
+        let tracker = Tracker::GitLab {
+            api_url: required(Self::GITLAB_API_URL_ENV)?,
+            project: required(Self::GITLAB_PROJECT_ENV)?,
+            token: required(Self::GITLAB_TOKEN_ENV)?,
+        };
+        Ok(Self::new(tracker).with_run(env::var("CI_JOB_NAME").ok(), env::var("CI_JOB_URL").ok()))
+    }
+
+    /// Set the label of the issues filed.
+    ///
+    /// Only open issues with this label are updated; the label must not be
+    /// used for issues filed by hand.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the issues.
+    #[must_use]
+    #[inline]
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Only file the failures matching any of the given patterns.
+    ///
+    /// A pattern matches the name of the job (e.g., `nightly-*` for every
+    /// failure of the nightly jobs) or the title of a failure (e.g.,
+    /// `*tests::network::*`), with `*` matching any sequence of characters and
+    /// `?` any single character. Every failure is filed if no patterns are
+    /// given.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The patterns.
+    #[must_use]
+    #[inline]
+    pub fn with_patterns(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the job which failed, mentioned in the issues.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The name of the job, if known.
+    /// * `run_url` - The URL of the job's log, if known.
+    #[must_use]
+    #[inline]
+    pub fn with_run(mut self, job: Option<String>, run_url: Option<String>) -> Self {
+        self.job = job;
+        self.run_url = run_url;
+        self
+    }
+
+    /// Whether an issue is filed for a failure.
+    #[must_use]
+    #[inline]
+    pub fn matches(&self, failure: &Failure) -> bool {
+        self.patterns.is_empty()
+            || self.patterns.iter().any(|pattern| {
+                self.job
+                    .as_deref()
+                    .is_some_and(|job| matches_glob(pattern.as_bytes(), job.as_bytes()))
+                    || matches_glob(pattern.as_bytes(), failure.title.as_bytes())
+            })
+    }
+
+    /// Open an issue for each failure matching the patterns, or comment on
+    /// the open issue already filed for it.
+    ///
+    /// Failures with the same fingerprint are filed once.
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - The failures of the run.
+    ///
+    /// # Returns
+    ///
+    /// The number of issues opened and updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request to the API fails.
+    #[inline]
+    pub fn file(&self, failures: &[Failure]) -> Result<Filed, Error> {
+        let mut seen = HashSet::new();
+        let unique: Vec<_> = failures
+            .iter()
+            .filter(|failure| self.matches(failure))
+            .filter(|failure| seen.insert(&failure.fingerprint))
+            .collect();
+        let mut filed = Filed::default();
+        if unique.is_empty() {
+            return Ok(filed);
+        }
+
+        let agent = http::agent();
+        let open = self.tracker.open_issues(&agent, &self.label)?;
+        for failure in unique {
+            let marker = failure.marker();
+            let existing = open.iter().find(|issue| {
+                issue
+                    .body
+                    .as_deref()
+                    .is_some_and(|body| body.contains(&marker))
+            });
+            if let Some(issue) = existing {
+                self.tracker
+                    .comment(&agent, issue.number, &self.comment_body(failure))?;
+                filed.updated = filed.updated.saturating_add(1);
+            } else {
+                self.tracker.open(
+                    &agent,
+                    &failure.title,
+                    &self.issue_body(failure),
+                    &self.label,
+                )?;
+                filed.opened = filed.opened.saturating_add(1);
+            }
+        }
+        Ok(filed)
+    }
+
+    /// The job in which the failure occurred, linked to its log if known.
+    fn run(&self) -> String {
+        let job = match &self.job {
+            Some(job) => format!("the `{job}` job"),
+            None => "a job".to_owned(),
+        };
+        match &self.run_url {
+            Some(url) => format!("{job} ([log]({url}))"),
+            None => job,
+        }
+    }
+
+    /// The body of the issue opened for a failure.
+    fn issue_body(&self, failure: &Failure) -> String {
+        format!(
+            "cifmt found this failure in {}.\n\n```text\n{}\n```\n\nFurther occurrences are \
+             added as comments while this issue is open.\n\n{}",
+            self.run(),
+            failure.details,
+            failure.marker()
+        )
+    }
+
+    /// The body of the comment added to the open issue of a failure.
+    fn comment_body(&self, failure: &Failure) -> String {
+        format!(
+            "Failed again in {}.\n\n```text\n{}\n```",
+            self.run(),
+            failure.details
+        )
+    }
+}
+
+/// Read a required environment variable.
+fn required(key: &'static str) -> Result<String, Error> {
+    env::var(key).map_err(|_err| Error::MissingEnv(key))
+}
+
+/// Errors relating to filing issues.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A required environment variable is not set.
+    #[error("The {0} environment variable is not set")]
+    MissingEnv(&'static str),
+    /// A request to the API failed.
+    #[error("Request to the issue tracker failed: {0}")]
+    Request(#[from] ureq::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::{Failure, IssueFiler, Tracker, fingerprint, truncate};
+    use crate::{
+        fingerprint::Fingerprint,
+        tool::{AnyTool as _, CargoCheck, CargoLibtest},
+    };
+
+    fn github() -> IssueFiler {
+        IssueFiler::new(Tracker::GitHub {
+            api_url: "https://api.github.com/".to_owned(),
+            repository: "octo/repo".to_owned(),
+            token: "secret".to_owned(),
+        })
+    }
+
+    #[test]
+    fn from_message() {
+        let output = concat!(
+            r#"{"type":"test","event":"failed","name":"tests::fetch","stdout":"timed out after 30s"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"tests::parse"}"#,
+            "\n",
+            r#"{"type":"suite","event":"failed","passed":1,"failed":1,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.5}"#,
+            "\n",
+        );
+        let failures: Vec<_> = CargoLibtest::default()
+            .parse_dyn(output.as_bytes())
+            .iter()
+            .filter_map(|message| Failure::from_message("cargo-libtest", message.as_ref()))
+            .collect();
+        let [failure] = failures.as_slice() else {
+            panic!("expected one failure: {failures:?}");
+        };
+        assert_eq!(failure.title, "Test Failed: tests::fetch");
+        assert_eq!(failure.fingerprint, fingerprint("test\0tests::fetch"));
+        assert!(
+            failure.details.contains("timed out after 30s"),
+            "{failure:?}"
+        );
+    }
+
+    #[test]
+    fn error_fingerprint() {
+        let output = concat!(
+            r#"{"reason":"compiler-message","package_id":"p 0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/w/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"rendered":null,"$message_type":"diagnostic","children":[],"level":"error","message":"cannot find value `y` in this scope","spans":[{"byte_end":1,"byte_start":0,"column_end":6,"column_start":5,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":3,"line_start":3,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"E0425","explanation":null}}}"#,
+            "\n",
+        );
+        let messages = CargoCheck::default().parse_dyn(output.as_bytes());
+        let [message] = messages.as_slice() else {
+            panic!("expected one message");
+        };
+        let failure =
+            Failure::from_message("cargo-check", message.as_ref()).expect("expected a failure");
+        let expected = Fingerprint::new(
+            "cargo-check",
+            "E0425",
+            Some("src/lib.rs"),
+            "cannot find value `y` in this scope",
+        );
+        assert_eq!(failure.fingerprint, expected.to_string());
+    }
+
+    #[test]
+    fn fingerprint_ignores_digits() {
+        assert_eq!(
+            fingerprint("error\0src/lib.rs\0src/lib.rs:3: ERROR: E0425"),
+            fingerprint("error\0src/lib.rs\0src/lib.rs:12: ERROR: E0425")
+        );
+        assert_ne!(fingerprint("test\0a"), fingerprint("test\0b"));
+        assert_eq!(fingerprint("").len(), 16);
+    }
+
+    #[test]
+    fn truncates_at_char_boundary() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("aé", 2), "a…");
+    }
+
+    #[test]
+    fn matches() {
+        let failure = Failure::new("test\0network::fetch", "Test Failed: network::fetch", "");
+        assert!(github().matches(&failure), "no patterns");
+
+        let filer = github().with_patterns(["nightly-*"]);
+        assert!(!filer.matches(&failure), "job unknown");
+        let nightly = filer.with_run(Some("nightly-beta".to_owned()), None);
+        assert!(nightly.matches(&failure), "job matched");
+
+        let by_title = github().with_patterns(["*network::*"]);
+        assert!(by_title.matches(&failure), "title matched");
+    }
+
+    #[test]
+    fn bodies() {
+        let filer = github().with_run(
+            Some("nightly".to_owned()),
+            Some("https://github.com/octo/repo/actions/runs/1".to_owned()),
+        );
+        let failure = Failure::new("test\0t", "Test Failed: t", "FAILED: t\npanicked\n");
+        insta::assert_snapshot!(filer.issue_body(&failure), @"
+        cifmt found this failure in the `nightly` job ([log](https://github.com/octo/repo/actions/runs/1)).
+
+        ```text
+        FAILED: t
+        panicked
+        ```
+
+        Further occurrences are added as comments while this issue is open.
+
+        <!-- cifmt-fingerprint: 8d3ebcdc856e5f91 -->
+        ");
+        insta::assert_snapshot!(filer.comment_body(&failure), @"
+        Failed again in the `nightly` job ([log](https://github.com/octo/repo/actions/runs/1)).
+
+        ```text
+        FAILED: t
+        panicked
+        ```
+        ");
+    }
+
+    #[test]
+    fn requests() {
+        let gitlab = Tracker::GitLab {
+            api_url: "https://gitlab.com/api/v4".to_owned(),
+            project: "42".to_owned(),
+            token: "secret".to_owned(),
+        };
+        assert_eq!(
+            gitlab.url("issues/3/notes"),
+            "https://gitlab.com/api/v4/projects/42/issues/3/notes"
+        );
+        assert_eq!(
+            gitlab.issue_request("title", "body", "cifmt").to_string(),
+            r#"{"description":"body","labels":"cifmt","title":"title"}"#
+        );
+
+        let filer = github();
+        assert_eq!(
+            filer.tracker.url("issues"),
+            "https://api.github.com/repos/octo/repo/issues"
+        );
+        assert_eq!(
+            filer
+                .tracker
+                .issue_request("title", "body", "cifmt")
+                .to_string(),
+            r#"{"body":"body","labels":["cifmt"],"title":"title"}"#
+        );
+    }
+
+    #[test]
+    fn redacted() {
+        let debug = format!("{:?}", github());
+        assert!(!debug.contains("secret"), "{debug}");
+    }
+}
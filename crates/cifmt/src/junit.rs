@@ -0,0 +1,403 @@
+//! JUnit test reports.
+//!
+//! Several CI platforms (e.g., AWS CodeBuild, GitLab CI and Jenkins) display
+//! test results read from a report in the JUnit XML format, rather than from
+//! the log. This module collects the results of the tests run by a tool into
+//! a [`Report`], which can be written in this format.
+//!
+//! The format has no formal specification; the report follows the subset
+//! understood by most consumers:
+//! <https://github.com/testmoapp/junitxml>.
+
+use core::{fmt, time::Duration};
+use std::io::{self, Write};
+
+/// The outcome of a single test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TestOutcome {
+    /// The test passed.
+    Passed,
+    /// The test failed.
+    Failed,
+    /// The test was not run.
+    Skipped,
+}
+
+/// The result of a single test, as reported by a message.
+///
+/// See [`Classify::test_result`](crate::ci_message::Classify::test_result).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TestResult<'a> {
+    /// The full name of the test (e.g., `tests::it_works`).
+    pub name: &'a str,
+    /// The outcome of the test.
+    pub outcome: TestOutcome,
+    /// How long the test took to run, if known.
+    pub duration: Option<Duration>,
+    /// A message explaining the outcome (e.g., the reason a test was
+    /// ignored), if any.
+    pub message: Option<&'a str>,
+    /// The output captured while the test ran, if any.
+    pub output: Option<&'a str>,
+}
+
+impl<'a> TestResult<'a> {
+    /// Create a test result without any duration, message or output.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The full name of the test.
+    /// * `outcome` - The outcome of the test.
+    #[must_use]
+    #[inline]
+    pub fn new(name: &'a str, outcome: TestOutcome) -> Self {
+        Self {
+            name,
+            outcome,
+            duration: None,
+            message: None,
+            output: None,
+        }
+    }
+}
+
+/// A test case within a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TestCase {
+    /// The full name of the test.
+    name: String,
+    /// The outcome of the test.
+    outcome: TestOutcome,
+    /// How long the test took to run, if known.
+    duration: Option<Duration>,
+    /// A message explaining the outcome, if any.
+    message: Option<String>,
+    /// The output captured while the test ran, if any.
+    output: Option<String>,
+}
+
+impl TestCase {
+    /// Split the name of the test into the name of its class (the path of
+    /// the module containing it) and its own name.
+    fn split_name(&self) -> (&str, &str) {
+        self.name.rsplit_once("::").unwrap_or(("", &self.name))
+    }
+}
+
+/// A JUnit report of the results of the tests run by a tool.
+///
+/// All tests are reported within a single test suite, named after the tool.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::junit::{Report, TestOutcome, TestResult};
+///
+/// let mut report = Report::new("cargo-libtest");
+/// report.record(TestResult::new("tests::it_works", TestOutcome::Passed));
+///
+/// let mut xml = Vec::new();
+/// report.write_xml(&mut xml).expect("writing to a Vec cannot fail");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// The name of the test suite.
+    name: String,
+    /// The test cases, in the order in which they were recorded.
+    cases: Vec<TestCase>,
+    /// Properties of the test suite (e.g., the metadata of the run).
+    properties: Vec<(String, String)>,
+}
+
+impl Report {
+    /// Create an empty report.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the test suite (typically the tool's name).
+    #[must_use]
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            cases: Vec::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// Set the properties of the test suite, written before its test cases.
+    ///
+    /// # Arguments
+    ///
+    /// * `properties` - The name and value of each property.
+    #[inline]
+    pub fn set_properties(
+        &mut self,
+        properties: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) {
+        self.properties = properties
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+    }
+
+    /// Record the result of a test.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result to record.
+    #[inline]
+    pub fn record(&mut self, result: TestResult<'_>) {
+        self.cases.push(TestCase {
+            name: result.name.to_owned(),
+            outcome: result.outcome,
+            duration: result.duration,
+            message: result.message.map(str::to_owned),
+            output: result.output.map(str::to_owned),
+        });
+    }
+
+    /// The number of tests recorded.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Whether no tests have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+
+    /// The number of tests recorded with the given outcome.
+    ///
+    /// # Arguments
+    ///
+    /// * `outcome` - The outcome of the tests to count.
+    #[must_use]
+    #[inline]
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.outcome == outcome)
+            .count()
+    }
+
+    /// The results of the tests recorded, in the order in which they were
+    /// recorded.
+    #[inline]
+    pub fn results(&self) -> impl Iterator<Item = TestResult<'_>> {
+        self.cases.iter().map(|case| TestResult {
+            name: &case.name,
+            outcome: case.outcome,
+            duration: case.duration,
+            message: case.message.as_deref(),
+            output: case.output.as_deref(),
+        })
+    }
+
+    /// Write the report in the JUnit XML format.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    #[inline]
+    pub fn write_xml(&self, mut writer: impl Write) -> io::Result<()> {
+        let time = Seconds(self.cases.iter().filter_map(|case| case.duration).sum());
+        let counts = format!(
+            r#"tests="{}" failures="{}" skipped="{}" time="{time}""#,
+            self.len(),
+            self.count(TestOutcome::Failed),
+            self.count(TestOutcome::Skipped),
+        );
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, "<testsuites {counts}>")?;
+        writeln!(
+            writer,
+            r#"  <testsuite name="{}" {counts}>"#,
+            Escaped(&self.name)
+        )?;
+        if !self.properties.is_empty() {
+            writeln!(writer, "    <properties>")?;
+            for (name, value) in &self.properties {
+                writeln!(
+                    writer,
+                    r#"      <property name="{}" value="{}"/>"#,
+                    Escaped(name),
+                    Escaped(value)
+                )?;
+            }
+            writeln!(writer, "    </properties>")?;
+        }
+        for case in &self.cases {
+            write_case(&mut writer, case)?;
+        }
+        writeln!(writer, "  </testsuite>")?;
+        writeln!(writer, "</testsuites>")?;
+        writer.flush()
+    }
+}
+
+/// Write a single test case.
+fn write_case(writer: &mut impl Write, case: &TestCase) -> io::Result<()> {
+    let (class, name) = case.split_name();
+    write!(
+        writer,
+        r#"    <testcase classname="{}" name="{}""#,
+        Escaped(class),
+        Escaped(name)
+    )?;
+    if let Some(duration) = case.duration {
+        write!(writer, r#" time="{}""#, Seconds(duration))?;
+    }
+
+    let message = case.message.as_deref();
+    let output = case.output.as_deref().filter(|output| !output.is_empty());
+    if case.outcome == TestOutcome::Passed && output.is_none() {
+        return writeln!(writer, "/>");
+    }
+    writeln!(writer, ">")?;
+
+    match case.outcome {
+        TestOutcome::Passed => {}
+        TestOutcome::Failed => {
+            let summary = message.unwrap_or("Test failed");
+            writeln!(
+                writer,
+                r#"      <failure message="{}">{}</failure>"#,
+                Escaped(summary),
+                Escaped(output.unwrap_or(summary))
+            )?;
+        }
+        TestOutcome::Skipped => match message {
+            Some(reason) => writeln!(writer, r#"      <skipped message="{}"/>"#, Escaped(reason))?,
+            None => writeln!(writer, "      <skipped/>")?,
+        },
+    }
+    // The output of failed tests is already included in the failure.
+    if case.outcome != TestOutcome::Failed
+        && let Some(text) = output
+    {
+        writeln!(writer, "      <system-out>{}</system-out>", Escaped(text))?;
+    }
+    writeln!(writer, "    </testcase>")
+}
+
+/// A duration displayed in seconds, with millisecond precision.
+struct Seconds(Duration);
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3}", self.0.as_secs_f64())
+    }
+}
+
+/// Text escaped for use within XML attributes and elements.
+///
+/// Control characters other than tabs and newlines are not permitted in XML
+/// at all, and are removed.
+pub(crate) struct Escaped<'a>(pub &'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                '\t' | '\n' | '\r' => write!(f, "{c}")?,
+                _ if c.is_control() => {}
+                _ => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{Escaped, Report, TestOutcome, TestResult};
+
+    #[rstest]
+    #[case::plain("it_works", "it_works")]
+    #[case::markup("a < b && c > \"d\"", "a &lt; b &amp;&amp; c &gt; &quot;d&quot;")]
+    #[case::control("bell\u{7}\ttab\nline", "bell\ttab\nline")]
+    fn escape(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(Escaped(text).to_string(), expected);
+    }
+
+    #[test]
+    fn write_xml() {
+        let mut report = Report::new("cargo-libtest");
+        report.record(TestResult {
+            duration: Some(Duration::from_millis(1500)),
+            ..TestResult::new("tests::it_works", TestOutcome::Passed)
+        });
+        report.record(TestResult {
+            duration: Some(Duration::from_millis(20)),
+            output: Some("assertion `left == right` failed\n"),
+            ..TestResult::new("tests::it_fails", TestOutcome::Failed)
+        });
+        report.record(TestResult {
+            message: Some("slow"),
+            ..TestResult::new("it_is_ignored", TestOutcome::Skipped)
+        });
+        assert_eq!(report.len(), 3);
+
+        let mut xml = Vec::new();
+        report.write_xml(&mut xml).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&xml), @r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuites tests="3" failures="1" skipped="1" time="1.520">
+          <testsuite name="cargo-libtest" tests="3" failures="1" skipped="1" time="1.520">
+            <testcase classname="tests" name="it_works" time="1.500"/>
+            <testcase classname="tests" name="it_fails" time="0.020">
+              <failure message="Test failed">assertion `left == right` failed
+        </failure>
+            </testcase>
+            <testcase classname="" name="it_is_ignored">
+              <skipped message="slow"/>
+            </testcase>
+          </testsuite>
+        </testsuites>
+        "#);
+    }
+
+    #[test]
+    fn write_xml_properties() {
+        let mut report = Report::new("cargo-libtest");
+        report.set_properties([("tool", "cargo-libtest"), ("command-line", "cifmt <args>")]);
+        report.record(TestResult::new("tests::it_works", TestOutcome::Passed));
+
+        let mut xml = Vec::new();
+        report.write_xml(&mut xml).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&xml), @r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuites tests="1" failures="0" skipped="0" time="0.000">
+          <testsuite name="cargo-libtest" tests="1" failures="0" skipped="0" time="0.000">
+            <properties>
+              <property name="tool" value="cargo-libtest"/>
+              <property name="command-line" value="cifmt &lt;args&gt;"/>
+            </properties>
+            <testcase classname="tests" name="it_works"/>
+          </testsuite>
+        </testsuites>
+        "#);
+    }
+}
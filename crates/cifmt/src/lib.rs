@@ -7,7 +7,7 @@
 //!
 //! # Overview
 //!
-//! The library is organized around three main concepts:
+//! The library is organized around four main concepts:
 //!
 //! 1. **CI Platforms** ([`ci`]): Contains platform-specific implementations
 //!    that implement the [`ci::Platform`] trait for formatting messages
@@ -19,10 +19,73 @@
 //! 3. **Messages** ([`CiMessage`]): A trait for types that can be formatted as
 //!    CI messages.
 //!
+//! 4. **Pipelines** ([`pipeline`]): Drive a tool's parser and write the
+//!    formatted messages to one or more output targets, each with its own
+//!    platform.
+//!
+//! Output which was already captured in full can also be formatted in a
+//! single call, through [`format_str`] (see [`embed`]).
+
+#![expect(
+    clippy::pub_use,
+    reason = "the entry points for embedders are re-exported at the root"
+)]
 
+pub mod ansi;
+pub mod capture;
+pub mod catalog;
+#[cfg(feature = "checks")]
+pub mod checks;
+pub mod checkstyle;
 pub mod ci;
 pub mod ci_message;
+pub mod codeclimate;
+pub mod embed;
+pub mod exit_code;
+pub mod fingerprint;
+pub mod github_annotations;
+pub mod history;
+pub mod html;
+#[cfg(any(
+    feature = "checks",
+    feature = "issues",
+    feature = "notify",
+    feature = "review"
+))]
+mod http;
+#[cfg(feature = "issues")]
+pub mod issues;
+pub mod junit;
+pub mod lints;
+pub mod markdown;
+pub mod matcher;
+pub mod metadata;
+pub mod normalized;
+#[cfg(feature = "notify")]
+pub mod notify;
+pub mod outcome;
+pub mod owners;
+pub mod packages;
+pub mod pipeline;
+pub mod raw;
+pub mod rdjson;
+pub mod report;
+pub mod resilience;
+#[cfg(feature = "review")]
+pub mod review;
+pub mod sarif;
+pub mod selftest;
+pub mod sizes;
+pub mod skips;
+pub mod suites;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod timeline;
+pub mod timeout;
 pub mod tool;
+pub mod utf8;
+
+pub use embed::{FormattedOutput, format_reader, format_str};
 
 pub mod prelude {
     //! A prelude module for convenient imports.
@@ -32,6 +95,6 @@ pub mod prelude {
     //! imports.
     #![expect(clippy::pub_use, reason = "convenience re-exports")]
     pub use crate::ci::Platform;
-    pub use crate::ci_message::CiMessage;
+    pub use crate::ci_message::{CiMessage, Classify};
     pub use crate::tool::{Detect, DynTool, Tool};
 }
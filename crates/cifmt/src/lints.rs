@@ -0,0 +1,159 @@
+//! Statistics of the lints reported by a tool.
+//!
+//! Compilers and linters identify the kind of each issue they report with a
+//! code (e.g., `clippy::needless_borrow`). Counting the issues by lint shows
+//! which kinds are the most common, which helps teams prioritize clean-up
+//! work. The counts are collected into [`LintStats`], and the most common
+//! lints are listed in the job summary (see
+//! [`JobSummary`](crate::markdown::JobSummary)) by a [`LintSummary`]. Error
+//! codes (e.g., `E0308`) are not lints, and are not counted (see
+//! [`Classify::lint`](crate::ci_message::Classify::lint)).
+
+use std::collections::HashMap;
+
+/// The number of issues reported for each code.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LintStats {
+    /// The number of issues, keyed by their code.
+    counts: HashMap<String, usize>,
+}
+
+impl LintStats {
+    /// Record an issue with the given code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code of the issue (e.g., `unused_variables`).
+    #[inline]
+    pub fn record(&mut self, code: &str) {
+        if let Some(count) = self.counts.get_mut(code) {
+            *count = count.saturating_add(1);
+        } else {
+            self.counts.insert(code.to_owned(), 1);
+        }
+    }
+
+    /// Whether no issues with a code have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The number of issues recorded with the given code.
+    #[must_use]
+    #[inline]
+    pub fn count(&self, code: &str) -> usize {
+        self.counts.get(code).copied().unwrap_or_default()
+    }
+
+    /// The most common codes, along with their number of issues.
+    ///
+    /// Codes are ordered by decreasing number of issues, and then by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of codes to return.
+    #[must_use]
+    #[inline]
+    pub fn top(&self, limit: usize) -> Vec<(&str, usize)> {
+        let mut top: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(code, count)| (code.as_str(), *count))
+            .collect();
+        top.sort_by(|lhs, rhs| rhs.1.cmp(&lhs.1).then_with(|| lhs.0.cmp(rhs.0)));
+        top.truncate(limit);
+        top
+    }
+}
+
+/// The most common codes, listed in the job summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LintSummary {
+    /// The most common codes, along with their number of issues.
+    pub top: Vec<(String, usize)>,
+    /// The number of other codes, which are omitted.
+    pub omitted: usize,
+}
+
+impl LintSummary {
+    /// The maximum number of codes listed.
+    pub const MAX_CODES: usize = 10;
+
+    /// Summarize the most common codes.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The number of issues reported for each code.
+    #[must_use]
+    #[inline]
+    pub fn new(stats: &LintStats) -> Self {
+        let top: Vec<_> = stats
+            .top(Self::MAX_CODES)
+            .into_iter()
+            .map(|(code, count)| (code.to_owned(), count))
+            .collect();
+        Self {
+            omitted: stats.counts.len().saturating_sub(top.len()),
+            top,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{LintStats, LintSummary};
+
+    fn stats() -> LintStats {
+        let mut stats = LintStats::default();
+        for code in [
+            "unused_variables",
+            "clippy::needless_borrow",
+            "dead_code",
+            "clippy::needless_borrow",
+            "unused_variables",
+            "clippy::needless_borrow",
+        ] {
+            stats.record(code);
+        }
+        stats
+    }
+
+    #[test]
+    fn top() {
+        let stats = stats();
+        assert_eq!(stats.count("unused_variables"), 2);
+        assert_eq!(stats.count("missing_docs"), 0);
+        assert_eq!(
+            stats.top(2),
+            [("clippy::needless_borrow", 3), ("unused_variables", 2)]
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let mut many = stats();
+        for i in 0..10_u32 {
+            many.record(&format!("lint_{i}"));
+        }
+        let truncated = LintSummary::new(&many);
+        assert_eq!(truncated.top.len(), LintSummary::MAX_CODES);
+        assert_eq!(truncated.omitted, 3);
+
+        let summary = LintSummary::new(&stats());
+        assert_eq!(
+            summary.top,
+            [
+                ("clippy::needless_borrow".to_owned(), 3),
+                ("unused_variables".to_owned(), 2),
+                ("dead_code".to_owned(), 1),
+            ]
+        );
+        assert_eq!(summary.omitted, 0);
+    }
+}
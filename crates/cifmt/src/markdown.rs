@@ -0,0 +1,596 @@
+//! Markdown summaries of a tool's output.
+//!
+//! Some platforms display a summary written in Markdown alongside the log
+//! (e.g., the job summary of GitHub Actions, see
+//! [`GitHub::summary`](crate::ci::GitHub::summary)). A [`JobSummary`]
+//! renders the totals collected by a [`Pipeline`](crate::pipeline::Pipeline)
+//! as such a summary: the outcome of the build, the results of the tests with
+//! tables of the failed, slowest and skipped tests, the results of each test
+//! suite with the details of its failures, the issues of each crate and the
+//! most common lints, and the trend of the recent runs (see
+//! [`history`](crate::history)).
+
+use core::{cmp::Reverse, fmt};
+
+use crate::{
+    catalog::Title,
+    history::Trend,
+    junit::{Report, TestOutcome, TestResult},
+    lints::{LintStats, LintSummary},
+    owners::OwnerStats,
+    packages::PackageStats,
+    skips::{SkipStats, SkipSummary},
+    suites::SuiteStats,
+};
+
+/// A Markdown summary of the output of a tool, as collected by a
+/// [`Pipeline`](crate::pipeline::Pipeline) (see
+/// [`Pipeline::job_summary`](crate::pipeline::Pipeline::job_summary)).
+///
+/// The summary is rendered through its [`Display`](fmt::Display)
+/// implementation.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct JobSummary<'a> {
+    /// The name of the tool, used as the heading of the summary.
+    pub tool: &'a str,
+    /// Whether the build reported by the tool succeeded, if reported.
+    pub build_result: Option<bool>,
+    /// Number of errors reported.
+    pub errors: usize,
+    /// Number of warnings reported.
+    pub warnings: usize,
+    /// Whether the tool's output is incomplete.
+    pub incomplete: bool,
+    /// The results of the tests run by the tool, if collected.
+    pub tests: Option<&'a Report>,
+    /// Number of errors and warnings by their code.
+    pub lints: &'a LintStats,
+    /// Number of errors and warnings by their package.
+    pub packages: &'a PackageStats,
+    /// Number of errors and warnings by the owners of their file.
+    pub owners: &'a OwnerStats,
+    /// Number of skipped tests by the reason given.
+    pub skips: &'a SkipStats,
+    /// The results of each test suite.
+    pub suites: &'a SuiteStats,
+    /// The latest run compared against the previous runs, if a history is
+    /// kept.
+    pub trend: Option<Trend<'a>>,
+}
+
+impl JobSummary<'_> {
+    /// The maximum number of failed tests listed.
+    pub const MAX_FAILED_TESTS: usize = 50;
+
+    /// The maximum number of slowest tests listed.
+    pub const MAX_SLOWEST_TESTS: usize = 10;
+
+    /// The maximum length of the reason given for a failed test, in
+    /// characters.
+    pub const MAX_REASON_LENGTH: usize = 120;
+
+    /// Write the results of the tests.
+    fn write_tests(f: &mut fmt::Formatter<'_>, report: &Report) -> fmt::Result {
+        let failed = report.count(TestOutcome::Failed);
+        writeln!(f, "#### Tests")?;
+        writeln!(f)?;
+        writeln!(f, "| Passed | Failed | Skipped | Total |")?;
+        writeln!(f, "| -----: | -----: | ------: | ----: |")?;
+        writeln!(
+            f,
+            "| {} | {failed} | {} | {} |",
+            report.count(TestOutcome::Passed),
+            report.count(TestOutcome::Skipped),
+            report.len()
+        )?;
+
+        if failed > 0 {
+            writeln!(f)?;
+            writeln!(f, "#### Failed tests")?;
+            writeln!(f)?;
+            writeln!(f, "| Test | Reason |")?;
+            writeln!(f, "| :--- | :----- |")?;
+            for result in report
+                .results()
+                .filter(|result| result.outcome == TestOutcome::Failed)
+                .take(Self::MAX_FAILED_TESTS)
+            {
+                writeln!(
+                    f,
+                    "| `{}` | {} |",
+                    Cell(result.name),
+                    Cell(&reason(&result))
+                )?;
+            }
+            let omitted = failed.saturating_sub(Self::MAX_FAILED_TESTS);
+            if omitted > 0 {
+                writeln!(f)?;
+                writeln!(f, "{omitted} more failed test(s) omitted.")?;
+            }
+        }
+
+        let mut timed: Vec<_> = report
+            .results()
+            .filter_map(|result| result.duration.map(|duration| (result.name, duration)))
+            .collect();
+        if !timed.is_empty() {
+            // The sort is stable, so tests taking as long keep their order.
+            timed.sort_by_key(|&(_, duration)| Reverse(duration));
+            writeln!(f)?;
+            writeln!(f, "#### Slowest tests")?;
+            writeln!(f)?;
+            writeln!(f, "| Test | Duration |")?;
+            writeln!(f, "| :--- | -------: |")?;
+            for (name, duration) in timed.into_iter().take(Self::MAX_SLOWEST_TESTS) {
+                writeln!(f, "| `{}` | {:.3}s |", Cell(name), duration.as_secs_f64())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the results of each test suite, followed by the details of the
+    /// tests which failed within each suite in collapsed sections.
+    fn write_suites(f: &mut fmt::Formatter<'_>, suites: &SuiteStats) -> fmt::Result {
+        writeln!(f, "#### Test suites")?;
+        writeln!(f)?;
+        writeln!(f, "| Suite | Passed | Failed | Ignored | Duration |")?;
+        writeln!(f, "| :---- | -----: | -----: | ------: | -------: |")?;
+        for (index, suite) in suites.suites().iter().enumerate() {
+            let result = &suite.result;
+            write!(
+                f,
+                "| Suite {} | {} | {} | {} | ",
+                index.saturating_add(1),
+                result.passed,
+                result.failed,
+                result.ignored
+            )?;
+            match result.duration {
+                Some(duration) => writeln!(f, "{:.3}s |", duration.as_secs_f64())?,
+                None => writeln!(f, "- |")?,
+            }
+        }
+
+        for (index, suite) in suites.suites().iter().enumerate() {
+            if suite.failures.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, "<details>")?;
+            writeln!(
+                f,
+                "<summary>Suite {}: {} failed test(s)</summary>",
+                index.saturating_add(1),
+                suite.failures.len()
+            )?;
+            for failure in &suite.failures {
+                writeln!(f)?;
+                writeln!(f, "**`{}`**", Cell(&failure.name))?;
+                if let Some(details) = &failure.details {
+                    if failure.omitted > 0 {
+                        writeln!(f)?;
+                        writeln!(f, "{} earlier line(s) omitted.", failure.omitted)?;
+                    }
+                    let fence = Fence::for_text(details);
+                    writeln!(f)?;
+                    writeln!(f, "{fence}text")?;
+                    writeln!(f, "{details}")?;
+                    writeln!(f, "{fence}")?;
+                }
+            }
+            writeln!(f)?;
+            writeln!(f, "</details>")?;
+        }
+        Ok(())
+    }
+
+    /// Write the reasons for which tests were skipped.
+    fn write_skips(f: &mut fmt::Formatter<'_>, skips: &SkipStats) -> fmt::Result {
+        let summary = SkipSummary::new(skips);
+        writeln!(f, "#### Skipped tests")?;
+        writeln!(f)?;
+        writeln!(f, "| Reason | Count |")?;
+        writeln!(f, "| :----- | ----: |")?;
+        for (reason, count) in &summary.top {
+            match reason {
+                Some(text) => writeln!(f, "| {} | {count} |", Cell(text))?,
+                None => writeln!(f, "| *No reason given* | {count} |")?,
+            }
+        }
+        if summary.omitted > 0 {
+            writeln!(f)?;
+            writeln!(f, "{} more reason(s) omitted.", summary.omitted)?;
+        }
+        Ok(())
+    }
+
+    /// Write the issues of each package.
+    fn write_packages(f: &mut fmt::Formatter<'_>, packages: &PackageStats) -> fmt::Result {
+        writeln!(f, "#### Issues by crate")?;
+        writeln!(f)?;
+        writeln!(f, "| Crate | Errors | Warnings |")?;
+        writeln!(f, "| :---- | -----: | -------: |")?;
+        for (package, counts) in packages.sorted() {
+            writeln!(
+                f,
+                "| `{}` | {} | {} |",
+                Cell(package),
+                counts.errors,
+                counts.warnings
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the issues of each owner.
+    fn write_owners(f: &mut fmt::Formatter<'_>, owners: &OwnerStats) -> fmt::Result {
+        writeln!(f, "#### Issues by owner")?;
+        writeln!(f)?;
+        writeln!(f, "| Owner | Errors | Warnings |")?;
+        writeln!(f, "| :---- | -----: | -------: |")?;
+        for (owner, counts) in owners.sorted() {
+            writeln!(
+                f,
+                "| {} | {} | {} |",
+                Cell(owner),
+                counts.errors,
+                counts.warnings
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the most common lints of the errors and warnings.
+    fn write_lints(f: &mut fmt::Formatter<'_>, lints: &LintStats) -> fmt::Result {
+        let summary = LintSummary::new(lints);
+        writeln!(f, "#### {}", Title::TopLintOffenders)?;
+        writeln!(f)?;
+        writeln!(f, "| Code | Count |")?;
+        writeln!(f, "| :--- | ----: |")?;
+        for (code, count) in &summary.top {
+            writeln!(f, "| `{}` | {count} |", Cell(code))?;
+        }
+        if summary.omitted > 0 {
+            writeln!(f)?;
+            writeln!(f, "{} more code(s) omitted.", summary.omitted)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for JobSummary<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "### {}", self.tool)?;
+        writeln!(f)?;
+        match self.build_result {
+            Some(true) => write!(f, "**Build finished successfully** with ")?,
+            Some(false) => write!(f, "**Build failed** with ")?,
+            None => {}
+        }
+        writeln!(
+            f,
+            "{} error(s) and {} warning(s).",
+            self.errors, self.warnings
+        )?;
+        if self.incomplete {
+            writeln!(f)?;
+            writeln!(f, "> [!WARNING]")?;
+            writeln!(f, "> The output of {} is incomplete.", self.tool)?;
+        }
+
+        if let Some(report) = self.tests.filter(|report| !report.is_empty()) {
+            writeln!(f)?;
+            Self::write_tests(f, report)?;
+        }
+        if !self.suites.is_empty() {
+            writeln!(f)?;
+            Self::write_suites(f, self.suites)?;
+        }
+        if !self.skips.is_empty() {
+            writeln!(f)?;
+            Self::write_skips(f, self.skips)?;
+        }
+        if !self.packages.is_empty() {
+            writeln!(f)?;
+            Self::write_packages(f, self.packages)?;
+        }
+        if !self.owners.is_empty() {
+            writeln!(f)?;
+            Self::write_owners(f, self.owners)?;
+        }
+        if !self.lints.is_empty() {
+            writeln!(f)?;
+            Self::write_lints(f, self.lints)?;
+        }
+        if let Some(trend) = &self.trend {
+            writeln!(f)?;
+            write!(f, "{trend}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The reason a test failed, truncated to [`JobSummary::MAX_REASON_LENGTH`].
+///
+/// This is the test's message if any, or else the first line of its output
+/// following the location of a panic (i.e., the panic message), or else the
+/// first line of its output.
+fn reason(result: &TestResult<'_>) -> String {
+    let text = result
+        .message
+        .or_else(|| {
+            let output = result.output?;
+            let mut lines = output.lines().filter(|line| !line.trim().is_empty());
+            let first = lines.clone().next();
+            lines
+                .find(|line| line.contains("panicked at"))
+                .and_then(|_| lines.next())
+                .or(first)
+        })
+        .unwrap_or("Test failed")
+        .trim();
+    match text.char_indices().nth(JobSummary::MAX_REASON_LENGTH) {
+        Some((end, _)) => format!("{}…", text.get(..end).unwrap_or(text)),
+        None => text.to_owned(),
+    }
+}
+
+/// Text escaped for use within a cell of a Markdown table.
+///
+/// Pipes would otherwise end the cell, and line breaks the row.
+pub(crate) struct Cell<'a>(pub &'a str);
+
+impl fmt::Display for Cell<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '|' => f.write_str("\\|")?,
+                '\n' | '\r' => f.write_str(" ")?,
+                _ => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The fence of a Markdown code block, longer than any run of backticks
+/// within the text it encloses.
+struct Fence(usize);
+
+impl Fence {
+    /// The fence of a code block enclosing the given text.
+    fn for_text(text: &str) -> Self {
+        let longest = text
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default();
+        Self(longest.saturating_add(1).max(3))
+    }
+}
+
+impl fmt::Display for Fence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for _ in 0..self.0 {
+            f.write_str("`")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{Cell, Fence, JobSummary};
+    use crate::{
+        ci_message::Severity,
+        junit::{Report, TestOutcome, TestResult},
+        lints::LintStats,
+        owners::OwnerStats,
+        packages::PackageStats,
+        skips::SkipStats,
+        suites::{SuiteResult, SuiteStats},
+    };
+
+    #[rstest]
+    #[case::plain("tests::it_works", "tests::it_works")]
+    #[case::pipe("a | b\nc", "a \\| b c")]
+    fn cell(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(Cell(text).to_string(), expected);
+    }
+
+    #[rstest]
+    #[case::plain("boom", "```")]
+    #[case::fenced("```rust\nfn main() {}\n````", "`````")]
+    fn fence(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(Fence::for_text(text).to_string(), expected);
+    }
+
+    #[test]
+    fn render() {
+        let mut report = Report::new("cargo-libtest");
+        report.record(TestResult {
+            duration: Some(Duration::from_millis(1500)),
+            ..TestResult::new("tests::slow", TestOutcome::Passed)
+        });
+        report.record(TestResult {
+            duration: Some(Duration::from_millis(20)),
+            output: Some("\nthread 'tests::fails' panicked at src/lib.rs:3:5:\nboom\n"),
+            ..TestResult::new("tests::fails", TestOutcome::Failed)
+        });
+        report.record(TestResult {
+            message: Some("left | right"),
+            ..TestResult::new("tests::pipes", TestOutcome::Failed)
+        });
+        report.record(TestResult {
+            message: Some("requires docker"),
+            ..TestResult::new("tests::ignored", TestOutcome::Skipped)
+        });
+        let mut lints = LintStats::default();
+        lints.record("unused_variables");
+        let mut packages = PackageStats::default();
+        packages.record("core", Severity::Warning);
+        let mut owners = OwnerStats::default();
+        owners.record(&["@org/core".to_owned()], Severity::Warning);
+        let mut skips = SkipStats::default();
+        for result in report.results() {
+            skips.record(&result);
+        }
+
+        let summary = JobSummary {
+            tool: "cargo-libtest",
+            build_result: Some(false),
+            errors: 2,
+            warnings: 1,
+            incomplete: true,
+            tests: Some(&report),
+            lints: &lints,
+            packages: &packages,
+            owners: &owners,
+            skips: &skips,
+            suites: &SuiteStats::default(),
+            trend: None,
+        };
+        insta::assert_snapshot!(summary.to_string(), @r#"
+        ### cargo-libtest
+
+        **Build failed** with 2 error(s) and 1 warning(s).
+
+        > [!WARNING]
+        > The output of cargo-libtest is incomplete.
+
+        #### Tests
+
+        | Passed | Failed | Skipped | Total |
+        | -----: | -----: | ------: | ----: |
+        | 1 | 2 | 1 | 4 |
+
+        #### Failed tests
+
+        | Test | Reason |
+        | :--- | :----- |
+        | `tests::fails` | boom |
+        | `tests::pipes` | left \| right |
+
+        #### Slowest tests
+
+        | Test | Duration |
+        | :--- | -------: |
+        | `tests::slow` | 1.500s |
+        | `tests::fails` | 0.020s |
+
+        #### Skipped tests
+
+        | Reason | Count |
+        | :----- | ----: |
+        | requires docker | 1 |
+
+        #### Issues by crate
+
+        | Crate | Errors | Warnings |
+        | :---- | -----: | -------: |
+        | `core` | 0 | 1 |
+
+        #### Issues by owner
+
+        | Owner | Errors | Warnings |
+        | :---- | -----: | -------: |
+        | @org/core | 0 | 1 |
+
+        #### Top lint offenders
+
+        | Code | Count |
+        | :--- | ----: |
+        | `unused_variables` | 1 |
+        "#);
+
+        let empty = JobSummary {
+            build_result: None,
+            errors: 0,
+            warnings: 0,
+            incomplete: false,
+            tests: None,
+            lints: &LintStats::default(),
+            packages: &PackageStats::default(),
+            owners: &OwnerStats::default(),
+            skips: &SkipStats::default(),
+            ..summary
+        };
+        insta::assert_snapshot!(empty.to_string(), @"
+        ### cargo-libtest
+
+        0 error(s) and 0 warning(s).
+        ");
+    }
+
+    #[test]
+    fn render_suites() {
+        let mut suites = SuiteStats::default();
+        suites.record_test(&TestResult {
+            output: Some("\nthread 'tests::fails' panicked at src/lib.rs:3:5:\nboom\n"),
+            ..TestResult::new("tests::fails", TestOutcome::Failed)
+        });
+        suites.record_test(&TestResult {
+            message: Some("```\nsnapshot\n```"),
+            ..TestResult::new("tests::fenced", TestOutcome::Failed)
+        });
+        suites.record_suite(SuiteResult {
+            duration: Some(Duration::from_millis(1520)),
+            ..SuiteResult::new(1, 2, 1)
+        });
+        suites.record_suite(SuiteResult::new(3, 0, 0));
+
+        let summary = JobSummary {
+            tool: "cargo-libtest",
+            build_result: None,
+            errors: 0,
+            warnings: 0,
+            incomplete: false,
+            tests: None,
+            lints: &LintStats::default(),
+            packages: &PackageStats::default(),
+            owners: &OwnerStats::default(),
+            skips: &SkipStats::default(),
+            suites: &suites,
+            trend: None,
+        };
+        insta::assert_snapshot!(summary.to_string(), @"
+        ### cargo-libtest
+
+        0 error(s) and 0 warning(s).
+
+        #### Test suites
+
+        | Suite | Passed | Failed | Ignored | Duration |
+        | :---- | -----: | -----: | ------: | -------: |
+        | Suite 1 | 1 | 2 | 1 | 1.520s |
+        | Suite 2 | 3 | 0 | 0 | - |
+
+        <details>
+        <summary>Suite 1: 2 failed test(s)</summary>
+
+        **`tests::fails`**
+
+        ```text
+        thread 'tests::fails' panicked at src/lib.rs:3:5:
+        boom
+        ```
+
+        **`tests::fenced`**
+
+        ````text
+        ```
+        snapshot
+        ```
+        ````
+
+        </details>
+        ");
+    }
+}
@@ -0,0 +1,220 @@
+//! Problem matchers for the plain-text output of tools.
+//!
+//! Rather than streaming a tool's output through cifmt, GitHub Actions can be
+//! told to scan the log for diagnostics itself with a [problem
+//! matcher](https://github.com/actions/toolkit/blob/main/docs/problem-matchers.md):
+//! a set of regular expressions, registered for the subsequent steps with
+//! [`GitHub::add_matcher`](crate::ci::GitHub::add_matcher), each match of
+//! which creates an annotation. This is less precise than parsing the tool's
+//! structured output, but requires no change to how the tool is run.
+//!
+//! Each supported tool provides a [`ProblemMatcher`] for its human-readable
+//! output (e.g., [`CargoCheck::problem_matcher`](crate::tool::CargoCheck::problem_matcher)).
+
+use serde::Serialize;
+
+/// A problem matcher, scanning the log for diagnostics spanning one or more
+/// consecutive lines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "problem matcher is the name used by GitHub"
+)]
+pub struct ProblemMatcher {
+    /// The unique name of the matcher, used to unregister it.
+    pub owner: String,
+    /// The severity of the matches whose pattern captures none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    /// The patterns matching consecutive lines of a diagnostic.
+    pub pattern: Vec<Pattern>,
+}
+
+impl ProblemMatcher {
+    /// Create a problem matcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The unique name of the matcher.
+    /// * `pattern` - The patterns matching consecutive lines of a diagnostic.
+    #[must_use]
+    #[inline]
+    pub fn new(owner: impl Into<String>, pattern: Vec<Pattern>) -> Self {
+        Self {
+            owner: owner.into(),
+            severity: None,
+            pattern,
+        }
+    }
+
+    /// Set the severity of the matches whose pattern captures none.
+    ///
+    /// # Arguments
+    ///
+    /// * `severity` - Either `error` or `warning`.
+    #[must_use]
+    #[inline]
+    pub fn with_severity(mut self, severity: impl Into<String>) -> Self {
+        self.severity = Some(severity.into());
+        self
+    }
+
+    /// The JSON definition of the matcher, as read by
+    /// [`GitHub::add_matcher`](crate::ci::GitHub::add_matcher).
+    #[must_use]
+    #[inline]
+    pub fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Definition<'a> {
+            #[serde(rename = "problemMatcher")]
+            problem_matcher: [&'a ProblemMatcher; 1],
+        }
+
+        serde_json::to_string_pretty(&Definition {
+            problem_matcher: [self],
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// A regular expression matching a line of a diagnostic, and the capture
+/// groups holding each of its properties.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Pattern {
+    /// The regular expression, in JavaScript syntax.
+    pub regexp: String,
+    /// The group capturing the severity (`error` or `warning`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<usize>,
+    /// The group capturing the path of the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<usize>,
+    /// The group capturing the line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// The group capturing the column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// The group capturing the code of the diagnostic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<usize>,
+    /// The group capturing the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<usize>,
+    /// Whether the pattern matches each of the following lines, until one
+    /// does not match (only valid for the last pattern of a matcher).
+    #[serde(rename = "loop", skip_serializing_if = "core::ops::Not::not")]
+    pub repeat: bool,
+}
+
+impl Pattern {
+    /// Create a pattern capturing no properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `regexp` - The regular expression, in JavaScript syntax.
+    #[must_use]
+    #[inline]
+    pub fn new(regexp: impl Into<String>) -> Self {
+        Self {
+            regexp: regexp.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the group capturing the severity.
+    #[must_use]
+    #[inline]
+    pub const fn severity(mut self, group: usize) -> Self {
+        self.severity = Some(group);
+        self
+    }
+
+    /// Set the group capturing the path of the file.
+    #[must_use]
+    #[inline]
+    pub const fn file(mut self, group: usize) -> Self {
+        self.file = Some(group);
+        self
+    }
+
+    /// Set the group capturing the line.
+    #[must_use]
+    #[inline]
+    pub const fn line(mut self, group: usize) -> Self {
+        self.line = Some(group);
+        self
+    }
+
+    /// Set the group capturing the column.
+    #[must_use]
+    #[inline]
+    pub const fn column(mut self, group: usize) -> Self {
+        self.column = Some(group);
+        self
+    }
+
+    /// Set the group capturing the code of the diagnostic.
+    #[must_use]
+    #[inline]
+    pub const fn code(mut self, group: usize) -> Self {
+        self.code = Some(group);
+        self
+    }
+
+    /// Set the group capturing the message.
+    #[must_use]
+    #[inline]
+    pub const fn message(mut self, group: usize) -> Self {
+        self.message = Some(group);
+        self
+    }
+
+    /// Match each of the following lines, creating an annotation for each,
+    /// until one does not match.
+    #[must_use]
+    #[inline]
+    pub const fn repeated(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, ProblemMatcher};
+
+    #[test]
+    fn to_json() {
+        let matcher = ProblemMatcher::new(
+            "example",
+            vec![
+                Pattern::new(r"^(\S+):(\d+): (.+)$")
+                    .file(1)
+                    .line(2)
+                    .message(3),
+            ],
+        )
+        .with_severity("warning");
+        insta::assert_snapshot!(matcher.to_json(), @r#"
+        {
+          "problemMatcher": [
+            {
+              "owner": "example",
+              "severity": "warning",
+              "pattern": [
+                {
+                  "regexp": "^(\\S+):(\\d+): (.+)$",
+                  "file": 1,
+                  "line": 2,
+                  "message": 3
+                }
+              ]
+            }
+          ]
+        }
+        "#);
+    }
+}
@@ -0,0 +1,269 @@
+//! Metadata describing a run of cifmt.
+//!
+//! To trace a log or a report back to the run which produced it, a
+//! [`RunMetadata`] records the tool whose output was formatted, the version
+//! of cifmt, the platforms formatted for, the command line, the host and the
+//! time at which the run started. Given to a pipeline (see
+//! [`Pipeline::with_metadata`](crate::pipeline::Pipeline::with_metadata)),
+//! it is written as a group at the start of each target, and included in the
+//! JUnit report and the Chrome trace.
+
+use core::time::Duration;
+use std::{
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::ci::PlatformKind;
+
+/// Metadata describing a run of cifmt.
+///
+/// See the [module documentation](self) for details.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::ci::PlatformKind;
+/// use cifmt::metadata::RunMetadata;
+///
+/// let metadata = RunMetadata::new("cargo-check")
+///     .with_platforms([PlatformKind::GitHub])
+///     .with_command_line(["cifmt", "format", "cargo-check"]);
+/// assert_eq!(metadata.properties()[0], ("tool", "cargo-check".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "metadata alone does not say what it describes"
+)]
+pub struct RunMetadata {
+    /// The name of the tool whose output is formatted.
+    pub tool: String,
+    /// The version of cifmt.
+    pub version: String,
+    /// The platforms formatted for.
+    pub platforms: Vec<PlatformKind>,
+    /// The command line of the run, if known.
+    pub command_line: Option<String>,
+    /// The operating system of the host (e.g., `linux`).
+    pub os: String,
+    /// The architecture of the host (e.g., `x86_64`).
+    pub arch: String,
+    /// The time at which the run started, in RFC 3339 format (UTC).
+    pub timestamp: String,
+}
+
+impl RunMetadata {
+    /// Collect the metadata of the current run.
+    ///
+    /// The version, host and timestamp are taken from the current process;
+    /// the platforms and command line are unknown until set.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool whose output is formatted.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            platforms: Vec::new(),
+            command_line: None,
+            os: env::consts::OS.to_owned(),
+            arch: env::consts::ARCH.to_owned(),
+            timestamp: now(),
+        }
+    }
+
+    /// Set the platforms formatted for.
+    ///
+    /// # Arguments
+    ///
+    /// * `platforms` - The platform of each target, in order.
+    #[must_use]
+    #[inline]
+    pub fn with_platforms(mut self, platforms: impl IntoIterator<Item = PlatformKind>) -> Self {
+        self.platforms = platforms.into_iter().collect();
+        self
+    }
+
+    /// Set the command line of the run.
+    ///
+    /// Arguments containing whitespace are quoted.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments, starting with the program.
+    #[must_use]
+    #[inline]
+    pub fn with_command_line(mut self, args: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let quoted: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                let text = arg.as_ref();
+                if text.is_empty() || text.contains(char::is_whitespace) {
+                    format!("'{}'", text.replace('\'', r"'\''"))
+                } else {
+                    text.to_owned()
+                }
+            })
+            .collect();
+        self.command_line = Some(quoted.join(" "));
+        self
+    }
+
+    /// The metadata as named properties, in the order in which they are
+    /// written.
+    ///
+    /// The command line is omitted if unknown.
+    #[must_use]
+    #[inline]
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        let platforms: Vec<_> = self.platforms.iter().map(ToString::to_string).collect();
+        let mut properties = vec![
+            ("tool", self.tool.clone()),
+            ("cifmt-version", self.version.clone()),
+            ("platform", platforms.join(", ")),
+        ];
+        if let Some(command_line) = &self.command_line {
+            properties.push(("command-line", command_line.clone()));
+        }
+        properties.extend([
+            ("os", self.os.clone()),
+            ("arch", self.arch.clone()),
+            ("timestamp", self.timestamp.clone()),
+        ]);
+        properties
+    }
+}
+
+/// The current time, in RFC 3339 format (UTC).
+pub(crate) fn now() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    rfc3339(since_epoch)
+}
+
+/// Format a time as RFC 3339 in UTC, to the second.
+///
+/// # Arguments
+///
+/// * `since_epoch` - The time since the Unix epoch.
+fn rfc3339(since_epoch: Duration) -> String {
+    let secs = since_epoch.as_secs();
+    let days = secs.div_euclid(86_400);
+    let time = secs.rem_euclid(86_400);
+
+    // Convert the number of days to a civil date, following
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let shifted = days.saturating_add(719_468);
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era = day_of_era
+        .saturating_sub(day_of_era.div_euclid(1_460))
+        .saturating_add(day_of_era.div_euclid(36_524))
+        .saturating_sub(day_of_era.div_euclid(146_096))
+        .div_euclid(365);
+    let day_of_year = day_of_era.saturating_sub(
+        year_of_era
+            .saturating_mul(365)
+            .saturating_add(year_of_era.div_euclid(4))
+            .saturating_sub(year_of_era.div_euclid(100)),
+    );
+    let shifted_month = day_of_year
+        .saturating_mul(5)
+        .saturating_add(2)
+        .div_euclid(153);
+    let day = day_of_year
+        .saturating_sub(
+            shifted_month
+                .saturating_mul(153)
+                .saturating_add(2)
+                .div_euclid(5),
+        )
+        .saturating_add(1);
+    let (month, year_offset) = if shifted_month < 10 {
+        (shifted_month.saturating_add(3), 0)
+    } else {
+        (shifted_month.saturating_sub(9), 1)
+    };
+    let year = era
+        .saturating_mul(400)
+        .saturating_add(year_of_era)
+        .saturating_add(year_offset);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time.div_euclid(3_600),
+        time.rem_euclid(3_600).div_euclid(60),
+        time.rem_euclid(60)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{RunMetadata, rfc3339};
+    use crate::ci::PlatformKind;
+
+    #[rstest]
+    #[case::epoch(0, "1970-01-01T00:00:00Z")]
+    #[case::leap_day(951_782_400, "2000-02-29T00:00:00Z")]
+    #[case::end_of_year(1_704_067_199, "2023-12-31T23:59:59Z")]
+    #[case::recent(1_791_980_130, "2026-10-14T12:15:30Z")]
+    fn formats_rfc3339(#[case] secs: u64, #[case] expected: &str) {
+        assert_eq!(rfc3339(Duration::from_secs(secs)), expected);
+    }
+
+    #[test]
+    fn properties() {
+        let metadata = RunMetadata {
+            timestamp: "2026-10-14T12:15:30Z".to_owned(),
+            os: "linux".to_owned(),
+            arch: "x86_64".to_owned(),
+            version: "1.2.3".to_owned(),
+            ..RunMetadata::new("cargo-check")
+                .with_platforms([PlatformKind::GitHub, PlatformKind::Plain])
+                .with_command_line(["cifmt", "format", "--output", "plain=log file.txt"])
+        };
+        insta::assert_debug_snapshot!(metadata.properties(), @r#"
+        [
+            (
+                "tool",
+                "cargo-check",
+            ),
+            (
+                "cifmt-version",
+                "1.2.3",
+            ),
+            (
+                "platform",
+                "github, plain",
+            ),
+            (
+                "command-line",
+                "cifmt format --output 'plain=log file.txt'",
+            ),
+            (
+                "os",
+                "linux",
+            ),
+            (
+                "arch",
+                "x86_64",
+            ),
+            (
+                "timestamp",
+                "2026-10-14T12:15:30Z",
+            ),
+        ]
+        "#);
+    }
+}
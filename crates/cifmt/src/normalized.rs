@@ -0,0 +1,281 @@
+//! Normalized JSON records of the parsed messages.
+//!
+//! Each tool reports its messages in its own shape. A [`NormalizedMessage`]
+//! describes a parsed message in a schema which is the same for all tools,
+//! for scripts which consume the output of any tool supported without
+//! parsing it themselves: its severity, code, title and body, the locations
+//! it annotates, the result of the test it reports and its timing. A
+//! pipeline can write the record of each message as a line of JSON as it is
+//! parsed (see
+//! [`Pipeline::with_normalized_messages`](crate::pipeline::Pipeline::with_normalized_messages)).
+//!
+//! For example, the record of a warning of rustc is:
+//!
+//! ```json
+//! {"tool":"cargo-check","kind":"Diagnostic","severity":"warning","code":"unused_variables","package":"project","title":"warning: unused_variables","body":"unused variable: `x`","locations":[{"file":"src/lib.rs","line":2,"column":9,"end_line":2,"end_column":10}],"fingerprint":"8ceeb276274fe423","test":null,"timing":null}
+//! ```
+//!
+//! Unlike the [raw records](crate::raw), which describe the classification
+//! of a message to debug a parser, the schema is stable: every key is present
+//! in each record, with `null` (or an empty list of locations) for what does
+//! not apply to the message, and keys are only ever added. The locations are
+//! those of the message's annotations as formatted for GitHub Actions, so
+//! that the paths are relative to the root of the checkout, and durations
+//! are in seconds.
+
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+use serde::Serialize;
+
+use crate::{
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::DynCiMessage,
+    fingerprint::Fingerprint,
+    junit::TestOutcome,
+    timeline::Timing,
+};
+
+/// A location annotated by a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Location {
+    /// The path of the file, relative to the root of the checkout.
+    pub file: String,
+    /// The line of the location, if known.
+    pub line: Option<u32>,
+    /// The column of the location, if known.
+    pub column: Option<u32>,
+    /// The last line of the location, if known.
+    pub end_line: Option<u32>,
+    /// The last column of the location, if known.
+    pub end_column: Option<u32>,
+}
+
+/// The result of the test reported by a message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Test<'a> {
+    /// The full name of the test.
+    pub name: &'a str,
+    /// The outcome of the test: `passed`, `failed` or `skipped`.
+    pub outcome: &'static str,
+    /// How long the test took to run, in seconds, if known.
+    pub duration: Option<f64>,
+}
+
+/// The timing information carried by a message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Time<'a> {
+    /// What the message reports: the `start` or the `end` of a section, or
+    /// something which has just completed (`elapsed`).
+    pub event: &'static str,
+    /// The track (e.g., crate or test name) to which the section belongs.
+    pub track: &'a str,
+    /// The name of the section.
+    pub name: &'a str,
+    /// The category of what has completed (e.g., `build` or `test`), if
+    /// known.
+    pub category: Option<&'a str>,
+    /// The time at which the section started or ended according to the
+    /// tool's own clock, or how long what has completed took, in seconds.
+    pub seconds: f64,
+}
+
+impl<'a> From<Timing<'a>> for Time<'a> {
+    #[inline]
+    fn from(timing: Timing<'a>) -> Self {
+        match timing {
+            Timing::SectionStart { track, name, time } => Self {
+                event: "start",
+                track,
+                name,
+                category: None,
+                seconds: time.as_secs_f64(),
+            },
+            Timing::SectionEnd { track, name, time } => Self {
+                event: "end",
+                track,
+                name,
+                category: None,
+                seconds: time.as_secs_f64(),
+            },
+            Timing::Elapsed {
+                track,
+                name,
+                category,
+                duration,
+            } => Self {
+                event: "elapsed",
+                track,
+                name,
+                category: Some(category),
+                seconds: duration.as_secs_f64(),
+            },
+        }
+    }
+}
+
+/// The normalized record of a parsed message.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "the record is of a message, and would be ambiguous as a bare `Message`"
+)]
+pub struct NormalizedMessage<'a> {
+    /// The name of the tool which parsed the message.
+    pub tool: &'a str,
+    /// The kind of the message (e.g., `Diagnostic`).
+    pub kind: &'static str,
+    /// The severity of the message (e.g., `warning`).
+    pub severity: String,
+    /// The code of the issue reported by the message, if any.
+    pub code: Option<&'a str>,
+    /// The package to which the message belongs, if any.
+    pub package: Option<&'a str>,
+    /// The title of the message's first annotation, if any.
+    pub title: Option<String>,
+    /// The message of the diagnostic reported by the message, or else of its
+    /// first annotation, or else the message formatted as plain text.
+    pub body: String,
+    /// The locations annotated by the message.
+    pub locations: Vec<Location>,
+    /// The fingerprint of the diagnostic reported by the message, if any.
+    pub fingerprint: Option<String>,
+    /// The result of the test reported by the message, if any.
+    pub test: Option<Test<'a>>,
+    /// The timing information carried by the message, if any.
+    pub timing: Option<Time<'a>>,
+}
+
+impl<'a> NormalizedMessage<'a> {
+    /// Describe a parsed message.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool which parsed the message.
+    /// * `message` - The message.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: &'a str, message: &'a dyn DynCiMessage) -> Self {
+        let formatted = message.format_for(PlatformKind::GitHub);
+        let annotations: Vec<_> = formatted
+            .lines()
+            .filter_map(WorkflowAnnotation::parse)
+            .collect();
+        let title = annotations
+            .first()
+            .and_then(|annotation| annotation.param("title"))
+            .map(Cow::into_owned);
+        let body = message.message().map_or_else(
+            || {
+                annotations
+                    .first()
+                    .map(|annotation| annotation.message().into_owned())
+                    .filter(|text| !text.is_empty())
+                    .unwrap_or_else(|| message.format_for(PlatformKind::Plain))
+            },
+            str::to_owned,
+        );
+        let locations = annotations
+            .iter()
+            .filter_map(|annotation| {
+                let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+                Some(Location {
+                    file: annotation.param("file")?.into_owned(),
+                    line: number("line"),
+                    column: number("col"),
+                    end_line: number("endLine"),
+                    end_column: number("endColumn"),
+                })
+            })
+            .collect();
+        Self {
+            tool,
+            kind: message.kind(),
+            severity: message.severity().to_string(),
+            code: message.code(),
+            package: message.package(),
+            title,
+            body,
+            locations,
+            fingerprint: Fingerprint::of(tool, message).map(|print| print.to_string()),
+            test: message.test_result().map(|result| Test {
+                name: result.name,
+                outcome: match result.outcome {
+                    TestOutcome::Passed => "passed",
+                    TestOutcome::Failed => "failed",
+                    TestOutcome::Skipped => "skipped",
+                },
+                duration: result.duration.map(|duration| duration.as_secs_f64()),
+            }),
+            timing: message.timing().map(Time::from),
+        }
+    }
+
+    /// Write the record as a single line of JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `writer` fails.
+    #[inline]
+    pub fn write_json_line(&self, mut writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer(&mut writer, self)?;
+        writer.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::NormalizedMessage;
+    use crate::tool::{AnyTool, CargoCheck, CargoLibtest};
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+"#;
+
+    /// The normalized records of the messages parsed by a tool.
+    fn records(tool: &mut dyn AnyTool, input: &str) -> String {
+        let mut messages = tool.parse_dyn(input.as_bytes());
+        messages.extend(tool.finish_dyn());
+        let mut bytes = Vec::new();
+        for message in messages {
+            NormalizedMessage::new(tool.name(), &*message)
+                .write_json_line(&mut bytes)
+                .expect("written");
+        }
+        String::from_utf8(bytes).expect("valid UTF-8")
+    }
+
+    #[test]
+    fn diagnostic() {
+        let output = records(&mut CargoCheck::default(), WARNING);
+        assert_eq!(output.lines().count(), 2);
+        insta::assert_snapshot!(output, @r#"
+        {"tool":"cargo-check","kind":"Diagnostic","severity":"warning","code":"unused_variables","package":"project","title":"warning: unused_variables","body":"unused variable: `x`","locations":[{"file":"src/lib.rs","line":2,"column":9,"end_line":2,"end_column":10}],"fingerprint":"8ceeb276274fe423","test":null,"timing":null}
+        {"tool":"cargo-check","kind":"IncompleteBuild","severity":"warning","code":null,"package":null,"title":"Incomplete Build","body":"Cargo's output ended without reporting the result of the build","locations":[],"fingerprint":null,"test":null,"timing":null}
+        "#);
+    }
+
+    #[test]
+    fn test() {
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"tests::a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::a","exec_time":0.25,"stdout":"boom\n"}"#,
+            "\n",
+        );
+        let output = records(&mut CargoLibtest::default(), input);
+        insta::assert_snapshot!(output, @r#"
+        {"tool":"cargo-libtest","kind":"TestStarted","severity":"debug","code":null,"package":null,"title":null,"body":"Test: tests::a","locations":[],"fingerprint":null,"test":null,"timing":null}
+        {"tool":"cargo-libtest","kind":"TestFailed","severity":"error","code":null,"package":null,"title":"Test Failed: tests::a (executed in 0.25s)","body":"boom\n\nerror:   Test Failed: tests::a (executed in 0.25s)","locations":[],"fingerprint":null,"test":{"name":"tests::a","outcome":"failed","duration":0.25},"timing":{"event":"elapsed","track":"tests::a","name":"tests::a","category":"test","seconds":0.25}}
+        "#);
+    }
+}
@@ -0,0 +1,866 @@
+//! Notifications of the result of a run, posted to chat.
+//!
+//! Teams which monitor their CI through chat want to hear about a run without
+//! opening its log. A [`Notifier`] (see [`Pipeline::with_notifier`]) posts a
+//! summary of the run to one or more incoming webhooks once the tool's output
+//! has been processed: whether it passed, the number of errors, warnings and
+//! failed tests, and the first failures (see
+//! [`Notifier::with_max_failures`]). Each failure links to the line of its
+//! error, or else to the log of the job.
+//!
+//! Each [`Webhook`] is that of a channel on
+//! [Slack](https://api.slack.com/messaging/webhooks), where the summary is
+//! laid out with Block Kit, on Microsoft
+//! [Teams](https://learn.microsoft.com/en-us/microsoftteams/platform/webhooks-and-connectors/how-to/connectors-using),
+//! where it is an Adaptive Card, or on
+//! [Discord](https://discord.com/developers/docs/resources/webhook), where it
+//! is an embed. The job and the links are taken from the environment of
+//! GitHub Actions, GitLab CI or Buildkite (see
+//! [`Notifier::with_run_from_env`]).
+//!
+//! This module requires the `notify` feature.
+//!
+//! [`Pipeline::with_notifier`]: crate::pipeline::Pipeline::with_notifier
+
+use core::{fmt, time::Duration};
+use std::env;
+
+use serde_json::{Value, json};
+
+use crate::{
+    catalog::Title,
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+    http,
+    junit::TestOutcome,
+};
+
+/// The maximum length of the title of a failure, in characters.
+const MAX_TITLE: usize = 150;
+
+/// The maximum length of the text of a section of a Slack message, in
+/// characters.
+const MAX_SLACK_TEXT: usize = 3000;
+
+/// The maximum length of the list of failures of an Adaptive Card, in
+/// characters, which keeps the card within the size Teams accepts.
+const MAX_TEAMS_TEXT: usize = 3000;
+
+/// The maximum length of the description of a Discord embed, in characters.
+const MAX_DISCORD_TEXT: usize = 4096;
+
+/// The maximum length of the title of a Discord embed, in characters.
+const MAX_DISCORD_TITLE: usize = 256;
+
+/// The color of the embed of a run which passed, as an RGB integer.
+const DISCORD_PASSED: u32 = 0x001a_7f37;
+
+/// The color of the embed of a run which failed, as an RGB integer.
+const DISCORD_FAILED: u32 = 0x00cf_222e;
+
+/// The webhook to which notifications are posted.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Webhook {
+    /// An incoming webhook of a Slack channel.
+    Slack {
+        /// The URL of the webhook, which grants anyone holding it the right
+        /// to post to the channel.
+        url: String,
+    },
+    /// An incoming webhook of a Microsoft Teams channel (e.g., created by a
+    /// Workflows app), which receives Adaptive Cards.
+    Teams {
+        /// The URL of the webhook, which grants anyone holding it the right
+        /// to post to the channel.
+        url: String,
+    },
+    /// A webhook of a Discord channel.
+    Discord {
+        /// The URL of the webhook, which grants anyone holding it the right
+        /// to post to the channel.
+        url: String,
+    },
+}
+
+impl Webhook {
+    /// Environment variable holding the URL of the Slack webhook.
+    pub const SLACK_ENV: &str = "SLACK_WEBHOOK_URL";
+
+    /// Environment variable holding the URL of the Teams webhook.
+    pub const TEAMS_ENV: &str = "TEAMS_WEBHOOK_URL";
+
+    /// Environment variable holding the URL of the Discord webhook.
+    pub const DISCORD_ENV: &str = "DISCORD_WEBHOOK_URL";
+
+    /// The Slack webhook set in the environment (see
+    /// [`Webhook::SLACK_ENV`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the webhook is not set in the environment.
+    #[inline]
+    pub fn slack_from_env() -> Result<Self, Error> {
+        Ok(Self::Slack {
+            url: required(Self::SLACK_ENV)?,
+        })
+    }
+
+    /// The Teams webhook set in the environment (see
+    /// [`Webhook::TEAMS_ENV`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the webhook is not set in the environment.
+    #[inline]
+    pub fn teams_from_env() -> Result<Self, Error> {
+        Ok(Self::Teams {
+            url: required(Self::TEAMS_ENV)?,
+        })
+    }
+
+    /// The Discord webhook set in the environment (see
+    /// [`Webhook::DISCORD_ENV`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the webhook is not set in the environment.
+    #[inline]
+    pub fn discord_from_env() -> Result<Self, Error> {
+        Ok(Self::Discord {
+            url: required(Self::DISCORD_ENV)?,
+        })
+    }
+
+    /// The URL to which notifications are posted.
+    fn url(&self) -> &str {
+        match self {
+            Self::Slack { url } | Self::Teams { url } | Self::Discord { url } => url,
+        }
+    }
+}
+
+impl fmt::Debug for Webhook {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Slack { .. } => f.debug_struct("Slack").field("url", &"[redacted]").finish(),
+            Self::Teams { .. } => f.debug_struct("Teams").field("url", &"[redacted]").finish(),
+            Self::Discord { .. } => f
+                .debug_struct("Discord")
+                .field("url", &"[redacted]")
+                .finish(),
+        }
+    }
+}
+
+/// A failure of the run, listed in the notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Failure {
+    /// The title of the failure (e.g., `Test Failed: tests::it_works`).
+    pub title: String,
+    /// The URL of the failure's source or log, if known.
+    pub link: Option<String>,
+}
+
+/// Posts a summary of a run to chat.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    /// The webhooks to which the summary is posted.
+    webhooks: Vec<Webhook>,
+    /// The maximum number of failures listed.
+    max_failures: usize,
+    /// The name of the job, if known.
+    job: Option<String>,
+    /// The URL of the job's log, if known.
+    run_url: Option<String>,
+    /// The URL of the sources at the commit built, if known.
+    source_url: Option<String>,
+    /// Number of errors reported.
+    errors: usize,
+    /// Number of warnings reported.
+    warnings: usize,
+    /// Number of tests run.
+    tests: usize,
+    /// Number of tests which failed.
+    failed_tests: usize,
+    /// The first failures of the run.
+    failures: Vec<Failure>,
+    /// Number of failures beyond those listed.
+    omitted: usize,
+}
+
+impl Notifier {
+    /// The number of failures listed, unless set otherwise.
+    pub const DEFAULT_MAX_FAILURES: usize = 5;
+
+    /// Post notifications to the given webhook.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook.
+    #[must_use]
+    #[inline]
+    pub fn new(webhook: Webhook) -> Self {
+        Self {
+            webhooks: vec![webhook],
+            max_failures: Self::DEFAULT_MAX_FAILURES,
+            job: None,
+            run_url: None,
+            source_url: None,
+            errors: 0,
+            warnings: 0,
+            tests: 0,
+            failed_tests: 0,
+            failures: Vec::new(),
+            omitted: 0,
+        }
+    }
+
+    /// Also post notifications to another webhook (e.g., to broadcast the
+    /// summary to the channels of several services).
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook.
+    #[must_use]
+    #[inline]
+    pub fn with_webhook(mut self, webhook: Webhook) -> Self {
+        self.webhooks.push(webhook);
+        self
+    }
+
+    /// Set the maximum number of failures listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of failures.
+    #[must_use]
+    #[inline]
+    pub fn with_max_failures(mut self, max: usize) -> Self {
+        self.max_failures = max;
+        self
+    }
+
+    /// Set the job of the run, mentioned and linked in the notification.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The name of the job, if known.
+    /// * `run_url` - The URL of the job's log, if known.
+    #[must_use]
+    #[inline]
+    pub fn with_run(mut self, job: Option<String>, run_url: Option<String>) -> Self {
+        self.job = job;
+        self.run_url = run_url;
+        self
+    }
+
+    /// Set the URL of the sources at the commit built (e.g.,
+    /// `https://github.com/owner/repo/blob/<sha>`), to which the path and the
+    /// line of an error are appended to link to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the sources, if known.
+    #[must_use]
+    #[inline]
+    pub fn with_source_url(mut self, url: Option<String>) -> Self {
+        self.source_url = url;
+        self
+    }
+
+    /// Set the job of the run and the URL of its sources from the environment
+    /// of GitHub Actions, GitLab CI or Buildkite, if any.
+    #[must_use]
+    #[inline]
+    pub fn with_run_from_env(self) -> Self {
+        let var = |key: &str| env::var(key).ok();
+        if var("GITHUB_ACTIONS").is_some() {
+            let repository = var("GITHUB_SERVER_URL").zip(var("GITHUB_REPOSITORY"));
+            let run_url = repository
+                .clone()
+                .zip(var("GITHUB_RUN_ID"))
+                .map(|((server, repo), run)| format!("{server}/{repo}/actions/runs/{run}"));
+            let source_url = repository
+                .zip(var("GITHUB_SHA"))
+                .map(|((server, repo), sha)| format!("{server}/{repo}/blob/{sha}"));
+            self.with_run(var("GITHUB_JOB"), run_url)
+                .with_source_url(source_url)
+        } else if var("GITLAB_CI").is_some() {
+            let source_url = var("CI_PROJECT_URL")
+                .zip(var("CI_COMMIT_SHA"))
+                .map(|(project, sha)| format!("{project}/-/blob/{sha}"));
+            self.with_run(var("CI_JOB_NAME"), var("CI_JOB_URL"))
+                .with_source_url(source_url)
+        } else if var("BUILDKITE").is_some() {
+            let run_url = var("BUILDKITE_BUILD_URL").map(|build| match var("BUILDKITE_JOB_ID") {
+                Some(id) => format!("{build}#{id}"),
+                None => build,
+            });
+            self.with_run(var("BUILDKITE_LABEL"), run_url)
+        } else {
+            self
+        }
+    }
+
+    /// Record the outcome of a message: the result of the test it reports,
+    /// or else its severity, and its failure, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        // The results of test suites are not failures of their own, since
+        // their failed tests are, and failed tests are counted apart from
+        // the errors.
+        if message.suite_result().is_some() {
+            return;
+        }
+        let failure = if let Some(result) = message.test_result() {
+            self.tests = self.tests.saturating_add(1);
+            if result.outcome != TestOutcome::Failed {
+                return;
+            }
+            self.failed_tests = self.failed_tests.saturating_add(1);
+            Failure {
+                title: format!("{}: {}", Title::TestFailed.text(), result.name),
+                link: self.run_url.clone(),
+            }
+        } else {
+            match message.severity() {
+                Severity::Error => self.errors = self.errors.saturating_add(1),
+                Severity::Warning => self.warnings = self.warnings.saturating_add(1),
+                Severity::Debug | Severity::Notice => {}
+            }
+            if message.severity() < Severity::Error {
+                return;
+            }
+            let details = message.format_for(PlatformKind::Plain);
+            let Some(summary) = details.lines().find(|line| !line.trim().is_empty()) else {
+                return;
+            };
+            Failure {
+                title: summary.trim().to_owned(),
+                link: self.source_link(message).or_else(|| self.run_url.clone()),
+            }
+        };
+        if self.failures.len() < self.max_failures {
+            self.failures.push(Failure {
+                title: truncate(&failure.title, MAX_TITLE),
+                ..failure
+            });
+        } else {
+            self.omitted = self.omitted.saturating_add(1);
+        }
+    }
+
+    /// The link to the line of the first annotation of a message, if the
+    /// URL of the sources is known.
+    fn source_link(&self, message: &dyn DynCiMessage) -> Option<String> {
+        let source_url = self.source_url.as_deref()?;
+        let formatted = message.format_for(PlatformKind::GitHub);
+        let annotation = formatted
+            .lines()
+            .filter_map(WorkflowAnnotation::parse)
+            .find(|annotation| annotation.param("file").is_some())?;
+        let path = annotation.param("file")?;
+        Some(match annotation.param("line") {
+            Some(line) => format!("{source_url}/{path}#L{line}"),
+            None => format!("{source_url}/{path}"),
+        })
+    }
+
+    /// Whether the run passed, without errors or failed tests.
+    #[must_use]
+    #[inline]
+    pub fn passed(&self) -> bool {
+        self.errors == 0 && self.failed_tests == 0
+    }
+
+    /// The first failures of the run, as listed in the notification.
+    #[must_use]
+    #[inline]
+    pub fn failures(&self) -> &[Failure] {
+        &self.failures
+    }
+
+    /// The number of issues and tests of the run, and how long it took.
+    fn counts(&self, elapsed: Duration) -> String {
+        let tests = if self.tests > 0 {
+            format!(", {} of {} test(s) failed", self.failed_tests, self.tests)
+        } else {
+            String::new()
+        };
+        format!(
+            "{} error(s), {} warning(s){tests} in {:.1}s",
+            self.errors,
+            self.warnings,
+            elapsed.as_secs_f64()
+        )
+    }
+
+    /// Whether the run passed or failed, as described in the notification.
+    fn status(&self) -> &'static str {
+        if self.passed() { "passed" } else { "failed" }
+    }
+
+    /// The items listing the failures, each formatted by `item`, followed by
+    /// the number of failures omitted, if any.
+    ///
+    /// Failures beyond `max` bytes are omitted, leaving room for the number
+    /// omitted.
+    fn failure_items(&self, max: usize, item: impl Fn(&Failure) -> String) -> Vec<String> {
+        let mut items = Vec::new();
+        let mut length = 0_usize;
+        let mut omitted = self.omitted;
+        for failure in &self.failures {
+            let formatted = item(failure);
+            length = length.saturating_add(formatted.len()).saturating_add(1);
+            if length > max.saturating_sub(50) {
+                omitted = omitted.saturating_add(1);
+            } else {
+                items.push(formatted);
+            }
+        }
+        if omitted > 0 {
+            items.push(format!("…and {omitted} more"));
+        }
+        items
+    }
+
+    /// The payload of the notification posted to a webhook.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook, whose service determines the layout.
+    /// * `tool` - The name of the tool which was run.
+    /// * `elapsed` - How long the run took.
+    #[must_use]
+    #[inline]
+    pub fn payload(&self, webhook: &Webhook, tool: &str, elapsed: Duration) -> Value {
+        match webhook {
+            Webhook::Slack { .. } => self.slack_payload(tool, elapsed),
+            Webhook::Teams { .. } => self.teams_payload(tool, elapsed),
+            Webhook::Discord { .. } => self.discord_payload(tool, elapsed),
+        }
+    }
+
+    /// The payload of a Slack message, laid out with Block Kit.
+    fn slack_payload(&self, tool: &str, elapsed: Duration) -> Value {
+        let status = self.status();
+        let emoji = if self.passed() {
+            ":white_check_mark:"
+        } else {
+            ":x:"
+        };
+        let job = self
+            .job
+            .as_deref()
+            .map_or_else(String::new, |job| format!(" in `{}`", slack_escape(job)));
+        let log = self
+            .run_url
+            .as_deref()
+            .map_or_else(String::new, |url| format!(" (<{url}|log>)"));
+        let headline = format!("{emoji} *{}* {status}{job}{log}", slack_escape(tool));
+        let counts = self.counts(elapsed);
+        let mut blocks = vec![
+            json!({ "type": "section", "text": { "type": "mrkdwn", "text": headline } }),
+            json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": counts }] }),
+        ];
+        if !self.failures.is_empty() {
+            let items = self.failure_items(MAX_SLACK_TEXT, |failure| {
+                let title = slack_escape(&failure.title);
+                match &failure.link {
+                    Some(link) => format!("• <{link}|{title}>"),
+                    None => format!("• {title}"),
+                }
+            });
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": items.join("\n") },
+            }));
+        }
+
+        json!({
+            "text": format!("{tool} {status}: {counts}"),
+            "blocks": blocks,
+        })
+    }
+
+    /// The payload of a Teams message, as an Adaptive Card.
+    fn teams_payload(&self, tool: &str, elapsed: Duration) -> Value {
+        let job = self
+            .job
+            .as_deref()
+            .map_or_else(String::new, |job| format!(" in {}", markdown_escape(job)));
+        let mut body = vec![
+            json!({
+                "type": "TextBlock",
+                "text": format!("{} {}{job}", markdown_escape(tool), self.status()),
+                "size": "Medium",
+                "weight": "Bolder",
+                "color": if self.passed() { "Good" } else { "Attention" },
+                "wrap": true,
+            }),
+            json!({
+                "type": "TextBlock",
+                "text": self.counts(elapsed),
+                "isSubtle": true,
+                "spacing": "None",
+                "wrap": true,
+            }),
+        ];
+        if !self.failures.is_empty() {
+            let items = self.failure_items(MAX_TEAMS_TEXT, |failure| {
+                let title = markdown_escape(&failure.title);
+                match &failure.link {
+                    Some(link) => format!("- [{title}]({link})"),
+                    None => format!("- {title}"),
+                }
+            });
+            // Adaptive Cards separate the items of a list by carriage returns.
+            body.push(json!({ "type": "TextBlock", "text": items.join("\r"), "wrap": true }));
+        }
+        let actions: Vec<Value> = self
+            .run_url
+            .iter()
+            .map(|url| json!({ "type": "Action.OpenUrl", "title": "View log", "url": url }))
+            .collect();
+
+        json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": body,
+                    "actions": actions,
+                },
+            }],
+        })
+    }
+
+    /// The payload of a Discord message, as an embed.
+    fn discord_payload(&self, tool: &str, elapsed: Duration) -> Value {
+        let job = self
+            .job
+            .as_deref()
+            .map_or_else(String::new, |job| format!(" in {job}"));
+        let headline = truncate(&format!("{tool} {}{job}", self.status()), MAX_DISCORD_TITLE);
+        let counts = self.counts(elapsed);
+        let description = if self.failures.is_empty() {
+            counts
+        } else {
+            let max = MAX_DISCORD_TEXT.saturating_sub(counts.len());
+            let items = self.failure_items(max, |failure| {
+                let title = markdown_escape(&failure.title);
+                match &failure.link {
+                    Some(link) => format!("- [{title}](<{link}>)"),
+                    None => format!("- {title}"),
+                }
+            });
+            format!("{counts}\n\n{}", items.join("\n"))
+        };
+        let color = if self.passed() {
+            DISCORD_PASSED
+        } else {
+            DISCORD_FAILED
+        };
+
+        let mut embed = json!({ "title": headline, "description": description, "color": color });
+        if let Some(url) = &self.run_url
+            && let Some(fields) = embed.as_object_mut()
+        {
+            fields.insert("url".to_owned(), json!(url));
+        }
+
+        json!({
+            "username": "cifmt",
+            "embeds": [embed],
+            // The titles of failures are not meant to mention anyone.
+            "allowed_mentions": { "parse": [] },
+        })
+    }
+
+    /// Post the summary of the run to each webhook.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool which was run.
+    /// * `elapsed` - How long the run took.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request to a webhook fails.
+    #[inline]
+    pub fn notify(&self, tool: &str, elapsed: Duration) -> Result<(), Error> {
+        let agent = http::agent();
+        for webhook in &self.webhooks {
+            agent
+                .post(webhook.url())
+                .send_json(self.payload(webhook, tool, elapsed))?;
+        }
+        Ok(())
+    }
+}
+
+/// Escape the characters with a meaning in Slack's `mrkdwn`.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Escape the characters with a meaning in the Markdown of Teams and
+/// Discord.
+fn markdown_escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|character| {
+            let escape = matches!(character, '\\' | '*' | '_' | '~' | '`' | '[' | ']');
+            escape.then_some('\\').into_iter().chain([character])
+        })
+        .collect()
+}
+
+/// Read a required environment variable.
+fn required(key: &'static str) -> Result<String, Error> {
+    env::var(key).map_err(|_err| Error::MissingEnv(key))
+}
+
+/// Truncate text to at most `max` characters.
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", text.get(..end).unwrap_or_default()),
+        None => text.to_owned(),
+    }
+}
+
+/// Errors relating to notifications.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A required environment variable is not set.
+    #[error("The {0} environment variable is not set")]
+    MissingEnv(&'static str),
+    /// The request to the webhook failed.
+    #[error("Request to the webhook failed: {0}")]
+    Request(#[from] ureq::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Notifier, Webhook, markdown_escape, truncate};
+    use crate::tool::{AnyTool as _, CargoCheck, CargoLibtest};
+
+    fn slack() -> Webhook {
+        Webhook::Slack {
+            url: "https://hooks.slack.com/services/T0/B0/secret".to_owned(),
+        }
+    }
+
+    fn notifier() -> Notifier {
+        Notifier::new(slack())
+            .with_webhook(Webhook::Discord {
+                url: "https://discord.com/api/webhooks/0/secret".to_owned(),
+            })
+            .with_run(
+                Some("test".to_owned()),
+                Some("https://github.com/octo/repo/actions/runs/1".to_owned()),
+            )
+            .with_source_url(Some("https://github.com/octo/repo/blob/abc".to_owned()))
+    }
+
+    #[test]
+    fn debug_redacts_url() {
+        let debug = format!("{:?}", notifier());
+        assert!(!debug.contains("secret"), "{debug}");
+    }
+
+    #[test]
+    fn escapes_markdown() {
+        assert_eq!(markdown_escape("a_b [c] *d*"), r"a\_b \[c\] \*d\*");
+    }
+
+    #[test]
+    fn truncates() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("aéb", 2), "aé…");
+    }
+
+    #[test]
+    fn failed_tests() {
+        let output = concat!(
+            r#"{"type":"test","event":"ok","name":"tests::a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::<b>","stdout":"boom"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::c"}"#,
+            "\n",
+        );
+        let mut notifier = notifier().with_max_failures(1);
+        for message in CargoLibtest::default().parse_dyn(output.as_bytes()) {
+            notifier.record(message.as_ref());
+        }
+        assert!(!notifier.passed());
+        let payload = notifier.payload(&slack(), "cargo-libtest", Duration::from_millis(1500));
+        insta::assert_snapshot!(serde_json::to_string_pretty(&payload).expect("valid JSON"), @r#"
+        {
+          "blocks": [
+            {
+              "text": {
+                "text": ":x: *cargo-libtest* failed in `test` (<https://github.com/octo/repo/actions/runs/1|log>)",
+                "type": "mrkdwn"
+              },
+              "type": "section"
+            },
+            {
+              "elements": [
+                {
+                  "text": "0 error(s), 0 warning(s), 2 of 3 test(s) failed in 1.5s",
+                  "type": "mrkdwn"
+                }
+              ],
+              "type": "context"
+            },
+            {
+              "text": {
+                "text": "• <https://github.com/octo/repo/actions/runs/1|Test Failed: tests::&lt;b&gt;>\n…and 1 more",
+                "type": "mrkdwn"
+              },
+              "type": "section"
+            }
+          ],
+          "text": "cargo-libtest failed: 0 error(s), 0 warning(s), 2 of 3 test(s) failed in 1.5s"
+        }
+        "#);
+    }
+
+    #[test]
+    fn error_links_to_source() {
+        let output = concat!(
+            r#"{"reason":"compiler-message","package_id":"p 0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/w/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"rendered":null,"$message_type":"diagnostic","children":[],"level":"error","message":"cannot find value `y` in this scope","spans":[{"byte_end":1,"byte_start":0,"column_end":6,"column_start":5,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":3,"line_start":3,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"E0425","explanation":null}}}"#,
+            "\n",
+        );
+        let mut notifier = notifier();
+        for message in CargoCheck::default().parse_dyn(output.as_bytes()) {
+            notifier.record(message.as_ref());
+        }
+        let [failure] = notifier.failures() else {
+            panic!("expected one failure: {:?}", notifier.failures());
+        };
+        assert_eq!(
+            failure.link.as_deref(),
+            Some("https://github.com/octo/repo/blob/abc/src/lib.rs#L3")
+        );
+    }
+
+    #[test]
+    fn passed() {
+        let notifier = notifier().with_run(None, None);
+        assert!(notifier.passed());
+        let payload = notifier.payload(&slack(), "cargo-check", Duration::ZERO);
+        insta::assert_snapshot!(serde_json::to_string(&payload).expect("valid JSON"), @r#"{"blocks":[{"text":{"text":":white_check_mark: *cargo-check* passed","type":"mrkdwn"},"type":"section"},{"elements":[{"text":"0 error(s), 0 warning(s) in 0.0s","type":"mrkdwn"}],"type":"context"}],"text":"cargo-check passed: 0 error(s), 0 warning(s) in 0.0s"}"#);
+    }
+
+    /// A notifier which recorded a failed test.
+    fn failed() -> Notifier {
+        let output = concat!(
+            r#"{"type":"test","event":"ok","name":"tests::a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::b_c","stdout":"boom"}"#,
+            "\n",
+        );
+        let mut notifier = notifier();
+        for message in CargoLibtest::default().parse_dyn(output.as_bytes()) {
+            notifier.record(message.as_ref());
+        }
+        notifier
+    }
+
+    #[test]
+    fn teams() {
+        let webhook = Webhook::Teams {
+            url: "https://example.webhook.office.com/secret".to_owned(),
+        };
+        let payload = failed().payload(&webhook, "cargo-libtest", Duration::from_secs(2));
+        insta::assert_snapshot!(serde_json::to_string_pretty(&payload).expect("valid JSON"), @r#"
+        {
+          "attachments": [
+            {
+              "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "actions": [
+                  {
+                    "title": "View log",
+                    "type": "Action.OpenUrl",
+                    "url": "https://github.com/octo/repo/actions/runs/1"
+                  }
+                ],
+                "body": [
+                  {
+                    "color": "Attention",
+                    "size": "Medium",
+                    "text": "cargo-libtest failed in test",
+                    "type": "TextBlock",
+                    "weight": "Bolder",
+                    "wrap": true
+                  },
+                  {
+                    "isSubtle": true,
+                    "spacing": "None",
+                    "text": "0 error(s), 0 warning(s), 1 of 2 test(s) failed in 2.0s",
+                    "type": "TextBlock",
+                    "wrap": true
+                  },
+                  {
+                    "text": "- [Test Failed: tests::b\\_c](https://github.com/octo/repo/actions/runs/1)",
+                    "type": "TextBlock",
+                    "wrap": true
+                  }
+                ],
+                "type": "AdaptiveCard",
+                "version": "1.4"
+              },
+              "contentType": "application/vnd.microsoft.card.adaptive"
+            }
+          ],
+          "type": "message"
+        }
+        "#);
+    }
+
+    #[test]
+    fn discord() {
+        let webhook = Webhook::Discord {
+            url: "https://discord.com/api/webhooks/0/secret".to_owned(),
+        };
+        let payload = failed().payload(&webhook, "cargo-libtest", Duration::from_secs(2));
+        insta::assert_snapshot!(serde_json::to_string_pretty(&payload).expect("valid JSON"), @r#"
+        {
+          "allowed_mentions": {
+            "parse": []
+          },
+          "embeds": [
+            {
+              "color": 13574702,
+              "description": "0 error(s), 0 warning(s), 1 of 2 test(s) failed in 2.0s\n\n- [Test Failed: tests::b\\_c](<https://github.com/octo/repo/actions/runs/1>)",
+              "title": "cargo-libtest failed in test",
+              "url": "https://github.com/octo/repo/actions/runs/1"
+            }
+          ],
+          "username": "cifmt"
+        }
+        "#);
+    }
+}
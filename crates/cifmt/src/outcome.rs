@@ -0,0 +1,100 @@
+//! The outcome of a build.
+//!
+//! Tools which build a project report whether the build succeeded once it
+//! finishes (e.g., cargo's `build-finished` message, see
+//! [`Classify::build_result`]). Rather than being written where it is
+//! reported, the result is summarized at the end of the output by a
+//! [`BuildSummary`], along with the number of errors and warnings reported
+//! during the build.
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// The final summary of a build, written at the end of the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BuildSummary {
+    /// Whether the build succeeded.
+    pub success: bool,
+    /// Number of errors reported during the build.
+    pub errors: usize,
+    /// Number of warnings reported during the build.
+    pub warnings: usize,
+}
+
+impl BuildSummary {
+    /// Summarize the outcome of a build.
+    ///
+    /// # Arguments
+    ///
+    /// * `success` - Whether the build succeeded.
+    /// * `errors` - Number of errors reported during the build.
+    /// * `warnings` - Number of warnings reported during the build.
+    #[must_use]
+    #[inline]
+    pub fn new(success: bool, errors: usize, warnings: usize) -> Self {
+        Self {
+            success,
+            errors,
+            warnings,
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for BuildSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let (title, status) = if self.success {
+            (Title::BuildComplete, "Build finished successfully")
+        } else {
+            (Title::BuildFailed, "Build failed")
+        };
+        P::annotate(
+            self.severity(),
+            format!(
+                "{status} with {} error(s) and {} warning(s)",
+                self.errors, self.warnings
+            ),
+            None,
+            Some(title.text()),
+        )
+    }
+}
+
+impl Classify for BuildSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        if self.success {
+            Severity::Notice
+        } else {
+            Severity::Error
+        }
+    }
+}
+
+impl Timed for BuildSummary {}
+
+impl Captured for BuildSummary {}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildSummary;
+    use crate::ci::{AzureDevOps, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[test]
+    fn format() {
+        let success = BuildSummary::new(true, 0, 2);
+        insta::assert_snapshot!(<BuildSummary as CiMessage<Plain>>::format(&success), @"notice:  Build Complete: Build finished successfully with 0 error(s) and 2 warning(s)");
+        insta::assert_snapshot!(<BuildSummary as CiMessage<AzureDevOps>>::format(&success), @"notice:  Build Complete: Build finished successfully with 0 error(s) and 2 warning(s)");
+
+        let failure = BuildSummary::new(false, 3, 1);
+        insta::assert_snapshot!(<BuildSummary as CiMessage<GitHub>>::format(&failure), @"::error title=Build Failed::Build failed with 3 error(s) and 1 warning(s)");
+        insta::assert_snapshot!(<BuildSummary as CiMessage<AzureDevOps>>::format(&failure), @"##vso[task.logissue type=error]Build Failed: Build failed with 3 error(s) and 1 warning(s)");
+    }
+}
@@ -0,0 +1,419 @@
+//! Owners of the files of a repository.
+//!
+//! A [`CODEOWNERS`](https://docs.github.com/en/repositories/managing-your-repositorys-settings-and-features/customizing-your-repository/about-code-owners)
+//! file assigns the files of a repository to their owners (e.g., teams such
+//! as `@org/backend`). Given to a pipeline (see
+//! [`Pipeline::with_code_owners`](crate::pipeline::Pipeline::with_code_owners)),
+//! the [`CodeOwners`] attribute the issues reported in each file (see
+//! [`Classify::file`]) to its owners. The errors and warnings of each owner
+//! are collected into [`OwnerStats`], and reported at the end of the output by
+//! an [`OwnerSummary`]. Review comments can also mention the owners of the
+//! file they are posted on (see `PullRequest::with_code_owners` of the
+//! `review` module).
+//!
+//! ```
+//! use cifmt::owners::CodeOwners;
+//!
+//! let owners = CodeOwners::parse("* @org/core\n/docs/ @org/docs\n*.py @org/python @alice\n");
+//! assert_eq!(owners.owners("src/lib.rs"), ["@org/core"]);
+//! assert_eq!(owners.owners("docs/guide/intro.md"), ["@org/docs"]);
+//! assert_eq!(owners.owners("docs/conf.py"), ["@org/python", "@alice"]);
+//! ```
+
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::{self, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    packages::{PackageCounts, PackageStats},
+    timeline::Timed,
+};
+
+/// A rule of a `CODEOWNERS` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    /// The segments of the pattern, `**` matching any number of segments.
+    segments: Vec<String>,
+    /// Whether the pattern only matches directories (i.e., it ends with a
+    /// slash).
+    directory: bool,
+    /// The owners of the files matched.
+    owners: Vec<String>,
+}
+
+impl Rule {
+    /// Parse a line of a `CODEOWNERS` file.
+    ///
+    /// # Returns
+    ///
+    /// The rule, or `None` if the line is blank or a comment.
+    fn parse(line: &str) -> Option<Self> {
+        let content = line.split_once(" #").map_or(line, |(before, _)| before);
+        let mut words = content.split_whitespace();
+        let pattern = words.next().filter(|word| !word.starts_with('#'))?;
+        let owners = words.map(str::to_owned).collect();
+
+        let directory = pattern.ends_with('/');
+        let trimmed = pattern.trim_end_matches('/');
+        // Patterns with a slash other than at their end are relative to the
+        // root, and others match at any depth.
+        let anchored = trimmed.contains('/');
+        let mut segments: Vec<_> = trimmed
+            .trim_start_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_owned)
+            .collect();
+        if !anchored {
+            segments.insert(0, "**".to_owned());
+        }
+        Some(Self {
+            segments,
+            directory,
+            owners,
+        })
+    }
+
+    /// Whether the rule matches a file, given the segments of its path.
+    ///
+    /// Patterns match the file itself or any directory containing it, except
+    /// for directory patterns, which only match directories, and patterns
+    /// ending with a wildcard (e.g., `docs/*`), which only match the files
+    /// directly within a directory.
+    fn matches(&self, path: &[&str]) -> bool {
+        let wildcard = self
+            .segments
+            .last()
+            .is_some_and(|last| last != "**" && last.contains('*'));
+        let longest = if self.directory {
+            path.len().saturating_sub(1)
+        } else {
+            path.len()
+        };
+        let shortest = if wildcard && !self.directory {
+            path.len()
+        } else {
+            1
+        };
+        (shortest..=longest).any(|length| {
+            path.get(..length)
+                .is_some_and(|prefix| matches_segments(&self.segments, prefix))
+        })
+    }
+}
+
+/// Whether the segments of a pattern match the segments of a path.
+fn matches_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len())
+            .any(|skipped| matches_segments(rest, path.get(skipped..).unwrap_or(&[]))),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, remaining)| {
+            matches_glob(first.as_bytes(), segment.as_bytes()) && matches_segments(rest, remaining)
+        }),
+    }
+}
+
+/// Whether a glob matches a segment of a path, `*` matching any sequence of
+/// characters and `?` any single character.
+pub(crate) fn matches_glob(glob: &[u8], text: &[u8]) -> bool {
+    match glob.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => {
+            (0..=text.len()).any(|skipped| matches_glob(rest, text.get(skipped..).unwrap_or(&[])))
+        }
+        Some((b'?', rest)) => text
+            .split_first()
+            .is_some_and(|(_, remaining)| matches_glob(rest, remaining)),
+        Some((byte, rest)) => text
+            .split_first()
+            .is_some_and(|(first, remaining)| first == byte && matches_glob(rest, remaining)),
+    }
+}
+
+/// The owners of the files of a repository, as assigned by a `CODEOWNERS`
+/// file.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "named after the CODEOWNERS file it is read from"
+)]
+pub struct CodeOwners {
+    /// The rules, in the order of the file.
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// The locations at which GitHub looks for the `CODEOWNERS` file,
+    /// relative to the root of the repository, in order of preference.
+    pub const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+    /// Parse the contents of a `CODEOWNERS` file.
+    ///
+    /// Each line assigns the files matched by a pattern to the owners which
+    /// follow it, and comments start with `#`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The contents of the file.
+    #[must_use]
+    #[inline]
+    pub fn parse(text: &str) -> Self {
+        Self {
+            rules: text.lines().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// Read a `CODEOWNERS` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read, or is not valid UTF-8.
+    #[inline]
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Ok(Self::parse(&text))
+    }
+
+    /// Find the `CODEOWNERS` file of a repository, at the first of the
+    /// [`CodeOwners::LOCATIONS`] which exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root of the repository.
+    #[must_use]
+    #[inline]
+    pub fn find(root: &Path) -> Option<PathBuf> {
+        Self::LOCATIONS
+            .iter()
+            .map(|location| root.join(location))
+            .find(|path| path.is_file())
+    }
+
+    /// The owners of a file, as assigned by the last rule matching it.
+    ///
+    /// The path is normalized as in annotations (see
+    /// [`set_checkout_root`](crate::ci::set_checkout_root)), and files
+    /// outside the checkout have no owners.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file.
+    #[must_use]
+    #[inline]
+    pub fn owners(&self, path: &str) -> &[String] {
+        let Some(relative) = ci::relative_to_checkout(path) else {
+            return &[];
+        };
+        let segments: Vec<_> = relative
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&segments))
+            .map_or(&[], |rule| &rule.owners)
+    }
+}
+
+/// The number of errors and warnings reported in the files of each owner.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OwnerStats {
+    /// The number of issues, keyed by owner.
+    counts: PackageStats,
+}
+
+impl OwnerStats {
+    /// Record an issue reported in a file, for each of its owners.
+    ///
+    /// Issues other than errors and warnings are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `owners` - The owners of the file.
+    /// * `severity` - The severity of the issue.
+    #[inline]
+    pub fn record(&mut self, owners: &[String], severity: Severity) {
+        for owner in owners {
+            self.counts.record(owner, severity);
+        }
+    }
+
+    /// Whether no issues have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The number of issues recorded for the given owner.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, owner: &str) -> PackageCounts {
+        self.counts.get(owner)
+    }
+
+    /// The owners with issues, along with their number of issues.
+    ///
+    /// Owners are ordered by decreasing number of errors, then by decreasing
+    /// number of warnings, and then by name.
+    #[must_use]
+    #[inline]
+    pub fn sorted(&self) -> Vec<(&str, PackageCounts)> {
+        self.counts.sorted()
+    }
+}
+
+/// A table of the errors and warnings of each owner, written at the end of
+/// the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OwnerSummary {
+    /// The owners with issues, along with their number of issues.
+    pub owners: Vec<(String, PackageCounts)>,
+}
+
+impl OwnerSummary {
+    /// Summarize the issues of each owner.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The number of issues reported for each owner.
+    #[must_use]
+    #[inline]
+    pub fn new(stats: &OwnerStats) -> Self {
+        Self {
+            owners: stats
+                .sorted()
+                .into_iter()
+                .map(|(owner, counts)| (owner.to_owned(), counts))
+                .collect(),
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for OwnerSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let errors = self
+            .owners
+            .iter()
+            .map(|(_, counts)| counts.errors.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Errors".len());
+        let warnings = self
+            .owners
+            .iter()
+            .map(|(_, counts)| counts.warnings.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Warnings".len());
+        let mut lines = vec![P::group(Title::IssuesByOwner.text())];
+        lines.push(format!(
+            "{:>errors$}  {:>warnings$}  Owner",
+            "Errors", "Warnings"
+        ));
+        for (owner, counts) in &self.owners {
+            lines.push(format!(
+                "{:>errors$}  {:>warnings$}  {owner}",
+                counts.errors, counts.warnings
+            ));
+        }
+        lines.push(P::endgroup());
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for OwnerSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl Timed for OwnerSummary {}
+
+impl Captured for OwnerSummary {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{CodeOwners, OwnerStats, OwnerSummary};
+    use crate::ci::GitHub;
+    use crate::ci_message::{CiMessage, Severity};
+
+    /// A `CODEOWNERS` file exercising each kind of pattern.
+    const CODEOWNERS: &str = "
+# Default owners
+*       @org/core
+
+*.js    @org/web # Trailing comment
+/build/logs/ @org/ops
+docs/*  docs@example.com
+apps/   @octocat
+/scripts/ @org/scripts
+**/logs @org/logs
+/vendor/
+";
+
+    #[rstest]
+    #[case::default("src/lib.rs", &["@org/core"])]
+    #[case::extension("web/src/app.js", &["@org/web"])]
+    #[case::anchored_directory("build/logs/out.txt", &["@org/logs"])]
+    #[case::nested_anchored("build/logs/deep/out.txt", &["@org/logs"])]
+    #[case::direct_child("docs/intro.md", &["docs@example.com"])]
+    #[case::nested_child("docs/guide/intro.md", &["@org/core"])]
+    #[case::directory_anywhere("src/apps/main.rs", &["@octocat"])]
+    #[case::not_a_directory("src/apps", &["@org/core"])]
+    #[case::anchored_elsewhere("src/scripts/run.sh", &["@org/core"])]
+    #[case::anchored_root("scripts/run.sh", &["@org/scripts"])]
+    #[case::double_star("var/app/logs/today.log", &["@org/logs"])]
+    #[case::unowned("vendor/lib.rs", &[])]
+    #[case::outside_checkout("../other/src/lib.rs", &[])]
+    fn owners(#[case] path: &str, #[case] expected: &[&str]) {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        assert_eq!(owners.owners(path), expected);
+    }
+
+    #[test]
+    fn summary() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        let mut stats = OwnerStats::default();
+        for (path, severity) in [
+            ("src/lib.rs", Severity::Warning),
+            ("web/app.js", Severity::Error),
+            ("src/main.rs", Severity::Warning),
+            ("web/app.js", Severity::Notice),
+            ("vendor/lib.rs", Severity::Error),
+        ] {
+            stats.record(owners.owners(path), severity);
+        }
+        assert_eq!(stats.get("@org/core").warnings, 2);
+
+        let summary = OwnerSummary::new(&stats);
+        insta::assert_snapshot!(<OwnerSummary as CiMessage<GitHub>>::format(&summary), @"
+        ::group::Issues by owner
+        Errors  Warnings  Owner
+             1         0  @org/web
+             0         2  @org/core
+        ::endgroup::
+        ");
+    }
+}
@@ -0,0 +1,244 @@
+//! Statistics of the issues reported for each package.
+//!
+//! Tools building a workspace report issues for several packages (e.g., the
+//! crates of a Cargo workspace). Counting the errors and warnings of each
+//! package shows where the issues lie. The counts are collected into
+//! [`PackageStats`], and reported at the end of the output by a
+//! [`PackageSummary`] when issues were reported for more than one package.
+
+use std::collections::HashMap;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// The number of errors and warnings reported for a package.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PackageCounts {
+    /// Number of issues with [`Severity::Error`].
+    pub errors: usize,
+    /// Number of issues with [`Severity::Warning`].
+    pub warnings: usize,
+}
+
+/// The number of errors and warnings reported for each package.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PackageStats {
+    /// The number of issues, keyed by the name of their package.
+    counts: HashMap<String, PackageCounts>,
+}
+
+impl PackageStats {
+    /// Record an issue reported for the given package.
+    ///
+    /// Issues other than errors and warnings are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `package` - The name of the package.
+    /// * `severity` - The severity of the issue.
+    #[inline]
+    pub fn record(&mut self, package: &str, severity: Severity) {
+        if !matches!(severity, Severity::Error | Severity::Warning) {
+            return;
+        }
+        let counts = self.counts.entry(package.to_owned()).or_default();
+        if severity == Severity::Error {
+            counts.errors = counts.errors.saturating_add(1);
+        } else {
+            counts.warnings = counts.warnings.saturating_add(1);
+        }
+    }
+
+    /// The number of packages for which issues have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Whether no issues have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The number of issues recorded for the given package.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, package: &str) -> PackageCounts {
+        self.counts.get(package).copied().unwrap_or_default()
+    }
+
+    /// The packages with issues, along with their number of issues.
+    ///
+    /// Packages are ordered by decreasing number of errors, then by
+    /// decreasing number of warnings, and then by name.
+    #[must_use]
+    #[inline]
+    pub fn sorted(&self) -> Vec<(&str, PackageCounts)> {
+        let mut sorted: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(package, counts)| (package.as_str(), *counts))
+            .collect();
+        sorted.sort_by(|lhs, rhs| {
+            rhs.1
+                .errors
+                .cmp(&lhs.1.errors)
+                .then_with(|| rhs.1.warnings.cmp(&lhs.1.warnings))
+                .then_with(|| lhs.0.cmp(rhs.0))
+        });
+        sorted
+    }
+}
+
+/// A table of the errors and warnings of each package, written at the end of
+/// the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PackageSummary {
+    /// The packages with issues, along with their number of issues.
+    pub packages: Vec<(String, PackageCounts)>,
+}
+
+impl PackageSummary {
+    /// Summarize the issues of each package.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The number of issues reported for each package.
+    #[must_use]
+    #[inline]
+    pub fn new(stats: &PackageStats) -> Self {
+        Self {
+            packages: stats
+                .sorted()
+                .into_iter()
+                .map(|(package, counts)| (package.to_owned(), counts))
+                .collect(),
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for PackageSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let errors = self
+            .packages
+            .iter()
+            .map(|(_, counts)| counts.errors.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Errors".len());
+        let warnings = self
+            .packages
+            .iter()
+            .map(|(_, counts)| counts.warnings.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Warnings".len());
+        let mut lines = vec![P::group(Title::IssuesByCrate.text())];
+        lines.push(format!(
+            "{:>errors$}  {:>warnings$}  Crate",
+            "Errors", "Warnings"
+        ));
+        for (package, counts) in &self.packages {
+            lines.push(format!(
+                "{:>errors$}  {:>warnings$}  {package}",
+                counts.errors, counts.warnings
+            ));
+        }
+        lines.push(P::endgroup());
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for PackageSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl Timed for PackageSummary {}
+
+impl Captured for PackageSummary {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{PackageCounts, PackageStats, PackageSummary};
+    use crate::ci::{AzureDevOps, Plain};
+    use crate::ci_message::{CiMessage, Severity};
+
+    fn stats() -> PackageStats {
+        let mut stats = PackageStats::default();
+        for (package, severity) in [
+            ("core", Severity::Warning),
+            ("cli", Severity::Error),
+            ("core", Severity::Warning),
+            ("cli", Severity::Notice),
+            ("macros", Severity::Warning),
+            ("macros", Severity::Debug),
+            ("core", Severity::Warning),
+        ] {
+            stats.record(package, severity);
+        }
+        stats
+    }
+
+    #[test]
+    fn sorted() {
+        let stats = stats();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(
+            stats.get("core"),
+            PackageCounts {
+                errors: 0,
+                warnings: 3
+            }
+        );
+        assert_eq!(stats.get("unknown"), PackageCounts::default());
+        assert_eq!(
+            stats
+                .sorted()
+                .into_iter()
+                .map(|(package, _)| package)
+                .collect::<Vec<_>>(),
+            ["cli", "core", "macros"]
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let summary = PackageSummary::new(&stats());
+        insta::assert_snapshot!(<PackageSummary as CiMessage<Plain>>::format(&summary), @"
+        Issues by crate
+        Errors  Warnings  Crate
+             1         0  cli
+             0         3  core
+             0         1  macros
+        ");
+        insta::assert_snapshot!(<PackageSummary as CiMessage<AzureDevOps>>::format(&summary), @"
+        ##[group]Issues by crate
+        Errors  Warnings  Crate
+             1         0  cli
+             0         3  core
+             0         1  macros
+        ##[endgroup]
+        ");
+    }
+}
@@ -0,0 +1,3774 @@
+//! Multi-target formatting pipeline.
+//!
+//! A [`Pipeline`] parses the output of a tool once, and writes the formatted
+//! messages to any number of [`Target`]s. Each target is associated with its
+//! own platform, which allows (for example) GitHub Actions annotations to be
+//! written to stdout while a plain text log is written to a file.
+//!
+//! Output to each target is buffered, and flushed at well-defined points: at
+//! the end of each chunk of input, whenever a group is closed, and
+//! periodically (see [`Pipeline::with_flush_interval`]) so that the output of
+//! long-running tools continues to stream.
+//!
+//! Output captured by the tool (e.g., the stdout of a failed test) is truncated
+//! to each target's output limit, which defaults to a limit suited to the
+//! target's platform (see [`PlatformKind::default_output_limit`]). The full
+//! output can be written to an artifact file instead (see
+//! [`Pipeline::with_artifact_dir`]). Similarly, annotations for GitHub Actions
+//! beyond the number the platform displays are written to the log as plain
+//! text (see [`GitHub::MAX_ANNOTATIONS_PER_STEP`](crate::ci::GitHub::MAX_ANNOTATIONS_PER_STEP)).
+//!
+//! A target can also explain its output instead of writing it (see
+//! [`Target::with_explanations`]): each message is described along with the
+//! output it would produce, and the filters applied to it, which helps to
+//! debug the configuration of the pipeline. The raw record of each parsed
+//! message can also be written as JSON alongside the output (see
+//! [`Pipeline::with_raw_messages`]), which helps to debug a parser, as can
+//! a normalized record in a schema which is the same for all tools (see
+//! [`Pipeline::with_normalized_messages`]), for scripts which consume it.
+//!
+//! Input can either be provided chunk by chunk through [`Pipeline::process`],
+//! or read to completion through [`Pipeline::run`], which reads, parses and
+//! writes on separate threads. Several files of output (e.g., one for each
+//! shard of a matrix build) can also be parsed in parallel through
+//! [`Pipeline::run_files`]. ANSI escape sequences can be removed from the
+//! input before it is parsed (see [`Pipeline::with_ansi_stripping`]), and
+//! invalid UTF-8 can be replaced (see [`Pipeline::with_lossy_utf8`]). The
+//! messages of concurrent producers can be ordered deterministically (see
+//! [`Pipeline::with_stable_ordering`]), and the results of tests collected
+//! into a JUnit report (see [`Pipeline::with_junit_report`]), and the
+//! annotations into diagnostics for reviewdog (see
+//! [`Pipeline::with_rdjson_diagnostics`]), issues for Code Climate (see
+//! [`Pipeline::with_codeclimate_issues`]), a Checkstyle report (see
+//! [`Pipeline::with_checkstyle_report`]), a SARIF report with the fixes
+//! which can be applied automatically (see [`Pipeline::with_sarif_report`])
+//! or annotations for the toolkit of GitHub Actions (see
+//! [`Pipeline::with_github_annotations`]), and both into an HTML report (see [`Pipeline::with_html_report`]). The messages of each
+//! crate of a workspace can also be grouped (see
+//! [`Pipeline::with_crate_groups`]), and the sizes of the binaries built
+//! tracked (see [`Pipeline::with_binary_sizes`]). Each target can start with
+//! a header describing the run (see [`Pipeline::with_metadata`]). Once all
+//! input has been processed, [`Pipeline::finish`] summarizes the issues of
+//! each crate, the reasons tests were skipped, the sizes of the binaries and
+//! the outcome of the build, writes the final status for platforms which
+//! report one, and creates a Buildkite annotation
+//! summarizing the errors if enabled (see
+//! [`Pipeline::with_buildkite_annotations`]), and writes a GitHub Actions job
+//! summary if enabled (see [`Pipeline::with_step_summary`]), comparing the
+//! run against the previous ones recorded in a history if kept (see
+//! [`Pipeline::with_history`]). The full report of the run can also be
+//! written as an artifact of the job, for when the job summary or the
+//! annotations exceed the limits of the platform (see
+//! [`Pipeline::with_report_artifact`]).
+
+use core::{fmt, mem, time::Duration};
+use std::{
+    fs,
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvError, SyncSender, TryRecvError},
+    thread,
+    time::Instant,
+};
+
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+
+#[cfg(feature = "checks")]
+use crate::checks::{CheckAnnotation, CheckRun};
+#[cfg(feature = "issues")]
+use crate::issues::{Failure, IssueFiler};
+#[cfg(feature = "notify")]
+use crate::notify::Notifier;
+use crate::{
+    ansi::AnsiStripper,
+    capture::{self, TestGrouping},
+    catalog::Title,
+    checkstyle,
+    ci::{
+        AnnotationLimiter, AnnotationStyle, AzureDevOps, Buildkite, CodeBlock, CodeBuild, GitHub,
+        Phase, PlatformKind, TaskResult, WorkflowAnnotation, plain_debug,
+    },
+    ci_message::{DynCiMessage, Severity, insert_snippet},
+    codeclimate::Issues,
+    fingerprint::Fingerprint,
+    github_annotations,
+    history::{History, RunSummary},
+    html,
+    junit::{Report, TestOutcome},
+    lints::LintStats,
+    markdown::JobSummary,
+    metadata::RunMetadata,
+    normalized::NormalizedMessage,
+    outcome::BuildSummary,
+    owners::{CodeOwners, OwnerStats, OwnerSummary},
+    packages::{PackageStats, PackageSummary},
+    raw::RawMessage,
+    rdjson::Diagnostics,
+    report::{FullReport, ReportArtifact, file_size},
+    resilience, sarif,
+    sizes::{SizeStats, SizeSummary},
+    skips::{SkipStats, SkipSummary},
+    suites::SuiteStats,
+    timeline::Timeline,
+    timeout::IdleTimeout,
+    tool::AnyTool,
+    utf8::{InvalidUtf8, Utf8Replacer},
+};
+
+/// How debug messages are written to a target, for platforms which hide them
+/// unless debug logging is enabled (i.e., GitHub Actions, see
+/// [`GitHub::debug_enabled`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DebugMessages {
+    /// Write debug messages as they are.
+    #[default]
+    Write,
+    /// Omit debug messages, and count them in a single line at the end of the
+    /// output, which saves writing thousands of lines nobody sees (e.g., the
+    /// artifacts built).
+    Omit,
+    /// Write debug messages as plain lines, which are shown whether or not
+    /// debug logging is enabled.
+    Downgrade,
+}
+
+/// An output target for a [`Pipeline`].
+///
+/// A target combines a platform, which determines how messages are
+/// formatted, with a writer, which determines where the formatted messages
+/// are written.
+pub struct Target<'a> {
+    /// The platform used to format messages for this target.
+    platform: PlatformKind,
+    /// The destination of the formatted messages.
+    writer: BufWriter<Box<dyn Write + 'a>>,
+    /// The maximum size of captured output, in bytes.
+    output_limit: Option<usize>,
+    /// Limiter for the number of annotations, for platforms which limit it.
+    limiter: Option<AnnotationLimiter>,
+    /// How the output of each test is grouped.
+    test_grouping: TestGrouping,
+    /// How debug messages are written.
+    debug_messages: DebugMessages,
+    /// Number of debug messages omitted.
+    debug_omitted: usize,
+    /// Whether each message is explained instead of written.
+    explain: bool,
+    /// The phase of the tool's run with which each line is prefixed, for
+    /// platforms whose logs are filtered by phase (i.e., AWS CodeBuild).
+    phase: Option<Phase>,
+}
+
+impl<'a> Target<'a> {
+    /// Create a new output target.
+    ///
+    /// Writes to the target are buffered. Any buffered output is flushed when
+    /// the target is dropped, though errors are only reported by an explicit
+    /// [`Pipeline::flush`].
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform used to format messages.
+    /// * `writer` - The destination of the formatted messages.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cifmt::{ci::PlatformKind, pipeline::Target};
+    ///
+    /// let target = Target::new(PlatformKind::GitHub, std::io::stdout());
+    /// ```
+    #[inline]
+    pub fn new(platform: PlatformKind, writer: impl Write + 'a) -> Self {
+        Self {
+            platform,
+            writer: BufWriter::new(Box::new(writer)),
+            output_limit: platform.default_output_limit(),
+            limiter: (platform == PlatformKind::GitHub).then(AnnotationLimiter::default),
+            test_grouping: TestGrouping::default(),
+            debug_messages: DebugMessages::default(),
+            debug_omitted: 0,
+            explain: false,
+            phase: None,
+        }
+    }
+
+    /// Set the maximum size of captured output written to this target.
+    ///
+    /// Captured output (e.g., the stdout of a failed test) exceeding this
+    /// limit is truncated, keeping its head and tail. Defaults to the
+    /// platform's [`PlatformKind::default_output_limit`].
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum size in bytes, or `None` for no limit.
+    #[must_use]
+    #[inline]
+    pub fn with_output_limit(mut self, limit: Option<usize>) -> Self {
+        self.output_limit = limit;
+        self
+    }
+
+    /// Set the maximum number of annotations written to this target, for
+    /// platforms which limit the number of annotations (i.e., GitHub Actions).
+    ///
+    /// The annotations beyond the limit are written to the log as plain lines,
+    /// and counted in a single warning at the end of the output. Errors take
+    /// priority over warnings and notices, which only use the annotations not
+    /// needed for errors. The platform's own limit on the number of
+    /// annotations of each type always applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of annotations, or `None` to only apply
+    ///   the platform's limit.
+    #[must_use]
+    #[inline]
+    pub fn with_annotation_limit(mut self, limit: Option<usize>) -> Self {
+        self.limiter = self.limiter.map(|limiter| limiter.with_limit(limit));
+        self
+    }
+
+    /// Set how the output of each test is grouped on this target.
+    ///
+    /// Defaults to [`TestGrouping::All`]. With [`TestGrouping::Passing`],
+    /// the output of failing tests is written outside of any group, so that
+    /// it is visible without expanding a group.
+    ///
+    /// # Arguments
+    ///
+    /// * `grouping` - How the output of each test is grouped.
+    #[must_use]
+    #[inline]
+    pub fn with_test_grouping(mut self, grouping: TestGrouping) -> Self {
+        self.test_grouping = grouping;
+        self
+    }
+
+    /// Set how debug messages are written to this target (see
+    /// [`DebugMessages`]).
+    ///
+    /// Defaults to [`DebugMessages::Write`].
+    ///
+    /// # Arguments
+    ///
+    /// * `handling` - How debug messages are written.
+    #[must_use]
+    #[inline]
+    pub fn with_debug_messages(mut self, handling: DebugMessages) -> Self {
+        self.debug_messages = handling;
+        self
+    }
+
+    /// Set whether this target explains each message instead of writing it.
+    ///
+    /// Defaults to `false`. Otherwise, each message is described in plain
+    /// text: its kind, severity, code, package and file, the filters applied
+    /// to it for this target (e.g., the truncation of captured output, or the
+    /// limit on annotations), and the output it would produce. Nothing else
+    /// is written for the platform (e.g., the groups of each crate, or the
+    /// final status of an Azure Pipelines task).
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether messages are explained.
+    #[must_use]
+    #[inline]
+    pub fn with_explanations(mut self, enabled: bool) -> Self {
+        self.explain = enabled;
+        self
+    }
+
+    /// The maximum size of captured output written to this target.
+    #[must_use]
+    #[inline]
+    pub fn output_limit(&self) -> Option<usize> {
+        self.output_limit
+    }
+
+    /// The platform used to format messages for this target.
+    #[must_use]
+    #[inline]
+    pub fn platform(&self) -> PlatformKind {
+        self.platform
+    }
+
+    /// Write text to this target, prefixing each line with the phase of the
+    /// tool's run if the platform's logs are filtered by phase.
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        match self.phase {
+            Some(phase) => self
+                .writer
+                .write_all(CodeBuild::prefixed(phase, text).as_bytes()),
+            None => self.writer.write_all(text.as_bytes()),
+        }
+    }
+}
+
+impl fmt::Debug for Target<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Target")
+            .field("platform", &self.platform)
+            .field("output_limit", &self.output_limit)
+            .field("test_grouping", &self.test_grouping)
+            .field("debug_messages", &self.debug_messages)
+            .field("explain", &self.explain)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Size of each chunk read by [`Pipeline::run`].
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Number of chunks or message batches buffered between the stages of
+/// [`Pipeline::run`].
+const CHANNEL_CAPACITY: usize = 16;
+
+/// Default interval after which buffered output is flushed.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether a formatted message closes a group.
+///
+/// Groups are flushed as a whole, so that a log viewer does not render a
+/// partially written group.
+fn closes_group(platform: PlatformKind, formatted: &str) -> bool {
+    match platform {
+        PlatformKind::GitHub => formatted.trim_end().ends_with("::endgroup::"),
+        PlatformKind::AzureDevOps => formatted.trim_end().ends_with("##[endgroup]"),
+        // Sections extend until the next section starts.
+        PlatformKind::Buildkite => formatted.starts_with("--- ") || formatted.starts_with("+++ "),
+        PlatformKind::Plain
+        | PlatformKind::CodeBuild
+        | PlatformKind::CloudBuild
+        | PlatformKind::Terminal => false,
+    }
+}
+
+/// A formatted message with its debug lines omitted or downgraded to plain
+/// lines, along with the number of lines affected, if the platform hides debug
+/// messages and the message contains any.
+///
+/// # Arguments
+///
+/// * `platform` - The platform for which the message is formatted.
+/// * `formatted` - The formatted message.
+/// * `handling` - How debug messages are written.
+fn filter_debug(
+    platform: PlatformKind,
+    formatted: &str,
+    handling: DebugMessages,
+) -> Option<(String, usize)> {
+    if handling == DebugMessages::Write {
+        return None;
+    }
+    let prefix = match platform {
+        PlatformKind::GitHub => "::debug::",
+        PlatformKind::Plain
+        | PlatformKind::AzureDevOps
+        | PlatformKind::Buildkite
+        | PlatformKind::CodeBuild
+        | PlatformKind::CloudBuild
+        | PlatformKind::Terminal => return None,
+    };
+    let affected = formatted
+        .split_inclusive('\n')
+        .filter(|line| line.starts_with(prefix))
+        .count();
+    (affected > 0).then(|| {
+        let filtered = formatted
+            .split_inclusive('\n')
+            .filter_map(|line| match line.strip_prefix(prefix) {
+                None => Some(line.to_owned()),
+                Some(message) => {
+                    (handling == DebugMessages::Downgrade).then(|| plain_debug(message))
+                }
+            })
+            .collect();
+        (filtered, affected)
+    })
+}
+
+/// Write a message to a target, or explain it if the target explains its
+/// output (see [`Target::with_explanations`]).
+///
+/// # Arguments
+///
+/// * `target` - The target.
+/// * `message` - The message.
+/// * `buffer` - Buffer into which the message is formatted.
+/// * `truncated` - The full captured output of the message, if it must be
+///   truncated for any target.
+/// * `number` - The number of the message, counting from one.
+/// * `fingerprint` - The fingerprint of the message's diagnostic, if any.
+fn write_message(
+    target: &mut Target<'_>,
+    message: &mut dyn DynCiMessage,
+    buffer: &mut String,
+    truncated: Option<&Truncated>,
+    number: usize,
+    fingerprint: Option<Fingerprint>,
+) -> io::Result<()> {
+    let mut filters = Vec::new();
+    if let Some(Truncated { full, artifact }) = truncated
+        && let Some((_, output)) = message.captured_output_mut()
+    {
+        *output = match target.output_limit {
+            Some(limit) => {
+                filters.push(format!("captured output truncated to {limit} byte(s)"));
+                capture::truncate(full, limit, artifact.as_deref())
+            }
+            None => full.clone(),
+        };
+    }
+
+    message.set_test_grouping(target.test_grouping);
+    buffer.clear();
+    message
+        .write_for(target.platform, buffer)
+        .map_err(io::Error::other)?;
+    buffer.push('\n');
+    if let Some((filtered, affected)) = filter_debug(target.platform, buffer, target.debug_messages)
+    {
+        if target.debug_messages == DebugMessages::Downgrade {
+            filters.push(format!("{affected} debug line(s) written as plain text"));
+        } else {
+            target.debug_omitted = target.debug_omitted.saturating_add(affected);
+            filters.push(format!("{affected} debug line(s) omitted"));
+        }
+        *buffer = if filtered.trim().is_empty() {
+            String::new()
+        } else {
+            filtered
+        };
+    }
+    if let Some(limiter) = &mut target.limiter
+        && let Some(limited) = limiter.apply(message.severity(), buffer)
+    {
+        filters.push("annotation(s) beyond the limit written as plain text".to_owned());
+        *buffer = limited;
+    }
+    if let Some(phase) = target.phase {
+        *buffer = CodeBuild::prefixed(phase, &*buffer);
+    }
+
+    if target.explain {
+        let explanation = Explanation {
+            number,
+            fingerprint,
+            message,
+            platform: target.platform,
+            filters: &filters,
+            output: buffer,
+        };
+        return write!(target.writer, "{explanation}");
+    }
+    target.writer.write_all(buffer.as_bytes())?;
+    if closes_group(target.platform, buffer) {
+        target.writer.flush()?;
+    }
+    Ok(())
+}
+
+/// A description of a message and of the output it produces for a target,
+/// written instead of the output (see [`Target::with_explanations`]).
+struct Explanation<'a> {
+    /// The number of the message, counting from one.
+    number: usize,
+    /// The fingerprint of the message's diagnostic, if any.
+    fingerprint: Option<Fingerprint>,
+    /// The message.
+    message: &'a dyn DynCiMessage,
+    /// The platform of the target.
+    platform: PlatformKind,
+    /// The filters applied to the message for the target.
+    filters: &'a [String],
+    /// The output produced for the target, once filtered.
+    output: &'a str,
+}
+
+impl fmt::Display for Explanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Message {}: {}", self.number, self.message.kind())?;
+        writeln!(f, "  Severity: {}", self.message.severity())?;
+        let details = [
+            ("Code", self.message.code()),
+            ("Package", self.message.package()),
+            ("File", self.message.file()),
+        ];
+        for (name, value) in details {
+            if let Some(text) = value {
+                writeln!(f, "  {name}: {text}")?;
+            }
+        }
+        if let Some(fingerprint) = self.fingerprint {
+            writeln!(f, "  Fingerprint: {fingerprint}")?;
+        }
+        if self.filters.is_empty() {
+            writeln!(f, "  Filters: none")?;
+        } else {
+            writeln!(f, "  Filters: {}", self.filters.join("; "))?;
+        }
+        if self.output.trim().is_empty() {
+            writeln!(f, "  Output for {}: none", self.platform)?;
+        } else {
+            writeln!(f, "  Output for {}:", self.platform)?;
+            for line in self.output.trim_end().lines() {
+                if line.is_empty() {
+                    writeln!(f)?;
+                } else {
+                    writeln!(f, "    {line}")?;
+                }
+            }
+        }
+        writeln!(f)
+    }
+}
+
+/// Maximum number of errors included in a Buildkite annotation.
+const MAX_ANNOTATED_ERRORS: usize = 64;
+
+/// Maximum size of a single error within a Buildkite annotation, in bytes.
+///
+/// Together with [`MAX_ANNOTATED_ERRORS`], this keeps the annotation well
+/// within Buildkite's limit of 1 MiB.
+const MAX_ANNOTATED_ERROR_SIZE: usize = 8 * 1024;
+
+/// The body of a Buildkite annotation summarizing the errors.
+///
+/// # Arguments
+///
+/// * `tool` - The name of the tool which reported the errors.
+/// * `annotated` - The errors to include, formatted as plain text.
+/// * `errors` - The total number of errors.
+fn annotation_body<'b>(
+    tool: &'b str,
+    annotated: &'b [String],
+    errors: usize,
+) -> impl fmt::Display + 'b {
+    fmt::from_fn(move |f| {
+        writeln!(f, "**{tool}** reported {errors} error(s)")?;
+        for error in annotated {
+            writeln!(f)?;
+            write!(f, "{}", CodeBlock(error))?;
+        }
+        let omitted = errors.saturating_sub(annotated.len());
+        if omitted > 0 {
+            writeln!(f)?;
+            writeln!(f, "{omitted} more error(s) omitted.")?;
+        }
+        Ok(())
+    })
+}
+
+/// A formatting pipeline.
+///
+/// The pipeline feeds the tool's output to the parser, and formats each
+/// resulting message once per target. Messages are written to all targets
+/// in the order in which they are parsed, unless stable ordering is enabled
+/// (see [`Pipeline::with_stable_ordering`]).
+///
+/// The pipeline also keeps count of the messages it has processed by
+/// severity (see [`Pipeline::errors`], [`Pipeline::warnings`] and
+/// [`Pipeline::failed_tests`]), by code (see [`Pipeline::lint_stats`]) and
+/// by package (see [`Pipeline::package_stats`]), and records the result of
+/// the build reported by the tool (see [`Pipeline::build_succeeded`]), which
+/// allows embedders to implement their own gating logic without having to
+/// re-parse the formatted output. Timing information carried by the messages
+/// is collected into a [`Timeline`].
+///
+/// # Example
+///
+/// ```
+/// use cifmt::{
+///     ci::PlatformKind,
+///     pipeline::{Pipeline, Target},
+///     tool::CargoCheck,
+/// };
+///
+/// let mut github = Vec::new();
+/// let mut plain = Vec::new();
+///
+/// let mut pipeline = Pipeline::new(
+///     Box::new(CargoCheck::default()),
+///     vec![
+///         Target::new(PlatformKind::GitHub, &mut github),
+///         Target::new(PlatformKind::Plain, &mut plain),
+///     ],
+/// );
+/// pipeline
+///     .process(b"{\"reason\":\"build-finished\",\"success\":true}\n")
+///     .expect("writing to a Vec cannot fail");
+/// pipeline.finish().expect("writing to a Vec cannot fail");
+///
+/// assert_eq!(pipeline.errors(), 0);
+/// assert_eq!(pipeline.build_succeeded(), Some(true));
+/// drop(pipeline);
+///
+/// assert!(String::from_utf8_lossy(&github).starts_with("::notice"));
+/// assert!(String::from_utf8_lossy(&plain).contains("Build finished successfully"));
+/// ```
+pub struct Pipeline<'a> {
+    /// The tool used to parse the input.
+    tool: Box<dyn AnyTool>,
+    /// Filter removing ANSI escape sequences from the input, if enabled.
+    stripper: Option<AnsiStripper>,
+    /// Filter replacing invalid UTF-8 in the input, if enabled.
+    replacer: Option<Utf8Replacer>,
+    /// Whether the tool's parser panicked, after which it is not used again.
+    poisoned: bool,
+    /// Whether the input was read from files, each parsed to its end by a
+    /// tool of its own (see [`Pipeline::run_files`]), so that the pipeline's
+    /// tool is not finished.
+    files: bool,
+    /// Number of invalid UTF-8 sequences replaced in those files.
+    replaced: usize,
+    /// The destination of the parsed messages.
+    sink: Sink<'a>,
+}
+
+/// The output half of a [`Pipeline`].
+///
+/// This is kept separate from the tool so that the tool can be moved to a
+/// parser thread while the messages are written on the current thread.
+struct Sink<'a> {
+    /// The name of the tool used to parse the input.
+    tool: &'static str,
+    /// The targets to which formatted messages are written.
+    targets: Vec<Target<'a>>,
+    /// Number of messages with [`Severity::Error`].
+    errors: usize,
+    /// Number of messages with [`Severity::Warning`].
+    warnings: usize,
+    /// Number of messages reporting a failed test.
+    failed_tests: usize,
+    /// Number of messages written so far.
+    emitted: usize,
+    /// Number of messages parsed so far.
+    parsed: usize,
+    /// The destination of the raw record of each parsed message, if enabled.
+    raw: Option<BufWriter<Box<dyn Write + 'a>>>,
+    /// The destination of the normalized record of each parsed message, if
+    /// enabled.
+    normalized: Option<BufWriter<Box<dyn Write + 'a>>>,
+    /// Number of errors and warnings by their code.
+    lints: LintStats,
+    /// Number of errors and warnings by their package.
+    packages: PackageStats,
+    /// The owners of the files of the repository, if enabled.
+    owners: Option<CodeOwners>,
+    /// Number of errors and warnings by the owners of their file.
+    owner_stats: OwnerStats,
+    /// Number of skipped tests, by the reason given.
+    skips: SkipStats,
+    /// Results of the test suites, with their failed tests.
+    suites: SuiteStats,
+    /// Whether messages are grouped by their package.
+    crate_groups: bool,
+    /// The package whose group is open, if any.
+    open_group: Option<String>,
+    /// The highest severity of all messages processed so far.
+    max_severity: Option<Severity>,
+    /// Whether any message reported that the tool's output is incomplete.
+    incomplete: bool,
+    /// Whether the builds reported by the messages succeeded, if any were
+    /// reported.
+    build_result: Option<bool>,
+    /// Timing information collected from the messages.
+    timeline: Timeline,
+    /// The time at which the pipeline was created.
+    started: Instant,
+    /// Interval after which buffered output is flushed.
+    flush_interval: Duration,
+    /// The time at which the targets were last flushed.
+    last_flush: Instant,
+    /// Buffer into which each message is formatted, reused across messages.
+    buffer: String,
+    /// Directory to which truncated output is written in full.
+    artifact_dir: Option<PathBuf>,
+    /// Number of artifacts written so far.
+    artifacts: usize,
+    /// Errors to include in a Buildkite annotation, formatted as plain text,
+    /// if enabled.
+    annotated: Option<Vec<String>>,
+    /// Messages held back to be sorted by their key, if stable ordering is
+    /// enabled.
+    ordered: Option<Vec<Box<dyn DynCiMessage>>>,
+    /// Results of the tests reported by the messages, if enabled.
+    junit: Option<Report>,
+    /// Diagnostics for reviewdog from the annotations of the messages, if
+    /// enabled.
+    rdjson: Option<Diagnostics>,
+    /// Issues for Code Climate from the annotations of the messages, if
+    /// enabled.
+    codeclimate: Option<Issues>,
+    /// Checkstyle report of the annotations of the messages, if enabled.
+    checkstyle: Option<checkstyle::Report>,
+    /// SARIF report of the annotations of the messages, if enabled.
+    sarif: Option<sarif::Report>,
+    /// Annotations of the messages for `@actions/core`, if enabled.
+    github_annotations: Option<github_annotations::Annotations>,
+    /// HTML report of the annotations and the test results, if enabled.
+    html: Option<html::Report>,
+    /// The file to which the job summary is written, if enabled.
+    step_summary: Option<PathBuf>,
+    /// The artifact to which the full report is written, if enabled.
+    report: Option<ReportArtifact>,
+    /// Sizes of the binaries built, if enabled.
+    sizes: Option<SizeStats>,
+    /// Sizes to compare the binaries against, along with the allowed growth
+    /// in percent, if enabled.
+    size_baseline: Option<(SizeStats, u64)>,
+    /// Whether reading the input timed out.
+    timed_out: bool,
+    /// The summaries of the previous runs, if enabled.
+    history: Option<History>,
+    /// Metadata describing the run, if enabled.
+    metadata: Option<RunMetadata>,
+    /// The properties of the metadata, until written as a header.
+    header: Option<Vec<(&'static str, String)>>,
+    /// The check run on which the annotations beyond the limit are created,
+    /// if enabled.
+    #[cfg(feature = "checks")]
+    check_run: Option<CheckRun>,
+    /// The filer of issues for the failures of the run, if enabled.
+    #[cfg(feature = "issues")]
+    issue_filer: Option<IssueFiler>,
+    /// The failures of the run, if issues are filed.
+    #[cfg(feature = "issues")]
+    failures: Vec<Failure>,
+    /// The notifier posting a summary of the run, if enabled.
+    #[cfg(feature = "notify")]
+    notifier: Option<Notifier>,
+}
+
+/// Captured output which has been taken from a message for truncation.
+struct Truncated {
+    /// The full output.
+    full: String,
+    /// The artifact to which the full output was written, if any.
+    artifact: Option<PathBuf>,
+}
+
+impl Sink<'_> {
+    /// Submit a message to be written to all targets.
+    ///
+    /// Messages reporting the result of a build are not written, but recorded
+    /// for the [`BuildSummary`] written by [`Pipeline::finish`].
+    ///
+    /// If stable ordering is enabled, messages with a sort key are held back
+    /// until the next message without one (or the end of the output), and
+    /// then written sorted by their key.
+    fn submit(&mut self, mut message: Box<dyn DynCiMessage>) -> io::Result<()> {
+        self.parsed = self.parsed.saturating_add(1);
+        if let Some(raw) = &mut self.raw {
+            RawMessage::new(self.parsed, self.tool, &*message).write_json_line(raw)?;
+        }
+        if let Some(normalized) = &mut self.normalized {
+            NormalizedMessage::new(self.tool, &*message).write_json_line(normalized)?;
+        }
+        // The result of a build is summarized by `Pipeline::finish`, along
+        // with the issues reported.
+        if let Some(success) = message.build_result() {
+            self.build_result = Some(self.build_result.unwrap_or(true) && success);
+            return Ok(());
+        }
+        // Timings are recorded on arrival, as held back messages are written
+        // later.
+        if let Some(timing) = message.timing() {
+            self.timeline.record(timing, self.started.elapsed());
+        }
+        if let Some(report) = &mut self.junit
+            && let Some(result) = message.test_result()
+        {
+            report.record(result);
+        }
+        if let Some(sizes) = &mut self.sizes
+            && let Some((name, path)) = message.binary()
+        {
+            sizes.record_file(name, Path::new(path));
+        }
+        match &mut self.ordered {
+            Some(held) if message.sort_key().is_some() => {
+                held.push(message);
+                Ok(())
+            }
+            Some(_) => {
+                self.release()?;
+                self.emit(&mut *message)
+            }
+            None => self.emit(&mut *message),
+        }
+    }
+
+    /// Write all held back messages, sorted by their key.
+    fn release(&mut self) -> io::Result<()> {
+        let Some(ordered) = &mut self.ordered else {
+            return Ok(());
+        };
+        let mut held = mem::take(ordered);
+        // The sort is stable, so messages sharing a key keep their order.
+        held.sort_by(|lhs, rhs| lhs.sort_key().cmp(&rhs.sort_key()));
+        for mut message in held {
+            self.emit(&mut *message)?;
+        }
+        Ok(())
+    }
+
+    /// Record a message and write it to all targets.
+    fn emit(&mut self, message: &mut dyn DynCiMessage) -> io::Result<()> {
+        let severity = message.severity();
+        self.emitted = self.emitted.saturating_add(1);
+        match severity {
+            Severity::Error => self.errors = self.errors.saturating_add(1),
+            Severity::Warning => self.warnings = self.warnings.saturating_add(1),
+            Severity::Debug | Severity::Notice => {}
+        }
+        if severity >= Severity::Warning
+            && let Some(lint) = message.lint()
+        {
+            self.lints.record(lint);
+        }
+        if let Some(package) = message.package() {
+            self.packages.record(package, severity);
+        }
+        if let Some(owners) = &self.owners
+            && let Some(file) = message.file()
+        {
+            self.owner_stats.record(owners.owners(file), severity);
+        }
+        if message.is_test_failure() {
+            self.failed_tests = self.failed_tests.saturating_add(1);
+        }
+        if let Some(result) = message.test_result() {
+            self.skips.record(&result);
+            self.suites.record_test(&result);
+        }
+        if let Some(result) = message.suite_result() {
+            self.suites.record_suite(result);
+        }
+        self.max_severity = self.max_severity.max(Some(severity));
+        self.incomplete |= message.is_incomplete();
+        #[cfg(feature = "issues")]
+        if self.issue_filer.is_some()
+            && let Some(failure) = Failure::from_message(self.tool, message)
+        {
+            self.failures.push(failure);
+        }
+        #[cfg(feature = "notify")]
+        if let Some(notifier) = &mut self.notifier {
+            notifier.record(message);
+        }
+        if self.crate_groups {
+            self.switch_group(message.package(), severity)?;
+        }
+
+        let truncated = self.take_output(message)?;
+        let fingerprint = Fingerprint::of(self.tool, message);
+        for target in &mut self.targets {
+            write_message(
+                target,
+                message,
+                &mut self.buffer,
+                truncated.as_ref(),
+                self.emitted,
+                fingerprint,
+            )?;
+        }
+
+        if let Some(Truncated { full, .. }) = truncated
+            && let Some((_, output)) = message.captured_output_mut()
+        {
+            *output = full;
+        }
+
+        if let Some(diagnostics) = &mut self.rdjson {
+            diagnostics.record(message);
+        }
+        if let Some(issues) = &mut self.codeclimate {
+            issues.record(message);
+        }
+        if let Some(report) = &mut self.checkstyle {
+            report.record(message);
+        }
+        if let Some(report) = &mut self.sarif {
+            report.record(message);
+        }
+        if let Some(annotations) = &mut self.github_annotations {
+            annotations.record(message);
+        }
+        if let Some(report) = &mut self.html {
+            report.record(message);
+        }
+        if severity == Severity::Error
+            && let Some(annotated) = &mut self.annotated
+            && annotated.len() < MAX_ANNOTATED_ERRORS
+        {
+            let mut formatted = message.format_for(PlatformKind::Plain);
+            if let Some(snippet) = message.snippet() {
+                formatted = insert_snippet(&formatted, &snippet);
+            }
+            annotated.push(capture::truncate(
+                &formatted,
+                MAX_ANNOTATED_ERROR_SIZE,
+                None,
+            ));
+        }
+
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write the header describing the run to all targets, unless already
+    /// written or disabled.
+    fn write_header(&mut self) -> io::Result<()> {
+        let Some(properties) = self.header.take() else {
+            return Ok(());
+        };
+        for target in self.targets.iter_mut().filter(|target| !target.explain) {
+            let group = target.platform.group(Title::RunMetadata.text());
+            target.write_text(&format!("{group}\n"))?;
+            for (name, value) in &properties {
+                target.write_text(&format!("{name}: {value}\n"))?;
+            }
+            let endgroup = target.platform.endgroup();
+            if !endgroup.is_empty() {
+                target.write_text(&format!("{endgroup}\n"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the open group of a package and open the group of the next,
+    /// if a message belongs to another package.
+    ///
+    /// Messages without a package close the open group, unless they are
+    /// debug messages (e.g., a compiled artifact), which stay within it.
+    fn switch_group(&mut self, package: Option<&str>, severity: Severity) -> io::Result<()> {
+        if package == self.open_group.as_deref()
+            || (package.is_none() && severity == Severity::Debug)
+        {
+            return Ok(());
+        }
+        let closed = self.open_group.take().is_some();
+        for target in self.targets.iter_mut().filter(|target| !target.explain) {
+            let mut lines = Vec::new();
+            if closed {
+                lines.push(target.platform.endgroup());
+            }
+            if let Some(name) = package {
+                lines.push(target.platform.group(&format!("{}: {name}", Title::Crate)));
+            }
+            for line in lines.iter().filter(|line| !line.is_empty()) {
+                target.write_text(&format!("{line}\n"))?;
+            }
+            if closed {
+                target.writer.flush()?;
+            }
+        }
+        self.open_group = package.map(str::to_owned);
+        Ok(())
+    }
+
+    /// Take the captured output of a message if it must be truncated for any
+    /// target, writing the full output to an artifact if configured.
+    fn take_output(&mut self, message: &mut dyn DynCiMessage) -> io::Result<Option<Truncated>> {
+        let Some(limit) = self.targets.iter().filter_map(Target::output_limit).min() else {
+            return Ok(None);
+        };
+        let Some((name, output)) = message.captured_output_mut() else {
+            return Ok(None);
+        };
+        if output.len() <= limit {
+            return Ok(None);
+        }
+
+        let full = mem::take(output);
+        let artifact = match &self.artifact_dir {
+            Some(dir) => {
+                let path = capture::write_artifact(dir, self.artifacts, name, &full)?;
+                self.artifacts = self.artifacts.saturating_add(1);
+                Some(path)
+            }
+            None => None,
+        };
+        Ok(Some(Truncated { full, artifact }))
+    }
+
+    /// The annotations which were written to the log instead of being
+    /// created by a workflow command, if collected.
+    ///
+    /// The annotations are taken from the first target limiting them.
+    fn omitted(&self) -> &[WorkflowAnnotation] {
+        self.targets
+            .iter()
+            .find_map(|target| target.limiter.as_ref())
+            .map(AnnotationLimiter::collected)
+            .unwrap_or_default()
+    }
+
+    /// Create the annotations which were written to the log instead of being
+    /// created by a workflow command on the check run, if enabled.
+    ///
+    /// The annotations are taken from the first target limiting them, and
+    /// those which do not refer to a file are left in the log only.
+    #[cfg(feature = "checks")]
+    fn create_check_run(&self) {
+        let Some(run) = &self.check_run else {
+            return;
+        };
+        let annotations: Vec<_> = self
+            .omitted()
+            .iter()
+            .filter_map(CheckAnnotation::from_workflow)
+            .collect();
+        if annotations.is_empty() {
+            return;
+        }
+        if let Err(err) = run.create(&annotations) {
+            tracing::warn!("Unable to create the check run: {err}");
+        }
+    }
+
+    /// File issues for the failures of the run, if enabled.
+    #[cfg(feature = "issues")]
+    fn file_issues(&self) {
+        let Some(filer) = &self.issue_filer else {
+            return;
+        };
+        match filer.file(&self.failures) {
+            Ok(issues) => tracing::info!(
+                "Opened {} and updated {} issue(s)",
+                issues.opened,
+                issues.updated
+            ),
+            Err(err) => tracing::warn!("Unable to file the issues: {err}"),
+        }
+    }
+
+    /// Post the summary of the run, if enabled.
+    #[cfg(feature = "notify")]
+    fn notify(&self) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        match notifier.notify(self.tool, self.started.elapsed()) {
+            Ok(()) => tracing::info!("Posted the summary of the run"),
+            Err(err) => tracing::warn!("Unable to post the summary of the run: {err}"),
+        }
+    }
+
+    /// Write the batches of messages parsed by [`Pipeline::run`] as they are
+    /// received, flushing all targets whenever the writer has caught up.
+    ///
+    /// Writing stops at the end of the batches, at the first read error, or
+    /// at an idle timeout.
+    fn write_batches(
+        &mut self,
+        batches: &Receiver<io::Result<Vec<Box<dyn DynCiMessage>>>>,
+    ) -> io::Result<()> {
+        loop {
+            let batch = match batches.try_recv() {
+                Ok(batch) => batch,
+                Err(TryRecvError::Empty) => {
+                    // The writer has caught up, so flush before waiting.
+                    self.flush()?;
+                    match batches.recv() {
+                        Ok(batch) => batch,
+                        Err(RecvError) => break,
+                    }
+                }
+                Err(TryRecvError::Disconnected) => break,
+            };
+            let messages = match batch {
+                Ok(messages) => messages,
+                Err(err) => match IdleTimeout::from_error(&err) {
+                    Some(timeout) => {
+                        self.timed_out = true;
+                        self.submit(Box::new(timeout.clone()))?;
+                        break;
+                    }
+                    None => return Err(err),
+                },
+            };
+            for message in messages {
+                self.submit(message)?;
+            }
+        }
+        self.flush()
+    }
+
+    /// Flush all targets.
+    fn flush(&mut self) -> io::Result<()> {
+        for target in &mut self.targets {
+            target.writer.flush()?;
+        }
+        if let Some(raw) = &mut self.raw {
+            raw.flush()?;
+        }
+        if let Some(normalized) = &mut self.normalized {
+            normalized.flush()?;
+        }
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+impl<'a> Pipeline<'a> {
+    /// Create a new pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The tool used to parse the input.
+    /// * `targets` - The targets to which formatted messages are written.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: Box<dyn AnyTool>, mut targets: Vec<Target<'a>>) -> Self {
+        let name = tool.name();
+        for target in &mut targets {
+            if target.platform == PlatformKind::CodeBuild {
+                target.phase = Some(tool.phase());
+            }
+        }
+        Self {
+            tool,
+            stripper: None,
+            replacer: None,
+            poisoned: false,
+            files: false,
+            replaced: 0,
+            sink: Sink {
+                tool: name,
+                targets,
+                errors: 0,
+                warnings: 0,
+                failed_tests: 0,
+                emitted: 0,
+                parsed: 0,
+                raw: None,
+                normalized: None,
+                lints: LintStats::default(),
+                packages: PackageStats::default(),
+                owners: None,
+                owner_stats: OwnerStats::default(),
+                skips: SkipStats::default(),
+                suites: SuiteStats::default(),
+                crate_groups: false,
+                open_group: None,
+                max_severity: None,
+                incomplete: false,
+                build_result: None,
+                timeline: Timeline::new(),
+                started: Instant::now(),
+                flush_interval: DEFAULT_FLUSH_INTERVAL,
+                last_flush: Instant::now(),
+                buffer: String::new(),
+                artifact_dir: None,
+                artifacts: 0,
+                annotated: None,
+                ordered: None,
+                junit: None,
+                rdjson: None,
+                codeclimate: None,
+                checkstyle: None,
+                sarif: None,
+                github_annotations: None,
+                html: None,
+                step_summary: None,
+                report: None,
+                sizes: None,
+                size_baseline: None,
+                timed_out: false,
+                history: None,
+                metadata: None,
+                header: None,
+                #[cfg(feature = "checks")]
+                check_run: None,
+                #[cfg(feature = "issues")]
+                issue_filer: None,
+                #[cfg(feature = "issues")]
+                failures: Vec::new(),
+                #[cfg(feature = "notify")]
+                notifier: None,
+            },
+        }
+    }
+
+    /// Set the interval after which buffered output is flushed.
+    ///
+    /// While processing a chunk of input, the targets are flushed whenever
+    /// this interval has elapsed since the last flush. Defaults to
+    /// [`DEFAULT_FLUSH_INTERVAL`].
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The maximum time for which output is buffered.
+    #[must_use]
+    #[inline]
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.sink.flush_interval = interval;
+        self
+    }
+
+    /// Write captured output which is truncated for any target in full to an
+    /// artifact file within the given directory.
+    ///
+    /// The truncated output references the artifact, which allows the full
+    /// output to be uploaded and inspected separately.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to which artifacts are written. It is created
+    ///   if it does not exist.
+    #[must_use]
+    #[inline]
+    pub fn with_artifact_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.sink.artifact_dir = Some(dir.into());
+        self
+    }
+
+    /// Remove ANSI escape sequences from the input before it is parsed.
+    ///
+    /// This allows tools which emit colour codes even when their output is
+    /// piped to be parsed. See [`AnsiStripper`] for the sequences which are
+    /// removed.
+    #[must_use]
+    #[inline]
+    pub fn with_ansi_stripping(mut self) -> Self {
+        self.stripper = Some(AnsiStripper::default());
+        self
+    }
+
+    /// Replace invalid UTF-8 sequences in the input before it is parsed.
+    ///
+    /// Without this, lines containing invalid UTF-8 cannot be parsed and are
+    /// skipped. If any sequences are replaced, [`Pipeline::finish`] reports
+    /// this through an [`InvalidUtf8`] notice. See [`Utf8Replacer`] for
+    /// details of the replacement.
+    #[must_use]
+    #[inline]
+    pub fn with_lossy_utf8(mut self) -> Self {
+        self.replacer = Some(Utf8Replacer::default());
+        self
+    }
+
+    /// Summarize the errors in a Buildkite annotation once the pipeline is
+    /// finished.
+    ///
+    /// The annotation is created by [`Pipeline::finish`] through
+    /// [`Buildkite::annotate`], which requires the Buildkite agent to be
+    /// available. No annotation is created if no errors were reported.
+    #[must_use]
+    #[inline]
+    pub fn with_buildkite_annotations(mut self) -> Self {
+        self.sink.annotated = Some(Vec::new());
+        self
+    }
+
+    /// Order the messages of concurrent producers deterministically.
+    ///
+    /// Tools running tests or compiling crates in parallel emit their
+    /// messages in an order which differs from run to run. With this enabled,
+    /// messages with a [sort key](crate::ci_message::Classify::sort_key)
+    /// (e.g., the name of a test) are held back until the next message
+    /// without one (e.g., the end of a test suite), and then written sorted
+    /// by their key, which keeps messages sharing a key in their original
+    /// order. The output therefore only streams between such messages, and
+    /// held back messages are only counted once they are written.
+    #[must_use]
+    #[inline]
+    pub fn with_stable_ordering(mut self) -> Self {
+        self.sink.ordered = Some(Vec::new());
+        self
+    }
+
+    /// Attribute the errors and warnings to the owners of the file in which
+    /// they lie (see [`Classify::file`](crate::ci_message::Classify::file)),
+    /// as assigned by a `CODEOWNERS` file.
+    ///
+    /// The issues of each owner are summarized at the end of the output by
+    /// [`Pipeline::finish`], and in the job summary (see
+    /// [`Pipeline::with_step_summary`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `owners` - The owners of the files of the repository.
+    #[must_use]
+    #[inline]
+    pub fn with_code_owners(mut self, owners: CodeOwners) -> Self {
+        self.sink.owners = Some(owners);
+        self
+    }
+
+    /// Group the messages of each package (e.g., each crate of a workspace)
+    /// in the log.
+    ///
+    /// A group is opened whenever a message belongs to another package than
+    /// the previous one, and closed by the next message without a package
+    /// (other than debug messages) or at the end of the output. Messages of
+    /// concurrently built packages are interleaved, so that a package may
+    /// have several groups; stable ordering reduces this (see
+    /// [`Pipeline::with_stable_ordering`]). GitHub Actions and Azure
+    /// Pipelines do not nest groups, so that groups written by the messages
+    /// themselves (e.g., folded diagnostics) end the package's group early.
+    #[must_use]
+    #[inline]
+    pub fn with_crate_groups(mut self) -> Self {
+        self.sink.crate_groups = true;
+        self
+    }
+
+    /// Collect the results of the tests reported by the tool into a JUnit
+    /// report, named after the tool.
+    ///
+    /// Results are recorded as the messages reporting them are received, with
+    /// their captured output in full. The report is available through
+    /// [`Pipeline::junit_report`] and can be written once the pipeline is
+    /// finished, for platforms which display test results from such a report
+    /// (e.g., [`CodeBuild`](crate::ci::CodeBuild)).
+    #[must_use]
+    #[inline]
+    pub fn with_junit_report(mut self) -> Self {
+        self.sink.junit = Some(Report::new(self.tool.name()));
+        self
+    }
+
+    /// Collect the annotations of the messages as diagnostics for reviewdog,
+    /// which posts them as review comments on the lines changed by a pull
+    /// request, on any forge it supports.
+    ///
+    /// The diagnostics are available through [`Pipeline::rdjson_diagnostics`]
+    /// and can be written once the pipeline is finished, in the Reviewdog
+    /// Diagnostic Format (see [`Diagnostics`]).
+    #[must_use]
+    #[inline]
+    pub fn with_rdjson_diagnostics(mut self) -> Self {
+        self.sink.rdjson = Some(Diagnostics::new(self.tool.name()));
+        self
+    }
+
+    /// Collect the annotations of the messages as issues for Code Climate,
+    /// or for a dashboard reading the same format (e.g., GitLab's code
+    /// quality reports).
+    ///
+    /// The issues are available through [`Pipeline::codeclimate_issues`] and
+    /// can be written once the pipeline is finished (see [`Issues`]).
+    #[must_use]
+    #[inline]
+    pub fn with_codeclimate_issues(mut self) -> Self {
+        self.sink.codeclimate = Some(Issues::new(self.tool.name()));
+        self
+    }
+
+    /// Collect the annotations of the messages into a Checkstyle report,
+    /// for plugins of CI servers which read issues from such a report (e.g.,
+    /// the Warnings Next Generation plugin of Jenkins).
+    ///
+    /// The report is available through [`Pipeline::checkstyle_report`] and
+    /// can be written once the pipeline is finished (see
+    /// [`checkstyle::Report`]).
+    #[must_use]
+    #[inline]
+    pub fn with_checkstyle_report(mut self) -> Self {
+        self.sink.checkstyle = Some(checkstyle::Report::new(self.tool.name()));
+        self
+    }
+
+    /// Collect the annotations of the messages into a SARIF report, for code
+    /// scanning services (e.g., GitHub's code scanning alerts), along with
+    /// the fixes which the tool considers safe to apply automatically.
+    ///
+    /// The report is available through [`Pipeline::sarif_report`] and can be
+    /// written once the pipeline is finished (see [`sarif::Report`]).
+    #[must_use]
+    #[inline]
+    pub fn with_sarif_report(mut self) -> Self {
+        self.sink.sarif = Some(sarif::Report::new(self.tool.name()));
+        self
+    }
+
+    /// Collect the annotations of the messages as the arguments of the
+    /// annotation functions of `@actions/core`, to create them from another
+    /// step (e.g., through `actions/github-script`).
+    ///
+    /// The annotations are available through
+    /// [`Pipeline::github_annotations`] and can be written once the pipeline
+    /// is finished (see [`github_annotations::Annotations`]).
+    #[must_use]
+    #[inline]
+    pub fn with_github_annotations(mut self) -> Self {
+        self.sink.github_annotations = Some(github_annotations::Annotations::default());
+        self
+    }
+
+    /// Collect the annotations and the results of the tests into a
+    /// self-contained HTML report, to be uploaded as an artifact of the job.
+    ///
+    /// The report is available through [`Pipeline::html_report`] and can be
+    /// written once the pipeline is finished (see [`html::Report`]).
+    #[must_use]
+    #[inline]
+    pub fn with_html_report(mut self) -> Self {
+        self.sink.html = Some(html::Report::new(self.tool.name()));
+        self
+    }
+
+    /// Create the annotations beyond the limit of a GitHub Actions step on a
+    /// check run through the Checks API, once the pipeline is finished.
+    ///
+    /// GitHub creates only a limited number of annotations from the workflow
+    /// commands of a step (see [`Target::with_annotation_limit`]). The
+    /// annotations beyond the limit are still written to the log, and are
+    /// also created on the given check run by [`Pipeline::finish`]. This
+    /// requires the `checks` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The check run on which the annotations are created.
+    #[cfg(feature = "checks")]
+    #[must_use]
+    #[inline]
+    pub fn with_check_run(mut self, run: CheckRun) -> Self {
+        for target in &mut self.sink.targets {
+            target.limiter = target.limiter.take().map(AnnotationLimiter::collecting);
+        }
+        self.sink.check_run = Some(run);
+        self
+    }
+
+    /// File issues for the errors and failed tests of the run, once the
+    /// pipeline is finished.
+    ///
+    /// See [`IssueFiler`] for how failures are matched to the issues already
+    /// open. This requires the `issues` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `filer` - The filer of the issues.
+    #[cfg(feature = "issues")]
+    #[must_use]
+    #[inline]
+    pub fn with_issue_filer(mut self, filer: IssueFiler) -> Self {
+        self.sink.issue_filer = Some(filer);
+        self
+    }
+
+    /// Post a summary of the run to a chat webhook, once the pipeline is
+    /// finished.
+    ///
+    /// See [`Notifier`] for what the summary includes. This requires the
+    /// `notify` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `notifier` - The notifier.
+    #[cfg(feature = "notify")]
+    #[must_use]
+    #[inline]
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.sink.notifier = Some(notifier);
+        self
+    }
+
+    /// Write a Markdown summary of the output to the job summary of a GitHub
+    /// Actions step once the pipeline is finished.
+    ///
+    /// The summary (see [`Pipeline::job_summary`]) is appended to the given
+    /// file by [`Pipeline::finish`] through [`GitHub::summary`]. This also
+    /// collects the results of the tests, as for
+    /// [`Pipeline::with_junit_report`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The job summary (typically the path given by
+    ///   [`GitHub::STEP_SUMMARY_ENV`]).
+    #[must_use]
+    #[inline]
+    pub fn with_step_summary(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sink.step_summary = Some(path.into());
+        if self.sink.junit.is_none() {
+            self.sink.junit = Some(Report::new(self.tool.name()));
+        }
+        self
+    }
+
+    /// Write the full report of the run as an artifact of the job once the
+    /// pipeline is finished.
+    ///
+    /// The report holds the job summary (see [`Pipeline::job_summary`]),
+    /// followed by the annotations beyond the limit of a GitHub Actions step
+    /// (see [`Target::with_annotation_limit`]). Each target refers to the
+    /// report at its end, and the job summary (see
+    /// [`Pipeline::with_step_summary`]) links to it, truncated if it would
+    /// otherwise exceed [`GitHub::MAX_STEP_SUMMARY_SIZE`]. This also collects
+    /// the results of the tests, as for [`Pipeline::with_junit_report`].
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - The artifact to which the report is written.
+    #[must_use]
+    #[inline]
+    pub fn with_report_artifact(mut self, report: ReportArtifact) -> Self {
+        for target in &mut self.sink.targets {
+            target.limiter = target.limiter.take().map(AnnotationLimiter::collecting);
+        }
+        self.sink.report = Some(report);
+        if self.sink.junit.is_none() {
+            self.sink.junit = Some(Report::new(self.tool.name()));
+        }
+        self
+    }
+
+    /// Write the raw record of each parsed message as a line of JSON (see
+    /// [`RawMessage`]), alongside the output to the targets.
+    ///
+    /// Every message parsed is recorded as it arrives, including those which
+    /// are not written to the targets (e.g., the result of a build), before
+    /// any filter of a target applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the records (e.g., stderr).
+    #[must_use]
+    #[inline]
+    pub fn with_raw_messages(mut self, writer: impl Write + 'a) -> Self {
+        self.sink.raw = Some(BufWriter::new(Box::new(writer)));
+        self
+    }
+
+    /// Write the normalized record of each parsed message as a line of JSON
+    /// (see [`NormalizedMessage`]), as it is parsed.
+    ///
+    /// As for [`Pipeline::with_raw_messages`], every message parsed is
+    /// recorded, before any filter of a target applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the records (e.g., a file).
+    #[must_use]
+    #[inline]
+    pub fn with_normalized_messages(mut self, writer: impl Write + 'a) -> Self {
+        self.sink.normalized = Some(BufWriter::new(Box::new(writer)));
+        self
+    }
+
+    /// Track the sizes of the binaries built by the tool (see
+    /// [`Classify::binary`](crate::ci_message::Classify::binary)).
+    ///
+    /// The size of each binary is read once it is reported, and the sizes are
+    /// summarized by [`Pipeline::finish`] in a [`SizeSummary`].
+    #[must_use]
+    #[inline]
+    pub fn with_binary_sizes(mut self) -> Self {
+        if self.sink.sizes.is_none() {
+            self.sink.sizes = Some(SizeStats::default());
+        }
+        self
+    }
+
+    /// Compare the sizes of the binaries built by the tool against a
+    /// baseline, warning about each binary which grew by more than the given
+    /// percentage (see [`SizeSummary::regressions`]).
+    ///
+    /// This also tracks the sizes of the binaries, as for
+    /// [`Pipeline::with_binary_sizes`].
+    ///
+    /// # Arguments
+    ///
+    /// * `baseline` - The sizes of the binaries of a previous build.
+    /// * `threshold` - The allowed growth, in percent.
+    #[must_use]
+    #[inline]
+    pub fn with_size_baseline(mut self, baseline: SizeStats, threshold: u64) -> Self {
+        self.sink.size_baseline = Some((baseline, threshold));
+        self.with_binary_sizes()
+    }
+
+    /// Describe the run with its metadata.
+    ///
+    /// The metadata is written as a group at the start of each target, and
+    /// included in the JUnit report (see [`Pipeline::with_junit_report`]) and
+    /// the Chrome trace (see [`Timeline::write_chrome_trace`]) once the
+    /// pipeline is finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The metadata of the run.
+    #[must_use]
+    #[inline]
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.sink.header = Some(metadata.properties());
+        self.sink.metadata = Some(metadata);
+        self
+    }
+
+    /// Record a summary of the run in a history once the pipeline is
+    /// finished, and compare it against the previous runs in the job summary
+    /// (see [`Pipeline::with_step_summary`]).
+    ///
+    /// This also collects the results of the tests, as for
+    /// [`Pipeline::with_junit_report`], so as to record the failed tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The summaries of the previous runs.
+    #[must_use]
+    #[inline]
+    pub fn with_history(mut self, history: History) -> Self {
+        self.sink.history = Some(history);
+        if self.sink.junit.is_none() {
+            self.sink.junit = Some(Report::new(self.tool.name()));
+        }
+        self
+    }
+
+    /// Get the name of the tool used to parse the input.
+    #[must_use]
+    #[inline]
+    pub fn tool_name(&self) -> &'static str {
+        self.tool.name()
+    }
+
+    /// Get the targets of this pipeline.
+    #[must_use]
+    #[inline]
+    pub fn targets(&self) -> &[Target<'a>] {
+        &self.sink.targets
+    }
+
+    /// Number of messages with [`Severity::Error`] processed so far.
+    #[must_use]
+    #[inline]
+    pub fn errors(&self) -> usize {
+        self.sink.errors
+    }
+
+    /// Number of messages with [`Severity::Warning`] processed so far.
+    #[must_use]
+    #[inline]
+    pub fn warnings(&self) -> usize {
+        self.sink.warnings
+    }
+
+    /// Number of messages reporting a failed test processed so far.
+    #[must_use]
+    #[inline]
+    pub fn failed_tests(&self) -> usize {
+        self.sink.failed_tests
+    }
+
+    /// Number of errors and warnings processed so far, by their lint (see
+    /// [`Classify::lint`](crate::ci_message::Classify::lint)).
+    #[must_use]
+    #[inline]
+    pub fn lint_stats(&self) -> &LintStats {
+        &self.sink.lints
+    }
+
+    /// Number of errors and warnings processed so far, by the package to
+    /// which they belong (see
+    /// [`Classify::package`](crate::ci_message::Classify::package)).
+    #[must_use]
+    #[inline]
+    pub fn package_stats(&self) -> &PackageStats {
+        &self.sink.packages
+    }
+
+    /// Number of errors and warnings processed so far, by the owners of the
+    /// file in which they lie, if enabled (see
+    /// [`Pipeline::with_code_owners`]).
+    #[must_use]
+    #[inline]
+    pub fn owner_stats(&self) -> &OwnerStats {
+        &self.sink.owner_stats
+    }
+
+    /// Number of tests processed so far, and of those skipped by the reason
+    /// given (see
+    /// [`Classify::test_result`](crate::ci_message::Classify::test_result)).
+    #[must_use]
+    #[inline]
+    pub fn skip_stats(&self) -> &SkipStats {
+        &self.sink.skips
+    }
+
+    /// The highest severity of all messages processed so far.
+    ///
+    /// Returns `None` if no messages have been processed.
+    #[must_use]
+    #[inline]
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.sink.max_severity
+    }
+
+    /// Whether any message processed so far reported that the tool's output
+    /// is incomplete, for example because it ended in the middle of a
+    /// message or before a test suite finished.
+    #[must_use]
+    #[inline]
+    pub fn is_incomplete(&self) -> bool {
+        self.sink.incomplete
+    }
+
+    /// Whether the build reported by the tool succeeded (see
+    /// [`Classify::build_result`](crate::ci_message::Classify::build_result)).
+    ///
+    /// Returns `None` if the tool has not reported the result of a build, and
+    /// `Some(false)` if any of the builds it reported failed.
+    #[must_use]
+    #[inline]
+    pub fn build_succeeded(&self) -> Option<bool> {
+        self.sink.build_result
+    }
+
+    /// Whether [`Pipeline::run`] stopped reading because no input was
+    /// received within an idle timeout.
+    #[must_use]
+    #[inline]
+    pub fn timed_out(&self) -> bool {
+        self.sink.timed_out
+    }
+
+    /// The timing information collected so far.
+    #[must_use]
+    #[inline]
+    pub fn timeline(&self) -> &Timeline {
+        &self.sink.timeline
+    }
+
+    /// The sizes of the binaries built so far, if tracked (see
+    /// [`Pipeline::with_binary_sizes`]).
+    #[must_use]
+    #[inline]
+    pub fn binary_sizes(&self) -> Option<&SizeStats> {
+        self.sink.sizes.as_ref()
+    }
+
+    /// The history of the runs, including this run once the pipeline is
+    /// finished, if enabled (see [`Pipeline::with_history`]).
+    #[must_use]
+    #[inline]
+    pub fn history(&self) -> Option<&History> {
+        self.sink.history.as_ref()
+    }
+
+    /// A summary of the run so far, as recorded in the history (see
+    /// [`Pipeline::with_history`]).
+    ///
+    /// The tests are only included if their results are collected (see
+    /// [`Pipeline::with_junit_report`]).
+    #[must_use]
+    #[inline]
+    pub fn run_summary(&self) -> RunSummary {
+        let tests = self.sink.junit.as_ref();
+        RunSummary {
+            errors: self.sink.errors,
+            warnings: self.sink.warnings,
+            tests: tests.map_or(0, Report::len),
+            failed_tests: tests
+                .map(|report| {
+                    report
+                        .results()
+                        .filter(|result| result.outcome == TestOutcome::Failed)
+                        .map(|result| result.name.to_owned())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            duration_ms: u64::try_from(self.sink.started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            ..RunSummary::new(self.tool.name())
+        }
+    }
+
+    /// The JUnit report of the test results collected so far, if enabled
+    /// (see [`Pipeline::with_junit_report`] and
+    /// [`Pipeline::with_step_summary`]).
+    #[must_use]
+    #[inline]
+    pub fn junit_report(&self) -> Option<&Report> {
+        self.sink.junit.as_ref()
+    }
+
+    /// The diagnostics for reviewdog collected so far, if enabled (see
+    /// [`Pipeline::with_rdjson_diagnostics`]).
+    #[must_use]
+    #[inline]
+    pub fn rdjson_diagnostics(&self) -> Option<&Diagnostics> {
+        self.sink.rdjson.as_ref()
+    }
+
+    /// The issues for Code Climate collected so far, if enabled (see
+    /// [`Pipeline::with_codeclimate_issues`]).
+    #[must_use]
+    #[inline]
+    pub fn codeclimate_issues(&self) -> Option<&Issues> {
+        self.sink.codeclimate.as_ref()
+    }
+
+    /// The Checkstyle report of the annotations collected so far, if enabled
+    /// (see [`Pipeline::with_checkstyle_report`]).
+    #[must_use]
+    #[inline]
+    pub fn checkstyle_report(&self) -> Option<&checkstyle::Report> {
+        self.sink.checkstyle.as_ref()
+    }
+
+    /// The SARIF report of the annotations collected so far, if enabled (see
+    /// [`Pipeline::with_sarif_report`]).
+    #[must_use]
+    #[inline]
+    pub fn sarif_report(&self) -> Option<&sarif::Report> {
+        self.sink.sarif.as_ref()
+    }
+
+    /// The annotations for `@actions/core` collected so far, if enabled (see
+    /// [`Pipeline::with_github_annotations`]).
+    #[must_use]
+    #[inline]
+    pub fn github_annotations(&self) -> Option<&github_annotations::Annotations> {
+        self.sink.github_annotations.as_ref()
+    }
+
+    /// The HTML report of the annotations and test results collected so far,
+    /// if enabled (see [`Pipeline::with_html_report`]).
+    #[must_use]
+    #[inline]
+    pub fn html_report(&self) -> Option<&html::Report> {
+        self.sink.html.as_ref()
+    }
+
+    /// A Markdown summary of the messages processed so far, including the
+    /// results of the tests if they are collected (see
+    /// [`Pipeline::with_junit_report`]).
+    #[must_use]
+    #[inline]
+    pub fn job_summary(&self) -> JobSummary<'_> {
+        JobSummary {
+            tool: self.tool.name(),
+            build_result: self.sink.build_result,
+            errors: self.sink.errors,
+            warnings: self.sink.warnings,
+            incomplete: self.sink.incomplete,
+            tests: self.sink.junit.as_ref(),
+            lints: &self.sink.lints,
+            packages: &self.sink.packages,
+            owners: &self.sink.owner_stats,
+            skips: &self.sink.skips,
+            suites: &self.sink.suites,
+            trend: self.sink.history.as_ref().and_then(History::trend),
+        }
+    }
+
+    /// Parse a chunk of the tool's output and write the formatted messages to
+    /// all targets.
+    ///
+    /// Successive calls are expected to provide contiguous data from the
+    /// tool's output. All targets are flushed once the chunk has been
+    /// processed.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - A chunk of the tool's output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to any of the targets fails.
+    #[inline]
+    pub fn process(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.sink.write_header()?;
+        let messages = parse(
+            &mut *self.tool,
+            &mut self.poisoned,
+            self.stripper.as_mut(),
+            self.replacer.as_mut(),
+            buf,
+        );
+        for message in messages {
+            self.sink.submit(message)?;
+        }
+        self.sink.flush()
+    }
+
+    /// Read the tool's output until the end of input, and write the
+    /// formatted messages to all targets.
+    ///
+    /// Unlike repeated calls to [`Pipeline::process`], reading, parsing, and
+    /// writing each happen on their own thread, connected by bounded
+    /// channels. This allows the three stages to overlap, so that the
+    /// pipeline keeps up with tools which produce output quickly. Messages
+    /// are still written in the order in which they are parsed, and the
+    /// targets are flushed whenever the writer has caught up with the
+    /// parser.
+    ///
+    /// The reader is expected to continue from any data already passed to
+    /// [`Pipeline::process`].
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of the tool's output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the input or writing to any of the targets
+    /// fails. In the latter case, parsing stops, and reading stops after the
+    /// chunk currently being read. If reading fails because no input was received within an
+    /// idle timeout (see [`IdleReader`](crate::timeout::IdleReader)), an
+    /// [`IdleTimeout`] error is reported instead, and reading stops without
+    /// an error so that the pipeline can be finished (see
+    /// [`Pipeline::timed_out`]).
+    #[inline]
+    pub fn run(&mut self, reader: impl Read + Send) -> io::Result<()> {
+        self.sink.write_header()?;
+        let Self {
+            tool,
+            stripper,
+            replacer,
+            poisoned,
+            sink,
+            ..
+        } = self;
+        thread::scope(|scope| {
+            let (chunk_tx, chunk_rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+            let (batch_tx, batch_rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+            scope.spawn(move || read_chunks(reader, &chunk_tx));
+            scope.spawn(move || {
+                for chunk in chunk_rx {
+                    let batch = chunk.map(|data: Vec<u8>| {
+                        parse(
+                            &mut **tool,
+                            poisoned,
+                            stripper.as_mut(),
+                            replacer.as_mut(),
+                            &data,
+                        )
+                    });
+                    if batch_tx.send(batch).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let result = sink.write_batches(&batch_rx);
+            // Drop the receiver before the scope joins the threads, so that
+            // the parser, and in turn the reader, stop rather than block on a
+            // full channel if writing failed.
+            drop(batch_rx);
+            result
+        })
+    }
+
+    /// Read several files of the tool's output, parse them in parallel, and
+    /// write the formatted messages to all targets.
+    ///
+    /// Each file is parsed to its end by a tool of its own, created by
+    /// `new_tool`, on the global [rayon] thread pool. This suits the
+    /// consolidation of many reports (e.g., one for each shard of a matrix
+    /// build), which would otherwise be parsed one after the other. The
+    /// messages of the files are then written in the order in which the paths
+    /// are given, whichever file is parsed first, so that the output is
+    /// identical across runs.
+    ///
+    /// As each file is finished by its own tool, the pipeline's tool is not
+    /// used, and is not finished by [`Pipeline::finish`]; it should be of the
+    /// same kind, as it names the tool in the summaries.
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - The files of the tool's output.
+    /// * `new_tool` - Creates the tool which parses a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading any of the files fails, in which case no
+    /// message is written, or if writing to any of the targets fails.
+    #[inline]
+    pub fn run_files<P>(
+        &mut self,
+        paths: &[P],
+        new_tool: impl Fn() -> Box<dyn AnyTool> + Sync,
+    ) -> io::Result<()>
+    where
+        P: AsRef<Path> + Sync,
+    {
+        self.sink.write_header()?;
+        let strip = self.stripper.is_some();
+        let lossy = self.replacer.is_some();
+        // Collecting into a `Vec` keeps the results in the order of the paths.
+        let results: Vec<_> = paths
+            .par_iter()
+            .map(|path| parse_file(path.as_ref(), new_tool(), strip, lossy))
+            .collect();
+        let parsed = results.into_iter().collect::<io::Result<Vec<_>>>()?;
+
+        self.files = true;
+        for (messages, replaced) in parsed {
+            self.replaced = self.replaced.saturating_add(replaced);
+            for message in messages {
+                self.sink.submit(message)?;
+            }
+        }
+        self.sink.flush()
+    }
+
+    /// Flush all targets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if flushing any of the targets fails.
+    #[inline]
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.sink.flush()
+    }
+
+    /// Finish processing at the end of the tool's output, write the final
+    /// status to all targets and flush them.
+    ///
+    /// This should be called once the tool's output has been processed in
+    /// its entirety. Any messages the tool reports at the end of its output
+    /// (see [`Tool::finish`](crate::tool::Tool::finish)) are written first,
+    /// followed by an [`InvalidUtf8`] notice if invalid UTF-8 was replaced,
+    /// any messages held back for stable ordering, a [`PackageSummary`] of the
+    /// issues of each package if several had issues, a [`SkipSummary`] of the
+    /// reasons tests were skipped, if any, a [`SizeSummary`] of the binaries
+    /// built if tracked, preceded by a warning for each binary which grew
+    /// beyond the threshold of the baseline, and a [`BuildSummary`] if the
+    /// tool reported the result of a build.
+    /// Targets whose platform reports a final status (e.g., the
+    /// `task.complete` command of Azure DevOps) then have it written based on
+    /// the messages processed, and GitHub Actions targets have a warning
+    /// written if annotations exceeded the platform's limit (see
+    /// [`GitHub::MAX_ANNOTATIONS_PER_STEP`](crate::ci::GitHub::MAX_ANNOTATIONS_PER_STEP));
+    /// all other targets are only flushed. Finally,
+    /// the Buildkite annotation is created, the run recorded in the history
+    /// and the job summary written, if enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to or flushing any of the targets fails,
+    /// if the Buildkite annotation cannot be created, or if the job summary
+    /// cannot be written. A failure to create the check run, file the issues
+    /// or post the notification is logged instead.
+    #[inline]
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.sink.write_header()?;
+        if let Some(metadata) = &self.sink.metadata {
+            let properties = metadata.properties();
+            if let Some(report) = &mut self.sink.junit {
+                report.set_properties(properties.iter().cloned());
+            }
+            self.sink.timeline.set_metadata(properties);
+        }
+        let name = self.tool.name();
+        let mut replaced = self.replaced;
+        if !self.files {
+            if let Some(filter) = &mut self.replacer {
+                let rest = filter.finish();
+                for message in
+                    resilience::guard(name, &mut self.poisoned, || self.tool.parse_dyn(rest))
+                {
+                    self.sink.submit(message)?;
+                }
+                replaced = filter.replaced();
+            }
+            for message in resilience::guard(name, &mut self.poisoned, || self.tool.finish_dyn()) {
+                self.sink.submit(message)?;
+            }
+        }
+        if replaced > 0 {
+            self.sink.submit(Box::new(InvalidUtf8 { replaced }))?;
+        }
+        self.sink.release()?;
+        self.sink.switch_group(None, Severity::Notice)?;
+        if self.sink.packages.len() > 1 {
+            self.sink
+                .emit(&mut PackageSummary::new(&self.sink.packages))?;
+        }
+        if !self.sink.owner_stats.is_empty() {
+            self.sink
+                .emit(&mut OwnerSummary::new(&self.sink.owner_stats))?;
+        }
+        if !self.sink.skips.is_empty() {
+            self.sink.emit(&mut SkipSummary::new(&self.sink.skips))?;
+        }
+        if let Some(sizes) = self.sink.sizes.as_ref().filter(|sizes| !sizes.is_empty()) {
+            let baseline = self.sink.size_baseline.as_ref();
+            let mut summary = SizeSummary::new(sizes, baseline.map(|(stats, _)| stats));
+            let regressions = baseline
+                .map(|&(_, threshold)| summary.regressions(threshold))
+                .unwrap_or_default();
+            for mut regression in regressions {
+                self.sink.emit(&mut regression)?;
+            }
+            self.sink.emit(&mut summary)?;
+        }
+        if let Some(success) = self.sink.build_result {
+            self.sink.emit(&mut BuildSummary::new(
+                success,
+                self.sink.errors,
+                self.sink.warnings,
+            ))?;
+        }
+
+        let report = self.write_report()?;
+        let sink = &mut self.sink;
+        for target in &mut sink.targets {
+            if let Some(location) = &report {
+                target.write_text(&format!("Full report: {location}\n"))?;
+            }
+            if let Some(summary) = target
+                .limiter
+                .as_ref()
+                .filter(|_| !target.explain)
+                .and_then(|limiter| limiter.summary(sink.step_summary.is_some()))
+            {
+                target.writer.write_all(summary.as_bytes())?;
+            }
+            if target.debug_omitted > 0 {
+                writeln!(
+                    target.writer,
+                    "{} debug message(s) omitted; re-run with debug logging enabled to show them",
+                    target.debug_omitted
+                )?;
+            }
+            if target.platform == PlatformKind::AzureDevOps && !target.explain {
+                let result = match sink.max_severity {
+                    Some(Severity::Error) => TaskResult::Failed,
+                    Some(Severity::Warning) => TaskResult::SucceededWithIssues,
+                    _ => TaskResult::Succeeded,
+                };
+                let mut message = format!("{} error(s), {} warning(s)", sink.errors, sink.warnings);
+                if sink.incomplete {
+                    message.push_str("; output incomplete");
+                }
+                let complete = AzureDevOps::complete(result).message(&message).format();
+                target.writer.write_all(complete.as_bytes())?;
+            }
+        }
+        sink.flush()?;
+        self.publish_summaries()
+    }
+
+    /// Write the full report as an artifact, if enabled.
+    ///
+    /// # Returns
+    ///
+    /// Where the report can be found: its URL if known, or else its path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be written or registered.
+    fn write_report(&self) -> io::Result<Option<String>> {
+        let Some(report) = &self.sink.report else {
+            return Ok(None);
+        };
+        let content = FullReport {
+            summary: self.job_summary(),
+            omitted: self.sink.omitted(),
+        };
+        let path = report.write(&content.to_string())?;
+        Ok(Some(
+            report.url().unwrap_or_else(|| path.display().to_string()),
+        ))
+    }
+
+    /// Create the Buildkite annotation, record the run in the history, write
+    /// the job summary, create the check run, file the issues and post the
+    /// notification, if enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Buildkite annotation or the job summary cannot
+    /// be created. As the check run, the issues and the notification repeat
+    /// what the log already shows, a failure to create them is only logged.
+    fn publish_summaries(&mut self) -> io::Result<()> {
+        if let Some(annotated) = &self.sink.annotated
+            && !annotated.is_empty()
+        {
+            let tool = self.tool.name();
+            let body = annotation_body(tool, annotated, self.sink.errors).to_string();
+            Buildkite::annotate(&body)
+                .style(AnnotationStyle::Error)
+                .context(&format!("cifmt-{tool}"))
+                .run()?;
+        }
+        if self.sink.history.is_some() {
+            let run = self.run_summary();
+            if let Some(history) = &mut self.sink.history {
+                history.record(run);
+            }
+        }
+        if let Some(path) = &self.sink.step_summary {
+            let mut summary = self.job_summary().to_string();
+            if let Some(report) = &self.sink.report {
+                let max_size = GitHub::MAX_STEP_SUMMARY_SIZE.saturating_sub(file_size(path));
+                summary = report.link_summary(&summary, max_size);
+            }
+            GitHub::summary(&summary).path(path).write()?;
+        }
+        #[cfg(feature = "checks")]
+        self.sink.create_check_run();
+        #[cfg(feature = "issues")]
+        self.sink.file_issues();
+        #[cfg(feature = "notify")]
+        self.sink.notify();
+        Ok(())
+    }
+}
+
+/// Parse a chunk of the tool's output, removing escape sequences first if a
+/// stripper is provided, and replacing invalid UTF-8 if a replacer is
+/// provided.
+///
+/// A panic of the parser is reported as a [`ParserPanic`](crate::resilience::ParserPanic),
+/// after which the tool is poisoned and the rest of the output is not parsed
+/// (see [`resilience`]).
+fn parse(
+    tool: &mut dyn AnyTool,
+    poisoned: &mut bool,
+    stripper: Option<&mut AnsiStripper>,
+    replacer: Option<&mut Utf8Replacer>,
+    buf: &[u8],
+) -> Vec<Box<dyn DynCiMessage>> {
+    let name = tool.name();
+    let input = match stripper {
+        Some(filter) => filter.strip(buf),
+        None => buf,
+    };
+    let chunk = match replacer {
+        Some(filter) => filter.replace(input),
+        None => input,
+    };
+    resilience::guard(name, poisoned, || tool.parse_dyn(chunk))
+}
+
+/// Read a file of the tool's output, and parse it to its end.
+///
+/// ANSI escape sequences are removed first if `strip` is set, and invalid
+/// UTF-8 replaced if `lossy` is set, as by the filters of a [`Pipeline`].
+///
+/// # Returns
+///
+/// The messages parsed, and the number of invalid UTF-8 sequences replaced.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+fn parse_file(
+    path: &Path,
+    mut tool: Box<dyn AnyTool>,
+    strip: bool,
+    lossy: bool,
+) -> io::Result<(Vec<Box<dyn DynCiMessage>>, usize)> {
+    let data = fs::read(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("Failed to read {}: {err}", path.display()),
+        )
+    })?;
+    let name = tool.name();
+    let mut poisoned = false;
+    let mut stripper = strip.then(AnsiStripper::default);
+    let mut replacer = lossy.then(Utf8Replacer::default);
+    let mut messages = parse(
+        &mut *tool,
+        &mut poisoned,
+        stripper.as_mut(),
+        replacer.as_mut(),
+        &data,
+    );
+    let mut invalid = 0;
+    if let Some(filter) = &mut replacer {
+        let rest = filter.finish();
+        messages.extend(resilience::guard(name, &mut poisoned, || {
+            tool.parse_dyn(rest)
+        }));
+        invalid = filter.replaced();
+    }
+    messages.extend(resilience::guard(name, &mut poisoned, || tool.finish_dyn()));
+    Ok((messages, invalid))
+}
+
+/// Read chunks from a reader and send them down a channel.
+///
+/// Reading stops at the end of input, on the first error (which is sent down
+/// the channel), or once the receiver has been dropped.
+pub(crate) fn read_chunks(mut reader: impl Read, tx: &SyncSender<io::Result<Vec<u8>>>) {
+    loop {
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+        let result = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                chunk.truncate(n);
+                Ok(chunk)
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => Err(err),
+        };
+        let is_err = result.is_err();
+        if tx.send(result).is_err() || is_err {
+            break;
+        }
+    }
+}
+
+impl fmt::Debug for Pipeline<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("tool", &self.tool.name())
+            .field("targets", &self.sink.targets)
+            .field("errors", &self.sink.errors)
+            .field("warnings", &self.sink.warnings)
+            .field("failed_tests", &self.sink.failed_tests)
+            .field("incomplete", &self.sink.incomplete)
+            .field("strip_ansi", &self.stripper.is_some())
+            .field("lossy_utf8", &self.replacer.is_some())
+            .field("buildkite_annotations", &self.sink.annotated.is_some())
+            .field("flush_interval", &self.sink.flush_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{cell::RefCell, time::Duration};
+    use std::{fs, io, rc::Rc};
+
+    use pretty_assertions::assert_eq;
+
+    use super::{DebugMessages, Pipeline, Target, annotation_body};
+    use crate::{
+        capture::TestGrouping,
+        ci::PlatformKind,
+        ci_message::Severity,
+        history::{History, RunSummary},
+        metadata::RunMetadata,
+        report::ReportArtifact,
+        sizes::SizeStats,
+        timeout::IdleTimeout,
+        tool::{CargoCheck, CargoLibtest},
+    };
+
+    /// Writer which records the number of bytes written at each flush.
+    #[derive(Clone, Default)]
+    struct FlushRecorder {
+        /// Total number of bytes written.
+        written: Rc<RefCell<usize>>,
+        /// Number of bytes written at the time of each flush.
+        flushes: Rc<RefCell<Vec<usize>>>,
+    }
+
+    impl io::Write for FlushRecorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut written = self.written.borrow_mut();
+            *written = written.saturating_add(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes.borrow_mut().push(*self.written.borrow());
+            Ok(())
+        }
+    }
+
+    const INPUT: &str = concat!(
+        r#"{"type":"test","event":"started","name":"a"}"#,
+        "\n",
+        r#"{"type":"test","event":"ok","name":"a"}"#,
+        "\n",
+    );
+
+    #[test]
+    fn single_target() {
+        let mut out = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut out)],
+        );
+        pipeline.process(INPUT.as_bytes()).expect("write failed");
+        drop(pipeline);
+
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&out),
+            @"
+        Test: a
+        notice:  Test Passed: a
+        "
+        );
+    }
+
+    #[test]
+    fn multiple_targets() {
+        let mut github = Vec::new();
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut github),
+                Target::new(PlatformKind::Plain, &mut plain),
+            ],
+        );
+
+        // Split the input across chunks to ensure both targets see all messages
+        let (head, tail) = INPUT.split_at(10);
+        pipeline.process(head.as_bytes()).expect("write failed");
+        pipeline.process(tail.as_bytes()).expect("write failed");
+        drop(pipeline);
+
+        assert_eq!(
+            String::from_utf8_lossy(&plain),
+            "Test: a\nnotice:  Test Passed: a\n"
+        );
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&github),
+            @"
+        ::group::Test: a
+        ::notice title=Test Passed%3A a::
+        ::endgroup::
+        "
+        );
+    }
+
+    #[test]
+    fn debug_messages() {
+        let mut output = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut output)
+                    .with_test_grouping(TestGrouping::Passing)
+                    .with_debug_messages(DebugMessages::Omit),
+            ],
+        );
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"a"}"#,
+            "\n",
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&output), @"
+        ::group::Test: a
+        ::notice title=Test Passed%3A a::
+        ::endgroup::
+        1 debug message(s) omitted; re-run with debug logging enabled to show them
+        ");
+    }
+
+    #[test]
+    fn debug_messages_downgraded() {
+        let mut output = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut output)
+                    .with_test_grouping(TestGrouping::Passing)
+                    .with_debug_messages(DebugMessages::Downgrade),
+            ],
+        );
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"a"}"#,
+            "\n",
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&output), @"
+        Debug: Test Started: a
+        ::group::Test: a
+        ::notice title=Test Passed%3A a::
+        ::endgroup::
+        ");
+    }
+
+    #[test]
+    fn codebuild_phase() {
+        let mut codebuild = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::CodeBuild, &mut codebuild)],
+        );
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ignored","name":"a","message":"slow"}"#,
+            "\n",
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&codebuild), @"
+        [TEST] Test: a
+        [TEST] Test Ignored: a - slow
+        [TEST] Skipped tests
+        [TEST] Count  Reason
+        [TEST]     1  slow
+        [TEST] 1 of 1 test(s) skipped (100%)
+        ");
+    }
+
+    #[test]
+    fn metadata() {
+        let mut github = Vec::new();
+        let mut plain = Vec::new();
+        let metadata = RunMetadata {
+            timestamp: "2026-10-14T12:15:30Z".to_owned(),
+            os: "linux".to_owned(),
+            arch: "x86_64".to_owned(),
+            version: "1.2.3".to_owned(),
+            ..RunMetadata::new("cargo-libtest")
+                .with_platforms([PlatformKind::GitHub, PlatformKind::Plain])
+        };
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut github),
+                Target::new(PlatformKind::Plain, &mut plain),
+            ],
+        )
+        .with_metadata(metadata)
+        .with_junit_report();
+        pipeline
+            .process(concat!(r#"{"type":"test","event":"ok","name":"a"}"#, "\n").as_bytes())
+            .expect("write failed");
+        pipeline.finish().expect("write failed");
+
+        let mut xml = Vec::new();
+        pipeline
+            .junit_report()
+            .expect("report enabled")
+            .write_xml(&mut xml)
+            .expect("write failed");
+        assert!(
+            String::from_utf8_lossy(&xml)
+                .contains(r#"<property name="timestamp" value="2026-10-14T12:15:30Z"/>"#)
+        );
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&github), @"
+        ::group::Run Metadata
+        tool: cargo-libtest
+        cifmt-version: 1.2.3
+        platform: github, plain
+        os: linux
+        arch: x86_64
+        timestamp: 2026-10-14T12:15:30Z
+        ::endgroup::
+        ::notice title=Test Passed%3A a::
+        ::endgroup::
+        ");
+        insta::assert_snapshot!(String::from_utf8_lossy(&plain), @"
+        Run Metadata
+        tool: cargo-libtest
+        cifmt-version: 1.2.3
+        platform: github, plain
+        os: linux
+        arch: x86_64
+        timestamp: 2026-10-14T12:15:30Z
+        notice:  Test Passed: a
+        ");
+    }
+
+    #[test]
+    fn expand_failures() {
+        let mut passing = Vec::new();
+        let mut all = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut passing)
+                    .with_test_grouping(TestGrouping::Passing),
+                Target::new(PlatformKind::GitHub, &mut all),
+            ],
+        );
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"b"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"a","stdout":"setting up a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"b","stdout":"panicked in b"}"#,
+            "\n",
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&passing), @"
+        ::debug::Test Started: a
+        ::debug::Test Started: b
+        ::group::Test: a
+        setting up a
+        ::notice title=Test Passed%3A a::
+        ::endgroup::
+        panicked in b
+        ::error title=Test Failed%3A b::
+        ");
+        insta::assert_snapshot!(String::from_utf8_lossy(&all), @"
+        ::group::Test: a
+        ::group::Test: b
+        setting up a
+        ::notice title=Test Passed%3A a::
+        ::endgroup::
+        panicked in b
+        ::endgroup::
+        ::error title=Test Failed%3A b::
+        ");
+    }
+
+    #[test]
+    fn counters() {
+        let input = concat!(
+            r#"{"type":"test","event":"ok","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"b"}"#,
+            "\n",
+            r#"{"type":"test","event":"timeout","name":"c"}"#,
+            "\n",
+            r#"{"type":"suite","event":"failed","passed":1,"failed":2,"ignored":0,"measured":0,"filtered_out":0}"#,
+            "\n",
+        );
+
+        let mut pipeline = Pipeline::new(Box::new(CargoLibtest::default()), vec![]);
+        assert_eq!(pipeline.max_severity(), None);
+
+        pipeline.process(input.as_bytes()).expect("write failed");
+        assert_eq!(pipeline.errors(), 3);
+        assert_eq!(pipeline.warnings(), 0);
+        assert_eq!(pipeline.failed_tests(), 2);
+        assert_eq!(pipeline.max_severity(), Some(Severity::Error));
+    }
+
+    #[test]
+    fn counters_warnings() {
+        let input = concat!(
+            r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+            r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+            r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+            r#""message":{"$message_type":"diagnostic","message":"unused","code":null,"#,
+            r#""level":"warning","spans":[],"children":[],"rendered":null}}"#,
+            "\n",
+            r#"{"reason":"build-finished","success":true}"#,
+            "\n",
+        );
+
+        let mut pipeline = Pipeline::new(Box::new(CargoCheck::default()), vec![]);
+        pipeline.process(input.as_bytes()).expect("write failed");
+        assert_eq!(pipeline.errors(), 0);
+        assert_eq!(pipeline.warnings(), 1);
+        assert_eq!(pipeline.failed_tests(), 0);
+        assert_eq!(pipeline.max_severity(), Some(Severity::Warning));
+    }
+
+    #[test]
+    fn lint_summary() {
+        let diagnostic = |level: &str, message: &str, code: &str| {
+            concat!(
+                r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+                r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+                r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+                r#""message":{"$message_type":"diagnostic","message":"{message}","#,
+                r#""code":{"code":"{code}","explanation":null},"#,
+                r#""level":"{level}","spans":[],"children":[],"rendered":null}}"#,
+                "\n",
+            )
+            .replace("{level}", level)
+            .replace("{message}", message)
+            .replace("{code}", code)
+        };
+        let input = [
+            diagnostic("warning", "unused a", "unused_variables"),
+            diagnostic("warning", "needless borrow", "clippy::needless_borrow"),
+            diagnostic("error", "mismatched types", "E0308"),
+            diagnostic("warning", "unused b", "unused_variables"),
+        ]
+        .concat();
+
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        assert_eq!(pipeline.lint_stats().count("unused_variables"), 2);
+        assert_eq!(pipeline.lint_stats().count("E0308"), 0);
+
+        // The most common lints are only listed in the job summary.
+        let summary = pipeline.job_summary().to_string();
+        let lints = summary.find("#### Top lint offenders").unwrap_or_default();
+        insta::assert_snapshot!(summary.get(lints..).unwrap_or_default(), @"
+        #### Top lint offenders
+
+        | Code | Count |
+        | :--- | ----: |
+        | `unused_variables` | 2 |
+        | `clippy::needless_borrow` | 1 |
+        ");
+        drop(pipeline);
+        assert!(!String::from_utf8_lossy(&plain).contains("Top lint offenders"));
+    }
+
+    #[test]
+    fn crate_groups() {
+        let warning = |package: &str, name: &str| {
+            concat!(
+                r#"{"reason":"compiler-message","package_id":"path+file:///w/{p}#0.1.0","#,
+                r#""manifest_path":"/w/{p}/Cargo.toml","#,
+                r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"{p}","src_path":"/w/{p}/src/lib.rs","#,
+                r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+                r#""message":{"$message_type":"diagnostic","message":"unused `{n}`","code":null,"#,
+                r#""level":"warning","spans":[],"children":[],"rendered":null}}"#,
+                "\n",
+            )
+            .replace("{p}", package)
+            .replace("{n}", name)
+        };
+        let input = [
+            warning("core", "a"),
+            warning("cli", "b"),
+            concat!(
+                r#"{"reason":"compiler-artifact","package_id":"path+file:///w/cli#0.1.0","#,
+                r#""manifest_path":"/w/cli/Cargo.toml","#,
+                r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"cli","src_path":"/w/cli/src/lib.rs","#,
+                r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+                r#""profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"#,
+                r#""features":[],"filenames":[],"executable":null,"fresh":false}"#,
+                "\n",
+            )
+            .to_owned(),
+            warning("core", "c"),
+            warning("core", "d"),
+        ]
+        .concat();
+
+        let mut github = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::GitHub, &mut github)],
+        )
+        .with_crate_groups();
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        assert_eq!(pipeline.package_stats().get("core").warnings, 3);
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&github), @"
+        ::group::Crate: core
+        ::warning title=warning::unused `a`
+
+        ::endgroup::
+        ::group::Crate: cli
+        ::warning title=warning::unused `b`
+
+        ::debug::Built artifact: cli (lib)
+        ::endgroup::
+        ::group::Crate: core
+        ::warning title=warning::unused `c`
+
+        ::warning title=warning::unused `d`
+
+        ::endgroup::
+        ::warning title=Incomplete Build::Cargo's output ended without reporting the result of the build
+        ::group::Issues by crate
+        Errors  Warnings  Crate
+             0         3  core
+             0         1  cli
+        ::endgroup::
+        ");
+    }
+
+    #[test]
+    fn finish() {
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"a"}"#,
+            "\n",
+        );
+
+        let mut azure = Vec::new();
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::AzureDevOps, &mut azure),
+                Target::new(PlatformKind::Plain, &mut plain),
+            ],
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        drop(pipeline);
+
+        assert_eq!(
+            String::from_utf8_lossy(&plain),
+            "Test: a\nerror:   Test Failed: a\n"
+        );
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&azure),
+            @"
+        ##[group]Test: a
+        ##[endgroup]
+        ##vso[task.logissue type=error]Test Failed: a
+        ##vso[task.complete result=Failed;]1 error(s), 0 warning(s)
+        "
+        );
+    }
+
+    /// Output of `cargo check` with the given number of warnings, followed by
+    /// the end of a successful build.
+    ///
+    /// The warnings are distinct, as duplicates are only annotated once.
+    fn distinct_warnings(count: u32) -> String {
+        (1..=count)
+            .map(|i| {
+                concat!(
+                    r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+                    r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+                    r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+                    r#""message":{"$message_type":"diagnostic","message":"unused {i}","code":null,"#,
+                    r#""level":"warning","spans":[],"children":[],"rendered":null}}"#,
+                    "\n",
+                )
+                .replace("{i}", &i.to_string())
+            })
+            .chain([r#"{"reason":"build-finished","success":true}"#.to_owned()])
+            .collect()
+    }
+
+    #[test]
+    fn annotation_limit() {
+        let warnings = distinct_warnings(11);
+        let mut github = Vec::new();
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut github),
+                Target::new(PlatformKind::Plain, &mut plain),
+            ],
+        );
+        pipeline.process(warnings.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        assert_eq!(pipeline.warnings(), 11);
+        drop(pipeline);
+
+        let output = String::from_utf8_lossy(&github);
+        assert_eq!(output.matches("::warning title=warning::").count(), 9);
+        let omitted = output.find("Warning: ").unwrap_or_default();
+        insta::assert_snapshot!(
+            output.get(omitted..).unwrap_or_default(),
+            @"
+        Warning: warning: unused 10
+
+        Warning: warning: unused 11
+
+        ::notice title=Build Complete::Build finished successfully with 0 error(s) and 11 warning(s)
+        ::warning title=Annotations Omitted::2 additional issue(s) not shown as annotations, see the log
+        "
+        );
+        assert!(!String::from_utf8_lossy(&plain).contains("Annotations Omitted"));
+    }
+
+    #[test]
+    fn explanations() {
+        let mut github = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut github)
+                    .with_annotation_limit(Some(12))
+                    .with_explanations(true),
+            ],
+        )
+        .with_crate_groups();
+        pipeline
+            .process(distinct_warnings(2).as_bytes())
+            .expect("write failed");
+        pipeline.finish().expect("finish failed");
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&github), @"
+        Message 1: Diagnostic
+          Severity: warning
+          Package: p
+          Fingerprint: ba472c790a8961b3
+          Filters: none
+          Output for github:
+            ::warning title=warning::unused 1
+
+        Message 2: Diagnostic
+          Severity: warning
+          Package: p
+          Fingerprint: ba472c790a8961b3
+          Filters: none
+          Output for github:
+            ::warning title=warning::unused 2
+
+        Message 3: BuildSummary
+          Severity: notice
+          Filters: annotation(s) beyond the limit written as plain text
+          Output for github:
+            Notice: Build Complete: Build finished successfully with 0 error(s) and 2 warning(s)
+        ");
+    }
+
+    #[test]
+    fn raw_messages() {
+        let mut plain = Vec::new();
+        let mut raw = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        )
+        .with_raw_messages(&mut raw);
+        pipeline
+            .process(distinct_warnings(1).as_bytes())
+            .expect("write failed");
+        pipeline.finish().expect("finish failed");
+        drop(pipeline);
+
+        assert!(String::from_utf8_lossy(&plain).contains("unused 1"));
+        insta::assert_snapshot!(String::from_utf8_lossy(&raw), @r#"
+        {"number":1,"tool":"cargo-check","kind":"Diagnostic","severity":"warning","code":null,"package":"p","file":null,"message":"unused 1","fingerprint":"ba472c790a8961b3","sort_key":"/p/src/lib.rs","test_failure":false,"incomplete":false,"build_result":null,"text":"warning: unused 1\n"}
+        {"number":2,"tool":"cargo-check","kind":"BuildFinished","severity":"notice","code":null,"package":null,"file":null,"message":null,"fingerprint":null,"sort_key":null,"test_failure":false,"incomplete":false,"build_result":true,"text":"Build finished successfully"}
+        "#);
+    }
+
+    #[test]
+    fn run_files() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let shard = |name: &str, count: u32| {
+            let path = dir.path().join(name);
+            let input = distinct_warnings(count).replace("unused", name);
+            fs::write(&path, input).expect("failed to write shard");
+            path
+        };
+        let paths = [shard("c", 1), shard("a", 2), shard("b", 1)];
+
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        );
+        pipeline
+            .run_files(&paths, || Box::new(CargoCheck::default()))
+            .expect("run failed");
+        pipeline.finish().expect("finish failed");
+        assert_eq!(pipeline.warnings(), 4);
+        assert!(!pipeline.is_incomplete());
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&plain), @"
+        warning: c 1
+
+        warning: a 1
+
+        warning: a 2
+
+        warning: b 1
+
+        notice:  Build Complete: Build finished successfully with 0 error(s) and 4 warning(s)
+        ");
+    }
+
+    #[test]
+    fn run_files_missing() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let path = dir.path().join("missing.json");
+
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        );
+        let error = pipeline
+            .run_files(&[&path], || Box::new(CargoCheck::default()))
+            .expect_err("the file is missing");
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().contains("missing.json"));
+    }
+
+    #[test]
+    fn report_artifact() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let summary_path = dir.path().join("summary.md");
+        let report = ReportArtifact::new(dir.path().join("reports"));
+        let path = report.path();
+
+        let mut github = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::GitHub, &mut github)],
+        )
+        .with_step_summary(&summary_path)
+        .with_report_artifact(report);
+        pipeline
+            .process(distinct_warnings(11).as_bytes())
+            .expect("write failed");
+        pipeline.finish().expect("finish failed");
+        drop(pipeline);
+
+        let output = String::from_utf8_lossy(&github);
+        assert!(output.contains(&format!("Full report: {}\n", path.display())));
+        let content = fs::read_to_string(&path).expect("read failed");
+        let omitted = content.find("#### Annotations").unwrap_or_default();
+        insta::assert_snapshot!(content.get(omitted..).unwrap_or_default(), @"
+        #### Annotations Not Shown
+
+        | Severity | Location | Title | Message |
+        | -------- | -------- | ----- | ------- |
+        | warning |  | warning | unused 10 |
+        | warning |  | warning | unused 11 |
+        ");
+        let summary = fs::read_to_string(&summary_path).expect("read failed");
+        assert!(summary.ends_with(&format!(
+            "> The full report is available in `{}`.\n",
+            path.display()
+        )));
+    }
+
+    #[test]
+    fn finish_truncated() {
+        let input = concat!(
+            r#"{"type":"suite","event":"started","test_count":1}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","na"#,
+        );
+
+        let mut azure = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::AzureDevOps, &mut azure)],
+        );
+        pipeline.process(input.as_bytes()).expect("write failed");
+        assert!(!pipeline.is_incomplete());
+        pipeline.finish().expect("write failed");
+        assert!(pipeline.is_incomplete());
+        assert_eq!(pipeline.warnings(), 2);
+        drop(pipeline);
+
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&azure),
+            @"
+        notice:  Test Suite Started: Running 1 tests
+        ##[group]Test: a
+        ##vso[task.logissue type=warning]Output ended within line 3 (at byte offset 95), discarding 31 bytes of an incomplete message: EOF while parsing a string at line 1 column 31
+
+        ##[endgroup]
+        ##vso[task.logissue type=warning]Incomplete Test Suite: Test suite ended without a result; 1 test(s) did not finish: a
+        ##vso[task.complete result=SucceededWithIssues;]0 error(s), 2 warning(s); output incomplete
+        "
+        );
+    }
+
+    #[test]
+    fn lossy_utf8() {
+        let input: &[u8] = b"{\"type\":\"test\",\"event\":\"failed\",\"name\":\"a\",\"stdout\":\"\xFF\xE2\x86\"}\n";
+
+        // Without replacement, the invalid line is reported as an error by the
+        // tool and skipped.
+        let mut plain = Vec::new();
+        let mut strict = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        );
+        strict.process(input).expect("write failed");
+        strict.finish().expect("write failed");
+        drop(strict);
+        assert_eq!(String::from_utf8_lossy(&plain), "");
+
+        // Split the input within the invalid sequence to ensure it is
+        // replaced only once.
+        let mut lossy = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut lossy)],
+        )
+        .with_lossy_utf8();
+        let (head, tail) = input.split_at(55);
+        pipeline.process(head).expect("write failed");
+        pipeline.process(tail).expect("write failed");
+        pipeline.finish().expect("write failed");
+        assert_eq!(pipeline.failed_tests(), 1);
+        drop(pipeline);
+
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&lossy),
+            @"
+        ��
+        error:   Test Failed: a
+        notice:  Invalid UTF-8: Replaced 2 invalid UTF-8 sequence(s) in the input with U+FFFD
+        "
+        );
+    }
+
+    #[test]
+    fn stable_ordering() {
+        let input = concat!(
+            r#"{"type":"suite","event":"started","test_count":3}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"c"}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"b"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"c"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"b"}"#,
+            "\n",
+            r#"{"type":"suite","event":"failed","passed":2,"failed":1,"ignored":0,"measured":0,"filtered_out":0}"#,
+            "\n",
+        );
+
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        )
+        .with_stable_ordering();
+        // Messages are held back until the suite finishes.
+        let (head, tail) = input.split_at(input.rfind(r#"{"type":"suite""#).unwrap_or(0));
+        pipeline.process(head.as_bytes()).expect("write failed");
+        assert_eq!(pipeline.failed_tests(), 0);
+        pipeline.process(tail.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        assert_eq!(pipeline.failed_tests(), 1);
+        drop(pipeline);
+
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&plain),
+            @"
+        notice:  Test Suite Started: Running 3 tests
+        Test: a
+        notice:  Test Passed: a
+        Test: b
+        notice:  Test Passed: b
+        Test: c
+        error:   Test Failed: c
+        error:   Test Suite Failed: 1 failed, 2 passed, 0 ignored, 0 measured, 0 filtered out
+        "
+        );
+    }
+
+    #[test]
+    fn buildkite() {
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"b"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"b","stdout":"boom"}"#,
+            "\n",
+        );
+
+        let mut buildkite = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Buildkite, &mut buildkite)],
+        )
+        .with_buildkite_annotations();
+        pipeline.process(input.as_bytes()).expect("write failed");
+
+        let annotated = pipeline.sink.annotated.clone().unwrap_or_default();
+        insta::assert_snapshot!(
+            annotation_body(pipeline.tool_name(), &annotated, pipeline.errors()),
+            @"
+        **cargo-libtest** reported 1 error(s)
+
+        ```text
+        boom
+        error:   Test Failed: b
+        ```
+        "
+        );
+        drop(pipeline);
+
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&buildkite),
+            @"
+        --- Test: a
+        --- Test: b
+        notice:  Test Passed: a
+        boom
+        ^^^ +++
+        error:   Test Failed: b
+        "
+        );
+    }
+
+    #[test]
+    fn buildkite_snippet() {
+        let input = concat!(
+            r#"{"reason":"compiler-message","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+            r#""target":{"kind":["lib"],"crate_types":["lib"],"name":"p","src_path":"/p/src/lib.rs","#,
+            r#""edition":"2024","doc":true,"doctest":true,"test":true},"#,
+            r#""message":{"$message_type":"diagnostic","message":"mismatched types","#,
+            r#""code":{"code":"E0308","explanation":null},"level":"error","spans":[{"#,
+            r#""file_name":"src/lib.rs","byte_start":30,"byte_end":33,"line_start":2,"line_end":2,"#,
+            r#""column_start":18,"column_end":21,"is_primary":true,"#,
+            r#""text":[{"text":"    let x: u8 = \"a\";","highlight_start":18,"highlight_end":21}],"#,
+            r#""label":"expected `u8`, found `&str`","suggested_replacement":null,"#,
+            r#""suggestion_applicability":null,"expansion":null}],"children":[],"rendered":null}}"#,
+            "\n",
+        );
+
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::Buildkite, Vec::new())],
+        )
+        .with_buildkite_annotations();
+        pipeline.process(input.as_bytes()).expect("write failed");
+
+        let annotated = pipeline.sink.annotated.clone().unwrap_or_default();
+        insta::assert_snapshot!(
+            annotation_body(pipeline.tool_name(), &annotated, pipeline.errors()),
+            @r#"
+        **cargo-check** reported 1 error(s)
+
+        ```text
+        src/lib.rs:2:18: error:   E0308: mismatched types
+         --> src/lib.rs:2:18
+          |
+        2 |     let x: u8 = "a";
+          |                  ^^^ expected `u8`, found `&str`
+        ```
+        "#
+        );
+    }
+
+    #[test]
+    fn buildkite_annotation_omitted() {
+        let annotated = ["error: a".to_owned(), "error: ``` b".to_owned()];
+        insta::assert_snapshot!(
+            annotation_body("cargo-check", &annotated, 5),
+            @"
+        **cargo-check** reported 5 error(s)
+
+        ```text
+        error: a
+        ```
+
+        ````text
+        error: ``` b
+        ````
+
+        3 more error(s) omitted.
+        "
+        );
+    }
+
+    #[test]
+    fn timeline() {
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"a","exec_time":0.5}"#,
+            "\n",
+        );
+
+        let mut pipeline = Pipeline::new(Box::new(CargoLibtest::default()), vec![]);
+        pipeline.process(input.as_bytes()).expect("write failed");
+
+        let spans = pipeline.timeline().spans();
+        assert_eq!(spans.len(), 1);
+        let span = spans.first().expect("span recorded");
+        assert_eq!(span.name, "a");
+        assert_eq!(span.category, "test");
+        assert_eq!(span.duration, core::time::Duration::from_millis(500));
+    }
+
+    #[test]
+    fn junit_report() {
+        let input = concat!(
+            r#"{"type":"test","event":"ok","name":"tests::a","exec_time":0.5}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::b","stdout":"0123456789abcdef\n"}"#,
+            "\n",
+            r#"{"type":"test","event":"ignored","name":"c"}"#,
+            "\n",
+        );
+
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, Vec::new()).with_output_limit(Some(4))],
+        )
+        .with_junit_report();
+        pipeline.process(input.as_bytes()).expect("write failed");
+
+        // The report holds the full output, regardless of truncation.
+        let mut xml = Vec::new();
+        pipeline
+            .junit_report()
+            .expect("report enabled")
+            .write_xml(&mut xml)
+            .expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&xml), @r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuites tests="3" failures="1" skipped="1" time="0.500">
+          <testsuite name="cargo-libtest" tests="3" failures="1" skipped="1" time="0.500">
+            <testcase classname="tests" name="a" time="0.500"/>
+            <testcase classname="tests" name="b">
+              <failure message="Test failed">0123456789abcdef
+        </failure>
+            </testcase>
+            <testcase classname="" name="c">
+              <skipped/>
+            </testcase>
+          </testsuite>
+        </testsuites>
+        "#);
+    }
+
+    #[test]
+    fn step_summary() {
+        let input = concat!(
+            r#"{"type":"suite","event":"started","test_count":2}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"tests::a","exec_time":0.5}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::b","stdout":"boom\n"}"#,
+            "\n",
+            r#"{"type":"suite","event":"failed","passed":1,"failed":1,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.5}"#,
+            "\n",
+        );
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let path = dir.path().join("summary.md");
+        fs::write(&path, "Previous step\n").expect("write failed");
+
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, Vec::new())],
+        )
+        .with_step_summary(&path);
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("finish failed");
+
+        let summary = fs::read_to_string(&path).expect("read failed");
+        insta::assert_snapshot!(summary, @"
+        Previous step
+        ### cargo-libtest
+
+        2 error(s) and 0 warning(s).
+
+        #### Tests
+
+        | Passed | Failed | Skipped | Total |
+        | -----: | -----: | ------: | ----: |
+        | 1 | 1 | 0 | 2 |
+
+        #### Failed tests
+
+        | Test | Reason |
+        | :--- | :----- |
+        | `tests::b` | boom |
+
+        #### Slowest tests
+
+        | Test | Duration |
+        | :--- | -------: |
+        | `tests::a` | 0.500s |
+
+        #### Test suites
+
+        | Suite | Passed | Failed | Ignored | Duration |
+        | :---- | -----: | -----: | ------: | -------: |
+        | Suite 1 | 1 | 1 | 0 | 0.500s |
+
+        <details>
+        <summary>Suite 1: 1 failed test(s)</summary>
+
+        **`tests::b`**
+
+        ```text
+        boom
+        ```
+
+        </details>
+        ");
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn notify_unreachable() {
+        use crate::notify::{Notifier, Webhook};
+
+        // Nothing listens on the discard port, so the request fails.
+        let webhook = Webhook::Slack {
+            url: "http://127.0.0.1:9/webhook".to_owned(),
+        };
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, Vec::new())],
+        )
+        .with_notifier(Notifier::new(webhook));
+        pipeline.process(INPUT.as_bytes()).expect("write failed");
+        pipeline.finish().expect("finish failed");
+    }
+
+    #[test]
+    fn history() {
+        let input = concat!(
+            r#"{"type":"test","event":"ok","name":"tests::a"}"#,
+            "\n",
+            r#"{"type":"test","event":"failed","name":"tests::b","stdout":"boom\n"}"#,
+            "\n",
+        );
+        let mut history = History::default();
+        history.record(RunSummary {
+            tests: 2,
+            failed_tests: vec!["tests::a".to_owned()],
+            ..RunSummary::new("cargo-libtest")
+        });
+
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, Vec::new())],
+        )
+        .with_history(history);
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("finish failed");
+
+        let runs = pipeline.history().expect("history kept").runs();
+        assert_eq!(runs.len(), 2);
+        let trend = pipeline.job_summary().trend.expect("a run is recorded");
+        assert_eq!(trend.current().tests, 2);
+        assert_eq!(trend.new_failures(), ["tests::b"]);
+        assert_eq!(trend.fixed_tests(), ["tests::a"]);
+    }
+
+    #[test]
+    fn binary_sizes() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let artifact = |name: &str, size: usize, test: bool| {
+            let path = dir.path().join(name);
+            fs::write(&path, vec![0; size]).expect("write failed");
+            concat!(
+                r#"{"reason":"compiler-artifact","package_id":"p","manifest_path":"/p/Cargo.toml","#,
+                r#""target":{"kind":["bin"],"crate_types":["bin"],"name":"{name}","src_path":"/p/src/main.rs","#,
+                r#""edition":"2024","doc":true,"doctest":false,"test":true},"#,
+                r#""profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"#,
+                r#""overflow_checks":true,"test":{test}},"features":[],"#,
+                r#""filenames":["{path}"],"executable":"{path}","fresh":false}"#,
+                "\n",
+            )
+            .replace("{name}", name)
+            .replace("{test}", &test.to_string())
+            .replace("{path}", &path.display().to_string())
+        };
+        let input = [
+            artifact("app", 1200, false),
+            artifact("app-tests", 5000, true),
+            artifact("cli", 300, false),
+            "{\"reason\":\"build-finished\",\"success\":true}\n".to_owned(),
+        ]
+        .concat();
+        let mut baseline = SizeStats::default();
+        baseline.record("app", 1000);
+        baseline.record("cli", 400);
+
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoCheck::default()),
+            vec![Target::new(PlatformKind::Plain, &mut plain)],
+        )
+        .with_size_baseline(baseline, 10);
+        pipeline.process(input.as_bytes()).expect("write failed");
+        pipeline.finish().expect("write failed");
+        assert_eq!(
+            pipeline.binary_sizes().and_then(|sizes| sizes.get("app")),
+            Some(1200)
+        );
+        assert_eq!(pipeline.warnings(), 1);
+        drop(pipeline);
+
+        let output = String::from_utf8_lossy(&plain);
+        let summary = output.find("warning: Binary").unwrap_or_default();
+        insta::assert_snapshot!(output.get(summary..).unwrap_or_default(), @"
+        warning: Binary Size Regression: app grew by 200 B (+20%) from 1000 B to 1200 B, exceeding the threshold of 10%
+        Binary sizes
+        Size (B)         Change  Binary
+            1200  +200 B (+20%)  app
+             300  -100 B (-25%)  cli
+        notice:  Build Complete: Build finished successfully with 0 error(s) and 1 warning(s)
+        ");
+    }
+
+    #[test]
+    fn flush_per_batch() {
+        let recorder = FlushRecorder::default();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, recorder.clone())],
+        );
+
+        pipeline.process(INPUT.as_bytes()).expect("write failed");
+        pipeline.process(INPUT.as_bytes()).expect("write failed");
+
+        // Each batch is written in a single flush.
+        assert_eq!(*recorder.flushes.borrow(), [32, 64]);
+    }
+
+    #[test]
+    fn flush_on_group_boundary() {
+        let recorder = FlushRecorder::default();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::GitHub, recorder.clone())],
+        );
+
+        let input = format!(
+            "{INPUT}{}",
+            r#"{"type":"test","event":"started","name":"b"}"#
+        );
+        pipeline
+            .process(format!("{input}\n").as_bytes())
+            .expect("write failed");
+
+        // The group for `a` is flushed as soon as it is closed, and the
+        // remainder at the end of the batch.
+        assert_eq!(*recorder.flushes.borrow(), [64, 81]);
+    }
+
+    #[test]
+    fn flush_on_interval() {
+        let recorder = FlushRecorder::default();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, recorder.clone())],
+        )
+        .with_flush_interval(Duration::ZERO);
+
+        pipeline.process(INPUT.as_bytes()).expect("write failed");
+
+        // With a zero interval, every message is flushed.
+        assert_eq!(*recorder.flushes.borrow(), [8, 32, 32]);
+    }
+
+    #[test]
+    fn run() {
+        let input = INPUT.repeat(1000);
+        let mut expected = Vec::new();
+        Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::GitHub, &mut expected)],
+        )
+        .process(input.as_bytes())
+        .expect("write failed");
+
+        let mut out = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::GitHub, &mut out)],
+        );
+        pipeline.run(input.as_bytes()).expect("run failed");
+        assert_eq!(pipeline.failed_tests(), 0);
+        drop(pipeline);
+
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            String::from_utf8_lossy(&expected)
+        );
+    }
+
+    #[test]
+    fn ansi_stripping() {
+        let input = concat!(
+            "\x1b[32m",
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\x1b[0m\n\x1b[1m",
+            r#"{"type":"test","event":"ok","name":"a"}"#,
+            "\x1b[0m\n",
+        );
+
+        let mut raw = Vec::new();
+        Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut raw)],
+        )
+        .process(input.as_bytes())
+        .expect("write failed");
+        assert_eq!(String::from_utf8_lossy(&raw), "");
+
+        let mut stripped = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut stripped)],
+        )
+        .with_ansi_stripping();
+        pipeline.run(input.as_bytes()).expect("run failed");
+        drop(pipeline);
+        assert_eq!(
+            String::from_utf8_lossy(&stripped),
+            "Test: a\nnotice:  Test Passed: a\n"
+        );
+    }
+
+    #[test]
+    fn run_read_error() {
+        /// Reader which fails after yielding the test input.
+        struct FailingReader(bool);
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 {
+                    return Err(io::Error::other("read failed"));
+                }
+                self.0 = true;
+                io::Read::read(&mut INPUT.as_bytes(), buf)
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, &mut out)],
+        );
+        let err = pipeline
+            .run(FailingReader(false))
+            .expect_err("read error not propagated");
+        drop(pipeline);
+
+        insta::assert_snapshot!(err, @"read failed");
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "Test: a\nnotice:  Test Passed: a\n"
+        );
+    }
+
+    #[test]
+    fn run_write_error() {
+        /// Writer which fails as if its pipe had been closed.
+        struct BrokenPipe;
+
+        impl io::Write for BrokenPipe {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // Enough input to fill both channels, so that the reader and parser
+        // would block if the writer stopped receiving without disconnecting.
+        let input = INPUT.repeat(64 * 1024);
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::Plain, BrokenPipe)],
+        );
+        let err = pipeline
+            .run(input.as_bytes())
+            .expect_err("write error not propagated");
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn run_idle_timeout() {
+        /// Reader which times out after yielding the start of a suite.
+        struct HangingReader(bool);
+
+        impl io::Read for HangingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        IdleTimeout {
+                            timeout: Duration::from_secs(30),
+                        },
+                    ));
+                }
+                self.0 = true;
+                let input = concat!(
+                    r#"{"type":"suite","event":"started","test_count":1}"#,
+                    "\n",
+                    r#"{"type":"test","event":"started","name":"a"}"#,
+                    "\n",
+                );
+                io::Read::read(&mut input.as_bytes(), buf)
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![Target::new(PlatformKind::GitHub, &mut out)],
+        );
+        pipeline.run(HangingReader(false)).expect("run failed");
+        pipeline.finish().expect("finish failed");
+        assert!(pipeline.timed_out());
+        assert!(pipeline.is_incomplete());
+        assert_eq!(pipeline.errors(), 1);
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&out), @"
+        ::notice title=Test Suite Started::Running 1 tests
+        ::group::Test: a
+        ::error title=Timeout::No output received for 30s; the tool may have hung, and its output is incomplete
+        ::endgroup::
+        ::warning title=Incomplete Test Suite::Test suite ended without a result; 1 test(s) did not finish: a
+        ");
+    }
+
+    #[test]
+    fn truncated_output() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let stdout = format!("{}{}", "a".repeat(100), "b".repeat(100));
+        let input = format!(
+            "{}\n",
+            serde_json::json!({
+                "type": "test",
+                "event": "failed",
+                "name": "tests::noisy",
+                "stdout": stdout,
+            })
+        );
+
+        let mut github = Vec::new();
+        let mut plain = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(CargoLibtest::default()),
+            vec![
+                Target::new(PlatformKind::GitHub, &mut github).with_output_limit(Some(10)),
+                Target::new(PlatformKind::Plain, &mut plain),
+            ],
+        )
+        .with_artifact_dir(dir.path());
+        pipeline.process(input.as_bytes()).expect("write failed");
+        drop(pipeline);
+
+        let artifact = dir.path().join("0000-tests__noisy.log");
+        assert_eq!(
+            std::fs::read_to_string(&artifact).expect("artifact not written"),
+            stdout
+        );
+        assert!(String::from_utf8_lossy(&plain).starts_with(&stdout));
+        insta::assert_snapshot!(
+            String::from_utf8_lossy(&github).replace(&dir.path().display().to_string(), "[DIR]"),
+            @"
+        aaaaa
+        [... 190 bytes omitted, full output in [DIR]/0000-tests__noisy.log ...]
+        bbbbb
+        ::endgroup::
+        ::error title=Test Failed%3A tests%3A%3Anoisy::
+        "
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Raw JSON records of the parsed messages, for debugging.
+//!
+//! A [`RawMessage`] describes a message as parsed, before any filter of a
+//! target applies, in a shape which is the same for all tools: its kind,
+//! severity and classification (see
+//! [`Classify`](crate::ci_message::Classify)), its fingerprint, and its
+//! formatting as plain text. A pipeline can write the record of each message
+//! as a line of JSON alongside the formatted output (see
+//! [`Pipeline::with_raw_messages`](crate::pipeline::Pipeline::with_raw_messages)),
+//! which shows what a renderer for a new platform receives, and can be
+//! attached to a report of a bug in a parser.
+//!
+//! For example, the record of a warning of rustc is:
+//!
+//! ```json
+//! {"number":1,"tool":"cargo-check","kind":"Diagnostic","severity":"warning","code":"unused_variables","package":"project","file":"src/lib.rs","message":"unused variable: `x`","fingerprint":"8ceeb276274fe423","sort_key":"src/lib.rs","test_failure":false,"incomplete":false,"build_result":null,"text":"WARNING: ..."}
+//! ```
+//!
+//! Every key is present in each record, with `null` for what does not apply
+//! to the message. Messages which are not written to the targets (e.g., the
+//! result of a build, which is summarized at the end) are recorded too.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{ci::PlatformKind, ci_message::DynCiMessage, fingerprint::Fingerprint};
+
+/// The record of a parsed message.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "the record is of a message, and would be ambiguous as a bare `Message`"
+)]
+pub struct RawMessage<'a> {
+    /// The number of the message among those parsed, counting from one.
+    pub number: usize,
+    /// The name of the tool which parsed the message.
+    pub tool: &'a str,
+    /// The kind of the message (e.g., `Diagnostic`).
+    pub kind: &'static str,
+    /// The severity of the message (e.g., `warning`).
+    pub severity: String,
+    /// The code of the issue reported by the message, if any.
+    pub code: Option<&'a str>,
+    /// The package to which the message belongs, if any.
+    pub package: Option<&'a str>,
+    /// The file of the issue reported by the message, if any.
+    pub file: Option<&'a str>,
+    /// The message of the diagnostic reported by the message, if any.
+    pub message: Option<&'a str>,
+    /// The fingerprint of the diagnostic reported by the message, if any.
+    pub fingerprint: Option<String>,
+    /// The key by which the message is ordered, if any.
+    pub sort_key: Option<&'a str>,
+    /// Whether the message reports a failed test.
+    pub test_failure: bool,
+    /// Whether the message reports that the tool's output is incomplete.
+    pub incomplete: bool,
+    /// Whether the build reported by the message succeeded, if it reports
+    /// the end of a build.
+    pub build_result: Option<bool>,
+    /// The message formatted as plain text.
+    pub text: String,
+}
+
+impl<'a> RawMessage<'a> {
+    /// Describe a parsed message.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The number of the message, counting from one.
+    /// * `tool` - The name of the tool which parsed the message.
+    /// * `message` - The message.
+    #[must_use]
+    #[inline]
+    pub fn new(number: usize, tool: &'a str, message: &'a dyn DynCiMessage) -> Self {
+        Self {
+            number,
+            tool,
+            kind: message.kind(),
+            severity: message.severity().to_string(),
+            code: message.code(),
+            package: message.package(),
+            file: message.file(),
+            message: message.message(),
+            fingerprint: Fingerprint::of(tool, message).map(|print| print.to_string()),
+            sort_key: message.sort_key(),
+            test_failure: message.is_test_failure(),
+            incomplete: message.is_incomplete(),
+            build_result: message.build_result(),
+            text: message.format_for(PlatformKind::Plain),
+        }
+    }
+
+    /// Write the record as a single line of JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `writer` fails.
+    #[inline]
+    pub fn write_json_line(&self, mut writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer(&mut writer, self)?;
+        writer.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::RawMessage;
+    use crate::tool::{CargoFmt, Tool as _};
+
+    #[test]
+    fn json_line() {
+        let mut tool = CargoFmt::default();
+        let mut messages = tool.parse(b"Diff in /w/src/lib.rs:2:\n-    a+b\n+    a + b\n");
+        messages.extend(tool.finish());
+        let mut bytes = Vec::new();
+        for (number, result) in (1..).zip(messages) {
+            let message = result.expect("valid message");
+            RawMessage::new(number, "cargo-fmt", &message)
+                .write_json_line(&mut bytes)
+                .expect("written");
+        }
+
+        let output = String::from_utf8(bytes).expect("valid UTF-8");
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid JSON"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        insta::assert_snapshot!(output, @r#"
+        {"number":1,"tool":"cargo-fmt","kind":"UnformattedFile","severity":"error","code":null,"package":null,"file":"/w/src/lib.rs","message":"File is not formatted","fingerprint":"7a55ffcbbdee6e11","sort_key":"/w/src/lib.rs","test_failure":false,"incomplete":false,"build_result":null,"text":"/w/src/lib.rs:2: error:   Unformatted File: File is not formatted (1 change(s)); run `cargo fmt` to format it\n/w/src/lib.rs\n@@ line 2 @@\n-    a+b\n+    a + b"}
+        {"number":2,"tool":"cargo-fmt","kind":"FmtSummary","severity":"notice","code":null,"package":null,"file":null,"message":null,"fingerprint":null,"sort_key":null,"test_failure":false,"incomplete":false,"build_result":null,"text":"notice:  Format Check: 1 file(s) need formatting"}
+        "#);
+    }
+}
@@ -0,0 +1,342 @@
+//! Diagnostics in the Reviewdog Diagnostic Format.
+//!
+//! [reviewdog](https://github.com/reviewdog/reviewdog) posts the diagnostics
+//! of linters as review comments on the pull requests of many forges (e.g.,
+//! GitHub, GitLab, Bitbucket and Gitea), filtered to the lines changed. This
+//! module collects the annotations of the messages parsed into
+//! [`Diagnostics`], which can be written in the [Reviewdog Diagnostic
+//! Format](https://github.com/reviewdog/reviewdog/tree/master/proto/rdf): as
+//! a single JSON object (`rdjson`), or as one diagnostic per line
+//! (`rdjsonl`). Either is read with `reviewdog -f rdjson` or `-f rdjsonl`.
+//!
+//! Each annotation of a message (as formatted for GitHub Actions, so that the
+//! paths are relative to the root of the checkout) becomes a diagnostic at
+//! its location, with the message's code, if any. Annotations which do not
+//! refer to a file are skipped, as reviewdog cannot comment on them.
+//!
+//! For example, the diagnostic of a warning of rustc is:
+//!
+//! ```json
+//! {"message":"unused variable: `x`","location":{"path":"src/lib.rs","range":{"start":{"line":2,"column":9},"end":{"line":2,"column":10}}},"severity":"WARNING","source":{"name":"cargo-check"},"code":{"value":"unused_variables"}}
+//! ```
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{
+    ci::{PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+};
+
+/// The severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+#[non_exhaustive]
+pub enum DiagnosticSeverity {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational diagnostic.
+    Info,
+}
+
+impl From<Severity> for DiagnosticSeverity {
+    #[inline]
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => Self::Error,
+            Severity::Warning => Self::Warning,
+            Severity::Notice | Severity::Debug => Self::Info,
+        }
+    }
+}
+
+/// A position within a file, counting lines and columns from one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Position {
+    /// The line of the position.
+    pub line: u32,
+    /// The column of the position, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+}
+
+/// The range of a diagnostic within its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Range {
+    /// The start of the range.
+    pub start: Position,
+    /// The end of the range, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<Position>,
+}
+
+/// The location of a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Location {
+    /// The path of the file, relative to the root of the checkout.
+    pub path: String,
+    /// The range of the diagnostic within the file, if it refers to a line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+}
+
+/// The tool which reported a diagnostic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Source {
+    /// The name of the tool (e.g., `cargo-check`).
+    pub name: String,
+}
+
+/// The code of a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Code {
+    /// The code (e.g., `unused_variables`).
+    pub value: String,
+}
+
+/// A diagnostic in the Reviewdog Diagnostic Format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    /// The message of the diagnostic, preceded by the title of its
+    /// annotation, unless the title only restates the code.
+    pub message: String,
+    /// The location of the diagnostic.
+    pub location: Location,
+    /// The severity of the diagnostic.
+    pub severity: DiagnosticSeverity,
+    /// The tool which reported the diagnostic.
+    pub source: Source,
+    /// The code of the diagnostic, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Code>,
+}
+
+impl Diagnostic {
+    /// Convert an annotation workflow command.
+    ///
+    /// # Arguments
+    ///
+    /// * `annotation` - The annotation.
+    /// * `source` - The name of the tool which reported the annotation.
+    /// * `code` - The code of the message annotated, if any.
+    ///
+    /// # Returns
+    ///
+    /// The diagnostic, or `None` if the annotation does not refer to a file.
+    fn from_workflow(
+        annotation: &WorkflowAnnotation,
+        source: &str,
+        code: Option<&str>,
+    ) -> Option<Self> {
+        let path = annotation.param("file")?.into_owned();
+        let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+        let range = number("line").map(|line| {
+            let end_line = number("endLine").unwrap_or(line).max(line);
+            Range {
+                start: Position {
+                    line,
+                    column: number("col"),
+                },
+                end: (end_line != line || number("endColumn").is_some()).then(|| Position {
+                    line: end_line,
+                    column: number("endColumn"),
+                }),
+            }
+        });
+        // The title is omitted when it only restates the code (e.g.,
+        // `warning: unused_variables`), which is given separately.
+        let message = match annotation.param("title") {
+            Some(title) if code.is_none_or(|value| !title.contains(value)) => {
+                format!("{title}: {}", annotation.message())
+            }
+            Some(_) | None => annotation.message().into_owned(),
+        };
+        Some(Self {
+            message,
+            location: Location { path, range },
+            severity: annotation.severity().into(),
+            source: Source {
+                name: source.to_owned(),
+            },
+            code: code.map(|value| Code {
+                value: value.to_owned(),
+            }),
+        })
+    }
+}
+
+/// The diagnostics collected from the messages of a tool.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::rdjson::Diagnostics;
+///
+/// let diagnostics = Diagnostics::new("cargo-check");
+///
+/// let mut jsonl = Vec::new();
+/// diagnostics.write_jsonl(&mut jsonl).expect("writing to a Vec cannot fail");
+/// assert!(jsonl.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Diagnostics {
+    /// The tool which reported the diagnostics.
+    source: Source,
+    /// The diagnostics, in the order in which they were recorded.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Create an empty collection of diagnostics.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The name of the tool reporting the diagnostics.
+    #[must_use]
+    #[inline]
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: Source {
+                name: source.into(),
+            },
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Record the annotations of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, whose annotations when formatted for
+    ///   GitHub Actions are recorded.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        let formatted = message.format_for(PlatformKind::GitHub);
+        let code = message.code();
+        self.diagnostics.extend(
+            formatted
+                .lines()
+                .filter_map(WorkflowAnnotation::parse)
+                .filter_map(|annotation| {
+                    Diagnostic::from_workflow(&annotation, &self.source.name, code)
+                }),
+        );
+    }
+
+    /// The number of diagnostics recorded.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Whether no diagnostics have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// The diagnostics recorded, in the order in which they were recorded.
+    #[must_use]
+    #[inline]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Write the diagnostics as a single JSON object, for `reviewdog -f
+    /// rdjson`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be written.
+    #[inline]
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    /// Write each diagnostic as a line of JSON, for `reviewdog -f rdjsonl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `writer` fails.
+    #[inline]
+    pub fn write_jsonl(&self, mut writer: impl Write) -> io::Result<()> {
+        for diagnostic in &self.diagnostics {
+            serde_json::to_writer(&mut writer, diagnostic)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Diagnostics;
+    use crate::tool::{CargoCheck, Tool as _};
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+"#;
+
+    #[test]
+    fn write() {
+        let mut tool = CargoCheck::default();
+        let mut messages = tool.parse(WARNING.as_bytes());
+        messages.extend(tool.finish());
+        let mut diagnostics = Diagnostics::new("cargo-check");
+        for message in messages {
+            diagnostics.record(&message.expect("valid message"));
+        }
+        assert_eq!(diagnostics.len(), 1);
+
+        let mut jsonl = Vec::new();
+        diagnostics.write_jsonl(&mut jsonl).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&jsonl), @r#"{"message":"unused variable: `x`","location":{"path":"src/lib.rs","range":{"start":{"line":2,"column":9},"end":{"line":2,"column":10}}},"severity":"WARNING","source":{"name":"cargo-check"},"code":{"value":"unused_variables"}}"#);
+
+        let mut json = Vec::new();
+        diagnostics.write_json(&mut json).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&json), @r#"
+        {
+          "source": {
+            "name": "cargo-check"
+          },
+          "diagnostics": [
+            {
+              "message": "unused variable: `x`",
+              "location": {
+                "path": "src/lib.rs",
+                "range": {
+                  "start": {
+                    "line": 2,
+                    "column": 9
+                  },
+                  "end": {
+                    "line": 2,
+                    "column": 10
+                  }
+                }
+              },
+              "severity": "WARNING",
+              "source": {
+                "name": "cargo-check"
+              },
+              "code": {
+                "value": "unused_variables"
+              }
+            }
+          ]
+        }
+        "#);
+    }
+}
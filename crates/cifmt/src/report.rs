@@ -0,0 +1,395 @@
+//! Full reports of a run, written as artifacts of the CI job.
+//!
+//! What a platform displays of a run is limited: GitHub Actions does not
+//! display a job summary larger than [`GitHub::MAX_STEP_SUMMARY_SIZE`], and
+//! only creates a few annotations for each step (see
+//! [`GitHub::MAX_ANNOTATIONS_PER_STEP`]). A [`ReportArtifact`] writes the full
+//! report of a run to a Markdown file instead (the job summary, followed by
+//! the annotations which were not created), and registers the file as an
+//! artifact of the job, so that the summary can link to it once truncated.
+//!
+//! How the file is registered depends on the platform (see [`Registration`]):
+//! Buildkite uploads it through its agent, while GitHub Actions and GitLab CI
+//! only upload the files listed in the configuration of the job.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use memchr::memrchr;
+
+use crate::{
+    ci::{Buildkite, GitHub, PlatformKind, WorkflowAnnotation},
+    markdown::{Cell, JobSummary},
+};
+
+/// How a report is registered as an artifact of the job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Registration {
+    /// Set the [`ReportArtifact::OUTPUT_NAME`] output of the GitHub Actions
+    /// step to the path of the report, for an `actions/upload-artifact` step
+    /// to upload, and link to the artifacts of the workflow run.
+    GitHub {
+        /// The URL of the workflow run, if known.
+        run_url: Option<String>,
+        /// The file setting the outputs of the step, if any.
+        output: Option<PathBuf>,
+    },
+    /// Link to the report within the artifacts of the GitLab CI job, which
+    /// must list the report under `artifacts:paths`.
+    GitLab {
+        /// The URL of the job, if known.
+        job_url: Option<String>,
+        /// The directory of the project, to which the paths of artifacts are
+        /// relative.
+        project_dir: Option<PathBuf>,
+    },
+    /// Upload the report through `buildkite-agent artifact upload` (see
+    /// [`Buildkite::upload_artifact`]).
+    Buildkite,
+    /// Only write the report.
+    Local,
+}
+
+/// The full report of a run, written to a file as an artifact of the job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "the report is only one of the artifacts written by a pipeline"
+)]
+pub struct ReportArtifact {
+    /// The directory in which the report is written.
+    dir: PathBuf,
+    /// How the report is registered as an artifact.
+    registration: Registration,
+}
+
+impl ReportArtifact {
+    /// The name of the file of the report.
+    pub const FILE_NAME: &str = "cifmt-report.md";
+
+    /// The name of the output of the GitHub Actions step holding the path of
+    /// the report.
+    pub const OUTPUT_NAME: &str = "report-path";
+
+    /// Write the report within a directory, without registering it.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory in which the report is written, which is
+    ///   created if necessary.
+    #[must_use]
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            registration: Registration::Local,
+        }
+    }
+
+    /// Write the report within a directory, and register it as an artifact
+    /// of the job of the platform detected from the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory in which the report is written, which is
+    ///   created if necessary.
+    #[must_use]
+    #[inline]
+    pub fn from_env(dir: impl Into<PathBuf>) -> Self {
+        let platform = PlatformKind::from_env();
+        let registration = if platform == PlatformKind::GitHub {
+            Registration::GitHub {
+                run_url: match (
+                    env::var("GITHUB_SERVER_URL"),
+                    env::var("GITHUB_REPOSITORY"),
+                    env::var("GITHUB_RUN_ID"),
+                ) {
+                    (Ok(server), Ok(repository), Ok(run)) => {
+                        Some(format!("{server}/{repository}/actions/runs/{run}"))
+                    }
+                    _ => None,
+                },
+                output: env::var_os(GitHub::OUTPUT_ENV).map(PathBuf::from),
+            }
+        } else if platform == PlatformKind::Buildkite {
+            Registration::Buildkite
+        } else if env::var("GITLAB_CI").is_ok_and(|value| value == "true") {
+            Registration::GitLab {
+                job_url: env::var("CI_JOB_URL").ok(),
+                project_dir: env::var_os("CI_PROJECT_DIR").map(PathBuf::from),
+            }
+        } else {
+            Registration::Local
+        };
+        Self::new(dir).with_registration(registration)
+    }
+
+    /// Set how the report is registered as an artifact of the job.
+    ///
+    /// # Arguments
+    ///
+    /// * `registration` - How the report is registered.
+    #[must_use]
+    #[inline]
+    pub fn with_registration(mut self, registration: Registration) -> Self {
+        self.registration = registration;
+        self
+    }
+
+    /// The path of the report.
+    #[must_use]
+    #[inline]
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(Self::FILE_NAME)
+    }
+
+    /// The URL at which the report can be found once uploaded, if known.
+    ///
+    /// On GitHub Actions, this is the list of the artifacts of the workflow
+    /// run, since an artifact is only given a URL once uploaded.
+    #[must_use]
+    #[inline]
+    pub fn url(&self) -> Option<String> {
+        match &self.registration {
+            Registration::GitHub { run_url, .. } => {
+                run_url.as_ref().map(|url| format!("{url}#artifacts"))
+            }
+            Registration::GitLab {
+                job_url,
+                project_dir,
+            } => {
+                let path = self.path();
+                let relative = project_dir
+                    .as_deref()
+                    .and_then(|dir| path.strip_prefix(dir).ok())
+                    .unwrap_or(&path);
+                let components: Vec<_> = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect();
+                job_url
+                    .as_ref()
+                    .map(|url| format!("{url}/artifacts/file/{}", components.join("/")))
+            }
+            Registration::Buildkite | Registration::Local => None,
+        }
+    }
+
+    /// Where the report can be found, as Markdown: a link to it if its URL
+    /// is known, or else its path.
+    fn location(&self) -> String {
+        match self.url() {
+            Some(url) => format!("in the [artifacts of the job]({url})"),
+            None => format!("in `{}`", self.path().display()),
+        }
+    }
+
+    /// Write the report, and register it as an artifact of the job.
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - The Markdown content of the report.
+    ///
+    /// # Returns
+    ///
+    /// The path of the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be written, or cannot be
+    /// registered.
+    #[inline]
+    pub fn write(&self, report: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path();
+        fs::write(&path, report)?;
+        match &self.registration {
+            Registration::GitHub {
+                output: Some(output),
+                ..
+            } => GitHub::set_output(Self::OUTPUT_NAME, &path.to_string_lossy())
+                .path(output)
+                .write()?,
+            Registration::Buildkite => Buildkite::upload_artifact(&path)?,
+            Registration::GitHub { output: None, .. }
+            | Registration::GitLab { .. }
+            | Registration::Local => {}
+        }
+        Ok(path)
+    }
+
+    /// Link a job summary to the report.
+    ///
+    /// A note linking to the report is appended to the summary, and the
+    /// summary is truncated at the end of a line if it would otherwise
+    /// exceed the given size.
+    ///
+    /// # Arguments
+    ///
+    /// * `summary` - The job summary.
+    /// * `max_size` - The maximum size of the summary, in bytes, including
+    ///   the note.
+    pub(crate) fn link_summary(&self, summary: &str, max_size: usize) -> String {
+        let location = self.location();
+        let note = format!("\n> [!NOTE]\n> The full report is available {location}.\n");
+        if summary.len().saturating_add(note.len()) <= max_size {
+            return format!("{summary}{note}");
+        }
+
+        let warning = format!(
+            "\n> [!WARNING]\n> This summary is truncated to the size displayed by the \
+             platform. The full report is available {location}.\n"
+        );
+        let available = max_size.saturating_sub(warning.len());
+        let end = summary
+            .as_bytes()
+            .get(..available)
+            .and_then(|head| memrchr(b'\n', head))
+            .map_or(0, |index| index.saturating_add(1));
+        format!("{}{warning}", summary.get(..end).unwrap_or_default())
+    }
+}
+
+/// The content of a full report: the job summary, followed by the
+/// annotations beyond the limit of the platform.
+pub(crate) struct FullReport<'a> {
+    /// The job summary.
+    pub summary: JobSummary<'a>,
+    /// The annotations which were written to the log instead of being
+    /// created.
+    pub omitted: &'a [WorkflowAnnotation],
+}
+
+impl core::fmt::Display for FullReport<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.summary)?;
+        if self.omitted.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        writeln!(f, "#### Annotations Not Shown")?;
+        writeln!(f)?;
+        writeln!(f, "| Severity | Location | Title | Message |")?;
+        writeln!(f, "| -------- | -------- | ----- | ------- |")?;
+        for annotation in self.omitted {
+            let location = match (annotation.param("file"), annotation.param("line")) {
+                (Some(file), Some(line)) => format!("`{file}:{line}`"),
+                (Some(file), None) => format!("`{file}`"),
+                (None, _) => String::new(),
+            };
+            writeln!(
+                f,
+                "| {} | {} | {} | {} |",
+                annotation.severity(),
+                Cell(&location),
+                Cell(&annotation.param("title").unwrap_or_default()),
+                Cell(&annotation.message())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The size of a file, or zero if it does not exist.
+///
+/// # Arguments
+///
+/// * `path` - The file.
+pub(crate) fn file_size(path: &Path) -> usize {
+    fs::metadata(path)
+        .ok()
+        .and_then(|metadata| usize::try_from(metadata.len()).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{Registration, ReportArtifact};
+
+    fn artifact(registration: Registration) -> ReportArtifact {
+        ReportArtifact::new("/builds/project/reports").with_registration(registration)
+    }
+
+    #[rstest]
+    #[case::github(
+        Registration::GitHub {
+            run_url: Some("https://github.com/owner/repo/actions/runs/1".to_owned()),
+            output: None,
+        },
+        Some("https://github.com/owner/repo/actions/runs/1#artifacts")
+    )]
+    #[case::gitlab(
+        Registration::GitLab {
+            job_url: Some("https://gitlab.com/group/project/-/jobs/2".to_owned()),
+            project_dir: Some(PathBuf::from("/builds/project")),
+        },
+        Some("https://gitlab.com/group/project/-/jobs/2/artifacts/file/reports/cifmt-report.md")
+    )]
+    #[case::gitlab_unknown(
+        Registration::GitLab {
+            job_url: None,
+            project_dir: None,
+        },
+        None
+    )]
+    #[case::buildkite(Registration::Buildkite, None)]
+    #[case::local(Registration::Local, None)]
+    fn url(#[case] registration: Registration, #[case] expected: Option<&str>) {
+        assert_eq!(artifact(registration).url().as_deref(), expected);
+    }
+
+    #[rstest]
+    fn link_summary() {
+        let report = artifact(Registration::Local);
+        let summary = "### cargo\n\n1 error(s) and 0 warning(s).\n";
+        insta::assert_snapshot!(report.link_summary(summary, 1024), @"
+        ### cargo
+
+        1 error(s) and 0 warning(s).
+
+        > [!NOTE]
+        > The full report is available in `/builds/project/reports/cifmt-report.md`.
+        ");
+        let long = format!(
+            "{summary}\n#### Tests\n\n| Passed | Failed | Skipped | Total |\n| 1 | 1 | 0 | 2 |\n"
+        );
+        insta::assert_snapshot!(report.link_summary(&long, 180), @"
+        ### cargo
+
+
+        > [!WARNING]
+        > This summary is truncated to the size displayed by the platform. The full report is available in `/builds/project/reports/cifmt-report.md`.
+        ");
+    }
+
+    #[rstest]
+    fn write() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let output = dir.path().join("output");
+        let report = ReportArtifact::new(dir.path().join("reports")).with_registration(
+            Registration::GitHub {
+                run_url: None,
+                output: Some(output.clone()),
+            },
+        );
+        let path = report.write("### cargo\n").expect("write failed");
+        assert_eq!(path, dir.path().join("reports/cifmt-report.md"));
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read failed"),
+            "### cargo\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&output).expect("read failed"),
+            format!("report-path={}\n", path.display())
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Resilience of the parsers to malformed input.
+//!
+//! The output of a tool is untrusted: it may be cut off, interleaved with the
+//! output of other programs, or contain arbitrary bytes relayed from a test.
+//! A parser must never panic, whatever its input. This is a property of every
+//! [`Tool`](crate::tool::Tool):
+//!
+//! - input which cannot be parsed is reported as an [error of the
+//!   tool](crate::tool::Tool::Error) (e.g., a line which is not valid JSON),
+//!   or as a message describing it (e.g., a [`TruncatedLine`] or a
+//!   [`TextOutput`]), and parsing continues with the next line;
+//! - a parser does not index, slice or do arithmetic which may fail on its
+//!   input, nor unwrap what it derives from it.
+//!
+//! The parsers are fuzzed against this property (see the targets of the `fuzz`
+//! directory, run with `cargo fuzz run <target>`).
+//!
+//! Should a parser nonetheless panic, the panic must not kill the CI step and
+//! lose the output written so far. The [`Pipeline`](crate::pipeline::Pipeline)
+//! catches the panic and reports it through a [`ParserPanic`] error instead.
+//! As the state of the parser may be inconsistent after a panic, the rest of
+//! the tool's output is read but not parsed, and the output is reported as
+//! incomplete.
+//!
+//! [`TruncatedLine`]: crate::tool::TruncatedLine
+//! [`TextOutput`]: crate::tool::TextOutput
+
+use core::{any::Any, fmt};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, DynCiMessage, Severity},
+    timeline::Timed,
+};
+
+/// A panic of the parser of a tool, after which the rest of its output was
+/// not parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParserPanic {
+    /// The name of the tool whose parser panicked.
+    pub tool: &'static str,
+    /// The message of the panic, if it has one.
+    pub reason: Option<String>,
+}
+
+impl ParserPanic {
+    /// Describe the panic of a parser.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool whose parser panicked.
+    /// * `payload` - The payload of the panic.
+    fn new(tool: &'static str, payload: &(dyn Any + Send)) -> Self {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|reason| (*reason).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned());
+        Self { tool, reason }
+    }
+}
+
+impl fmt::Display for ParserPanic {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The parser of {} panicked", self.tool)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+        write!(
+            f,
+            "; the rest of the output was not parsed. Please report this as a bug of cifmt."
+        )
+    }
+}
+
+impl<P: Platform> CiMessage<P> for ParserPanic {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            self.to_string(),
+            None,
+            Some(Title::ParserPanic.text()),
+        )
+    }
+}
+
+impl Classify for ParserPanic {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
+
+impl Timed for ParserPanic {}
+
+impl Captured for ParserPanic {}
+
+/// Run a parser, catching any panic.
+///
+/// # Arguments
+///
+/// * `tool` - The name of the tool whose parser is run.
+/// * `poisoned` - Whether the parser panicked before, in which case it is not
+///   run again. Set if the parser panics.
+/// * `parse` - The parser.
+///
+/// # Returns
+///
+/// The messages parsed, or a [`ParserPanic`] if the parser panicked.
+pub(crate) fn guard(
+    tool: &'static str,
+    poisoned: &mut bool,
+    parse: impl FnOnce() -> Vec<Box<dyn DynCiMessage>>,
+) -> Vec<Box<dyn DynCiMessage>> {
+    if *poisoned {
+        return Vec::new();
+    }
+    // The parser is discarded if it panics, so that its state does not
+    // matter.
+    panic::catch_unwind(AssertUnwindSafe(parse)).unwrap_or_else(|payload| {
+        *poisoned = true;
+        tracing::error!("The parser of {tool} panicked");
+        vec![Box::new(ParserPanic::new(tool, &*payload))]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::ParserPanic;
+    use crate::{
+        ci::{GitHub, PlatformKind},
+        ci_message::CiMessage,
+        pipeline::{Pipeline, Target},
+        tool::{
+            AnyTool, Bandit, Black, CargoCheck, CargoDoc, CargoFmt, CargoLibtest, CargoUdeps,
+            Cmake, Cppcheck, Eslint, GccDiagnostics, Junit, Lcov, LspDiagnostics, Miri, OsvScanner,
+            Phpunit, Pmd, Psalm, Pytest, RustcHuman, Surefire, Tool, Xcodebuild,
+        },
+        utf8::InvalidUtf8,
+    };
+
+    /// A tool whose parser panics on a line reading `panic`, and otherwise
+    /// reports a message for each chunk.
+    struct Panicking;
+
+    impl Tool for Panicking {
+        type Message = InvalidUtf8;
+        type Error = Infallible;
+
+        fn name(&self) -> &'static str {
+            "panicking"
+        }
+
+        fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+            assert!(buf != b"panic\n", "unexpected input");
+            vec![Ok(InvalidUtf8 {
+                replaced: buf.len(),
+            })]
+        }
+    }
+
+    #[test]
+    fn format() {
+        let message = ParserPanic {
+            tool: "cargo-check",
+            reason: Some("index out of bounds".to_owned()),
+        };
+        insta::assert_snapshot!(
+            <ParserPanic as CiMessage<GitHub>>::format(&message),
+            @"::error title=Parser Panic::The parser of cargo-check panicked (index out of bounds); the rest of the output was not parsed. Please report this as a bug of cifmt."
+        );
+    }
+
+    #[test]
+    fn caught() {
+        let mut output = Vec::new();
+        let mut pipeline = Pipeline::new(
+            Box::new(Panicking),
+            vec![Target::new(PlatformKind::Plain, &mut output)],
+        );
+        for chunk in ["a\n", "panic\n", "ab\n"] {
+            pipeline.process(chunk.as_bytes()).expect("write failed");
+        }
+        pipeline.finish().expect("finish failed");
+        assert_eq!(pipeline.errors(), 1);
+        assert!(pipeline.is_incomplete());
+        drop(pipeline);
+
+        insta::assert_snapshot!(String::from_utf8_lossy(&output), @"
+        notice:  Invalid UTF-8: Replaced 2 invalid UTF-8 sequence(s) in the input with U+FFFD
+        error:   Parser Panic: The parser of panicking panicked (unexpected input); the rest of the output was not parsed. Please report this as a bug of cifmt.
+        ");
+    }
+
+    /// Malformed input which each parser must survive.
+    const MALFORMED: &[&[u8]] = &[
+        b"",
+        b"\n\n\n",
+        b"{",
+        b"{\"reason\":\"compiler-message\"}\n",
+        b"{\"reason\":\"compiler-message\",\"message\":{\"spans\":[{\"line_start\":0}]}}\n",
+        b"{\"type\":\"test\",\"event\":\"failed\"}\n",
+        b"{\"type\":\"suite\",\"event\":\"ok\",\"passed\":-1}\n",
+        b"[{\"name\":\"a.rs\",\"mismatches\":[]}]\n",
+        b"Diff in :0:\n+\n-\n",
+        b"Diff in a.rs at line 99999999999:\n",
+        b"SF:\nDA:1\nDA:,\nLF:x\nLH:18446744073709551615\nLF:1\nend_of_record\n",
+        b"\xFF\xFE\x00\x01\x80\n",
+        b"{\"unused_deps\":{\"\":{\"manifest_path\":\"\",\"normal\":[\"\"]}}}\n",
+        b"error: Undefined Behavior: \n --> :0:\n = note: inside  at :99999999999:\nnote: inside \n",
+        b"{\"$message_type\":\"diagnostic\",\"message\":\"Undefined Behavior: \",\"children\":[{}]}\n",
+        b"error[E0308]: \n --> :0:99999999999\n  |\n0 | \xFF\n  | ^^^ help: : `\n  = note: \n          \n",
+        b"warning[: \n-->\n |\nhelp: \n  | \xe2\x82\xac^\n",
+        b"<testsuite time=\"NaN\"><testcase name=\"\" line=\"-1\" time=\"1e999\"><failure>&#xD800;&#;&",
+        b"</testcase></testsuite></testsuites><skipped/><system-out a='>'><![CDATA[\n",
+        b":0:: error: \n:99999999999:1: note:  \n  from :\n: In :\nIn file included from ,\n",
+        b"a.c:1:1: warning:  [-]\n  |\n\xFF: note: [\n",
+        b"<results><error id='' severity='' cwe='-1'><location line='99999999999'/><error/></errors>",
+        b"CMake Error at :0 ():\nCMake Warning (dev) in :\nCall Stack (most recent call first):\n  \xFF\nCMake Error at a:99999999999 (x):\n",
+        b"CMake Error: \n\n  \n\t\nThis warning is for project developers.\n",
+        b"{\"$report_type\":\"TestReport\",\"nodeid\":\"\",\"outcome\":\"failed\",\"when\":\"teardown\",\"duration\":-1,\"start\":1e999,\"longrepr\":[1,2]}\n",
+        b"{\"$report_type\":\"SessionFinish\",\"exitstatus\":2}\n{\"$report_type\":\"CollectReport\",\"nodeid\":\"::\",\"outcome\":\"failed\",\"longrepr\":{\"reprtraceback\":{\"reprentries\":[]}}}\n",
+        b"--- \t\n+++ \t\n@@ -99999999999,1 +1 @@\n \n\n-\n--- a:cell_\t\n@@ -0 +0 @@\n+\n\\\n",
+        b"would reformat \nerror: cannot format : \nerror: cannot format a: 99999999999:1: \xFF\n",
+        b"{\n  \"errors\": [{}],\n  \"results\": [{\"line_number\": -1}]\n}\n{\"results\": [{\"filename\": \"./\", \"line_number\": 99999999999}]}\n{\"results\": []\n",
+        b"[ERROR]  <<< FAILURE!\n[ERROR] ( -- Time elapsed: -1 s <<< ERROR!\n\tat .(:99999999999)\n\tat /.(a:1)\n[INFO] Tests run: 99999999999999999999, Failures: 1\n[ERROR] Tests run: 0, Failures: 9, Errors: 9, Skipped: 9, Time elapsed: NaN s -- in \n",
+        b"[ERROR] a()  Time elapsed: 1e400 sec <<< FAILURE!\n\xFF\n[INFO] ---< : >---\n[INFO]   from pom.xml\n[INFO] --- : @  ---\n[ERROR] Tests run: 1, Failures: 18446744073709551615, Errors: 18446744073709551615\n",
+        b"<pmd>\n<violation beginline=\"1\" rule=\"R\"/>\n<file name=\"\">\n<violation beginline=\"-1\" rule=\"R\" priority=\"999\">\n<error filename=\"a\" msg=\"\"><![CDATA[\n</file>\n",
+        b"{\n  \"pmdVersion\": \"7\",\n  \"files\": [{\"filename\": \"a\", \"violations\": [{\"beginline\": 99999999999}]}]\n}\n{\"files\": [], \"processingErrors\": [{}]}\n{\"files\": [\n",
+        b"[{\"severity\":\"error\",\"line_from\":-1,\"column_from\":1,\"file_name\":\"a\"}]\n[\n{\"type\": 1}\n]\n[{\"severity\":\"bogus\",\"line_from\":1,\"line_to\":1,\"type\":\"\",\"message\":\"\",\"file_name\":\"\",\"column_from\":0,\"column_to\":0,\"link\":\"\"}]\n[{\"line_from\":\n",
+        b"##teamcity[testFailed name='a' details='x.php:99999999999' flowId='1']\n##teamcity[testSuiteFinished]\n##teamcity[testStarted name='|0x' locationHint='php_qn://::' x='|\n##teamcity[testFinished duration='-1']\n<testsuites>\n<testcase name=\"a\" assertions=\"1\"><failure>::\n.php:1\n</testcase>\n",
+        b"Test Suite 'X' started at\nTest Case '-[A b]' started.\n:99999999999: error: -[A b] : x\n<unknown>: -[A b] : \nTest Case '-[A b]' failed (nan seconds).\nTest Suite 'X' failed at\n\t Executed 1 test in -1 (x) seconds\n{\n\"issues\": {\"errorSummaries\": {\"_values\": [{\"message\": {\"_value\": \"\"}, \"documentLocationInCreatingWorkspace\": {\"url\": {\"_value\": \"file://%%zz#StartingLineNumber=4294967295\"}}}]}}\n}\nerror: : error:  (in target '' from project '')\n=== BUILD TARGET  OF PROJECT \n",
+        b"{\"results\": [{\"source\": {\"path\": \"/\", \"type\": \"\"}, \"packages\": [{\"package\": {\"name\": \"\", \"version\": \"\", \"ecosystem\": \"\"}, \"vulnerabilities\": [{\"id\": \"\", \"database_specific\": {\"severity\": 1}, \"affected\": [{\"ranges\": [{\"events\": [{\"fixed\": \"\"}]}]}]}], \"groups\": [{\"ids\": [], \"max_severity\": \"NaN\"}, {\"ids\": [\"\"], \"max_severity\": \"-1e999\"}]}]}]}\n{\n\"results\": [{\"source\": {\"path\": \"\"}}]\n",
+        b"{\"uri\": \"file://%\", \"diagnostics\": [{\"range\": {\"start\": {\"line\": 4294967295, \"character\": 4294967295}, \"end\": {\"line\": 0, \"character\": 0}}, \"severity\": 255, \"code\": -9223372036854775808, \"codeDescription\": {\"href\": \"\"}, \"source\": \"\", \"message\": \"\", \"relatedInformation\": [{\"location\": {\"uri\": \"file:///%e2%82\", \"range\": {\"start\": {\"line\": 4294967295, \"character\": 0}, \"end\": {\"line\": 0, \"character\": 0}}}, \"message\": \"\"}]}]}\n{\"method\": \"textDocument/publishDiagnostics\", \"params\": {\"uri\": \"file:///C:\", \"diagnostics\": [{\"range\": null}]}}\n{\"uri\": 1}\n",
+        b"[{\"filePath\":\"\",\"messages\":[{\"severity\":-1,\"line\":0,\"endLine\":4294967295}]},{\"filePath\":1}]\n[{\"filePath\":\"a\",\"messages\":[{\"ruleId\":7,\"severity\":255,\"message\":\"\",\"line\":99999999999}]}]\n[{\"messages\":\n",
+        b"[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[\n",
+    ];
+
+    #[rstest]
+    fn malformed_input(
+        #[values(
+            Box::new(CargoCheck::default()),
+            Box::new(CargoLibtest::default()),
+            Box::new(CargoFmt::default()),
+            Box::new(CargoDoc::default()),
+            Box::new(Lcov::default()),
+            Box::new(CargoUdeps::default()),
+            Box::new(Miri::default()),
+            Box::new(RustcHuman::default()),
+            Box::new(Junit::default()),
+            Box::new(GccDiagnostics::default()),
+            Box::new(Cppcheck::default()),
+            Box::new(Cmake::default()),
+            Box::new(Pytest::default()),
+            Box::new(Black::default()),
+            Box::new(Bandit::default()),
+            Box::new(Surefire::default()),
+            Box::new(Pmd::default()),
+            Box::new(Psalm::default()),
+            Box::new(Eslint::default()),
+            Box::new(Phpunit::default()),
+            Box::new(Xcodebuild::default()),
+            Box::new(OsvScanner::default()),
+            Box::new(LspDiagnostics::default())
+        )]
+        tool: Box<dyn AnyTool>,
+    ) {
+        let mut output = Vec::new();
+        let mut pipeline =
+            Pipeline::new(tool, vec![Target::new(PlatformKind::GitHub, &mut output)]);
+        for input in MALFORMED {
+            pipeline.process(input).expect("write failed");
+        }
+        pipeline.finish().expect("finish failed");
+        drop(pipeline);
+
+        assert!(!String::from_utf8_lossy(&output).contains("Parser Panic"));
+    }
+}
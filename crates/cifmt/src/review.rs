@@ -0,0 +1,591 @@
+//! Annotations posted as review comments on a pull request.
+//!
+//! Annotations created by workflow commands are shown on the files changed by
+//! a pull request, but not in its conversation. This module posts the
+//! annotations of output formatted for GitHub Actions (e.g., written by
+//! `cifmt format --output github=annotations.log`) as the comments of a
+//! review of the pull request, through the [REST
+//! API](https://docs.github.com/en/rest/pulls/reviews).
+//!
+//! GitHub only accepts comments on the lines of the pull request's diff (see
+//! [`DiffLines`]), so annotations on other lines, or without a location, are
+//! skipped. Comments identical to one already posted (e.g., by a previous run
+//! of the workflow) are skipped as well.
+//!
+//! This module requires the `review` feature.
+
+use core::fmt;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    env, fs, io,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::json;
+use ureq::{Agent, RequestBuilder};
+
+use crate::{ci::WorkflowAnnotation, ci_message::Severity, http, owners::CodeOwners};
+
+/// The lines of each file which are part of a pull request's diff.
+///
+/// Only the lines of the new version of each file are recorded, whether they
+/// were added or are shown as context.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DiffLines {
+    /// The lines in the diff, keyed by the path of the file.
+    files: HashMap<String, BTreeSet<u32>>,
+}
+
+impl DiffLines {
+    /// Record the lines of a file in a unified diff.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file, relative to the root of the
+    ///   repository.
+    /// * `hunks` - The hunks of the unified diff of the file.
+    #[inline]
+    pub fn add_patch(&mut self, path: &str, hunks: &str) {
+        let lines = self.files.entry(path.to_owned()).or_default();
+        let mut next: Option<u32> = None;
+        for line in hunks.lines() {
+            if let Some(header) = line.strip_prefix("@@ ") {
+                next = header
+                    .split_whitespace()
+                    .find_map(|range| range.strip_prefix('+'))
+                    .and_then(|range| range.split(',').next())
+                    .and_then(|start| start.parse().ok());
+            } else if let Some(number) = next
+                && (line.starts_with('+') || line.starts_with(' ') || line.is_empty())
+            {
+                lines.insert(number);
+                next = Some(number.saturating_add(1));
+            }
+        }
+    }
+
+    /// Whether the given line of a file is part of the diff.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file, relative to the root of the
+    ///   repository.
+    /// * `line` - The line of the new version of the file.
+    #[must_use]
+    #[inline]
+    pub fn contains(&self, path: &str, line: u32) -> bool {
+        self.files
+            .get(path)
+            .is_some_and(|lines| lines.contains(&line))
+    }
+}
+
+/// A comment on a line of a pull request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "a bare Comment would be ambiguous outside this module"
+)]
+pub struct ReviewComment {
+    /// The path of the file, relative to the root of the repository.
+    pub path: String,
+    /// The line of the new version of the file.
+    pub line: u32,
+    /// The body of the comment, in Markdown.
+    pub body: String,
+}
+
+impl ReviewComment {
+    /// Convert the annotations of output formatted for GitHub Actions into
+    /// comments on the lines of the diff.
+    ///
+    /// Annotations without a location, or on a line outside the diff, are
+    /// skipped, and so are duplicates.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output formatted for GitHub Actions.
+    /// * `diff` - The lines of the pull request's diff.
+    #[must_use]
+    #[inline]
+    pub fn from_output(output: &str, diff: &DiffLines) -> Vec<Self> {
+        let mut seen = HashSet::new();
+        output
+            .lines()
+            .filter_map(WorkflowAnnotation::parse)
+            .filter_map(|annotation| Self::from_workflow(&annotation))
+            .filter(|comment| diff.contains(&comment.path, comment.line))
+            .filter(|comment| seen.insert(comment.clone()))
+            .collect()
+    }
+
+    /// Convert an annotation workflow command.
+    ///
+    /// # Returns
+    ///
+    /// The comment, or `None` if the annotation does not refer to a line of
+    /// a file.
+    fn from_workflow(annotation: &WorkflowAnnotation) -> Option<Self> {
+        let path = annotation.param("file")?.into_owned();
+        let line = annotation.param("line")?.parse().ok()?;
+        let level = match annotation.severity() {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+            Severity::Notice | Severity::Debug => "Notice",
+        };
+        let body = match annotation.param("title") {
+            Some(title) => format!("**{level}: {title}**\n\n{}", annotation.message()),
+            None => format!("**{level}**\n\n{}", annotation.message()),
+        };
+        Some(Self { path, line, body })
+    }
+
+    /// Mention the owners of the file commented on, at the end of the body.
+    ///
+    /// Only owners which can be mentioned (i.e., users and teams, rather than
+    /// email addresses) are included, and the body is unchanged if there are
+    /// none.
+    ///
+    /// # Arguments
+    ///
+    /// * `owners` - The owners of the files of the repository.
+    #[inline]
+    pub fn mention_owners(&mut self, owners: &CodeOwners) {
+        let mentions: Vec<_> = owners
+            .owners(&self.path)
+            .iter()
+            .filter(|owner| owner.starts_with('@'))
+            .map(String::as_str)
+            .collect();
+        if !mentions.is_empty() {
+            self.body = format!("{}\n\ncc {}", self.body, mentions.join(" "));
+        }
+    }
+}
+
+/// A comment already posted on a pull request.
+#[derive(Debug, Deserialize)]
+struct PostedComment {
+    /// The path of the file.
+    path: String,
+    /// The line of the new version of the file, or `None` if the comment is
+    /// outdated.
+    line: Option<u32>,
+    /// The body of the comment.
+    body: String,
+}
+
+/// A file changed by a pull request.
+#[derive(Debug, Deserialize)]
+struct ChangedFile {
+    /// The path of the file.
+    filename: String,
+    /// The hunks of the unified diff of the file, absent for binary files
+    /// and very large diffs.
+    patch: Option<String>,
+}
+
+/// The event which triggered a GitHub Actions workflow.
+#[derive(Debug, Deserialize)]
+struct Event {
+    /// The pull request, for events relating to one.
+    pull_request: Option<EventPullRequest>,
+}
+
+/// The pull request of an event.
+#[derive(Debug, Deserialize)]
+struct EventPullRequest {
+    /// The number of the pull request.
+    number: u64,
+    /// The head of the pull request.
+    head: EventHead,
+}
+
+/// The head of a pull request.
+#[derive(Debug, Deserialize)]
+struct EventHead {
+    /// The commit at the head of the pull request.
+    sha: String,
+}
+
+/// A pull request on which review comments are posted through the REST API.
+#[derive(Clone)]
+pub struct PullRequest {
+    /// The URL of the GitHub API.
+    api_url: String,
+    /// The repository, as `owner/name`.
+    repository: String,
+    /// The number of the pull request.
+    number: u64,
+    /// The commit at the head of the pull request.
+    head_sha: String,
+    /// The token used to authenticate with the API.
+    token: String,
+    /// The owners of the files of the repository, mentioned in the comments
+    /// if set.
+    code_owners: Option<CodeOwners>,
+}
+
+impl PullRequest {
+    /// The number of items requested per page of a listing.
+    const PER_PAGE: usize = 100;
+
+    /// Environment variable holding the path to the JSON payload of the event
+    /// which triggered the workflow.
+    pub const EVENT_PATH_ENV: &str = "GITHUB_EVENT_PATH";
+
+    /// Environment variable holding the URL of the GitHub API.
+    pub const API_URL_ENV: &str = "GITHUB_API_URL";
+
+    /// Environment variable holding the repository, as `owner/name`.
+    pub const REPOSITORY_ENV: &str = "GITHUB_REPOSITORY";
+
+    /// Environment variable holding the token used to authenticate with the
+    /// API, which must be granted the `pull-requests: write` permission.
+    pub const TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+    /// The URL of the GitHub API, if not set by [`PullRequest::API_URL_ENV`].
+    pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+    /// Refer to a pull request.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_url` - The URL of the GitHub API.
+    /// * `repository` - The repository, as `owner/name`.
+    /// * `number` - The number of the pull request.
+    /// * `head_sha` - The commit at the head of the pull request.
+    /// * `token` - The token used to authenticate with the API.
+    #[must_use]
+    #[inline]
+    pub fn new(
+        api_url: impl Into<String>,
+        repository: impl Into<String>,
+        number: u64,
+        head_sha: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_url: api_url.into(),
+            repository: repository.into(),
+            number,
+            head_sha: head_sha.into(),
+            token: token.into(),
+            code_owners: None,
+        }
+    }
+
+    /// Mention the owners of the file commented on in each comment (see
+    /// [`ReviewComment::mention_owners`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `owners` - The owners of the files of the repository.
+    #[must_use]
+    #[inline]
+    pub fn with_code_owners(mut self, owners: CodeOwners) -> Self {
+        self.code_owners = Some(owners);
+        self
+    }
+
+    /// Refer to the pull request which triggered the GitHub Actions workflow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event, the repository or the token is not set
+    /// in the environment, or if the event does not relate to a pull request.
+    #[inline]
+    pub fn from_env() -> Result<Self, Error> {
+        let var = |key: &'static str| env::var(key).map_err(|_err| Error::MissingEnv(key));
+        let event = Self::read_event(Path::new(&var(Self::EVENT_PATH_ENV)?))?;
+        Ok(Self::new(
+            env::var(Self::API_URL_ENV).unwrap_or_else(|_err| Self::DEFAULT_API_URL.to_owned()),
+            var(Self::REPOSITORY_ENV)?,
+            event.number,
+            event.head.sha,
+            var(Self::TOKEN_ENV)?,
+        ))
+    }
+
+    /// Read the pull request of the event which triggered the workflow.
+    fn read_event(path: &Path) -> Result<EventPullRequest, Error> {
+        let event: Event = serde_json::from_slice(&fs::read(path)?)?;
+        event.pull_request.ok_or(Error::NotPullRequest)
+    }
+
+    /// Post the annotations of output formatted for GitHub Actions as the
+    /// comments of a review of the pull request.
+    ///
+    /// Annotations outside the diff of the pull request, and comments already
+    /// posted (including the mentions of the owners, if enabled), are
+    /// skipped. No review is posted if no comments remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output formatted for GitHub Actions.
+    ///
+    /// # Returns
+    ///
+    /// The number of comments posted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request to the API fails.
+    #[inline]
+    pub fn publish(&self, output: &str) -> Result<usize, Error> {
+        let agent = http::agent();
+        let mut diff = DiffLines::default();
+        for file in self.list::<ChangedFile>(&agent, "files")? {
+            if let Some(patch) = &file.patch {
+                diff.add_patch(&file.filename, patch);
+            }
+        }
+        let posted: HashSet<_> = self
+            .list::<PostedComment>(&agent, "comments")?
+            .into_iter()
+            .filter_map(|comment| {
+                Some(ReviewComment {
+                    path: comment.path,
+                    line: comment.line?,
+                    body: comment.body,
+                })
+            })
+            .collect();
+
+        let comments: Vec<_> = ReviewComment::from_output(output, &diff)
+            .into_iter()
+            .map(|mut comment| {
+                if let Some(owners) = &self.code_owners {
+                    comment.mention_owners(owners);
+                }
+                comment
+            })
+            .filter(|comment| !posted.contains(comment))
+            .collect();
+        if comments.is_empty() {
+            return Ok(0);
+        }
+        let review = json!({
+            "commit_id": self.head_sha,
+            "event": "COMMENT",
+            "body": format!("cifmt found {} issue(s) in the changes.", comments.len()),
+            "comments": comments
+                .iter()
+                .map(|comment| json!({
+                    "path": comment.path,
+                    "line": comment.line,
+                    "side": "RIGHT",
+                    "body": comment.body,
+                }))
+                .collect::<Vec<_>>(),
+        });
+        self.headers(agent.post(self.url("reviews")))
+            .send_json(&review)?;
+        Ok(comments.len())
+    }
+
+    /// The URL of a resource of the pull request.
+    fn url(&self, resource: &str) -> String {
+        format!(
+            "{}/repos/{}/pulls/{}/{resource}",
+            self.api_url.trim_end_matches('/'),
+            self.repository,
+            self.number
+        )
+    }
+
+    /// Add the headers of the API to a request.
+    fn headers<B>(&self, request: RequestBuilder<B>) -> RequestBuilder<B> {
+        request
+            .header("Accept", "application/vnd.github+json")
+            .header("Authorization", &format!("Bearer {}", self.token))
+            .header("User-Agent", "cifmt")
+    }
+
+    /// List all items of a resource of the pull request, page by page.
+    fn list<T: DeserializeOwned>(&self, agent: &Agent, resource: &str) -> Result<Vec<T>, Error> {
+        let url = self.url(resource);
+        let mut items = Vec::new();
+        for page in 1_u32.. {
+            let batch: Vec<T> = self
+                .headers(agent.get(&url))
+                .query("per_page", Self::PER_PAGE.to_string())
+                .query("page", page.to_string())
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let last = batch.len() < Self::PER_PAGE;
+            items.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+impl fmt::Debug for PullRequest {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PullRequest")
+            .field("api_url", &self.api_url)
+            .field("repository", &self.repository)
+            .field("number", &self.number)
+            .field("head_sha", &self.head_sha)
+            .field("token", &"[redacted]")
+            .field("code_owners", &self.code_owners)
+            .finish()
+    }
+}
+
+/// Errors relating to posting review comments.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A required environment variable is not set.
+    #[error("The {0} environment variable is not set")]
+    MissingEnv(&'static str),
+    /// The workflow was not triggered by an event relating to a pull request.
+    #[error("The workflow was not triggered by a pull request")]
+    NotPullRequest,
+    /// The event which triggered the workflow cannot be read.
+    #[error("Failed to read the workflow event: {0}")]
+    Io(#[from] io::Error),
+    /// The event which triggered the workflow is not valid JSON.
+    #[error("Failed to parse the workflow event: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A request to the API failed.
+    #[error("Request to the GitHub API failed: {0}")]
+    Request(#[from] ureq::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{DiffLines, PullRequest, ReviewComment};
+    use crate::{ci::GitHub, owners::CodeOwners};
+
+    const PATCH: &str = "\
+@@ -1,3 +1,4 @@
+ use std::fmt;
+-use std::io;
++use std::io::{self, Write};
++use std::env;
+
+@@ -20,2 +21,3 @@ fn main() {
+     let x = 1;
++    let y = 2;
+ }
+\\ No newline at end of file";
+
+    #[test]
+    fn diff_lines() {
+        let mut diff = DiffLines::default();
+        diff.add_patch("src/main.rs", PATCH);
+        let lines: Vec<_> = (1..=25)
+            .filter(|&line| diff.contains("src/main.rs", line))
+            .collect();
+        assert_eq!(lines, [1, 2, 3, 4, 21, 22, 23]);
+        assert!(!diff.contains("src/lib.rs", 1), "file not in the diff");
+    }
+
+    #[test]
+    fn from_output() {
+        let mut diff = DiffLines::default();
+        diff.add_patch("src/main.rs", PATCH);
+        let unused = GitHub::warning("unused variable: `y`")
+            .file("src/main.rs")
+            .line(22)
+            .title("unused_variables")
+            .format();
+        let output = [
+            "::group::Checking\n".to_owned(),
+            unused.clone(),
+            unused,
+            GitHub::error("mismatched types")
+                .file("src/main.rs")
+                .line(2)
+                .format(),
+            GitHub::error("outside the diff")
+                .file("src/main.rs")
+                .line(10)
+                .format(),
+            GitHub::error("no location").format(),
+            "::endgroup::\n".to_owned(),
+        ]
+        .concat();
+
+        assert_eq!(
+            ReviewComment::from_output(&output, &diff),
+            [
+                ReviewComment {
+                    path: "src/main.rs".to_owned(),
+                    line: 22,
+                    body: "**Warning: unused_variables**\n\nunused variable: `y`".to_owned(),
+                },
+                ReviewComment {
+                    path: "src/main.rs".to_owned(),
+                    line: 2,
+                    body: "**Error**\n\nmismatched types".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mention_owners() {
+        let owners = CodeOwners::parse(
+            "* @org/core\n/docs/ docs@example.com\n*.rs @org/rust ops@example.com @alice\n",
+        );
+        let mut comment = ReviewComment {
+            path: "src/main.rs".to_owned(),
+            line: 2,
+            body: "**Error**\n\nmismatched types".to_owned(),
+        };
+        comment.mention_owners(&owners);
+        assert_eq!(
+            comment.body,
+            "**Error**\n\nmismatched types\n\ncc @org/rust @alice"
+        );
+
+        let mut unmentionable = ReviewComment {
+            path: "docs/index.md".to_owned(),
+            ..comment.clone()
+        };
+        unmentionable.mention_owners(&owners);
+        assert_eq!(unmentionable.body, comment.body);
+    }
+
+    #[test]
+    fn read_event() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let path = dir.path().join("event.json");
+        std::fs::write(
+            &path,
+            r#"{"action": "opened", "pull_request": {"number": 42, "head": {"sha": "abc123"}}}"#,
+        )
+        .expect("failed to write event");
+        let event = PullRequest::read_event(&path).expect("failed to read event");
+        assert_eq!((event.number, event.head.sha.as_str()), (42, "abc123"));
+
+        std::fs::write(&path, r#"{"ref": "refs/heads/main"}"#).expect("failed to write event");
+        PullRequest::read_event(&path).expect_err("expected a push event to be rejected");
+    }
+
+    #[test]
+    fn redacted() {
+        let pull = PullRequest::new("https://api", "owner/repo", 1, "abc123", "secret");
+        assert_eq!(
+            pull.url("files"),
+            "https://api/repos/owner/repo/pulls/1/files"
+        );
+        assert!(
+            !format!("{pull:?}").contains("secret"),
+            "token not redacted"
+        );
+    }
+}
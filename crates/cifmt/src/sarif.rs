@@ -0,0 +1,472 @@
+//! SARIF reports.
+//!
+//! Code scanning services (e.g., the code scanning alerts of GitHub, or the
+//! SARIF viewers of IDEs) read the issues found by an analyser from a report
+//! in the [Static Analysis Results Interchange
+//! Format](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html).
+//! This module collects the annotations of the messages parsed into a
+//! [`Report`], which can be written in this format.
+//!
+//! Each annotation of a message (as formatted for GitHub Actions, so that the
+//! paths are relative to the root of the checkout) becomes a result at its
+//! location, with the level `error`, `warning` or `note`, for the rule
+//! identified by the message's code if any. Annotations which do not refer
+//! to a file are skipped, as code scanning requires each result to have a
+//! location.
+//!
+//! The fixes which the tool considers safe to apply automatically (see
+//! [`Classify::fixes`](crate::ci_message::Classify::fixes)) are attached to
+//! the first result of their message, as a single fix replacing the spans of
+//! each file.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use serde::Serialize;
+
+use crate::{
+    ci::{NormalizedPath, PlatformKind, WorkflowAnnotation},
+    ci_message::{DynCiMessage, Severity},
+};
+
+/// The schema of the reports written.
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A fix of an issue which can be applied automatically, replacing a span of
+/// a file.
+///
+/// See [`Classify::fixes`](crate::ci_message::Classify::fixes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Fix<'a> {
+    /// The path of the file to change.
+    pub file: &'a str,
+    /// The first line of the span to replace, counting from one.
+    pub start_line: u32,
+    /// The column at which the span starts, counting characters from one.
+    pub start_column: u32,
+    /// The last line of the span to replace, counting from one.
+    pub end_line: u32,
+    /// The column following the end of the span, counting characters from
+    /// one.
+    pub end_column: u32,
+    /// The text replacing the span.
+    pub replacement: &'a str,
+}
+
+/// A message of a result or a fix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct Text {
+    /// The plain text of the message.
+    text: String,
+}
+
+/// The location of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct ArtifactLocation {
+    /// The path of the file, relative to the root of the checkout.
+    uri: String,
+}
+
+/// A span within a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// The first line of the span.
+    start_line: u32,
+    /// The column at which the span starts, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<u32>,
+    /// The last line of the span, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<u32>,
+    /// The column following the end of the span, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<u32>,
+}
+
+/// The location of a result within a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    /// The file of the result.
+    artifact_location: ArtifactLocation,
+    /// The span of the result, if its line is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+/// A location of a result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    /// The location of the result within a file.
+    physical_location: PhysicalLocation,
+}
+
+/// The replacement of a span of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    /// The span replaced.
+    deleted_region: Region,
+    /// The text replacing the span.
+    inserted_content: Text,
+}
+
+/// The changes made to a file by a fix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    /// The file changed.
+    artifact_location: ArtifactLocation,
+    /// The replacements made within the file.
+    replacements: Vec<Replacement>,
+}
+
+/// A fix of a result, made of the changes to each file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultFix {
+    /// The changes to each file.
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+/// A result within a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    /// The rule which was violated, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    /// The level of the result.
+    level: &'static str,
+    /// The message of the result, preceded by the title of its annotation
+    /// unless it only restates the rule.
+    message: Text,
+    /// The location of the result.
+    locations: [Location; 1],
+    /// The fixes of the result.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<ResultFix>,
+}
+
+/// The tool which reported the results of a run.
+#[derive(Debug, Serialize)]
+struct Driver<'a> {
+    /// The name of the tool.
+    name: &'a str,
+}
+
+/// The description of the tool of a run.
+#[derive(Debug, Serialize)]
+struct ToolComponent<'a> {
+    /// The tool which reported the results.
+    driver: Driver<'a>,
+}
+
+/// A run of a tool.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    /// The tool of the run.
+    tool: ToolComponent<'a>,
+    /// How the columns of the regions are counted.
+    column_kind: &'static str,
+    /// The results of the run.
+    results: &'a [Entry],
+}
+
+/// The SARIF log written by [`Report::write_json`].
+#[derive(Debug, Serialize)]
+struct Log<'a> {
+    /// The schema of the log.
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    /// The version of SARIF.
+    version: &'static str,
+    /// The runs of the log, of which there is one.
+    runs: [Run<'a>; 1],
+}
+
+/// The level of a result, as named by SARIF.
+const fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Notice | Severity::Debug => "note",
+    }
+}
+
+/// Combine fixes into a single fix of a result, with the changes of each
+/// file in the order of their paths.
+fn combine(fixes: &[Fix<'_>]) -> ResultFix {
+    let mut changes: BTreeMap<String, Vec<Replacement>> = BTreeMap::new();
+    for fix in fixes {
+        changes
+            .entry(NormalizedPath(fix.file).to_string())
+            .or_default()
+            .push(Replacement {
+                deleted_region: Region {
+                    start_line: fix.start_line,
+                    start_column: Some(fix.start_column),
+                    end_line: Some(fix.end_line),
+                    end_column: Some(fix.end_column),
+                },
+                inserted_content: Text {
+                    text: fix.replacement.to_owned(),
+                },
+            });
+    }
+    ResultFix {
+        artifact_changes: changes
+            .into_iter()
+            .map(|(uri, replacements)| ArtifactChange {
+                artifact_location: ArtifactLocation { uri },
+                replacements,
+            })
+            .collect(),
+    }
+}
+
+/// A SARIF report of the annotations of the messages of a tool.
+///
+/// # Example
+///
+/// ```
+/// use cifmt::sarif::Report;
+///
+/// let report = Report::new("cargo-check");
+///
+/// let mut json = Vec::new();
+/// report.write_json(&mut json).expect("writing to a Vec cannot fail");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// The name of the tool which reported the annotations.
+    tool: String,
+    /// The results, in the order in which they were recorded.
+    results: Vec<Entry>,
+}
+
+impl Report {
+    /// Create an empty report.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - The name of the tool reporting the annotations.
+    #[must_use]
+    #[inline]
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Record the annotations of a message, and its fixes.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, whose annotations when formatted for
+    ///   GitHub Actions are recorded.
+    #[inline]
+    pub fn record(&mut self, message: &dyn DynCiMessage) {
+        let formatted = message.format_for(PlatformKind::GitHub);
+        let code = message.code();
+        let fixes = message.fixes();
+        let mut attached = fixes.is_empty();
+        for annotation in formatted.lines().filter_map(WorkflowAnnotation::parse) {
+            let Some(path) = annotation.param("file") else {
+                continue;
+            };
+            let number = |name: &str| annotation.param(name)?.parse::<u32>().ok();
+            // The title is omitted when it only restates the code (e.g.,
+            // `warning: unused_variables`).
+            let text = match annotation.param("title") {
+                Some(title) if code.is_none_or(|value| !title.contains(value)) => {
+                    format!("{title}: {}", annotation.message())
+                }
+                Some(_) | None => annotation.message().into_owned(),
+            };
+            let region = number("line").map(|line| Region {
+                start_line: line,
+                start_column: number("col"),
+                end_line: number("endLine"),
+                end_column: number("endColumn"),
+            });
+            self.results.push(Entry {
+                rule_id: code.map(str::to_owned),
+                level: level(annotation.severity()),
+                message: Text { text },
+                locations: [Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation {
+                            uri: path.into_owned(),
+                        },
+                        region,
+                    },
+                }],
+                fixes: if attached {
+                    Vec::new()
+                } else {
+                    attached = true;
+                    vec![combine(&fixes)]
+                },
+            });
+        }
+    }
+
+    /// The number of results recorded.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Whether no results have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Write the report as a SARIF 2.1.0 log, with a single run of the tool.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be written.
+    #[inline]
+    pub fn write_json(&self, mut writer: impl Write) -> io::Result<()> {
+        let log = Log {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: [Run {
+                tool: ToolComponent {
+                    driver: Driver { name: &self.tool },
+                },
+                // Columns are counted in characters, as by rustc.
+                column_kind: "unicodeCodePoints",
+                results: &self.results,
+            }],
+        };
+        serde_json::to_writer_pretty(&mut writer, &log)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Report;
+    use crate::tool::{CargoCheck, Tool as _};
+
+    const FIXABLE: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w#project@0.1.0","manifest_path":"/w/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"help","message":"if this is intentional, prefix it with an underscore","rendered":null,"spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":"_x","suggestion_applicability":"MachineApplicable","text":[{"highlight_end":10,"highlight_start":9,"text":"    let x = 1;"}]}]}],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":30,"byte_start":29,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":10,"highlight_start":9,"text":"    let x = 1;"}]}]}}
+"#;
+
+    #[test]
+    fn write_json() {
+        let mut report = Report::new("cargo-check");
+        let mut check = CargoCheck::default();
+        let mut messages = check.parse(FIXABLE.as_bytes());
+        messages.extend(check.finish());
+        for message in messages {
+            report.record(&message.expect("valid message"));
+        }
+        assert_eq!(report.len(), 2);
+
+        let mut json = Vec::new();
+        report.write_json(&mut json).expect("write failed");
+        insta::assert_snapshot!(String::from_utf8_lossy(&json), @r#"
+        {
+          "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+          "version": "2.1.0",
+          "runs": [
+            {
+              "tool": {
+                "driver": {
+                  "name": "cargo-check"
+                }
+              },
+              "columnKind": "unicodeCodePoints",
+              "results": [
+                {
+                  "ruleId": "unused_variables",
+                  "level": "warning",
+                  "message": {
+                    "text": "unused variable: `x` (suggested fix: change line 2 to `let _x = 1;`)"
+                  },
+                  "locations": [
+                    {
+                      "physicalLocation": {
+                        "artifactLocation": {
+                          "uri": "src/lib.rs"
+                        },
+                        "region": {
+                          "startLine": 2,
+                          "startColumn": 9,
+                          "endLine": 2,
+                          "endColumn": 10
+                        }
+                      }
+                    }
+                  ],
+                  "fixes": [
+                    {
+                      "artifactChanges": [
+                        {
+                          "artifactLocation": {
+                            "uri": "src/lib.rs"
+                          },
+                          "replacements": [
+                            {
+                              "deletedRegion": {
+                                "startLine": 2,
+                                "startColumn": 9,
+                                "endLine": 2,
+                                "endColumn": 10
+                              },
+                              "insertedContent": {
+                                "text": "_x"
+                              }
+                            }
+                          ]
+                        }
+                      ]
+                    }
+                  ]
+                },
+                {
+                  "ruleId": "unused_variables",
+                  "level": "note",
+                  "message": {
+                    "text": "help: if this is intentional, prefix it with an underscore"
+                  },
+                  "locations": [
+                    {
+                      "physicalLocation": {
+                        "artifactLocation": {
+                          "uri": "src/lib.rs"
+                        },
+                        "region": {
+                          "startLine": 2,
+                          "startColumn": 9
+                        }
+                      }
+                    }
+                  ]
+                }
+              ]
+            }
+          ]
+        }
+        "#);
+    }
+}
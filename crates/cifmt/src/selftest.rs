@@ -0,0 +1,125 @@
+//! Sample output exercising each building block of a platform.
+//!
+//! When setting up a new CI integration, it is easiest to check how each kind
+//! of message is rendered by the platform from a known sample rather than
+//! from the output of a real build. The [`SelfTest`] message contains an
+//! annotation of each severity, with and without a location, inside a
+//! group, and [`SelfTest::JOB_SUMMARY`] contains a sample job summary.
+
+use crate::{
+    capture::Captured,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// A sample of each building block of a platform.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SelfTest;
+
+impl SelfTest {
+    /// The file to which the sample annotations with a location refer.
+    pub const FILE: &str = "README.md";
+
+    /// A sample job summary, in Markdown.
+    pub const JOB_SUMMARY: &str = "\
+### cifmt self-test
+
+If this summary is displayed, the job summary is written correctly.
+
+| Severity | Annotated |
+| :------- | --------: |
+| Error    |         2 |
+| Warning  |         2 |
+| Notice   |         2 |
+";
+}
+
+impl<P: Platform> CiMessage<P> for SelfTest {
+    #[inline]
+    fn format(&self) -> String {
+        let mut lines = vec![P::group("cifmt self-test")];
+        for (severity, name) in [
+            (Severity::Error, "error"),
+            (Severity::Warning, "warning"),
+            (Severity::Notice, "notice"),
+        ] {
+            lines.push(P::annotate(
+                severity,
+                format!("This {name} refers to line 1 of {}", Self::FILE),
+                Some((Self::FILE, 1, None)),
+                Some(&format!("Sample {name} with a location")),
+            ));
+            lines.push(P::annotate(
+                severity,
+                format!("This {name} refers to no file"),
+                None,
+                Some(&format!("Sample {name}")),
+            ));
+        }
+        lines.push(P::debug(
+            "This debug message is only shown if the platform shows debug messages",
+        ));
+        lines.push(P::endgroup());
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for SelfTest {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl Timed for SelfTest {}
+
+impl Captured for SelfTest {}
+
+#[cfg(test)]
+mod tests {
+    use super::SelfTest;
+    use crate::ci::{AzureDevOps, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[test]
+    fn format() {
+        insta::assert_snapshot!(<SelfTest as CiMessage<Plain>>::format(&SelfTest), @"
+        cifmt self-test
+        README.md:1: error:   Sample error with a location: This error refers to line 1 of README.md
+        error:   Sample error: This error refers to no file
+        README.md:1: warning: Sample warning with a location: This warning refers to line 1 of README.md
+        warning: Sample warning: This warning refers to no file
+        README.md:1: notice:  Sample notice with a location: This notice refers to line 1 of README.md
+        notice:  Sample notice: This notice refers to no file
+        This debug message is only shown if the platform shows debug messages
+        ");
+        insta::assert_snapshot!(<SelfTest as CiMessage<GitHub>>::format(&SelfTest), @"
+        ::group::cifmt self-test
+        ::error file=README.md,line=1,title=Sample error with a location::This error refers to line 1 of README.md
+        ::error title=Sample error::This error refers to no file
+        ::warning file=README.md,line=1,title=Sample warning with a location::This warning refers to line 1 of README.md
+        ::warning title=Sample warning::This warning refers to no file
+        ::notice file=README.md,line=1,title=Sample notice with a location::This notice refers to line 1 of README.md
+        ::notice title=Sample notice::This notice refers to no file
+        ::debug::This debug message is only shown if the platform shows debug messages
+        ::endgroup::
+        ");
+        insta::assert_snapshot!(<SelfTest as CiMessage<AzureDevOps>>::format(&SelfTest), @"
+        ##[group]cifmt self-test
+        ##vso[task.logissue type=error;sourcepath=README.md;linenumber=1]Sample error with a location: This error refers to line 1 of README.md
+        ##vso[task.logissue type=error]Sample error: This error refers to no file
+        ##vso[task.logissue type=warning;sourcepath=README.md;linenumber=1]Sample warning with a location: This warning refers to line 1 of README.md
+        ##vso[task.logissue type=warning]Sample warning: This warning refers to no file
+        README.md:1: notice:  Sample notice with a location: This notice refers to line 1 of README.md
+        notice:  Sample notice: This notice refers to no file
+        ##[debug]This debug message is only shown if the platform shows debug messages
+        ##[endgroup]
+        ");
+    }
+}
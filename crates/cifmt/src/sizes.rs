@@ -0,0 +1,383 @@
+//! Sizes of the binaries produced by a build.
+//!
+//! Tools which build a project report the binaries they produce (e.g., the
+//! executables of cargo's `compiler-artifact` messages, see
+//! [`Classify::binary`]). Their sizes are collected into [`SizeStats`] and
+//! reported at the end of the output by a [`SizeSummary`]. The sizes of a
+//! previous build can be stored as JSON (see [`SizeStats::write_json`]) to be
+//! used as a baseline, in which case a [`SizeRegression`] warning is reported
+//! for each binary which grew beyond a threshold.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Read, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// The size of each binary, in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+#[non_exhaustive]
+pub struct SizeStats {
+    /// The size of each binary, keyed by its name.
+    sizes: BTreeMap<String, u64>,
+}
+
+impl SizeStats {
+    /// Record the size of a binary, replacing any size previously recorded
+    /// for a binary of the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the binary.
+    /// * `size` - The size of the binary, in bytes.
+    #[inline]
+    pub fn record(&mut self, name: &str, size: u64) {
+        self.sizes.insert(name.to_owned(), size);
+    }
+
+    /// Record the size of a binary from its file.
+    ///
+    /// Binaries whose file cannot be read (e.g., because it was removed since
+    /// it was built) are skipped with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the binary.
+    /// * `path` - The path to the binary.
+    #[inline]
+    pub fn record_file(&mut self, name: &str, path: &Path) {
+        match fs::metadata(path) {
+            Ok(metadata) => self.record(name, metadata.len()),
+            Err(err) => warn!("Unable to read the size of {}: {err}", path.display()),
+        }
+    }
+
+    /// Whether no binaries have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// The size of the binary with the given name, if recorded.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.sizes.get(name).copied()
+    }
+
+    /// The binaries recorded, along with their size, ordered by name.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.sizes.iter().map(|(name, size)| (name.as_str(), *size))
+    }
+
+    /// Read sizes stored as a JSON object mapping the name of each binary to
+    /// its size in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read, or is not such an
+    /// object.
+    #[inline]
+    pub fn read_json(reader: impl Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Write the sizes as a JSON object mapping the name of each binary to its
+    /// size in bytes, as read by [`SizeStats::read_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be written.
+    #[inline]
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+}
+
+/// The size of a binary, compared to its size in a baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SizeChange {
+    /// The name of the binary.
+    pub name: String,
+    /// The size of the binary, in bytes.
+    pub size: u64,
+    /// The size of the binary in the baseline, in bytes, if present.
+    pub baseline: Option<u64>,
+}
+
+impl SizeChange {
+    /// Whether the binary grew by more than the given percentage of its size
+    /// in the baseline.
+    ///
+    /// Binaries absent from the baseline are never considered to exceed the
+    /// threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The allowed growth, in percent.
+    #[must_use]
+    #[inline]
+    pub fn exceeds(&self, threshold: u64) -> bool {
+        self.baseline.is_some_and(|baseline| {
+            self.size > baseline
+                && u128::from(self.size.abs_diff(baseline)).saturating_mul(100)
+                    > u128::from(baseline).saturating_mul(u128::from(threshold))
+        })
+    }
+
+    /// The change in size relative to the baseline, such as `+1024 B (+5%)`,
+    /// or `new` if the binary is absent from the baseline.
+    fn change(&self) -> String {
+        let Some(baseline) = self.baseline else {
+            return "new".to_owned();
+        };
+        let sign = if self.size < baseline { '-' } else { '+' };
+        let delta = self.size.abs_diff(baseline);
+        match delta.saturating_mul(100).checked_div(baseline) {
+            Some(percent) => format!("{sign}{delta} B ({sign}{percent}%)"),
+            None => format!("{sign}{delta} B"),
+        }
+    }
+}
+
+/// A table of the size of each binary, written at the end of the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SizeSummary {
+    /// The size of each binary, ordered by name.
+    pub binaries: Vec<SizeChange>,
+    /// Whether the sizes are compared to a baseline.
+    pub compared: bool,
+}
+
+impl SizeSummary {
+    /// Summarize the size of each binary.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The size of each binary.
+    /// * `baseline` - The sizes to compare against, if any.
+    #[must_use]
+    #[inline]
+    pub fn new(stats: &SizeStats, baseline: Option<&SizeStats>) -> Self {
+        Self {
+            binaries: stats
+                .iter()
+                .map(|(name, size)| SizeChange {
+                    name: name.to_owned(),
+                    size,
+                    baseline: baseline.and_then(|sizes| sizes.get(name)),
+                })
+                .collect(),
+            compared: baseline.is_some(),
+        }
+    }
+
+    /// The binaries which grew by more than the given percentage of their
+    /// size in the baseline (see [`SizeChange::exceeds`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The allowed growth, in percent.
+    #[must_use]
+    #[inline]
+    pub fn regressions(&self, threshold: u64) -> Vec<SizeRegression> {
+        self.binaries
+            .iter()
+            .filter(|binary| binary.exceeds(threshold))
+            .map(|binary| SizeRegression {
+                binary: binary.clone(),
+                threshold,
+            })
+            .collect()
+    }
+}
+
+impl<P: Platform> CiMessage<P> for SizeSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let width = self
+            .binaries
+            .iter()
+            .map(|binary| binary.size.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Size (B)".len());
+        let changes: Vec<_> = self.binaries.iter().map(SizeChange::change).collect();
+        let change_width = changes
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or_default()
+            .max("Change".len());
+        let mut lines = vec![P::group(Title::BinarySizes.text())];
+        if self.compared {
+            lines.push(format!(
+                "{:>width$}  {:>change_width$}  Binary",
+                "Size (B)", "Change"
+            ));
+            for (binary, change) in self.binaries.iter().zip(&changes) {
+                lines.push(format!(
+                    "{:>width$}  {change:>change_width$}  {}",
+                    binary.size, binary.name
+                ));
+            }
+        } else {
+            lines.push(format!("{:>width$}  Binary", "Size (B)"));
+            for binary in &self.binaries {
+                lines.push(format!("{:>width$}  {}", binary.size, binary.name));
+            }
+        }
+        lines.push(P::endgroup());
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for SizeSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl Timed for SizeSummary {}
+
+impl Captured for SizeSummary {}
+
+/// A binary which grew beyond the allowed threshold since the baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SizeRegression {
+    /// The size of the binary, compared to the baseline.
+    pub binary: SizeChange,
+    /// The allowed growth, in percent.
+    pub threshold: u64,
+}
+
+impl<P: Platform> CiMessage<P> for SizeRegression {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            format!(
+                "{} grew by {} from {} B to {} B, exceeding the threshold of {}%",
+                self.binary.name,
+                self.binary.change().trim_start_matches('+'),
+                self.binary.baseline.unwrap_or_default(),
+                self.binary.size,
+                self.threshold
+            ),
+            None,
+            Some(Title::BinarySizeRegression.text()),
+        )
+    }
+}
+
+impl Classify for SizeRegression {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+}
+
+impl Timed for SizeRegression {}
+
+impl Captured for SizeRegression {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{SizeChange, SizeStats, SizeSummary};
+    use crate::ci::{GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[rstest]
+    #[case::new(100, None, 0, false)]
+    #[case::shrunk(90, Some(100), 0, false)]
+    #[case::within(105, Some(100), 5, false)]
+    #[case::beyond(106, Some(100), 5, true)]
+    #[case::from_empty(1, Some(0), 5, true)]
+    fn exceeds(
+        #[case] size: u64,
+        #[case] baseline: Option<u64>,
+        #[case] threshold: u64,
+        #[case] expected: bool,
+    ) {
+        let change = SizeChange {
+            name: "app".to_owned(),
+            size,
+            baseline,
+        };
+        assert_eq!(change.exceeds(threshold), expected);
+    }
+
+    #[test]
+    fn json() {
+        let mut stats = SizeStats::default();
+        stats.record("app", 2048);
+        stats.record("cli", 512);
+        let mut json = Vec::new();
+        stats.write_json(&mut json).expect("write failed");
+        assert_eq!(
+            SizeStats::read_json(json.as_slice()).expect("read failed"),
+            stats
+        );
+        SizeStats::read_json(&b"[1, 2]"[..]).expect_err("expected an object");
+    }
+
+    #[test]
+    fn format() {
+        let mut stats = SizeStats::default();
+        stats.record("app", 2_200_000);
+        stats.record("cli", 900);
+        stats.record("tool", 4096);
+        insta::assert_snapshot!(<SizeSummary as CiMessage<Plain>>::format(&SizeSummary::new(&stats, None)), @"
+        Binary sizes
+        Size (B)  Binary
+         2200000  app
+             900  cli
+            4096  tool
+        ");
+
+        let mut baseline = SizeStats::default();
+        baseline.record("app", 2_000_000);
+        baseline.record("cli", 1000);
+        let summary = SizeSummary::new(&stats, Some(&baseline));
+        insta::assert_snapshot!(<SizeSummary as CiMessage<GitHub>>::format(&summary), @"
+        ::group::Binary sizes
+        Size (B)            Change  Binary
+         2200000  +200000 B (+10%)  app
+             900     -100 B (-10%)  cli
+            4096               new  tool
+        ::endgroup::
+        ");
+
+        let regressions = summary.regressions(5);
+        let [regression] = regressions.as_slice() else {
+            panic!("expected a single regression, got {regressions:?}");
+        };
+        insta::assert_snapshot!(<_ as CiMessage<GitHub>>::format(regression), @"::warning title=Binary Size Regression::app grew by 200000 B (+10%25) from 2000000 B to 2200000 B, exceeding the threshold of 5%25");
+    }
+}
@@ -0,0 +1,283 @@
+//! Statistics of the tests which were skipped, and why.
+//!
+//! Test harnesses report each test which is not run (e.g., libtest's ignored
+//! tests), along with the reason given for skipping it, if any (e.g.,
+//! `requires docker`). Rather than reviewing each skipped test, the tests are
+//! counted by their reason into [`SkipStats`], and reported at the end of the
+//! output by a [`SkipSummary`], which shows how much of the suite is skipped
+//! and why.
+
+use std::collections::HashMap;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    junit::{TestOutcome, TestResult},
+    timeline::Timed,
+};
+
+/// The number of tests run, and of tests skipped for each reason.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SkipStats {
+    /// The number of skipped tests, keyed by the reason given, if any.
+    reasons: HashMap<Option<String>, usize>,
+    /// The number of tests with a result, whether skipped or not.
+    tests: usize,
+}
+
+impl SkipStats {
+    /// Record the result of a test.
+    ///
+    /// Skipped tests are counted by their reason, which is the message of the
+    /// result with its lines joined, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the test.
+    #[inline]
+    pub fn record(&mut self, result: &TestResult<'_>) {
+        self.tests = self.tests.saturating_add(1);
+        if result.outcome != TestOutcome::Skipped {
+            return;
+        }
+        let reason = result
+            .message
+            .map(|message| message.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|reason| !reason.is_empty());
+        let count = self.reasons.entry(reason).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Whether no skipped tests have been recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.reasons.is_empty()
+    }
+
+    /// The number of skipped tests.
+    #[must_use]
+    #[inline]
+    pub fn skipped(&self) -> usize {
+        self.reasons.values().sum()
+    }
+
+    /// The number of tests with a result, whether skipped or not.
+    #[must_use]
+    #[inline]
+    pub fn tests(&self) -> usize {
+        self.tests
+    }
+
+    /// The number of tests skipped for the given reason, or without a reason
+    /// if `None`.
+    #[must_use]
+    #[inline]
+    pub fn count(&self, reason: Option<&str>) -> usize {
+        self.reasons
+            .get(&reason.map(str::to_owned))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The most common reasons, along with their number of skipped tests.
+    ///
+    /// Reasons are ordered by decreasing number of tests, and then by name,
+    /// with tests skipped without a reason last among equals.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of reasons to return.
+    #[must_use]
+    #[inline]
+    pub fn top(&self, limit: usize) -> Vec<(Option<&str>, usize)> {
+        let mut top: Vec<_> = self
+            .reasons
+            .iter()
+            .map(|(reason, count)| (reason.as_deref(), *count))
+            .collect();
+        top.sort_by(|lhs, rhs| {
+            rhs.1
+                .cmp(&lhs.1)
+                .then_with(|| lhs.0.is_none().cmp(&rhs.0.is_none()))
+                .then_with(|| lhs.0.cmp(&rhs.0))
+        });
+        top.truncate(limit);
+        top
+    }
+}
+
+/// A table of the reasons for which tests were skipped, written at the end
+/// of the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SkipSummary {
+    /// The most common reasons (or `None` for tests skipped without a
+    /// reason), along with their number of skipped tests.
+    pub top: Vec<(Option<String>, usize)>,
+    /// The number of other reasons, which are omitted.
+    pub omitted: usize,
+    /// The number of skipped tests.
+    pub skipped: usize,
+    /// The number of tests with a result, whether skipped or not.
+    pub tests: usize,
+}
+
+impl SkipSummary {
+    /// The maximum number of reasons listed.
+    pub const MAX_REASONS: usize = 10;
+
+    /// Summarize the most common reasons for which tests were skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The number of tests skipped for each reason.
+    #[must_use]
+    #[inline]
+    pub fn new(stats: &SkipStats) -> Self {
+        let top: Vec<_> = stats
+            .top(Self::MAX_REASONS)
+            .into_iter()
+            .map(|(reason, count)| (reason.map(str::to_owned), count))
+            .collect();
+        Self {
+            omitted: stats.reasons.len().saturating_sub(top.len()),
+            top,
+            skipped: stats.skipped(),
+            tests: stats.tests,
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for SkipSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let width = self
+            .top
+            .iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Count".len());
+        let mut lines = vec![P::group(Title::SkippedTests.text())];
+        lines.push(format!("{:>width$}  Reason", "Count"));
+        for (reason, count) in &self.top {
+            lines.push(format!(
+                "{count:>width$}  {}",
+                reason.as_deref().unwrap_or("(no reason given)")
+            ));
+        }
+        if self.omitted > 0 {
+            lines.push(format!("({} more reason(s) omitted)", self.omitted));
+        }
+        let percent = self
+            .skipped
+            .saturating_mul(100)
+            .checked_div(self.tests)
+            .unwrap_or_default();
+        lines.push(format!(
+            "{} of {} test(s) skipped ({percent}%)",
+            self.skipped, self.tests
+        ));
+        lines.push(P::endgroup());
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for SkipSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl Timed for SkipSummary {}
+
+impl Captured for SkipSummary {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{SkipStats, SkipSummary};
+    use crate::ci::{GitHub, Plain};
+    use crate::ci_message::CiMessage;
+    use crate::junit::{TestOutcome, TestResult};
+
+    fn stats() -> SkipStats {
+        let mut stats = SkipStats::default();
+        for (name, outcome, message) in [
+            ("a", TestOutcome::Passed, None),
+            ("b", TestOutcome::Skipped, Some("requires docker")),
+            ("c", TestOutcome::Skipped, None),
+            ("d", TestOutcome::Skipped, Some("requires\n  docker ")),
+            ("e", TestOutcome::Failed, Some("assertion failed")),
+            ("f", TestOutcome::Skipped, Some("flaky #123")),
+            ("g", TestOutcome::Skipped, Some("")),
+            ("h", TestOutcome::Passed, None),
+        ] {
+            stats.record(&TestResult {
+                message,
+                ..TestResult::new(name, outcome)
+            });
+        }
+        stats
+    }
+
+    #[test]
+    fn top() {
+        let stats = stats();
+        assert_eq!(stats.skipped(), 5);
+        assert_eq!(stats.tests(), 8);
+        assert_eq!(stats.count(Some("requires docker")), 2);
+        assert_eq!(stats.count(Some("assertion failed")), 0);
+        assert_eq!(
+            stats.top(3),
+            [
+                (Some("requires docker"), 2),
+                (None, 2),
+                (Some("flaky #123"), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let mut many = stats();
+        for i in 0..10_u32 {
+            many.record(&TestResult {
+                message: Some(&format!("reason {i}")),
+                ..TestResult::new("x", TestOutcome::Skipped)
+            });
+        }
+        let truncated = SkipSummary::new(&many);
+        assert_eq!(truncated.top.len(), SkipSummary::MAX_REASONS);
+        assert_eq!(truncated.omitted, 3);
+
+        let summary = SkipSummary::new(&stats());
+        insta::assert_snapshot!(<SkipSummary as CiMessage<Plain>>::format(&summary), @"
+        Skipped tests
+        Count  Reason
+            2  requires docker
+            2  (no reason given)
+            1  flaky #123
+        5 of 8 test(s) skipped (62%)
+        ");
+        insta::assert_snapshot!(<SkipSummary as CiMessage<GitHub>>::format(&summary), @"
+        ::group::Skipped tests
+        Count  Reason
+            2  requires docker
+            2  (no reason given)
+            1  flaky #123
+        5 of 8 test(s) skipped (62%)
+        ::endgroup::
+        ");
+    }
+}
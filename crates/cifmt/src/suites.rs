@@ -0,0 +1,211 @@
+//! Results of each test suite run.
+//!
+//! Test harnesses run the tests of each test binary as a suite, and report
+//! the number of tests which passed, failed and were ignored once the suite
+//! finishes (e.g., libtest's `suite` events). The results of each suite,
+//! along with the failed tests it ran, are collected into [`SuiteStats`] and
+//! rendered as a table in the job summary (see
+//! [`JobSummary`](crate::markdown::JobSummary)), so that a failure can be
+//! traced back to its suite without searching the log.
+
+use core::{mem, time::Duration};
+
+use crate::junit::{TestOutcome, TestResult};
+
+/// The result of a test suite, as reported by a message.
+///
+/// See [`Classify::suite_result`](crate::ci_message::Classify::suite_result).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SuiteResult {
+    /// Number of tests passed.
+    pub passed: usize,
+    /// Number of tests failed.
+    pub failed: usize,
+    /// Number of tests ignored.
+    pub ignored: usize,
+    /// How long the suite took to run, if known.
+    pub duration: Option<Duration>,
+}
+
+impl SuiteResult {
+    /// Create a suite result without a duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `passed` - Number of tests passed.
+    /// * `failed` - Number of tests failed.
+    /// * `ignored` - Number of tests ignored.
+    #[must_use]
+    #[inline]
+    pub const fn new(passed: usize, failed: usize, ignored: usize) -> Self {
+        Self {
+            passed,
+            failed,
+            ignored,
+            duration: None,
+        }
+    }
+}
+
+/// A failed test within a [`Suite`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FailedTest {
+    /// The full name of the test.
+    pub name: String,
+    /// The last [`SuiteStats::MAX_DETAIL_LINES`] lines of the test's output,
+    /// or else its message, if any.
+    pub details: Option<String>,
+    /// Number of lines of the output omitted from the details.
+    pub omitted: usize,
+}
+
+/// A finished test suite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Suite {
+    /// The result reported by the suite.
+    pub result: SuiteResult,
+    /// The tests which failed within the suite, in the order reported.
+    pub failures: Vec<FailedTest>,
+}
+
+/// The results of the test suites run, in the order in which they finished.
+///
+/// Harnesses such as libtest do not name their suites, which are instead
+/// numbered from 1 in the order in which they finished.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SuiteStats {
+    /// The suites which finished.
+    suites: Vec<Suite>,
+    /// The tests which failed within the suite still running.
+    pending: Vec<FailedTest>,
+}
+
+impl SuiteStats {
+    /// The maximum number of lines of a failed test's output kept.
+    pub const MAX_DETAIL_LINES: usize = 50;
+
+    /// Record the result of a test, within the suite still running.
+    ///
+    /// Only failed tests are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the test.
+    #[inline]
+    pub fn record_test(&mut self, result: &TestResult<'_>) {
+        if result.outcome != TestOutcome::Failed {
+            return;
+        }
+        let (details, omitted) = match result.output.filter(|output| !output.trim().is_empty()) {
+            Some(output) => {
+                let lines: Vec<_> = output
+                    .trim_end()
+                    .lines()
+                    .skip_while(|line| line.trim().is_empty())
+                    .collect();
+                let omitted = lines.len().saturating_sub(Self::MAX_DETAIL_LINES);
+                (
+                    Some(lines.get(omitted..).unwrap_or_default().join("\n")),
+                    omitted,
+                )
+            }
+            None => (result.message.map(str::to_owned), 0),
+        };
+        self.pending.push(FailedTest {
+            name: result.name.to_owned(),
+            details,
+            omitted,
+        });
+    }
+
+    /// Record the result of the suite still running, which owns the failed
+    /// tests recorded since the previous suite finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the suite.
+    #[inline]
+    pub fn record_suite(&mut self, result: SuiteResult) {
+        self.suites.push(Suite {
+            result,
+            failures: mem::take(&mut self.pending),
+        });
+    }
+
+    /// Whether no suite has finished.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.suites.is_empty()
+    }
+
+    /// The suites which finished, in order.
+    #[must_use]
+    #[inline]
+    pub fn suites(&self) -> &[Suite] {
+        &self.suites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{FailedTest, SuiteResult, SuiteStats};
+    use crate::junit::{TestOutcome, TestResult};
+
+    #[test]
+    fn record() {
+        let mut stats = SuiteStats::default();
+        assert!(stats.is_empty());
+
+        stats.record_test(&TestResult::new("tests::a", TestOutcome::Passed));
+        stats.record_test(&TestResult {
+            message: Some("timed out"),
+            ..TestResult::new("tests::b", TestOutcome::Failed)
+        });
+        stats.record_suite(SuiteResult {
+            duration: Some(Duration::from_secs(1)),
+            ..SuiteResult::new(1, 1, 0)
+        });
+        let output = (1_u32..=60)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        stats.record_test(&TestResult {
+            output: Some(&output),
+            ..TestResult::new("it_works", TestOutcome::Failed)
+        });
+        stats.record_suite(SuiteResult::new(0, 1, 0));
+
+        let [first, second] = stats.suites() else {
+            panic!("expected two suites, got {:?}", stats.suites());
+        };
+        assert_eq!(first.result.duration, Some(Duration::from_secs(1)));
+        assert_eq!(
+            first.failures,
+            [FailedTest {
+                name: "tests::b".to_owned(),
+                details: Some("timed out".to_owned()),
+                omitted: 0,
+            }]
+        );
+        let [failure] = second.failures.as_slice() else {
+            panic!("expected one failure, got {:?}", second.failures);
+        };
+        assert_eq!(failure.omitted, 10);
+        assert_eq!(
+            failure
+                .details
+                .as_deref()
+                .and_then(|details| details.lines().next()),
+            Some("line 11")
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Support for testing platform implementations against recorded output.
+//!
+//! A platform must render every kind of message of every tool. Rather than
+//! building messages by hand, the [`CORPORA`] hold the output recorded from
+//! each tool (the recordings in `corpora`, regenerated by `corpora/generate`
+//! and shared with the CLI tests), and [`render`] formats every message
+//! parsed from a corpus for a platform, so that the result can be compared
+//! against a snapshot. [`matrix`] renders every corpus for every platform, so
+//! that a single test covers them all:
+//!
+//! ```
+//! use cifmt::test_util;
+//!
+//! for rendering in test_util::matrix().expect("every corpus is detected") {
+//!     assert!(!rendering.output.is_empty(), "{} renders nothing", rendering.name());
+//!     // e.g., insta::assert_snapshot!(rendering.name(), rendering.output);
+//! }
+//! ```
+//!
+//! This module is available with the `test-util` feature.
+
+use crate::{ci::PlatformKind, tool};
+
+/// The output recorded from a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Corpus {
+    /// The name of the tool (e.g., `cargo-check`), as detected from the
+    /// output.
+    pub tool: &'static str,
+    /// The recorded output.
+    pub input: &'static str,
+}
+
+/// The output recorded from each tool.
+pub const CORPORA: [Corpus; 23] = [
+    Corpus {
+        tool: "cargo-check",
+        input: include_str!("../corpora/cargo-check.in"),
+    },
+    Corpus {
+        tool: "cargo-libtest",
+        input: include_str!("../corpora/cargo-libtest.in"),
+    },
+    Corpus {
+        tool: "cargo-fmt",
+        input: include_str!("../corpora/cargo-fmt.in"),
+    },
+    Corpus {
+        tool: "cargo-doc",
+        input: include_str!("../corpora/cargo-doc.in"),
+    },
+    Corpus {
+        tool: "lcov",
+        input: include_str!("../corpora/lcov.in"),
+    },
+    Corpus {
+        tool: "cargo-udeps",
+        input: include_str!("../corpora/cargo-udeps.in"),
+    },
+    Corpus {
+        tool: "miri",
+        input: include_str!("../corpora/miri.in"),
+    },
+    Corpus {
+        tool: "rustc-human",
+        input: include_str!("../corpora/rustc-human.in"),
+    },
+    Corpus {
+        tool: "junit",
+        input: include_str!("../corpora/junit.in"),
+    },
+    Corpus {
+        tool: "gcc-diagnostics",
+        input: include_str!("../corpora/gcc-diagnostics.in"),
+    },
+    Corpus {
+        tool: "cppcheck",
+        input: include_str!("../corpora/cppcheck.in"),
+    },
+    Corpus {
+        tool: "cmake",
+        input: include_str!("../corpora/cmake.in"),
+    },
+    Corpus {
+        tool: "pytest",
+        input: include_str!("../corpora/pytest.in"),
+    },
+    Corpus {
+        tool: "black",
+        input: include_str!("../corpora/black.in"),
+    },
+    Corpus {
+        tool: "bandit",
+        input: include_str!("../corpora/bandit.in"),
+    },
+    Corpus {
+        tool: "surefire",
+        input: include_str!("../corpora/surefire.in"),
+    },
+    Corpus {
+        tool: "pmd",
+        input: include_str!("../corpora/pmd.in"),
+    },
+    Corpus {
+        tool: "psalm",
+        input: include_str!("../corpora/psalm.in"),
+    },
+    Corpus {
+        tool: "eslint",
+        input: include_str!("../corpora/eslint.in"),
+    },
+    Corpus {
+        tool: "phpunit",
+        input: include_str!("../corpora/phpunit.in"),
+    },
+    Corpus {
+        tool: "xcodebuild",
+        input: include_str!("../corpora/xcodebuild.in"),
+    },
+    Corpus {
+        tool: "osv-scanner",
+        input: include_str!("../corpora/osv-scanner.in"),
+    },
+    Corpus {
+        tool: "lsp-diagnostics",
+        input: include_str!("../corpora/lsp-diagnostics.in"),
+    },
+];
+
+/// The messages of a corpus, rendered for a platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Rendering {
+    /// The corpus.
+    pub corpus: Corpus,
+    /// The platform.
+    pub platform: PlatformKind,
+    /// The formatted messages, one after the other.
+    pub output: String,
+}
+
+impl Rendering {
+    /// A name identifying the rendering (e.g., `cargo-check@github`), suitable
+    /// for naming its snapshot.
+    #[must_use]
+    #[inline]
+    pub fn name(&self) -> String {
+        format!("{}@{}", self.corpus.tool, self.platform)
+    }
+}
+
+/// Join the formatted messages, skipping those which render nothing on the
+/// platform.
+fn join(formatted: impl IntoIterator<Item = String>) -> String {
+    formatted
+        .into_iter()
+        .filter(|message| !message.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format every message of a corpus for a platform.
+///
+/// The tool is detected from the corpus, as with `cifmt format --detect`.
+///
+/// # Arguments
+///
+/// * `corpus` - The corpus.
+/// * `platform` - The platform.
+///
+/// # Errors
+///
+/// Returns an error if no tool is detected from the corpus.
+#[inline]
+pub fn render(corpus: &Corpus, platform: PlatformKind) -> Result<String, tool::Error> {
+    let mut tool = tool::detect_any(corpus.input.as_bytes())?;
+    let mut messages = tool.parse_dyn(corpus.input.as_bytes());
+    messages.extend(tool.finish_dyn());
+    Ok(join(
+        messages.iter().map(|message| message.format_for(platform)),
+    ))
+}
+
+/// Format every corpus for every platform.
+///
+/// # Errors
+///
+/// Returns an error if no tool is detected from a corpus.
+#[inline]
+pub fn matrix() -> Result<Vec<Rendering>, tool::Error> {
+    let mut renderings = Vec::new();
+    for corpus in CORPORA {
+        for platform in PlatformKind::ALL {
+            renderings.push(Rendering {
+                corpus,
+                platform,
+                output: render(&corpus, platform)?,
+            });
+        }
+    }
+    Ok(renderings)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{CORPORA, join, matrix, render};
+    use crate::ci::{GitHub, PlatformKind};
+    use crate::tool;
+
+    #[test]
+    fn detected() {
+        for corpus in CORPORA {
+            let detected = tool::detect_any(corpus.input.as_bytes()).expect("tool not detected");
+            assert_eq!(detected.name(), corpus.tool);
+        }
+    }
+
+    #[test]
+    fn generic() {
+        for corpus in CORPORA {
+            let mut tool =
+                tool::detect::<GitHub>(corpus.input.as_bytes()).expect("tool not detected");
+            let mut formatted = tool.parse_and_format(corpus.input.as_bytes());
+            formatted.extend(tool.finish_and_format());
+            assert_eq!(
+                join(formatted),
+                render(&corpus, PlatformKind::GitHub).expect("tool not detected")
+            );
+        }
+    }
+
+    #[test]
+    fn snapshots() {
+        let renderings = matrix().expect("tool not detected");
+        assert_eq!(
+            renderings.len(),
+            CORPORA.len().saturating_mul(PlatformKind::ALL.len())
+        );
+        for rendering in renderings {
+            insta::assert_snapshot!(rendering.name(), rendering.output);
+        }
+    }
+}
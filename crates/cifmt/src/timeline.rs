@@ -0,0 +1,462 @@
+//! Structured timing data for build and test phases.
+//!
+//! Several tools report how long individual phases took: rustc reports the
+//! start and end of compilation sections, cargo reports how long each unit
+//! took to build, and libtest reports the execution time of each test. This
+//! module collects these into a single [`Timeline`], which can be exported in
+//! the [Chrome trace-event
+//! format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU)
+//! and opened in viewers such as [Perfetto](https://ui.perfetto.dev) or
+//! `chrome://tracing`.
+
+use core::time::Duration;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
+
+use serde::Serialize;
+
+/// Timing information carried by a single message.
+///
+/// Timing information is either a point in time at which a section starts or
+/// ends (in which case the section's start and end are matched by the
+/// [`Timeline`]), or the elapsed time of something which has just completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Timing<'a> {
+    /// Start of a section.
+    SectionStart {
+        /// The track (e.g., crate name) to which the section belongs.
+        track: &'a str,
+        /// The name of the section.
+        name: &'a str,
+        /// The time at which the section started, according to the tool's
+        /// own clock.
+        time: Duration,
+    },
+    /// End of a section.
+    SectionEnd {
+        /// The track (e.g., crate name) to which the section belongs.
+        track: &'a str,
+        /// The name of the section.
+        name: &'a str,
+        /// The time at which the section ended, according to the tool's own
+        /// clock.
+        time: Duration,
+    },
+    /// Something which has just completed, having taken the given amount of
+    /// time.
+    Elapsed {
+        /// The track (e.g., crate or test name) to which the span belongs.
+        track: &'a str,
+        /// The name of the span.
+        name: &'a str,
+        /// The category of the span (e.g., `build` or `test`).
+        category: &'a str,
+        /// How long it took.
+        duration: Duration,
+    },
+}
+
+/// Trait for messages which may carry timing information.
+pub trait Timed {
+    /// The timing information carried by this message, if any.
+    ///
+    /// Defaults to `None`.
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        None
+    }
+}
+
+/// A completed span of time within a [`Timeline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Span {
+    /// The track (e.g., crate or test name) to which the span belongs.
+    pub track: String,
+    /// The name of the span.
+    pub name: String,
+    /// The category of the span.
+    pub category: String,
+    /// The start of the span, relative to the start of the timeline.
+    pub start: Duration,
+    /// How long the span lasted.
+    pub duration: Duration,
+}
+
+/// A collection of timed spans.
+///
+/// Spans are recorded from [`Timing`] information along with the time at
+/// which the message was observed, relative to the start of the timeline.
+/// This observation time is used to place spans which only report their
+/// duration (the span is assumed to have ended when it was observed), and to
+/// align sections reported against a tool's own clock.
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    /// The completed spans.
+    spans: Vec<Span>,
+    /// Sections which have started but not yet ended, keyed by track and name.
+    open: HashMap<(String, String), Duration>,
+    /// Offset used to convert section times to timeline times.
+    ///
+    /// This is the first section time encountered, paired with the time at
+    /// which it was observed.
+    section_epoch: Option<(Duration, Duration)>,
+    /// Metadata describing the run, written with the trace.
+    metadata: Vec<(String, String)>,
+}
+
+impl Timeline {
+    /// Create an empty timeline.
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the metadata describing the run (e.g., the properties of a
+    /// [`RunMetadata`](crate::metadata::RunMetadata)), written to the
+    /// `otherData` of the Chrome trace.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The name and value of each property.
+    #[inline]
+    pub fn set_metadata(
+        &mut self,
+        metadata: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) {
+        self.metadata = metadata
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+    }
+
+    /// Record timing information.
+    ///
+    /// # Arguments
+    ///
+    /// * `timing` - The timing information to record.
+    /// * `observed` - The time at which the timing information was observed,
+    ///   relative to the start of the timeline.
+    #[inline]
+    pub fn record(&mut self, timing: Timing<'_>, observed: Duration) {
+        match timing {
+            Timing::SectionStart { track, name, time } => {
+                let start = self.section_time(time, observed);
+                self.open.insert((track.to_owned(), name.to_owned()), start);
+            }
+            Timing::SectionEnd { track, name, time } => {
+                let end = self.section_time(time, observed);
+                let key = (track.to_owned(), name.to_owned());
+                if let Some(start) = self.open.remove(&key) {
+                    let (span_track, span_name) = key;
+                    self.spans.push(Span {
+                        track: span_track,
+                        name: span_name,
+                        category: "section".to_owned(),
+                        start,
+                        duration: end.saturating_sub(start),
+                    });
+                } else {
+                    tracing::debug!("Ignoring end of unknown section {name} in {track}");
+                }
+            }
+            Timing::Elapsed {
+                track,
+                name,
+                category,
+                duration,
+            } => self.spans.push(Span {
+                track: track.to_owned(),
+                name: name.to_owned(),
+                category: category.to_owned(),
+                start: observed.saturating_sub(duration),
+                duration,
+            }),
+        }
+    }
+
+    /// Convert a time from a tool's own clock to a timeline time.
+    fn section_time(&mut self, time: Duration, observed: Duration) -> Duration {
+        let &mut (epoch, offset) = self.section_epoch.get_or_insert((time, observed));
+        offset.saturating_add(time.saturating_sub(epoch))
+    }
+
+    /// The completed spans, in the order in which they were completed.
+    #[must_use]
+    #[inline]
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Whether the timeline contains no completed spans.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Write the timeline in the Chrome trace-event JSON format.
+    ///
+    /// Each track is rendered as a separate thread within a single process.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the trace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use cifmt::timeline::{Timeline, Timing};
+    ///
+    /// let mut timeline = Timeline::new();
+    /// timeline.record(
+    ///     Timing::Elapsed {
+    ///         track: "tests::it_works",
+    ///         name: "tests::it_works",
+    ///         category: "test",
+    ///         duration: Duration::from_millis(5),
+    ///     },
+    ///     Duration::from_millis(10),
+    /// );
+    ///
+    /// let mut trace = Vec::new();
+    /// timeline.write_chrome_trace(&mut trace).expect("writing to a Vec cannot fail");
+    /// ```
+    #[inline]
+    pub fn write_chrome_trace(&self, writer: impl Write) -> serde_json::Result<()> {
+        let mut tracks: Vec<&str> = Vec::new();
+        let mut events = Vec::with_capacity(self.spans.len());
+
+        for span in &self.spans {
+            let tid = if let Some(tid) = tracks.iter().position(|&t| t == span.track) {
+                tid
+            } else {
+                tracks.push(&span.track);
+                tracks.len().saturating_sub(1)
+            };
+            events.push(TraceEvent {
+                name: &span.name,
+                cat: Some(&span.category),
+                ph: "X",
+                ts: Some(span.start.as_micros()),
+                dur: Some(span.duration.as_micros()),
+                pid: 1,
+                tid,
+                args: None,
+            });
+        }
+
+        let metadata = tracks.iter().enumerate().map(|(tid, &track)| TraceEvent {
+            name: "thread_name",
+            cat: None,
+            ph: "M",
+            ts: None,
+            dur: None,
+            pid: 1,
+            tid,
+            args: Some(ThreadName { name: track }),
+        });
+
+        let trace = Trace {
+            events: metadata.chain(events).collect(),
+            display_time_unit: "ms",
+            other_data: self
+                .metadata
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect(),
+        };
+        serde_json::to_writer(writer, &trace)
+    }
+}
+
+/// Top-level Chrome trace object.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    /// The trace events.
+    #[serde(rename = "traceEvents")]
+    events: Vec<TraceEvent<'a>>,
+    /// The unit in which times are displayed.
+    display_time_unit: &'a str,
+    /// Metadata describing the run.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    other_data: BTreeMap<&'a str, &'a str>,
+}
+
+/// A single Chrome trace event.
+#[derive(Serialize)]
+struct TraceEvent<'a> {
+    /// The event name.
+    name: &'a str,
+    /// The event category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cat: Option<&'a str>,
+    /// The event phase (`X` for complete events, `M` for metadata).
+    ph: &'a str,
+    /// The event timestamp in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<u128>,
+    /// The event duration in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u128>,
+    /// The process ID.
+    pid: usize,
+    /// The thread ID.
+    tid: usize,
+    /// Additional arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<ThreadName<'a>>,
+}
+
+/// Arguments of a `thread_name` metadata event.
+#[derive(Serialize)]
+struct ThreadName<'a> {
+    /// The thread name.
+    name: &'a str,
+}
+
+/// Budgets for the duration of test suites, beyond which the completion of a
+/// suite is reported as a warning rather than a notice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TimeBudget {
+    /// The maximum duration of a single suite, if any.
+    pub suite: Option<Duration>,
+    /// The maximum total duration of all suites in the run, if any.
+    pub total: Option<Duration>,
+}
+
+impl TimeBudget {
+    /// Create budgets for the duration of test suites.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite` - The maximum duration of a single suite, if any.
+    /// * `total` - The maximum total duration of all suites, if any.
+    #[must_use]
+    #[inline]
+    pub fn new(suite: Option<Duration>, total: Option<Duration>) -> Self {
+        Self { suite, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Timeline, Timing};
+
+    #[test]
+    fn elapsed() {
+        let mut timeline = Timeline::new();
+        timeline.record(
+            Timing::Elapsed {
+                track: "t",
+                name: "t",
+                category: "test",
+                duration: Duration::from_millis(3),
+            },
+            Duration::from_millis(10),
+        );
+
+        let span = timeline.spans().first().expect("span recorded");
+        assert_eq!(span.start, Duration::from_millis(7));
+        assert_eq!(span.duration, Duration::from_millis(3));
+    }
+
+    #[test]
+    fn sections() {
+        let mut timeline = Timeline::new();
+        let section = |start: bool, micros: u64| {
+            let time = Duration::from_micros(micros);
+            if start {
+                Timing::SectionStart {
+                    track: "mylib",
+                    name: "codegen",
+                    time,
+                }
+            } else {
+                Timing::SectionEnd {
+                    track: "mylib",
+                    name: "codegen",
+                    time,
+                }
+            }
+        };
+
+        timeline.record(section(true, 1_000_000), Duration::from_millis(5));
+        assert!(timeline.is_empty());
+        timeline.record(section(false, 1_002_000), Duration::from_millis(50));
+
+        // The section is aligned to the first observation, and its duration is
+        // taken from the tool's own clock.
+        let span = timeline.spans().first().expect("span recorded");
+        assert_eq!(span.start, Duration::from_millis(5));
+        assert_eq!(span.duration, Duration::from_millis(2));
+    }
+
+    #[test]
+    fn unmatched_section_end() {
+        let mut timeline = Timeline::new();
+        timeline.record(
+            Timing::SectionEnd {
+                track: "mylib",
+                name: "codegen",
+                time: Duration::from_micros(10),
+            },
+            Duration::ZERO,
+        );
+        assert!(timeline.is_empty());
+    }
+
+    #[test]
+    fn chrome_trace() {
+        let mut timeline = Timeline::new();
+        for (name, duration, observed) in [("a", 2, 4), ("b", 1, 5), ("a", 1, 6)] {
+            timeline.record(
+                Timing::Elapsed {
+                    track: name,
+                    name,
+                    category: "test",
+                    duration: Duration::from_millis(duration),
+                },
+                Duration::from_millis(observed),
+            );
+        }
+
+        let mut buf = Vec::new();
+        timeline
+            .write_chrome_trace(&mut buf)
+            .expect("serialization failed");
+        let trace: serde_json::Value = serde_json::from_slice(&buf).expect("invalid JSON");
+        insta::assert_json_snapshot!(trace);
+    }
+
+    #[test]
+    fn chrome_trace_metadata() {
+        let mut timeline = Timeline::new();
+        timeline.set_metadata([("tool", "cargo-check")]);
+
+        let mut buf = Vec::new();
+        timeline
+            .write_chrome_trace(&mut buf)
+            .expect("serialization failed");
+        let trace: serde_json::Value = serde_json::from_slice(&buf).expect("invalid JSON");
+        assert_eq!(
+            trace.pointer("/otherData/tool"),
+            Some(&serde_json::Value::from("cargo-check"))
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Idle timeout on the tool's output.
+//!
+//! If the tool producing the output hangs, reading its output blocks forever,
+//! and so does the CI job. An [`IdleReader`] bounds the time spent waiting for
+//! the next chunk of output, failing the read with an [`IdleTimeout`] once it
+//! elapses. [`Pipeline::run`](crate::pipeline::Pipeline::run) reports such a
+//! failure through an [`IdleTimeout`] message and stops reading, so that the
+//! pipeline can be finished cleanly.
+
+use core::{fmt, time::Duration};
+use std::{
+    io::{self, Read},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+};
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    pipeline::read_chunks,
+    timeline::Timed,
+};
+
+/// A reader which fails if no input is received within a timeout.
+///
+/// The underlying reader is read on a background thread. Once the timeout
+/// elapses without any input, reads fail with an error of kind
+/// [`io::ErrorKind::TimedOut`] wrapping an [`IdleTimeout`] (see
+/// [`IdleTimeout::from_error`]). The background thread remains blocked on the
+/// underlying reader until it returns, or until the process exits.
+///
+/// # Example
+///
+/// ```
+/// use std::{io::Read, time::Duration};
+///
+/// use cifmt::timeout::IdleReader;
+///
+/// let mut reader = IdleReader::new(&b"output"[..], Duration::from_secs(30));
+/// let mut output = String::new();
+/// reader.read_to_string(&mut output).expect("input is available");
+/// assert_eq!(output, "output");
+/// ```
+#[derive(Debug)]
+pub struct IdleReader {
+    /// Chunks read by the background thread.
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    /// The chunk currently being read from.
+    chunk: Vec<u8>,
+    /// The position of the next unread byte within the chunk.
+    position: usize,
+    /// The maximum time to wait for the next chunk.
+    timeout: Duration,
+}
+
+impl IdleReader {
+    /// Create a reader which fails if no input is received within a timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The underlying reader, which is read on a background
+    ///   thread.
+    /// * `timeout` - The maximum time to wait for the next chunk of input.
+    #[must_use]
+    #[inline]
+    pub fn new(reader: impl Read + Send + 'static, timeout: Duration) -> Self {
+        // A single chunk is read ahead, so that the time spent waiting on the
+        // consumer does not count towards the timeout.
+        let (tx, rx) = mpsc::sync_channel(1);
+        thread::spawn(move || read_chunks(reader, &tx));
+        Self {
+            chunks: rx,
+            chunk: Vec::new(),
+            position: 0,
+            timeout,
+        }
+    }
+}
+
+impl Read for IdleReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.chunk.len() {
+            match self.chunks.recv_timeout(self.timeout) {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.position = 0;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        IdleTimeout {
+                            timeout: self.timeout,
+                        },
+                    ));
+                }
+                // The background thread stops at the end of input.
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+            }
+        }
+
+        let remaining = self.chunk.get(self.position..).unwrap_or_default();
+        let n = remaining.len().min(buf.len());
+        buf.get_mut(..n)
+            .unwrap_or_default()
+            .copy_from_slice(remaining.get(..n).unwrap_or_default());
+        self.position = self.position.saturating_add(n);
+        Ok(n)
+    }
+}
+
+/// An error that no output was received from the tool within the idle
+/// timeout, which suggests that the tool hung.
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "IdleTimeout distinguishes the timeout from that of a test"
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IdleTimeout {
+    /// The time for which no output was received.
+    pub timeout: Duration,
+}
+
+impl IdleTimeout {
+    /// Get the idle timeout which caused a read to fail, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - An error returned by an [`IdleReader`].
+    #[must_use]
+    #[inline]
+    pub fn from_error(error: &io::Error) -> Option<&Self> {
+        if error.kind() != io::ErrorKind::TimedOut {
+            return None;
+        }
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Display for IdleTimeout {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "No output received for {}s; the tool may have hung, and its output is incomplete",
+            self.timeout.as_secs_f64()
+        )
+    }
+}
+
+impl core::error::Error for IdleTimeout {}
+
+impl<P: Platform> CiMessage<P> for IdleTimeout {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            self.to_string(),
+            None,
+            Some(Title::Timeout.text()),
+        )
+    }
+}
+
+impl Classify for IdleTimeout {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
+
+impl Timed for IdleTimeout {}
+
+impl Captured for IdleTimeout {}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::{
+        io::{self, Read},
+        thread,
+    };
+
+    use pretty_assertions::assert_eq;
+
+    use super::{IdleReader, IdleTimeout};
+    use crate::ci::{AzureDevOps, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    /// A reader which produces a single chunk, and then hangs.
+    struct HangingReader(bool);
+
+    impl Read for HangingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0 {
+                // Far longer than the timeout of the test.
+                thread::sleep(Duration::from_hours(1));
+                return Ok(0);
+            }
+            self.0 = true;
+            let chunk = b"first\n";
+            buf.get_mut(..chunk.len())
+                .expect("buffer too small")
+                .copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn times_out() {
+        let timeout = Duration::from_millis(50);
+        let mut reader = IdleReader::new(HangingReader(false), timeout);
+
+        let mut buf = [0; 16];
+        let n = reader.read(&mut buf).expect("first chunk is available");
+        assert_eq!(buf.get(..n), Some(&b"first\n"[..]));
+
+        let err = reader.read(&mut buf).expect_err("reader hangs");
+        assert_eq!(
+            IdleTimeout::from_error(&err),
+            Some(&IdleTimeout { timeout })
+        );
+    }
+
+    #[test]
+    fn from_other_error() {
+        let err = io::Error::new(io::ErrorKind::TimedOut, "socket timed out");
+        assert_eq!(IdleTimeout::from_error(&err), None);
+    }
+
+    #[test]
+    fn format() {
+        let message = IdleTimeout {
+            timeout: Duration::from_secs(30),
+        };
+        insta::assert_snapshot!(<IdleTimeout as CiMessage<Plain>>::format(&message), @"error:   Timeout: No output received for 30s; the tool may have hung, and its output is incomplete");
+        insta::assert_snapshot!(<IdleTimeout as CiMessage<GitHub>>::format(&message), @"::error title=Timeout::No output received for 30s; the tool may have hung, and its output is incomplete");
+        insta::assert_snapshot!(
+            <IdleTimeout as CiMessage<AzureDevOps>>::format(&message),
+            @"##vso[task.logissue type=error]Timeout: No output received for 30s; the tool may have hung, and its output is incomplete"
+        );
+    }
+}
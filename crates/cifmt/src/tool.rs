@@ -6,13 +6,76 @@
 
 #![expect(clippy::pub_use, reason = "convenience re-exports of tool types")]
 
-use crate::ci::Platform;
+use core::{fmt, str::FromStr};
 
+use crate::{
+    capture::OutputPolicy,
+    ci::{Phase, Platform},
+    ci_message::DynCiMessage,
+};
+
+mod bandit;
+mod black;
 mod cargo_check;
+mod cargo_doc;
+mod cargo_fmt;
 mod cargo_libtest;
+mod cargo_udeps;
+mod cmake;
+mod cppcheck;
+mod eslint;
+mod gcc_diagnostics;
+mod junit;
+mod lcov;
+mod line_framer;
+mod lsp_diagnostics;
+mod miri;
+mod options;
+mod osv_scanner;
+mod overlong_line;
+mod passthrough;
+mod phpunit;
+mod pmd;
+mod psalm;
+mod pytest;
+mod rustc_human;
+mod surefire;
+mod truncated_line;
+mod xcodebuild;
+mod xml;
 
+pub use bandit::Bandit;
+pub use black::Black;
 pub use cargo_check::CargoCheck;
+pub use cargo_doc::CargoDoc;
+pub use cargo_fmt::CargoFmt;
 pub use cargo_libtest::CargoLibtest;
+pub use cargo_udeps::CargoUdeps;
+pub use cmake::Cmake;
+pub use cppcheck::Cppcheck;
+pub use eslint::Eslint;
+pub use gcc_diagnostics::GccDiagnostics;
+pub use junit::Junit;
+pub use lcov::Lcov;
+pub use line_framer::DEFAULT_MAX_LINE_LENGTH;
+pub use lsp_diagnostics::LspDiagnostics;
+pub use miri::Miri;
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "ToolOption is clearer than Option, which it would otherwise shadow"
+)]
+pub use options::{ToolOption, ToolOptions};
+pub use osv_scanner::OsvScanner;
+pub use overlong_line::OverlongLine;
+pub use passthrough::{Passthrough, TextOutput};
+pub use phpunit::Phpunit;
+pub use pmd::Pmd;
+pub use psalm::Psalm;
+pub use pytest::Pytest;
+pub use rustc_human::RustcHuman;
+pub use surefire::Surefire;
+pub use truncated_line::TruncatedLine;
+pub use xcodebuild::Xcodebuild;
 
 /// Trait for types that can detect a tool format from sample output.
 pub trait Detect {
@@ -57,6 +120,16 @@ pub trait Tool {
     /// Get the tool name as a string.
     fn name(&self) -> &'static str;
 
+    /// The phase of a CI job in which the tool runs.
+    ///
+    /// On platforms whose logs are filtered by phase (i.e., AWS CodeBuild),
+    /// each line written for the tool's messages is prefixed with it.
+    /// Defaults to [`Phase::Build`]; test runners return [`Phase::Test`].
+    #[inline]
+    fn phase(&self) -> Phase {
+        Phase::Build
+    }
+
     /// Parse messages from the tool's output.
     ///
     /// The parser is expected to read the entire buffer and extract all
@@ -78,6 +151,86 @@ pub trait Tool {
     /// A vector of results, each being either a successfully parsed message or
     /// an error if parsing failed for that message.
     fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>>;
+
+    /// Finish parsing at the end of the tool's output.
+    ///
+    /// This is called once no more data is available. Tools which buffer
+    /// their output must parse any remaining data here (e.g., a final line
+    /// without a terminating newline), and may also report messages which
+    /// only become known at the end of the output (e.g., a test suite which
+    /// never finished). A final message which was cut off part way through
+    /// should be reported as a [`TruncatedLine`] rather than as an error.
+    ///
+    /// # Returns
+    ///
+    /// A vector of results, as for [`Tool::parse`].
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        Vec::new()
+    }
+
+    /// Set the maximum length of a single line of the tool's output.
+    ///
+    /// Tools which split their output into lines cut lines exceeding this
+    /// length, on the boundary of a UTF-8 character, and report an
+    /// [`OverlongLine`] before parsing what was kept, so that a tool which
+    /// never emits a newline cannot exhaust the available memory. Defaults to
+    /// [`DEFAULT_MAX_LINE_LENGTH`].
+    ///
+    /// Tools which do not buffer their output by line may ignore this.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum length of a line in bytes.
+    #[inline]
+    fn set_max_line_length(&mut self, _max: usize) {}
+
+    /// Set the policy for lines of the tool's output which are not
+    /// structured messages.
+    ///
+    /// Such lines are reported as [`TextOutput`] according to the policy.
+    /// Defaults to [`Passthrough::Drop`].
+    ///
+    /// Tools whose output consists solely of structured messages may ignore
+    /// this.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy applied to each such line.
+    #[inline]
+    fn set_passthrough(&mut self, _policy: Passthrough) {}
+
+    /// Set the policy for the inclusion of output captured by the tool
+    /// (e.g., the stdout of a test) and of other free-form text accompanying
+    /// a result (e.g., the reason a test was ignored).
+    ///
+    /// Defaults to [`OutputPolicy::Always`]. Tools which capture no output
+    /// may ignore this.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy applied to each message.
+    #[inline]
+    fn set_output_policy(&mut self, _policy: OutputPolicy) {}
+
+    /// The options which the tool supports (see [`Tool::set_options`]).
+    ///
+    /// Defaults to none.
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        &[]
+    }
+
+    /// Set the options tuning the tool's messages.
+    ///
+    /// Tools ignore the options which they do not support (see
+    /// [`Tool::supported_options`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options.
+    #[inline]
+    fn set_options(&mut self, _options: &ToolOptions) {}
 }
 
 /// Dynamic tool wrapper that combines parsing and formatting.
@@ -96,6 +249,130 @@ pub trait DynTool<P: Platform> {
     ///
     /// Returns formatted strings ready for output to the specified platform.
     fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String>;
+
+    /// Finish parsing at the end of the tool's output, and format the
+    /// remaining messages.
+    ///
+    /// See [`Tool::finish`].
+    fn finish_and_format(&mut self) -> Vec<String>;
+}
+
+/// Platform-agnostic dynamic tool wrapper.
+///
+/// Unlike [`DynTool`], which formats messages for a single platform fixed at
+/// compile time, this trait only parses messages. The resulting messages can
+/// then be formatted for any number of platforms at runtime through
+/// [`DynCiMessage`].
+///
+/// This trait is implemented automatically for every [`Send`] [`Tool`] whose
+/// messages implement [`DynCiMessage`].
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "AnyTool is a clear name for a trait that erases both the tool and platform"
+)]
+pub trait AnyTool: Send {
+    /// Get the tool name.
+    fn name(&self) -> &'static str;
+
+    /// The phase of a CI job in which the tool runs.
+    ///
+    /// See [`Tool::phase`].
+    fn phase(&self) -> Phase;
+
+    /// Parse messages from the tool's output.
+    ///
+    /// Messages which fail to parse are skipped.
+    fn parse_dyn(&mut self, buf: &[u8]) -> Vec<Box<dyn DynCiMessage>>;
+
+    /// Finish parsing at the end of the tool's output.
+    ///
+    /// See [`Tool::finish`]. Messages which fail to parse are skipped.
+    fn finish_dyn(&mut self) -> Vec<Box<dyn DynCiMessage>>;
+
+    /// Set the maximum length of a single line of the tool's output.
+    ///
+    /// See [`Tool::set_max_line_length`].
+    fn set_max_line_length(&mut self, max: usize);
+
+    /// Set the policy for lines of the tool's output which are not
+    /// structured messages.
+    ///
+    /// See [`Tool::set_passthrough`].
+    fn set_passthrough(&mut self, policy: Passthrough);
+
+    /// Set the policy for the inclusion of output captured by the tool.
+    ///
+    /// See [`Tool::set_output_policy`].
+    fn set_output_policy(&mut self, policy: OutputPolicy);
+
+    /// The options which the tool supports.
+    ///
+    /// See [`Tool::supported_options`].
+    fn supported_options(&self) -> &'static [ToolOption];
+
+    /// Set the options tuning the tool's messages.
+    ///
+    /// See [`Tool::set_options`].
+    fn set_options(&mut self, options: &ToolOptions);
+}
+
+impl<T> AnyTool for T
+where
+    T: Tool + Send,
+    T::Message: DynCiMessage + 'static,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn phase(&self) -> Phase {
+        Tool::phase(self)
+    }
+
+    #[inline]
+    fn parse_dyn(&mut self, buf: &[u8]) -> Vec<Box<dyn DynCiMessage>> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| -> Box<dyn DynCiMessage> { Box::new(msg) })
+            .collect()
+    }
+
+    #[inline]
+    fn finish_dyn(&mut self) -> Vec<Box<dyn DynCiMessage>> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| -> Box<dyn DynCiMessage> { Box::new(msg) })
+            .collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        Tool::set_max_line_length(self, max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        Tool::set_passthrough(self, policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        Tool::set_output_policy(self, policy);
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        Tool::supported_options(self)
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        Tool::set_options(self, options);
+    }
 }
 
 /// Errors that can occur during tool detection.
@@ -105,6 +382,181 @@ pub enum Error {
     /// No tool format detected in the provided buffer.
     #[error("No tool format detected")]
     NoToolDetected,
+    /// The name of a tool (e.g., `cargo-check`) which is not known.
+    #[error("Unknown tool: {0}")]
+    UnknownTool(String),
+}
+
+/// Runtime identifier for a supported tool.
+///
+/// The tools are distinct types, which makes them unsuitable when the tool is
+/// only known at runtime (for example, when it is named by an embedder). This
+/// enum fills that gap, as [`PlatformKind`](crate::ci::PlatformKind) does for
+/// platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "ToolKind mirrors the name of PlatformKind"
+)]
+pub enum ToolKind {
+    /// The [`CargoLibtest`] tool.
+    CargoLibtest,
+    /// The [`CargoCheck`] tool.
+    CargoCheck,
+    /// The [`CargoFmt`] tool.
+    CargoFmt,
+    /// The [`CargoDoc`] tool.
+    CargoDoc,
+    /// The [`Lcov`] tool.
+    Lcov,
+    /// The [`CargoUdeps`] tool.
+    CargoUdeps,
+    /// The [`Miri`] tool.
+    Miri,
+    /// The [`RustcHuman`] tool.
+    RustcHuman,
+    /// The [`Junit`] tool.
+    Junit,
+    /// The [`GccDiagnostics`] tool.
+    GccDiagnostics,
+    /// The [`Cppcheck`] tool.
+    Cppcheck,
+    /// The [`Cmake`] tool.
+    Cmake,
+    /// The [`Pytest`] tool.
+    Pytest,
+    /// The [`Black`] tool.
+    Black,
+    /// The [`Bandit`] tool.
+    Bandit,
+    /// The [`Surefire`] tool.
+    Surefire,
+    /// The [`Pmd`] tool.
+    Pmd,
+    /// The [`Psalm`] tool.
+    Psalm,
+    /// The [`Eslint`] tool.
+    Eslint,
+    /// The [`Phpunit`] tool.
+    Phpunit,
+    /// The [`Xcodebuild`] tool.
+    Xcodebuild,
+    /// The [`OsvScanner`] tool.
+    OsvScanner,
+    /// The [`LspDiagnostics`] tool.
+    LspDiagnostics,
+}
+
+impl ToolKind {
+    /// All tools.
+    pub const ALL: [Self; 23] = [
+        Self::CargoLibtest,
+        Self::CargoCheck,
+        Self::CargoFmt,
+        Self::CargoDoc,
+        Self::Lcov,
+        Self::CargoUdeps,
+        Self::Miri,
+        Self::RustcHuman,
+        Self::Junit,
+        Self::GccDiagnostics,
+        Self::Cppcheck,
+        Self::Cmake,
+        Self::Pytest,
+        Self::Black,
+        Self::Bandit,
+        Self::Surefire,
+        Self::Pmd,
+        Self::Psalm,
+        Self::Eslint,
+        Self::Phpunit,
+        Self::Xcodebuild,
+        Self::OsvScanner,
+        Self::LspDiagnostics,
+    ];
+
+    /// The name of the tool (e.g., `cargo-check`), as given by
+    /// [`Tool::name`].
+    #[must_use]
+    #[inline]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CargoLibtest => "cargo-libtest",
+            Self::CargoCheck => "cargo-check",
+            Self::CargoFmt => "cargo-fmt",
+            Self::CargoDoc => "cargo-doc",
+            Self::Lcov => "lcov",
+            Self::CargoUdeps => "cargo-udeps",
+            Self::Miri => "miri",
+            Self::RustcHuman => "rustc-human",
+            Self::Junit => "junit",
+            Self::GccDiagnostics => "gcc-diagnostics",
+            Self::Cppcheck => "cppcheck",
+            Self::Cmake => "cmake",
+            Self::Pytest => "pytest",
+            Self::Black => "black",
+            Self::Bandit => "bandit",
+            Self::Surefire => "surefire",
+            Self::Pmd => "pmd",
+            Self::Psalm => "psalm",
+            Self::Eslint => "eslint",
+            Self::Phpunit => "phpunit",
+            Self::Xcodebuild => "xcodebuild",
+            Self::OsvScanner => "osv-scanner",
+            Self::LspDiagnostics => "lsp-diagnostics",
+        }
+    }
+
+    /// Create the tool, with its default settings.
+    #[must_use]
+    #[inline]
+    pub fn into_any_tool(self) -> Box<dyn AnyTool> {
+        match self {
+            Self::CargoLibtest => Box::new(CargoLibtest::default()),
+            Self::CargoCheck => Box::new(CargoCheck::default()),
+            Self::CargoFmt => Box::new(CargoFmt::default()),
+            Self::CargoDoc => Box::new(CargoDoc::default()),
+            Self::Lcov => Box::new(Lcov::default()),
+            Self::CargoUdeps => Box::new(CargoUdeps::default()),
+            Self::Miri => Box::new(Miri::default()),
+            Self::RustcHuman => Box::new(RustcHuman::default()),
+            Self::Junit => Box::new(Junit::default()),
+            Self::GccDiagnostics => Box::new(GccDiagnostics::default()),
+            Self::Cppcheck => Box::new(Cppcheck::default()),
+            Self::Cmake => Box::new(Cmake::default()),
+            Self::Pytest => Box::new(Pytest::default()),
+            Self::Black => Box::new(Black::default()),
+            Self::Bandit => Box::new(Bandit::default()),
+            Self::Surefire => Box::new(Surefire::default()),
+            Self::Pmd => Box::new(Pmd::default()),
+            Self::Psalm => Box::new(Psalm::default()),
+            Self::Eslint => Box::new(Eslint::default()),
+            Self::Phpunit => Box::new(Phpunit::default()),
+            Self::Xcodebuild => Box::new(Xcodebuild::default()),
+            Self::OsvScanner => Box::new(OsvScanner::default()),
+            Self::LspDiagnostics => Box::new(LspDiagnostics::default()),
+        }
+    }
+}
+
+impl fmt::Display for ToolKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ToolKind {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| Error::UnknownTool(s.to_owned()))
+    }
 }
 
 /// Detect which tool format is present in the buffer.
@@ -124,15 +576,300 @@ pub enum Error {
 #[inline]
 pub fn detect<P: Platform + 'static>(buffer: &[u8]) -> Result<Box<dyn DynTool<P>>, Error>
 where
+    cargo_doc::CargoDoc: DynTool<P>,
     cargo_check::CargoCheck: DynTool<P>,
     cargo_libtest::CargoLibtest: DynTool<P>,
+    cargo_fmt::CargoFmt: DynTool<P>,
+    lcov::Lcov: DynTool<P>,
+    cargo_udeps::CargoUdeps: DynTool<P>,
+    junit::Junit: DynTool<P>,
+    pytest::Pytest: DynTool<P>,
+    cppcheck::Cppcheck: DynTool<P>,
+    cmake::Cmake: DynTool<P>,
+    black::Black: DynTool<P>,
+    bandit::Bandit: DynTool<P>,
+    surefire::Surefire: DynTool<P>,
+    pmd::Pmd: DynTool<P>,
+    psalm::Psalm: DynTool<P>,
+    eslint::Eslint: DynTool<P>,
+    phpunit::Phpunit: DynTool<P>,
+    xcodebuild::Xcodebuild: DynTool<P>,
+    osv_scanner::OsvScanner: DynTool<P>,
+    lsp_diagnostics::LspDiagnostics: DynTool<P>,
+    miri::Miri: DynTool<P>,
+    rustc_human::RustcHuman: DynTool<P>,
+    gcc_diagnostics::GccDiagnostics: DynTool<P>,
 {
-    if let Some(tool) = cargo_check::CargoCheck::detect(buffer) {
+    // A byte order mark would otherwise cause the first line to be rejected.
+    let sample = buffer.strip_prefix(line_framer::UTF8_BOM).unwrap_or(buffer);
+    // The output of Miri and cargo doc is also that of cargo check, so is
+    // tried first.
+    if let Some(tool) = miri::Miri::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_doc::CargoDoc::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_check::CargoCheck::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_libtest::CargoLibtest::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_fmt::CargoFmt::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = lcov::Lcov::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_udeps::CargoUdeps::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    // The JUnit XML report of PHPUnit is also a JUnit XML report, so is
+    // tried first.
+    if let Some(tool) = phpunit::Phpunit::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = junit::Junit::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = pytest::Pytest::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cppcheck::Cppcheck::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cmake::Cmake::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = black::Black::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = bandit::Bandit::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = surefire::Surefire::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = pmd::Pmd::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = psalm::Psalm::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = eslint::Eslint::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = xcodebuild::Xcodebuild::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = osv_scanner::OsvScanner::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = lsp_diagnostics::LspDiagnostics::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    // Diagnostics of rustc may be part of the output of other tools (e.g.,
+    // Miri), so these are tried last.
+    if let Some(tool) = rustc_human::RustcHuman::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    // Diagnostics of GCC and Clang may be relayed by cargo from a build
+    // script (e.g., one using the `cc` crate), so these are tried after those
+    // of rustc.
+    if let Some(tool) = gcc_diagnostics::GccDiagnostics::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    Err(Error::NoToolDetected)
+}
+
+/// Detect which tool format is present in the buffer.
+///
+/// This is the platform-agnostic counterpart to [`detect`], returning a tool
+/// whose messages can be formatted for any platform.
+///
+/// # Arguments
+///
+/// * `sample` - A byte slice containing a sample of the tool's output. This
+///   should typically contain a few lines of output to allow for detection.
+///
+/// # Returns
+///
+/// Returns the detected tool if successful, otherwise returns an error.
+///
+/// # Errors
+///
+/// Returns `ToolError::NoToolDetected` if no known tool format is detected.
+#[inline]
+pub fn detect_any(buffer: &[u8]) -> Result<Box<dyn AnyTool>, Error> {
+    // A byte order mark would otherwise cause the first line to be rejected.
+    let sample = buffer.strip_prefix(line_framer::UTF8_BOM).unwrap_or(buffer);
+    // The output of Miri and cargo doc is also that of cargo check, so is
+    // tried first.
+    if let Some(tool) = miri::Miri::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_doc::CargoDoc::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_check::CargoCheck::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_libtest::CargoLibtest::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_fmt::CargoFmt::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = lcov::Lcov::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cargo_udeps::CargoUdeps::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    // The JUnit XML report of PHPUnit is also a JUnit XML report, so is
+    // tried first.
+    if let Some(tool) = phpunit::Phpunit::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = junit::Junit::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = pytest::Pytest::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cppcheck::Cppcheck::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = cmake::Cmake::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = black::Black::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = bandit::Bandit::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = surefire::Surefire::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = pmd::Pmd::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = psalm::Psalm::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = eslint::Eslint::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = xcodebuild::Xcodebuild::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = osv_scanner::OsvScanner::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    if let Some(tool) = lsp_diagnostics::LspDiagnostics::detect(sample) {
+        tracing::info!("Detected tool format: {}", Tool::name(&tool));
+        return Ok(Box::new(tool));
+    }
+
+    // Diagnostics of rustc may be part of the output of other tools (e.g.,
+    // Miri), so these are tried last.
+    if let Some(tool) = rustc_human::RustcHuman::detect(sample) {
         tracing::info!("Detected tool format: {}", Tool::name(&tool));
         return Ok(Box::new(tool));
     }
 
-    if let Some(tool) = cargo_libtest::CargoLibtest::detect(buffer) {
+    // Diagnostics of GCC and Clang may be relayed by cargo from a build
+    // script (e.g., one using the `cc` crate), so these are tried after those
+    // of rustc.
+    if let Some(tool) = gcc_diagnostics::GccDiagnostics::detect(sample) {
         tracing::info!("Detected tool format: {}", Tool::name(&tool));
         return Ok(Box::new(tool));
     }
@@ -0,0 +1,864 @@
+//! bandit JSON report.
+//!
+//! Support for parsing the report which bandit prints with `-f json`, so that
+//! the security issues found in Python code are annotated at their location.
+//! The report is a single JSON object, printed over many lines (bandit
+//! indents it), whose `results` are the issues found and whose `errors` are
+//! the files which could not be scanned (e.g., as they are not valid Python).
+//! As the report is only complete once its closing brace is read, it is
+//! parsed then; a report cut off before its end is reported as truncated.
+//!
+//! Each issue is annotated on its line, with the identifier and name of the
+//! test which found it (e.g., `B602: subprocess_popen_with_shell_equals_true`)
+//! and the CWE identifier of the weakness in the title. The level of the
+//! annotation combines the severity of the issue with the confidence of
+//! bandit in it:
+//!
+//! | Severity \ Confidence | High    | Medium  | Low     |
+//! | --------------------- | ------- | ------- | ------- |
+//! | High                  | error   | error   | warning |
+//! | Medium                | warning | warning | notice  |
+//! | Low                   | notice  | notice  | debug   |
+//!
+//! A file which could not be scanned is reported as a warning on its first
+//! line. The `./` with which bandit prefixes the paths of a recursive scan of
+//! the current directory (`bandit -r .`) is removed. As bandit logs to stderr
+//! (e.g., `[main]  INFO    running on Python 3.12.3`), any text outside of the
+//! report is passed through as plain text output.
+
+use core::{fmt, mem};
+use std::io::BufRead as _;
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the JSON report of bandit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BanditMessage {
+    /// A security issue found by bandit.
+    Issue(Box<BanditIssue>),
+
+    /// A file which bandit could not scan.
+    Error(BanditError),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// A report cut off before its end.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of the report.
+    TextOutput(TextOutput),
+}
+
+/// The severity of an issue, or the confidence of bandit in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+#[non_exhaustive]
+pub enum BanditLevel {
+    /// A low level.
+    Low,
+    /// A medium level.
+    Medium,
+    /// A high level.
+    High,
+    /// No level was given (or one which is not known).
+    #[serde(other)]
+    Undefined,
+}
+
+impl fmt::Display for BanditLevel {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Undefined => "undefined",
+        })
+    }
+}
+
+/// Remove the `./` with which bandit prefixes the paths of a scan of the
+/// current directory.
+fn normalize_path(path: String) -> String {
+    match path.strip_prefix("./") {
+        Some(relative) => relative.to_owned(),
+        None => path,
+    }
+}
+
+/// A security issue found by bandit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BanditIssue {
+    /// The identifier of the test which found the issue (e.g., `B602`).
+    pub test_id: String,
+    /// The name of the test (e.g., `subprocess_popen_with_shell_equals_true`).
+    pub test_name: String,
+    /// The severity of the issue.
+    pub severity: BanditLevel,
+    /// The confidence of bandit in the issue.
+    pub confidence: BanditLevel,
+    /// The CWE identifier of the weakness, if any.
+    pub cwe: Option<u32>,
+    /// The path of the file.
+    pub file: String,
+    /// The line of the issue (1-based).
+    pub line: u32,
+    /// The description of the issue.
+    pub message: String,
+    /// The link to the documentation of the test, if any.
+    pub more_info: Option<String>,
+}
+
+impl BanditIssue {
+    /// The title of the annotation (e.g., `B602:
+    /// subprocess_popen_with_shell_equals_true (CWE-78)`).
+    fn title(&self) -> String {
+        let mut title = if self.test_name.is_empty() {
+            self.test_id.clone()
+        } else {
+            format!("{}: {}", self.test_id, self.test_name)
+        };
+        if let Some(cwe) = self.cwe {
+            title = format!("{title} (CWE-{cwe})");
+        }
+        title
+    }
+}
+
+impl<P: Platform> CiMessage<P> for BanditIssue {
+    #[inline]
+    fn format(&self) -> String {
+        let mut message = format!(
+            "{} (severity: {}, confidence: {})",
+            self.message, self.severity, self.confidence
+        );
+        if let Some(more_info) = &self.more_info {
+            message = format!("{message}\nMore info: {more_info}");
+        }
+        P::annotate(
+            self.severity(),
+            message,
+            Some((&self.file, self.line, None)),
+            Some(&self.title()),
+        )
+    }
+}
+
+impl Classify for BanditIssue {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match (self.severity, self.confidence) {
+            (BanditLevel::High, BanditLevel::High | BanditLevel::Medium) => Severity::Error,
+            (BanditLevel::High, BanditLevel::Low | BanditLevel::Undefined)
+            | (BanditLevel::Medium, BanditLevel::High | BanditLevel::Medium) => Severity::Warning,
+            (BanditLevel::Medium, BanditLevel::Low | BanditLevel::Undefined)
+            | (
+                BanditLevel::Low | BanditLevel::Undefined,
+                BanditLevel::High | BanditLevel::Medium,
+            ) => Severity::Notice,
+            (
+                BanditLevel::Low | BanditLevel::Undefined,
+                BanditLevel::Low | BanditLevel::Undefined,
+            ) => Severity::Debug,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        Some(&self.test_id)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// A file which bandit could not scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BanditError {
+    /// The path of the file.
+    pub file: String,
+    /// The reason given by bandit (e.g., `syntax error while parsing AST from
+    /// file`).
+    pub reason: String,
+}
+
+impl<P: Platform> CiMessage<P> for BanditError {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            format!("The file was not scanned: {}", self.reason),
+            Some((&self.file, 1, None)),
+            Some(Title::ScanError.text()),
+        )
+    }
+}
+
+impl Classify for BanditError {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.reason)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for BanditMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Issue(msg) => <BanditIssue as CiMessage<P>>::format(msg),
+            Self::Error(msg) => <BanditError as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for BanditMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Issue(msg) => msg.severity(),
+            Self::Error(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Issue(msg) => msg.kind(),
+            Self::Error(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::Issue(_) | Self::Error(_) | Self::OverlongLine(_) | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.sort_key(),
+            Self::Error(msg) => Some(&msg.file),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.code(),
+            Self::Error(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.file(),
+            Self::Error(msg) => msg.file(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.message(),
+            Self::Error(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for BanditMessage {}
+
+impl Captured for BanditMessage {}
+
+/// The JSON report of bandit.
+#[derive(Debug, Deserialize)]
+struct Report {
+    /// The files which could not be scanned.
+    #[serde(default)]
+    errors: Vec<JsonError>,
+    /// The issues found.
+    results: Vec<JsonIssue>,
+}
+
+/// A file listed in the `errors` of the report.
+#[derive(Debug, Deserialize)]
+struct JsonError {
+    /// The path of the file.
+    filename: String,
+    /// The reason the file could not be scanned.
+    reason: String,
+}
+
+/// An issue listed in the `results` of the report.
+#[derive(Debug, Deserialize)]
+struct JsonIssue {
+    /// The path of the file.
+    filename: String,
+    /// The line of the issue.
+    line_number: u32,
+    /// The identifier of the test.
+    test_id: String,
+    /// The name of the test.
+    #[serde(default)]
+    test_name: String,
+    /// The severity of the issue.
+    issue_severity: BanditLevel,
+    /// The confidence of bandit in the issue.
+    issue_confidence: BanditLevel,
+    /// The description of the issue.
+    issue_text: String,
+    /// The weakness, given by bandit 1.7.3 and later.
+    #[serde(default)]
+    issue_cwe: Option<JsonCwe>,
+    /// The link to the documentation of the test.
+    #[serde(default)]
+    more_info: Option<String>,
+}
+
+/// The weakness of an issue.
+#[derive(Debug, Deserialize)]
+struct JsonCwe {
+    /// The CWE identifier, or 0 if there is none.
+    id: u32,
+}
+
+impl Report {
+    /// The messages of the report, the issues first.
+    fn into_messages(self) -> Vec<BanditMessage> {
+        let issues = self.results.into_iter().map(|issue| {
+            BanditMessage::Issue(Box::new(BanditIssue {
+                test_id: issue.test_id,
+                test_name: issue.test_name,
+                severity: issue.issue_severity,
+                confidence: issue.issue_confidence,
+                cwe: issue.issue_cwe.map(|cwe| cwe.id).filter(|&id| id != 0),
+                file: normalize_path(issue.filename),
+                line: issue.line_number,
+                message: issue.issue_text,
+                more_info: issue.more_info.filter(|link| !link.is_empty()),
+            }))
+        });
+        let errors = self.errors.into_iter().map(|error| {
+            BanditMessage::Error(BanditError {
+                file: normalize_path(error.filename),
+                reason: error.reason,
+            })
+        });
+        issues.chain(errors).collect()
+    }
+}
+
+/// The report being read.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The lines of the report read so far.
+    document: Vec<u8>,
+    /// The line number and offset of the first line of the report, if one is
+    /// being read.
+    start: Option<(usize, u64)>,
+}
+
+impl Reader {
+    /// Parse the report read so far.
+    fn parse(&mut self) -> Vec<Result<BanditMessage, serde_json::Error>> {
+        self.start = None;
+        match serde_json::from_slice::<Report>(&mem::take(&mut self.document)) {
+            Ok(report) => report.into_messages().into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        }
+    }
+
+    /// Parse a single line of the output of bandit.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the line.
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the report.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        position: (usize, u64),
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<BanditMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                // The report cannot be parsed without the whole line
+                self.start = None;
+                self.document.clear();
+                let mut results =
+                    vec![Ok(BanditMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(position, Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        if self.start.is_none() {
+            if line.first() != Some(&b'{') {
+                return text
+                    .handle(line)
+                    .map(|output| Ok(BanditMessage::TextOutput(output)))
+                    .into_iter()
+                    .collect();
+            }
+            self.start = Some(position);
+        }
+        self.document.extend_from_slice(line);
+        self.document.push(b'\n');
+
+        // bandit closes the report on a line of its own, unless it is
+        // printed on a single line (e.g., reformatted by another tool)
+        let single = self.document.len() == line.len().saturating_add(1);
+        if line == b"}" || (single && line.ends_with(b"}")) {
+            let results = self.parse();
+            if single
+                && let [Err(e)] = results.as_slice()
+                && e.is_eof()
+            {
+                // The report continues on the next lines
+                self.start = Some(position);
+                self.document.extend_from_slice(line);
+                self.document.push(b'\n');
+                return Vec::new();
+            }
+            return results;
+        }
+        Vec::new()
+    }
+
+    /// Report the report being read at the end of the output, if any.
+    fn finish(&mut self) -> Vec<Result<BanditMessage, serde_json::Error>> {
+        let Some((line_number, offset)) = self.start else {
+            return Vec::new();
+        };
+        // The last line may end within a string, which cannot contain the
+        // newline which ends it
+        if self.document.last() == Some(&b'\n') {
+            self.document.pop();
+        }
+        let length = self.document.len();
+        self.parse()
+            .into_iter()
+            .map(|result| {
+                result.or_else(|error| {
+                    if error.is_eof() {
+                        Ok(BanditMessage::TruncatedLine(TruncatedLine {
+                            line_number,
+                            offset,
+                            length,
+                            error: error.to_string(),
+                        }))
+                    } else {
+                        Err(error)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Tool implementation for parsing the JSON report of bandit.
+#[derive(Debug, Clone, Default)]
+pub struct Bandit {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of the report.
+    text: TextLines,
+    /// The report being read.
+    reader: Reader,
+}
+
+impl Detect for Bandit {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        // The keys of the report are sorted, so that the beginning of a report
+        // is recognised even if the sample ends within it
+        let mut errors = false;
+        let mut generated = false;
+        for line in sample.lines().map_while(Result::ok) {
+            errors |= line.starts_with("  \"errors\": [");
+            generated |= line.starts_with("  \"generated_at\": \"");
+            if (errors && generated)
+                || (line.starts_with('{')
+                    && line.contains("\"generated_at\"")
+                    && serde_json::from_str::<Report>(&line).is_ok())
+            {
+                return Some(Self::default());
+            }
+        }
+        None
+    }
+}
+
+impl Bandit {
+    /// A problem matcher for the human-readable output of bandit.
+    ///
+    /// Each issue is printed on five lines (e.g., `>> Issue:
+    /// [B602:subprocess_popen_with_shell_equals_true] subprocess call with
+    /// shell=True identified, security issue.`, followed by its severity, its
+    /// CWE, a link to the documentation and `Location: ./app.py:5:11`), which
+    /// give the test, message and location of the issue. Each match is
+    /// reported as a warning.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-bandit",
+            vec![
+                Pattern::new(r"^>> Issue: \[(B\d+):\w+\] (.+)$")
+                    .code(1)
+                    .message(2),
+                Pattern::new(r"^\s+Severity: \w+\s+Confidence: \w+$"),
+                Pattern::new(r"^\s+CWE: .+$"),
+                Pattern::new(r"^\s+More Info: .+$"),
+                Pattern::new(r"^\s+Location: (?:\./)?(.+):(\d+):(\d+)$")
+                    .file(1)
+                    .line(2)
+                    .column(3),
+            ],
+        )
+        .with_severity("warning")
+    }
+}
+
+impl Tool for Bandit {
+    type Message = BanditMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "bandit"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        loop {
+            let position = self.framer.position();
+            let Some(line) = self.framer.next_line() else {
+                break;
+            };
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+
+        results.extend(self.reader.finish());
+        if let Some(output) = self.text.finish() {
+            results.push(Ok(BanditMessage::TextOutput(output)));
+        }
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Bandit
+where
+    BanditMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Bandit, BanditMessage};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _, Severity};
+    use crate::tool::{Detect as _, Tool as _};
+
+    const REPORT: &str = r#"[main]	INFO	running on Python 3.12.3
+{
+  "errors": [
+    {
+      "filename": "./src/broken.py",
+      "reason": "syntax error while parsing AST from file"
+    }
+  ],
+  "generated_at": "2024-05-01T12:00:00Z",
+  "metrics": {
+    "_totals": {
+      "loc": 12,
+      "nosec": 0
+    }
+  },
+  "results": [
+    {
+      "code": "4 def run(command):\n5     return subprocess.run(command, shell=True)\n",
+      "col_offset": 11,
+      "filename": "./src/run.py",
+      "issue_confidence": "HIGH",
+      "issue_cwe": {
+        "id": 78,
+        "link": "https://cwe.mitre.org/data/definitions/78.html"
+      },
+      "issue_severity": "HIGH",
+      "issue_text": "subprocess call with shell=True identified, security issue.",
+      "line_number": 5,
+      "line_range": [
+        5
+      ],
+      "more_info": "https://bandit.readthedocs.io/en/1.7.8/plugins/b602_subprocess_popen_with_shell_equals_true.html",
+      "test_id": "B602",
+      "test_name": "subprocess_popen_with_shell_equals_true"
+    },
+    {
+      "filename": "src/config.py",
+      "issue_confidence": "MEDIUM",
+      "issue_severity": "LOW",
+      "issue_text": "Possible hardcoded password: 'hunter2'",
+      "line_number": 3,
+      "test_id": "B105",
+      "test_name": "hardcoded_password_string"
+    }
+  ]
+}
+[json]	INFO	JSON output written to file: <stdout>
+"#;
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        BanditMessage: CiMessage<P>,
+    {
+        let mut tool = Bandit::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <BanditMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn report() {
+        insta::assert_snapshot!(format::<GitHub>(REPORT), @"
+        ::error file=src/run.py,line=5,title=B602%3A subprocess_popen_with_shell_equals_true (CWE-78)::subprocess call with shell=True identified, security issue. (severity: high, confidence: high)%0AMore info: https://bandit.readthedocs.io/en/1.7.8/plugins/b602_subprocess_popen_with_shell_equals_true.html
+        ::notice file=src/config.py,line=3,title=B105%3A hardcoded_password_string::Possible hardcoded password: 'hunter2' (severity: low, confidence: medium)
+        ::warning file=src/broken.py,line=1,title=Scan Error::The file was not scanned: syntax error while parsing AST from file
+        ");
+    }
+
+    #[test]
+    fn chunked() {
+        let mut tool = Bandit::default();
+        let mut messages = Vec::new();
+        for chunk in REPORT.as_bytes().chunks(7) {
+            messages.extend(tool.parse(chunk));
+        }
+        messages.extend(tool.finish());
+        assert_eq!(
+            messages
+                .into_iter()
+                .map(|msg| msg.expect("valid message").severity())
+                .collect::<Vec<_>>(),
+            [Severity::Error, Severity::Notice, Severity::Warning]
+        );
+    }
+
+    #[test]
+    fn compact() {
+        let output = r#"{"errors": [], "generated_at": "2024-05-01T12:00:00Z", "results": [{"filename": "a.py", "issue_confidence": "LOW", "issue_severity": "HIGH", "issue_text": "Use of exec detected.", "line_number": 1, "test_id": "B102", "test_name": "exec_used", "issue_cwe": {"id": 0}}]}"#;
+        insta::assert_snapshot!(format::<Plain>(output), @"a.py:1: warning: B102: exec_used: Use of exec detected. (severity: high, confidence: low)");
+    }
+
+    #[test]
+    fn levels() {
+        let output = |severity: &str, confidence: &str| {
+            format!(
+                r#"{{"results": [{{"filename": "a.py", "issue_confidence": "{confidence}", "issue_severity": "{severity}", "issue_text": "", "line_number": 1, "test_id": "B101"}}]}}"#
+            )
+        };
+        for (severity, confidence, expected) in [
+            ("HIGH", "HIGH", Severity::Error),
+            ("HIGH", "MEDIUM", Severity::Error),
+            ("HIGH", "LOW", Severity::Warning),
+            ("MEDIUM", "MEDIUM", Severity::Warning),
+            ("MEDIUM", "LOW", Severity::Notice),
+            ("LOW", "HIGH", Severity::Notice),
+            ("LOW", "LOW", Severity::Debug),
+            ("UNDEFINED", "UNDEFINED", Severity::Debug),
+        ] {
+            let mut tool = Bandit::default();
+            let mut messages = tool.parse(output(severity, confidence).as_bytes());
+            messages.extend(tool.finish());
+            let [Ok(message)] = messages.as_slice() else {
+                panic!("expected one message for {severity}/{confidence}");
+            };
+            assert_eq!(message.severity(), expected, "{severity}/{confidence}");
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let output = REPORT.get(..200).expect("in bounds");
+        insta::assert_snapshot!(format::<Plain>(output), @"warning: Truncated Output: Output ended within line 2 (at byte offset 37), discarding 163 bytes of an incomplete message: EOF while parsing a string at line 8 column 34");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Bandit::detect(REPORT.as_bytes()).is_some());
+        // The beginning of a report
+        assert!(Bandit::detect(REPORT.get(..200).expect("in bounds").as_bytes()).is_some());
+        assert!(Bandit::detect(b"{\n  \"errors\": []\n}\n").is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Bandit::problem_matcher();
+        let [issue, severity, cwe, more_info, location] = matcher.pattern.as_slice() else {
+            panic!("expected five patterns");
+        };
+        let lines = [
+            (
+                issue,
+                ">> Issue: [B602:subprocess_popen_with_shell_equals_true] subprocess call with shell=True identified, security issue.",
+            ),
+            (severity, "   Severity: High   Confidence: High"),
+            (
+                cwe,
+                "   CWE: CWE-78 (https://cwe.mitre.org/data/definitions/78.html)",
+            ),
+            (
+                more_info,
+                "   More Info: https://bandit.readthedocs.io/en/1.7.8/plugins/b602_subprocess_popen_with_shell_equals_true.html",
+            ),
+            (location, "   Location: ./src/run.py:5:11"),
+        ];
+        for (pattern, line) in lines {
+            let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+            assert!(regex.is_match(line), "{line}");
+        }
+        let regex = regex::Regex::new(&location.regexp).expect("invalid regexp");
+        let captures = regex
+            .captures("   Location: ./src/run.py:5:11")
+            .expect("line not matched");
+        assert_eq!(
+            [1, 2, 3].map(|i| captures.get(i).map(|m| m.as_str())),
+            [Some("src/run.py"), Some("5"), Some("11")]
+        );
+    }
+}
@@ -0,0 +1,823 @@
+//! black check output format.
+//!
+//! Support for parsing the output of `black --check`, which lists each file
+//! black would reformat (`would reformat <file>`) and each file it cannot
+//! format (`error: cannot format <file>: <reason>`), followed by a summary.
+//! With `--diff`, black also prints a unified diff of the changes it would
+//! make to each file, headed by `--- <file>\t<timestamp>` and `+++
+//! <file>\t<timestamp>`.
+//!
+//! Each file is reported once, at the first line of the file which black
+//! would change, with the hunks of its diff in a group. As the diffs are
+//! printed to stdout and the list of files to stderr, the two may arrive in
+//! any order: a file is reported when its diff ends, and a file listed
+//! without a diff (i.e., without `--diff`) is reported at the end of the
+//! output, on its first line. The diff of a Jupyter notebook is split into
+//! its cells (`--- <file>:cell_<N>`), whose lines are not those of the file,
+//! so that a notebook is reported on its first line. A file which black
+//! cannot format is reported as an error, on the line of the syntax error if
+//! black gives one. A summary of the number of files which need reformatting
+//! follows at the end of the output.
+//!
+//! The colour codes with which black highlights the diff (`--color`) are
+//! removed, and any other output is passed through as plain text output.
+
+use core::{convert::Infallible, mem};
+use std::{collections::HashSet, io::BufRead as _};
+
+use crate::{
+    ansi,
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+    },
+};
+
+/// A message parsed from the output of `black --check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BlackMessage {
+    /// A file which black would reformat.
+    Unformatted(UnformattedFile),
+
+    /// A file which black cannot format.
+    Failed(FormatFailure),
+
+    /// The number of files which need reformatting, reported at the end of
+    /// the output.
+    Summary(BlackSummary),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// Output which is neither a diff nor a file listed by black.
+    TextOutput(TextOutput),
+}
+
+/// A file which black would reformat, with the diff of the changes if black
+/// printed one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnformattedFile {
+    /// The path of the file, as printed by black.
+    pub file: String,
+    /// The first line of the file which black would change (1-based), or the
+    /// first line of the file if black printed no diff.
+    pub line: u32,
+    /// The number of hunks of the diff.
+    pub changes: usize,
+    /// The lines of the diff, from the first hunk header.
+    pub diff: Vec<String>,
+}
+
+impl UnformattedFile {
+    /// Start a file, at its first line until a change is read.
+    fn new(file: &str) -> Self {
+        Self {
+            file: file.to_owned(),
+            line: 1,
+            changes: 0,
+            diff: Vec::new(),
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for UnformattedFile {
+    #[inline]
+    fn format(&self) -> String {
+        let message = if self.changes == 0 {
+            "File would be reformatted; run `black` to format it".to_owned()
+        } else {
+            format!(
+                "File would be reformatted ({} change(s)); run `black` to format it",
+                self.changes
+            )
+        };
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            message,
+            Some((&self.file, self.line, None)),
+            Some(Title::UnformattedFile.text()),
+        )];
+        if !self.diff.is_empty() {
+            lines.push(P::group(&self.file));
+            lines.extend(self.diff.iter().cloned());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for UnformattedFile {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some("File would be reformatted")
+    }
+}
+
+/// A file which black cannot format (e.g., as it cannot parse it).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FormatFailure {
+    /// The path of the file, as printed by black.
+    pub file: String,
+    /// The line of the syntax error (1-based), if black gives one.
+    pub line: Option<u32>,
+    /// The reason given by black.
+    pub message: String,
+}
+
+impl FormatFailure {
+    /// Parse the line black prints for a file it cannot format.
+    ///
+    /// # Returns
+    ///
+    /// The failure, or `None` if the line does not report one.
+    fn parse(line: &str) -> Option<Self> {
+        let (file, message) = line
+            .strip_prefix("error: cannot format ")?
+            .split_once(": ")?;
+        // black locates a syntax error as `<line>:<column>` within the reason
+        // (e.g., `Cannot parse for target version Python 3.12: 4:11: ...`)
+        let location = message.split(": ").find_map(|part| {
+            let (number, column) = part.split_once(':')?;
+            column.parse::<u32>().ok()?;
+            number.parse().ok()
+        });
+        Some(Self {
+            file: file.to_owned(),
+            line: location,
+            message: message.to_owned(),
+        })
+    }
+}
+
+impl<P: Platform> CiMessage<P> for FormatFailure {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            &self.message,
+            Some((&self.file, self.line.unwrap_or(1), None)),
+            Some(Title::FormatError.text()),
+        )
+    }
+}
+
+impl Classify for FormatFailure {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// The number of files which need reformatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BlackSummary {
+    /// The number of files which black would reformat.
+    pub files: usize,
+    /// The number of files which black cannot format.
+    pub failed: usize,
+}
+
+impl<P: Platform> CiMessage<P> for BlackSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let mut parts = Vec::new();
+        if self.files > 0 {
+            parts.push(format!("{} file(s) need reformatting", self.files));
+        }
+        if self.failed > 0 {
+            parts.push(format!("{} file(s) cannot be formatted", self.failed));
+        }
+        let message = if parts.is_empty() {
+            "All files are formatted".to_owned()
+        } else {
+            parts.join(", ")
+        };
+        P::annotate(
+            self.severity(),
+            message,
+            None,
+            Some(Title::FormatCheck.text()),
+        )
+    }
+}
+
+impl Classify for BlackSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl<P: Platform> CiMessage<P> for BlackMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Unformatted(msg) => <UnformattedFile as CiMessage<P>>::format(msg),
+            Self::Failed(msg) => <FormatFailure as CiMessage<P>>::format(msg),
+            Self::Summary(msg) => <BlackSummary as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for BlackMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Unformatted(msg) => msg.severity(),
+            Self::Failed(msg) => msg.severity(),
+            Self::Summary(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Unformatted(msg) => msg.kind(),
+            Self::Failed(msg) => msg.kind(),
+            Self::Summary(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Unformatted(msg) => Some(&msg.file),
+            Self::Failed(msg) => Some(&msg.file),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Unformatted(msg) => msg.file(),
+            Self::Failed(msg) => msg.file(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Unformatted(msg) => msg.message(),
+            Self::Failed(msg) => msg.message(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for BlackMessage {}
+
+impl Captured for BlackMessage {}
+
+/// Parse the header of the diff of a file (e.g., `--- src/app.py\t2024-05-01
+/// 12:00:00.000000+00:00`).
+///
+/// # Returns
+///
+/// The file and whether the diff is that of a cell of a notebook, or `None`
+/// if the line is not a header.
+fn parse_diff_header(line: &str) -> Option<(&str, bool)> {
+    let (path, _timestamp) = line.strip_prefix("--- ")?.split_once('\t')?;
+    if let Some((file, cell)) = path.rsplit_once(':')
+        && let Some(number) = cell.strip_prefix("cell_")
+        && number.parse::<u32>().is_ok()
+    {
+        return Some((file, true));
+    }
+    Some((path, false))
+}
+
+/// Parse the header of a hunk (e.g., `@@ -12,7 +12,8 @@`).
+///
+/// # Returns
+///
+/// The first line of the original text in the hunk, or `None` if the line is
+/// not a hunk header.
+fn parse_hunk(line: &str) -> Option<u32> {
+    let rest = line.strip_prefix("@@ -")?;
+    let end = rest.find([',', ' ']).unwrap_or(rest.len());
+    rest.get(..end)?.parse().ok()
+}
+
+/// Whether a line is part of the summary black prints at the end of its
+/// output (e.g., `1 file would be reformatted, 3 files would be left
+/// unchanged.`).
+fn is_summary(line: &str) -> bool {
+    [
+        " would be reformatted",
+        " would be left unchanged",
+        " would fail to reformat",
+    ]
+    .iter()
+    .any(|pattern| line.contains(pattern))
+}
+
+/// The files reported so far.
+#[derive(Debug, Clone, Default)]
+struct Files {
+    /// The file whose diff is being read.
+    current: Option<UnformattedFile>,
+    /// The line of the original text of the next line of the hunk being
+    /// read, until the first change of the current file is found.
+    seek: Option<u32>,
+    /// Whether the first change of the current file was found.
+    located: bool,
+    /// Whether the last line was the header of the original text, which is
+    /// followed by that of the formatted text.
+    header: bool,
+    /// The files listed by black whose diff has not been read, in order.
+    pending: Vec<String>,
+    /// The files reported so far.
+    reported: HashSet<String>,
+    /// The number of files which black cannot format.
+    failed: usize,
+}
+
+impl Files {
+    /// Report the file whose diff is being read, if any.
+    fn flush(&mut self) -> Option<BlackMessage> {
+        let file = self.current.take()?;
+        self.seek = None;
+        self.reported.insert(file.file.clone());
+        Some(BlackMessage::Unformatted(file))
+    }
+
+    /// Report the files listed by black without a diff.
+    fn flush_pending(&mut self) -> Vec<BlackMessage> {
+        let pending = mem::take(&mut self.pending);
+        pending
+            .iter()
+            .filter(|file| self.reported.insert((*file).clone()))
+            .map(|file| BlackMessage::Unformatted(UnformattedFile::new(file)))
+            .collect()
+    }
+
+    /// The summary of the files reported.
+    fn summary(&mut self) -> BlackMessage {
+        BlackMessage::Summary(BlackSummary {
+            files: mem::take(&mut self.reported).len(),
+            failed: mem::take(&mut self.failed),
+        })
+    }
+
+    /// Start the diff of a file.
+    fn start(&mut self, file: &str, cell: bool) -> Vec<BlackMessage> {
+        self.header = true;
+        // The cells of a notebook continue the diff of the same file
+        if let Some(current) = &self.current
+            && current.file == file
+        {
+            self.seek = None;
+            return Vec::new();
+        }
+        let results = self.flush().into_iter().collect();
+        self.pending.retain(|pending| pending != file);
+        self.current = Some(UnformattedFile::new(file));
+        self.located = cell;
+        results
+    }
+
+    /// Add a line to the diff of the current file.
+    ///
+    /// # Returns
+    ///
+    /// Whether the line continues the diff.
+    fn extend(&mut self, line: &str) -> bool {
+        let Some(current) = &mut self.current else {
+            return false;
+        };
+        if mem::take(&mut self.header) && line.starts_with("+++ ") {
+            return true;
+        }
+        if let Some(start) = parse_hunk(line) {
+            current.changes = current.changes.saturating_add(1);
+            if !self.located {
+                self.seek = Some(start);
+            }
+        } else {
+            match line.as_bytes().first() {
+                Some(b' ') => self.seek = self.seek.map(|next| next.saturating_add(1)),
+                Some(b'+' | b'-') => {
+                    if let Some(next) = self.seek.take() {
+                        current.line = next.max(1);
+                        self.located = true;
+                    }
+                }
+                Some(b'\\') => {}
+                _ => return false,
+            }
+        }
+        current.diff.push(line.to_owned());
+        true
+    }
+
+    /// Parse a single line of the output of `black --check`.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<BlackMessage> {
+        let raw = match next {
+            Line::Complete(raw) => raw,
+            Line::Overlong(raw) => {
+                let mut results = vec![BlackMessage::OverlongLine(OverlongLine { max_length })];
+                results.extend(self.parse_line(Line::Complete(raw), max_length, text));
+                return results;
+            }
+        };
+
+        // black colours its diffs with `--color`
+        let stripped;
+        let bytes = if raw.contains(&b'\x1b') {
+            stripped = ansi::strip(raw);
+            stripped.as_slice()
+        } else {
+            raw
+        };
+
+        // An empty line within a hunk is an empty line of context whose
+        // leading space was trimmed; any other is skipped
+        if bytes.is_empty() {
+            if self
+                .current
+                .as_ref()
+                .is_some_and(|current| current.changes > 0)
+            {
+                self.extend(" ");
+            }
+            return Vec::new();
+        }
+
+        let line = String::from_utf8_lossy(bytes);
+        if let Some((file, cell)) = parse_diff_header(&line) {
+            return self.start(file, cell);
+        }
+        if self.extend(&line) {
+            return Vec::new();
+        }
+
+        let mut results: Vec<_> = self.flush().into_iter().collect();
+        if let Some(file) = line.strip_prefix("would reformat ") {
+            if !self.reported.contains(file) && !self.pending.iter().any(|pending| pending == file)
+            {
+                self.pending.push(file.to_owned());
+            }
+        } else if let Some(failure) = FormatFailure::parse(&line) {
+            self.failed = self.failed.saturating_add(1);
+            results.push(BlackMessage::Failed(failure));
+        } else {
+            results.extend(text.handle(bytes).map(BlackMessage::TextOutput));
+        }
+        results
+    }
+}
+
+/// Tool implementation for parsing the output of `black --check`.
+#[derive(Debug, Clone, Default)]
+pub struct Black {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are neither a diff nor a file listed by black.
+    text: TextLines,
+    /// The files reported so far.
+    files: Files,
+}
+
+impl Detect for Black {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| {
+                line.starts_with("would reformat ")
+                    || line.starts_with("error: cannot format ")
+                    || is_summary(&line)
+            })
+            .then(Self::default)
+    }
+}
+
+impl Black {
+    /// A problem matcher for the files listed by `black --check`.
+    ///
+    /// The line (e.g., `would reformat src/app.py`) gives the file which
+    /// black would reformat, but not the line of the change. Each match is
+    /// reported as an error.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-black",
+            vec![Pattern::new("^(would reformat (.+))$").message(1).file(2)],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for Black {
+    type Message = BlackMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "black"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.files.parse_line(line, max_length, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.files.parse_line(line, max_length, &mut self.text));
+        }
+
+        results.extend(self.files.flush());
+        results.extend(self.files.flush_pending());
+        results.push(self.files.summary());
+        results.extend(self.text.finish().map(BlackMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Black
+where
+    BlackMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Black, BlackMessage, FormatFailure, parse_diff_header, parse_hunk};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{Detect as _, Tool as _};
+
+    const DIFF: &str = "\
+--- src/app.py\t2024-05-01 12:00:00.000000+00:00
++++ src/app.py\t2024-05-01 12:00:01.000000+00:00
+@@ -3,7 +3,7 @@
+
+
+ def greet(name):
+-    return 'Hello, ' + name
++    return \"Hello, \" + name
+
+
+ def main():
+@@ -12,2 +12,2 @@
+     greet(\"world\")
+-    return(0)
++    return 0
+would reformat src/app.py
+would reformat src/cli.py
+error: cannot format src/broken.py: Cannot parse for target version Python 3.12: 4:11:     return (
+
+Oh no! 💥 💔 💥
+2 files would be reformatted, 3 files would be left unchanged, 1 file would fail to reformat.
+";
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        BlackMessage: CiMessage<P>,
+    {
+        let mut tool = Black::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <BlackMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn headers() {
+        assert_eq!(
+            parse_diff_header("--- src/app.py\t2024-05-01 12:00:00.000000+00:00"),
+            Some(("src/app.py", false))
+        );
+        assert_eq!(
+            parse_diff_header("--- notebook.ipynb:cell_2\t2024-05-01 12:00:00+00:00"),
+            Some(("notebook.ipynb", true))
+        );
+        assert_eq!(parse_diff_header("--- removed line"), None);
+        assert_eq!(parse_hunk("@@ -12,7 +12,8 @@"), Some(12));
+        assert_eq!(parse_hunk("@@ -1 +1 @@"), Some(1));
+        assert_eq!(parse_hunk("@@ -x,1 +1 @@"), None);
+    }
+
+    #[test]
+    fn failure() {
+        assert_eq!(
+            FormatFailure::parse(
+                "error: cannot format a.py: Cannot parse for target version Python 3.12: 4:11:     return ("
+            )
+            .and_then(|failure| failure.line),
+            Some(4)
+        );
+        assert_eq!(
+            FormatFailure::parse(
+                "error: cannot format a.py: INTERNAL ERROR: Black produced invalid code"
+            )
+            .map(|failure| failure.line),
+            Some(None)
+        );
+        assert_eq!(FormatFailure::parse("error: unknown option"), None);
+    }
+
+    #[test]
+    fn diff() {
+        insta::assert_snapshot!(format::<GitHub>(DIFF), @r#"
+        ::error file=src/app.py,line=6,title=Unformatted File::File would be reformatted (2 change(s)); run `black` to format it
+        ::group::src/app.py
+        @@ -3,7 +3,7 @@
+         
+         
+         def greet(name):
+        -    return 'Hello, ' + name
+        +    return "Hello, " + name
+         
+         
+         def main():
+        @@ -12,2 +12,2 @@
+             greet("world")
+        -    return(0)
+        +    return 0
+        ::endgroup::
+        ::error file=src/broken.py,line=4,title=Format Error::Cannot parse for target version Python 3.12: 4:11:     return (
+        ::error file=src/cli.py,line=1,title=Unformatted File::File would be reformatted; run `black` to format it
+        ::notice title=Format Check::2 file(s) need reformatting, 1 file(s) cannot be formatted
+        "#);
+    }
+
+    #[test]
+    fn interleaved() {
+        // stderr listing the file before stdout printing its diff
+        let output = "would reformat a.py\n--- a.py\t2024-05-01 12:00:00+00:00\n+++ a.py\t2024-05-01 12:00:01+00:00\n@@ -1,2 +1,2 @@\n x = 1\n-y=2\n+y = 2\n";
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        a.py:2: error:   Unformatted File: File would be reformatted (1 change(s)); run `black` to format it
+        a.py
+        @@ -1,2 +1,2 @@
+         x = 1
+        -y=2
+        +y = 2
+        notice:  Format Check: 1 file(s) need reformatting
+        ");
+    }
+
+    #[test]
+    fn notebook() {
+        let output = "--- a.ipynb:cell_1\t2024-05-01 12:00:00+00:00\n+++ a.ipynb:cell_1\t2024-05-01 12:00:01+00:00\n@@ -5,1 +5,1 @@\n-x=1\n+x = 1\n--- a.ipynb:cell_3\t2024-05-01 12:00:00+00:00\n+++ a.ipynb:cell_3\t2024-05-01 12:00:01+00:00\n@@ -1,1 +1,1 @@\n-y=2\n+y = 2\nwould reformat a.ipynb\n";
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        a.ipynb:1: error:   Unformatted File: File would be reformatted (2 change(s)); run `black` to format it
+        a.ipynb
+        @@ -5,1 +5,1 @@
+        -x=1
+        +x = 1
+        @@ -1,1 +1,1 @@
+        -y=2
+        +y = 2
+        notice:  Format Check: 1 file(s) need reformatting
+        ");
+    }
+
+    #[test]
+    fn coloured() {
+        let output = "\x1b[1m--- a.py\t2024-05-01 12:00:00+00:00\x1b[0m\n\x1b[1m+++ a.py\t2024-05-01 12:00:01+00:00\x1b[0m\n\x1b[36m@@ -2,1 +2,1 @@\x1b[0m\n\x1b[31m-y=2\x1b[0m\n\x1b[32m+y = 2\x1b[0m\n";
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        a.py:2: error:   Unformatted File: File would be reformatted (1 change(s)); run `black` to format it
+        a.py
+        @@ -2,1 +2,1 @@
+        -y=2
+        +y = 2
+        notice:  Format Check: 1 file(s) need reformatting
+        ");
+    }
+
+    #[test]
+    fn formatted() {
+        let output = "All done! ✨ 🍰 ✨\n4 files would be left unchanged.\n";
+        insta::assert_snapshot!(format::<GitHub>(output), @"::notice title=Format Check::All files are formatted");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Black::detect(DIFF.as_bytes()).is_some());
+        assert!(Black::detect(b"All done!\n1 file would be left unchanged.\n").is_some());
+        assert!(Black::detect(b"Diff in src/lib.rs:1:\n").is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Black::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+        let captures = regex
+            .captures("would reformat src/app.py")
+            .expect("line not matched");
+        assert_eq!(captures.get(2).map(|m| m.as_str()), Some("src/app.py"));
+    }
+}
@@ -12,23 +12,44 @@
 
 mod build_finished;
 mod build_script_executed;
+mod build_script_warning;
 mod common;
 mod compiler_artifact;
 mod compiler_message;
+mod incomplete_build;
+mod timing_info;
+
+pub(crate) use build_finished::BuildFinished;
+pub(crate) use compiler_message::{
+    Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan, DiagnosticSpanLine,
+};
 
 use crate::{
-    ci::{GitHub, Plain, Platform},
-    ci_message::CiMessage,
+    capture::Captured,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    sarif::Fix,
+    timeline::{Timed, Timing},
     tool::{
-        Detect, DynTool, Tool,
+        Detect, DynTool, Tool, ToolOption, ToolOptions,
         cargo_check::{
-            build_finished::BuildFinished, build_script_executed::BuildScriptExecuted,
-            compiler_artifact::CompilerArtifact, compiler_message::CompilerMessage,
+            build_script_executed::BuildScriptExecuted, build_script_warning::BuildScriptWarning,
+            common::package_name, compiler_artifact::CompilerArtifact,
+            compiler_message::CompilerMessage, incomplete_build::IncompleteBuild,
+            timing_info::TimingInfo,
         },
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
     },
 };
 use serde::Deserialize;
-use std::io::BufRead;
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufRead,
+};
 
 /// A message from cargo's JSON output.
 ///
@@ -49,6 +70,29 @@ pub enum CargoMessage {
 
     /// Build finished.
     BuildFinished(BuildFinished),
+
+    /// Build timing of a single compilation unit.
+    TimingInfo(TimingInfo),
+
+    /// Warning emitted by a build script, written by cargo as text.
+    #[serde(skip_deserializing)]
+    BuildScriptWarning(BuildScriptWarning),
+
+    /// A line which exceeded the maximum line length.
+    #[serde(skip_deserializing)]
+    OverlongLine(OverlongLine),
+
+    /// An incomplete message at the end of the output.
+    #[serde(skip_deserializing)]
+    TruncatedLine(TruncatedLine),
+
+    /// The end of the output, reached without a `build-finished` message.
+    #[serde(skip_deserializing)]
+    IncompleteBuild(IncompleteBuild),
+
+    /// Human-readable output interleaved with the JSON messages.
+    #[serde(skip_deserializing)]
+    TextOutput(TextOutput),
 }
 
 impl CiMessage<Plain> for CargoMessage {
@@ -61,6 +105,12 @@ impl CiMessage<Plain> for CargoMessage {
                 <BuildScriptExecuted as CiMessage<Plain>>::format(msg)
             }
             Self::BuildFinished(msg) => <BuildFinished as CiMessage<Plain>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<Plain>>::format(msg),
+            Self::BuildScriptWarning(msg) => <BuildScriptWarning as CiMessage<Plain>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<Plain>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<Plain>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<Plain>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<Plain>>::format(msg),
         }
     }
 }
@@ -75,6 +125,354 @@ impl CiMessage<GitHub> for CargoMessage {
                 <BuildScriptExecuted as CiMessage<GitHub>>::format(msg)
             }
             Self::BuildFinished(msg) => <BuildFinished as CiMessage<GitHub>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<GitHub>>::format(msg),
+            Self::BuildScriptWarning(msg) => <BuildScriptWarning as CiMessage<GitHub>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<GitHub>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<GitHub>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<GitHub>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<GitHub>>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<AzureDevOps> for CargoMessage {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::CompilerMessage(msg) => <CompilerMessage as CiMessage<AzureDevOps>>::format(msg),
+            Self::CompilerArtifact(msg) => {
+                <CompilerArtifact as CiMessage<AzureDevOps>>::format(msg)
+            }
+            Self::BuildScriptExecuted(msg) => {
+                <BuildScriptExecuted as CiMessage<AzureDevOps>>::format(msg)
+            }
+            Self::BuildFinished(msg) => <BuildFinished as CiMessage<AzureDevOps>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<AzureDevOps>>::format(msg),
+            Self::BuildScriptWarning(msg) => {
+                <BuildScriptWarning as CiMessage<AzureDevOps>>::format(msg)
+            }
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<AzureDevOps>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<AzureDevOps>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<AzureDevOps>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<AzureDevOps>>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<Buildkite> for CargoMessage {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::CompilerMessage(msg) => <CompilerMessage as CiMessage<Buildkite>>::format(msg),
+            Self::CompilerArtifact(msg) => <CompilerArtifact as CiMessage<Buildkite>>::format(msg),
+            Self::BuildScriptExecuted(msg) => {
+                <BuildScriptExecuted as CiMessage<Buildkite>>::format(msg)
+            }
+            Self::BuildFinished(msg) => <BuildFinished as CiMessage<Buildkite>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<Buildkite>>::format(msg),
+            Self::BuildScriptWarning(msg) => {
+                <BuildScriptWarning as CiMessage<Buildkite>>::format(msg)
+            }
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<Buildkite>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<Buildkite>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<Buildkite>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<Buildkite>>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<CodeBuild> for CargoMessage {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::CompilerMessage(msg) => <CompilerMessage as CiMessage<CodeBuild>>::format(msg),
+            Self::CompilerArtifact(msg) => <CompilerArtifact as CiMessage<CodeBuild>>::format(msg),
+            Self::BuildScriptExecuted(msg) => {
+                <BuildScriptExecuted as CiMessage<CodeBuild>>::format(msg)
+            }
+            Self::BuildFinished(msg) => <BuildFinished as CiMessage<CodeBuild>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<CodeBuild>>::format(msg),
+            Self::BuildScriptWarning(msg) => {
+                <BuildScriptWarning as CiMessage<CodeBuild>>::format(msg)
+            }
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<CodeBuild>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<CodeBuild>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<CodeBuild>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<CodeBuild>>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<CloudBuild> for CargoMessage {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::CompilerMessage(msg) => <CompilerMessage as CiMessage<CloudBuild>>::format(msg),
+            Self::CompilerArtifact(msg) => <CompilerArtifact as CiMessage<CloudBuild>>::format(msg),
+            Self::BuildScriptExecuted(msg) => {
+                <BuildScriptExecuted as CiMessage<CloudBuild>>::format(msg)
+            }
+            Self::BuildFinished(msg) => <BuildFinished as CiMessage<CloudBuild>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<CloudBuild>>::format(msg),
+            Self::BuildScriptWarning(msg) => {
+                <BuildScriptWarning as CiMessage<CloudBuild>>::format(msg)
+            }
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<CloudBuild>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<CloudBuild>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<CloudBuild>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<CloudBuild>>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<Terminal> for CargoMessage {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::CompilerMessage(msg) => <CompilerMessage as CiMessage<Terminal>>::format(msg),
+            Self::CompilerArtifact(msg) => <CompilerArtifact as CiMessage<Terminal>>::format(msg),
+            Self::BuildScriptExecuted(msg) => {
+                <BuildScriptExecuted as CiMessage<Terminal>>::format(msg)
+            }
+            Self::BuildFinished(msg) => <BuildFinished as CiMessage<Terminal>>::format(msg),
+            Self::TimingInfo(msg) => <TimingInfo as CiMessage<Terminal>>::format(msg),
+            Self::BuildScriptWarning(msg) => {
+                <BuildScriptWarning as CiMessage<Terminal>>::format(msg)
+            }
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<Terminal>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<Terminal>>::format(msg),
+            Self::IncompleteBuild(msg) => <IncompleteBuild as CiMessage<Terminal>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<Terminal>>::format(msg),
+        }
+    }
+}
+
+impl Classify for CargoMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::CompilerMessage(msg) => msg.severity(),
+            Self::CompilerArtifact(msg) => msg.severity(),
+            Self::BuildScriptExecuted(msg) => msg.severity(),
+            Self::BuildFinished(msg) => msg.severity(),
+            Self::TimingInfo(msg) => msg.severity(),
+            Self::BuildScriptWarning(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::IncompleteBuild(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::CompilerMessage(msg) => msg.kind(),
+            Self::CompilerArtifact(msg) => msg.kind(),
+            Self::BuildScriptExecuted(msg) => msg.kind(),
+            Self::BuildFinished(msg) => msg.kind(),
+            Self::TimingInfo(msg) => msg.kind(),
+            Self::BuildScriptWarning(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::IncompleteBuild(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::IncompleteBuild(msg) => msg.is_incomplete(),
+            Self::CompilerMessage(_)
+            | Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::CompilerMessage(msg) => msg.sort_key(),
+            Self::CompilerArtifact(msg) => msg.sort_key(),
+            Self::BuildScriptExecuted(msg) => msg.sort_key(),
+            Self::TimingInfo(msg) => msg.sort_key(),
+            Self::BuildFinished(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::CompilerMessage(msg) => msg.code(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn lint(&self) -> Option<&str> {
+        match self {
+            Self::CompilerMessage(msg) => msg.lint(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        match self {
+            Self::CompilerMessage(msg) => msg.fixes(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn snippet(&self) -> Option<String> {
+        match self {
+            Self::CompilerMessage(msg) => msg.snippet(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::CompilerMessage(msg) => msg.file(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::CompilerMessage(msg) => msg.message(),
+            Self::BuildScriptWarning(msg) => msg.message(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        match self {
+            Self::CompilerMessage(msg) => msg.package(),
+            Self::BuildScriptWarning(msg) => msg.package(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn build_result(&self) -> Option<bool> {
+        match self {
+            Self::BuildFinished(msg) => msg.build_result(),
+            Self::CompilerMessage(_)
+            | Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn binary(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::CompilerArtifact(msg) => msg.binary(),
+            Self::CompilerMessage(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::TimingInfo(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Captured for CargoMessage {}
+
+impl Timed for CargoMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::CompilerMessage(msg) => msg.timing(),
+            Self::TimingInfo(msg) => msg.timing(),
+            Self::CompilerArtifact(_)
+            | Self::BuildScriptExecuted(_)
+            | Self::BuildFinished(_)
+            | Self::BuildScriptWarning(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteBuild(_)
+            | Self::TextOutput(_) => None,
         }
     }
 }
@@ -82,8 +480,200 @@ impl CiMessage<GitHub> for CargoMessage {
 /// Tool implementation for parsing cargo JSON output.
 #[derive(Debug, Clone, Default)]
 pub struct CargoCheck {
-    /// Buffer for incomplete JSON lines.
-    buffer: Vec<u8>,
+    /// Framer for splitting the output into JSON lines.
+    framer: LineFramer,
+    /// Handling of lines which are not JSON.
+    text: TextLines,
+    /// Whether child diagnostics are folded into their parent's annotation.
+    fold_children: bool,
+    /// The codes of the lints denied by policy.
+    denied: HashSet<String>,
+    /// Whether the source text of diagnostics is read from disk when absent.
+    read_sources: bool,
+    /// The targets for which each diagnostic has been reported, keyed by
+    /// its fingerprint.
+    reported: HashMap<String, Vec<String>>,
+    /// The path to the build script of each package, keyed by the package's
+    /// name.
+    build_scripts: HashMap<String, String>,
+    /// Whether cargo reported the result of the build.
+    finished: bool,
+}
+
+impl CargoCheck {
+    /// A problem matcher for the human-readable diagnostics of rustc, as
+    /// printed by `cargo check` without `--message-format json`.
+    ///
+    /// The diagnostic's header (e.g., `error[E0308]: mismatched types`) gives
+    /// its severity, code and message, and the following line (e.g.,
+    /// `  --> src/main.rs:4:18`) gives its location. Diagnostics without a
+    /// location, such as cargo's own summary, are not matched.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cargo-check",
+            vec![
+                Pattern::new(r"^(warning|error)(?:\[(\S+)\])?: (.+)$")
+                    .severity(1)
+                    .code(2)
+                    .message(3),
+                Pattern::new(r"^\s*--> (.+?):(\d+):(\d+)$")
+                    .file(1)
+                    .line(2)
+                    .column(3),
+            ],
+        )
+    }
+
+    /// Mark the parsed diagnostics for folding, if enabled.
+    fn fold(&self, results: &mut [Result<CargoMessage, serde_json::Error>]) {
+        if !self.fold_children {
+            return;
+        }
+        for msg in results.iter_mut().flatten() {
+            if let CargoMessage::CompilerMessage(compiler_msg) = msg {
+                compiler_msg.fold_children();
+            }
+        }
+    }
+
+    /// Mark the parsed diagnostics of denied lints.
+    fn deny(&self, results: &mut [Result<CargoMessage, serde_json::Error>]) {
+        if self.denied.is_empty() {
+            return;
+        }
+        for msg in results.iter_mut().flatten() {
+            if let CargoMessage::CompilerMessage(compiler_msg) = msg {
+                compiler_msg.deny(&self.denied);
+            }
+        }
+    }
+
+    /// Mark the parsed diagnostics of documentation lints.
+    pub(crate) fn document(results: &mut [Result<CargoMessage, serde_json::Error>]) {
+        for msg in results.iter_mut().flatten() {
+            if let CargoMessage::CompilerMessage(compiler_msg) = msg {
+                compiler_msg.document();
+            }
+        }
+    }
+
+    /// Read the source text of the parsed diagnostics from disk where absent,
+    /// if enabled.
+    fn load_sources(&self, results: &mut [Result<CargoMessage, serde_json::Error>]) {
+        if !self.read_sources {
+            return;
+        }
+        for msg in results.iter_mut().flatten() {
+            if let CargoMessage::CompilerMessage(compiler_msg) = msg {
+                compiler_msg.load_sources();
+            }
+        }
+    }
+
+    /// Attribute the warnings of build scripts to the build script of their
+    /// package.
+    ///
+    /// The path to a package's build script is taken from the artifact of its
+    /// compilation, which precedes the build script's execution.
+    fn attribute(&mut self, results: &mut [Result<CargoMessage, serde_json::Error>]) {
+        for msg in results.iter_mut().flatten() {
+            if let CargoMessage::CompilerArtifact(artifact) = msg
+                && artifact
+                    .target
+                    .kind
+                    .iter()
+                    .any(|kind| kind == "custom-build")
+                && let Some(path) = artifact.target.src_path.to_str()
+            {
+                self.build_scripts.insert(
+                    package_name(&artifact.package_id).to_owned(),
+                    path.to_owned(),
+                );
+            } else if let CargoMessage::BuildScriptWarning(warning) = msg {
+                warning.build_script = self.build_scripts.get(&warning.package).cloned();
+            }
+        }
+    }
+
+    /// Record whether cargo reported the result of the build.
+    fn track(&mut self, results: &[Result<CargoMessage, serde_json::Error>]) {
+        self.finished |= results
+            .iter()
+            .flatten()
+            .any(|msg| matches!(msg, CargoMessage::BuildFinished(_)));
+    }
+
+    /// Mark the diagnostics which were already reported for another target.
+    ///
+    /// Cargo reports the diagnostics for each target it compiles, so those
+    /// in a library are typically reported again for its tests and binaries.
+    fn deduplicate(&mut self, results: &mut [Result<CargoMessage, serde_json::Error>]) {
+        for msg in results.iter_mut().flatten() {
+            let CargoMessage::CompilerMessage(compiler_msg) = msg else {
+                continue;
+            };
+            let Some(fingerprint) = compiler_msg.fingerprint() else {
+                continue;
+            };
+            let target = compiler_msg.target_label();
+            match self.reported.get_mut(&fingerprint) {
+                Some(targets) => {
+                    if !targets.contains(&target) {
+                        targets.push(target);
+                    }
+                    compiler_msg.duplicate_of = Some(targets.clone());
+                }
+                None => {
+                    self.reported.insert(fingerprint, vec![target]);
+                }
+            }
+        }
+    }
+}
+
+/// Parse a single line of cargo's output.
+///
+/// # Returns
+///
+/// The parsed messages, which are empty if the line should be skipped.
+fn parse_line(
+    next: Line<'_>,
+    max_length: usize,
+    text: &mut TextLines,
+) -> Vec<Result<CargoMessage, serde_json::Error>> {
+    let line = match next {
+        Line::Complete(line) => line,
+        Line::Overlong(line) => {
+            let mut results = vec![Ok(CargoMessage::OverlongLine(OverlongLine { max_length }))];
+            results.extend(parse_line(Line::Complete(line), max_length, text));
+            return results;
+        }
+    };
+
+    // Skip empty lines
+    if line.is_empty() {
+        return Vec::new();
+    }
+
+    // Try to parse as JSON
+    let result = match serde_json::from_slice::<CargoMessage>(line) {
+        Ok(msg) => Some(Ok(msg)),
+        // Only report error if it looks like JSON (starts with '{'), otherwise
+        // pass non-JSON lines (like plain text output) through
+        Err(e) if line.first() == Some(&b'{') => Some(Err(e)),
+        Err(_) => match str::from_utf8(line)
+            .ok()
+            .and_then(BuildScriptWarning::parse)
+        {
+            Some(warning) => Some(Ok(CargoMessage::BuildScriptWarning(warning))),
+            None => text
+                .handle(line)
+                .map(|output| Ok(CargoMessage::TextOutput(output))),
+        },
+    };
+    result.into_iter().collect()
 }
 
 impl Detect for CargoCheck {
@@ -114,40 +704,86 @@ impl Tool for CargoCheck {
 
     #[inline]
     fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
-        let mut results = Vec::new();
-
-        // Append new data to buffer
-        self.buffer.extend_from_slice(buf);
+        self.framer.push(buf);
 
         // Process complete lines
-        while let Some(newline_pos) = self.buffer.iter().position(|&b| b == b'\n') {
-            // Extract line bytes (including newline)
-            let mut line_bytes = self.buffer.drain(..=newline_pos).collect::<Vec<u8>>();
-            if line_bytes.last() == Some(&b'\n') {
-                line_bytes.pop();
-            }
-            let line = line_bytes.as_slice();
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(parse_line(line, max_length, &mut self.text));
+        }
 
-            // Skip empty lines
-            if line.is_empty() {
-                continue;
-            }
+        self.track(&results);
+        self.attribute(&mut results);
+        self.fold(&mut results);
+        self.deny(&mut results);
+        self.load_sources(&mut results);
+        self.deduplicate(&mut results);
+        results
+    }
 
-            // Try to parse as JSON
-            match serde_json::from_slice::<CargoMessage>(line) {
-                Ok(msg) => results.push(Ok(msg)),
-                Err(e) => {
-                    // Only report error if it looks like JSON (starts with '{')
-                    if line.first() == Some(&b'{') {
-                        results.push(Err(e));
-                    }
-                    // Otherwise skip non-JSON lines (like plain text output)
-                }
-            }
-        }
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let final_line: Vec<_> = self
+            .framer
+            .finish()
+            .into_iter()
+            .flat_map(|line| {
+                parse_line(line, max_length, &mut self.text)
+                    .into_iter()
+                    .map(move |result| {
+                        result.or_else(|error| {
+                            TruncatedLine::from_final_line(position, line, error)
+                                .map(CargoMessage::TruncatedLine)
+                        })
+                    })
+            })
+            .collect();
+        let collected = self
+            .text
+            .finish()
+            .map(|output| Ok(CargoMessage::TextOutput(output)));
+        let mut results: Vec<_> = final_line.into_iter().chain(collected).collect();
 
+        self.track(&results);
+        if !self.finished {
+            results.push(Ok(CargoMessage::IncompleteBuild(IncompleteBuild)));
+        }
+        self.attribute(&mut results);
+        self.fold(&mut results);
+        self.deny(&mut results);
+        self.load_sources(&mut results);
+        self.deduplicate(&mut results);
         results
     }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        &[
+            ToolOption::FoldChildren,
+            ToolOption::DeniedLints,
+            ToolOption::ReadSources,
+        ]
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        self.fold_children = options.fold_children;
+        self.denied = options.denied_lints.iter().cloned().collect();
+        self.read_sources = options.read_sources;
+    }
 }
 
 impl<P: Platform> DynTool<P> for CargoCheck
@@ -167,14 +803,26 @@ where
             .map(|msg| msg.format())
             .collect()
     }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use std::fs;
+
     use super::CargoMessage;
     use crate::{
-        ci::{GitHub, Plain},
-        ci_message::CiMessage,
+        ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+        ci_message::{CiMessage, Classify, Severity},
+        tool::{CargoCheck, Tool, ToolOptions},
     };
     use pretty_assertions::assert_eq;
 
@@ -201,6 +849,10 @@ pub(crate) mod tests {
                 super::build_finished::tests::cases()
                     .map(|(desc, json, msg)| (desc, json, CargoMessage::BuildFinished(msg))),
             )
+            .chain(
+                super::timing_info::tests::cases()
+                    .map(|(desc, json, msg)| (desc, json, CargoMessage::TimingInfo(msg))),
+            )
     }
 
     #[test]
@@ -229,4 +881,270 @@ pub(crate) mod tests {
             insta::assert_snapshot!(formatted);
         }
     }
+
+    #[test]
+    fn format_azure() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <CargoMessage as CiMessage<AzureDevOps>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_buildkite() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <CargoMessage as CiMessage<Buildkite>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_codebuild() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <CargoMessage as CiMessage<CodeBuild>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_cloudbuild() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <CargoMessage as CiMessage<CloudBuild>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_terminal() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            // Escape sequences are shown as `\e`, to keep the snapshots legible.
+            let formatted =
+                <CargoMessage as CiMessage<Terminal>>::format(&message).replace('\x1b', "\\e");
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn fold_children() {
+        let mut tool = CargoCheck::default();
+        Tool::set_options(&mut tool, &ToolOptions::default().with_fold_children(true));
+
+        let (_, json, _) = cases()
+            .find(|(desc, _, _)| desc == "compiler_message_target_bin_warning_with_fix")
+            .expect("case exists");
+        let messages = tool
+            .parse(format!("{json}\n").as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        let [message] = messages.as_slice() else {
+            panic!("expected a single message, got {messages:?}");
+        };
+
+        insta::assert_snapshot!(<CargoMessage as CiMessage<GitHub>>::format(message), @"
+        ::group::warning: unused variable: `x`
+        src/main.rs:3:9: warning: unused_variables: unused variable: `x`
+        src/main.rs:3:9: notice:  help: if this is intentional, prefix it with an underscore
+          --> src/main.rs:3:9
+        -    let x = 5;
+        +    let _x = 5;
+        ::endgroup::
+        ::warning file=src/main.rs,line=3,col=9,endLine=3,endColumn=10,title=warning%3A unused_variables::unused variable: `x` (suggested fix: change line 3 to `let _x = 5;`) - help: if this is intentional, prefix it with an underscore
+        ");
+        insta::assert_snapshot!(<CargoMessage as CiMessage<AzureDevOps>>::format(message), @"
+        ##[group]warning: unused variable: `x`
+        src/main.rs:3:9: warning: unused_variables: unused variable: `x`
+        src/main.rs:3:9: notice:  help: if this is intentional, prefix it with an underscore
+          --> src/main.rs:3:9
+        -    let x = 5;
+        +    let _x = 5;
+        ##[endgroup]
+        ##vso[task.logissue type=warning;sourcepath=src/main.rs;linenumber=3;columnnumber=9;code=unused_variables]unused variable: `x` (suggested fix: change line 3 to `let _x = 5;`)%0Ahelp: if this is intentional, prefix it with an underscore
+        ");
+    }
+
+    #[test]
+    fn denied_lints() {
+        let mut tool = CargoCheck::default();
+        Tool::set_options(
+            &mut tool,
+            &ToolOptions::default().with_denied_lints(vec!["unused_variables".to_owned()]),
+        );
+
+        let lines: Vec<_> = [
+            "compiler_message_target_bin_warning_with_fix",
+            "compiler_message_target_bin_warning_without_code",
+        ]
+        .iter()
+        .map(|name| {
+            let (_, json, _) = cases()
+                .find(|(desc, _, _)| desc == name)
+                .expect("case exists");
+            json.to_string()
+        })
+        .collect();
+        let messages = tool
+            .parse(format!("{}\n", lines.join("\n")).as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        assert_eq!(
+            messages.iter().map(Classify::severity).collect::<Vec<_>>(),
+            [Severity::Error, Severity::Warning]
+        );
+
+        let formatted: Vec<_> = messages
+            .iter()
+            .map(<CargoMessage as CiMessage<GitHub>>::format)
+            .collect();
+        insta::assert_snapshot!(formatted.join(""), @"
+        ::error file=src/main.rs,line=3,col=9,endLine=3,endColumn=10,title=denied warning%3A unused_variables::unused variable: `x` (suggested fix: change line 3 to `let _x = 5;`)
+        ::notice file=src/main.rs,line=3,col=9,title=help::if this is intentional, prefix it with an underscore
+        ::warning file=src/lib.rs,line=1,col=5,endLine=1,endColumn=13,title=warning::unused import: `std::io`
+        ");
+    }
+
+    #[test]
+    fn read_sources() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "fn main() {\n\n    let x = 5;\n}\n").expect("write failed");
+        let file_name = path.to_string_lossy();
+
+        let (_, mut json, _) = cases()
+            .find(|(desc, _, _)| desc == "compiler_message_target_bin_error_with_code")
+            .expect("case exists");
+        let span = json
+            .pointer_mut("/message/spans/0")
+            .and_then(serde_json::Value::as_object_mut)
+            .expect("case has a span");
+        span.insert("file_name".to_owned(), file_name.as_ref().into());
+        span.insert("text".to_owned(), serde_json::json!([]));
+
+        let line = format!("{json}\n");
+        let snippets: Vec<_> = [false, true]
+            .into_iter()
+            .flat_map(|read| {
+                let mut tool = CargoCheck::default();
+                Tool::set_options(&mut tool, &ToolOptions::default().with_read_sources(read));
+                tool.parse(line.as_bytes())
+            })
+            .map(|message| message.expect("Failed to parse").snippet())
+            .collect();
+        assert_eq!(
+            snippets,
+            [
+                None,
+                Some(format!(
+                    " --> {file_name}:3:9\n  |\n3 |     let x = 5;\n  |         ^ unused variable\n"
+                ))
+            ]
+        );
+    }
+
+    #[test]
+    fn deduplicate() {
+        let mut tool = CargoCheck::default();
+
+        let lines: Vec<_> = [
+            "compiler_message_target_lib_error_with_code",
+            "compiler_message_target_bin_error_with_code",
+            "compiler_message_target_bin_warning_without_code",
+            "compiler_message_target_lib_error_with_code",
+        ]
+        .iter()
+        .map(|name| {
+            let (_, json, _) = cases()
+                .find(|(desc, _, _)| desc == name)
+                .expect("case exists");
+            json.to_string()
+        })
+        .collect();
+        let formatted: Vec<_> = tool
+            .parse(format!("{}\n", lines.join("\n")).as_bytes())
+            .into_iter()
+            .map(|msg| <CargoMessage as CiMessage<GitHub>>::format(&msg.expect("Failed to parse")))
+            .collect();
+
+        insta::assert_snapshot!(formatted.join("\n"), @"
+        ::error file=src/main.rs,line=3,col=9,endLine=3,endColumn=10,title=error%3A unused_variables::unused variable: `x`
+
+        ::debug::Already reported error: unused variable: `x` (targets: mylib (lib), myapp (bin))
+        ::warning file=src/lib.rs,line=1,col=5,endLine=1,endColumn=13,title=warning::unused import: `std::io`
+
+        ::debug::Already reported error: unused variable: `x` (targets: mylib (lib), myapp (bin))
+        ");
+    }
+
+    #[test]
+    fn build_script_warning() {
+        let mut tool = CargoCheck::default();
+        let input = concat!(
+            "warning: other@1.0.0: unattributed\n",
+            r#"{"reason":"compiler-artifact","package_id":"path+file:///w/mypackage#0.1.0","#,
+            r#""manifest_path":"/w/mypackage/Cargo.toml","#,
+            r#""target":{"kind":["custom-build"],"crate_types":["bin"],"name":"build-script-build","#,
+            r#""src_path":"/w/mypackage/build.rs","edition":"2024","doc":false,"doctest":false,"test":false},"#,
+            r#""profile":{"opt_level":"0","debuginfo":0,"debug_assertions":true,"overflow_checks":true,"test":false},"#,
+            r#""features":[],"filenames":[],"executable":null,"fresh":false}"#,
+            "\n",
+            "warning: mypackage@0.1.0: libfoo not found, using the bundled copy\n",
+            "warning: unused manifest key: package.foo\n",
+        );
+        let messages: Vec<_> = tool
+            .parse(input.as_bytes())
+            .into_iter()
+            .map(|msg| msg.expect("Failed to parse"))
+            .collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages.get(2).and_then(Classify::package),
+            Some("mypackage")
+        );
+
+        let formatted: Vec<_> = messages
+            .iter()
+            .map(<CargoMessage as CiMessage<GitHub>>::format)
+            .collect();
+        insta::assert_snapshot!(formatted.join("\n"), @"
+        ::warning title=Build script warning%3A other@1.0.0::unattributed
+        ::debug::Built artifact: build-script-build (custom-build)
+        ::warning file=/w/mypackage/build.rs,line=1,title=Build script warning%3A mypackage@0.1.0::libfoo not found, using the bundled copy
+        ");
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let regexes: Vec<_> = CargoCheck::problem_matcher()
+            .pattern
+            .iter()
+            .map(|pattern| regex::Regex::new(&pattern.regexp).expect("invalid regexp"))
+            .collect();
+        let [header, location] = regexes.as_slice() else {
+            panic!("expected two patterns");
+        };
+        let groups = |regex: &regex::Regex, line: &'static str| {
+            let captures = regex.captures(line).expect("line not matched");
+            [1, 2, 3].map(|i| captures.get(i).map(|m| m.as_str()))
+        };
+
+        assert_eq!(
+            groups(header, "error[E0308]: mismatched types"),
+            [Some("error"), Some("E0308"), Some("mismatched types")]
+        );
+        assert_eq!(
+            groups(header, "warning: unused variable: `x`"),
+            [Some("warning"), None, Some("unused variable: `x`")]
+        );
+        assert_eq!(
+            groups(location, "   --> src/main.rs:4:18"),
+            [Some("src/main.rs"), Some("4"), Some("18")]
+        );
+        assert!(!location.is_match("error: could not compile `foo`"));
+    }
 }
@@ -5,8 +5,9 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    catalog::Title,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
 };
 
 /// Build finished message.
@@ -30,12 +31,66 @@ impl CiMessage<GitHub> for BuildFinished {
     fn format(&self) -> String {
         if self.success {
             GitHub::notice("Build finished successfully")
-                .title("Build Complete")
+                .title(Title::BuildComplete.text())
                 .format()
         } else {
-            GitHub::error("Build failed").title("Build Failed").format()
+            GitHub::error("Build failed")
+                .title(Title::BuildFailed.text())
+                .format()
+        }
+    }
+}
+
+impl CiMessage<AzureDevOps> for BuildFinished {
+    fn format(&self) -> String {
+        if self.success {
+            format!("{}: Build finished successfully\n", Title::BuildComplete)
+        } else {
+            AzureDevOps::error("Build failed").format()
+        }
+    }
+}
+
+impl CiMessage<Buildkite> for BuildFinished {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for BuildFinished {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for BuildFinished {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for BuildFinished {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for BuildFinished {
+    fn severity(&self) -> Severity {
+        if self.success {
+            Severity::Notice
+        } else {
+            Severity::Error
         }
     }
+
+    fn build_result(&self) -> Option<bool> {
+        Some(self.success)
+    }
 }
 
 #[cfg(test)]
@@ -9,8 +9,8 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
 };
 
 /// Build script execution result.
@@ -42,6 +42,50 @@ impl CiMessage<GitHub> for BuildScriptExecuted {
     }
 }
 
+impl CiMessage<AzureDevOps> for BuildScriptExecuted {
+    fn format(&self) -> String {
+        AzureDevOps::debug(format!("Build script executed: {}", self.package_id))
+    }
+}
+
+impl CiMessage<Buildkite> for BuildScriptExecuted {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for BuildScriptExecuted {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for BuildScriptExecuted {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for BuildScriptExecuted {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for BuildScriptExecuted {
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.package_id)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::BuildScriptExecuted;
@@ -0,0 +1,113 @@
+//! Warnings emitted by build scripts.
+//!
+//! Build scripts emit warnings by printing `cargo::warning=MESSAGE` (or
+//! `cargo:warning=MESSAGE`). These are not part of Cargo's JSON messages, but
+//! are written by Cargo to stderr as `warning: NAME@VERSION: MESSAGE` once the
+//! build script has run. When stderr is interleaved with the JSON messages
+//! (e.g., `cargo check --message-format json 2>&1`), such lines are parsed
+//! into a [`BuildScriptWarning`], which is attributed to the package's build
+//! script.
+
+use crate::{
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+};
+
+/// A warning emitted by a build script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BuildScriptWarning {
+    /// The name of the package whose build script emitted the warning.
+    pub package: String,
+    /// The version of the package.
+    pub version: String,
+    /// The warning.
+    pub message: String,
+    /// The path to the build script, if known from the artifact of its
+    /// compilation.
+    pub build_script: Option<String>,
+}
+
+impl BuildScriptWarning {
+    /// Parse a line written by Cargo for a build script's warning.
+    ///
+    /// Returns `None` if the line is not such a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line, without its line terminator.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix("warning: ")?;
+        let (spec, message) = rest.split_once(": ")?;
+        let (package, version) = spec.split_once('@')?;
+        if package.is_empty() || version.is_empty() || spec.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Self {
+            package: package.to_owned(),
+            version: version.to_owned(),
+            message: message.to_owned(),
+            build_script: None,
+        })
+    }
+}
+
+/// The warning is annotated on the build script, if known. Build scripts do
+/// not report the line emitting a warning, so the annotation refers to the
+/// first line of the build script.
+impl<P: Platform> CiMessage<P> for BuildScriptWarning {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            &self.message,
+            self.build_script.as_deref().map(|path| (path, 1, None)),
+            Some(&format!(
+                "Build script warning: {}@{}",
+                self.package, self.version
+            )),
+        )
+    }
+}
+
+impl Classify for BuildScriptWarning {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        Some(&self.package)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::BuildScriptWarning;
+
+    #[rstest]
+    #[case("warning: mypackage@0.1.0: libfoo not found", Some(("mypackage", "0.1.0", "libfoo not found")))]
+    #[case("warning: my-sys@1.2.3-rc.1: a: b", Some(("my-sys", "1.2.3-rc.1", "a: b")))]
+    #[case("warning: unused manifest key: package.foo", None)]
+    #[case("warning: build failed, waiting for other jobs to finish...", None)]
+    #[case("warning: user@example.com wrote: hello", None)]
+    #[case("error: mypackage@0.1.0: failed", None)]
+    fn parse(#[case] line: &str, #[case] expected: Option<(&str, &str, &str)>) {
+        let parsed = BuildScriptWarning::parse(line);
+        assert_eq!(
+            parsed
+                .as_ref()
+                .map(|w| (w.package.as_str(), w.version.as_str(), w.message.as_str())),
+            expected
+        );
+    }
+}
@@ -43,12 +43,52 @@ pub struct Profile {
     pub test: bool,
 }
 
+/// The name of the package identified by a Cargo package ID.
+///
+/// Package IDs are either of the form `name version (source)` (before Cargo
+/// 1.77), or a package ID specification of the form `source#name@version`,
+/// where the name is omitted if it is the last segment of the source's path.
+pub(crate) fn package_name(package_id: &str) -> &str {
+    if let Some((name, _)) = package_id.split_once(' ') {
+        return name;
+    }
+    match package_id.split_once('#') {
+        Some((_, fragment)) if fragment.contains('@') => {
+            fragment.split_once('@').map_or(fragment, |(name, _)| name)
+        }
+        Some((source, _)) => {
+            let path = source.split_once('?').map_or(source, |(path, _)| path);
+            path.trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(path)
+        }
+        None => package_id,
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
-    use super::{Profile, Target};
+    use super::{Profile, Target, package_name};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
     use serde_json::json;
     use std::path::PathBuf;
 
+    #[rstest]
+    #[case("mypackage 0.1.0 (path+file:///path/to/package)", "mypackage")]
+    #[case("path+file:///path/to/package#mypackage@0.1.0", "mypackage")]
+    #[case("path+file:///path/to/mypackage#0.1.0", "mypackage")]
+    #[case(
+        "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.219",
+        "serde"
+    )]
+    #[case("git+https://github.com/org/repo.git?branch=main#foo@0.1.0", "foo")]
+    #[case("simple", "simple")]
+    fn package_names(#[case] package_id: &str, #[case] expected: &str) {
+        assert_eq!(package_name(package_id), expected);
+    }
+
     /// Test data for Target structs.
     pub fn target_cases() -> impl Iterator<Item = (String, serde_json::Value, Target)> {
         [
@@ -8,8 +8,8 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
     tool::cargo_check::common::{Profile, Target},
 };
 
@@ -70,6 +70,71 @@ impl CiMessage<GitHub> for CompilerArtifact {
     }
 }
 
+impl CiMessage<AzureDevOps> for CompilerArtifact {
+    fn format(&self) -> String {
+        if self.fresh {
+            AzureDevOps::debug(format!(
+                "Artifact up-to-date: {} ({})",
+                self.target.name,
+                self.target.kind.join(", ")
+            ))
+        } else {
+            AzureDevOps::debug(format!(
+                "Built artifact: {} ({})",
+                self.target.name,
+                self.target.kind.join(", ")
+            ))
+        }
+    }
+}
+
+impl CiMessage<Buildkite> for CompilerArtifact {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for CompilerArtifact {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for CompilerArtifact {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for CompilerArtifact {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for CompilerArtifact {
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+
+    fn sort_key(&self) -> Option<&str> {
+        self.target.src_path.to_str()
+    }
+
+    /// Executables built for tests and benchmarks are not tracked, as they are
+    /// not shipped.
+    fn binary(&self) -> Option<(&str, &str)> {
+        self.executable
+            .as_deref()
+            .filter(|_| !self.profile.test)
+            .map(|path| (self.target.name.as_str(), path))
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::CompilerArtifact;
@@ -8,12 +8,23 @@
 //! additional metadata about the package and target that generated the message.
 mod rustc_message;
 
+pub(crate) use rustc_message::{
+    Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan, DiagnosticSpanLine,
+};
+
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
-    tool::cargo_check::{common::Target, compiler_message::rustc_message::RustcMessage},
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
+    sarif::Fix,
+    timeline::{Timed, Timing},
+    tool::cargo_check::{
+        common::{Target, package_name},
+        compiler_message::rustc_message::RustcMessage,
+    },
+    tool::{cargo_doc, miri},
 };
 use serde::Deserialize;
+use std::collections::HashSet;
 
 /// Compiler message (errors, warnings, notes).
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -26,20 +37,222 @@ pub struct CompilerMessage {
     pub target: Target,
     /// The rustc message from the compiler.
     pub message: RustcMessage,
+    /// If the diagnostic was already reported for another target, the
+    /// targets for which it has been reported so far, including this one.
+    ///
+    /// The diagnostic is then only noted in the log, rather than annotated
+    /// again.
+    #[serde(skip)]
+    pub duplicate_of: Option<Vec<String>>,
+}
+
+impl CompilerMessage {
+    /// Fold the children of a diagnostic into its annotation.
+    ///
+    /// See [`ToolOptions::fold_children`](crate::tool::ToolOptions::fold_children).
+    pub(crate) fn fold_children(&mut self) {
+        if let RustcMessage::Diagnostic(diagnostic) = &mut self.message {
+            diagnostic.fold_children = true;
+        }
+    }
+
+    /// Mark a diagnostic as denied by policy, if its code is in the
+    /// deny-list.
+    ///
+    /// See [`ToolOptions::denied_lints`](crate::tool::ToolOptions::denied_lints).
+    pub(crate) fn deny(&mut self, denied: &HashSet<String>) {
+        if let RustcMessage::Diagnostic(diagnostic) = &mut self.message
+            && let Some(code) = &diagnostic.code
+        {
+            diagnostic.denied = denied.contains(&code.code);
+        }
+    }
+
+    /// Mark a diagnostic of a documentation lint with the title of its
+    /// annotation.
+    ///
+    /// See [`CargoDoc`](crate::tool::CargoDoc).
+    pub(crate) fn document(&mut self) {
+        if let RustcMessage::Diagnostic(diagnostic) = &mut self.message
+            && let Some(code) = &diagnostic.code
+        {
+            diagnostic.documentation = cargo_doc::lint_title(&code.code);
+        }
+    }
+
+    /// Mark a diagnostic of undefined behavior with its kind.
+    ///
+    /// See [`Miri`](crate::tool::Miri).
+    pub(crate) fn undefined_behavior(&mut self) {
+        if let RustcMessage::Diagnostic(diagnostic) = &mut self.message {
+            diagnostic.undefined_behavior = miri::undefined_behavior(&diagnostic.message);
+        }
+    }
+
+    /// Read the source text of a diagnostic from disk where absent.
+    ///
+    /// See [`ToolOptions::read_sources`](crate::tool::ToolOptions::read_sources).
+    pub(crate) fn load_sources(&mut self) {
+        if let RustcMessage::Diagnostic(diagnostic) = &mut self.message {
+            diagnostic.load_sources();
+        }
+    }
+
+    /// A key identifying the diagnostic, such that the same diagnostic
+    /// reported for several targets has the same key.
+    ///
+    /// Returns `None` for messages other than diagnostics.
+    pub(crate) fn fingerprint(&self) -> Option<String> {
+        match &self.message {
+            RustcMessage::Diagnostic(diagnostic) => Some(diagnostic.fingerprint()),
+            RustcMessage::Artifact(_)
+            | RustcMessage::FutureIncompat(_)
+            | RustcMessage::UnusedExterns(_)
+            | RustcMessage::SectionTiming(_) => None,
+        }
+    }
+
+    /// The name and kinds of the target, for example `mypackage (lib)`.
+    pub(crate) fn target_label(&self) -> String {
+        format!("{} ({})", self.target.name, self.target.kind.join(", "))
+    }
+
+    /// The note logged in place of a duplicate diagnostic.
+    fn duplicate_note(&self, targets: &[String]) -> String {
+        let summary = match &self.message {
+            RustcMessage::Diagnostic(diagnostic) => {
+                format!("{}: {}", diagnostic.level, diagnostic.message)
+            }
+            RustcMessage::Artifact(_)
+            | RustcMessage::FutureIncompat(_)
+            | RustcMessage::UnusedExterns(_)
+            | RustcMessage::SectionTiming(_) => "diagnostic".to_owned(),
+        };
+        format!(
+            "Already reported {} (targets: {})",
+            summary.lines().next().unwrap_or_default(),
+            targets.join(", ")
+        )
+    }
 }
 
 impl CiMessage<Plain> for CompilerMessage {
     fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return self.duplicate_note(targets);
+        }
         <RustcMessage as CiMessage<Plain>>::format(&self.message)
     }
 }
 
 impl CiMessage<GitHub> for CompilerMessage {
     fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return GitHub::debug(self.duplicate_note(targets));
+        }
         <RustcMessage as CiMessage<GitHub>>::format(&self.message)
     }
 }
 
+impl CiMessage<AzureDevOps> for CompilerMessage {
+    fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return AzureDevOps::debug(self.duplicate_note(targets));
+        }
+        <RustcMessage as CiMessage<AzureDevOps>>::format(&self.message)
+    }
+}
+
+impl CiMessage<Buildkite> for CompilerMessage {
+    fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return self.duplicate_note(targets);
+        }
+        <RustcMessage as CiMessage<Buildkite>>::format(&self.message)
+    }
+}
+
+impl CiMessage<CodeBuild> for CompilerMessage {
+    fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return self.duplicate_note(targets);
+        }
+        <RustcMessage as CiMessage<CodeBuild>>::format(&self.message)
+    }
+}
+
+impl CiMessage<CloudBuild> for CompilerMessage {
+    fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return CloudBuild::entry(Severity::Debug, self.duplicate_note(targets), None);
+        }
+        <RustcMessage as CiMessage<CloudBuild>>::format(&self.message)
+    }
+}
+
+impl CiMessage<Terminal> for CompilerMessage {
+    fn format(&self) -> String {
+        if let Some(targets) = &self.duplicate_of {
+            return Terminal::styled(Severity::Debug, self.duplicate_note(targets));
+        }
+        <RustcMessage as CiMessage<Terminal>>::format(&self.message)
+    }
+}
+
+impl Classify for CompilerMessage {
+    fn severity(&self) -> Severity {
+        if self.duplicate_of.is_some() {
+            return Severity::Debug;
+        }
+        self.message.severity()
+    }
+
+    fn kind(&self) -> &'static str {
+        self.message.kind()
+    }
+
+    fn sort_key(&self) -> Option<&str> {
+        self.target.src_path.to_str()
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.message.code()
+    }
+
+    fn lint(&self) -> Option<&str> {
+        self.message.lint()
+    }
+
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        self.message.fixes()
+    }
+
+    fn snippet(&self) -> Option<String> {
+        if self.duplicate_of.is_some() {
+            return None;
+        }
+        self.message.snippet()
+    }
+
+    fn package(&self) -> Option<&str> {
+        Some(package_name(&self.package_id))
+    }
+
+    fn file(&self) -> Option<&str> {
+        self.message.file()
+    }
+
+    fn message(&self) -> Option<&str> {
+        self.message.message()
+    }
+}
+
+impl Timed for CompilerMessage {
+    fn timing(&self) -> Option<Timing<'_>> {
+        self.message.timing(&self.target.name)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::CompilerMessage;
@@ -67,6 +280,7 @@ pub(crate) mod tests {
                         manifest_path: "/path/to/package/Cargo.toml".to_owned(),
                         target: target.clone(),
                         message: rustc_msg.clone(),
+                        duplicate_of: None,
                     },
                 )
             })
@@ -6,17 +6,26 @@ mod future_incompat;
 mod section_timing;
 mod unused_externs;
 
+pub(crate) use diagnostic::{
+    Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan, DiagnosticSpanLine,
+};
+
 use crate::{
-    ci::{GitHub, Plain},
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+    timeline::Timing,
     tool::cargo_check::compiler_message::rustc_message::{
-        artifact::Artifact, diagnostic::Diagnostic, section_timing::SectionTiming,
+        artifact::Artifact,
+        section_timing::{SectionTiming, TimingEvent},
         unused_externs::UnusedExterns,
     },
 };
 use crate::{
-    ci_message::CiMessage,
+    ci_message::{CiMessage, Classify, Severity},
+    sarif::Fix,
     tool::cargo_check::compiler_message::rustc_message::future_incompat::FutureIncompat,
 };
+use core::time::Duration;
+
 use serde::Deserialize;
 
 /// A message from rustc's JSON output.
@@ -43,6 +52,33 @@ pub enum RustcMessage {
     SectionTiming(SectionTiming),
 }
 
+impl RustcMessage {
+    /// The timing information carried by this message, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `track` - The track to which the timing belongs, typically the name
+    ///   of the target being compiled.
+    pub fn timing<'a>(&'a self, track: &'a str) -> Option<Timing<'a>> {
+        let Self::SectionTiming(section) = self else {
+            return None;
+        };
+        let time = Duration::from_micros(section.time);
+        Some(match section.event {
+            TimingEvent::Start => Timing::SectionStart {
+                track,
+                name: &section.name,
+                time,
+            },
+            TimingEvent::End => Timing::SectionEnd {
+                track,
+                name: &section.name,
+                time,
+            },
+        })
+    }
+}
+
 impl CiMessage<Plain> for RustcMessage {
     fn format(&self) -> String {
         match self {
@@ -67,6 +103,148 @@ impl CiMessage<GitHub> for RustcMessage {
     }
 }
 
+impl CiMessage<AzureDevOps> for RustcMessage {
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => CiMessage::<AzureDevOps>::format(msg),
+            Self::Artifact(msg) => CiMessage::<AzureDevOps>::format(msg),
+            Self::FutureIncompat(msg) => CiMessage::<AzureDevOps>::format(msg),
+            Self::UnusedExterns(msg) => CiMessage::<AzureDevOps>::format(msg),
+            Self::SectionTiming(msg) => CiMessage::<AzureDevOps>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<Buildkite> for RustcMessage {
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => CiMessage::<Buildkite>::format(msg),
+            Self::Artifact(msg) => CiMessage::<Buildkite>::format(msg),
+            Self::FutureIncompat(msg) => CiMessage::<Buildkite>::format(msg),
+            Self::UnusedExterns(msg) => CiMessage::<Buildkite>::format(msg),
+            Self::SectionTiming(msg) => CiMessage::<Buildkite>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<CodeBuild> for RustcMessage {
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => CiMessage::<CodeBuild>::format(msg),
+            Self::Artifact(msg) => CiMessage::<CodeBuild>::format(msg),
+            Self::FutureIncompat(msg) => CiMessage::<CodeBuild>::format(msg),
+            Self::UnusedExterns(msg) => CiMessage::<CodeBuild>::format(msg),
+            Self::SectionTiming(msg) => CiMessage::<CodeBuild>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<CloudBuild> for RustcMessage {
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => CiMessage::<CloudBuild>::format(msg),
+            Self::Artifact(msg) => CiMessage::<CloudBuild>::format(msg),
+            Self::FutureIncompat(msg) => CiMessage::<CloudBuild>::format(msg),
+            Self::UnusedExterns(msg) => CiMessage::<CloudBuild>::format(msg),
+            Self::SectionTiming(msg) => CiMessage::<CloudBuild>::format(msg),
+        }
+    }
+}
+
+impl CiMessage<Terminal> for RustcMessage {
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => CiMessage::<Terminal>::format(msg),
+            Self::Artifact(msg) => CiMessage::<Terminal>::format(msg),
+            Self::FutureIncompat(msg) => CiMessage::<Terminal>::format(msg),
+            Self::UnusedExterns(msg) => CiMessage::<Terminal>::format(msg),
+            Self::SectionTiming(msg) => CiMessage::<Terminal>::format(msg),
+        }
+    }
+}
+
+impl Classify for RustcMessage {
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Diagnostic(msg) => msg.severity(),
+            Self::Artifact(msg) => msg.severity(),
+            Self::FutureIncompat(msg) => msg.severity(),
+            Self::UnusedExterns(msg) => msg.severity(),
+            Self::SectionTiming(msg) => msg.severity(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Diagnostic(msg) => msg.kind(),
+            Self::Artifact(msg) => msg.kind(),
+            Self::FutureIncompat(msg) => msg.kind(),
+            Self::UnusedExterns(msg) => msg.kind(),
+            Self::SectionTiming(msg) => msg.kind(),
+        }
+    }
+
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.code(),
+            Self::Artifact(_)
+            | Self::FutureIncompat(_)
+            | Self::UnusedExterns(_)
+            | Self::SectionTiming(_) => None,
+        }
+    }
+
+    fn lint(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.lint(),
+            Self::Artifact(_)
+            | Self::FutureIncompat(_)
+            | Self::UnusedExterns(_)
+            | Self::SectionTiming(_) => None,
+        }
+    }
+
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        match self {
+            Self::Diagnostic(msg) => msg.fixes(),
+            Self::Artifact(_)
+            | Self::FutureIncompat(_)
+            | Self::UnusedExterns(_)
+            | Self::SectionTiming(_) => Vec::new(),
+        }
+    }
+
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.file(),
+            Self::Artifact(_)
+            | Self::FutureIncompat(_)
+            | Self::UnusedExterns(_)
+            | Self::SectionTiming(_) => None,
+        }
+    }
+
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.message(),
+            Self::Artifact(_)
+            | Self::FutureIncompat(_)
+            | Self::UnusedExterns(_)
+            | Self::SectionTiming(_) => None,
+        }
+    }
+
+    fn snippet(&self) -> Option<String> {
+        match self {
+            Self::Diagnostic(msg) => msg.snippet(),
+            Self::Artifact(_)
+            | Self::FutureIncompat(_)
+            | Self::UnusedExterns(_)
+            | Self::SectionTiming(_) => None,
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use crate::tool::cargo_check::compiler_message::rustc_message::diagnostic;
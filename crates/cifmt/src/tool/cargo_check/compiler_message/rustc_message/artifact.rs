@@ -8,8 +8,8 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
 };
 
 /// Artifact notification emitted when a file artifact has been saved to disk.
@@ -36,6 +36,49 @@ impl CiMessage<GitHub> for Artifact {
     }
 }
 
+impl CiMessage<AzureDevOps> for Artifact {
+    fn format(&self) -> String {
+        AzureDevOps::debug(format!(
+            "Generated artifact: {} ({})",
+            self.artifact, self.emit
+        ))
+    }
+}
+
+impl CiMessage<Buildkite> for Artifact {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for Artifact {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for Artifact {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for Artifact {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for Artifact {
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+}
+
 /// The kind of artifact that was generated.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "kebab-case")]
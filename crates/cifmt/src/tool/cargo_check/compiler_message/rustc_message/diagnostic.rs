@@ -1,7 +1,17 @@
 //! Diagnostic messages from rustc.
 
-use crate::ci::{GitHub, Plain};
-use crate::ci_message::CiMessage;
+use core::{fmt, iter};
+use std::fs;
+
+use crate::catalog::Title;
+use crate::ci::{
+    AZURE_ENDGROUP, Annotation, AnnotationParams, AzureDevOps, AzureGroup, Buildkite, CloudBuild,
+    CodeBuild, ENDGROUP, GitHub, Group, Issue, IssueParams, NormalizedPath, Plain, Terminal,
+    is_in_checkout,
+};
+use crate::ci_message::{CiMessage, Classify, Severity, insert_snippet};
+use crate::sarif::Fix;
+use crate::tool::miri::UbKind;
 use serde::{Deserialize, Serialize};
 
 /// A diagnostic message from the compiler.
@@ -19,38 +29,258 @@ pub struct Diagnostic {
     pub children: Vec<Diagnostic>,
     /// Rendered version of the diagnostic.
     pub rendered: Option<String>,
+    /// Whether the children are folded into the annotation of this
+    /// diagnostic, rather than annotated separately (see
+    /// [`ToolOptions::fold_children`](crate::tool::ToolOptions::fold_children)).
+    #[serde(skip)]
+    pub fold_children: bool,
+    /// Whether the lint is denied by policy (see
+    /// [`ToolOptions::denied_lints`](crate::tool::ToolOptions::denied_lints)), in
+    /// which case the diagnostic is annotated as an error, whichever level
+    /// rustc reported.
+    #[serde(skip)]
+    pub denied: bool,
+    /// The title of the annotation of a documentation lint (e.g., a broken
+    /// intra-doc link), reported by `cargo doc` (see
+    /// [`CargoDoc`](crate::tool::CargoDoc)), in place of its level and code.
+    #[serde(skip)]
+    pub documentation: Option<Title>,
+    /// The kind of undefined behavior reported by Miri (see
+    /// [`Miri`](crate::tool::Miri)), after which the annotation is titled in
+    /// place of its level.
+    #[serde(skip)]
+    pub undefined_behavior: Option<UbKind>,
 }
 
-impl CiMessage<Plain> for Diagnostic {
-    fn format(&self) -> String {
-        let mut result = String::new();
+/// A fix suggested by the compiler which can be applied automatically.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct SuggestedFix<'a> {
+    /// The span to replace.
+    pub span: &'a DiagnosticSpan,
+    /// The text replacing the span.
+    pub replacement: &'a str,
+}
 
-        // Format the main diagnostic
-        let annotation = match self.level {
-            DiagnosticLevel::Error | DiagnosticLevel::InternalCompilerError => {
-                let title = if let Some(code) = &self.code {
-                    format!("{}: {}", self.level, code.code)
-                } else {
-                    self.level.to_string()
-                };
+impl Diagnostic {
+    /// A key identifying the diagnostic, such that the same diagnostic
+    /// reported for several targets has the same key.
+    pub(crate) fn fingerprint(&self) -> String {
+        let code = self.code.as_ref().map_or("", |code| code.code.as_str());
+        let location = fmt::from_fn(|f| match self.spans.iter().find(|span| span.is_primary) {
+            Some(span) => write!(
+                f,
+                "{}:{}:{}",
+                span.file_name, span.line_start, span.column_start
+            ),
+            None => Ok(()),
+        });
+        format!("{}[{code}] {location}: {}", self.level, self.message)
+    }
+
+    /// The title of the annotation of a root diagnostic, made of its level
+    /// and code (or the title of a documentation lint, or of undefined
+    /// behavior), and prefixed with `denied` if the lint is denied by policy.
+    fn title(&self) -> String {
+        let prefix = if self.denied { "denied " } else { "" };
+        if let Some(title) = self.documentation {
+            return format!("{prefix}{}", title.text());
+        }
+        if let Some(kind) = self.undefined_behavior {
+            return format!("{prefix}{}", kind.title());
+        }
+        match &self.code {
+            Some(code) => format!("{prefix}{}: {}", self.level, code.code),
+            None => format!("{prefix}{}", self.level),
+        }
+    }
+
+    /// The title of a root diagnostic in plain text, which is that of its
+    /// annotation without the level, as the severity precedes it.
+    fn plain_title(&self) -> Option<String> {
+        let prefix = if self.denied { "denied " } else { "" };
+        if self.documentation.is_some() || self.undefined_behavior.is_some() {
+            return Some(self.title());
+        }
+        match &self.code {
+            Some(code) => Some(format!("{prefix}{}", code.code)),
+            None if self.denied => Some("denied".to_owned()),
+            None if self.level == DiagnosticLevel::InternalCompilerError => {
+                Some("internal compiler error".to_owned())
+            }
+            None => None,
+        }
+    }
 
-                format!("error: {} ({})\n", self.message, title)
+    /// The span at which the diagnostic is annotated.
+    ///
+    /// This is the primary span, unless it lies within the expansion of a
+    /// macro outside of the checkout (for example, `assert_eq!` in the
+    /// standard library), in which case the annotation is moved to the
+    /// innermost invocation of a macro within the checkout, or failing that,
+    /// the outermost invocation.
+    fn annotated_span(&self) -> Option<&DiagnosticSpan> {
+        let primary = self.spans.iter().find(|span| span.is_primary)?;
+        primary
+            .expansion_chain()
+            .find(|span| span.is_user_code())
+            .or_else(|| primary.expansion_chain().last())
+    }
+
+    /// Display a note listing the macros expanded between the annotated span
+    /// and the primary span, to follow the message of an annotation.
+    fn expansion_note(&self) -> impl fmt::Display {
+        let primary = self.spans.iter().find(|span| span.is_primary);
+        let annotated = self.annotated_span();
+        let macros: Vec<_> = primary
+            .into_iter()
+            .flat_map(DiagnosticSpan::expansion_chain)
+            .take_while(|span| Some(*span) != annotated)
+            .filter_map(|span| span.expansion.as_deref())
+            .map(|expansion| expansion.macro_decl_name.as_str())
+            .collect();
+        fmt::from_fn(move |f| {
+            for (i, name) in macros.iter().enumerate() {
+                f.write_str(if i == 0 {
+                    " (in the expansion of "
+                } else {
+                    " within "
+                })?;
+                write!(f, "`{name}`")?;
+            }
+            if macros.is_empty() {
+                Ok(())
+            } else {
+                f.write_str(")")
             }
-            DiagnosticLevel::Warning => {
-                let title = if let Some(code) = &self.code {
-                    format!("{}: {}", self.level, code.code)
+        })
+    }
+
+    /// Read the source text of the spans which lack it from disk (see
+    /// [`ToolOptions::read_sources`](crate::tool::ToolOptions::read_sources)).
+    pub(crate) fn load_sources(&mut self) {
+        for span in &mut self.spans {
+            span.load_text();
+        }
+    }
+
+    /// The fixes suggested by the compiler for this diagnostic, which can be
+    /// applied automatically.
+    ///
+    /// Suggestions are typically attached to the `help` children of a
+    /// diagnostic, and these are included. Only suggestions which rustc
+    /// considers [machine
+    /// applicable](SuggestionApplicability::MachineApplicable) are returned.
+    #[must_use]
+    pub fn suggested_fixes(&self) -> Vec<SuggestedFix<'_>> {
+        let mut fixes: Vec<_> = self.spans.iter().filter_map(DiagnosticSpan::fix).collect();
+        for child in &self.children {
+            fixes.extend(child.suggested_fixes());
+        }
+        fixes
+    }
+
+    /// Display a note summarizing the suggested fixes, to follow the message
+    /// of an annotation.
+    fn fix_note(&self) -> impl fmt::Display {
+        let fixes = self.suggested_fixes();
+        fmt::from_fn(move |f| {
+            for (i, fix) in fixes.iter().enumerate() {
+                f.write_str(if i == 0 { " (suggested fix: " } else { "; " })?;
+                let span = fix.span;
+                let lines = fmt::from_fn(|out| {
+                    if span.line_start == span.line_end {
+                        write!(out, "line {}", span.line_start)
+                    } else {
+                        write!(out, "lines {}-{}", span.line_start, span.line_end)
+                    }
+                });
+                // Annotations are single-line
+                let replaced = fix
+                    .replaced()
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if replaced.is_empty() {
+                    write!(f, "remove {lines}")?;
                 } else {
-                    self.level.to_string()
-                };
+                    write!(f, "change {lines} to `{replaced}`")?;
+                }
+            }
+            if fixes.is_empty() {
+                Ok(())
+            } else {
+                f.write_str(")")
+            }
+        })
+    }
 
-                format!("warning: {} ({})\n", self.message, title)
+    /// Display the messages of all descendants, to follow the message of an
+    /// annotation into which they are folded.
+    ///
+    /// # Arguments
+    ///
+    /// * `separator` - Precedes each message. Newlines within messages are
+    ///   replaced by spaces unless the separator contains one.
+    fn folded_children(&self, separator: &str) -> impl fmt::Display {
+        fmt::from_fn(move |f| {
+            for child in &self.children {
+                write!(f, "{separator}{}: ", child.level)?;
+                if separator.contains('\n') {
+                    f.write_str(&child.message)?;
+                } else {
+                    f.write_str(&child.message.replace('\n', " "))?;
+                }
+                write!(f, "{}", child.folded_children(separator))?;
             }
+            Ok(())
+        })
+    }
+
+    /// Display the full diagnostic, as rendered by rustc if available, to be
+    /// included in the log when the children are folded.
+    ///
+    /// Ends with a newline.
+    fn full_text(&self) -> impl fmt::Display {
+        fmt::from_fn(|f| match &self.rendered {
+            Some(rendered) if rendered.ends_with('\n') => f.write_str(rendered),
+            Some(rendered) => writeln!(f, "{rendered}"),
+            None => f.write_str(&<Self as CiMessage<Plain>>::format(self)),
+        })
+    }
+}
+
+impl CiMessage<Plain> for Diagnostic {
+    fn format(&self) -> String {
+        let mut result = String::new();
+
+        // Format the main diagnostic
+        let location = self.annotated_span().map(|span| {
+            (
+                span.file_name.as_str(),
+                span.line_start,
+                Some(span.column_start),
+            )
+        });
+        let title = match self.level {
+            DiagnosticLevel::Error
+            | DiagnosticLevel::InternalCompilerError
+            | DiagnosticLevel::Warning => self.plain_title(),
             DiagnosticLevel::Note | DiagnosticLevel::Help | DiagnosticLevel::FailureNote => {
-                format!("{}: {}\n", self.level, self.message)
+                Some(self.level.as_str().to_owned())
             }
         };
+        let annotation = Plain::line(self.severity(), &self.message, location, title.as_deref());
 
         result.push_str(&annotation);
+        result.push('\n');
+
+        // Show the suggested fixes as a diff of the affected lines
+        for fix in self.spans.iter().filter_map(DiagnosticSpan::fix) {
+            result.push_str(&fix.diff().to_string());
+        }
 
         // Format child diagnostics (notes, help messages, etc.)
         for child in &self.children {
@@ -63,78 +293,244 @@ impl CiMessage<Plain> for Diagnostic {
 
 impl CiMessage<GitHub> for Diagnostic {
     fn format(&self) -> String {
-        // Find the primary span for location information
-        let primary_span = self.spans.iter().find(|s| s.is_primary);
+        fmt::from_fn(|f| <Self as CiMessage<GitHub>>::write_to(self, f)).to_string()
+    }
 
-        let mut result = String::new();
+    fn write_to(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        // Find the span for location information
+        let span = self.annotated_span();
 
         // Format the main diagnostic
-        let annotation = match self.level {
-            DiagnosticLevel::Error | DiagnosticLevel::InternalCompilerError => {
-                let title = if let Some(code) = &self.code {
-                    format!("{}: {}", self.level, code.code)
-                } else {
-                    self.level.to_string()
-                };
-
-                if let Some(span) = primary_span {
-                    GitHub::error(&self.message)
-                        .file(&span.file_name)
-                        .line(span.line_start)
-                        .col(span.column_start)
-                        .end_line(span.line_end)
-                        .end_column(span.column_end)
-                        .title(&title)
-                        .format()
-                } else {
-                    GitHub::error(&self.message).title(&title).format()
-                }
-            }
-            DiagnosticLevel::Warning => {
-                let title = if let Some(code) = &self.code {
-                    format!("{}: {}", self.level, code.code)
-                } else {
-                    self.level.to_string()
-                };
-
-                if let Some(span) = primary_span {
-                    GitHub::warning(&self.message)
-                        .file(&span.file_name)
-                        .line(span.line_start)
-                        .col(span.column_start)
-                        .end_line(span.line_end)
-                        .end_column(span.column_end)
-                        .title(&title)
-                        .format()
+        let mut params = AnnotationParams {
+            file: span.map(|s| s.file_name.as_str()),
+            line: span.map(|s| s.line_start),
+            col: span.map(|s| s.column_start),
+            ..AnnotationParams::default()
+        };
+        let coded_title;
+        let mut is_root = false;
+        let command = match self.level {
+            DiagnosticLevel::Error
+            | DiagnosticLevel::InternalCompilerError
+            | DiagnosticLevel::Warning => {
+                is_root = true;
+                params.end_line = span.map(|s| s.line_end);
+                params.end_column = span.map(|s| s.column_end);
+                coded_title = self.title();
+                params.title = Some(&coded_title);
+
+                if self.severity() == Severity::Warning {
+                    "warning"
                 } else {
-                    GitHub::warning(&self.message).title(&title).format()
+                    "error"
                 }
             }
             DiagnosticLevel::Note | DiagnosticLevel::Help | DiagnosticLevel::FailureNote => {
                 // For child diagnostics, format as notice
-                if let Some(span) = primary_span {
-                    GitHub::notice(&self.message)
-                        .file(&span.file_name)
-                        .line(span.line_start)
-                        .col(span.column_start)
-                        .title(&self.level.to_string())
-                        .format()
-                } else {
-                    GitHub::notice(&self.message)
-                        .title(&self.level.to_string())
-                        .format()
-                }
+                params.title = Some(self.level.as_str());
+                "notice"
             }
         };
 
-        result.push_str(&annotation);
+        if self.fold_children && !self.children.is_empty() {
+            // The full diagnostic remains available in the log.
+            let title = format_args!("{}: {}", self.level, self.message);
+            write!(out, "{}{}{ENDGROUP}", Group(title), self.full_text())?;
+        }
+
+        write!(
+            out,
+            "{}",
+            Annotation {
+                command,
+                params,
+                // Fixes are suggested by the children, which are annotated
+                // separately, but are most visible on the root.
+                message: fmt::from_fn(|f| {
+                    write!(f, "{}{}", self.message, self.expansion_note())?;
+                    if is_root {
+                        write!(f, "{}", self.fix_note())?;
+                    }
+                    // Annotations are single-line
+                    if self.fold_children {
+                        write!(f, "{}", self.folded_children(" - "))?;
+                    }
+                    Ok(())
+                }),
+            }
+        )?;
+        if self.fold_children {
+            return Ok(());
+        }
 
         // Format child diagnostics (notes, help messages, etc.)
         for child in &self.children {
-            result.push_str(&<Diagnostic as CiMessage<GitHub>>::format(child));
+            <Diagnostic as CiMessage<GitHub>>::write_to(child, out)?;
         }
 
-        result
+        Ok(())
+    }
+}
+
+impl CiMessage<AzureDevOps> for Diagnostic {
+    fn format(&self) -> String {
+        fmt::from_fn(|f| <Self as CiMessage<AzureDevOps>>::write_to(self, f)).to_string()
+    }
+
+    fn write_to(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let kind = match self.level {
+            DiagnosticLevel::Error | DiagnosticLevel::InternalCompilerError => "error",
+            DiagnosticLevel::Warning if self.denied => "error",
+            DiagnosticLevel::Warning => "warning",
+            DiagnosticLevel::Note | DiagnosticLevel::Help | DiagnosticLevel::FailureNote => {
+                // Azure has no notices, so child diagnostics are logged as
+                // plain text following their parent.
+                writeln!(out, "{}: {}", self.level, self.message)?;
+                return self.children.iter().try_for_each(|child| {
+                    <Diagnostic as CiMessage<AzureDevOps>>::write_to(child, out)
+                });
+            }
+        };
+
+        if self.fold_children && !self.children.is_empty() {
+            // The full diagnostic remains available in the log.
+            let title = format_args!("{}: {}", self.level, self.message);
+            write!(
+                out,
+                "{}{}{AZURE_ENDGROUP}",
+                AzureGroup(title),
+                self.full_text()
+            )?;
+        }
+
+        let span = self.annotated_span();
+        let issue = Issue {
+            kind,
+            params: IssueParams {
+                source_path: span.map(|s| s.file_name.as_str()),
+                line_number: span.map(|s| s.line_start),
+                column_number: span.map(|s| s.column_start),
+                code: self.code.as_ref().map(|code| code.code.as_str()),
+            },
+            message: fmt::from_fn(|f| {
+                write!(
+                    f,
+                    "{}{}{}",
+                    self.message,
+                    self.expansion_note(),
+                    self.fix_note()
+                )?;
+                if self.fold_children {
+                    write!(f, "{}", self.folded_children("\n"))?;
+                }
+                Ok(())
+            }),
+        };
+        write!(out, "{issue}")?;
+        if self.fold_children {
+            return Ok(());
+        }
+
+        for child in &self.children {
+            <Diagnostic as CiMessage<AzureDevOps>>::write_to(child, out)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CiMessage<Buildkite> for Diagnostic {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for Diagnostic {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for Diagnostic {
+    fn format(&self) -> String {
+        let location = self
+            .annotated_span()
+            .map(|span| (span.file_name.as_str(), span.line_start));
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            location,
+        )
+    }
+}
+
+/// The diagnostic is shown as rendered by rustc if available, such that its
+/// source excerpts are indented below the highlighted message. Otherwise, a
+/// snippet of the source follows the message, if its text is known.
+impl CiMessage<Terminal> for Diagnostic {
+    fn format(&self) -> String {
+        if let Some(rendered) = &self.rendered {
+            return Terminal::message(self.severity(), rendered);
+        }
+        let plain = <Self as CiMessage<Plain>>::format(self);
+        let text = match self.snippet() {
+            Some(snippet) => insert_snippet(&plain, &snippet),
+            None => plain,
+        };
+        Terminal::message(self.severity(), text)
+    }
+}
+
+impl Classify for Diagnostic {
+    fn severity(&self) -> Severity {
+        match self.level {
+            DiagnosticLevel::Error | DiagnosticLevel::InternalCompilerError => Severity::Error,
+            DiagnosticLevel::Warning if self.denied => Severity::Error,
+            DiagnosticLevel::Warning => Severity::Warning,
+            DiagnosticLevel::Note | DiagnosticLevel::Help | DiagnosticLevel::FailureNote => {
+                Severity::Notice
+            }
+        }
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.code.as_ref().map(|code| code.code.as_str())
+    }
+
+    /// Error codes (e.g., `E0308`) identify errors rather than lints.
+    fn lint(&self) -> Option<&str> {
+        self.code().filter(|code| {
+            !code.strip_prefix('E').is_some_and(|number| {
+                number.len() == 4 && number.bytes().all(|digit| digit.is_ascii_digit())
+            })
+        })
+    }
+
+    fn file(&self) -> Option<&str> {
+        self.annotated_span().map(|span| span.file_name.as_str())
+    }
+
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        self.suggested_fixes()
+            .into_iter()
+            .map(|fix| Fix {
+                file: &fix.span.file_name,
+                start_line: fix.span.line_start,
+                start_column: fix.span.column_start,
+                end_line: fix.span.line_end,
+                end_column: fix.span.column_end,
+                replacement: fix.replacement,
+            })
+            .collect()
+    }
+
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+
+    fn snippet(&self) -> Option<String> {
+        let span = self.annotated_span().filter(|span| !span.text.is_empty())?;
+        Some(span.snippet().to_string())
     }
 }
 
@@ -171,19 +567,27 @@ pub enum DiagnosticLevel {
     InternalCompilerError,
 }
 
-impl std::fmt::Display for DiagnosticLevel {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl DiagnosticLevel {
+    /// The level as it appears in rustc's output.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
         match self {
-            Self::Error => write!(f, "error"),
-            Self::Warning => write!(f, "warning"),
-            Self::Note => write!(f, "note"),
-            Self::Help => write!(f, "help"),
-            Self::FailureNote => write!(f, "failure-note"),
-            Self::InternalCompilerError => write!(f, "error: internal compiler error"),
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+            Self::Help => "help",
+            Self::FailureNote => "failure-note",
+            Self::InternalCompilerError => "error: internal compiler error",
         }
     }
 }
 
+impl fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Source code span information.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DiagnosticSpan {
@@ -215,6 +619,188 @@ pub struct DiagnosticSpan {
     pub expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
 }
 
+impl DiagnosticSpan {
+    /// This span, followed by the spans of the macro invocations from which
+    /// it was expanded, innermost first.
+    fn expansion_chain(&self) -> impl Iterator<Item = &DiagnosticSpan> {
+        iter::successors(Some(self), |span| Some(&span.expansion.as_deref()?.span))
+    }
+
+    /// Whether this span lies within a file of the checkout, rather than in a
+    /// dependency, the standard library, or a placeholder such as
+    /// `<::core::macros::panic macros>`.
+    fn is_user_code(&self) -> bool {
+        !self.file_name.starts_with('<') && is_in_checkout(&self.file_name)
+    }
+
+    /// Display an excerpt of the source text of this span, with the
+    /// highlighted columns underlined and followed by the label, in the style
+    /// of rustc.
+    ///
+    /// At most [`MAX_SNIPPET_LINES`] lines are shown. Each line ends with a
+    /// newline.
+    fn snippet(&self) -> impl fmt::Display {
+        fmt::from_fn(|f| {
+            let last = self
+                .line_start
+                .saturating_add(u32::try_from(self.text.len()).unwrap_or(u32::MAX))
+                .saturating_sub(1);
+            let width = last.max(self.line_end).to_string().len();
+            writeln!(
+                f,
+                "{:width$}--> {}:{}:{}",
+                "",
+                NormalizedPath(&self.file_name),
+                self.line_start,
+                self.column_start
+            )?;
+            writeln!(f, "{:width$} |", "")?;
+            let shown = self.text.len().min(MAX_SNIPPET_LINES);
+            for (line, number) in self.text.iter().take(shown).zip(self.line_start..) {
+                writeln!(f, "{number:>width$} | {}", line.text)?;
+                let start = line.highlight_start.saturating_sub(1);
+                let length = line
+                    .highlight_end
+                    .saturating_sub(line.highlight_start)
+                    .max(1);
+                write!(f, "{:width$} | ", "")?;
+                for _ in 0..start {
+                    f.write_str(" ")?;
+                }
+                for _ in 0..length {
+                    f.write_str("^")?;
+                }
+                match &self.label {
+                    Some(label) if shown == self.text.len() && number == last => {
+                        writeln!(f, " {label}")?;
+                    }
+                    _ => writeln!(f)?,
+                }
+            }
+            if shown < self.text.len() {
+                writeln!(f, "{:width$} | ...", "")?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Read the source text of this span and of the macro invocations from
+    /// which it was expanded from disk, if not included in the diagnostic.
+    ///
+    /// Paths are relative to the workspace root, which is assumed to be the
+    /// current directory. Files which cannot be read are skipped.
+    fn load_text(&mut self) {
+        if let Some(expansion) = &mut self.expansion {
+            expansion.span.load_text();
+        }
+        if !self.text.is_empty() || self.file_name.starts_with('<') {
+            return;
+        }
+        let Ok(source) = fs::read_to_string(&self.file_name) else {
+            return;
+        };
+        let skip = usize::try_from(self.line_start.saturating_sub(1)).unwrap_or(usize::MAX);
+        let count = usize::try_from(self.line_end.saturating_sub(self.line_start))
+            .unwrap_or_default()
+            .saturating_add(1);
+        let (column_start, column_end) = (self.column_start, self.column_end);
+        self.text = source
+            .lines()
+            .skip(skip)
+            .take(count)
+            .enumerate()
+            .map(|(index, text)| DiagnosticSpanLine {
+                text: text.to_owned(),
+                highlight_start: if index == 0 { column_start } else { 1 },
+                highlight_end: if index.saturating_add(1) == count {
+                    column_end
+                } else {
+                    u32::try_from(text.chars().count())
+                        .unwrap_or(u32::MAX)
+                        .saturating_add(1)
+                },
+            })
+            .collect();
+    }
+
+    /// The fix suggested for this span, if it can be applied automatically.
+    fn fix(&self) -> Option<SuggestedFix<'_>> {
+        (self.suggestion_applicability == Some(SuggestionApplicability::MachineApplicable))
+            .then_some(())
+            .and(self.suggested_replacement.as_deref())
+            .map(|replacement| SuggestedFix {
+                span: self,
+                replacement,
+            })
+    }
+}
+
+impl SuggestedFix<'_> {
+    /// The lines affected by the fix, once it is applied.
+    ///
+    /// If the source text is not known, this is only the replacement.
+    fn replaced(&self) -> String {
+        let span = self.span;
+        let (Some(first), Some(last)) = (span.text.first(), span.text.last()) else {
+            return self.replacement.to_owned();
+        };
+        let prefix = first
+            .text
+            .get(..char_offset(&first.text, first.highlight_start));
+        let suffix = last.text.get(char_offset(&last.text, last.highlight_end)..);
+        format!(
+            "{}{}{}",
+            prefix.unwrap_or_default(),
+            self.replacement,
+            suffix.unwrap_or_default()
+        )
+    }
+
+    /// Display the fix as a diff of the lines it affects.
+    ///
+    /// Each line ends with a newline.
+    fn diff(&self) -> impl fmt::Display {
+        fmt::from_fn(|f| {
+            let span = self.span;
+            writeln!(
+                f,
+                "  --> {}:{}:{}",
+                NormalizedPath(&span.file_name),
+                span.line_start,
+                span.column_start
+            )?;
+            if span.text.is_empty() {
+                // Without the source text, only the replacement is known.
+                return self
+                    .replacement
+                    .lines()
+                    .try_for_each(|line| writeln!(f, "+{line}"));
+            }
+            for line in &span.text {
+                writeln!(f, "-{}", line.text)?;
+            }
+            self.replaced()
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .try_for_each(|line| writeln!(f, "+{line}"))
+        })
+    }
+}
+
+/// The maximum number of lines of source text shown in a snippet.
+const MAX_SNIPPET_LINES: usize = 5;
+
+/// The byte offset of a column within a line.
+///
+/// # Arguments
+///
+/// * `text` - The line.
+/// * `column` - The column (1-based, counted in characters).
+fn char_offset(text: &str, column: u32) -> usize {
+    let skip = usize::try_from(column.saturating_sub(1)).unwrap_or(usize::MAX);
+    text.char_indices().nth(skip).map_or(text.len(), |(i, _)| i)
+}
+
 /// A line of source text in a diagnostic span.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DiagnosticSpanLine {
@@ -258,7 +844,10 @@ pub struct DiagnosticSpanMacroExpansion {
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use super::{Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan};
+    use super::{
+        Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan, DiagnosticSpanMacroExpansion,
+        SuggestionApplicability,
+    };
     use serde_json::json;
 
     /// Test data for diagnostic messages.
@@ -328,6 +917,10 @@ pub(crate) mod tests {
                     }],
                     children: vec![],
                     rendered: None,
+                    fold_children: false,
+                    denied: false,
+                    documentation: None,
+                    undefined_behavior: None,
                 },
             ),
             (
@@ -384,9 +977,215 @@ pub(crate) mod tests {
                     }],
                     children: vec![],
                     rendered: None,
+                    fold_children: false,
+                    denied: false,
+                    documentation: None,
+                    undefined_behavior: None,
+                },
+            ),
+            (
+                "warning_with_fix".to_owned(),
+                json!({
+                    "$message_type": "diagnostic",
+                    "message": "unused variable: `x`",
+                    "code": {
+                        "code": "unused_variables",
+                        "explanation": null,
+                    },
+                    "level": "warning",
+                    "spans": [{
+                        "file_name": "src/main.rs",
+                        "byte_start": 50,
+                        "byte_end": 51,
+                        "line_start": 3,
+                        "line_end": 3,
+                        "column_start": 9,
+                        "column_end": 10,
+                        "is_primary": true,
+                        "text": [{
+                            "text": "    let x = 5;",
+                            "highlight_start": 9,
+                            "highlight_end": 10,
+                        }],
+                        "label": null,
+                        "suggested_replacement": null,
+                        "suggestion_applicability": null,
+                        "expansion": null,
+                    }],
+                    "children": [{
+                        "message": "if this is intentional, prefix it with an underscore",
+                        "code": null,
+                        "level": "help",
+                        "spans": [{
+                            "file_name": "src/main.rs",
+                            "byte_start": 50,
+                            "byte_end": 51,
+                            "line_start": 3,
+                            "line_end": 3,
+                            "column_start": 9,
+                            "column_end": 10,
+                            "is_primary": true,
+                            "text": [{
+                                "text": "    let x = 5;",
+                                "highlight_start": 9,
+                                "highlight_end": 10,
+                            }],
+                            "label": null,
+                            "suggested_replacement": "_x",
+                            "suggestion_applicability": "MachineApplicable",
+                            "expansion": null,
+                        }],
+                        "children": [],
+                        "rendered": null,
+                    }],
+                    "rendered": null,
+                }),
+                Diagnostic {
+                    message: "unused variable: `x`".to_owned(),
+                    code: Some(DiagnosticCode {
+                        code: "unused_variables".to_owned(),
+                        explanation: None,
+                    }),
+                    level: DiagnosticLevel::Warning,
+                    spans: vec![unused_x_span(None)],
+                    children: vec![Diagnostic {
+                        message: "if this is intentional, prefix it with an underscore".to_owned(),
+                        code: None,
+                        level: DiagnosticLevel::Help,
+                        spans: vec![unused_x_span(Some("_x"))],
+                        children: vec![],
+                        rendered: None,
+                        fold_children: false,
+                        denied: false,
+                        documentation: None,
+                        undefined_behavior: None,
+                    }],
+                    rendered: None,
+                    fold_children: false,
+                    denied: false,
+                    documentation: None,
+                    undefined_behavior: None,
+                },
+            ),
+            (
+                "error_in_macro".to_owned(),
+                json!({
+                    "$message_type": "diagnostic",
+                    "message": "`Foo` doesn't implement `Debug`",
+                    "code": {
+                        "code": "E0277",
+                        "explanation": null,
+                    },
+                    "level": "error",
+                    "spans": [{
+                        "file_name": "/rustc/1234/library/core/src/macros/mod.rs",
+                        "byte_start": 1500,
+                        "byte_end": 1520,
+                        "line_start": 46,
+                        "line_end": 46,
+                        "column_start": 21,
+                        "column_end": 41,
+                        "is_primary": true,
+                        "text": [],
+                        "label": null,
+                        "suggested_replacement": null,
+                        "suggestion_applicability": null,
+                        "expansion": {
+                            "span": {
+                                "file_name": "src/main.rs",
+                                "byte_start": 120,
+                                "byte_end": 142,
+                                "line_start": 8,
+                                "line_end": 8,
+                                "column_start": 5,
+                                "column_end": 27,
+                                "is_primary": false,
+                                "text": [],
+                                "label": null,
+                                "suggested_replacement": null,
+                                "suggestion_applicability": null,
+                                "expansion": null,
+                            },
+                            "macro_decl_name": "assert_eq!",
+                            "def_site_span": null,
+                        },
+                    }],
+                    "children": [],
+                    "rendered": null,
+                }),
+                Diagnostic {
+                    message: "`Foo` doesn't implement `Debug`".to_owned(),
+                    code: Some(DiagnosticCode {
+                        code: "E0277".to_owned(),
+                        explanation: None,
+                    }),
+                    level: DiagnosticLevel::Error,
+                    spans: vec![DiagnosticSpan {
+                        file_name: "/rustc/1234/library/core/src/macros/mod.rs".to_owned(),
+                        byte_start: 1500,
+                        byte_end: 1520,
+                        line_start: 46,
+                        line_end: 46,
+                        column_start: 21,
+                        column_end: 41,
+                        is_primary: true,
+                        text: vec![],
+                        label: None,
+                        suggested_replacement: None,
+                        suggestion_applicability: None,
+                        expansion: Some(Box::new(DiagnosticSpanMacroExpansion {
+                            span: DiagnosticSpan {
+                                file_name: "src/main.rs".to_owned(),
+                                byte_start: 120,
+                                byte_end: 142,
+                                line_start: 8,
+                                line_end: 8,
+                                column_start: 5,
+                                column_end: 27,
+                                is_primary: false,
+                                text: vec![],
+                                label: None,
+                                suggested_replacement: None,
+                                suggestion_applicability: None,
+                                expansion: None,
+                            },
+                            macro_decl_name: "assert_eq!".to_owned(),
+                            def_site_span: None,
+                        })),
+                    }],
+                    children: vec![],
+                    rendered: None,
+                    fold_children: false,
+                    denied: false,
+                    documentation: None,
+                    undefined_behavior: None,
                 },
             ),
         ]
         .into_iter()
     }
+
+    /// The span of the unused variable in the `warning_with_fix` case.
+    fn unused_x_span(replacement: Option<&str>) -> DiagnosticSpan {
+        DiagnosticSpan {
+            file_name: "src/main.rs".to_owned(),
+            byte_start: 50,
+            byte_end: 51,
+            line_start: 3,
+            line_end: 3,
+            column_start: 9,
+            column_end: 10,
+            is_primary: true,
+            text: vec![super::DiagnosticSpanLine {
+                text: "    let x = 5;".to_owned(),
+                highlight_start: 9,
+                highlight_end: 10,
+            }],
+            label: None,
+            suggested_replacement: replacement.map(str::to_owned),
+            suggestion_applicability: replacement
+                .map(|_| SuggestionApplicability::MachineApplicable),
+            expansion: None,
+        }
+    }
 }
@@ -6,8 +6,9 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    catalog::Title,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
     tool::cargo_check::compiler_message::rustc_message::diagnostic::Diagnostic,
 };
 
@@ -41,7 +42,7 @@ impl CiMessage<GitHub> for FutureIncompat {
         if !self.future_incompat_report.is_empty() {
             result.push_str(
                 &GitHub::warning("Future incompatibility warnings detected")
-                    .title("Future Incompatibility Report")
+                    .title(Title::FutureIncompatibilityReport.text())
                     .format(),
             );
 
@@ -54,6 +55,62 @@ impl CiMessage<GitHub> for FutureIncompat {
     }
 }
 
+impl CiMessage<AzureDevOps> for FutureIncompat {
+    fn format(&self) -> String {
+        let mut result = String::new();
+
+        if !self.future_incompat_report.is_empty() {
+            result.push_str(
+                &AzureDevOps::warning("Future incompatibility warnings detected").format(),
+            );
+
+            for entry in &self.future_incompat_report {
+                result.push_str(&CiMessage::<AzureDevOps>::format(&entry.diagnostic));
+            }
+        }
+
+        result
+    }
+}
+
+impl CiMessage<Buildkite> for FutureIncompat {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for FutureIncompat {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for FutureIncompat {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for FutureIncompat {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for FutureIncompat {
+    fn severity(&self) -> Severity {
+        if self.future_incompat_report.is_empty() {
+            Severity::Debug
+        } else {
+            Severity::Warning
+        }
+    }
+}
+
 /// A single entry in the future incompatibility report.
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct FutureIncompatEntry {
@@ -6,8 +6,8 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
 };
 
 /// Compilation section timing information (unstable).
@@ -39,6 +39,49 @@ impl CiMessage<GitHub> for SectionTiming {
     }
 }
 
+impl CiMessage<AzureDevOps> for SectionTiming {
+    fn format(&self) -> String {
+        AzureDevOps::debug(format!(
+            "Compilation section {} {}: {} ({}μs)",
+            self.name, self.event, self.name, self.time
+        ))
+    }
+}
+
+impl CiMessage<Buildkite> for SectionTiming {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for SectionTiming {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for SectionTiming {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for SectionTiming {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for SectionTiming {
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+}
+
 /// Timing event type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "lowercase")]
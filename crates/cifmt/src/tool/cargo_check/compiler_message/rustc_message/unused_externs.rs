@@ -5,8 +5,9 @@
 use serde::Deserialize;
 
 use crate::{
-    ci::{GitHub, Plain},
-    ci_message::CiMessage,
+    catalog::Title,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
 };
 
 /// Unused extern crate dependencies report.
@@ -43,15 +44,71 @@ impl CiMessage<GitHub> for UnusedExterns {
 
         match self.lint_level.as_str() {
             "deny" | "forbid" => GitHub::error(&message)
-                .title("Unused Dependencies")
+                .title(Title::UnusedDependencies.text())
                 .format(),
             _ => GitHub::warning(&message)
-                .title("Unused Dependencies")
+                .title(Title::UnusedDependencies.text())
                 .format(),
         }
     }
 }
 
+impl CiMessage<AzureDevOps> for UnusedExterns {
+    fn format(&self) -> String {
+        if self.unused_names.is_empty() {
+            return String::new();
+        }
+
+        let message = format!("Unused dependencies: {}", self.unused_names.join(", "));
+
+        match self.lint_level.as_str() {
+            "deny" | "forbid" => AzureDevOps::error(&message).format(),
+            _ => AzureDevOps::warning(&message).format(),
+        }
+    }
+}
+
+impl CiMessage<Buildkite> for UnusedExterns {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for UnusedExterns {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for UnusedExterns {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for UnusedExterns {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for UnusedExterns {
+    fn severity(&self) -> Severity {
+        if self.unused_names.is_empty() {
+            return Severity::Debug;
+        }
+
+        match self.lint_level.as_str() {
+            "deny" | "forbid" => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::UnusedExterns;
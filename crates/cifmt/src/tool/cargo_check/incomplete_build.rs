@@ -0,0 +1,40 @@
+//! Builds whose output ended without a result.
+
+use crate::{
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+};
+
+/// A build whose output ended without a `build-finished` message.
+///
+/// This is reported at the end of cargo's output if cargo never reported the
+/// result of the build, which typically means that cargo crashed or was
+/// killed, or that its output was cut short.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IncompleteBuild;
+
+impl<P: Platform> CiMessage<P> for IncompleteBuild {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            "Cargo's output ended without reporting the result of the build",
+            None,
+            Some(Title::IncompleteBuild.text()),
+        )
+    }
+}
+
+impl Classify for IncompleteBuild {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,138 @@
+//! Build timing messages from Cargo's JSON output.
+//!
+//! This module defines the `TimingInfo` type which represents the
+//! `"timing-info"` JSON message emitted by Cargo (when run with the unstable
+//! `--timings=json` flag) after each compilation unit finishes.
+use core::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::{Timed, Timing},
+    tool::cargo_check::common::Target,
+};
+
+/// Timing information for a single compilation unit.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TimingInfo {
+    /// The Package ID.
+    pub package_id: String,
+    /// The Cargo target that was built.
+    pub target: Target,
+    /// The compilation mode (e.g., `build`, `check`, `test`).
+    pub mode: String,
+    /// Total time taken to build the unit, in seconds.
+    pub duration: f64,
+    /// Time taken to generate the crate metadata, in seconds.
+    #[serde(default)]
+    pub rmeta_time: Option<f64>,
+}
+
+impl CiMessage<Plain> for TimingInfo {
+    fn format(&self) -> String {
+        format!(
+            "Timing: {} ({}) in {:.2}s",
+            self.target.name, self.mode, self.duration
+        )
+    }
+}
+
+impl CiMessage<GitHub> for TimingInfo {
+    fn format(&self) -> String {
+        GitHub::debug(format!(
+            "Timing: {} ({}) in {:.2}s",
+            self.target.name, self.mode, self.duration
+        ))
+    }
+}
+
+impl CiMessage<AzureDevOps> for TimingInfo {
+    fn format(&self) -> String {
+        AzureDevOps::debug(format!(
+            "Timing: {} ({}) in {:.2}s",
+            self.target.name, self.mode, self.duration
+        ))
+    }
+}
+
+impl CiMessage<Buildkite> for TimingInfo {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CodeBuild> for TimingInfo {
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for TimingInfo {
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for TimingInfo {
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for TimingInfo {
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+
+    fn sort_key(&self) -> Option<&str> {
+        self.target.src_path.to_str()
+    }
+}
+
+impl Timed for TimingInfo {
+    fn timing(&self) -> Option<Timing<'_>> {
+        Some(Timing::Elapsed {
+            track: &self.target.name,
+            name: &self.target.name,
+            category: &self.mode,
+            duration: Duration::try_from_secs_f64(self.duration).ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::TimingInfo;
+    use crate::tool::cargo_check::common;
+    use serde_json::json;
+
+    /// Test data for timing info messages.
+    pub fn cases() -> impl Iterator<Item = (String, serde_json::Value, TimingInfo)> {
+        common::tests::target_cases().map(|(target_desc, target_json, target)| {
+            (
+                format!("timing_info_{target_desc}"),
+                json!({
+                    "reason": "timing-info",
+                    "package_id": "mypackage 0.1.0 (path+file:///path/to/package)",
+                    "target": target_json,
+                    "mode": "check",
+                    "duration": 1.25_f64,
+                    "rmeta_time": 0.5_f64,
+                }),
+                TimingInfo {
+                    package_id: "mypackage 0.1.0 (path+file:///path/to/package)".to_owned(),
+                    target,
+                    mode: "check".to_owned(),
+                    duration: 1.25,
+                    rmeta_time: Some(0.5_f64),
+                },
+            )
+        })
+    }
+}
@@ -0,0 +1,290 @@
+//! Cargo doc JSON output format.
+//!
+//! Support for parsing the output of `cargo doc --message-format json`. The
+//! messages are those of `cargo check` (see [`CargoCheck`]), except that the
+//! diagnostics are the warnings of rustdoc about the documentation itself,
+//! such as a broken intra-doc link (`rustdoc::broken_intra_doc_links`) or an
+//! item without documentation (`missing_docs`), and the artifacts are the
+//! generated HTML pages.
+//!
+//! The diagnostics of documentation lints are annotated on the offending doc
+//! comment (or the undocumented item), titled after the lint (e.g., `Broken
+//! Doc Link`) rather than its level and code.
+
+use std::{io::BufRead as _, path::Path};
+
+use crate::{
+    capture::OutputPolicy,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify as _},
+    matcher::{Pattern, ProblemMatcher},
+    tool::{
+        Detect, DynTool, Tool, ToolOption, ToolOptions,
+        cargo_check::{CargoCheck, CargoMessage},
+        passthrough::Passthrough,
+    },
+};
+
+/// The title of the annotation of a documentation lint.
+///
+/// # Arguments
+///
+/// * `code` - The code of the lint (e.g., `rustdoc::broken_intra_doc_links`).
+///
+/// # Returns
+///
+/// The title, or `None` if the lint is not about the documentation.
+pub(crate) fn lint_title(code: &str) -> Option<Title> {
+    match code {
+        "rustdoc::broken_intra_doc_links" | "rustdoc::private_intra_doc_links" => {
+            Some(Title::BrokenDocLink)
+        }
+        "missing_docs" | "rustdoc::missing_crate_level_docs" => Some(Title::MissingDocs),
+        _ if code.starts_with("rustdoc::") => Some(Title::Documentation),
+        _ => None,
+    }
+}
+
+/// Tool implementation for parsing `cargo doc` JSON output.
+#[derive(Debug, Clone, Default)]
+pub struct CargoDoc {
+    /// The parser of cargo's JSON messages.
+    check: CargoCheck,
+}
+
+impl CargoDoc {
+    /// A problem matcher for the human-readable warnings of rustdoc, as
+    /// printed by `cargo doc` without `--message-format json`.
+    ///
+    /// The warnings have the same form as the diagnostics of rustc (see
+    /// [`CargoCheck::problem_matcher`]).
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cargo-doc",
+            vec![
+                Pattern::new(r"^(warning|error)(?:\[(\S+)\])?: (.+)$")
+                    .severity(1)
+                    .code(2)
+                    .message(3),
+                Pattern::new(r"^\s*--> (.+?):(\d+):(\d+)$")
+                    .file(1)
+                    .line(2)
+                    .column(3),
+            ],
+        )
+    }
+}
+
+/// Whether a line of cargo's output shows that the documentation is built:
+/// the status printed by cargo for each crate, the diagnostic of a rustdoc
+/// lint, or an HTML page generated by rustdoc.
+fn is_doc_line(line: &str) -> bool {
+    if line.trim_start().starts_with("Documenting ") {
+        return true;
+    }
+    match serde_json::from_str::<CargoMessage>(line) {
+        Ok(CargoMessage::CompilerMessage(msg)) => {
+            msg.code().is_some_and(|code| code.starts_with("rustdoc::"))
+        }
+        Ok(CargoMessage::CompilerArtifact(artifact)) => artifact.filenames.iter().any(|file| {
+            file.contains("/doc/")
+                && Path::new(file)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("html"))
+        }),
+        _ => false,
+    }
+}
+
+impl Detect for CargoDoc {
+    type Tool = Self;
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        CargoCheck::detect(sample)?;
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| is_doc_line(&line))
+            .then(Self::default)
+    }
+}
+
+impl Tool for CargoDoc {
+    type Message = CargoMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "cargo-doc"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        let mut results = self.check.parse(buf);
+        CargoCheck::document(&mut results);
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let mut results = self.check.finish();
+        CargoCheck::document(&mut results);
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.check.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.check.set_passthrough(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.check.set_output_policy(policy);
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        self.check.supported_options()
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        self.check.set_options(options);
+    }
+}
+
+impl<P: Platform> DynTool<P> for CargoDoc
+where
+    CargoMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{CargoDoc, lint_title};
+    use crate::{
+        catalog::Title,
+        ci::GitHub,
+        ci_message::CiMessage,
+        tool::{CargoCheck, Detect, Tool, ToolOptions},
+    };
+
+    const BROKEN_LINK: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w/project#0.1.0","manifest_path":"/w/project/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/project/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unresolved link to `Missing`\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"note","message":"`#[warn(rustdoc::broken_intra_doc_links)]` on by default","rendered":null,"spans":[]}],"level":"warning","message":"unresolved link to `Missing`","spans":[{"byte_end":70,"byte_start":63,"column_end":28,"column_start":21,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":"no item named `Missing` in scope","line_end":4,"line_start":4,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"rustdoc::broken_intra_doc_links","explanation":null}}}"#;
+
+    const MISSING_DOCS: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w/project#0.1.0","manifest_path":"/w/project/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/project/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: missing documentation for a function\n","$message_type":"diagnostic","children":[],"level":"warning","message":"missing documentation for a function","spans":[{"byte_end":152,"byte_start":119,"column_end":34,"column_start":1,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":9,"line_start":9,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"missing_docs","explanation":null}}}"#;
+
+    const DOC_ARTIFACT: &str = r#"{"reason":"compiler-artifact","package_id":"path+file:///w/project#0.1.0","manifest_path":"/w/project/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/project/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":["/w/project/target/doc/project/index.html"],"executable":null,"fresh":false}"#;
+
+    const BUILD_FINISHED: &str = r#"{"reason":"build-finished","success":true}"#;
+
+    #[test]
+    fn lint_titles() {
+        assert_eq!(
+            lint_title("rustdoc::broken_intra_doc_links"),
+            Some(Title::BrokenDocLink)
+        );
+        assert_eq!(
+            lint_title("rustdoc::private_intra_doc_links"),
+            Some(Title::BrokenDocLink)
+        );
+        assert_eq!(lint_title("missing_docs"), Some(Title::MissingDocs));
+        assert_eq!(
+            lint_title("rustdoc::invalid_html_tags"),
+            Some(Title::Documentation)
+        );
+        assert_eq!(lint_title("unused_variables"), None);
+    }
+
+    #[test]
+    fn detect() {
+        let doc = [MISSING_DOCS, DOC_ARTIFACT, BUILD_FINISHED].join("\n");
+        assert!(CargoDoc::detect(doc.as_bytes()).is_some());
+        let link = [BROKEN_LINK, BUILD_FINISHED].join("\n");
+        assert!(CargoDoc::detect(link.as_bytes()).is_some());
+        let status = [
+            " Documenting project v0.1.0 (/w/project)",
+            MISSING_DOCS,
+            BUILD_FINISHED,
+        ]
+        .join("\n");
+        assert!(CargoDoc::detect(status.as_bytes()).is_some());
+
+        let check = [MISSING_DOCS, BUILD_FINISHED].join("\n");
+        assert!(CargoDoc::detect(check.as_bytes()).is_none());
+        assert!(CargoCheck::detect(check.as_bytes()).is_some());
+        assert!(CargoDoc::detect(b"Diff in src/lib.rs:1:\n").is_none());
+    }
+
+    #[test]
+    fn format_github() {
+        let input = [BROKEN_LINK, MISSING_DOCS, DOC_ARTIFACT, BUILD_FINISHED].join("\n");
+        let mut tool = CargoDoc::default();
+        let mut messages = tool.parse(input.as_bytes());
+        messages.extend(tool.finish());
+        let output: Vec<_> = messages
+            .into_iter()
+            .map(|msg| CiMessage::<GitHub>::format(&msg.expect("failed to parse")))
+            .collect();
+
+        insta::assert_snapshot!(output.join("\n"), @"
+        ::warning file=src/lib.rs,line=4,col=21,endLine=4,endColumn=28,title=Broken Doc Link::unresolved link to `Missing`
+        ::notice title=note::`#[warn(rustdoc::broken_intra_doc_links)]` on by default
+
+        ::warning file=src/lib.rs,line=9,col=1,endLine=9,endColumn=34,title=Missing Docs::missing documentation for a function
+
+        ::debug::Built artifact: project (lib)
+        ::notice title=Build Complete::Build finished successfully
+        ");
+    }
+
+    #[test]
+    fn denied_lints() {
+        let mut tool = CargoDoc::default();
+        tool.set_options(
+            &ToolOptions::default()
+                .with_denied_lints(vec!["rustdoc::broken_intra_doc_links".to_owned()]),
+        );
+        let output: Vec<_> = tool
+            .parse(format!("{BROKEN_LINK}\n").as_bytes())
+            .into_iter()
+            .map(|msg| CiMessage::<GitHub>::format(&msg.expect("failed to parse")))
+            .collect();
+
+        insta::assert_snapshot!(output.join("\n"), @"
+        ::error file=src/lib.rs,line=4,col=21,endLine=4,endColumn=28,title=denied Broken Doc Link::unresolved link to `Missing`
+        ::notice title=note::`#[warn(rustdoc::broken_intra_doc_links)]` on by default
+        ");
+    }
+}
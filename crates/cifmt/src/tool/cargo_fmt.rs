@@ -0,0 +1,696 @@
+//! Cargo fmt check output format.
+//!
+//! Support for parsing the output of `cargo fmt --check`, which lists the
+//! changes rustfmt would make to each file which is not formatted. The
+//! human-readable output prints a diff for each change, headed by `Diff in
+//! <file> at line <N>:` (or `Diff in <file>:<N>:` for recent versions of
+//! rustfmt). With `cargo fmt --check -- --emit json`, each invocation of
+//! rustfmt instead prints a JSON array of the files and their mismatches on a
+//! single line.
+//!
+//! Each file is reported once, at the first line which is not formatted, with
+//! the diff of its changes in a group; the colour codes with which rustfmt
+//! highlights the diff are removed. A summary of the number of files which
+//! need formatting follows at the end of the output.
+
+use core::mem;
+use std::io::BufRead as _;
+
+use serde::Deserialize;
+
+use crate::{
+    ansi,
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the output of `cargo fmt --check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FmtMessage {
+    /// A file which is not formatted.
+    Unformatted(UnformattedFile),
+
+    /// The number of files which are not formatted, reported at the end of
+    /// the output.
+    Summary(FmtSummary),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// An incomplete JSON report at the end of the output.
+    TruncatedLine(TruncatedLine),
+
+    /// Human-readable output which is not part of a diff.
+    TextOutput(TextOutput),
+}
+
+/// A file which is not formatted, with the changes rustfmt would make to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnformattedFile {
+    /// The path of the file, as printed by rustfmt.
+    pub file: String,
+    /// The first line of the file which is not formatted (1-based).
+    pub line: u32,
+    /// The number of changes rustfmt would make.
+    pub changes: usize,
+    /// The lines of the diff, with each change headed by `@@ line <N> @@`.
+    pub diff: Vec<String>,
+}
+
+impl UnformattedFile {
+    /// Start a file at its first change.
+    fn new(file: &str, line: u32) -> Self {
+        Self {
+            file: file.to_owned(),
+            line,
+            changes: 0,
+            diff: Vec::new(),
+        }
+    }
+
+    /// Start a change at the given line.
+    fn change(&mut self, line: u32) {
+        self.changes = self.changes.saturating_add(1);
+        self.line = self.line.min(line);
+        self.diff.push(format!("@@ line {line} @@"));
+    }
+}
+
+impl<P: Platform> CiMessage<P> for UnformattedFile {
+    #[inline]
+    fn format(&self) -> String {
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            format!(
+                "File is not formatted ({} change(s)); run `cargo fmt` to format it",
+                self.changes
+            ),
+            Some((&self.file, self.line, None)),
+            Some(Title::UnformattedFile.text()),
+        )];
+        if !self.diff.is_empty() {
+            lines.push(P::group(&self.file));
+            lines.extend(self.diff.iter().cloned());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for UnformattedFile {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some("File is not formatted")
+    }
+}
+
+/// The number of files which are not formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FmtSummary {
+    /// The number of files which are not formatted.
+    pub files: usize,
+}
+
+impl<P: Platform> CiMessage<P> for FmtSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let message = if self.files == 0 {
+            "All files are formatted".to_owned()
+        } else {
+            format!("{} file(s) need formatting", self.files)
+        };
+        P::annotate(
+            self.severity(),
+            message,
+            None,
+            Some(Title::FormatCheck.text()),
+        )
+    }
+}
+
+impl Classify for FmtSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl<P: Platform> CiMessage<P> for FmtMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Unformatted(msg) => <UnformattedFile as CiMessage<P>>::format(msg),
+            Self::Summary(msg) => <FmtSummary as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for FmtMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Unformatted(msg) => msg.severity(),
+            Self::Summary(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Unformatted(msg) => msg.kind(),
+            Self::Summary(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::Unformatted(_)
+            | Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Unformatted(msg) => Some(&msg.file),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Unformatted(msg) => msg.file(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Unformatted(msg) => msg.message(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for FmtMessage {}
+
+impl Captured for FmtMessage {}
+
+/// A file listed in rustfmt's JSON output (`--emit json`).
+#[derive(Debug, Deserialize)]
+struct JsonFile {
+    /// The path of the file.
+    name: String,
+    /// The changes rustfmt would make to the file.
+    mismatches: Vec<JsonMismatch>,
+}
+
+/// A change listed in rustfmt's JSON output.
+#[derive(Debug, Deserialize)]
+struct JsonMismatch {
+    /// The first line of the original text (1-based).
+    original_begin_line: u32,
+    /// The original text.
+    original: String,
+    /// The formatted text.
+    expected: String,
+}
+
+impl JsonFile {
+    /// The file as reported, or `None` if it has no mismatches.
+    fn into_unformatted(self) -> Option<UnformattedFile> {
+        let Self { name, mismatches } = self;
+        let first = mismatches.first()?.original_begin_line;
+        let mut file = UnformattedFile::new(&name, first);
+        for mismatch in mismatches {
+            file.change(mismatch.original_begin_line);
+            file.diff
+                .extend(mismatch.original.lines().map(|line| format!("-{line}")));
+            file.diff
+                .extend(mismatch.expected.lines().map(|line| format!("+{line}")));
+        }
+        Some(file)
+    }
+}
+
+/// Parse the header of a change in rustfmt's human-readable output.
+///
+/// # Returns
+///
+/// The file and line of the change, or `None` if the line is not a header.
+fn parse_header(line: &str) -> Option<(&str, u32)> {
+    let rest = line.strip_prefix("Diff in ")?.strip_suffix(':')?;
+    let (file, number) = rest
+        .rsplit_once(" at line ")
+        .or_else(|| rest.rsplit_once(':'))?;
+    Some((file, number.parse().ok()?))
+}
+
+/// Whether a line of rustfmt's human-readable output continues a diff.
+fn is_diff_line(line: &[u8]) -> bool {
+    matches!(line.first(), Some(b' ' | b'+' | b'-'))
+}
+
+/// The files reported so far, used to aggregate the changes of each file and
+/// count the files which are not formatted.
+#[derive(Debug, Clone, Default)]
+struct Files {
+    /// The file whose changes are being read from the human-readable output.
+    current: Option<UnformattedFile>,
+    /// The number of files reported so far.
+    count: usize,
+}
+
+impl Files {
+    /// Report a file which is not formatted.
+    fn report(&mut self, file: UnformattedFile) -> FmtMessage {
+        self.count = self.count.saturating_add(1);
+        FmtMessage::Unformatted(file)
+    }
+
+    /// Report the file being read, if any.
+    fn flush(&mut self) -> Option<Result<FmtMessage, serde_json::Error>> {
+        let file = self.current.take()?;
+        Some(Ok(self.report(file)))
+    }
+
+    /// Parse a single line of the output of `cargo fmt --check`.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<FmtMessage, serde_json::Error>> {
+        let raw = match next {
+            Line::Complete(raw) => raw,
+            Line::Overlong(raw) => {
+                let mut results = vec![Ok(FmtMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(Line::Complete(raw), max_length, text));
+                return results;
+            }
+        };
+
+        // rustfmt colours its diffs even when its output is piped
+        let stripped;
+        let line = if raw.contains(&b'\x1b') {
+            stripped = ansi::strip(raw);
+            stripped.as_slice()
+        } else {
+            raw
+        };
+
+        // Skip empty lines, which rustfmt prints between diffs
+        if line.is_empty() {
+            return Vec::new();
+        }
+
+        if let Ok(header) = str::from_utf8(line)
+            && let Some((file, number)) = parse_header(header)
+        {
+            let mut results = Vec::new();
+            if self
+                .current
+                .as_ref()
+                .is_none_or(|current| current.file != file)
+            {
+                results.extend(self.flush());
+                self.current = Some(UnformattedFile::new(file, number));
+            }
+            if let Some(current) = &mut self.current {
+                current.change(number);
+            }
+            return results;
+        }
+
+        if let Some(current) = &mut self.current
+            && is_diff_line(line)
+        {
+            current
+                .diff
+                .push(String::from_utf8_lossy(line).into_owned());
+            return Vec::new();
+        }
+
+        if line.first() == Some(&b'[') {
+            return match serde_json::from_slice::<Vec<JsonFile>>(line) {
+                Ok(files) => {
+                    let mut results: Vec<_> = self.flush().into_iter().collect();
+                    for file in files.into_iter().filter_map(JsonFile::into_unformatted) {
+                        results.push(Ok(self.report(file)));
+                    }
+                    results
+                }
+                Err(e) => vec![Err(e)],
+            };
+        }
+
+        text.handle(line)
+            .map(|output| Ok(FmtMessage::TextOutput(output)))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Tool implementation for parsing the output of `cargo fmt --check`.
+#[derive(Debug, Clone, Default)]
+pub struct CargoFmt {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a diff.
+    text: TextLines,
+    /// The files reported so far.
+    files: Files,
+}
+
+impl Detect for CargoFmt {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| {
+                parse_header(&line).is_some()
+                    || (line.starts_with('[')
+                        && serde_json::from_str::<Vec<JsonFile>>(&line).is_ok())
+            })
+            .then(Self::default)
+    }
+}
+
+impl CargoFmt {
+    /// A problem matcher for the headers of the diffs printed by `cargo fmt
+    /// --check`.
+    ///
+    /// The header (e.g., `Diff in src/lib.rs at line 4:`) gives the file and
+    /// line of a change. Each match is reported as an error.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cargo-fmt",
+            vec![
+                Pattern::new(r"^(Diff in (.+?)(?: at line |:)(\d+):)$")
+                    .message(1)
+                    .file(2)
+                    .line(3),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for CargoFmt {
+    type Message = FmtMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "cargo-fmt"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.files.parse_line(line, max_length, &mut self.text));
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            for result in self.files.parse_line(line, max_length, &mut self.text) {
+                results.push(result.or_else(|error| {
+                    TruncatedLine::from_final_line(position, line, error)
+                        .map(FmtMessage::TruncatedLine)
+                }));
+            }
+        }
+
+        results.extend(self.files.flush());
+        results.push(Ok(FmtMessage::Summary(FmtSummary {
+            files: mem::take(&mut self.files.count),
+        })));
+        if let Some(output) = self.text.finish() {
+            results.push(Ok(FmtMessage::TextOutput(output)));
+        }
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for CargoFmt
+where
+    FmtMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{CargoFmt, FmtMessage, parse_header};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{Detect as _, Tool as _};
+
+    const TEXT: &str = "\
+Diff in /w/src/lib.rs at line 3:
+ fn add(a: u32, b: u32) -> u32 {
+-    a+b
++    a + b
+ }
+
+Diff in /w/src/lib.rs at line 10:
+-fn  sub() {}
++fn sub() {}
+
+Diff in /w/src/main.rs:1:
+-fn main(){}
++fn main() {}
+
+";
+
+    const JSON: &str = r#"[{"name":"/w/src/lib.rs","mismatches":[{"original_begin_line":3,"original_end_line":3,"expected_begin_line":3,"expected_end_line":3,"original":"    a+b\n","expected":"    a + b\n"}]}]"#;
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        FmtMessage: CiMessage<P>,
+    {
+        let mut tool = CargoFmt::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <FmtMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn headers() {
+        assert_eq!(
+            parse_header("Diff in /w/src/lib.rs at line 3:"),
+            Some(("/w/src/lib.rs", 3))
+        );
+        assert_eq!(
+            parse_header(r"Diff in C:\w\src\lib.rs:12:"),
+            Some((r"C:\w\src\lib.rs", 12))
+        );
+        assert_eq!(parse_header("Diff in /w/src/lib.rs:"), None);
+        assert_eq!(parse_header("error: unexpected token"), None);
+    }
+
+    #[test]
+    fn text() {
+        insta::assert_snapshot!(format::<GitHub>(TEXT), @"
+        ::error file=/w/src/lib.rs,line=3,title=Unformatted File::File is not formatted (2 change(s)); run `cargo fmt` to format it
+        ::group::/w/src/lib.rs
+        @@ line 3 @@
+         fn add(a: u32, b: u32) -> u32 {
+        -    a+b
+        +    a + b
+         }
+        @@ line 10 @@
+        -fn  sub() {}
+        +fn sub() {}
+        ::endgroup::
+        ::error file=/w/src/main.rs,line=1,title=Unformatted File::File is not formatted (1 change(s)); run `cargo fmt` to format it
+        ::group::/w/src/main.rs
+        @@ line 1 @@
+        -fn main(){}
+        +fn main() {}
+        ::endgroup::
+        ::notice title=Format Check::2 file(s) need formatting
+        ");
+    }
+
+    #[test]
+    fn json() {
+        insta::assert_snapshot!(format::<Plain>(JSON), @"
+        /w/src/lib.rs:3: error:   Unformatted File: File is not formatted (1 change(s)); run `cargo fmt` to format it
+        /w/src/lib.rs
+        @@ line 3 @@
+        -    a+b
+        +    a + b
+        notice:  Format Check: 1 file(s) need formatting
+        ");
+    }
+
+    #[test]
+    fn coloured() {
+        let output = "Diff in src/lib.rs:2:\n\x1b[31m-    a+b\n\x1b(B\x1b[m\x1b[32m+    a + b\n\x1b(B\x1b[m }\n";
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        src/lib.rs:2: error:   Unformatted File: File is not formatted (1 change(s)); run `cargo fmt` to format it
+        src/lib.rs
+        @@ line 2 @@
+        -    a+b
+        +    a + b
+         }
+        notice:  Format Check: 1 file(s) need formatting
+        ");
+    }
+
+    #[test]
+    fn formatted() {
+        insta::assert_snapshot!(format::<GitHub>(""), @"::notice title=Format Check::All files are formatted");
+    }
+
+    #[test]
+    fn truncated_json() {
+        let output = JSON
+            .get(..JSON.len().saturating_sub(10))
+            .expect("in bounds");
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 173 bytes of an incomplete message: EOF while parsing a string at line 1 column 173
+        notice:  Format Check: All files are formatted
+        ");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(CargoFmt::detect(TEXT.as_bytes()).is_some());
+        assert!(CargoFmt::detect(JSON.as_bytes()).is_some());
+        assert!(CargoFmt::detect(b"Compiling foo v0.1.0\n").is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = CargoFmt::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+        for (line, file, number) in [
+            ("Diff in /w/src/lib.rs at line 3:", "/w/src/lib.rs", "3"),
+            ("Diff in src/main.rs:12:", "src/main.rs", "12"),
+        ] {
+            let captures = regex.captures(line).expect("line not matched");
+            assert_eq!(
+                [2, 3].map(|i| captures.get(i).map(|m| m.as_str())),
+                [Some(file), Some(number)]
+            );
+        }
+    }
+}
@@ -6,21 +6,38 @@
 //! <https://github.com/rust-lang/rust/blob/master/library/test/src/formatters/json.rs>.
 
 mod bench_message;
+mod doctest;
+mod incomplete_suite;
 mod report_message;
+mod slow_suite;
 mod suite_message;
 mod test_message;
 
-use std::io::BufRead;
+use core::{mem, time::Duration};
+use std::{collections::HashMap, io::BufRead};
 
 use crate::{
-    ci::{GitHub, Plain, Platform},
-    ci_message::CiMessage,
+    capture::{Captured, OutputPolicy, TestGrouping},
+    ci::{Phase, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    junit::TestResult,
+    matcher::{Pattern, ProblemMatcher},
+    suites::SuiteResult,
+    timeline::{Timed, Timing},
     tool::{
-        Detect, DynTool, Tool,
+        Detect, DynTool, Tool, ToolOption, ToolOptions,
         cargo_libtest::{
-            bench_message::BenchMessage, report_message::ReportMessage,
-            suite_message::SuiteMessage, test_message::TestMessage,
+            bench_message::BenchMessage,
+            incomplete_suite::IncompleteSuite,
+            report_message::ReportMessage,
+            slow_suite::{BudgetTracker, SlowSuite},
+            suite_message::SuiteMessage,
+            test_message::{TestLocation, TestMessage},
         },
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
     },
 };
 use serde::Deserialize;
@@ -44,28 +61,222 @@ pub enum LibTestMessage {
 
     /// Doctest timing report.
     Report(ReportMessage),
+
+    /// A line which exceeded the maximum line length.
+    #[serde(skip_deserializing)]
+    OverlongLine(OverlongLine),
+
+    /// An incomplete message at the end of the output.
+    #[serde(skip_deserializing)]
+    TruncatedLine(TruncatedLine),
+
+    /// Human-readable output interleaved with the JSON messages.
+    #[serde(skip_deserializing)]
+    TextOutput(TextOutput),
+
+    /// A test suite which did not finish before the end of the output.
+    #[serde(skip_deserializing)]
+    IncompleteSuite(IncompleteSuite),
+
+    /// A test suite which passed, but exceeded its time budget.
+    #[serde(skip_deserializing)]
+    SlowSuite(SlowSuite),
 }
 
-impl CiMessage<Plain> for LibTestMessage {
+impl<P: Platform> CiMessage<P> for LibTestMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
     #[inline]
     fn format(&self) -> String {
         match self {
-            Self::Test(test_msg) => <TestMessage as CiMessage<Plain>>::format(test_msg),
-            Self::Suite(suite_msg) => <SuiteMessage as CiMessage<Plain>>::format(suite_msg),
-            Self::Bench(bench_msg) => <BenchMessage as CiMessage<Plain>>::format(bench_msg),
-            Self::Report(report_msg) => <ReportMessage as CiMessage<Plain>>::format(report_msg),
+            Self::Test(test_msg) => <TestMessage as CiMessage<P>>::format(test_msg),
+            Self::Suite(suite_msg) => <SuiteMessage as CiMessage<P>>::format(suite_msg),
+            Self::Bench(bench_msg) => <BenchMessage as CiMessage<P>>::format(bench_msg),
+            Self::Report(report_msg) => <ReportMessage as CiMessage<P>>::format(report_msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+            Self::IncompleteSuite(msg) => <IncompleteSuite as CiMessage<P>>::format(msg),
+            Self::SlowSuite(msg) => <SlowSuite as CiMessage<P>>::format(msg),
         }
     }
 }
 
-impl CiMessage<GitHub> for LibTestMessage {
+impl Classify for LibTestMessage {
     #[inline]
-    fn format(&self) -> String {
+    fn severity(&self) -> Severity {
         match self {
-            Self::Test(test_msg) => <TestMessage as CiMessage<GitHub>>::format(test_msg),
-            Self::Suite(suite_msg) => <SuiteMessage as CiMessage<GitHub>>::format(suite_msg),
-            Self::Bench(bench_msg) => <BenchMessage as CiMessage<GitHub>>::format(bench_msg),
-            Self::Report(report_msg) => <ReportMessage as CiMessage<GitHub>>::format(report_msg),
+            Self::Test(test_msg) => test_msg.severity(),
+            Self::Suite(suite_msg) => suite_msg.severity(),
+            Self::Bench(bench_msg) => bench_msg.severity(),
+            Self::Report(report_msg) => report_msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+            Self::IncompleteSuite(msg) => msg.severity(),
+            Self::SlowSuite(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Test(test_msg) => test_msg.kind(),
+            Self::Suite(suite_msg) => suite_msg.kind(),
+            Self::Bench(bench_msg) => bench_msg.kind(),
+            Self::Report(report_msg) => report_msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+            Self::IncompleteSuite(msg) => msg.kind(),
+            Self::SlowSuite(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        match self {
+            Self::Test(test_msg) => test_msg.is_test_failure(),
+            Self::Suite(_)
+            | Self::Bench(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_)
+            | Self::IncompleteSuite(_)
+            | Self::SlowSuite(_) => false,
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::IncompleteSuite(msg) => msg.is_incomplete(),
+            Self::Test(_)
+            | Self::Suite(_)
+            | Self::Bench(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TextOutput(_)
+            | Self::SlowSuite(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Test(test_msg) => test_msg.sort_key(),
+            Self::Bench(bench_msg) => bench_msg.sort_key(),
+            Self::Suite(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteSuite(_)
+            | Self::TextOutput(_)
+            | Self::SlowSuite(_) => None,
+        }
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        match self {
+            Self::Test(test_msg) => test_msg.test_result(),
+            Self::Suite(_)
+            | Self::Bench(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteSuite(_)
+            | Self::TextOutput(_)
+            | Self::SlowSuite(_) => None,
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            Self::Suite(suite_msg) => suite_msg.suite_result(),
+            Self::SlowSuite(msg) => msg.suite_result(),
+            Self::Test(_)
+            | Self::Bench(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::IncompleteSuite(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for LibTestMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::Test(
+                TestMessage::Ok {
+                    name,
+                    exec_time: Some(exec_time),
+                    ..
+                }
+                | TestMessage::Failed {
+                    name,
+                    exec_time: Some(exec_time),
+                    ..
+                },
+            ) => Some(Timing::Elapsed {
+                track: name,
+                name,
+                category: "test",
+                duration: Duration::try_from_secs_f64(*exec_time).ok()?,
+            }),
+            Self::Test(_)
+            | Self::Suite(_)
+            | Self::Bench(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_)
+            | Self::IncompleteSuite(_)
+            | Self::SlowSuite(_) => None,
+        }
+    }
+}
+
+impl Captured for LibTestMessage {
+    #[inline]
+    fn captured_output_mut(&mut self) -> Option<(&str, &mut String)> {
+        match self {
+            Self::Test(
+                TestMessage::Ok {
+                    name,
+                    stdout: Some(stdout),
+                    ..
+                }
+                | TestMessage::Failed {
+                    name,
+                    stdout: Some(stdout),
+                    ..
+                },
+            ) => Some((name, stdout)),
+            Self::Test(_)
+            | Self::Suite(_)
+            | Self::Bench(_)
+            | Self::Report(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_)
+            | Self::IncompleteSuite(_)
+            | Self::SlowSuite(_) => None,
+        }
+    }
+
+    #[inline]
+    fn set_test_grouping(&mut self, grouping: TestGrouping) {
+        if let Self::Test(test_msg) = self {
+            test_msg.set_grouping(grouping);
         }
     }
 }
@@ -73,8 +284,119 @@ impl CiMessage<GitHub> for LibTestMessage {
 /// Tool implementation for parsing cargo test (libtest) JSON output.
 #[derive(Debug, Clone, Default)]
 pub struct CargoLibtest {
-    /// Buffer for incomplete JSON lines.
-    buffer: Vec<u8>,
+    /// Framer for splitting the output into JSON lines.
+    framer: LineFramer,
+    /// Handling of lines which are not JSON.
+    text: TextLines,
+    /// Progress of the test suite currently running.
+    suite: SuiteProgress,
+    /// Policy for the inclusion of the tests' output.
+    output: OutputPolicy,
+    /// Locations of the tests discovered so far, by name.
+    ///
+    /// Names are not unique across test binaries, but each binary reports
+    /// the discovery of its tests before running them.
+    locations: HashMap<String, TestLocation>,
+    /// Durations of the suites run so far, against their budgets.
+    budget: BudgetTracker,
+}
+
+/// Progress of a running test suite, used to detect output which ends before
+/// the suite has finished.
+#[derive(Debug, Clone, Default)]
+struct SuiteProgress {
+    /// Whether a suite has started and not yet reported its result.
+    running: bool,
+    /// Names of the tests which have started and not yet finished.
+    pending: Vec<String>,
+}
+
+impl SuiteProgress {
+    /// Update the progress with a parsed message.
+    fn track(&mut self, message: &LibTestMessage) {
+        match message {
+            LibTestMessage::Suite(SuiteMessage::Started { .. }) => {
+                self.running = true;
+                self.pending.clear();
+            }
+            LibTestMessage::Suite(SuiteMessage::Ok { .. } | SuiteMessage::Failed { .. }) => {
+                self.running = false;
+                self.pending.clear();
+            }
+            LibTestMessage::Test(TestMessage::Started { name, .. }) => {
+                self.pending.push(name.clone());
+            }
+            LibTestMessage::Test(
+                TestMessage::Ok { name, .. }
+                | TestMessage::Failed { name, .. }
+                | TestMessage::Ignored { name, .. },
+            ) => {
+                if let Some(pos) = self.pending.iter().position(|pending| pending == name) {
+                    self.pending.remove(pos);
+                }
+            }
+            // A timeout only warns that a test is slow; the test still reports
+            // its result later.
+            LibTestMessage::Suite(_)
+            | LibTestMessage::Test(_)
+            | LibTestMessage::Bench(_)
+            | LibTestMessage::Report(_)
+            | LibTestMessage::OverlongLine(_)
+            | LibTestMessage::TruncatedLine(_)
+            | LibTestMessage::TextOutput(_)
+            | LibTestMessage::IncompleteSuite(_)
+            | LibTestMessage::SlowSuite(_) => {}
+        }
+    }
+
+    /// Report the running suite, if any, as incomplete.
+    ///
+    /// Tests which started without an enclosing suite (e.g., in a partial
+    /// capture of the output) are reported likewise, as the group of the last
+    /// is still open.
+    fn finish(&mut self) -> Option<IncompleteSuite> {
+        let Self { running, pending } = mem::take(self);
+        (running || !pending.is_empty()).then_some(IncompleteSuite { pending })
+    }
+}
+
+/// Parse a single line of libtest's output.
+///
+/// # Returns
+///
+/// The parsed messages, which are empty if the line should be skipped.
+fn parse_line(
+    next: Line<'_>,
+    max_length: usize,
+    text: &mut TextLines,
+) -> Vec<Result<LibTestMessage, serde_json::Error>> {
+    let line = match next {
+        Line::Complete(line) => line,
+        Line::Overlong(line) => {
+            let mut results = vec![Ok(LibTestMessage::OverlongLine(OverlongLine {
+                max_length,
+            }))];
+            results.extend(parse_line(Line::Complete(line), max_length, text));
+            return results;
+        }
+    };
+
+    // Skip empty lines
+    if line.is_empty() {
+        return Vec::new();
+    }
+
+    // Try to parse as JSON
+    let result = match serde_json::from_slice::<LibTestMessage>(line) {
+        Ok(msg) => Some(Ok(msg)),
+        // Only report error if it looks like JSON (starts with '{'), otherwise
+        // pass non-JSON lines (like rust output) through
+        Err(e) if line.first() == Some(&b'{') => Some(Err(e)),
+        Err(_) => text
+            .handle(line)
+            .map(|output| Ok(LibTestMessage::TextOutput(output))),
+    };
+    result.into_iter().collect()
 }
 
 impl Detect for CargoLibtest {
@@ -95,6 +417,51 @@ impl Detect for CargoLibtest {
     }
 }
 
+impl CargoLibtest {
+    /// A problem matcher for the panics of failed tests, as printed by
+    /// `cargo test` without `--format json`.
+    ///
+    /// The panic's header (e.g., `thread 'tests::add' panicked at
+    /// src/lib.rs:10:5:`) gives its location, and the following line its
+    /// message. Each match is reported as an error.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cargo-libtest",
+            vec![
+                Pattern::new(r"^thread '.+' panicked at (.+?):(\d+):(\d+):$")
+                    .file(1)
+                    .line(2)
+                    .column(3),
+                Pattern::new("^(.+)$").message(1),
+            ],
+        )
+        .with_severity("error")
+    }
+
+    /// Track the progress of the test suite through the parsed messages,
+    /// locate failed tests, apply the output policy to them, and report
+    /// suites exceeding their time budget.
+    fn track(&mut self, results: &mut [Result<LibTestMessage, serde_json::Error>]) {
+        for msg in results.iter_mut().flatten() {
+            self.suite.track(msg);
+            if let LibTestMessage::Suite(suite_msg) = msg
+                && let Some(slow) = self.budget.track(suite_msg)
+            {
+                *msg = LibTestMessage::SlowSuite(slow);
+            }
+            if let LibTestMessage::Test(test_msg) = msg {
+                if let Some((name, location)) = test_msg.discovered_location() {
+                    self.locations.insert(name.to_owned(), location);
+                }
+                test_msg.resolve_location(|name| self.locations.get(name));
+                test_msg.apply_output_policy(self.output);
+            }
+        }
+    }
+}
+
 impl Tool for CargoLibtest {
     type Message = LibTestMessage;
     type Error = serde_json::Error;
@@ -105,40 +472,81 @@ impl Tool for CargoLibtest {
     }
 
     #[inline]
-    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
-        let mut results = Vec::new();
+    fn phase(&self) -> Phase {
+        Phase::Test
+    }
 
-        // Append new data to buffer
-        self.buffer.extend_from_slice(buf);
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
 
         // Process complete lines
-        while let Some(newline_pos) = self.buffer.iter().position(|&b| b == b'\n') {
-            let mut line_bytes = self.buffer.drain(..=newline_pos).collect::<Vec<u8>>();
-            if line_bytes.last() == Some(&b'\n') {
-                line_bytes.pop();
-            }
-            let line = line_bytes.as_slice();
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(parse_line(line, max_length, &mut self.text));
+        }
 
-            // Skip empty lines
-            if line.is_empty() {
-                continue;
-            }
+        self.track(&mut results);
+        results
+    }
 
-            // Try to parse as JSON
-            match serde_json::from_slice::<LibTestMessage>(line) {
-                Ok(msg) => results.push(Ok(msg)),
-                Err(e) => {
-                    // Only report error if it looks like JSON (starts with '{')
-                    if line.first() == Some(&b'{') {
-                        results.push(Err(e));
-                    }
-                    // Otherwise skip non-JSON lines (like rust output)
-                }
-            }
-        }
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results: Vec<_> = self
+            .framer
+            .finish()
+            .into_iter()
+            .flat_map(|line| {
+                parse_line(line, max_length, &mut self.text)
+                    .into_iter()
+                    .map(move |result| {
+                        result.or_else(|error| {
+                            TruncatedLine::from_final_line(position, line, error)
+                                .map(LibTestMessage::TruncatedLine)
+                        })
+                    })
+            })
+            .collect();
 
+        self.track(&mut results);
+        if let Some(incomplete) = self.suite.finish() {
+            results.push(Ok(LibTestMessage::IncompleteSuite(incomplete)));
+        }
+        // The collected lines follow the incomplete suite, which closes the
+        // group of its unfinished test.
+        if let Some(output) = self.text.finish() {
+            results.push(Ok(LibTestMessage::TextOutput(output)));
+        }
         results
     }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.output = policy;
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        &[ToolOption::TimeBudget]
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        self.budget = BudgetTracker::new(options.time_budget);
+    }
 }
 
 impl<P: Platform> DynTool<P> for CargoLibtest
@@ -158,16 +566,32 @@ where
             .map(|msg| msg.format())
             .collect()
     }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
     use pretty_assertions::assert_eq;
+    use rstest::rstest;
 
     use crate::ci_message::CiMessage;
     use crate::{
-        ci::{GitHub, Plain},
-        tool::cargo_libtest::LibTestMessage,
+        capture::{OutputPolicy, TestGrouping},
+        ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+        tool::{
+            CargoLibtest, OverlongLine, Passthrough, TextOutput, Tool, TruncatedLine,
+            cargo_libtest::{
+                LibTestMessage, incomplete_suite::IncompleteSuite, test_message::TestMessage,
+            },
+        },
     };
 
     macro_rules! set_snapshot_suffix {
@@ -221,4 +645,363 @@ pub(crate) mod tests {
             insta::assert_snapshot!(formatted);
         }
     }
+
+    #[test]
+    fn format_azure() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <LibTestMessage as CiMessage<AzureDevOps>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_buildkite() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <LibTestMessage as CiMessage<Buildkite>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_codebuild() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <LibTestMessage as CiMessage<CodeBuild>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_cloudbuild() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            let formatted = <LibTestMessage as CiMessage<CloudBuild>>::format(&message);
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn format_terminal() {
+        for (desc, _, message) in cases() {
+            set_snapshot_suffix!("{desc}");
+            // Escape sequences are shown as `\e`, to keep the snapshots legible.
+            let formatted =
+                <LibTestMessage as CiMessage<Terminal>>::format(&message).replace('\x1b', "\\e");
+            insta::assert_snapshot!(formatted);
+        }
+    }
+
+    #[test]
+    fn parse_overlong_line() {
+        let mut tool = CargoLibtest::default();
+        Tool::set_max_line_length(&mut tool, 64);
+
+        let long_name = "a".repeat(64);
+        let input = format!(
+            "{{\"type\":\"test\",\"event\":\"started\",\"name\":\"{long_name}\"}}\n\
+             {{\"type\":\"test\",\"event\":\"started\",\"name\":\"b\"}}\n"
+        );
+        let [overlong, truncated, started] = tool
+            .parse(input.as_bytes())
+            .try_into()
+            .expect("three messages");
+
+        // The first bytes of the line are parsed after the warning.
+        assert_eq!(
+            overlong.expect("valid message"),
+            LibTestMessage::OverlongLine(OverlongLine { max_length: 64 })
+        );
+        assert!(truncated.is_err_and(|error| error.is_eof()));
+        assert_eq!(
+            started.expect("valid message"),
+            LibTestMessage::Test(TestMessage::Started {
+                name: "b".to_owned(),
+                grouping: TestGrouping::All,
+            })
+        );
+    }
+
+    #[test]
+    fn passthrough_collect() {
+        let mut tool = CargoLibtest::default();
+        Tool::set_passthrough(&mut tool, Passthrough::Collect);
+
+        let input = concat!(
+            "    Blocking waiting for file lock on build directory\n",
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            "   Running unittests src/lib.rs\n",
+        );
+        let mut messages = tool
+            .parse(input.as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        messages.extend(tool.finish().into_iter().map(Result::unwrap));
+
+        assert_eq!(
+            messages,
+            [
+                LibTestMessage::Test(TestMessage::Started {
+                    name: "a".to_owned(),
+                    grouping: TestGrouping::All,
+                }),
+                LibTestMessage::IncompleteSuite(IncompleteSuite {
+                    pending: vec!["a".to_owned()]
+                }),
+                LibTestMessage::TextOutput(TextOutput::Collected(vec![
+                    "    Blocking waiting for file lock on build directory".to_owned(),
+                    "   Running unittests src/lib.rs".to_owned(),
+                ])),
+            ]
+        );
+    }
+
+    #[test]
+    fn passthrough_raw() {
+        let mut tool = CargoLibtest::default();
+        Tool::set_passthrough(&mut tool, Passthrough::Raw);
+
+        let messages = tool
+            .parse(b"note: run with `RUST_BACKTRACE=1`\n\n{\"type\":\"test\"\n")
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+
+        // Empty lines are skipped, and malformed JSON is not passed through.
+        assert_eq!(
+            messages,
+            [LibTestMessage::TextOutput(TextOutput::Raw(
+                "note: run with `RUST_BACKTRACE=1`".to_owned()
+            ))]
+        );
+    }
+
+    #[rstest]
+    #[case::always(OutputPolicy::Always)]
+    #[case::on_failure(OutputPolicy::OnFailure)]
+    #[case::never(OutputPolicy::Never)]
+    #[case::truncated(OutputPolicy::Truncated)]
+    fn output_policy(#[case] policy: OutputPolicy) {
+        let mut tool = CargoLibtest::default();
+        Tool::set_output_policy(&mut tool, policy);
+
+        let noisy = format!("{}\n", "noise ".repeat(500));
+        let input = [
+            serde_json::json!({"type": "test", "event": "ok", "name": "passes", "stdout": "passing output\n"}),
+            serde_json::json!({"type": "test", "event": "failed", "name": "fails", "stdout": "failing output\n"}),
+            serde_json::json!({"type": "test", "event": "failed", "name": "noisy", "stdout": noisy}),
+            serde_json::json!({"type": "test", "event": "ignored", "name": "skipped", "message": "too slow"}),
+        ]
+        .map(|message| message.to_string() + "\n")
+        .concat();
+        let messages = tool
+            .parse(input.as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+
+        let format = |format: fn(&LibTestMessage) -> String| {
+            messages.iter().map(format).collect::<Vec<_>>().join("\n")
+        };
+        set_snapshot_suffix!("{policy:?}_plain");
+        insta::assert_snapshot!(format(<LibTestMessage as CiMessage<Plain>>::format));
+        set_snapshot_suffix!("{policy:?}_github");
+        insta::assert_snapshot!(format(<LibTestMessage as CiMessage<GitHub>>::format));
+    }
+
+    #[test]
+    fn discovered_location() {
+        let mut tool = CargoLibtest::default();
+
+        let input = [
+            serde_json::json!({"type": "test", "event": "discovered", "name": "tests::fails", "ignore": false, "source_path": "src/lib.rs", "start_line": 12_u32, "start_col": 8_u32, "end_line": 12_u32, "end_col": 13_u32}),
+            serde_json::json!({"type": "test", "event": "timeout", "name": "tests::fails"}),
+            serde_json::json!({"type": "test", "event": "failed", "name": "tests::fails", "message": "oops"}),
+            serde_json::json!({"type": "test", "event": "failed", "name": "tests::unknown"}),
+        ]
+        .map(|message| message.to_string() + "\n")
+        .concat();
+        let formatted = tool
+            .parse(input.as_bytes())
+            .into_iter()
+            .map(|message| {
+                <LibTestMessage as CiMessage<GitHub>>::format(&message.expect("Failed to parse"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        insta::assert_snapshot!(formatted, @"
+        ::debug::Discovered test: tests::fails (ignored: false, message: None, location: src/lib.rs:12:8-12:13)
+        ::endgroup::
+        ::error file=src/lib.rs,line=12,title=Test Timeout::tests::fails
+        ::endgroup::
+        ::error file=src/lib.rs,line=12,title=Test Failed%3A tests%3A%3Afails::oops
+        ::endgroup::
+        ::error title=Test Failed%3A tests%3A%3Aunknown::
+        ");
+    }
+
+    #[test]
+    fn finish_unterminated_line() {
+        let mut tool = CargoLibtest::default();
+
+        assert!(
+            tool.parse(br#"{"type":"test","event":"started","name":"a"}"#)
+                .is_empty()
+        );
+
+        let messages = tool
+            .finish()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        assert_eq!(
+            messages,
+            [
+                LibTestMessage::Test(TestMessage::Started {
+                    name: "a".to_owned(),
+                    grouping: TestGrouping::All,
+                }),
+                LibTestMessage::IncompleteSuite(IncompleteSuite {
+                    pending: vec!["a".to_owned()]
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn finish_truncated_line() {
+        let mut tool = CargoLibtest::default();
+
+        let input = concat!(
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","na"#,
+        );
+        assert_eq!(tool.parse(input.as_bytes()).len(), 1);
+
+        let messages = tool
+            .finish()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        assert_eq!(
+            messages,
+            [
+                LibTestMessage::TruncatedLine(TruncatedLine {
+                    line_number: 2,
+                    offset: 45,
+                    length: 31,
+                    error: "EOF while parsing a string at line 1 column 31".to_owned(),
+                }),
+                LibTestMessage::IncompleteSuite(IncompleteSuite {
+                    pending: vec!["a".to_owned()]
+                }),
+            ]
+        );
+
+        // A complete but invalid final line is still reported as an error.
+        assert!(tool.parse(br#"{"type":"test","event":"ok"} x"#).is_empty());
+        let results = tool.finish();
+        assert_eq!(results.len(), 1);
+        assert!(results.first().is_some_and(Result::is_err));
+    }
+
+    #[test]
+    fn finish_incomplete_suite() {
+        let mut tool = CargoLibtest::default();
+
+        let input = concat!(
+            r#"{"type":"suite","event":"started","test_count":3}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"a"}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"b"}"#,
+            "\n",
+            r#"{"type":"test","event":"started","name":"c"}"#,
+            "\n",
+            r#"{"type":"test","event":"ok","name":"b"}"#,
+            "\n",
+        );
+        assert_eq!(tool.parse(input.as_bytes()).len(), 5);
+
+        let messages = tool
+            .finish()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        assert_eq!(
+            messages,
+            [LibTestMessage::IncompleteSuite(IncompleteSuite {
+                pending: vec!["a".to_owned(), "c".to_owned()]
+            })]
+        );
+
+        // A suite which reported its result is complete.
+        let complete = concat!(
+            r#"{"type":"suite","event":"started","test_count":0}"#,
+            "\n",
+            r#"{"type":"suite","event":"ok","passed":0,"failed":0,"ignored":0,"measured":0,"filtered_out":0}"#,
+            "\n",
+        );
+        assert_eq!(tool.parse(complete.as_bytes()).len(), 2);
+        assert!(tool.finish().is_empty());
+    }
+
+    #[test]
+    fn finish_test_without_suite() {
+        let mut tool = CargoLibtest::default();
+
+        let input = concat!(r#"{"type":"test","event":"started","name":"a"}"#, "\n");
+        let started = tool
+            .parse(input.as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        let finished = tool
+            .finish()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to parse");
+        let formatted = started
+            .iter()
+            .chain(&finished)
+            .map(<LibTestMessage as CiMessage<GitHub>>::format)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // The group of the unfinished test is closed.
+        insta::assert_snapshot!(formatted, @"
+        ::group::Test: a
+        ::endgroup::
+        ::warning title=Incomplete Test Suite::Test suite ended without a result; 1 test(s) did not finish: a
+        ");
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = CargoLibtest::problem_matcher();
+        assert_eq!(matcher.severity.as_deref(), Some("error"));
+        let regexes: Vec<_> = matcher
+            .pattern
+            .iter()
+            .map(|pattern| regex::Regex::new(&pattern.regexp).expect("invalid regexp"))
+            .collect();
+        let [header, message] = regexes.as_slice() else {
+            panic!("expected two patterns");
+        };
+
+        let captures = header
+            .captures("thread 'tests::add' panicked at src/lib.rs:10:5:")
+            .expect("header not matched");
+        assert_eq!(
+            [1, 2, 3].map(|i| captures.get(i).map(|m| m.as_str())),
+            [Some("src/lib.rs"), Some("10"), Some("5")]
+        );
+        assert!(message.is_match("assertion `left == right` failed"));
+        assert!(!header.is_match("test tests::add ... FAILED"));
+    }
 }
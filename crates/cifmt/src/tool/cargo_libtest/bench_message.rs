@@ -1,7 +1,8 @@
 //! Benchmark result messages from cargo test.
 
-use crate::ci::{GitHub, Plain};
-use crate::ci_message::CiMessage;
+use crate::catalog::Title;
+use crate::ci::Platform;
+use crate::ci_message::{CiMessage, Classify, Severity};
 use serde::Deserialize;
 
 /// Benchmark result message.
@@ -18,31 +19,31 @@ pub struct BenchMessage {
     pub mib_per_second: Option<u64>,
 }
 
-impl CiMessage<Plain> for BenchMessage {
+impl<P: Platform> CiMessage<P> for BenchMessage {
     fn format(&self) -> String {
         let throughput = self
             .mib_per_second
             .map(|mb| format!(" ({mb} MiB/s)"))
             .unwrap_or_default();
-        format!(
-            "BENCH: {}: {} ns/iter (± {}){}",
-            self.name, self.median, self.deviation, throughput
+        P::annotate(
+            self.severity(),
+            format!(
+                "{}: {} ns/iter (± {}){}",
+                self.name, self.median, self.deviation, throughput
+            ),
+            None,
+            Some(Title::BenchmarkResult.text()),
         )
     }
 }
 
-impl CiMessage<GitHub> for BenchMessage {
-    fn format(&self) -> String {
-        let throughput = self
-            .mib_per_second
-            .map(|mb| format!(" ({mb} MiB/s)"))
-            .unwrap_or_default();
-        GitHub::notice(&format!(
-            "{}: {} ns/iter (± {}){}",
-            self.name, self.median, self.deviation, throughput
-        ))
-        .title("Benchmark Result")
-        .format()
+impl Classify for BenchMessage {
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.name)
     }
 }
 
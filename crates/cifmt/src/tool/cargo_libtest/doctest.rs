@@ -0,0 +1,54 @@
+//! Locations of doctests.
+//!
+//! Rustdoc names each doctest after the file and item containing it, and the
+//! line at which the code block starts, for example:
+//!
+//! ```text
+//! src/lib.rs - module::func (line 42)
+//! src/lib.rs - (line 1)
+//! src/lib.rs - module::Struct::method (line 7) - compile fail
+//! ```
+//!
+//! Unlike regular tests, whose location is only known from a `discovered`
+//! event, the location of a doctest can therefore be recovered from its name.
+
+/// Extract the file and line of a doctest from its name.
+///
+/// # Arguments
+///
+/// * `name` - The name of the test.
+///
+/// # Returns
+///
+/// The path of the file (as reported by rustdoc) and the line at which the
+/// doctest starts, or `None` if the name is not that of a doctest.
+pub(crate) fn location(name: &str) -> Option<(&str, u32)> {
+    let (file, rest) = name.split_once(" - ")?;
+    let (_, suffix) = rest.rsplit_once("(line ")?;
+    let (digits, _) = suffix.split_once(')')?;
+    let line = digits.parse().ok()?;
+    (!file.is_empty()).then_some((file, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::location;
+
+    #[rstest]
+    #[case::item("src/lib.rs - module::func (line 42)", Some(("src/lib.rs", 42)))]
+    #[case::crate_level("src/lib.rs - (line 1)", Some(("src/lib.rs", 1)))]
+    #[case::compile_fail(
+        "src/lib.rs - module::Struct::method (line 7) - compile fail",
+        Some(("src/lib.rs", 7))
+    )]
+    #[case::windows(r"src\lib.rs - func (line 3)", Some((r"src\lib.rs", 3)))]
+    #[case::unit_test("tests::it_works", None)]
+    #[case::no_line("src/lib.rs - func", None)]
+    #[case::bad_line("src/lib.rs - func (line x)", None)]
+    fn parse(#[case] name: &str, #[case] expected: Option<(&str, u32)>) {
+        assert_eq!(location(name), expected);
+    }
+}
@@ -0,0 +1,113 @@
+//! Test suites whose output ended prematurely.
+
+use core::fmt;
+
+use crate::catalog::Title;
+use crate::ci::Platform;
+use crate::ci_message::{CiMessage, Classify, Severity};
+
+/// A test suite whose output ended before the suite finished.
+///
+/// This is reported at the end of the tool's output if a suite was started
+/// but never reported its result, which typically means that the test binary
+/// crashed or was killed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IncompleteSuite {
+    /// Names of the tests which were started but never finished, in the
+    /// order in which they were started.
+    pub pending: Vec<String>,
+}
+
+impl IncompleteSuite {
+    /// Display the description of the incomplete suite.
+    fn description(&self) -> impl fmt::Display {
+        fmt::from_fn(|f| {
+            f.write_str("Test suite ended without a result")?;
+            if !self.pending.is_empty() {
+                write!(
+                    f,
+                    "; {} test(s) did not finish: {}",
+                    self.pending.len(),
+                    self.pending.join(", ")
+                )?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<P: Platform> CiMessage<P> for IncompleteSuite {
+    #[inline]
+    fn format(&self) -> String {
+        let mut lines = Vec::with_capacity(2);
+        // The group of the last unfinished test is still open.
+        if !self.pending.is_empty() {
+            lines.push(P::endgroup());
+        }
+        lines.push(P::annotate(
+            self.severity(),
+            self.description().to_string(),
+            None,
+            Some(Title::IncompleteTestSuite.text()),
+        ));
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for IncompleteSuite {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncompleteSuite;
+    use crate::ci::{AzureDevOps, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[test]
+    fn format() {
+        let message = IncompleteSuite {
+            pending: vec!["tests::a".to_owned(), "tests::b".to_owned()],
+        };
+        insta::assert_snapshot!(
+            <IncompleteSuite as CiMessage<Plain>>::format(&message),
+            @"warning: Incomplete Test Suite: Test suite ended without a result; 2 test(s) did not finish: tests::a, tests::b"
+        );
+        insta::assert_snapshot!(
+            <IncompleteSuite as CiMessage<GitHub>>::format(&message),
+            @"
+        ::endgroup::
+        ::warning title=Incomplete Test Suite::Test suite ended without a result; 2 test(s) did not finish: tests::a, tests::b
+        "
+        );
+        insta::assert_snapshot!(
+            <IncompleteSuite as CiMessage<AzureDevOps>>::format(&message),
+            @"
+        ##[endgroup]
+        ##vso[task.logissue type=warning]Incomplete Test Suite: Test suite ended without a result; 2 test(s) did not finish: tests::a, tests::b
+        "
+        );
+    }
+
+    #[test]
+    fn format_no_pending() {
+        let message = IncompleteSuite { pending: vec![] };
+        insta::assert_snapshot!(
+            <IncompleteSuite as CiMessage<GitHub>>::format(&message),
+            @"::warning title=Incomplete Test Suite::Test suite ended without a result"
+        );
+    }
+}
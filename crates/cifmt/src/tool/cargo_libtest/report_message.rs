@@ -1,7 +1,8 @@
 //! Doctest timing report messages from cargo test.
 
-use crate::ci::{GitHub, Plain};
-use crate::ci_message::CiMessage;
+use crate::catalog::Title;
+use crate::ci::Platform;
+use crate::ci_message::{CiMessage, Classify, Severity};
 use serde::Deserialize;
 
 /// Doctest timing report.
@@ -13,23 +14,23 @@ pub struct ReportMessage {
     pub compilation_time: f64,
 }
 
-impl CiMessage<Plain> for ReportMessage {
+impl<P: Platform> CiMessage<P> for ReportMessage {
     fn format(&self) -> String {
-        format!(
-            "REPORT: Total: {:.2}s, Compilation: {:.2}s",
-            self.total_time, self.compilation_time
+        P::annotate(
+            self.severity(),
+            format!(
+                "Total: {:.2}s, Compilation: {:.2}s",
+                self.total_time, self.compilation_time
+            ),
+            None,
+            Some(Title::DoctestReport.text()),
         )
     }
 }
 
-impl CiMessage<GitHub> for ReportMessage {
-    fn format(&self) -> String {
-        GitHub::notice(&format!(
-            "Total: {:.2}s, Compilation: {:.2}s",
-            self.total_time, self.compilation_time
-        ))
-        .title("Doctest Report")
-        .format()
+impl Classify for ReportMessage {
+    fn severity(&self) -> Severity {
+        Severity::Notice
     }
 }
 
@@ -0,0 +1,243 @@
+//! Test suites which exceeded their time budget.
+
+use core::{fmt, time::Duration};
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    suites::SuiteResult,
+    timeline::{TimeBudget, Timed},
+    tool::cargo_libtest::suite_message::SuiteMessage,
+};
+
+/// A test suite which passed, but took longer than its budget.
+///
+/// This replaces the notice of the suite's completion, so that a slow
+/// increase in the duration of the tests is noticed before it becomes a
+/// problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SlowSuite {
+    /// Number of tests passed.
+    pub passed: usize,
+    /// Number of tests ignored.
+    pub ignored: usize,
+    /// Number of benchmarks measured.
+    pub measured: usize,
+    /// Number of tests filtered out.
+    pub filtered_out: usize,
+    /// The duration of the suite.
+    pub exec_time: Duration,
+    /// The budget for a single suite, if exceeded by this suite.
+    pub suite_budget: Option<Duration>,
+    /// The total duration of the suites run so far, and the budget it
+    /// exceeded, if exceeded with this suite.
+    pub total: Option<(Duration, Duration)>,
+}
+
+impl<P: Platform> CiMessage<P> for SlowSuite {
+    #[inline]
+    fn format(&self) -> String {
+        let message = fmt::from_fn(|f| {
+            write!(
+                f,
+                "{} passed, {} ignored, {} measured, {} filtered out in {:.2}s",
+                self.passed,
+                self.ignored,
+                self.measured,
+                self.filtered_out,
+                self.exec_time.as_secs_f64()
+            )?;
+            if let Some(budget) = self.suite_budget {
+                write!(
+                    f,
+                    ", exceeding the budget of {:.2}s per suite",
+                    budget.as_secs_f64()
+                )?;
+            }
+            if let Some((total, budget)) = self.total {
+                write!(
+                    f,
+                    ", bringing the test run to {:.2}s and exceeding its budget of {:.2}s",
+                    total.as_secs_f64(),
+                    budget.as_secs_f64()
+                )?;
+            }
+            Ok(())
+        });
+        P::annotate(
+            self.severity(),
+            message.to_string(),
+            None,
+            Some(Title::SlowTestSuite.text()),
+        )
+    }
+}
+
+impl Classify for SlowSuite {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        Some(SuiteResult {
+            duration: Some(self.exec_time),
+            ..SuiteResult::new(self.passed, 0, self.ignored)
+        })
+    }
+}
+
+impl Timed for SlowSuite {}
+
+impl Captured for SlowSuite {}
+
+/// Tracks the duration of the suites run against their budgets.
+#[derive(Debug, Clone, Default)]
+pub(super) struct BudgetTracker {
+    /// The budgets for the duration of the suites.
+    budget: TimeBudget,
+    /// The total duration of the suites run so far.
+    total: Duration,
+    /// Whether a suite was reported for exceeding the total budget.
+    reported: bool,
+}
+
+impl BudgetTracker {
+    /// Track the duration of the suites against the given budgets.
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - The budgets for the duration of the suites.
+    pub(super) fn new(budget: TimeBudget) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    /// Record the completion of a suite.
+    ///
+    /// Only passing suites are reported, as failing suites are already
+    /// reported as errors. If the total budget is first exceeded by a failing
+    /// suite, the next passing suite reports it.
+    ///
+    /// # Returns
+    ///
+    /// The suite, if it passed and exceeded a budget.
+    pub(super) fn track(&mut self, message: &SuiteMessage) -> Option<SlowSuite> {
+        let (SuiteMessage::Ok {
+            exec_time: Some(secs),
+            ..
+        }
+        | SuiteMessage::Failed {
+            exec_time: Some(secs),
+            ..
+        }) = message
+        else {
+            return None;
+        };
+        let exec_time = Duration::try_from_secs_f64(*secs).ok()?;
+        self.total = self.total.saturating_add(exec_time);
+
+        let &SuiteMessage::Ok {
+            passed,
+            ignored,
+            measured,
+            filtered_out,
+            ..
+        } = message
+        else {
+            return None;
+        };
+        let suite_budget = self.budget.suite.filter(|&budget| exec_time > budget);
+        let total = self
+            .budget
+            .total
+            .filter(|&budget| !self.reported && self.total > budget)
+            .map(|budget| (self.total, budget));
+        self.reported |= total.is_some();
+        (suite_budget.is_some() || total.is_some()).then_some(SlowSuite {
+            passed,
+            ignored,
+            measured,
+            filtered_out,
+            exec_time,
+            suite_budget,
+            total,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{BudgetTracker, SlowSuite};
+    use crate::ci::{GitHub, Plain};
+    use crate::ci_message::CiMessage;
+    use crate::timeline::TimeBudget;
+    use crate::tool::cargo_libtest::suite_message::SuiteMessage;
+
+    fn ok(exec_time: f64) -> SuiteMessage {
+        SuiteMessage::Ok {
+            passed: 3,
+            failed: 0,
+            ignored: 1,
+            measured: 0,
+            filtered_out: 0,
+            exec_time: Some(exec_time),
+        }
+    }
+
+    #[test]
+    fn track() {
+        let mut tracker = BudgetTracker::new(TimeBudget::new(
+            Some(Duration::from_secs(10)),
+            Some(Duration::from_secs(20)),
+        ));
+        assert_eq!(tracker.track(&ok(5.0)), None);
+        let suite = tracker.track(&ok(12.0)).expect("expected a slow suite");
+        assert_eq!(suite.suite_budget, Some(Duration::from_secs(10)));
+        assert_eq!(suite.total, None);
+
+        // The total budget is exceeded by a failing suite, and reported by
+        // the next passing suite only.
+        let failed = SuiteMessage::Failed {
+            passed: 0,
+            failed: 1,
+            ignored: 0,
+            measured: 0,
+            filtered_out: 0,
+            exec_time: Some(4.0_f64),
+        };
+        assert_eq!(tracker.track(&failed), None);
+        let run = tracker.track(&ok(1.0)).expect("expected a slow suite");
+        assert_eq!(run.suite_budget, None);
+        assert_eq!(
+            run.total,
+            Some((Duration::from_secs(22), Duration::from_secs(20)))
+        );
+        assert_eq!(tracker.track(&ok(1.0)), None);
+    }
+
+    #[test]
+    fn format() {
+        let message = SlowSuite {
+            passed: 3,
+            ignored: 1,
+            measured: 0,
+            filtered_out: 0,
+            exec_time: Duration::from_millis(12_500),
+            suite_budget: Some(Duration::from_secs(10)),
+            total: Some((Duration::from_secs(125), Duration::from_mins(2))),
+        };
+        insta::assert_snapshot!(<SlowSuite as CiMessage<Plain>>::format(&message), @"warning: Slow Test Suite: 3 passed, 1 ignored, 0 measured, 0 filtered out in 12.50s, exceeding the budget of 10.00s per suite, bringing the test run to 125.00s and exceeding its budget of 120.00s");
+        insta::assert_snapshot!(<SlowSuite as CiMessage<GitHub>>::format(&message), @"::warning title=Slow Test Suite::3 passed, 1 ignored, 0 measured, 0 filtered out in 12.50s, exceeding the budget of 10.00s per suite, bringing the test run to 125.00s and exceeding its budget of 120.00s");
+    }
+}
@@ -1,7 +1,11 @@
 //! Test suite-level events from cargo test.
 
-use crate::ci::{GitHub, Plain};
-use crate::ci_message::CiMessage;
+use core::{fmt, time::Duration};
+
+use crate::catalog::Title;
+use crate::ci::Platform;
+use crate::ci_message::{CiMessage, Classify, Severity};
+use crate::suites::SuiteResult;
 use serde::Deserialize;
 
 /// Suite-level events.
@@ -68,23 +72,48 @@ pub enum SuiteMessage {
     },
 }
 
-impl CiMessage<Plain> for SuiteMessage {
+impl<P: Platform> CiMessage<P> for SuiteMessage {
     fn format(&self) -> String {
+        /// Display the execution time, if any.
+        fn time_info(exec_time: Option<f64>) -> impl fmt::Display {
+            fmt::from_fn(move |f| match exec_time {
+                Some(t) => write!(f, " in {t:.2}s"),
+                None => Ok(()),
+            })
+        }
+
         match self {
-            &Self::Discovery => "SUITE: Test Discovery Started".to_owned(),
+            &Self::Discovery => P::group(Title::TestDiscovery.text()),
 
             Self::Completed {
                 tests,
                 benchmarks,
                 total,
                 ignored,
-            } => format!(
-                "SUITE: Test Discovery Completed - Discovered {total} items: {tests} tests, {benchmarks} benchmarks, {ignored} ignored"
-            ),
+            } => [
+                P::endgroup(),
+                P::annotate(
+                    self.severity(),
+                    format!(
+                        "Discovered {total} items: {tests} tests, {benchmarks} benchmarks, {ignored} ignored"
+                    ),
+                    None,
+                    Some(Title::TestDiscovery.text()),
+                ),
+            ]
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
 
-            &Self::Started { test_count, .. } => {
-                format!("SUITE: Test Suite Started - Running {test_count} tests")
-            }
+            // We don't start a group here because the individual tests will
+            // create their own groups.
+            &Self::Started { test_count, .. } => P::annotate(
+                self.severity(),
+                format!("Running {test_count} tests"),
+                None,
+                Some(Title::TestSuiteStarted.text()),
+            ),
 
             Self::Failed {
                 passed,
@@ -93,14 +122,15 @@ impl CiMessage<Plain> for SuiteMessage {
                 measured,
                 filtered_out,
                 exec_time,
-            } => {
-                let time_info = exec_time
-                    .map(|t| format!(" in {t:.2}s"))
-                    .unwrap_or_default();
+            } => P::annotate(
+                self.severity(),
                 format!(
-                    "SUITE: Test Suite Failed - {failed} failed, {passed} passed, {ignored} ignored, {measured} measured, {filtered_out} filtered out{time_info}"
-                )
-            }
+                    "{failed} failed, {passed} passed, {ignored} ignored, {measured} measured, {filtered_out} filtered out{}",
+                    time_info(*exec_time)
+                ),
+                None,
+                Some(Title::TestSuiteFailed.text()),
+            ),
 
             Self::Ok {
                 passed,
@@ -109,84 +139,59 @@ impl CiMessage<Plain> for SuiteMessage {
                 measured,
                 filtered_out,
                 exec_time,
-            } => {
-                let time_info = exec_time
-                    .map(|t| format!(" in {t:.2}s"))
-                    .unwrap_or_default();
+            } => P::annotate(
+                self.severity(),
                 format!(
-                    "SUITE: Test Suite Passed - {passed} passed, {failed} failed, {ignored} ignored, {measured} measured, {filtered_out} filtered out{time_info}"
-                )
-            }
+                    "{passed} passed, {failed} failed, {ignored} ignored, {measured} measured, {filtered_out} filtered out{}",
+                    time_info(*exec_time)
+                ),
+                None,
+                Some(Title::TestSuitePassed.text()),
+            ),
         }
     }
 }
 
-impl CiMessage<GitHub> for SuiteMessage {
-    fn format(&self) -> String {
+impl Classify for SuiteMessage {
+    fn severity(&self) -> Severity {
         match self {
-            &Self::Discovery => GitHub::group("Test Discovery"),
-
-            Self::Completed {
-                tests,
-                benchmarks,
-                total,
-                ignored,
-            } => {
-                let mut parts = Vec::new();
-
-                parts.push(GitHub::endgroup());
-                parts.push(GitHub::notice(&format!(
-                    "Discovered {total} items: {tests} tests, {benchmarks} benchmarks, {ignored} ignored"
-                ))
-                .title("Test Discovery")
-                .format());
-
-                parts.join("")
+            Self::Discovery | Self::Completed { .. } | Self::Started { .. } | Self::Ok { .. } => {
+                Severity::Notice
             }
+            Self::Failed { .. } => Severity::Error,
+        }
+    }
 
-            &Self::Started { test_count, .. } => {
-                // We don't start a group here because the individual tests will
-                // create their own groups.
-                GitHub::notice(&format!("Running {test_count} tests"))
-                    .title("Test Suite Started")
-                    .format()
-            }
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Discovery => "DiscoveryStarted",
+            Self::Completed { .. } => "DiscoveryCompleted",
+            Self::Started { .. } => "SuiteStarted",
+            Self::Ok { .. } => "SuitePassed",
+            Self::Failed { .. } => "SuiteFailed",
+        }
+    }
 
-            Self::Failed {
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            &(Self::Ok {
                 passed,
                 failed,
                 ignored,
-                measured,
-                filtered_out,
                 exec_time,
-            } => {
-                let time_info = exec_time
-                    .map(|t| format!(" in {t:.2}s"))
-                    .unwrap_or_default();
-                GitHub::error(&format!(
-                        "{failed} failed, {passed} passed, {ignored} ignored, {measured} measured, {filtered_out} filtered out{time_info}"
-                    ))
-                    .title("Test Suite Failed")
-                    .format()
+                ..
             }
-
-            Self::Ok {
+            | Self::Failed {
                 passed,
                 failed,
                 ignored,
-                measured,
-                filtered_out,
                 exec_time,
-            } => {
-                let time_info = exec_time
-                    .map(|t| format!(" in {t:.2}s"))
-                    .unwrap_or_default();
-                GitHub::notice(&format!(
-                        "{passed} passed, {failed} failed, {ignored} ignored, {measured} measured, {filtered_out} filtered out{time_info}"
-                    ))
-                    .title("Test Suite Passed")
-                    .format()
-            }
+                ..
+            }) => Some(SuiteResult {
+                duration: exec_time.and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+                ..SuiteResult::new(passed, failed, ignored)
+            }),
+            Self::Discovery | Self::Completed { .. } | Self::Started { .. } => None,
         }
     }
 }
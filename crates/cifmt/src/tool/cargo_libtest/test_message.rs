@@ -1,7 +1,13 @@
 //! Individual test events from cargo test.
 
-use crate::ci::{GitHub, Plain};
-use crate::ci_message::CiMessage;
+use core::{fmt, time::Duration};
+
+use crate::capture::{OutputPolicy, TestGrouping};
+use crate::catalog::Title;
+use crate::ci::Platform;
+use crate::ci_message::{CiMessage, Classify, Severity};
+use crate::junit::{TestOutcome, TestResult};
+use crate::tool::cargo_libtest::doctest;
 use serde::Deserialize;
 
 /// Individual test events.
@@ -34,6 +40,10 @@ pub enum TestMessage {
     Started {
         /// Test name.
         name: String,
+        /// How the test's output is grouped, on the target for which the
+        /// message is formatted.
+        #[serde(skip)]
+        grouping: TestGrouping,
     },
 
     /// Test passed.
@@ -46,6 +56,10 @@ pub enum TestMessage {
         /// Optional stdout output.
         #[serde(skip_serializing_if = "Option::is_none")]
         stdout: Option<String>,
+        /// How the test's output is grouped, on the target for which the
+        /// message is formatted.
+        #[serde(skip)]
+        grouping: TestGrouping,
     },
 
     /// Test failed.
@@ -61,12 +75,26 @@ pub enum TestMessage {
         /// Optional failure message.
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
+        /// Location of the test, if it was discovered.
+        #[serde(skip)]
+        location: Option<TestLocation>,
+        /// How the test's output is grouped, on the target for which the
+        /// message is formatted.
+        #[serde(skip)]
+        grouping: TestGrouping,
     },
 
     /// Test timed out.
     Timeout {
         /// Test name.
         name: String,
+        /// Location of the test, if it was discovered.
+        #[serde(skip)]
+        location: Option<TestLocation>,
+        /// How the test's output is grouped, on the target for which the
+        /// message is formatted.
+        #[serde(skip)]
+        grouping: TestGrouping,
     },
 
     /// Test ignored.
@@ -79,91 +107,132 @@ pub enum TestMessage {
     },
 }
 
-impl CiMessage<Plain> for TestMessage {
-    fn format(&self) -> String {
+/// The location at which a test is defined.
+///
+/// This is not part of libtest's messages about the test's outcome, and is
+/// taken from the `discovered` event of the test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TestLocation {
+    /// Source file path.
+    pub file: String,
+    /// Starting line number.
+    pub line: u32,
+}
+
+impl TestMessage {
+    /// Apply a policy for the inclusion of the test's stdout and of the
+    /// reason it was ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy to apply.
+    pub(crate) fn apply_output_policy(&mut self, policy: OutputPolicy) {
+        match self {
+            Self::Ok { stdout, .. } => policy.apply(stdout, false),
+            Self::Failed { stdout, .. } => policy.apply(stdout, true),
+            Self::Ignored { message, .. } => policy.apply(message, false),
+            Self::Discovered { .. } | Self::Started { .. } | Self::Timeout { .. } => {}
+        }
+    }
+
+    /// Set how the test's output is grouped, on the target for which the
+    /// message is formatted next.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The grouping of the target.
+    pub(crate) fn set_grouping(&mut self, target: TestGrouping) {
+        match self {
+            Self::Started { grouping, .. }
+            | Self::Ok { grouping, .. }
+            | Self::Failed { grouping, .. }
+            | Self::Timeout { grouping, .. } => *grouping = target,
+            Self::Discovered { .. } | Self::Ignored { .. } => {}
+        }
+    }
+
+    /// The location of the test, as given by its `discovered` event.
+    ///
+    /// # Returns
+    ///
+    /// The location, or `None` if this message does not describe the
+    /// discovery of a test, or its location is invalid.
+    pub(crate) fn discovered_location(&self) -> Option<(&str, TestLocation)> {
         match self {
             Self::Discovered {
                 name,
-                ignore,
-                ignore_message,
                 source_path,
                 start_line,
-                start_col,
-                end_line,
-                end_col,
-            } => format!(
-                "TEST DISCOVERED: {name} (ignored: {ignore}, message: {ignore_message:?}, location: {source_path}:{start_line}:{start_col}-{end_line}:{end_col})"
-            ),
-
-            Self::Started { name } => format!("TEST STARTED: {name}"),
-
-            Self::Ok {
+                ..
+            } => Some((
                 name,
-                exec_time,
-                stdout,
-            } => {
-                let mut parts = Vec::with_capacity(2);
-
-                if let Some(v) = stdout.as_ref().filter(|s| !s.is_empty()) {
-                    parts.push(v.clone());
-                }
-
-                parts.push(format!(
-                    "TEST OK: {}{}",
-                    name,
-                    exec_time
-                        .map(|t| format!(" (executed in {t:.2}s)"))
-                        .unwrap_or_default()
-                ));
+                TestLocation {
+                    file: source_path.clone(),
+                    line: u32::try_from(*start_line).ok()?,
+                },
+            )),
+            Self::Started { .. }
+            | Self::Ok { .. }
+            | Self::Failed { .. }
+            | Self::Timeout { .. }
+            | Self::Ignored { .. } => None,
+        }
+    }
 
-                parts.join("\n")
-            }
+    /// Attach the location of the test to a failure, unless it is already
+    /// known.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolve` - Look up the location of a test by name.
+    pub(crate) fn resolve_location<'a>(
+        &mut self,
+        resolve: impl FnOnce(&str) -> Option<&'a TestLocation>,
+    ) {
+        if let Self::Failed { name, location, .. } | Self::Timeout { name, location, .. } = self
+            && location.is_none()
+        {
+            *location = resolve(name).cloned();
+        }
+    }
 
+    /// The file and line at which the test is defined, if known.
+    ///
+    /// The location of a failed test is known if it was discovered (see
+    /// [`TestMessage::resolve_location`]). Doctests are otherwise located
+    /// from their name (see [`doctest::location`]).
+    fn location(&self) -> Option<(&str, u32)> {
+        match self {
             Self::Failed {
-                name,
-                message,
-                stdout,
-                exec_time,
-            } => {
-                let mut parts = Vec::with_capacity(2);
-
-                if let Some(v) = stdout.as_ref().filter(|s| !s.is_empty()) {
-                    parts.push(v.clone());
-                }
-
-                parts.push(format!(
-                    "TEST FAILED: {}{}{}\n",
-                    name,
-                    exec_time
-                        .map(|t| format!(" (executed in {t:.2}s)"))
-                        .unwrap_or_default(),
-                    message
-                        .as_ref()
-                        .map(|m| format!(" - {m}"))
-                        .unwrap_or_default()
-                ));
-
-                parts.join("\n")
+                location: Some(TestLocation { file, line }),
+                ..
             }
-
-            Self::Timeout { name } => format!("TEST TIMEOUT: {name}"),
-
-            Self::Ignored { name, message } => format!(
-                "TEST IGNORED: {}{}",
-                name,
-                message
-                    .as_ref()
-                    .filter(|s| !s.is_empty())
-                    .map(|s| format!(" - {}", s.replace('\n', " ")))
-                    .unwrap_or_default()
-            ),
+            | Self::Timeout {
+                location: Some(TestLocation { file, line }),
+                ..
+            } => Some((file, *line)),
+            Self::Discovered { name, .. }
+            | Self::Started { name, .. }
+            | Self::Ok { name, .. }
+            | Self::Failed { name, .. }
+            | Self::Timeout { name, .. }
+            | Self::Ignored { name, .. } => doctest::location(name),
         }
     }
 }
 
-impl CiMessage<GitHub> for TestMessage {
+/// Display a title followed by the name of the test (e.g., `Test Failed:
+/// tests::a`).
+fn titled(title: Title, name: &str) -> impl fmt::Display {
+    fmt::from_fn(move |f| write!(f, "{title}: {name}"))
+}
+
+impl<P: Platform> CiMessage<P> for TestMessage {
     fn format(&self) -> String {
-        match self {
+        let location = self.location().map(|(file, line)| (file, line, None));
+        let stdout = |stdout: &Option<String>| stdout.clone().filter(|s| !s.is_empty());
+        let lines = match self {
             Self::Discovered {
                 name,
                 ignore,
@@ -173,80 +242,177 @@ impl CiMessage<GitHub> for TestMessage {
                 start_col,
                 end_line,
                 end_col,
-            } => GitHub::debug(format!(
+            } => vec![P::debug(format!(
                 "Discovered test: {name} (ignored: {ignore}, message: {ignore_message:?}, location: {source_path}:{start_line}:{start_col}-{end_line}:{end_col})",
-            )),
+            ))],
+
+            Self::Started {
+                name,
+                grouping: TestGrouping::All,
+            } => vec![P::group(titled(Title::Test, name).to_string())],
 
-            Self::Started { name } => GitHub::group(format!("Test: {name}")),
+            // The group is only opened once the test is known to pass.
+            Self::Started {
+                name,
+                grouping: TestGrouping::Passing,
+            } => vec![P::debug(titled(Title::TestStarted, name).to_string())],
 
             Self::Ok {
                 name,
                 exec_time,
-                stdout,
+                stdout: output,
+                grouping,
             } => {
-                let mut parts = Vec::with_capacity(3);
-
-                if let Some(v) = stdout.as_ref().filter(|s| !s.is_empty()) {
-                    parts.push(v.clone() + "\n");
+                let mut lines = Vec::with_capacity(4);
+                if *grouping == TestGrouping::Passing {
+                    lines.push(P::group(titled(Title::Test, name).to_string()));
                 }
-
-                parts.push(
-                    GitHub::notice(
-                        &exec_time
-                            .map(|t| format!("Executed in {t:.2}s"))
-                            .unwrap_or_default(),
-                    )
-                    .title(&format!("Test Passed: {name}"))
-                    .format(),
-                );
-
-                parts.push(GitHub::endgroup());
-
-                parts.join("")
+                lines.extend(stdout(output));
+                lines.push(P::annotate(
+                    Severity::Notice,
+                    exec_time
+                        .map(|t| format!("Executed in {t:.2}s"))
+                        .unwrap_or_default(),
+                    None,
+                    Some(&titled(Title::TestPassed, name).to_string()),
+                ));
+                lines.push(P::endgroup());
+                lines
             }
 
             Self::Failed {
                 name,
                 message,
-                stdout,
+                stdout: output,
                 exec_time,
+                grouping,
+                ..
             } => {
-                let mut parts = Vec::with_capacity(3);
+                let mut lines = Vec::with_capacity(3);
+                lines.extend(stdout(output));
+                if *grouping == TestGrouping::All {
+                    lines.push(P::endgroup());
+                }
+                lines.push(P::annotate(
+                    self.severity(),
+                    message.as_deref().unwrap_or_default(),
+                    location,
+                    Some(&format!(
+                        "{}{}",
+                        titled(Title::TestFailed, name),
+                        exec_time
+                            .map(|t| format!(" (executed in {t:.2}s)"))
+                            .unwrap_or_default()
+                    )),
+                ));
+                lines
+            }
 
-                if let Some(v) = stdout.as_ref().filter(|s| !s.is_empty()) {
-                    parts.push(v.clone() + "\n");
+            Self::Timeout { name, grouping, .. } => {
+                let mut lines = Vec::with_capacity(2);
+                if *grouping == TestGrouping::All {
+                    lines.push(P::endgroup());
                 }
+                lines.push(P::annotate(
+                    self.severity(),
+                    name,
+                    location,
+                    Some(Title::TestTimeout.text()),
+                ));
+                lines
+            }
 
-                parts.push(GitHub::endgroup());
+            // Ignored tests are summarized by their reason at the end of the
+            // output (see `SkipSummary`), rather than annotated individually.
+            Self::Ignored { name, message } => vec![P::debug(format!(
+                "{}{}",
+                titled(Title::TestIgnored, name),
+                message
+                    .as_deref()
+                    .filter(|m| !m.is_empty())
+                    .map(|m| format!(" - {}", m.replace('\n', " ")))
+                    .unwrap_or_default()
+            ))],
+        };
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
 
-                let time_info = exec_time
-                    .map(|t| format!(" (executed in {t:.2}s)"))
-                    .unwrap_or_default();
+impl Classify for TestMessage {
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Discovered { .. } | Self::Started { .. } | Self::Ignored { .. } => {
+                Severity::Debug
+            }
+            Self::Ok { .. } => Severity::Notice,
+            Self::Failed { .. } | Self::Timeout { .. } => Severity::Error,
+        }
+    }
 
-                parts.push(
-                    GitHub::notice(message.as_deref().unwrap_or_default())
-                        .title(&format!("Test Failed: {name}{time_info}"))
-                        .format(),
-                );
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Discovered { .. } => "TestDiscovered",
+            Self::Started { .. } => "TestStarted",
+            Self::Ok { .. } => "TestPassed",
+            Self::Failed { .. } => "TestFailed",
+            Self::Timeout { .. } => "TestTimeout",
+            Self::Ignored { .. } => "TestIgnored",
+        }
+    }
 
-                parts.join("")
-            }
+    fn is_test_failure(&self) -> bool {
+        matches!(self, Self::Failed { .. } | Self::Timeout { .. })
+    }
 
-            Self::Timeout { name } => [
-                GitHub::endgroup(),
-                GitHub::error(name).title("Test Timeout").format(),
-            ]
-            .join(""),
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Discovered { name, .. }
+            | Self::Started { name, .. }
+            | Self::Ok { name, .. }
+            | Self::Failed { name, .. }
+            | Self::Timeout { name, .. }
+            | Self::Ignored { name, .. } => Some(name),
+        }
+    }
 
-            Self::Ignored { name, message } => GitHub::notice(
-                &message
-                    .as_deref()
-                    .filter(|s| !s.is_empty())
-                    .map(|s| s.replace('\n', " "))
-                    .unwrap_or_default(),
-            )
-            .title(&format!("Test Ignored: {name}"))
-            .format(),
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        let duration = |exec_time: &Option<f64>| {
+            exec_time.and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        };
+        match self {
+            Self::Ok {
+                name,
+                exec_time,
+                stdout,
+                ..
+            } => Some(TestResult {
+                duration: duration(exec_time),
+                output: stdout.as_deref(),
+                ..TestResult::new(name, TestOutcome::Passed)
+            }),
+            Self::Failed {
+                name,
+                exec_time,
+                stdout,
+                message,
+                ..
+            } => Some(TestResult {
+                duration: duration(exec_time),
+                message: message.as_deref(),
+                output: stdout.as_deref(),
+                ..TestResult::new(name, TestOutcome::Failed)
+            }),
+            Self::Ignored { name, message } => Some(TestResult {
+                message: message.as_deref(),
+                ..TestResult::new(name, TestOutcome::Skipped)
+            }),
+            // A timeout only warns that a test is slow, and is followed by
+            // its result.
+            Self::Discovered { .. } | Self::Started { .. } | Self::Timeout { .. } => None,
         }
     }
 }
@@ -254,9 +420,11 @@ impl CiMessage<GitHub> for TestMessage {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::TestMessage;
+    use crate::capture::TestGrouping;
     use serde_json::json;
 
     /// Test data for test messages: (JSON value, message instance, description).
+    #[expect(clippy::too_many_lines, reason = "Test data with many variants")]
     pub fn cases() -> impl Iterator<Item = (String, serde_json::Value, TestMessage)> {
         [
             (
@@ -292,6 +460,7 @@ pub(crate) mod tests {
                 }),
                 TestMessage::Started {
                     name: "test_example".to_owned(),
+                    grouping: TestGrouping::All,
                 },
             ),
             (
@@ -306,6 +475,7 @@ pub(crate) mod tests {
                     name: "test_example".to_owned(),
                     exec_time: Some(0.001),
                     stdout: None,
+                    grouping: TestGrouping::All,
                 },
             ),
             (
@@ -322,6 +492,26 @@ pub(crate) mod tests {
                     exec_time: Some(0.003),
                     stdout: None,
                     message: Some("assertion failed".to_owned()),
+                    location: None,
+                    grouping: TestGrouping::All,
+                },
+            ),
+            (
+                "test_failed_doctest".to_owned(),
+                json!({
+                    "type":"test",
+                    "event":"failed",
+                    "name":"src/lib.rs - module::func (line 42)",
+                    "exec_time":0.003,
+                    "stdout":"assertion failed",
+                }),
+                TestMessage::Failed {
+                    name: "src/lib.rs - module::func (line 42)".to_owned(),
+                    exec_time: Some(0.003),
+                    stdout: Some("assertion failed".to_owned()),
+                    message: None,
+                    location: None,
+                    grouping: TestGrouping::All,
                 },
             ),
             (
@@ -333,6 +523,8 @@ pub(crate) mod tests {
                 }),
                 TestMessage::Timeout {
                     name: "test_hanging".to_owned(),
+                    location: None,
+                    grouping: TestGrouping::All,
                 },
             ),
             (
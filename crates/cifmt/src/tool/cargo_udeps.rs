@@ -0,0 +1,809 @@
+//! Cargo udeps output format.
+//!
+//! Support for parsing the output of `cargo udeps --output json`, which lists
+//! the dependencies of each package of the workspace which are not used by
+//! any of its targets. The report is printed on a single line at the end of
+//! the output, as a JSON object whose `unused_deps` map each package to the
+//! unused dependencies of each kind (`normal`, `development` and `build`),
+//! along with the path of the package's manifest. The human-readable output
+//! of cargo (e.g., `Compiling ...`) precedes it when stderr is redirected to
+//! stdout.
+//!
+//! Each unused dependency is reported with a warning, annotated on the line
+//! of the manifest where it is declared in the table of its kind (e.g.,
+//! `[dev-dependencies]`, or `[target.'cfg(unix)'.dev-dependencies]`). The
+//! manifest is read from disk; if it cannot be read, or the dependency is not
+//! found in it (e.g., it is declared in a way which is not recognised), the
+//! first line of the manifest is annotated instead. A summary of the number
+//! of unused dependencies follows at the end of the output.
+
+use core::{fmt, mem};
+use std::{collections::BTreeMap, fs, io::BufRead as _};
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the output of `cargo udeps`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UdepsMessage {
+    /// A dependency which is not used.
+    Unused(UnusedDependency),
+
+    /// The number of unused dependencies, reported at the end of the output.
+    Summary(UdepsSummary),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// An incomplete JSON report at the end of the output.
+    TruncatedLine(TruncatedLine),
+
+    /// Human-readable output which is not part of the report.
+    TextOutput(TextOutput),
+}
+
+/// The kind of a dependency, after the table of the manifest in which it is
+/// declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DependencyKind {
+    /// A dependency of the package's targets (`[dependencies]`).
+    Normal,
+    /// A dependency of the package's tests, examples and benchmarks
+    /// (`[dev-dependencies]`).
+    Development,
+    /// A dependency of the package's build script (`[build-dependencies]`).
+    Build,
+}
+
+impl DependencyKind {
+    /// The name of the table of the manifest in which dependencies of this
+    /// kind are declared.
+    #[must_use]
+    #[inline]
+    pub const fn table(self) -> &'static str {
+        match self {
+            Self::Normal => "dependencies",
+            Self::Development => "dev-dependencies",
+            Self::Build => "build-dependencies",
+        }
+    }
+
+    /// The kind of the dependencies declared in a table, from its name
+    /// without the target (e.g., `dev-dependencies`).
+    fn from_table(table: &str) -> Option<Self> {
+        match table {
+            "dependencies" => Some(Self::Normal),
+            // Cargo still accepts the names with underscores
+            "dev-dependencies" | "dev_dependencies" => Some(Self::Development),
+            "build-dependencies" | "build_dependencies" => Some(Self::Build),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DependencyKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.table())
+    }
+}
+
+/// A dependency which is not used by any target of its package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnusedDependency {
+    /// The name of the package which declares the dependency.
+    pub package: String,
+    /// The path of the package's manifest.
+    pub manifest_path: String,
+    /// The name of the dependency, as declared in the manifest.
+    pub name: String,
+    /// The kind of the dependency.
+    pub kind: DependencyKind,
+    /// The line of the manifest which declares the dependency, if found.
+    pub line: Option<u32>,
+    /// The message of the diagnostic (e.g., ``unused dependency `log` in
+    /// `[dependencies]` ``), from which its fingerprint is computed.
+    pub message: String,
+}
+
+impl UnusedDependency {
+    /// Describe an unused dependency.
+    ///
+    /// # Arguments
+    ///
+    /// * `package` - The name of the package which declares the dependency.
+    /// * `manifest_path` - The path of the package's manifest.
+    /// * `name` - The name of the dependency.
+    /// * `kind` - The kind of the dependency.
+    fn new(package: &str, manifest_path: &str, name: String, kind: DependencyKind) -> Self {
+        let message = format!("unused dependency `{name}` in `[{kind}]`");
+        Self {
+            package: package.to_owned(),
+            manifest_path: manifest_path.to_owned(),
+            name,
+            kind,
+            line: None,
+            message,
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for UnusedDependency {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            format!(
+                "Dependency `{}` of package `{}` is not used (in `[{}]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive",
+                self.name, self.package, self.kind
+            ),
+            Some((&self.manifest_path, self.line.unwrap_or(1), None)),
+            Some(Title::UnusedDependencies.text()),
+        )
+    }
+}
+
+impl Classify for UnusedDependency {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        Some(&self.package)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.manifest_path)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// The number of unused dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UdepsSummary {
+    /// Whether a report of `cargo udeps` was found in the output.
+    pub reported: bool,
+    /// The number of unused dependencies.
+    pub dependencies: usize,
+    /// The number of packages with unused dependencies.
+    pub packages: usize,
+}
+
+impl<P: Platform> CiMessage<P> for UdepsSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let message = if !self.reported {
+            "No report of unused dependencies found".to_owned()
+        } else if self.dependencies == 0 {
+            "All dependencies are used".to_owned()
+        } else {
+            format!(
+                "{} unused dependency(ies) in {} package(s)",
+                self.dependencies, self.packages
+            )
+        };
+        P::annotate(
+            self.severity(),
+            message,
+            None,
+            Some(Title::DependencyCheck.text()),
+        )
+    }
+}
+
+impl Classify for UdepsSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl<P: Platform> CiMessage<P> for UdepsMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Unused(msg) => <UnusedDependency as CiMessage<P>>::format(msg),
+            Self::Summary(msg) => <UdepsSummary as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for UdepsMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Unused(msg) => msg.severity(),
+            Self::Summary(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Unused(msg) => msg.kind(),
+            Self::Summary(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::Unused(_) | Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => {
+                false
+            }
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Unused(msg) => Some(&msg.manifest_path),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        match self {
+            Self::Unused(msg) => msg.package(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Unused(msg) => msg.file(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Unused(msg) => msg.message(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for UdepsMessage {}
+
+impl Captured for UdepsMessage {}
+
+/// The report of `cargo udeps --output json`.
+#[derive(Debug, Deserialize)]
+struct Report {
+    /// The unused dependencies of each package, by the package's ID (e.g.,
+    /// `foo 0.1.0 (path+file:///w/foo)`).
+    unused_deps: BTreeMap<String, PackageReport>,
+}
+
+/// The unused dependencies of a package.
+#[derive(Debug, Deserialize)]
+struct PackageReport {
+    /// The path of the package's manifest.
+    manifest_path: String,
+    /// The unused dependencies of the package's targets.
+    #[serde(default)]
+    normal: Vec<String>,
+    /// The unused dependencies of the package's tests, examples and
+    /// benchmarks.
+    #[serde(default)]
+    development: Vec<String>,
+    /// The unused dependencies of the package's build script.
+    #[serde(default)]
+    build: Vec<String>,
+}
+
+impl PackageReport {
+    /// The unused dependencies of the package, located in its manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `package` - The name of the package.
+    fn into_unused(self, package: &str) -> Vec<UnusedDependency> {
+        let Self {
+            manifest_path,
+            normal,
+            development,
+            build,
+        } = self;
+        let manifest = fs::read_to_string(&manifest_path).ok();
+        [
+            (DependencyKind::Normal, normal),
+            (DependencyKind::Development, development),
+            (DependencyKind::Build, build),
+        ]
+        .into_iter()
+        .flat_map(|(kind, names)| names.into_iter().map(move |name| (kind, name)))
+        .map(|(kind, name)| {
+            let mut unused = UnusedDependency::new(package, &manifest_path, name, kind);
+            unused.line = manifest
+                .as_deref()
+                .and_then(|text| find_dependency(text, kind, &unused.name));
+            unused
+        })
+        .collect()
+    }
+}
+
+/// The name of a package from its ID.
+///
+/// Package IDs are either of the form `foo 0.1.0 (<source>)`, or a package ID
+/// specification such as `path+file:///w/foo#0.1.0` or
+/// `registry+https://github.com/rust-lang/crates.io-index#foo@0.1.0`.
+fn package_name(id: &str) -> &str {
+    let first = id.split_whitespace().next().unwrap_or(id);
+    let Some((url, fragment)) = first.rsplit_once('#') else {
+        return first;
+    };
+    match fragment.split_once('@') {
+        Some((name, _)) => name,
+        None => url.rsplit('/').next().unwrap_or(url),
+    }
+}
+
+/// The kind of the dependencies declared in a table of a manifest.
+///
+/// # Arguments
+///
+/// * `header` - The name of the table, without quotes or whitespace (e.g.,
+///   `target.cfg(unix).dev-dependencies`).
+fn table_kind(header: &str) -> Option<DependencyKind> {
+    let (prefix, table) = header.rsplit_once('.').unwrap_or(("", header));
+    if prefix.is_empty() || prefix.starts_with("target.") {
+        DependencyKind::from_table(table)
+    } else {
+        None
+    }
+}
+
+/// Find the line of a manifest which declares a dependency.
+///
+/// The dependency is looked for in the tables of its kind, either as a key
+/// (e.g., `log = "0.4"` or `log.workspace = true`) or as a table of its own
+/// (e.g., `[dependencies.log]`).
+///
+/// # Arguments
+///
+/// * `manifest` - The text of the manifest.
+/// * `kind` - The kind of the dependency.
+/// * `name` - The name of the dependency, as declared in the manifest.
+///
+/// # Returns
+///
+/// The line of the declaration (1-based), or `None` if it is not found.
+fn find_dependency(manifest: &str, kind: DependencyKind, name: &str) -> Option<u32> {
+    let unquote = |key: &str| -> String {
+        key.chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '"' | '\''))
+            .collect()
+    };
+    let mut in_table = false;
+    for (number, raw) in (1_u32..).zip(manifest.lines()) {
+        let line = raw.trim();
+        if let Some(rest) = line.strip_prefix('[') {
+            let header = unquote(rest.split_once(']').map_or(rest, |(inner, _)| inner));
+            in_table = table_kind(&header) == Some(kind);
+            if let Some((table, key)) = header.rsplit_once('.')
+                && key == name
+                && table_kind(table) == Some(kind)
+            {
+                return Some(number);
+            }
+        } else if in_table
+            && let Some((key, _)) = line.split_once('=')
+            && unquote(key.split('.').next().unwrap_or(key)) == name
+        {
+            return Some(number);
+        }
+    }
+    None
+}
+
+/// Whether a line of the output may be the report of `cargo udeps`.
+fn is_report(line: &[u8]) -> bool {
+    line.first() == Some(&b'{')
+}
+
+/// The unused dependencies reported so far.
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    /// Whether a report was found.
+    reported: bool,
+    /// The number of unused dependencies reported so far.
+    dependencies: usize,
+    /// The number of packages with unused dependencies reported so far.
+    packages: usize,
+}
+
+impl Totals {
+    /// Parse a single line of the output of `cargo udeps`.
+    ///
+    /// # Returns
+    ///
+    /// The messages parsed from the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<UdepsMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                let mut results = vec![Ok(UdepsMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        // Skip empty lines
+        if line.is_empty() {
+            return Vec::new();
+        }
+
+        if is_report(line) {
+            return match serde_json::from_slice::<Report>(line) {
+                Ok(report) => {
+                    self.reported = true;
+                    let mut results = Vec::new();
+                    for (id, package) in report.unused_deps {
+                        let unused = package.into_unused(package_name(&id));
+                        if !unused.is_empty() {
+                            self.packages = self.packages.saturating_add(1);
+                            self.dependencies = self.dependencies.saturating_add(unused.len());
+                        }
+                        results.extend(unused.into_iter().map(|dep| Ok(UdepsMessage::Unused(dep))));
+                    }
+                    results
+                }
+                Err(e) => vec![Err(e)],
+            };
+        }
+
+        text.handle(line)
+            .map(|output| Ok(UdepsMessage::TextOutput(output)))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Tool implementation for parsing the output of `cargo udeps`.
+#[derive(Debug, Clone, Default)]
+pub struct CargoUdeps {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of the report.
+    text: TextLines,
+    /// The unused dependencies reported so far.
+    totals: Totals,
+}
+
+impl Detect for CargoUdeps {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| is_report(line.as_bytes()) && serde_json::from_str::<Report>(&line).is_ok())
+            .then(Self::default)
+    }
+}
+
+impl CargoUdeps {
+    /// A problem matcher for the human-readable output of `cargo udeps`.
+    ///
+    /// Each unused dependency is listed on a line of its own (e.g.,
+    /// `     └─── "log"`), below the table of the package which declares it.
+    /// As the manifest is not named on the line, each match is reported as a
+    /// warning without a location, with the quoted name of the dependency as
+    /// its message.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cargo-udeps",
+            vec![Pattern::new(r#"^\s+└─── ("[^"]+")$"#).message(1)],
+        )
+        .with_severity("warning")
+    }
+}
+
+impl Tool for CargoUdeps {
+    type Message = UdepsMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "cargo-udeps"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.totals.parse_line(line, max_length, &mut self.text));
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            for result in self.totals.parse_line(line, max_length, &mut self.text) {
+                results.push(result.or_else(|error| {
+                    TruncatedLine::from_final_line(position, line, error)
+                        .map(UdepsMessage::TruncatedLine)
+                }));
+            }
+        }
+
+        let totals = mem::take(&mut self.totals);
+        results.push(Ok(UdepsMessage::Summary(UdepsSummary {
+            reported: totals.reported,
+            dependencies: totals.dependencies,
+            packages: totals.packages,
+        })));
+        if let Some(output) = self.text.finish() {
+            results.push(Ok(UdepsMessage::TextOutput(output)));
+        }
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for CargoUdeps
+where
+    UdepsMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    use super::{CargoUdeps, DependencyKind, UdepsMessage, find_dependency, package_name};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{CargoCheck, CargoFmt, Detect as _, Tool as _};
+
+    const REPORT: &str = r#"{"success":false,"unused_deps":{"app 0.1.0 (path+file:///w/app)":{"manifest_path":"/w/app/Cargo.toml","normal":["anyhow","log"],"development":["rstest"],"build":[]},"core 0.1.0 (path+file:///w/core)":{"manifest_path":"/w/core/Cargo.toml","normal":[],"development":[],"build":[]}},"note":"Note: They might be false-positive.\n"}"#;
+
+    const MANIFEST: &str = r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+anyhow = "1"
+"log" = { version = "0.4" } # logging
+serde.workspace = true
+
+[target.'cfg(unix)'.dev-dependencies]
+libc = "0.2"
+
+[dev-dependencies.rstest]
+version = "0.26"
+
+[build-dependencies]
+log = "0.4"
+"#;
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        UdepsMessage: CiMessage<P>,
+    {
+        let mut tool = CargoUdeps::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <UdepsMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn package_names() {
+        assert_eq!(package_name("app 0.1.0 (path+file:///w/app)"), "app");
+        assert_eq!(package_name("path+file:///w/app#0.1.0"), "app");
+        assert_eq!(package_name("path+file:///w/app#core@0.1.0"), "core");
+        assert_eq!(
+            package_name("registry+https://github.com/rust-lang/crates.io-index#log@0.4.22"),
+            "log"
+        );
+    }
+
+    #[test]
+    fn dependencies() {
+        let find = |kind, name| find_dependency(MANIFEST, kind, name);
+        assert_eq!(find(DependencyKind::Normal, "anyhow"), Some(6));
+        assert_eq!(find(DependencyKind::Normal, "log"), Some(7));
+        assert_eq!(find(DependencyKind::Normal, "serde"), Some(8));
+        assert_eq!(find(DependencyKind::Development, "libc"), Some(11));
+        assert_eq!(find(DependencyKind::Development, "rstest"), Some(13));
+        assert_eq!(find(DependencyKind::Build, "log"), Some(17));
+        assert_eq!(find(DependencyKind::Normal, "libc"), None);
+        assert_eq!(find(DependencyKind::Normal, "version"), None);
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(REPORT), @"
+        /w/app/Cargo.toml:1: warning: Unused Dependencies: Dependency `anyhow` of package `app` is not used (in `[dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        /w/app/Cargo.toml:1: warning: Unused Dependencies: Dependency `log` of package `app` is not used (in `[dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        /w/app/Cargo.toml:1: warning: Unused Dependencies: Dependency `rstest` of package `app` is not used (in `[dev-dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        notice:  Dependency Check: 3 unused dependency(ies) in 1 package(s)
+        ");
+    }
+
+    #[test]
+    fn manifest() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let manifest = dir.child("Cargo.toml");
+        manifest
+            .write_str(MANIFEST)
+            .expect("failed to write manifest");
+        let path = manifest.path().display().to_string();
+        let report = REPORT.replace("/w/app/Cargo.toml", &path);
+
+        let output = format::<GitHub>(&report).replace(&path, "/w/app/Cargo.toml");
+        insta::assert_snapshot!(output, @"
+        ::warning file=/w/app/Cargo.toml,line=6,title=Unused Dependencies::Dependency `anyhow` of package `app` is not used (in `[dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        ::warning file=/w/app/Cargo.toml,line=7,title=Unused Dependencies::Dependency `log` of package `app` is not used (in `[dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        ::warning file=/w/app/Cargo.toml,line=13,title=Unused Dependencies::Dependency `rstest` of package `app` is not used (in `[dev-dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        ::notice title=Dependency Check::3 unused dependency(ies) in 1 package(s)
+        ");
+    }
+
+    #[test]
+    fn text() {
+        let output = format!("   Compiling app v0.1.0 (/w/app)\n{REPORT}\n");
+        insta::assert_snapshot!(format::<Plain>(&output), @"
+        /w/app/Cargo.toml:1: warning: Unused Dependencies: Dependency `anyhow` of package `app` is not used (in `[dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        /w/app/Cargo.toml:1: warning: Unused Dependencies: Dependency `log` of package `app` is not used (in `[dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        /w/app/Cargo.toml:1: warning: Unused Dependencies: Dependency `rstest` of package `app` is not used (in `[dev-dependencies]`); remove it, or ignore it in `package.metadata.cargo-udeps.ignore` if it is a false positive
+        notice:  Dependency Check: 3 unused dependency(ies) in 1 package(s)
+        ");
+    }
+
+    #[test]
+    fn unused_free() {
+        let output = r#"{"success":true,"unused_deps":{},"note":null}"#;
+        insta::assert_snapshot!(format::<GitHub>(output), @"::notice title=Dependency Check::All dependencies are used");
+    }
+
+    #[test]
+    fn empty() {
+        insta::assert_snapshot!(format::<GitHub>(""), @"::notice title=Dependency Check::No report of unused dependencies found");
+    }
+
+    #[test]
+    fn truncated() {
+        let output = REPORT.get(..40).expect("in bounds");
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 40 bytes of an incomplete message: EOF while parsing a string at line 1 column 40
+        notice:  Dependency Check: No report of unused dependencies found
+        ");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(CargoUdeps::detect(REPORT.as_bytes()).is_some());
+        assert!(
+            CargoUdeps::detect(b"{\"reason\":\"build-finished\",\"success\":true}\n").is_none()
+        );
+        assert!(CargoCheck::detect(REPORT.as_bytes()).is_none());
+        assert!(CargoFmt::detect(REPORT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = CargoUdeps::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regex
+            .captures("     └─── \"log\"")
+            .expect("dependency not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("\"log\""));
+        assert!(!regex.is_match("└─── dependencies"));
+        assert!(!regex.is_match("`app v0.1.0 (/w/app)`"));
+    }
+}
@@ -0,0 +1,664 @@
+//! CMake diagnostics.
+//!
+//! Support for parsing the errors and warnings which CMake prints while it
+//! configures a project and generates its build system (e.g., `cmake -B
+//! build`), so that they are annotated on the line of the `CMakeLists.txt`
+//! (or of the included script) which caused them.
+//!
+//! A diagnostic starts with a header (e.g., `CMake Error at
+//! CMakeLists.txt:10 (message):`), which gives its level, its location and
+//! the command which reported it, and continues with the indented lines
+//! under it, which are its message. The call stack which CMake prints under
+//! a diagnostic in an included script (`Call Stack (most recent call
+//! first):`) is kept at the end of the message. A diagnostic of the
+//! generation step without a line (e.g., `CMake Error in CMakeLists.txt:`)
+//! is not located, and one without a file (e.g., `CMake Error: The source
+//! directory "/src" does not exist.`) has its message on the header.
+//!
+//! Any other output (e.g., the `-- ` lines of the checks which CMake runs) is
+//! passed through as plain text output.
+
+use core::{convert::Infallible, fmt};
+use std::io::BufRead as _;
+
+use crate::{
+    ansi,
+    capture::Captured,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+    },
+};
+
+/// The line which CMake prints before the call stack of a diagnostic.
+const CALL_STACK: &str = "Call Stack (most recent call first):";
+
+/// The line which ends the warnings for project developers.
+const DEV_WARNING_END: &str = "This warning is for project developers.";
+
+/// A message parsed from the output of CMake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CmakeMessage {
+    /// An error or warning of CMake.
+    Diagnostic(CmakeDiagnostic),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// Output which is not part of a diagnostic.
+    TextOutput(TextOutput),
+}
+
+impl<P: Platform> CiMessage<P> for CmakeMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => <CmakeDiagnostic as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for CmakeMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Diagnostic(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Diagnostic(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.file(),
+            Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for CmakeMessage {}
+
+impl Captured for CmakeMessage {}
+
+/// The level of a diagnostic of CMake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CmakeLevel {
+    /// An error (e.g., of `message(SEND_ERROR)` or `message(FATAL_ERROR)`).
+    Error,
+    /// The use of a deprecated feature, made an error.
+    DeprecationError,
+    /// A warning.
+    Warning,
+    /// A warning for the developers of the project (e.g., of
+    /// `message(AUTHOR_WARNING)`).
+    DevWarning,
+    /// The use of a deprecated feature.
+    DeprecationWarning,
+}
+
+impl CmakeLevel {
+    /// All levels, each before any other whose header starts with its own.
+    const ALL: [Self; 5] = [
+        Self::Error,
+        Self::DeprecationError,
+        Self::DevWarning,
+        Self::Warning,
+        Self::DeprecationWarning,
+    ];
+
+    /// The start of the header of a diagnostic of the level.
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "CMake Error",
+            Self::DeprecationError => "CMake Deprecation Error",
+            Self::Warning => "CMake Warning",
+            Self::DevWarning => "CMake Warning (dev)",
+            Self::DeprecationWarning => "CMake Deprecation Warning",
+        }
+    }
+}
+
+impl fmt::Display for CmakeLevel {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error or warning of CMake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CmakeDiagnostic {
+    /// The level of the diagnostic.
+    pub level: CmakeLevel,
+    /// The file of the diagnostic (e.g., `CMakeLists.txt`), if any.
+    pub file: Option<String>,
+    /// The line of the diagnostic, if any.
+    pub line: Option<u32>,
+    /// The command which reported the diagnostic (e.g., `find_package`), if
+    /// any.
+    pub command: Option<String>,
+    /// The message of the diagnostic, with its paragraphs separated by empty
+    /// lines.
+    pub message: String,
+    /// The frames of the call stack, most recent first (e.g.,
+    /// `CMakeLists.txt:13 (include)`).
+    pub call_stack: Vec<String>,
+}
+
+impl CmakeDiagnostic {
+    /// The title of the annotation (e.g., `CMake Error (find_package)`).
+    fn title(&self) -> String {
+        match (&self.command, &self.file) {
+            (Some(command), _) => format!("{} ({command})", self.level),
+            (None, Some(file)) if self.line.is_none() => format!("{} in {file}", self.level),
+            (None, Some(_) | None) => self.level.to_string(),
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for CmakeDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        let mut message = self.message.clone();
+        if !self.call_stack.is_empty() {
+            message = format!("{message}\n{CALL_STACK}");
+            for frame in &self.call_stack {
+                message = format!("{message}\n  {frame}");
+            }
+        }
+        let location = self
+            .file
+            .as_deref()
+            .zip(self.line)
+            .map(|(file, line)| (file, line, None));
+        P::annotate(self.severity(), message, location, Some(&self.title()))
+    }
+}
+
+impl Classify for CmakeDiagnostic {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.level {
+            CmakeLevel::Error | CmakeLevel::DeprecationError => Severity::Error,
+            CmakeLevel::Warning | CmakeLevel::DevWarning | CmakeLevel::DeprecationWarning => {
+                Severity::Warning
+            }
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// Parse the header of a diagnostic (e.g., `CMake Error at CMakeLists.txt:10
+/// (message):`).
+///
+/// # Returns
+///
+/// The diagnostic, without the lines of its message which follow the
+/// header, or `None` if the line is not the header of a diagnostic.
+fn parse_header(line: &str) -> Option<CmakeDiagnostic> {
+    let (level, rest) = CmakeLevel::ALL
+        .iter()
+        .find_map(|&level| Some((level, line.strip_prefix(level.as_str())?)))?;
+    let mut diagnostic = CmakeDiagnostic {
+        level,
+        file: None,
+        line: None,
+        command: None,
+        message: String::new(),
+        call_stack: Vec::new(),
+    };
+    if let Some(message) = rest.strip_prefix(':') {
+        message.trim().clone_into(&mut diagnostic.message);
+    } else if let Some(located) = rest.strip_prefix(" at ") {
+        let (location, command) = located
+            .strip_suffix("):")?
+            .rsplit_once(" (")
+            .filter(|(_, command)| !command.is_empty())?;
+        let (file, number) = location.rsplit_once(':')?;
+        diagnostic.file = Some(file.to_owned());
+        diagnostic.line = Some(number.parse().ok()?);
+        diagnostic.command = Some(command.to_owned());
+    } else {
+        let file = rest.strip_prefix(" in ")?.strip_suffix(':')?;
+        diagnostic.file = Some(file.to_owned());
+    }
+    Some(diagnostic)
+}
+
+/// A diagnostic being read, up to the first line which is not indented.
+#[derive(Debug, Clone)]
+struct Block {
+    /// The diagnostic read so far.
+    diagnostic: CmakeDiagnostic,
+    /// The lines of the message, without their indentation.
+    lines: Vec<String>,
+    /// Whether the call stack is being read.
+    in_call_stack: bool,
+}
+
+impl Block {
+    /// Read a line of the diagnostic.
+    ///
+    /// # Returns
+    ///
+    /// Whether the line is part of the diagnostic.
+    fn read(&mut self, line: &str) -> bool {
+        if line == CALL_STACK {
+            self.in_call_stack = true;
+        } else if line.trim().is_empty() {
+            if !self.in_call_stack {
+                self.lines.push(String::new());
+            }
+        } else if !line.starts_with(char::is_whitespace) {
+            return false;
+        } else if self.in_call_stack {
+            self.diagnostic.call_stack.push(line.trim().to_owned());
+        } else {
+            let dedented = line.strip_prefix("  ").unwrap_or(line);
+            self.lines.push(dedented.trim_end().to_owned());
+        }
+        true
+    }
+
+    /// Finish reading the diagnostic.
+    fn finish(self) -> CmakeDiagnostic {
+        let mut diagnostic = self.diagnostic;
+        let body = self.lines.join("\n");
+        let trimmed = body.trim_matches('\n');
+        if !trimmed.is_empty() {
+            diagnostic.message = if diagnostic.message.is_empty() {
+                trimmed.to_owned()
+            } else {
+                format!("{}\n{trimmed}", diagnostic.message)
+            };
+        }
+        diagnostic
+    }
+}
+
+/// The state of the parser, apart from the framing of its lines.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The diagnostic being read.
+    block: Option<Block>,
+}
+
+impl Reader {
+    /// Report the diagnostic being read, if any.
+    fn flush(&mut self) -> Option<CmakeMessage> {
+        let block = self.block.take()?;
+        Some(CmakeMessage::Diagnostic(block.finish()))
+    }
+
+    /// Parse a single line of the output.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<CmakeMessage> {
+        let raw = match next {
+            Line::Complete(raw) => raw,
+            Line::Overlong(raw) => {
+                let mut results: Vec<_> = self.flush().into_iter().collect();
+                results.push(CmakeMessage::OverlongLine(OverlongLine { max_length }));
+                results.extend(self.parse_line(Line::Complete(raw), max_length, text));
+                return results;
+            }
+        };
+
+        // CMake colours its messages when forced to (e.g., with
+        // `CLICOLOR_FORCE=1`)
+        let stripped;
+        let bytes = if raw.contains(&b'\x1b') {
+            stripped = ansi::strip(raw);
+            stripped.as_slice()
+        } else {
+            raw
+        };
+        let line = String::from_utf8_lossy(bytes);
+
+        if let Some(block) = &mut self.block {
+            if block.read(&line) {
+                return Vec::new();
+            }
+            // The line ending a warning for developers is part of it
+            if line.starts_with(DEV_WARNING_END) {
+                return self.flush().into_iter().collect();
+            }
+        }
+
+        let mut results: Vec<_> = self.flush().into_iter().collect();
+        if let Some(diagnostic) = parse_header(&line) {
+            self.block = Some(Block {
+                diagnostic,
+                lines: Vec::new(),
+                in_call_stack: false,
+            });
+        } else if !line.trim().is_empty() {
+            results.extend(text.handle(bytes).map(CmakeMessage::TextOutput));
+        }
+        results
+    }
+}
+
+/// Tool implementation for parsing the errors and warnings of CMake.
+#[derive(Debug, Clone, Default)]
+pub struct Cmake {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a diagnostic.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl Cmake {
+    /// A problem matcher for the errors of CMake.
+    ///
+    /// The header of an error with a location is matched with the first
+    /// line of its message. Warnings are not matched.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cmake",
+            vec![
+                Pattern::new(r"^CMake Error at (.+):(\d+) \((.+)\):$")
+                    .file(1)
+                    .line(2)
+                    .code(3),
+                Pattern::new(r"^\s+(.+)$").message(1),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Detect for Cmake {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| parse_header(&line).is_some())
+            .then(Self::default)
+    }
+}
+
+impl Tool for Cmake {
+    type Message = CmakeMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "cmake"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+
+        // A diagnostic cut off before its end is reported as far as it was
+        // read
+        results.extend(self.reader.flush());
+        results.extend(self.text.finish().map(CmakeMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Cmake
+where
+    CmakeMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Cmake, CmakeLevel, CmakeMessage, parse_header};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{Detect as _, GccDiagnostics, Tool as _, passthrough::Passthrough};
+
+    const OUTPUT: &str = "CMake Warning (dev) in CMakeLists.txt:
+  Syntax Warning in cmake code at
+
+    /example/project/CMakeLists.txt:7:21
+
+  Argument not separated from preceding token by whitespace.
+This warning is for project developers.  Use -Wno-dev to suppress it.
+
+-- The C compiler identification is GNU 12.2.0
+CMake Error at CMakeLists.txt:10 (message):
+  The option ENABLE_FOO requires ENABLE_BAR
+
+
+CMake Error at cmake/Dependencies.cmake:3 (find_package):
+  By not providing \"FindZstd.cmake\" in CMAKE_MODULE_PATH this project has
+  asked CMake to find a package configuration file provided by \"Zstd\", but
+  CMake did not find one.
+Call Stack (most recent call first):
+  CMakeLists.txt:13 (include)
+
+
+-- Configuring incomplete, errors occurred!
+";
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut Cmake, output: &str) -> String
+    where
+        CmakeMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <CmakeMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn headers() {
+        let error =
+            parse_header("CMake Error at cmake/Deps.cmake:3 (find_package):").expect("header");
+        assert_eq!(error.level, CmakeLevel::Error);
+        assert_eq!(error.file.as_deref(), Some("cmake/Deps.cmake"));
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.command.as_deref(), Some("find_package"));
+        let dev =
+            parse_header("CMake Warning (dev) at CMakeLists.txt:2 (message):").expect("header");
+        assert_eq!(dev.level, CmakeLevel::DevWarning);
+        let deprecation =
+            parse_header("CMake Deprecation Warning at CMakeLists.txt:1 (cmake_minimum_required):")
+                .expect("header");
+        assert_eq!(deprecation.level, CmakeLevel::DeprecationWarning);
+        let generate = parse_header("CMake Error in src/CMakeLists.txt:").expect("header");
+        assert_eq!(generate.file.as_deref(), Some("src/CMakeLists.txt"));
+        assert_eq!(generate.line, None);
+        let unlocated = parse_header("CMake Error: The source directory \"/src\" does not exist.")
+            .expect("header");
+        assert_eq!(
+            unlocated.message,
+            "The source directory \"/src\" does not exist."
+        );
+        assert_eq!(
+            parse_header("CMake Error at CMakeLists.txt:x (message):"),
+            None
+        );
+        assert_eq!(parse_header("-- Configuring done"), None);
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(&mut Cmake::default(), OUTPUT), @r#"
+        ::warning title=CMake Warning (dev) in CMakeLists.txt::Syntax Warning in cmake code at%0A%0A  /example/project/CMakeLists.txt:7:21%0A%0AArgument not separated from preceding token by whitespace.
+        ::error file=CMakeLists.txt,line=10,title=CMake Error (message)::The option ENABLE_FOO requires ENABLE_BAR
+        ::error file=cmake/Dependencies.cmake,line=3,title=CMake Error (find_package)::By not providing "FindZstd.cmake" in CMAKE_MODULE_PATH this project has%0Aasked CMake to find a package configuration file provided by "Zstd", but%0ACMake did not find one.%0ACall Stack (most recent call first):%0A  CMakeLists.txt:13 (include)
+        "#);
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(&mut Cmake::default(), OUTPUT), @r#"
+        warning: CMake Warning (dev) in CMakeLists.txt: Syntax Warning in cmake code at\n\n  /example/project/CMakeLists.txt:7:21\n\nArgument not separated from preceding token by whitespace.
+        CMakeLists.txt:10: error:   CMake Error (message): The option ENABLE_FOO requires ENABLE_BAR
+        cmake/Dependencies.cmake:3: error:   CMake Error (find_package): By not providing "FindZstd.cmake" in CMAKE_MODULE_PATH this project has\nasked CMake to find a package configuration file provided by "Zstd", but\nCMake did not find one.\nCall Stack (most recent call first):\n  CMakeLists.txt:13 (include)
+        "#);
+    }
+
+    #[test]
+    fn passthrough() {
+        let mut tool = Cmake::default();
+        tool.set_passthrough(Passthrough::Raw);
+        insta::assert_snapshot!(format::<Plain>(&mut tool, OUTPUT), @r#"
+        warning: CMake Warning (dev) in CMakeLists.txt: Syntax Warning in cmake code at\n\n  /example/project/CMakeLists.txt:7:21\n\nArgument not separated from preceding token by whitespace.
+        -- The C compiler identification is GNU 12.2.0
+        CMakeLists.txt:10: error:   CMake Error (message): The option ENABLE_FOO requires ENABLE_BAR
+        cmake/Dependencies.cmake:3: error:   CMake Error (find_package): By not providing "FindZstd.cmake" in CMAKE_MODULE_PATH this project has\nasked CMake to find a package configuration file provided by "Zstd", but\nCMake did not find one.\nCall Stack (most recent call first):\n  CMakeLists.txt:13 (include)
+        -- Configuring incomplete, errors occurred!
+        "#);
+    }
+
+    #[test]
+    fn truncated() {
+        let output = "CMake Error at CMakeLists.txt:10 (message):\n  The option ENABLE_FOO";
+        insta::assert_snapshot!(format::<GitHub>(&mut Cmake::default(), output), @"::error file=CMakeLists.txt,line=10,title=CMake Error (message)::The option ENABLE_FOO");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Cmake::detect(OUTPUT.as_bytes()).is_some());
+        assert!(Cmake::detect(b"-- Configuring done\n").is_none());
+        assert!(GccDiagnostics::detect(OUTPUT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Cmake::problem_matcher();
+        let [header, message] = matcher.pattern.as_slice() else {
+            panic!("expected two patterns");
+        };
+        let located = regex::Regex::new(&header.regexp).expect("invalid regexp");
+        let indented = regex::Regex::new(&message.regexp).expect("invalid regexp");
+
+        let captures = located
+            .captures("CMake Error at cmake/Deps.cmake:3 (find_package):")
+            .expect("header not matched");
+        assert_eq!(
+            captures.get(1).map(|m| m.as_str()),
+            Some("cmake/Deps.cmake")
+        );
+        assert_eq!(captures.get(3).map(|m| m.as_str()), Some("find_package"));
+        assert!(!located.is_match("CMake Warning at CMakeLists.txt:2 (message):"));
+        assert!(indented.is_match("  The option ENABLE_FOO requires ENABLE_BAR"));
+    }
+}
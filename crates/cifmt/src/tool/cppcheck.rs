@@ -0,0 +1,634 @@
+//! cppcheck XML results.
+//!
+//! Support for parsing the results which cppcheck writes with `--xml` (the
+//! version 2 of its format), so that the issues found in C and C++ code are
+//! annotated at their location.
+//!
+//! Each `<error>` is an issue, reported as it ends. Its severity is mapped to
+//! that of the annotation: `error` issues are errors, `warning` issues are
+//! warnings, `style`, `performance` and `portability` issues are notices,
+//! and `information` issues (e.g., a missing include file) are only logged.
+//! The first `<location>` of an issue is where it is annotated, with the
+//! issue's `id` (e.g., `nullPointer`) as its code, and each other location
+//! (e.g., the assignment which made a pointer null) is annotated as a note,
+//! with its `info` attribute as its message. The `file` and `line`
+//! attributes of an `<error>` in the version 1 of the format are read as its
+//! location.
+//!
+//! As cppcheck writes its results to stderr and its progress (e.g.,
+//! `Checking src/main.c ...`) to stdout, any text outside of an issue is
+//! passed through as plain text output.
+
+use core::{convert::Infallible, fmt};
+
+use crate::{
+    capture::Captured,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        xml::{Element, Event, XmlReader},
+    },
+};
+
+/// A message parsed from the XML results of cppcheck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CppcheckMessage {
+    /// An issue found by cppcheck.
+    Issue(Box<CppcheckIssue>),
+
+    /// Output which is not part of the results.
+    TextOutput(TextOutput),
+}
+
+impl<P: Platform> CiMessage<P> for CppcheckMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Issue(msg) => <CppcheckIssue as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for CppcheckMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Issue(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Issue(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.code(),
+            Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.file(),
+            Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.message(),
+            Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for CppcheckMessage {}
+
+impl Captured for CppcheckMessage {}
+
+/// The severity of an issue found by cppcheck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CppcheckSeverity {
+    /// A bug (e.g., a null pointer dereference).
+    Error,
+    /// A likely bug, or code which is suspicious.
+    Warning,
+    /// A stylistic issue (e.g., an unused variable).
+    Style,
+    /// Code which could be faster.
+    Performance,
+    /// Code which may not behave the same on other platforms or compilers.
+    Portability,
+    /// Information about the analysis itself (e.g., a missing include file),
+    /// or any severity which is not known.
+    Information,
+}
+
+impl CppcheckSeverity {
+    /// Parse the `severity` attribute of an issue.
+    fn parse(severity: &str) -> Self {
+        match severity {
+            "error" => Self::Error,
+            "warning" => Self::Warning,
+            "style" => Self::Style,
+            "performance" => Self::Performance,
+            "portability" => Self::Portability,
+            _ => Self::Information,
+        }
+    }
+}
+
+impl fmt::Display for CppcheckSeverity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Style => "style",
+            Self::Performance => "performance",
+            Self::Portability => "portability",
+            Self::Information => "information",
+        })
+    }
+}
+
+/// A location of an issue (i.e., a `<location>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Location {
+    /// The file.
+    pub file: String,
+    /// The line.
+    pub line: u32,
+    /// The column, if known.
+    pub column: Option<u32>,
+    /// What happens at the location (e.g., `Null pointer dereference`), if
+    /// given.
+    pub info: Option<String>,
+}
+
+impl Location {
+    /// Read a location from the attributes of its element.
+    ///
+    /// # Returns
+    ///
+    /// The location, or `None` if the element has no file or line.
+    fn new(element: &Element) -> Option<Self> {
+        Some(Self {
+            file: element
+                .attribute("file")
+                .filter(|file| !file.is_empty())?
+                .to_owned(),
+            line: element.attribute("line")?.trim().parse().ok()?,
+            column: element
+                .attribute("column")
+                .and_then(|column| column.trim().parse().ok()),
+            info: element
+                .attribute("info")
+                .filter(|info| !info.is_empty())
+                .map(str::to_owned),
+        })
+    }
+}
+
+/// An issue found by cppcheck (i.e., an `<error>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CppcheckIssue {
+    /// The identifier of the check (e.g., `nullPointer`).
+    pub id: String,
+    /// The severity of the issue.
+    pub severity: CppcheckSeverity,
+    /// The short message of the issue.
+    pub message: String,
+    /// The long message of the issue, if it differs from the short one.
+    pub verbose: Option<String>,
+    /// The CWE identifier of the weakness, if any.
+    pub cwe: Option<u32>,
+    /// The locations of the issue, the one at which it is reported first.
+    pub locations: Vec<Location>,
+}
+
+impl CppcheckIssue {
+    /// Read an issue from the attributes of its element.
+    fn new(element: &Element) -> Self {
+        let message = element.attribute("msg").unwrap_or_default().to_owned();
+        Self {
+            id: element.attribute("id").unwrap_or_default().to_owned(),
+            severity: CppcheckSeverity::parse(element.attribute("severity").unwrap_or_default()),
+            verbose: element
+                .attribute("verbose")
+                .filter(|verbose| !verbose.is_empty() && *verbose != message)
+                .map(str::to_owned),
+            message,
+            cwe: element
+                .attribute("cwe")
+                .and_then(|cwe| cwe.trim().parse().ok())
+                .filter(|&cwe| cwe != 0),
+            // The location of an issue in the version 1 of the format
+            locations: Location::new(element).into_iter().collect(),
+        }
+    }
+
+    /// The title of the annotation (e.g., `error: nullPointer`).
+    fn title(&self) -> String {
+        format!("{}: {}", self.severity, self.id)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for CppcheckIssue {
+    #[inline]
+    fn format(&self) -> String {
+        let mut message = match &self.verbose {
+            Some(verbose) => format!("{}\n{verbose}", self.message),
+            None => self.message.clone(),
+        };
+        if let Some(cwe) = self.cwe {
+            message = format!("{message} (CWE-{cwe})");
+        }
+        let (primary, related) = match self.locations.split_first() {
+            Some((first, rest)) => (Some((first.file.as_str(), first.line, first.column)), rest),
+            None => (None, &[][..]),
+        };
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            message,
+            primary,
+            Some(&self.title()),
+        )];
+        lines.extend(related.iter().map(|location| {
+            P::annotate(
+                Severity::Notice,
+                location.info.as_deref().unwrap_or("Related location"),
+                Some((location.file.as_str(), location.line, location.column)),
+                Some("note"),
+            )
+        }));
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for CppcheckIssue {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.severity {
+            CppcheckSeverity::Error => Severity::Error,
+            CppcheckSeverity::Warning => Severity::Warning,
+            CppcheckSeverity::Style
+            | CppcheckSeverity::Performance
+            | CppcheckSeverity::Portability => Severity::Notice,
+            CppcheckSeverity::Information => Severity::Debug,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.locations
+            .first()
+            .map(|location| location.file.as_str())
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// The state of the parser, apart from the reading of the XML.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The issue being read, if any.
+    issue: Option<CppcheckIssue>,
+}
+
+impl Reader {
+    /// Report the issue being read, if any.
+    fn flush(&mut self) -> Option<CppcheckMessage> {
+        let issue = self.issue.take()?;
+        Some(CppcheckMessage::Issue(Box::new(issue)))
+    }
+
+    /// Handle an event of the results.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the event.
+    fn handle(&mut self, event: Event, text: &mut TextLines) -> Vec<CppcheckMessage> {
+        match event {
+            Event::Start(element) if element.name == "error" => {
+                let mut results: Vec<_> = self.flush().into_iter().collect();
+                self.issue = Some(CppcheckIssue::new(&element));
+                if element.empty {
+                    results.extend(self.flush());
+                }
+                results
+            }
+            Event::Start(element) if element.name == "location" => {
+                if let Some(issue) = &mut self.issue {
+                    issue.locations.extend(Location::new(&element));
+                }
+                Vec::new()
+            }
+            Event::End(name) if name == "error" => self.flush().into_iter().collect(),
+            Event::Text(content) if self.issue.is_none() => content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| text.handle(line.as_bytes()))
+                .map(CppcheckMessage::TextOutput)
+                .collect(),
+            Event::Start(_) | Event::End(_) | Event::Text(_) => Vec::new(),
+        }
+    }
+}
+
+/// Tool implementation for parsing the XML results of cppcheck.
+#[derive(Debug, Clone, Default)]
+pub struct Cppcheck {
+    /// Reader of the events of the results.
+    xml: XmlReader,
+    /// The state of the parser.
+    reader: Reader,
+    /// Handling of text outside of the issues.
+    text: TextLines,
+}
+
+impl Cppcheck {
+    /// A problem matcher for the XML results of cppcheck printed to the log.
+    ///
+    /// An `<error>` of severity `error` or `warning` is matched with the
+    /// `<location>` on the next line, as cppcheck writes them, with their
+    /// attributes in the order in which cppcheck writes them. Issues of other
+    /// severities are not matched.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-cppcheck",
+            vec![
+                Pattern::new(r#"^\s*<error id="([^"]+)" severity="(error|warning)" msg="([^"]*)""#)
+                    .code(1)
+                    .severity(2)
+                    .message(3),
+                Pattern::new(r#"^\s*<location file="([^"]+)" line="(\d+)"(?: column="(\d+)")?"#)
+                    .file(1)
+                    .line(2)
+                    .column(3),
+            ],
+        )
+    }
+}
+
+impl Detect for Cppcheck {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        memchr::memmem::find(sample, b"<cppcheck ").map(|_| Self::default())
+    }
+}
+
+impl Tool for Cppcheck {
+    type Message = CppcheckMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "cppcheck"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.xml.push(buf);
+
+        let mut results = Vec::new();
+        while let Some(event) = self.xml.next_event() {
+            results.extend(self.reader.handle(event, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let mut results = Vec::new();
+        if let Some(event) = self.xml.finish() {
+            results.extend(self.reader.handle(event, &mut self.text));
+        }
+
+        // An issue cut off before its end is reported as far as it was read
+        results.extend(self.reader.flush());
+        results.extend(self.text.finish().map(CppcheckMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Cppcheck
+where
+    CppcheckMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Cppcheck, CppcheckMessage, CppcheckSeverity};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{Detect as _, Junit, Passthrough, Tool as _};
+
+    const RESULTS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<results version="2">
+    <cppcheck version="2.10"/>
+    <errors>
+Checking src/main.c ...
+        <error id="nullPointer" severity="error" msg="Null pointer dereference: p" verbose="Null pointer dereference: p" cwe="476" file0="src/main.c">
+            <location file="src/main.c" line="6" column="13" info="Null pointer dereference"/>
+            <location file="src/main.c" line="5" column="14" info="Assignment &apos;p=NULL&apos;, assigned value is 0"/>
+            <symbol>p</symbol>
+        </error>
+        <error id="passedByValue" severity="performance" msg="Function parameter &apos;name&apos; should be passed by const reference." verbose="Parameter &apos;name&apos; is passed by value." cwe="398" file0="src/shapes.cpp">
+            <location file="src/shapes.cpp" line="3" column="31"/>
+        </error>
+        <error id="missingIncludeSystem" severity="information" msg="Cppcheck cannot find all the include files" verbose="Cppcheck cannot find all the include files"/>
+    </errors>
+</results>
+"#;
+
+    /// Parse the whole results, fed in chunks of the given size.
+    fn parse(results: &str, chunk: usize) -> Vec<CppcheckMessage> {
+        let mut tool = Cppcheck::default();
+        let mut messages = Vec::new();
+        for bytes in results.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole results, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut Cppcheck, results: &str) -> String
+    where
+        CppcheckMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(results.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <CppcheckMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(&mut Cppcheck::default(), RESULTS), @"
+        ::error file=src/main.c,line=6,col=13,title=error%3A nullPointer::Null pointer dereference: p (CWE-476)
+        ::notice file=src/main.c,line=5,col=14,title=note::Assignment 'p=NULL', assigned value is 0
+        ::notice file=src/shapes.cpp,line=3,col=31,title=performance%3A passedByValue::Function parameter 'name' should be passed by const reference.%0AParameter 'name' is passed by value. (CWE-398)
+        ::debug::information: missingIncludeSystem: Cppcheck cannot find all the include files
+        ");
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(&mut Cppcheck::default(), RESULTS), @r"
+        src/main.c:6:13: error:   error: nullPointer: Null pointer dereference: p (CWE-476)
+        src/main.c:5:14: notice:  note: Assignment 'p=NULL', assigned value is 0
+        src/shapes.cpp:3:31: notice:  performance: passedByValue: Function parameter 'name' should be passed by const reference.\nParameter 'name' is passed by value. (CWE-398)
+        debug:   information: missingIncludeSystem: Cppcheck cannot find all the include files
+        ");
+    }
+
+    #[test]
+    fn chunked() {
+        let whole = parse(RESULTS, RESULTS.len());
+        for chunk in [1, 5, 64] {
+            assert_eq!(parse(RESULTS, chunk), whole, "chunks of {chunk}");
+        }
+    }
+
+    #[test]
+    fn issues() {
+        let issues: Vec<_> = parse(RESULTS, RESULTS.len())
+            .into_iter()
+            .filter_map(|msg| match msg {
+                CppcheckMessage::Issue(issue) => Some(issue),
+                CppcheckMessage::TextOutput(_) => None,
+            })
+            .collect();
+        let [null, by_value, include] = issues.as_slice() else {
+            panic!("expected three issues");
+        };
+        assert_eq!(null.severity, CppcheckSeverity::Error);
+        assert_eq!(null.code(), Some("nullPointer"));
+        assert_eq!(null.verbose, None);
+        assert_eq!(null.cwe, Some(476));
+        assert_eq!(null.locations.len(), 2);
+        assert_eq!(
+            null.locations
+                .get(1)
+                .and_then(|location| location.info.as_deref()),
+            Some("Assignment 'p=NULL', assigned value is 0")
+        );
+        assert_eq!(by_value.severity, CppcheckSeverity::Performance);
+        assert_eq!(by_value.file(), Some("src/shapes.cpp"));
+        assert_eq!(include.severity, CppcheckSeverity::Information);
+        assert_eq!(include.locations, []);
+    }
+
+    #[test]
+    fn version1() {
+        let results = "<results>\n<error file=\"src/main.c\" line=\"6\" id=\"nullPointer\" severity=\"error\" msg=\"Null pointer dereference\"/>\n</results>\n";
+        insta::assert_snapshot!(format::<Plain>(&mut Cppcheck::default(), results), @"src/main.c:6: error:   error: nullPointer: Null pointer dereference");
+    }
+
+    #[test]
+    fn passthrough() {
+        let mut tool = Cppcheck::default();
+        tool.set_passthrough(Passthrough::Raw);
+        insta::assert_snapshot!(format::<Plain>(&mut tool, RESULTS), @r"
+        Checking src/main.c ...
+        src/main.c:6:13: error:   error: nullPointer: Null pointer dereference: p (CWE-476)
+        src/main.c:5:14: notice:  note: Assignment 'p=NULL', assigned value is 0
+        src/shapes.cpp:3:31: notice:  performance: passedByValue: Function parameter 'name' should be passed by const reference.\nParameter 'name' is passed by value. (CWE-398)
+        debug:   information: missingIncludeSystem: Cppcheck cannot find all the include files
+        ");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Cppcheck::detect(RESULTS.as_bytes()).is_some());
+        assert!(Cppcheck::detect(b"Checking src/main.c ...\n").is_none());
+        assert!(Junit::detect(RESULTS.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Cppcheck::problem_matcher();
+        let [error, location] = matcher.pattern.as_slice() else {
+            panic!("expected two patterns");
+        };
+        let issue = regex::Regex::new(&error.regexp).expect("invalid regexp");
+        let located = regex::Regex::new(&location.regexp).expect("invalid regexp");
+
+        let captures = issue
+            .captures(r#"        <error id="nullPointer" severity="error" msg="Null pointer dereference: p" cwe="476">"#)
+            .expect("error not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("nullPointer"));
+        assert!(!issue.is_match(r#"<error id="unreadVariable" severity="style" msg="x">"#));
+        let span = located
+            .captures(r#"            <location file="src/main.c" line="6" column="13" info="Null pointer dereference"/>"#)
+            .expect("location not matched");
+        assert_eq!(span.get(1).map(|m| m.as_str()), Some("src/main.c"));
+        assert_eq!(span.get(3).map(|m| m.as_str()), Some("13"));
+    }
+}
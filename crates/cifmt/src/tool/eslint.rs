@@ -0,0 +1,697 @@
+//! ESLint JSON report.
+//!
+//! Support for parsing the report which ESLint prints with `--format json`,
+//! so that the problems found in JavaScript and TypeScript code are annotated
+//! at their location. The report is a JSON array of the files linted, each
+//! with its problems, printed on a single line (or over many lines, once
+//! reformatted by another tool), which is parsed once its closing bracket is
+//! read; a report cut off before its end is reported as truncated.
+//!
+//! Each problem is annotated from the line and column at which it starts to
+//! those at which it ends, where the platform supports it (e.g., GitHub
+//! Actions), titled after its rule (e.g., `no-unused-vars`), which is also
+//! its code. Problems of the `error` severity (`2`) are errors, and those of
+//! the `warn` severity (`1`) warnings. Fatal problems, which are not reported
+//! by a rule (e.g., a parsing error), are titled as a scan error, and
+//! problems without a location (e.g., a file which is ignored) are
+//! annotated without one, naming the file in their message.
+//!
+//! Any text outside of the report (e.g., the output of npm, when ESLint is
+//! run through a script) is passed through as plain text output.
+
+use core::mem;
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the JSON report of ESLint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EslintMessage {
+    /// A problem found by ESLint.
+    Problem(Box<EslintProblem>),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// A report cut off before its end.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of the report.
+    TextOutput(TextOutput),
+}
+
+/// The severity of a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "u8")]
+#[non_exhaustive]
+pub enum EslintSeverity {
+    /// A problem of a rule configured as `warn` (`1`), or of any severity
+    /// which is not known.
+    Warning,
+    /// A problem of a rule configured as `error` (`2`), or a fatal problem.
+    Error,
+}
+
+impl From<u8> for EslintSeverity {
+    #[inline]
+    fn from(severity: u8) -> Self {
+        if severity >= 2 {
+            Self::Error
+        } else {
+            Self::Warning
+        }
+    }
+}
+
+/// A problem found by ESLint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EslintProblem {
+    /// The rule which reported the problem (e.g., `no-unused-vars`), if any.
+    pub rule: Option<String>,
+    /// The severity of the problem.
+    pub severity: EslintSeverity,
+    /// Whether the problem prevented the file from being linted (e.g., a
+    /// parsing error).
+    pub fatal: bool,
+    /// The message of the problem.
+    pub message: String,
+    /// The path of the file linted.
+    pub file: String,
+    /// The line and column at which the problem starts, if known.
+    pub start: Option<(u32, u32)>,
+    /// The line and column at which the problem ends, if known.
+    pub end: Option<(u32, u32)>,
+}
+
+/// The problem is annotated over the columns which it spans, where the
+/// platform supports it.
+impl<P: Platform> CiMessage<P> for EslintProblem {
+    #[inline]
+    fn format(&self) -> String {
+        let title = match &self.rule {
+            Some(rule) => Some(rule.as_str()),
+            None if self.fatal => Some(Title::ScanError.text()),
+            None => None,
+        };
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => P::annotate_span(
+                self.severity(),
+                &self.message,
+                &self.file,
+                (start, end),
+                title,
+            ),
+            (Some((line, column)), None) => P::annotate(
+                self.severity(),
+                &self.message,
+                Some((&self.file, line, Some(column))),
+                title,
+            ),
+            (None, _) => P::annotate(
+                self.severity(),
+                format!("{}: {}", self.file, self.message),
+                None,
+                title,
+            ),
+        }
+    }
+}
+
+impl Classify for EslintProblem {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.severity {
+            EslintSeverity::Error => Severity::Error,
+            EslintSeverity::Warning => Severity::Warning,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for EslintMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Problem(msg) => <EslintProblem as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for EslintMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Problem(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Problem(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Problem(msg) => msg.code(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Problem(msg) => msg.file(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Problem(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for EslintMessage {}
+
+impl Captured for EslintMessage {}
+
+/// A problem listed for a file in the report.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonProblem {
+    /// The rule which reported the problem.
+    #[serde(default)]
+    rule_id: Option<String>,
+    /// The severity of the problem.
+    severity: EslintSeverity,
+    /// Whether the problem is fatal.
+    #[serde(default)]
+    fatal: bool,
+    /// The message of the problem.
+    message: String,
+    /// The line at which the problem starts.
+    #[serde(default)]
+    line: Option<u32>,
+    /// The column at which the problem starts.
+    #[serde(default)]
+    column: Option<u32>,
+    /// The line at which the problem ends.
+    #[serde(default)]
+    end_line: Option<u32>,
+    /// The column at which the problem ends.
+    #[serde(default)]
+    end_column: Option<u32>,
+}
+
+/// A file listed in the report.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFile {
+    /// The path of the file.
+    file_path: String,
+    /// The problems found in the file.
+    messages: Vec<JsonProblem>,
+}
+
+impl JsonFile {
+    /// The problems found in the file, in the order in which they are
+    /// listed.
+    fn into_messages(self) -> impl Iterator<Item = EslintMessage> {
+        let file = self.file_path;
+        self.messages.into_iter().map(move |problem| {
+            // Problems without a location are reported at line 0 by older
+            // versions
+            let start = problem
+                .line
+                .filter(|&line| line > 0)
+                .map(|line| (line, problem.column.unwrap_or(1)));
+            let end = start.and(problem.end_line.zip(problem.end_column));
+            EslintMessage::Problem(Box::new(EslintProblem {
+                rule: problem.rule_id,
+                severity: problem.severity,
+                fatal: problem.fatal,
+                message: problem.message,
+                file: file.clone(),
+                start,
+                end,
+            }))
+        })
+    }
+}
+
+/// The report being read.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The lines of the report read so far.
+    document: Vec<u8>,
+    /// The line number and offset of the first line of the report, if one is
+    /// being read.
+    start: Option<(usize, u64)>,
+}
+
+impl Reader {
+    /// Parse the report read so far.
+    fn parse(&mut self) -> Vec<Result<EslintMessage, serde_json::Error>> {
+        self.start = None;
+        match serde_json::from_slice::<Vec<JsonFile>>(&mem::take(&mut self.document)) {
+            Ok(files) => files
+                .into_iter()
+                .flat_map(JsonFile::into_messages)
+                .map(Ok)
+                .collect(),
+            Err(e) => vec![Err(e)],
+        }
+    }
+
+    /// Parse a single line of the output of ESLint.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the line.
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the report.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        position: (usize, u64),
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<EslintMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                // The report cannot be parsed without the whole line
+                self.start = None;
+                self.document.clear();
+                let mut results =
+                    vec![Ok(EslintMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(position, Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        if self.start.is_none() {
+            if line.first() != Some(&b'[') {
+                return text
+                    .handle(line)
+                    .map(|output| Ok(EslintMessage::TextOutput(output)))
+                    .into_iter()
+                    .collect();
+            }
+            self.start = Some(position);
+        }
+        self.document.extend_from_slice(line);
+        self.document.push(b'\n');
+
+        // ESLint prints the report on a single line, which may also be
+        // reformatted to close the report on a line of its own
+        let single = self.document.len() == line.len().saturating_add(1);
+        if line == b"]" || (single && line.ends_with(b"]")) {
+            let results = self.parse();
+            if single
+                && let [Err(e)] = results.as_slice()
+                && e.is_eof()
+            {
+                // The report continues on the next lines
+                self.start = Some(position);
+                self.document.extend_from_slice(line);
+                self.document.push(b'\n');
+                return Vec::new();
+            }
+            return results;
+        }
+        Vec::new()
+    }
+
+    /// Report the report being read at the end of the output, if any.
+    fn finish(&mut self) -> Vec<Result<EslintMessage, serde_json::Error>> {
+        let Some((line_number, offset)) = self.start else {
+            return Vec::new();
+        };
+        // The last line may end within a string, which cannot contain the
+        // newline which ends it
+        if self.document.last() == Some(&b'\n') {
+            self.document.pop();
+        }
+        let length = self.document.len();
+        self.parse()
+            .into_iter()
+            .map(|result| {
+                result.or_else(|error| {
+                    if error.is_eof() {
+                        Ok(EslintMessage::TruncatedLine(TruncatedLine {
+                            line_number,
+                            offset,
+                            length,
+                            error: error.to_string(),
+                        }))
+                    } else {
+                        Err(error)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Tool implementation for parsing the JSON report of ESLint.
+#[derive(Debug, Clone, Default)]
+pub struct Eslint {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of the report.
+    text: TextLines,
+    /// The report being read.
+    reader: Reader,
+}
+
+impl Detect for Eslint {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        // The fields of each file, whether the report is printed on a single
+        // line or reformatted
+        let field = |name: &[u8]| memchr::memmem::find(sample, name).is_some();
+        (field(b"\"filePath\":") && field(b"\"messages\":") && field(b"\"errorCount\":"))
+            .then(Self::default)
+    }
+}
+
+impl Eslint {
+    /// A problem matcher for the default (`stylish`) output of ESLint.
+    ///
+    /// The path of each file with problems is printed on a line of its own,
+    /// followed by a line for each problem (e.g., `  1:7  error  'unused' is
+    /// assigned a value but never used  no-unused-vars`), which gives its
+    /// location, severity, message and rule.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-eslint",
+            vec![
+                Pattern::new(r"^([^\s].*)$").file(1),
+                Pattern::new(r"^\s+(\d+):(\d+)\s+(error|warning)\s+(.+?)(?:\s\s+([\w@/-]+))?$")
+                    .line(1)
+                    .column(2)
+                    .severity(3)
+                    .message(4)
+                    .code(5)
+                    .repeated(),
+            ],
+        )
+    }
+}
+
+impl Tool for Eslint {
+    type Message = EslintMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "eslint"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        loop {
+            let position = self.framer.position();
+            let Some(line) = self.framer.next_line() else {
+                break;
+            };
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+
+        results.extend(self.reader.finish());
+        results.extend(
+            self.text
+                .finish()
+                .map(|output| Ok(EslintMessage::TextOutput(output))),
+        );
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Eslint
+where
+    EslintMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Eslint, EslintMessage, EslintSeverity};
+    use crate::ci::{AzureDevOps, GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{Detect as _, Psalm, Tool as _};
+
+    const REPORT: &str = r#"[{"filePath":"/home/user/app/src/broken.js","messages":[{"ruleId":null,"fatal":true,"severity":2,"message":"Parsing error: Unexpected token )","line":2,"column":17}],"suppressedMessages":[],"errorCount":1,"fatalErrorCount":1,"warningCount":0,"fixableErrorCount":0,"fixableWarningCount":0,"source":"export function add(a, b) {\n  return (a + b));\n}\n","usedDeprecatedRules":[]},{"filePath":"/home/user/app/src/index.js","messages":[{"ruleId":"no-unused-vars","severity":2,"message":"'unused' is assigned a value but never used.","line":1,"column":7,"nodeType":"Identifier","messageId":"unusedVar","endLine":1,"endColumn":13},{"ruleId":"no-console","severity":1,"message":"Unexpected console statement.","line":7,"column":3,"nodeType":"MemberExpression","messageId":"unexpected","endLine":7,"endColumn":14}],"suppressedMessages":[],"errorCount":1,"fatalErrorCount":0,"warningCount":1,"fixableErrorCount":0,"fixableWarningCount":0,"source":"const unused = 1;\n","usedDeprecatedRules":[]},{"filePath":"/home/user/app/vendor/lib.js","messages":[{"fatal":false,"severity":1,"message":"File ignored because of a matching ignore pattern. Use \"--no-ignore\" to override."}],"suppressedMessages":[],"errorCount":0,"fatalErrorCount":0,"warningCount":1,"fixableErrorCount":0,"fixableWarningCount":0,"usedDeprecatedRules":[]}]
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<EslintMessage> {
+        let mut tool = Eslint::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        EslintMessage: CiMessage<P>,
+    {
+        let mut tool = Eslint::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <EslintMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(REPORT), @r#"
+        ::error file=/home/user/app/src/broken.js,line=2,col=17,title=Scan Error::Parsing error: Unexpected token )
+        ::error file=/home/user/app/src/index.js,line=1,col=7,endLine=1,endColumn=13,title=no-unused-vars::'unused' is assigned a value but never used.
+        ::warning file=/home/user/app/src/index.js,line=7,col=3,endLine=7,endColumn=14,title=no-console::Unexpected console statement.
+        ::warning ::/home/user/app/vendor/lib.js: File ignored because of a matching ignore pattern. Use "--no-ignore" to override.
+        "#);
+    }
+
+    #[test]
+    fn azure() {
+        insta::assert_snapshot!(format::<AzureDevOps>(REPORT), @r#"
+        ##vso[task.logissue type=error;sourcepath=/home/user/app/src/broken.js;linenumber=2;columnnumber=17]Scan Error: Parsing error: Unexpected token )
+        ##vso[task.logissue type=error;sourcepath=/home/user/app/src/index.js;linenumber=1;columnnumber=7]no-unused-vars: 'unused' is assigned a value but never used.
+        ##vso[task.logissue type=warning;sourcepath=/home/user/app/src/index.js;linenumber=7;columnnumber=3]no-console: Unexpected console statement.
+        ##vso[task.logissue type=warning]/home/user/app/vendor/lib.js: File ignored because of a matching ignore pattern. Use "--no-ignore" to override.
+        "#);
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(REPORT), @r#"
+        /home/user/app/src/broken.js:2:17: error:   Scan Error: Parsing error: Unexpected token )
+        /home/user/app/src/index.js:1:7: error:   no-unused-vars: 'unused' is assigned a value but never used.
+        /home/user/app/src/index.js:7:3: warning: no-console: Unexpected console statement.
+        warning: /home/user/app/vendor/lib.js: File ignored because of a matching ignore pattern. Use "--no-ignore" to override.
+        "#);
+    }
+
+    #[test]
+    fn problems() {
+        let messages = parse(REPORT, 7);
+        assert_eq!(messages, parse(REPORT, REPORT.len()));
+        let [
+            EslintMessage::Problem(fatal),
+            EslintMessage::Problem(unused),
+            EslintMessage::Problem(console),
+            EslintMessage::Problem(ignored),
+        ] = messages.as_slice()
+        else {
+            panic!("expected four problems");
+        };
+        assert!(fatal.fatal);
+        assert_eq!(fatal.code(), None);
+        assert_eq!((fatal.start, fatal.end), (Some((2, 17)), None));
+        assert_eq!(unused.severity, EslintSeverity::Error);
+        assert_eq!(unused.code(), Some("no-unused-vars"));
+        assert_eq!(unused.end, Some((1, 13)));
+        assert_eq!(console.severity, EslintSeverity::Warning);
+        assert_eq!(ignored.start, None);
+    }
+
+    #[test]
+    fn reformatted() {
+        let output = "> lint\n> eslint -f json .\n\n[\n  {\"filePath\": \"index.js\", \"messages\": [{\"ruleId\": \"eqeqeq\", \"severity\": 2,\n   \"message\": \"Expected '===' and instead saw '=='.\", \"line\": 4, \"column\": 12, \"endLine\": 4, \"endColumn\": 14}], \"errorCount\": 1}\n]\n";
+        insta::assert_snapshot!(format::<Plain>(output), @"index.js:4:12: error:   eqeqeq: Expected '===' and instead saw '=='.");
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = REPORT.get(..200).expect("valid index");
+        insta::assert_snapshot!(format::<Plain>(cut), @"warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 200 bytes of an incomplete message: EOF while parsing a string at line 1 column 200");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Eslint::detect(REPORT.as_bytes()).is_some());
+        assert!(Eslint::detect(b"[]\n").is_none());
+        assert!(Psalm::detect(REPORT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Eslint::problem_matcher();
+        let [file, problem] = matcher.pattern.as_slice() else {
+            panic!("expected two patterns");
+        };
+        assert!(problem.repeat);
+        let file_regex = regex::Regex::new(&file.regexp).expect("invalid regexp");
+        let regex = regex::Regex::new(&problem.regexp).expect("invalid regexp");
+
+        assert!(file_regex.is_match("/home/user/app/src/index.js"));
+        let captures = regex
+            .captures("  1:7  error  'unused' is assigned a value but never used  no-unused-vars")
+            .expect("problem not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("1"));
+        assert_eq!(captures.get(3).map(|m| m.as_str()), Some("error"));
+        assert_eq!(
+            captures.get(4).map(|m| m.as_str()),
+            Some("'unused' is assigned a value but never used")
+        );
+        assert_eq!(captures.get(5).map(|m| m.as_str()), Some("no-unused-vars"));
+        assert!(!regex.is_match("✖ 2 problems (1 error, 1 warning)"));
+    }
+}
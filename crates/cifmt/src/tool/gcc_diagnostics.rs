@@ -0,0 +1,875 @@
+//! GCC and Clang diagnostics.
+//!
+//! Support for parsing the diagnostics which GCC and Clang print for C and
+//! C++ (e.g., `src/main.c:6:5: error: too many arguments to function
+//! 'greet'`), so that the builds of C and C++ projects (e.g., with `make` or
+//! `cmake --build`) are annotated without any extra tooling.
+//!
+//! Each error or warning is annotated at its location, with the option which
+//! enables it (e.g., `[-Wunused-variable]`) as its code. The notes which
+//! follow it (e.g., `note: declared here`) are annotated at their own
+//! locations, and a note continued by the next one (e.g., `note:   candidate
+//! expects 1 argument, 3 provided`, as GCC prints under each candidate of an
+//! overloaded function) is read as one. The context which the compiler
+//! prints before a diagnostic (e.g., `In file included from src/main.c:1:`,
+//! or `src/util.h: In function 'helper':`) is appended to its message, as
+//! the annotation of a diagnostic in a header does not otherwise tell which
+//! file included it. The source excerpt under a diagnostic is kept as its
+//! snippet.
+//!
+//! The summaries of Clang (e.g., `1 warning and 2 errors generated.`) and of
+//! GCC (`compilation terminated.`) are skipped, and any other output (e.g.,
+//! that of `make`) is passed through as plain text output.
+
+use core::{convert::Infallible, fmt, iter, mem};
+use std::io::BufRead as _;
+
+use crate::{
+    ansi,
+    capture::Captured,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+    },
+};
+
+/// The markers between the location and the message of a diagnostic, with
+/// the level which they introduce.
+const LEVELS: [(&str, GccLevel); 4] = [
+    (": fatal error: ", GccLevel::FatalError),
+    (": error: ", GccLevel::Error),
+    (": warning: ", GccLevel::Warning),
+    (": note: ", GccLevel::Note),
+];
+
+/// A message parsed from the diagnostics of GCC or Clang.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GccMessage {
+    /// A diagnostic of the compiler.
+    Diagnostic(Box<GccDiagnostic>),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// Output which is not part of a diagnostic.
+    TextOutput(TextOutput),
+}
+
+impl<P: Platform> CiMessage<P> for GccMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => <GccDiagnostic as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for GccMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Diagnostic(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Diagnostic(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.code(),
+            Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn snippet(&self) -> Option<String> {
+        match self {
+            Self::Diagnostic(msg) => msg.snippet(),
+            Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.file(),
+            Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for GccMessage {}
+
+impl Captured for GccMessage {}
+
+/// The level of a diagnostic of GCC or Clang.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GccLevel {
+    /// An error after which the compiler stopped (e.g., a missing header).
+    FatalError,
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// A note, which adds to the diagnostic before it.
+    Note,
+}
+
+impl fmt::Display for GccLevel {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FatalError => "fatal error",
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        })
+    }
+}
+
+/// The location of a diagnostic in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Location {
+    /// The file, as given to the compiler.
+    pub file: String,
+    /// The line.
+    pub line: u32,
+    /// The column, if printed.
+    pub column: Option<u32>,
+}
+
+/// A diagnostic of GCC or Clang.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GccDiagnostic {
+    /// The level of the diagnostic.
+    pub level: GccLevel,
+    /// The message of the diagnostic, without the option which enables it.
+    pub message: String,
+    /// The option which enables the diagnostic (e.g., `-Wunused-variable`),
+    /// if any.
+    pub option: Option<String>,
+    /// The location of the diagnostic, if any.
+    pub location: Option<Location>,
+    /// The program which reported a diagnostic without a location (e.g.,
+    /// `collect2` for the failure of the linker).
+    pub program: Option<String>,
+    /// The context printed before the diagnostic (e.g., `In file included
+    /// from src/main.c:1`), one line per entry.
+    pub context: Vec<String>,
+    /// The source excerpt printed under the diagnostic, one line per entry.
+    pub excerpt: Vec<String>,
+    /// The notes which follow the diagnostic.
+    pub notes: Vec<GccDiagnostic>,
+}
+
+impl GccDiagnostic {
+    /// Create a diagnostic from its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header of the diagnostic.
+    /// * `lines` - The lines of context printed before the diagnostic,
+    ///   which are kept without their indentation and trailing punctuation.
+    fn new(header: &Header<'_>, lines: &[String]) -> Self {
+        let context = lines
+            .iter()
+            .map(|line| line.trim().trim_end_matches([':', ',']).to_owned())
+            .collect();
+        Self {
+            level: header.level,
+            message: header.message.to_owned(),
+            option: header.option.map(str::to_owned),
+            location: header.location.clone(),
+            program: header.program.map(str::to_owned),
+            context,
+            excerpt: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Read a note of the diagnostic, which continues the previous note if
+    /// its message is indented.
+    fn note(&mut self, header: &Header<'_>) {
+        if header.message.starts_with(char::is_whitespace)
+            && let Some(last) = self.notes.last_mut()
+        {
+            last.message = format!("{}\n{}", last.message, header.message.trim_start());
+        } else {
+            self.notes.push(Self::new(header, &[]));
+        }
+    }
+
+    /// Read a line of the source excerpt, which is only kept for the
+    /// diagnostic itself, not for its notes.
+    fn excerpt(&mut self, line: &str) {
+        if self.notes.is_empty() {
+            self.excerpt.push(line.to_owned());
+        }
+    }
+
+    /// The title of the annotation: the option enabling the diagnostic (e.g.,
+    /// `-Wunused-variable`), or otherwise the program reporting it, if any.
+    ///
+    /// The level is left out, as the severity of the annotation conveys it.
+    fn title(&self) -> Option<&str> {
+        self.option.as_deref().or(self.program.as_deref())
+    }
+
+    /// The file, line and column at which the diagnostic is annotated, if
+    /// any.
+    fn annotated_location(&self) -> Option<(&str, u32, Option<u32>)> {
+        self.location
+            .as_ref()
+            .map(|location| (location.file.as_str(), location.line, location.column))
+    }
+}
+
+impl<P: Platform> CiMessage<P> for GccDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        let message = iter::once(&self.message)
+            .chain(&self.context)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            message,
+            self.annotated_location(),
+            self.title(),
+        )];
+        lines.extend(self.notes.iter().map(|note| {
+            P::annotate(
+                note.severity(),
+                &note.message,
+                note.annotated_location(),
+                note.title(),
+            )
+        }));
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for GccDiagnostic {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.level {
+            GccLevel::FatalError | GccLevel::Error => Severity::Error,
+            GccLevel::Warning => Severity::Warning,
+            GccLevel::Note => Severity::Notice,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        self.option.as_deref()
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.location
+            .as_ref()
+            .map(|location| location.file.as_str())
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+
+    #[inline]
+    fn snippet(&self) -> Option<String> {
+        (!self.excerpt.is_empty()).then(|| format!("{}\n", self.excerpt.join("\n")))
+    }
+}
+
+/// The header of a diagnostic (e.g., `src/main.c:6:5: error: too many
+/// arguments to function 'greet'`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Header<'a> {
+    /// The level of the diagnostic.
+    level: GccLevel,
+    /// The location of the diagnostic, if any.
+    location: Option<Location>,
+    /// The program which reported the diagnostic, if it has no location.
+    program: Option<&'a str>,
+    /// The message of the diagnostic.
+    message: &'a str,
+    /// The option which enables the diagnostic, if any.
+    option: Option<&'a str>,
+}
+
+impl<'a> Header<'a> {
+    /// Parse the header of a diagnostic.
+    ///
+    /// # Returns
+    ///
+    /// The header, or `None` if the line is not the header of a diagnostic.
+    fn parse(line: &'a str) -> Option<Self> {
+        let (start, marker, level) = LEVELS
+            .iter()
+            .filter_map(|&(marker, level)| Some((line.find(marker)?, marker, level)))
+            .min_by_key(|&(start, _, _)| start)?;
+        let head = line.get(..start)?;
+        let text = line.get(start.saturating_add(marker.len())..)?;
+        let location = parse_location(head);
+        // A diagnostic without a location is reported by a program (e.g.,
+        // `collect2: error: ld returned 1 exit status`)
+        let program = if location.is_some() {
+            None
+        } else if head.is_empty() || head.contains(char::is_whitespace) {
+            return None;
+        } else {
+            Some(head)
+        };
+        let (message, option) = split_option(text);
+        Some(Self {
+            level,
+            location,
+            program,
+            message,
+            option,
+        })
+    }
+}
+
+/// Parse a location as printed by GCC or Clang (e.g., `src/main.c:6:5`, or
+/// `src/main.c:6` without a column).
+///
+/// # Returns
+///
+/// The location, or `None` if the text is not a location.
+fn parse_location(head: &str) -> Option<Location> {
+    let (rest, last) = head.rsplit_once(':')?;
+    let number = last.parse().ok()?;
+    if let Some((file, line)) = rest.rsplit_once(':')
+        && let Ok(parsed) = line.parse()
+        && !file.is_empty()
+    {
+        return Some(Location {
+            file: file.to_owned(),
+            line: parsed,
+            column: Some(number),
+        });
+    }
+    (!rest.is_empty()).then(|| Location {
+        file: rest.to_owned(),
+        line: number,
+        column: None,
+    })
+}
+
+/// Split a message from the option which enables it (e.g., `unused variable
+/// 'x' [-Wunused-variable]`).
+fn split_option(text: &str) -> (&str, Option<&str>) {
+    text.strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once(" ["))
+        .filter(|(_, option)| option.starts_with('-'))
+        .map_or((text, None), |(message, option)| (message, Some(option)))
+}
+
+/// Whether a line is a summary of the compiler (e.g., `2 errors generated.`).
+fn is_summary(line: &str) -> bool {
+    line == "compilation terminated."
+        || (line.ends_with(" generated.") && (line.contains(" warning") || line.contains(" error")))
+}
+
+/// The state of the parser, apart from the framing of its lines.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The diagnostic being read.
+    block: Option<GccDiagnostic>,
+    /// The context of the next diagnostic, one line per entry.
+    context: Vec<String>,
+    /// Whether a diagnostic was read since the last line of context, after
+    /// which a line of context starts a new context.
+    used: bool,
+}
+
+impl Reader {
+    /// Report the diagnostic being read, if any.
+    fn flush(&mut self) -> Option<GccMessage> {
+        let block = self.block.take()?;
+        Some(GccMessage::Diagnostic(Box::new(block)))
+    }
+
+    /// Drop the context, which is passed through as plain text output if no
+    /// diagnostic followed it.
+    fn release(&mut self, text: &mut TextLines) -> Vec<GccMessage> {
+        let context = mem::take(&mut self.context);
+        if mem::take(&mut self.used) {
+            return Vec::new();
+        }
+        context
+            .iter()
+            .filter_map(|line| text.handle(line.as_bytes()))
+            .map(GccMessage::TextOutput)
+            .collect()
+    }
+
+    /// Whether a line is part of the context of the next diagnostic (e.g.,
+    /// `In file included from src/main.c:1:`, `src/util.h: In function
+    /// 'helper':`, or `src/a.cpp:10:6:   required from here`).
+    fn is_context(&self, line: &str) -> bool {
+        let trimmed = line.trim();
+        let ended = trimmed.trim_end_matches([':', ',']);
+        let included = || {
+            self.context.last().is_some_and(|last| {
+                last.starts_with("In file included from ") || last.trim_start().starts_with("from ")
+            })
+        };
+        if line.starts_with("In file included from ") {
+            trimmed.len() != ended.len()
+        } else if line.starts_with(char::is_whitespace) {
+            trimmed.starts_with("from ") && trimmed.len() != ended.len() && included()
+        } else if let Some((head, _)) = line.split_once(":   ") {
+            parse_location(head).is_some()
+        } else if let Some((_, scope)) = line.split_once(": ") {
+            (scope.starts_with("In ") || scope.starts_with("At ")) && line.ends_with(':')
+        } else {
+            false
+        }
+    }
+
+    /// Parse a single line of the output.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<GccMessage> {
+        let raw = match next {
+            Line::Complete(raw) => raw,
+            Line::Overlong(raw) => {
+                let mut results: Vec<_> = self.flush().into_iter().collect();
+                results.push(GccMessage::OverlongLine(OverlongLine { max_length }));
+                results.extend(self.parse_line(Line::Complete(raw), max_length, text));
+                return results;
+            }
+        };
+
+        // The compilers colour their diagnostics when forced to (e.g., with
+        // `-fdiagnostics-color=always`)
+        let stripped;
+        let bytes = if raw.contains(&b'\x1b') {
+            stripped = ansi::strip(raw);
+            stripped.as_slice()
+        } else {
+            raw
+        };
+        let line = String::from_utf8_lossy(bytes);
+
+        if line.trim().is_empty() || is_summary(&line) {
+            return self.flush().into_iter().collect();
+        }
+
+        if let Some(header) = Header::parse(&line) {
+            if header.level == GccLevel::Note
+                && let Some(block) = &mut self.block
+            {
+                block.note(&header);
+                return Vec::new();
+            }
+            let results = self.flush().into_iter().collect();
+            self.used = true;
+            self.block = Some(GccDiagnostic::new(&header, &self.context));
+            return results;
+        }
+
+        if self.is_context(&line) {
+            let results = self.flush().into_iter().collect();
+            if mem::take(&mut self.used) {
+                self.context.clear();
+            }
+            self.context.push(line.into_owned());
+            return results;
+        }
+
+        if let Some(block) = &mut self.block
+            && line.starts_with(char::is_whitespace)
+        {
+            block.excerpt(&line);
+            return Vec::new();
+        }
+
+        let mut results: Vec<_> = self.flush().into_iter().collect();
+        results.extend(self.release(text));
+        results.extend(text.handle(bytes).map(GccMessage::TextOutput));
+        results
+    }
+}
+
+/// Tool implementation for parsing the diagnostics of GCC and Clang.
+#[derive(Debug, Clone, Default)]
+pub struct GccDiagnostics {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a diagnostic.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl GccDiagnostics {
+    /// A problem matcher for the diagnostics of GCC and Clang.
+    ///
+    /// Only errors and warnings are matched; notes are left to the
+    /// annotations of `cifmt`.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-gcc-diagnostics",
+            vec![
+                Pattern::new(
+                    r"^(.+?):(\d+):(?:(\d+):)? (?:fatal )?(error|warning): (.+?)(?: \[(-[^\]]+)\])?$",
+                )
+                .file(1)
+                .line(2)
+                .column(3)
+                .severity(4)
+                .message(5)
+                .code(6),
+            ],
+        )
+    }
+}
+
+impl Detect for GccDiagnostics {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .filter_map(|line| Header::parse(&line).map(|header| header.location.is_some()))
+            .any(|located| located)
+            .then(Self::default)
+    }
+}
+
+impl Tool for GccDiagnostics {
+    type Message = GccMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "gcc-diagnostics"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+
+        // A diagnostic cut off before its end is reported as far as it was
+        // read
+        results.extend(self.reader.flush());
+        results.extend(self.reader.release(&mut self.text));
+        results.extend(self.text.finish().map(GccMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for GccDiagnostics
+where
+    GccMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{GccDiagnostics, GccLevel, GccMessage, Header, Location, parse_location};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{Detect as _, RustcHuman, Tool as _, passthrough::Passthrough};
+
+    const OUTPUT: &str = "cc -Wall -c -o src/main.o src/main.c
+In file included from src/main.c:1:
+src/util.h: In function 'helper':
+src/util.h:2:9: warning: unused variable 'unused' [-Wunused-variable]
+    2 |     int unused = 1;
+      |         ^~~~~~
+src/main.c: In function 'main':
+src/main.c:6:5: error: too many arguments to function 'greet'
+    6 |     greet(\"world\", 1);
+      |     ^~~~~
+src/main.c:3:6: note: declared here
+    3 | void greet(const char *name);
+      |      ^~~~~
+make: *** [<builtin>: src/main.o] Error 1
+g++ -Wall -c -o src/shapes.o src/shapes.cpp
+src/shapes.cpp:4:17: error: no matching function for call to 'area(int, int, int)'
+    4 | int total = area(1, 2, 3);
+      |             ~~~~^~~~~~~~~
+src/shapes.cpp:1:5: note: candidate: 'int area(int)'
+    1 | int area(int side);
+      |     ^~~~
+src/shapes.cpp:1:5: note:   candidate expects 1 argument, 3 provided
+make: *** [<builtin>: src/shapes.o] Error 1
+";
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut GccDiagnostics, output: &str) -> String
+    where
+        GccMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <GccMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn headers() {
+        let header =
+            Header::parse("src/util.h:2:9: warning: unused variable 'x' [-Wunused-variable]")
+                .expect("header");
+        assert_eq!(header.level, GccLevel::Warning);
+        assert_eq!(header.message, "unused variable 'x'");
+        assert_eq!(header.option, Some("-Wunused-variable"));
+        let fatal =
+            Header::parse("src/main.c:1:10: fatal error: missing.h: No such file or directory")
+                .expect("header");
+        assert_eq!(fatal.level, GccLevel::FatalError);
+        assert_eq!(fatal.message, "missing.h: No such file or directory");
+        let linker = Header::parse("collect2: error: ld returned 1 exit status").expect("header");
+        assert_eq!(linker.program, Some("collect2"));
+        assert_eq!(linker.location, None);
+        assert_eq!(Header::parse("make: *** [Makefile:2: all] Error 1"), None);
+        assert_eq!(Header::parse("In file included from src/main.c:1:"), None);
+    }
+
+    #[test]
+    fn locations() {
+        assert_eq!(
+            parse_location("src/main.c:6:5"),
+            Some(Location {
+                file: "src/main.c".to_owned(),
+                line: 6,
+                column: Some(5),
+            })
+        );
+        assert_eq!(
+            parse_location("C:\\src\\main.c:6"),
+            Some(Location {
+                file: "C:\\src\\main.c".to_owned(),
+                line: 6,
+                column: None,
+            })
+        );
+        assert_eq!(parse_location("src/main.c"), None);
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(&mut GccDiagnostics::default(), OUTPUT), @"
+        ::warning file=src/util.h,line=2,col=9,title=-Wunused-variable::unused variable 'unused'%0AIn file included from src/main.c:1%0Asrc/util.h: In function 'helper'
+        ::error file=src/main.c,line=6,col=5::too many arguments to function 'greet'%0Asrc/main.c: In function 'main'
+        ::notice file=src/main.c,line=3,col=6::declared here
+        ::error file=src/shapes.cpp,line=4,col=17::no matching function for call to 'area(int, int, int)'
+        ::notice file=src/shapes.cpp,line=1,col=5::candidate: 'int area(int)'%0Acandidate expects 1 argument, 3 provided
+        ");
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(&mut GccDiagnostics::default(), OUTPUT), @r"
+        src/util.h:2:9: warning: -Wunused-variable: unused variable 'unused'\nIn file included from src/main.c:1\nsrc/util.h: In function 'helper'
+        src/main.c:6:5: error:   too many arguments to function 'greet'\nsrc/main.c: In function 'main'
+        src/main.c:3:6: notice:  declared here
+        src/shapes.cpp:4:17: error:   no matching function for call to 'area(int, int, int)'
+        src/shapes.cpp:1:5: notice:  candidate: 'int area(int)'\ncandidate expects 1 argument, 3 provided
+        ");
+    }
+
+    #[test]
+    fn notes() {
+        let mut tool = GccDiagnostics::default();
+        let mut messages = tool.parse(OUTPUT.as_bytes());
+        messages.extend(tool.finish());
+        let diagnostics: Vec<_> = messages
+            .into_iter()
+            .filter_map(|msg| match msg.expect("valid message") {
+                GccMessage::Diagnostic(diagnostic) => Some(diagnostic),
+                GccMessage::OverlongLine(_) | GccMessage::TextOutput(_) => None,
+            })
+            .collect();
+        let [unused, arguments, overload] = diagnostics.as_slice() else {
+            panic!("expected three diagnostics");
+        };
+        assert_eq!(
+            unused.context,
+            [
+                "In file included from src/main.c:1",
+                "src/util.h: In function 'helper'"
+            ]
+        );
+        assert_eq!(
+            unused.snippet().as_deref(),
+            Some("    2 |     int unused = 1;\n      |         ^~~~~~\n")
+        );
+        assert_eq!(arguments.context, ["src/main.c: In function 'main'"]);
+        assert_eq!(arguments.notes.len(), 1);
+        let [candidate] = overload.notes.as_slice() else {
+            panic!("expected one note");
+        };
+        assert_eq!(
+            candidate.message,
+            "candidate: 'int area(int)'\ncandidate expects 1 argument, 3 provided"
+        );
+    }
+
+    #[test]
+    fn passthrough() {
+        let mut tool = GccDiagnostics::default();
+        tool.set_passthrough(Passthrough::Raw);
+        insta::assert_snapshot!(format::<Plain>(&mut tool, "src/a.c: In function 'f':\nmake: Nothing to be done.\n"), @"
+        src/a.c: In function 'f':
+        make: Nothing to be done.
+        ");
+    }
+
+    #[test]
+    fn colored() {
+        let output = "\x1b[01m\x1b[Ksrc/main.c:7:12:\x1b[m\x1b[K \x1b[01;31m\x1b[Kerror: \x1b[m\x1b[K'missing' undeclared\n";
+        insta::assert_snapshot!(format::<GitHub>(&mut GccDiagnostics::default(), output), @"::error file=src/main.c,line=7,col=12::'missing' undeclared");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(GccDiagnostics::detect(OUTPUT.as_bytes()).is_some());
+        assert!(GccDiagnostics::detect(b"collect2: error: ld returned 1 exit status\n").is_none());
+        assert!(GccDiagnostics::detect(b"    Checking foo v0.1.0\n").is_none());
+        assert!(RustcHuman::detect(OUTPUT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = GccDiagnostics::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regexp = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regexp
+            .captures("src/util.h:2:9: warning: unused variable 'x' [-Wunused-variable]")
+            .expect("warning not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("src/util.h"));
+        assert_eq!(
+            captures.get(5).map(|m| m.as_str()),
+            Some("unused variable 'x'")
+        );
+        assert_eq!(
+            captures.get(6).map(|m| m.as_str()),
+            Some("-Wunused-variable")
+        );
+        assert!(regexp.is_match("src/main.c:1:10: fatal error: missing.h: No such file"));
+        assert!(!regexp.is_match("src/main.c:3:6: note: declared here"));
+    }
+}
@@ -0,0 +1,993 @@
+//! JUnit XML reports.
+//!
+//! Support for parsing the JUnit XML reports written by the test runners of
+//! most languages (e.g., pytest's `--junitxml`, Maven Surefire, Jest's
+//! `jest-junit`, or `go-junit-report`), so that their results are reported
+//! the same way as those of libtest, whatever the language of the tests.
+//!
+//! The report is read as it is received, by a minimal XML reader. Each
+//! `<testcase>` is reported as it ends: a test with a `<failure>` or an
+//! `<error>` failed, a test with `<skipped>` was ignored, and any other test
+//! passed. A failed test is annotated at its `file` and `line` attributes
+//! (or failing these, at the `file` attribute of its suite) when the report
+//! has them, and the details of its failure and the test's `<system-out>` and
+//! `<system-err>` follow the annotation in a group. Each `<testsuite>` is reported as it ends,
+//! with the tally of the tests it contains directly, so that the tests of
+//! nested suites are not counted twice.
+//!
+//! Any text outside of the report is passed through as plain text output.
+
+use core::{convert::Infallible, fmt, mem, time::Duration};
+
+use crate::{
+    capture::{Captured, OutputPolicy},
+    catalog::Title,
+    ci::{Phase, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    junit::{TestOutcome, TestResult},
+    matcher::{Pattern, ProblemMatcher},
+    suites::SuiteResult,
+    timeline::{Timed, Timing},
+    tool::{
+        Detect, DynTool, Tool,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        xml::{Element, Event, XmlReader},
+    },
+};
+
+/// A message parsed from a JUnit XML report.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum JunitMessage {
+    /// The result of a test.
+    TestCase(TestCase),
+
+    /// The result of a test suite.
+    TestSuite(TestSuite),
+
+    /// A report which ended before all of its test suites.
+    IncompleteReport(IncompleteReport),
+
+    /// Output which is not part of the report.
+    TextOutput(TextOutput),
+}
+
+/// The result of a test (i.e., a `<testcase>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TestCase {
+    /// The full name of the test (e.g., `tests.test_math.test_add`), which is
+    /// its `name` attribute qualified by its `classname` attribute.
+    pub name: String,
+    /// The outcome of the test.
+    pub outcome: TestOutcome,
+    /// The message of the failure, or the reason the test was skipped, if
+    /// any.
+    pub message: Option<String>,
+    /// The details of the failure (e.g., a stack trace), or of the reason the
+    /// test was skipped, if any.
+    pub details: Option<String>,
+    /// The output captured while the test ran (i.e., its `<system-out>` and
+    /// `<system-err>`), if any.
+    pub output: Option<String>,
+    /// How long the test took to run, if known.
+    pub duration: Option<Duration>,
+    /// The file in which the test is defined, if known.
+    pub file: Option<String>,
+    /// The line at which the test is defined, if known.
+    pub line: Option<u32>,
+}
+
+/// The result of a test suite (i.e., a `<testsuite>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TestSuite {
+    /// The name of the suite.
+    pub name: String,
+    /// The tally of the tests of the suite.
+    pub result: SuiteResult,
+}
+
+/// A report which ended before all of its test suites, which typically means
+/// that the test runner crashed or was killed while writing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IncompleteReport {
+    /// The name of the innermost suite which did not end, if any.
+    pub suite: Option<String>,
+    /// The name of the test which did not end, if any.
+    pub pending: Option<String>,
+}
+
+/// Display a title followed by a name (e.g., `Test Failed: tests.test_a`).
+fn titled(title: Title, name: &str) -> String {
+    format!("{title}: {name}")
+}
+
+/// Display a duration in seconds (e.g., ` in 0.12s`), if known.
+fn time_info(duration: Option<Duration>, prefix: &'static str) -> impl fmt::Display {
+    fmt::from_fn(move |f| match duration {
+        Some(time) => write!(f, "{prefix}{:.2}s", time.as_secs_f64()),
+        None => Ok(()),
+    })
+}
+
+/// Parse a duration in seconds (e.g., `0.012`).
+fn parse_duration(seconds: &str) -> Option<Duration> {
+    Duration::try_from_secs_f64(seconds.trim().parse().ok()?).ok()
+}
+
+impl TestCase {
+    /// Create a passed test from the attributes of its `<testcase>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The start tag of the test.
+    /// * `suite_file` - The file of the test's suite, if known.
+    fn new(element: &Element, suite_file: Option<&str>) -> Self {
+        let name = element.attribute("name").unwrap_or_default();
+        let qualified = match element
+            .attribute("classname")
+            .filter(|class| !class.is_empty())
+        {
+            Some(class) if !name.starts_with(class) => format!("{class}.{name}"),
+            Some(_) | None => name.to_owned(),
+        };
+        Self {
+            name: qualified,
+            outcome: TestOutcome::Passed,
+            message: None,
+            details: None,
+            output: None,
+            duration: element.attribute("time").and_then(parse_duration),
+            file: element
+                .attribute("file")
+                .or(suite_file)
+                .filter(|file| !file.is_empty())
+                .map(str::to_owned),
+            line: element
+                .attribute("line")
+                .and_then(|line| line.trim().parse().ok()),
+        }
+    }
+
+    /// Record the outcome of the test from a `<failure>`, an `<error>` or a
+    /// `<skipped>` element.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The start tag of the outcome.
+    fn record(&mut self, element: &Element) {
+        self.outcome = if element.name == "skipped" {
+            TestOutcome::Skipped
+        } else {
+            TestOutcome::Failed
+        };
+        // The type of the failure (e.g., `AssertionError`) stands in for a
+        // missing message
+        self.message = element
+            .attribute("message")
+            .or_else(|| element.attribute("type"))
+            .map(str::trim)
+            .filter(|message| !message.is_empty())
+            .map(str::to_owned);
+    }
+
+    /// Finish reading the test, applying the policy for the inclusion of its
+    /// output.
+    fn finish(mut self, policy: OutputPolicy) -> Self {
+        self.details = self
+            .details
+            .map(|details| details.trim().to_owned())
+            .filter(|details| !details.is_empty());
+        if self.message.is_none() {
+            self.message = self
+                .details
+                .as_deref()
+                .and_then(|details| details.lines().next())
+                .map(str::to_owned);
+        }
+        self.output = self
+            .output
+            .map(|output| output.trim_end().to_owned())
+            .filter(|output| !output.is_empty());
+        policy.apply(&mut self.output, self.outcome == TestOutcome::Failed);
+        self
+    }
+
+    /// The location at which the test is annotated, which is only known
+    /// for a failed test whose report has its file.
+    fn location(&self) -> Option<(&str, u32, Option<u32>)> {
+        if self.outcome != TestOutcome::Failed {
+            return None;
+        }
+        Some((self.file.as_deref()?, self.line.unwrap_or(1), None))
+    }
+
+    /// The details of the test shown in its group: those of a failure, and
+    /// the output of the test.
+    fn shown_details(&self) -> impl Iterator<Item = &String> {
+        self.details
+            .iter()
+            .filter(|_| self.outcome == TestOutcome::Failed)
+            .chain(&self.output)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for TestCase {
+    #[inline]
+    fn format(&self) -> String {
+        let (title, message) = match self.outcome {
+            TestOutcome::Passed => (
+                Title::TestPassed,
+                match self.duration {
+                    Some(_) => time_info(self.duration, "Executed in ").to_string(),
+                    None => "Passed".to_owned(),
+                },
+            ),
+            TestOutcome::Failed => (
+                Title::TestFailed,
+                self.message.clone().unwrap_or_else(|| "Failed".to_owned()),
+            ),
+            TestOutcome::Skipped => (
+                Title::TestIgnored,
+                self.message.clone().unwrap_or_else(|| "Skipped".to_owned()),
+            ),
+        };
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            message,
+            self.location(),
+            Some(&titled(title, &self.name)),
+        )];
+        if self.shown_details().next().is_some() {
+            lines.push(P::group(titled(Title::Test, &self.name)));
+            lines.extend(self.shown_details().cloned());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for TestCase {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.outcome {
+            TestOutcome::Passed => Severity::Notice,
+            TestOutcome::Failed => Severity::Error,
+            TestOutcome::Skipped => Severity::Debug,
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self.outcome {
+            TestOutcome::Passed => "TestPassed",
+            TestOutcome::Failed => "TestFailed",
+            TestOutcome::Skipped => "TestIgnored",
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        self.outcome == TestOutcome::Failed
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        Some(TestResult {
+            duration: self.duration,
+            message: self.message.as_deref(),
+            output: self.output.as_deref(),
+            ..TestResult::new(&self.name, self.outcome)
+        })
+    }
+}
+
+impl<P: Platform> CiMessage<P> for TestSuite {
+    #[inline]
+    fn format(&self) -> String {
+        let SuiteResult {
+            passed,
+            failed,
+            ignored,
+            duration,
+        } = self.result;
+        let title = if failed > 0 {
+            Title::TestSuiteFailed
+        } else {
+            Title::TestSuitePassed
+        };
+        P::annotate(
+            self.severity(),
+            format!(
+                "{passed} passed, {failed} failed, {ignored} ignored{}",
+                time_info(duration, " in ")
+            ),
+            None,
+            Some(&titled(title, &self.name)),
+        )
+    }
+}
+
+impl Classify for TestSuite {
+    #[inline]
+    fn severity(&self) -> Severity {
+        if self.result.failed > 0 {
+            Severity::Error
+        } else {
+            Severity::Notice
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        if self.result.failed > 0 {
+            "SuiteFailed"
+        } else {
+            "SuitePassed"
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        Some(self.result)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for IncompleteReport {
+    #[inline]
+    fn format(&self) -> String {
+        let mut message = match &self.suite {
+            Some(suite) => format!("Report ended before test suite {suite}"),
+            None => "Report ended before its test suites".to_owned(),
+        };
+        if let Some(test) = &self.pending {
+            message = format!("{message}; test {test} did not finish");
+        }
+        P::annotate(
+            self.severity(),
+            message,
+            None,
+            Some(Title::IncompleteTestSuite.text()),
+        )
+    }
+}
+
+impl Classify for IncompleteReport {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
+
+impl<P: Platform> CiMessage<P> for JunitMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::TestCase(msg) => <TestCase as CiMessage<P>>::format(msg),
+            Self::TestSuite(msg) => <TestSuite as CiMessage<P>>::format(msg),
+            Self::IncompleteReport(msg) => <IncompleteReport as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for JunitMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::TestCase(msg) => msg.severity(),
+            Self::TestSuite(msg) => msg.severity(),
+            Self::IncompleteReport(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TestCase(msg) => msg.kind(),
+            Self::TestSuite(msg) => msg.kind(),
+            Self::IncompleteReport(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        match self {
+            Self::TestCase(msg) => msg.is_test_failure(),
+            Self::TestSuite(_) | Self::IncompleteReport(_) | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::IncompleteReport(msg) => msg.is_incomplete(),
+            Self::TestCase(_) | Self::TestSuite(_) | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::TestCase(msg) => msg.sort_key(),
+            Self::TestSuite(_) | Self::IncompleteReport(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::TestCase(msg) => msg.file(),
+            Self::TestSuite(_) | Self::IncompleteReport(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::TestCase(msg) => msg.message(),
+            Self::TestSuite(_) | Self::IncompleteReport(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        match self {
+            Self::TestCase(msg) => msg.test_result(),
+            Self::TestSuite(_) | Self::IncompleteReport(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            Self::TestSuite(msg) => msg.suite_result(),
+            Self::TestCase(_) | Self::IncompleteReport(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for JunitMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::TestCase(TestCase {
+                name,
+                duration: Some(duration),
+                ..
+            }) => Some(Timing::Elapsed {
+                track: name,
+                name,
+                category: "test",
+                duration: *duration,
+            }),
+            Self::TestCase(_)
+            | Self::TestSuite(_)
+            | Self::IncompleteReport(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Captured for JunitMessage {
+    #[inline]
+    fn captured_output_mut(&mut self) -> Option<(&str, &mut String)> {
+        match self {
+            Self::TestCase(TestCase {
+                name,
+                output: Some(output),
+                ..
+            }) => Some((name, output)),
+            Self::TestCase(_)
+            | Self::TestSuite(_)
+            | Self::IncompleteReport(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+/// A test suite being read.
+#[derive(Debug, Clone)]
+struct OpenSuite {
+    /// The name of the suite.
+    name: String,
+    /// The file of the suite, if known.
+    file: Option<String>,
+    /// The tally of the tests of the suite read so far.
+    result: SuiteResult,
+}
+
+/// The part of a test being read to which text belongs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// The details of the failure, or of the reason the test was skipped.
+    Details,
+    /// The output captured while the test ran.
+    Output,
+}
+
+/// The state of the parser, apart from the reading of the XML.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The suites being read, innermost last.
+    suites: Vec<OpenSuite>,
+    /// The test being read, if any.
+    case: Option<TestCase>,
+    /// The part of the test to which text belongs, if any.
+    field: Option<Field>,
+    /// The number of elements which were started but not ended.
+    depth: usize,
+}
+
+impl Reader {
+    /// Handle an event of the report.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the event.
+    fn handle(
+        &mut self,
+        event: Event,
+        policy: OutputPolicy,
+        text: &mut TextLines,
+    ) -> Vec<JunitMessage> {
+        match event {
+            Event::Start(element) => {
+                if !element.empty {
+                    self.depth = self.depth.saturating_add(1);
+                }
+                self.start(&element, policy)
+            }
+            Event::End(name) => {
+                self.depth = self.depth.saturating_sub(1);
+                self.end(&name, policy)
+            }
+            Event::Text(content) => self.text(&content, text),
+        }
+    }
+
+    /// Handle the start tag of an element.
+    fn start(&mut self, element: &Element, policy: OutputPolicy) -> Vec<JunitMessage> {
+        match element.name.as_str() {
+            "testsuite" => {
+                self.suites.push(OpenSuite {
+                    name: element.attribute("name").unwrap_or_default().to_owned(),
+                    file: element.attribute("file").map(str::to_owned),
+                    result: SuiteResult {
+                        duration: element.attribute("time").and_then(parse_duration),
+                        ..SuiteResult::new(0, 0, 0)
+                    },
+                });
+                if element.empty {
+                    return self.end("testsuite", policy);
+                }
+            }
+            "testcase" => {
+                let suite_file = self.suites.last().and_then(|suite| suite.file.as_deref());
+                self.case = Some(TestCase::new(element, suite_file));
+                if element.empty {
+                    return self.end("testcase", policy);
+                }
+            }
+            "failure" | "error" | "skipped" => {
+                if let Some(case) = &mut self.case {
+                    case.record(element);
+                    self.field = (!element.empty).then_some(Field::Details);
+                }
+            }
+            "system-out" | "system-err" if self.case.is_some() && !element.empty => {
+                self.field = Some(Field::Output);
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    /// Handle the end tag of an element.
+    fn end(&mut self, name: &str, policy: OutputPolicy) -> Vec<JunitMessage> {
+        match name {
+            "testsuite" => self
+                .suites
+                .pop()
+                .map(|suite| {
+                    JunitMessage::TestSuite(TestSuite {
+                        name: suite.name,
+                        result: suite.result,
+                    })
+                })
+                .into_iter()
+                .collect(),
+            "testcase" => {
+                self.field = None;
+                let Some(case) = self.case.take().map(|open| open.finish(policy)) else {
+                    return Vec::new();
+                };
+                if let Some(suite) = self.suites.last_mut() {
+                    let tally = match case.outcome {
+                        TestOutcome::Passed => &mut suite.result.passed,
+                        TestOutcome::Failed => &mut suite.result.failed,
+                        TestOutcome::Skipped => &mut suite.result.ignored,
+                    };
+                    *tally = tally.saturating_add(1);
+                }
+                vec![JunitMessage::TestCase(case)]
+            }
+            "failure" | "error" | "skipped" | "system-out" | "system-err" => {
+                self.field = None;
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Handle text, which is either part of a test, or output outside of the
+    /// report.
+    fn text(&mut self, content: &str, text: &mut TextLines) -> Vec<JunitMessage> {
+        match (self.field, &mut self.case) {
+            (Some(Field::Details), Some(case)) => {
+                case.details.get_or_insert_default().push_str(content);
+            }
+            (Some(Field::Output), Some(case)) => {
+                case.output.get_or_insert_default().push_str(content);
+            }
+            _ if self.depth == 0 => {
+                return content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| text.handle(line.as_bytes()))
+                    .map(JunitMessage::TextOutput)
+                    .collect();
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    /// Report a report which ended before all of its suites, if any.
+    fn finish(&mut self) -> Option<JunitMessage> {
+        let pending = self.case.take().map(|case| case.name);
+        let suites = mem::take(&mut self.suites);
+        self.field = None;
+        self.depth = 0;
+        if suites.is_empty() && pending.is_none() {
+            return None;
+        }
+        Some(JunitMessage::IncompleteReport(IncompleteReport {
+            suite: suites.last().map(|suite| suite.name.clone()),
+            pending,
+        }))
+    }
+}
+
+/// Tool implementation for parsing JUnit XML reports.
+#[derive(Debug, Clone, Default)]
+pub struct Junit {
+    /// Reader of the events of the report.
+    xml: XmlReader,
+    /// The state of the parser.
+    reader: Reader,
+    /// Handling of text outside of the report.
+    text: TextLines,
+    /// The policy for the inclusion of the output of tests.
+    policy: OutputPolicy,
+}
+
+impl Junit {
+    /// A problem matcher for a JUnit XML report printed to the log (e.g.,
+    /// with `cat`).
+    ///
+    /// Each `<failure>` or `<error>` on a line of its own, as in reports
+    /// written with one element per line, is reported as an error with its
+    /// `message` attribute as its message. As the test's location is on the
+    /// line of its `<testcase>`, the error is not located.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-junit",
+            vec![Pattern::new(r#"^\s*<(?:failure|error)\s[^>]*?\bmessage="([^"]*)""#).message(1)],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Detect for Junit {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        memchr::memmem::find(sample, b"<testsuite").map(|_| Self::default())
+    }
+}
+
+impl Tool for Junit {
+    type Message = JunitMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "junit"
+    }
+
+    #[inline]
+    fn phase(&self) -> Phase {
+        Phase::Test
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.xml.push(buf);
+
+        let mut results = Vec::new();
+        while let Some(event) = self.xml.next_event() {
+            results.extend(self.reader.handle(event, self.policy, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let mut results = Vec::new();
+        if let Some(event) = self.xml.finish() {
+            results.extend(self.reader.handle(event, self.policy, &mut self.text));
+        }
+        results.extend(self.reader.finish());
+        results.extend(self.text.finish().map(JunitMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.policy = policy;
+    }
+}
+
+impl<P: Platform> DynTool<P> for Junit
+where
+    JunitMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Junit, JunitMessage};
+    use crate::capture::OutputPolicy;
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify};
+    use crate::suites::SuiteResult;
+    use crate::tool::{CargoLibtest, Detect as _, Passthrough, RustcHuman, Tool as _};
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="com.example.CalculatorTest" tests="3" time="0.25" file="src/test/java/com/example/CalculatorTest.java">
+    <testcase classname="com.example.CalculatorTest" name="adds" time="0.01"/>
+    <testcase classname="com.example.CalculatorTest" name="divides" line="42" time="0.2">
+      <failure message="expected: &lt;2&gt; but was: &lt;0&gt;" type="org.opentest4j.AssertionFailedError"><![CDATA[org.opentest4j.AssertionFailedError: expected: <2> but was: <0>
+	at com.example.CalculatorTest.divides(CalculatorTest.java:42)]]></failure>
+      <system-out>dividing 4 by 2</system-out>
+    </testcase>
+    <testcase classname="com.example.CalculatorTest" name="multiplies">
+      <skipped message="disabled"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+    /// Parse the whole report, fed in chunks of the given size.
+    fn parse(report: &str, chunk: usize) -> Vec<JunitMessage> {
+        let mut tool = Junit::default();
+        let mut messages = Vec::new();
+        for bytes in report.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole report, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut Junit, report: &str) -> String
+    where
+        JunitMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(report.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <JunitMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(&mut Junit::default(), REPORT), @"
+        ::notice title=Test Passed%3A com.example.CalculatorTest.adds::Executed in 0.01s
+        ::error file=src/test/java/com/example/CalculatorTest.java,line=42,title=Test Failed%3A com.example.CalculatorTest.divides::expected: <2> but was: <0>
+        ::group::Test: com.example.CalculatorTest.divides
+        org.opentest4j.AssertionFailedError: expected: <2> but was: <0>
+        	at com.example.CalculatorTest.divides(CalculatorTest.java:42)
+        dividing 4 by 2
+        ::endgroup::
+        ::debug::Test Ignored: com.example.CalculatorTest.multiplies: disabled
+        ::error title=Test Suite Failed%3A com.example.CalculatorTest::1 passed, 1 failed, 1 ignored in 0.25s
+        ");
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(&mut Junit::default(), REPORT), @"
+        notice:  Test Passed: com.example.CalculatorTest.adds: Executed in 0.01s
+        src/test/java/com/example/CalculatorTest.java:42: error:   Test Failed: com.example.CalculatorTest.divides: expected: <2> but was: <0>
+        Test: com.example.CalculatorTest.divides
+        org.opentest4j.AssertionFailedError: expected: <2> but was: <0>
+        	at com.example.CalculatorTest.divides(CalculatorTest.java:42)
+        dividing 4 by 2
+        debug:   Test Ignored: com.example.CalculatorTest.multiplies: disabled
+        error:   Test Suite Failed: com.example.CalculatorTest: 1 passed, 1 failed, 1 ignored in 0.25s
+        ");
+    }
+
+    #[test]
+    fn chunked() {
+        assert_eq!(parse(REPORT, 5), parse(REPORT, REPORT.len()));
+    }
+
+    #[test]
+    fn results() {
+        let messages = parse(REPORT, REPORT.len());
+
+        let failure = messages
+            .iter()
+            .find(|msg| msg.is_test_failure())
+            .expect("failure not reported");
+        assert_eq!(
+            failure.file(),
+            Some("src/test/java/com/example/CalculatorTest.java")
+        );
+        assert_eq!(failure.message(), Some("expected: <2> but was: <0>"));
+        let result = failure.test_result().expect("no test result");
+        assert_eq!(result.name, "com.example.CalculatorTest.divides");
+        assert_eq!(result.output, Some("dividing 4 by 2"));
+        assert_eq!(result.duration, Some(Duration::from_millis(200)));
+
+        let suites: Vec<_> = messages.iter().filter_map(Classify::suite_result).collect();
+        assert_eq!(
+            suites,
+            vec![SuiteResult {
+                duration: Some(Duration::from_millis(250)),
+                ..SuiteResult::new(1, 1, 1)
+            }]
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let report = r#"<testsuite name="all"><testsuite name="unit"><testcase name="a"/><testcase name="b"><error/></testcase></testsuite><testcase name="c"/></testsuite>"#;
+        let mut tool = Junit::default();
+        let suites: Vec<_> = tool
+            .parse(report.as_bytes())
+            .into_iter()
+            .filter_map(|msg| msg.expect("valid message").suite_result())
+            .collect();
+        assert_eq!(
+            suites,
+            vec![SuiteResult::new(1, 1, 0), SuiteResult::new(1, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn incomplete() {
+        let report =
+            r#"<testsuite name="unit"><testcase classname="a" name="b"><system-out>partial"#;
+        let mut tool = Junit::default();
+        let output = format::<GitHub>(&mut tool, report);
+        insta::assert_snapshot!(output, @"::warning title=Incomplete Test Suite::Report ended before test suite unit; test a.b did not finish");
+    }
+
+    #[test]
+    fn output_policy() {
+        let mut tool = Junit::default();
+        tool.set_output_policy(OutputPolicy::Never);
+        let captured = format::<Plain>(&mut tool, REPORT);
+        assert!(!captured.contains("dividing 4 by 2"));
+    }
+
+    #[test]
+    fn passthrough() {
+        let report = format!("Running tests...\n{REPORT}Done.\n");
+        let mut tool = Junit::default();
+        tool.set_passthrough(Passthrough::Raw);
+        let output = format::<Plain>(&mut tool, &report);
+        assert!(output.starts_with("Running tests..."), "{output}");
+        assert!(output.ends_with("Done."), "{output}");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Junit::detect(REPORT.as_bytes()).is_some());
+        assert!(Junit::detect(b"error[E0308]: mismatched types\n").is_none());
+        assert!(CargoLibtest::detect(REPORT.as_bytes()).is_none());
+        assert!(RustcHuman::detect(REPORT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Junit::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regex
+            .captures(r#"      <failure type="AssertionError" message="assert 1 == 2">"#)
+            .expect("failure not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("assert 1 == 2"));
+        assert!(regex.is_match(r#"<error message="boom"/>"#));
+        assert!(!regex.is_match(r#"<skipped message="disabled"/>"#));
+    }
+}
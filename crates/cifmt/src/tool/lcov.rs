@@ -0,0 +1,798 @@
+//! LCOV coverage tracefile format.
+//!
+//! Support for parsing LCOV tracefiles, as written by `cargo llvm-cov --lcov`
+//! (or `grcov --output-type lcov`). A tracefile holds a record for each
+//! source file, starting with `SF:<path>` and ending with `end_of_record`, in
+//! which the lines (`DA:<line>,<count>`), functions and branches which were
+//! executed are listed and counted (`LF`/`LH`, `FNF`/`FNH`, `BRF`/`BRH`).
+//! The format is documented in the [`geninfo` manual].
+//!
+//! The coverage of each file is reported once its record ends, and the total
+//! coverage follows at the end of the output. With a threshold (see
+//! [`ToolOptions::coverage_threshold`]), files whose line coverage is below it
+//! are annotated with a warning at their first line which was not executed,
+//! as is the total.
+//!
+//! [`geninfo` manual]: https://github.com/linux-test-project/lcov/blob/master/man/geninfo.1
+
+use core::{convert::Infallible, fmt, mem};
+use std::io::BufRead as _;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::{NormalizedPath, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool, ToolOption, ToolOptions,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+    },
+};
+
+/// A message parsed from an LCOV tracefile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LcovMessage {
+    /// The coverage of a source file.
+    File(FileCoverage),
+
+    /// The total coverage of all source files, reported at the end of the
+    /// output.
+    Summary(CoverageSummary),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// Output which is not part of a tracefile.
+    TextOutput(TextOutput),
+}
+
+/// The number of items (e.g., lines) which were found and executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Counts {
+    /// The number of instrumented items.
+    pub found: u64,
+    /// The number of items executed at least once.
+    pub hit: u64,
+}
+
+impl Counts {
+    /// Add the counts of another file.
+    fn add(&mut self, other: Self) {
+        self.found = self.found.saturating_add(other.found);
+        self.hit = self.hit.saturating_add(other.hit);
+    }
+
+    /// Whether the share of items executed is below a threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `percent` - The threshold, in percent.
+    fn is_below(self, percent: u8) -> bool {
+        self.found > 0
+            && u128::from(self.hit).saturating_mul(100)
+                < u128::from(self.found).saturating_mul(u128::from(percent))
+    }
+
+    /// Display the share of items executed, rounded to one decimal (e.g.,
+    /// `57.1%`), or `-` if no items were found.
+    fn percent(self) -> impl fmt::Display {
+        fmt::from_fn(move |f| {
+            let found = u128::from(self.found);
+            let permille = u128::from(self.hit)
+                .saturating_mul(1000)
+                .saturating_add(found.checked_div(2).unwrap_or_default())
+                .checked_div(found);
+            match permille {
+                Some(tenths) => write!(
+                    f,
+                    "{}.{}%",
+                    tenths.checked_div(10).unwrap_or_default(),
+                    tenths.checked_rem(10).unwrap_or_default()
+                ),
+                None => f.write_str("-"),
+            }
+        })
+    }
+
+    /// Display the share of items executed along with the counts (e.g.,
+    /// `57.1% of lines (4 of 7)`).
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The name of the items, in the plural.
+    fn describe(self, items: &'static str) -> impl fmt::Display {
+        fmt::from_fn(move |f| {
+            write!(
+                f,
+                "{} of {items} ({} of {})",
+                self.percent(),
+                self.hit,
+                self.found
+            )
+        })
+    }
+}
+
+/// The coverage of lines, functions and branches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Coverage {
+    /// The lines found and executed.
+    pub lines: Counts,
+    /// The functions found and executed.
+    pub functions: Counts,
+    /// The branches found and taken.
+    pub branches: Counts,
+}
+
+impl Coverage {
+    /// Add the coverage of another file.
+    fn add(&mut self, other: Self) {
+        self.lines.add(other.lines);
+        self.functions.add(other.functions);
+        self.branches.add(other.branches);
+    }
+}
+
+/// Lines are always shown; functions and branches only if any were found.
+impl fmt::Display for Coverage {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.describe("lines"))?;
+        if self.functions.found > 0 {
+            write!(f, ", {}", self.functions.describe("functions"))?;
+        }
+        if self.branches.found > 0 {
+            write!(f, ", {}", self.branches.describe("branches"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The coverage of a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileCoverage {
+    /// The path of the file, as written in the tracefile.
+    pub file: String,
+    /// The coverage of the file.
+    pub coverage: Coverage,
+    /// The first line of the file which was not executed, if any.
+    pub first_uncovered: Option<u32>,
+    /// The line coverage below which the file is reported with a warning, in
+    /// percent.
+    pub threshold: Option<u8>,
+}
+
+impl FileCoverage {
+    /// Start the record of a file.
+    fn new(file: &str) -> Self {
+        Self {
+            file: file.to_owned(),
+            coverage: Coverage::default(),
+            first_uncovered: None,
+            threshold: None,
+        }
+    }
+
+    /// Whether the line coverage of the file is below the threshold.
+    fn is_below_threshold(&self) -> bool {
+        self.threshold
+            .is_some_and(|percent| self.coverage.lines.is_below(percent))
+    }
+}
+
+impl<P: Platform> CiMessage<P> for FileCoverage {
+    #[inline]
+    fn format(&self) -> String {
+        match self.threshold.filter(|_| self.is_below_threshold()) {
+            Some(percent) => P::annotate(
+                self.severity(),
+                format!(
+                    "Line coverage is below the threshold of {percent}%: {}",
+                    self.coverage
+                ),
+                Some((&self.file, self.first_uncovered.unwrap_or(1), None)),
+                Some(Title::LowCoverage.text()),
+            ),
+            None => format!("{}: {}", NormalizedPath(&self.file), self.coverage),
+        }
+    }
+}
+
+impl Classify for FileCoverage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        if self.is_below_threshold() {
+            Severity::Warning
+        } else {
+            Severity::Notice
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        self.is_below_threshold()
+            .then_some("Line coverage is below the threshold")
+    }
+}
+
+/// The total coverage of all source files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CoverageSummary {
+    /// The number of source files.
+    pub files: usize,
+    /// The total coverage of the files.
+    pub coverage: Coverage,
+    /// The line coverage below which the total is reported with a warning, in
+    /// percent.
+    pub threshold: Option<u8>,
+}
+
+impl CoverageSummary {
+    /// Whether the total line coverage is below the threshold.
+    fn is_below_threshold(&self) -> bool {
+        self.threshold
+            .is_some_and(|percent| self.coverage.lines.is_below(percent))
+    }
+}
+
+impl<P: Platform> CiMessage<P> for CoverageSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let total = if self.files == 0 {
+            "No coverage data found".to_owned()
+        } else {
+            format!(
+                "Total coverage of {} file(s): {}",
+                self.files, self.coverage
+            )
+        };
+        let message = match self.threshold.filter(|_| self.is_below_threshold()) {
+            Some(percent) => format!("{total}; below the threshold of {percent}%"),
+            None => total,
+        };
+        P::annotate(self.severity(), message, None, Some(Title::Coverage.text()))
+    }
+}
+
+impl Classify for CoverageSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        if self.is_below_threshold() {
+            Severity::Warning
+        } else {
+            Severity::Notice
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for LcovMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::File(msg) => <FileCoverage as CiMessage<P>>::format(msg),
+            Self::Summary(msg) => <CoverageSummary as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for LcovMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::File(msg) => msg.severity(),
+            Self::Summary(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::File(msg) => msg.kind(),
+            Self::Summary(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::File(msg) => Some(&msg.file),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::File(msg) => msg.file(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::File(msg) => msg.message(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for LcovMessage {}
+
+impl Captured for LcovMessage {}
+
+/// A line of a tracefile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Record<'a> {
+    /// The start of the record of a file (`SF:<path>`).
+    SourceFile(&'a str),
+    /// The execution count of a line (`DA:<line>,<count>[,<checksum>]`).
+    Line(u32, u64),
+    /// A count of the record (e.g., `LF:<count>`).
+    Count(&'a str, u64),
+    /// The end of the record of a file.
+    End,
+    /// Another line of a tracefile (e.g., `TN:` or `FNDA:<count>,<name>`).
+    Other,
+}
+
+/// The prefixes of the lines of a tracefile which are not used.
+const OTHER_PREFIXES: &[&str] = &["TN:", "VER:", "FN:", "FNL:", "FNA:", "FNDA:", "BRDA:"];
+
+/// Parse a line of a tracefile.
+///
+/// # Returns
+///
+/// The line, or `None` if it is not part of a tracefile.
+fn parse_record(line: &str) -> Option<Record<'_>> {
+    if line == "end_of_record" {
+        return Some(Record::End);
+    }
+    if let Some(path) = line.strip_prefix("SF:") {
+        return Some(Record::SourceFile(path));
+    }
+    if let Some(rest) = line.strip_prefix("DA:") {
+        let mut fields = rest.split(',');
+        let number = fields.next()?.parse().ok()?;
+        let count = fields.next()?.parse().ok()?;
+        return Some(Record::Line(number, count));
+    }
+    if let Some((key, value)) = line.split_once(':')
+        && matches!(key, "LF" | "LH" | "FNF" | "FNH" | "BRF" | "BRH")
+    {
+        return Some(Record::Count(key, value.parse().ok()?));
+    }
+    OTHER_PREFIXES
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+        .then_some(Record::Other)
+}
+
+/// The files read so far, used to compute the total coverage.
+#[derive(Debug, Clone, Default)]
+struct Files {
+    /// The file whose record is being read.
+    current: Option<FileCoverage>,
+    /// The lines of the current file counted from its `DA` lines, used if
+    /// the record does not count them itself.
+    lines: Counts,
+    /// The number of files reported so far.
+    count: usize,
+    /// The total coverage of the files reported so far.
+    total: Coverage,
+    /// The line coverage below which files are reported with a warning.
+    threshold: Option<u8>,
+}
+
+impl Files {
+    /// Report the file being read, if any.
+    fn flush(&mut self) -> Option<LcovMessage> {
+        let mut file = self.current.take()?;
+        let lines = mem::take(&mut self.lines);
+        if file.coverage.lines == Counts::default() {
+            file.coverage.lines = lines;
+        }
+        file.threshold = self.threshold;
+        self.count = self.count.saturating_add(1);
+        self.total.add(file.coverage);
+        Some(LcovMessage::File(file))
+    }
+
+    /// Report the total coverage of the files reported so far.
+    fn summary(&mut self) -> LcovMessage {
+        LcovMessage::Summary(CoverageSummary {
+            files: mem::take(&mut self.count),
+            coverage: mem::take(&mut self.total),
+            threshold: self.threshold,
+        })
+    }
+
+    /// Read a line of a tracefile.
+    ///
+    /// # Returns
+    ///
+    /// The message completed by the line, if any.
+    fn read(&mut self, record: Record<'_>) -> Option<LcovMessage> {
+        match record {
+            Record::SourceFile(path) => {
+                let previous = self.flush();
+                self.current = Some(FileCoverage::new(path));
+                return previous;
+            }
+            Record::End => return self.flush(),
+            Record::Other => return None,
+            Record::Line(_, _) | Record::Count(_, _) => {}
+        }
+        let file = self.current.as_mut()?;
+        if let Record::Line(number, count) = record {
+            self.lines.found = self.lines.found.saturating_add(1);
+            if count > 0 {
+                self.lines.hit = self.lines.hit.saturating_add(1);
+            } else if file.first_uncovered.is_none_or(|first| number < first) {
+                file.first_uncovered = Some(number);
+            }
+        } else if let Record::Count(key, value) = record {
+            let coverage = &mut file.coverage;
+            let field = match key {
+                "LF" => &mut coverage.lines.found,
+                "LH" => &mut coverage.lines.hit,
+                "FNF" => &mut coverage.functions.found,
+                "FNH" => &mut coverage.functions.hit,
+                "BRF" => &mut coverage.branches.found,
+                _ => &mut coverage.branches.hit,
+            };
+            *field = value;
+        }
+        None
+    }
+
+    /// Parse a single line of the output.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<LcovMessage> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                let mut results = vec![LcovMessage::OverlongLine(OverlongLine { max_length })];
+                results.extend(self.parse_line(Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        // Skip empty lines
+        if line.is_empty() {
+            return Vec::new();
+        }
+
+        let message = match str::from_utf8(line).ok().and_then(parse_record) {
+            Some(record) => self.read(record),
+            None => text.handle(line).map(LcovMessage::TextOutput),
+        };
+        message.into_iter().collect()
+    }
+}
+
+/// Tool implementation for parsing LCOV tracefiles.
+#[derive(Debug, Clone, Default)]
+pub struct Lcov {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a tracefile.
+    text: TextLines,
+    /// The files read so far.
+    files: Files,
+}
+
+impl Lcov {
+    /// A problem matcher for the report printed by `cargo llvm-cov` without
+    /// `--lcov`.
+    ///
+    /// Each row of the table of files with lines which were not executed
+    /// (e.g., `src/lib.rs  4  2  50.00%  2  1  50.00%  7  3  57.14%  0  0  -`)
+    /// is reported as a warning on the file, with the row as its message.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        let count = r"\s+\d+";
+        let percent = r"\s+(?:\d+\.\d+%|-)";
+        ProblemMatcher::new(
+            "cifmt-lcov",
+            vec![
+                Pattern::new(format!(
+                    r"^((\S*[./]\S*){count}{count}{percent}{count}{count}{percent}{count}\s+[1-9]\d*{percent}.*)$"
+                ))
+                .message(1)
+                .file(2),
+            ],
+        )
+        .with_severity("warning")
+    }
+}
+
+impl Detect for Lcov {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        let mut source_files = false;
+        let mut records = false;
+        for line in sample.lines().map_while(Result::ok) {
+            match parse_record(&line) {
+                Some(Record::SourceFile(_)) => source_files = true,
+                Some(Record::Line(_, _) | Record::Count(_, _) | Record::End) => records = true,
+                Some(Record::Other) | None => {}
+            }
+        }
+        (source_files && records).then(Self::default)
+    }
+}
+
+impl Tool for Lcov {
+    type Message = LcovMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "lcov"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(
+                self.files
+                    .parse_line(line, max_length, &mut self.text)
+                    .into_iter()
+                    .map(Ok),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.files.parse_line(line, max_length, &mut self.text));
+        }
+
+        // A record cut off before its end is reported as far as it was read
+        results.extend(self.files.flush());
+        results.push(self.files.summary());
+        results.extend(self.text.finish().map(LcovMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        &[ToolOption::CoverageThreshold]
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        self.files.threshold = options.coverage_threshold;
+    }
+}
+
+impl<P: Platform> DynTool<P> for Lcov
+where
+    LcovMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Counts, Lcov, LcovMessage, Record, parse_record};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{CargoFmt, Detect as _, Tool as _, ToolOptions};
+
+    const TRACEFILE: &str = "\
+SF:/w/src/lib.rs
+FN:1,_RNvCs1_7project3add
+FN:5,_RNvCs1_7project3sub
+FNDA:1,_RNvCs1_7project3add
+FNDA:0,_RNvCs1_7project3sub
+FNF:2
+FNH:1
+DA:1,1
+DA:2,1
+DA:3,1
+DA:5,0
+DA:6,0
+DA:7,0
+DA:9,1
+BRF:0
+BRH:0
+LF:7
+LH:4
+end_of_record
+SF:/w/src/main.rs
+FN:1,_RNvCs1_4main4main
+FNDA:1,_RNvCs1_4main4main
+FNF:1
+FNH:1
+DA:1,1
+DA:2,1
+DA:3,1
+LF:3
+LH:3
+end_of_record
+";
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str, threshold: Option<u8>) -> String
+    where
+        LcovMessage: CiMessage<P>,
+    {
+        let mut tool = Lcov::default();
+        tool.set_options(&ToolOptions::default().with_coverage_threshold(threshold));
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <LcovMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn records() {
+        assert_eq!(
+            parse_record("SF:src/lib.rs"),
+            Some(Record::SourceFile("src/lib.rs"))
+        );
+        assert_eq!(parse_record("DA:3,12"), Some(Record::Line(3, 12)));
+        assert_eq!(parse_record("DA:3,0,abcdef"), Some(Record::Line(3, 0)));
+        assert_eq!(parse_record("LH:4"), Some(Record::Count("LH", 4)));
+        assert_eq!(parse_record("FNDA:1,main"), Some(Record::Other));
+        assert_eq!(parse_record("end_of_record"), Some(Record::End));
+        assert_eq!(parse_record("DA:x,1"), None);
+        assert_eq!(parse_record("Finished report saved"), None);
+    }
+
+    #[test]
+    fn percent() {
+        let counts = |hit, found| Counts { found, hit }.percent().to_string();
+        assert_eq!(counts(4, 7), "57.1%");
+        assert_eq!(counts(3, 3), "100.0%");
+        assert_eq!(counts(2, 3), "66.7%");
+        assert_eq!(counts(0, 0), "-");
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(TRACEFILE, None), @"
+        /w/src/lib.rs: 57.1% of lines (4 of 7), 50.0% of functions (1 of 2)
+        /w/src/main.rs: 100.0% of lines (3 of 3), 100.0% of functions (1 of 1)
+        notice:  Coverage: Total coverage of 2 file(s): 70.0% of lines (7 of 10), 66.7% of functions (2 of 3)
+        ");
+    }
+
+    #[test]
+    fn threshold() {
+        insta::assert_snapshot!(format::<GitHub>(TRACEFILE, Some(80)), @"
+        ::warning file=/w/src/lib.rs,line=5,title=Low Coverage::Line coverage is below the threshold of 80%25: 57.1%25 of lines (4 of 7), 50.0%25 of functions (1 of 2)
+        /w/src/main.rs: 100.0% of lines (3 of 3), 100.0% of functions (1 of 1)
+        ::warning title=Coverage::Total coverage of 2 file(s): 70.0%25 of lines (7 of 10), 66.7%25 of functions (2 of 3); below the threshold of 80%25
+        ");
+    }
+
+    #[test]
+    fn counted_lines() {
+        let output = "SF:src/lib.rs\nDA:1,1\nDA:2,0\nend_of_record\n";
+        insta::assert_snapshot!(format::<Plain>(output, Some(50)), @"
+        src/lib.rs: 50.0% of lines (1 of 2)
+        notice:  Coverage: Total coverage of 1 file(s): 50.0% of lines (1 of 2)
+        ");
+    }
+
+    #[test]
+    fn truncated() {
+        let end = TRACEFILE.find("DA:6").expect("line of the tracefile");
+        let output = TRACEFILE.get(..end).expect("in bounds");
+        insta::assert_snapshot!(format::<Plain>(output, None), @"
+        /w/src/lib.rs: 75.0% of lines (3 of 4), 50.0% of functions (1 of 2)
+        notice:  Coverage: Total coverage of 1 file(s): 75.0% of lines (3 of 4), 50.0% of functions (1 of 2)
+        ");
+    }
+
+    #[test]
+    fn empty() {
+        insta::assert_snapshot!(format::<GitHub>("", Some(80)), @"::notice title=Coverage::No coverage data found");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Lcov::detect(TRACEFILE.as_bytes()).is_some());
+        assert!(Lcov::detect(b"Diff in src/lib.rs:1:\n").is_none());
+        assert!(CargoFmt::detect(TRACEFILE.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Lcov::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let row = "src/lib.rs                          4                 2    50.00%           2                 1    50.00%           7                 3    57.14%           0                 0         -";
+        let captures = regex.captures(row).expect("row not matched");
+        assert_eq!(captures.get(2).map(|m| m.as_str()), Some("src/lib.rs"));
+
+        let covered = "src/main.rs                         2                 0   100.00%           1                 0   100.00%           3                 0   100.00%           0                 0         -";
+        assert!(!regex.is_match(covered));
+        let total = "TOTAL                               6                 2    66.67%           3                 1    66.67%          10                 3    70.00%           0                 0         -";
+        assert!(!regex.is_match(total));
+    }
+}
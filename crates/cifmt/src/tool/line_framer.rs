@@ -0,0 +1,426 @@
+//! Line framing for newline-delimited tool output.
+//!
+//! Most tools emit one message per line (e.g., JSON Lines). Since the input is
+//! provided in arbitrary chunks, a line may be split across several chunks.
+//! The [`LineFramer`] buffers incomplete lines until they are terminated.
+//!
+//! Both `\n` and `\r\n` line endings are accepted, as tools running on Windows
+//! commonly emit the latter. A UTF-8 byte order mark at the start of the
+//! stream is skipped.
+
+use core::mem;
+
+/// Default maximum length of a single line, in bytes.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 16 * 1024 * 1024;
+
+/// The UTF-8 encoding of the byte order mark.
+pub(crate) const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// A line produced by a [`LineFramer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Line<'a> {
+    /// A complete line, without its terminating newline (or carriage return
+    /// and newline).
+    Complete(&'a [u8]),
+    /// A line which exceeded the maximum line length, cut to its first bytes
+    /// within the limit on the boundary of a UTF-8 character. The rest of the
+    /// line is discarded.
+    Overlong(&'a [u8]),
+}
+
+/// Splits a stream of bytes into lines.
+///
+/// Data is provided through [`LineFramer::push`], and complete lines are
+/// retrieved through [`LineFramer::next_line`]. Any trailing incomplete line
+/// is retained until a subsequent push completes it.
+///
+/// Lines are borrowed directly from the internal buffer. Consumed lines are
+/// only discarded at the start of the next push, so extracting `n` lines from a
+/// chunk is linear in the size of the chunk rather than quadratic.
+///
+/// To bound memory usage, lines longer than the maximum line length are
+/// reported as [`Line::Overlong`], holding the first bytes of the line, as
+/// soon as the limit is exceeded, and the remainder of the line is discarded
+/// as it arrives.
+#[derive(Debug, Clone)]
+pub(crate) struct LineFramer {
+    /// Buffered data, of which everything before `start` has been consumed.
+    buffer: Vec<u8>,
+    /// Offset of the first unconsumed byte in the buffer.
+    start: usize,
+    /// Offset up to which the buffer is known not to contain a newline.
+    ///
+    /// This avoids rescanning an incomplete line each time more data is
+    /// pushed.
+    scanned: usize,
+    /// The maximum length of a line, in bytes.
+    max_line_length: usize,
+    /// Whether the remainder of an overlong line is being discarded.
+    discarding: bool,
+    /// Whether the start of the stream may still hold a byte order mark.
+    at_stream_start: bool,
+    /// Number of bytes drained from the buffer since the framer was created.
+    drained: u64,
+    /// Offset of the start of the current stream, relative to the same
+    /// origin as `drained`.
+    stream_start: u64,
+    /// Number of lines consumed in the current stream.
+    lines: usize,
+}
+
+impl Default for LineFramer {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            start: 0,
+            scanned: 0,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            discarding: false,
+            at_stream_start: true,
+            drained: 0,
+            stream_start: 0,
+            lines: 0,
+        }
+    }
+}
+
+impl LineFramer {
+    /// Set the maximum length of a line.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum length of a line in bytes, excluding the
+    ///   terminating newline.
+    pub(crate) fn set_max_line_length(&mut self, max: usize) {
+        self.max_line_length = max;
+    }
+
+    /// The maximum length of a line, in bytes.
+    pub(crate) fn max_line_length(&self) -> usize {
+        self.max_line_length
+    }
+
+    /// The position of the first unconsumed byte in the stream.
+    ///
+    /// # Returns
+    ///
+    /// The number of the line starting at this position (1-indexed), and its
+    /// offset from the start of the stream in bytes.
+    pub(crate) fn position(&self) -> (usize, u64) {
+        let start = u64::try_from(self.start).unwrap_or(u64::MAX);
+        let offset = self
+            .drained
+            .saturating_add(start)
+            .saturating_sub(self.stream_start);
+        (self.lines.saturating_add(1), offset)
+    }
+
+    /// Append data to the framer.
+    ///
+    /// Lines consumed since the last push are discarded first, so that only
+    /// the trailing incomplete line (if any) is moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The next chunk of the tool's output.
+    pub(crate) fn push(&mut self, buf: &[u8]) {
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.drained = self
+                .drained
+                .saturating_add(u64::try_from(self.start).unwrap_or(u64::MAX));
+            self.scanned = self.scanned.saturating_sub(self.start);
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(buf);
+
+        if self.at_stream_start {
+            // The byte order mark may be split across pushes, in which case
+            // the check is repeated on the next push.
+            let head = self.buffer.get(..UTF8_BOM.len()).unwrap_or(&self.buffer);
+            if head.len() == UTF8_BOM.len() || !UTF8_BOM.starts_with(head) {
+                self.at_stream_start = false;
+                if head == UTF8_BOM {
+                    self.start = UTF8_BOM.len();
+                    self.scanned = self.scanned.max(self.start);
+                }
+            }
+        }
+    }
+
+    /// Take the next line from the framer.
+    ///
+    /// # Returns
+    ///
+    /// The next line, or `None` if the buffer does not contain a complete
+    /// line and the incomplete line (if any) has not exceeded the maximum
+    /// line length.
+    pub(crate) fn next_line(&mut self) -> Option<Line<'_>> {
+        loop {
+            let unscanned = self.buffer.get(self.scanned..)?;
+            let Some(offset) = memchr::memchr(b'\n', unscanned) else {
+                let pending = self.buffer.len().saturating_sub(self.start);
+                if self.discarding || pending > self.max_line_length {
+                    // Cut the incomplete line; it is reported only once.
+                    let first = !self.discarding;
+                    self.discarding = true;
+                    let line_start = mem::replace(&mut self.start, self.buffer.len());
+                    self.scanned = self.start;
+                    if !first {
+                        return None;
+                    }
+                    let content = self.buffer.get(line_start..)?;
+                    return Some(Line::Overlong(truncate(content, self.max_line_length)));
+                }
+                self.scanned = self.buffer.len();
+                return None;
+            };
+
+            let newline_pos = self.scanned.saturating_add(offset);
+            let line_start = self.start;
+            self.start = newline_pos.saturating_add(1);
+            self.scanned = self.start;
+            self.lines = self.lines.saturating_add(1);
+
+            if self.discarding {
+                // End of an overlong line which has already been reported.
+                self.discarding = false;
+                continue;
+            }
+            let content = self.buffer.get(line_start..newline_pos)?;
+            let line = content.strip_suffix(b"\r").unwrap_or(content);
+            if line.len() > self.max_line_length {
+                return Some(Line::Overlong(truncate(line, self.max_line_length)));
+            }
+            return Some(Line::Complete(line));
+        }
+    }
+
+    /// Take the final line from the framer at the end of the stream.
+    ///
+    /// Any data following the last newline forms the final line, which is
+    /// returned even though it is not terminated. The framer is then reset
+    /// for a new stream.
+    ///
+    /// # Returns
+    ///
+    /// The final line, or `None` if the stream ended with a newline, or with
+    /// the remainder of an overlong line which has already been reported.
+    pub(crate) fn finish(&mut self) -> Option<Line<'_>> {
+        let line_start = mem::replace(&mut self.start, self.buffer.len());
+        self.scanned = self.start;
+        self.at_stream_start = true;
+        self.lines = 0;
+        self.stream_start = self
+            .drained
+            .saturating_add(u64::try_from(self.start).unwrap_or(u64::MAX));
+        if mem::take(&mut self.discarding) {
+            return None;
+        }
+
+        let content = self.buffer.get(line_start..)?;
+        let line = content.strip_suffix(b"\r").unwrap_or(content);
+        if line.is_empty() {
+            None
+        } else if line.len() > self.max_line_length {
+            Some(Line::Overlong(truncate(line, self.max_line_length)))
+        } else {
+            Some(Line::Complete(line))
+        }
+    }
+}
+
+/// The first bytes of a line, up to the maximum length of a line.
+///
+/// The line is cut on the boundary of a UTF-8 character, so that a character
+/// is not split. A character spans at most four bytes, so at most three bytes
+/// are given up; binary output is cut at the maximum length regardless.
+fn truncate(line: &[u8], max_length: usize) -> &[u8] {
+    let mut end = max_length.min(line.len());
+    for _ in 0..3_u8 {
+        if line.get(end).is_none_or(|&byte| byte & 0xC0 != 0x80) {
+            break;
+        }
+        end = end.saturating_sub(1);
+    }
+    line.get(..end).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Line, LineFramer};
+
+    #[test]
+    fn split_across_pushes() {
+        let mut framer = LineFramer::default();
+
+        framer.push(b"first\nsec");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"first")));
+        assert_eq!(framer.next_line(), None);
+
+        framer.push(b"ond\n\nthird");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"second")));
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"")));
+        assert_eq!(framer.next_line(), None);
+
+        framer.push(b"\n");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"third")));
+        assert_eq!(framer.next_line(), None);
+    }
+
+    #[test]
+    fn long_line_in_small_chunks() {
+        let mut framer = LineFramer::default();
+
+        for _ in 0..1000_usize {
+            framer.push(b"ab");
+            assert_eq!(framer.next_line(), None);
+        }
+        framer.push(b"\nc\n");
+
+        assert_eq!(
+            framer.next_line(),
+            Some(Line::Complete("ab".repeat(1000).as_bytes()))
+        );
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"c")));
+        assert_eq!(framer.next_line(), None);
+    }
+
+    #[test]
+    fn overlong_line() {
+        let mut framer = LineFramer::default();
+        framer.set_max_line_length(4);
+
+        // A complete line exceeding the limit
+        framer.push(b"abcd\nabcde\nab");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"abcd")));
+        assert_eq!(framer.next_line(), Some(Line::Overlong(b"abcd")));
+        assert_eq!(framer.next_line(), None);
+
+        // An incomplete line exceeding the limit is reported once, and
+        // discarded until it ends.
+        framer.push(b"cde");
+        assert_eq!(framer.next_line(), Some(Line::Overlong(b"abcd")));
+        assert_eq!(framer.next_line(), None);
+        framer.push(b"fghijklmnop");
+        assert_eq!(framer.next_line(), None);
+        framer.push(b"qrs\nxyz\n");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"xyz")));
+        assert_eq!(framer.next_line(), None);
+    }
+
+    #[test]
+    fn overlong_line_utf8() {
+        let mut framer = LineFramer::default();
+        framer.set_max_line_length(5);
+
+        // The line is cut before the character spanning the limit.
+        framer.push("abc€\nab€cd\n".as_bytes());
+        assert_eq!(framer.next_line(), Some(Line::Overlong(b"abc")));
+        assert_eq!(framer.next_line(), Some(Line::Overlong("ab€".as_bytes())));
+
+        // Binary output is cut at the limit.
+        framer.push(b"\x80\x80\x80\x80\x80\x80\x80");
+        assert_eq!(framer.finish(), Some(Line::Overlong(b"\x80\x80")));
+    }
+
+    #[test]
+    fn crlf() {
+        let mut framer = LineFramer::default();
+
+        framer.push(b"first\r\nsecond\r");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"first")));
+        assert_eq!(framer.next_line(), None);
+
+        framer.push(b"\n\r\ninner\rcr\n");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"second")));
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"")));
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"inner\rcr")));
+        assert_eq!(framer.next_line(), None);
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        // Every possible split point must yield the same lines.
+        let input = b"\xEF\xBB\xBF{}\n\xEF\xBB\xBF{}\n";
+        for split in 0..=input.len() {
+            let (first, second) = input.split_at(split);
+            let mut framer = LineFramer::default();
+            let mut lines = Vec::new();
+            for chunk in [first, second] {
+                framer.push(chunk);
+                while let Some(line) = framer.next_line() {
+                    lines.push(match line {
+                        Line::Complete(content) => Some(content.to_vec()),
+                        Line::Overlong(_) => None,
+                    });
+                }
+            }
+            assert_eq!(
+                lines,
+                [Some(b"{}".to_vec()), Some(b"\xEF\xBB\xBF{}".to_vec())],
+                "split at {split}"
+            );
+        }
+    }
+
+    #[test]
+    fn finish() {
+        let mut framer = LineFramer::default();
+        framer.set_max_line_length(5);
+
+        framer.push(b"abc\nlast\r");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"abc")));
+        assert_eq!(framer.next_line(), None);
+        assert_eq!(framer.finish(), Some(Line::Complete(b"last")));
+        assert_eq!(framer.finish(), None);
+
+        // The framer is reset for a new stream.
+        framer.push(b"\xEF\xBB\xBFabcdef");
+        assert_eq!(framer.next_line(), Some(Line::Overlong(b"abcde")));
+        assert_eq!(framer.finish(), None);
+        framer.push(b"ab\n\n");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"ab")));
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"")));
+        assert_eq!(framer.finish(), None);
+    }
+
+    #[test]
+    fn position() {
+        let mut framer = LineFramer::default();
+        framer.set_max_line_length(4);
+        assert_eq!(framer.position(), (1, 0));
+
+        framer.push(b"ab\r\nabcdef");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"ab")));
+        assert_eq!(framer.position(), (2, 4));
+        assert_eq!(framer.next_line(), Some(Line::Overlong(b"abcd")));
+
+        framer.push(b"gh\nxy");
+        assert_eq!(framer.next_line(), None);
+        assert_eq!(framer.position(), (3, 13));
+        assert_eq!(framer.finish(), Some(Line::Complete(b"xy")));
+
+        // Positions are relative to the start of the new stream.
+        assert_eq!(framer.position(), (1, 0));
+        framer.push(b"\xEF\xBB\xBFa\nb");
+        assert_eq!(framer.next_line(), Some(Line::Complete(b"a")));
+        assert_eq!(framer.position(), (2, 5));
+    }
+
+    #[test]
+    fn bounded_memory() {
+        let mut framer = LineFramer::default();
+        framer.set_max_line_length(16);
+
+        let garbage = [0xff_u8; 64];
+        for _ in 0..100_usize {
+            framer.push(&garbage);
+            while framer.next_line().is_some() {}
+        }
+        assert!(framer.buffer.len() <= 16 + garbage.len());
+    }
+}
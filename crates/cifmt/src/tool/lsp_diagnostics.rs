@@ -0,0 +1,873 @@
+//! LSP diagnostics.
+//!
+//! Support for parsing a stream of the diagnostics of the Language Server
+//! Protocol, one JSON object per line, so that any tool which speaks the
+//! protocol (or a script which mimics it) can have its diagnostics annotated.
+//! Each line is the parameters of a `textDocument/publishDiagnostics`
+//! notification (e.g., `{"uri": "file:///app/main.py", "diagnostics": [...]}`),
+//! or the whole notification, with the parameters under `params`.
+//!
+//! Each diagnostic is annotated from the line and column at which its range
+//! starts to those at which it ends, where the platform supports it (e.g.,
+//! GitHub Actions), counted from one rather than from zero as in the
+//! protocol. Its severity gives its level: errors, warnings, and notices for
+//! information and hints, with diagnostics without a severity being errors.
+//! It is titled after its source and code (e.g., `pyright(reportMissingImports)`),
+//! with the link to the documentation of its code and its related
+//! information following its message. As servers publish the diagnostics of
+//! a document again whenever they analyse it, a diagnostic which was already
+//! annotated is skipped.
+//!
+//! Any other output is passed through as plain text output.
+
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::{
+        Annotation, AnnotationParams, AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Issue,
+        IssueParams, Plain, Platform, Terminal,
+    },
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+        xcodebuild::percent_decode,
+    },
+};
+
+/// A message parsed from a stream of LSP diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LspMessage {
+    /// A diagnostic of a document.
+    Diagnostic(Box<LspDiagnostic>),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// An incomplete JSON object at the end of the output.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not a notification of diagnostics.
+    TextOutput(TextOutput),
+}
+
+/// The severity of a diagnostic, as numbered by the protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LspSeverity {
+    /// An error (`1`), or a diagnostic without a severity.
+    Error,
+    /// A warning (`2`).
+    Warning,
+    /// Information (`3`).
+    Information,
+    /// A hint (`4`), or any severity which is not known.
+    Hint,
+}
+
+impl From<Option<u8>> for LspSeverity {
+    #[inline]
+    fn from(severity: Option<u8>) -> Self {
+        match severity {
+            None | Some(1) => Self::Error,
+            Some(2) => Self::Warning,
+            Some(3) => Self::Information,
+            Some(_) => Self::Hint,
+        }
+    }
+}
+
+/// A diagnostic of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LspDiagnostic {
+    /// The file of the document, from its URI (e.g., `/app/main.py` for
+    /// `file:///app/main.py`).
+    pub file: String,
+    /// The line at which the diagnostic starts.
+    pub line: u32,
+    /// The column at which the diagnostic starts.
+    pub column: u32,
+    /// The line at which the diagnostic ends.
+    pub end_line: u32,
+    /// The column at which the diagnostic ends.
+    pub end_column: u32,
+    /// The severity of the diagnostic.
+    pub severity: LspSeverity,
+    /// The code of the diagnostic (e.g., `reportMissingImports`), if any.
+    pub code: Option<String>,
+    /// The tool which reported the diagnostic (e.g., `pyright`), if given.
+    pub source: Option<String>,
+    /// The message of the diagnostic.
+    pub message: String,
+    /// The link to the documentation of the code, if given.
+    pub href: Option<String>,
+    /// The locations related to the diagnostic, with their messages (e.g.,
+    /// `/app/main.py:3: first defined here`).
+    pub related: Vec<String>,
+}
+
+impl LspDiagnostic {
+    /// The title of the annotation, from the source and code of the
+    /// diagnostic (e.g., `pyright(reportMissingImports)`), if either is
+    /// given.
+    fn title(&self) -> Option<String> {
+        match (&self.source, &self.code) {
+            (Some(source), Some(code)) => Some(format!("{source}({code})")),
+            (Some(name), None) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// The message of the annotation, followed by the link to the
+    /// documentation of the code and the related information.
+    fn text(&self) -> String {
+        let mut lines = vec![self.message.clone()];
+        if let Some(href) = &self.href {
+            lines.push(format!("{}: {href}", Title::Documentation));
+        }
+        lines.extend(self.related.iter().cloned());
+        lines.join("\n")
+    }
+
+    /// Format the diagnostic with the building blocks of the platform, which
+    /// only locate it at its start, not its end.
+    fn annotate<P: Platform>(&self) -> String {
+        P::annotate(
+            self.severity(),
+            self.text(),
+            Some((&self.file, self.line, Some(self.column))),
+            self.title().as_deref(),
+        )
+    }
+}
+
+impl CiMessage<Plain> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        self.annotate::<Plain>()
+    }
+}
+
+/// The diagnostic is annotated over the columns which it spans.
+impl CiMessage<GitHub> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        let command = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Debug | Severity::Notice => "notice",
+        };
+        let title = self.title();
+        Annotation {
+            command,
+            params: AnnotationParams {
+                file: Some(&self.file),
+                line: Some(self.line),
+                col: Some(self.column),
+                end_line: Some(self.end_line),
+                end_column: Some(self.end_column),
+                title: title.as_deref(),
+            },
+            message: self.text(),
+        }
+        .to_string()
+        .trim_end_matches('\n')
+        .to_owned()
+    }
+}
+
+/// Errors and warnings are reported as issues at their column, with their
+/// code. Notices are logged as plain text, as Azure has no equivalent.
+impl CiMessage<AzureDevOps> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        let kind = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Debug | Severity::Notice => return self.annotate::<AzureDevOps>(),
+        };
+        Issue {
+            kind,
+            params: IssueParams {
+                source_path: Some(&self.file),
+                line_number: Some(self.line),
+                column_number: Some(self.column),
+                code: self.code.as_deref(),
+            },
+            message: self.text(),
+        }
+        .to_string()
+        .trim_end_matches('\n')
+        .to_owned()
+    }
+}
+
+impl CiMessage<Buildkite> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        self.annotate::<Buildkite>()
+    }
+}
+
+impl CiMessage<CodeBuild> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        self.annotate::<CodeBuild>()
+    }
+}
+
+impl CiMessage<CloudBuild> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        self.annotate::<CloudBuild>()
+    }
+}
+
+impl CiMessage<Terminal> for LspDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        self.annotate::<Terminal>()
+    }
+}
+
+impl Classify for LspDiagnostic {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.severity {
+            LspSeverity::Error => Severity::Error,
+            LspSeverity::Warning => Severity::Warning,
+            LspSeverity::Information | LspSeverity::Hint => Severity::Notice,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for LspMessage
+where
+    LspDiagnostic: CiMessage<P>,
+    OverlongLine: CiMessage<P>,
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => <LspDiagnostic as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for LspMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Diagnostic(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Diagnostic(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::Diagnostic(_) | Self::OverlongLine(_) | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.sort_key(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.code(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.file(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for LspMessage {}
+
+impl Captured for LspMessage {}
+
+/// A line of the stream: the parameters of a notification, or the whole
+/// notification.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Notification {
+    /// A `textDocument/publishDiagnostics` notification.
+    Message {
+        /// The parameters of the notification.
+        params: PublishDiagnosticsParams,
+    },
+    /// The parameters of a notification.
+    Params(PublishDiagnosticsParams),
+}
+
+/// The diagnostics of a document.
+#[derive(Debug, Deserialize)]
+struct PublishDiagnosticsParams {
+    /// The URI of the document.
+    uri: String,
+    /// The diagnostics of the document.
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A position in a document, counted from zero.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Position {
+    /// The line of the position.
+    line: u32,
+    /// The character of the position within its line.
+    character: u32,
+}
+
+/// A range of a document.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Range {
+    /// The start of the range.
+    start: Position,
+    /// The end of the range.
+    end: Position,
+}
+
+/// The code of a diagnostic, a number or a string.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Code {
+    /// A numbered code.
+    Number(i64),
+    /// A named code.
+    Text(String),
+}
+
+/// The description of the code of a diagnostic.
+#[derive(Debug, Deserialize)]
+struct CodeDescription {
+    /// The link to the documentation of the code.
+    href: String,
+}
+
+/// A location of a document.
+#[derive(Debug, Deserialize)]
+struct Location {
+    /// The URI of the document.
+    uri: String,
+    /// The range of the location.
+    range: Range,
+}
+
+/// A location related to a diagnostic.
+#[derive(Debug, Deserialize)]
+struct RelatedInformation {
+    /// The location.
+    location: Location,
+    /// The message of the location.
+    message: String,
+}
+
+/// A diagnostic, as given by the protocol.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Diagnostic {
+    /// The range of the diagnostic.
+    range: Range,
+    /// The severity of the diagnostic, if given.
+    severity: Option<u8>,
+    /// The code of the diagnostic, if any.
+    code: Option<Code>,
+    /// The description of the code, if given.
+    code_description: Option<CodeDescription>,
+    /// The tool which reported the diagnostic, if given.
+    source: Option<String>,
+    /// The message of the diagnostic.
+    message: String,
+    /// The locations related to the diagnostic.
+    #[serde(default)]
+    related_information: Vec<RelatedInformation>,
+}
+
+/// The file of a document from its URI (e.g., `/app/my file.py` for
+/// `file:///app/my%20file.py`, or `C:/app/main.py` for
+/// `file:///c%3A/app/main.py`); URIs of other schemes are kept as given.
+fn uri_to_file(uri: &str) -> String {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return uri.to_owned();
+    };
+    let decoded = percent_decode(path);
+    // A drive letter follows the slash of the path on Windows
+    let bytes = decoded.as_bytes();
+    match bytes {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => {
+            decoded.get(1..).unwrap_or(&decoded).to_owned()
+        }
+        _ => decoded,
+    }
+}
+
+impl Diagnostic {
+    /// Convert the diagnostic of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The file of the document.
+    fn into_diagnostic(self, file: &str) -> LspDiagnostic {
+        let Range { start, end } = self.range;
+        LspDiagnostic {
+            file: file.to_owned(),
+            line: start.line.saturating_add(1),
+            column: start.character.saturating_add(1),
+            end_line: end.line.saturating_add(1),
+            end_column: end.character.saturating_add(1),
+            severity: self.severity.into(),
+            code: self.code.map(|code| match code {
+                Code::Number(number) => number.to_string(),
+                Code::Text(text) => text,
+            }),
+            source: self.source.filter(|source| !source.is_empty()),
+            message: self.message,
+            href: self.code_description.map(|description| description.href),
+            related: self
+                .related_information
+                .into_iter()
+                .map(|related| {
+                    format!(
+                        "{}:{}: {}",
+                        uri_to_file(&related.location.uri),
+                        related.location.range.start.line.saturating_add(1),
+                        related.message
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Whether a line of the output may be a notification of diagnostics.
+fn is_notification(line: &[u8]) -> bool {
+    line.first() == Some(&b'{')
+}
+
+/// The diagnostics annotated so far.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The diagnostics annotated so far, by their file, range, code and
+    /// message.
+    seen: HashSet<(String, [u32; 4], Option<String>, String)>,
+}
+
+impl Reader {
+    /// Parse a single line of the stream.
+    ///
+    /// # Returns
+    ///
+    /// The messages parsed from the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<LspMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                let mut results = vec![Ok(LspMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        // Skip empty lines
+        if line.is_empty() {
+            return Vec::new();
+        }
+
+        if !is_notification(line) {
+            return text
+                .handle(line)
+                .map(|output| Ok(LspMessage::TextOutput(output)))
+                .into_iter()
+                .collect();
+        }
+
+        let params = match serde_json::from_slice::<Notification>(line) {
+            Ok(Notification::Message { params } | Notification::Params(params)) => params,
+            Err(e) => return vec![Err(e)],
+        };
+        let file = uri_to_file(&params.uri);
+        params
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.into_diagnostic(&file))
+            .filter(|diagnostic| {
+                self.seen.insert((
+                    diagnostic.file.clone(),
+                    [
+                        diagnostic.line,
+                        diagnostic.column,
+                        diagnostic.end_line,
+                        diagnostic.end_column,
+                    ],
+                    diagnostic.code.clone(),
+                    diagnostic.message.clone(),
+                ))
+            })
+            .map(|diagnostic| Ok(LspMessage::Diagnostic(Box::new(diagnostic))))
+            .collect()
+    }
+}
+
+/// Tool implementation for parsing a stream of LSP diagnostics.
+#[derive(Debug, Clone, Default)]
+pub struct LspDiagnostics {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not notifications of diagnostics.
+    text: TextLines,
+    /// The diagnostics annotated so far.
+    reader: Reader,
+}
+
+impl Detect for LspDiagnostics {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        // The fields of the parameters of the notifications, or the method
+        // of the whole notifications
+        let field = |name: &[u8]| memchr::memmem::find(sample, name).is_some();
+        ((field(b"\"uri\":") && field(b"\"diagnostics\":") && field(b"\"range\":"))
+            || field(b"\"textDocument/publishDiagnostics\""))
+        .then(Self::default)
+    }
+}
+
+impl LspDiagnostics {
+    /// A problem matcher for a stream of LSP diagnostics.
+    ///
+    /// Each line gives the URI of a document and its diagnostics. As a
+    /// problem matcher can neither match the many diagnostics of a line nor
+    /// convert the positions of the protocol, which are counted from zero,
+    /// only the file of the document and the message of its first diagnostic
+    /// are matched, which is reported as an error.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-lsp-diagnostics",
+            vec![
+                Pattern::new(
+                    r#"^\{.*"uri":\s*"file://([^"]+)".*?"message":\s*"((?:[^"\\]|\\.)*)""#,
+                )
+                .file(1)
+                .message(2),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for LspDiagnostics {
+    type Message = LspMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "lsp-diagnostics"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            for result in self.reader.parse_line(line, max_length, &mut self.text) {
+                results.push(result.or_else(|error| {
+                    TruncatedLine::from_final_line(position, line, error)
+                        .map(LspMessage::TruncatedLine)
+                }));
+            }
+        }
+
+        if let Some(output) = self.text.finish() {
+            results.push(Ok(LspMessage::TextOutput(output)));
+        }
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for LspDiagnostics
+where
+    LspMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{LspDiagnostics, LspMessage, LspSeverity, uri_to_file};
+    use crate::ci::{AzureDevOps, GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{CargoCheck, Detect as _, Tool as _};
+
+    const STREAM: &str = r#"{"uri":"file:///app/src/main.py","diagnostics":[{"range":{"start":{"line":0,"character":7},"end":{"line":0,"character":15}},"severity":1,"code":"reportMissingImports","codeDescription":{"href":"https://github.com/microsoft/pyright/blob/main/docs/configuration.md#reportMissingImports"},"source":"Pyright","message":"Import \"requests\" could not be resolved"},{"range":{"start":{"line":4,"character":4},"end":{"line":4,"character":10}},"severity":2,"source":"Pyright","message":"Variable \"unused\" is not accessed","tags":[1]}]}
+{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{"uri":"file:///app/src/lib.rs","version":3,"diagnostics":[{"range":{"start":{"line":9,"character":8},"end":{"line":9,"character":9}},"severity":4,"code":"E0384","source":"rustc","message":"cannot assign twice to immutable variable `x`","relatedInformation":[{"location":{"uri":"file:///app/src/lib.rs","range":{"start":{"line":8,"character":8},"end":{"line":8,"character":9}}},"message":"first assignment to `x`"}]}]}}
+{"uri":"file:///app/src/main.py","diagnostics":[{"range":{"start":{"line":0,"character":7},"end":{"line":0,"character":15}},"severity":1,"code":"reportMissingImports","codeDescription":{"href":"https://github.com/microsoft/pyright/blob/main/docs/configuration.md#reportMissingImports"},"source":"Pyright","message":"Import \"requests\" could not be resolved"}]}
+{"uri":"untitled:Untitled-1","diagnostics":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":3}},"severity":3,"code":42,"message":"Unknown word"}]}
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<LspMessage> {
+        let mut tool = LspDiagnostics::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        LspMessage: CiMessage<P>,
+    {
+        let mut tool = LspDiagnostics::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <LspMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(STREAM), @r#"
+        ::error file=/app/src/main.py,line=1,col=8,endLine=1,endColumn=16,title=Pyright(reportMissingImports)::Import "requests" could not be resolved%0ADocumentation: https://github.com/microsoft/pyright/blob/main/docs/configuration.md#reportMissingImports
+        ::warning file=/app/src/main.py,line=5,col=5,endLine=5,endColumn=11,title=Pyright::Variable "unused" is not accessed
+        ::notice file=/app/src/lib.rs,line=10,col=9,endLine=10,endColumn=10,title=rustc(E0384)::cannot assign twice to immutable variable `x`%0A/app/src/lib.rs:9: first assignment to `x`
+        ::notice file=untitled%3AUntitled-1,line=1,col=1,endLine=1,endColumn=4,title=42::Unknown word
+        "#);
+    }
+
+    #[test]
+    fn azure() {
+        insta::assert_snapshot!(format::<AzureDevOps>(STREAM), @r#"
+        ##vso[task.logissue type=error;sourcepath=/app/src/main.py;linenumber=1;columnnumber=8;code=reportMissingImports]Import "requests" could not be resolved%0ADocumentation: https://github.com/microsoft/pyright/blob/main/docs/configuration.md#reportMissingImports
+        ##vso[task.logissue type=warning;sourcepath=/app/src/main.py;linenumber=5;columnnumber=5]Variable "unused" is not accessed
+        /app/src/lib.rs:10:9: notice:  rustc(E0384): cannot assign twice to immutable variable `x`\n/app/src/lib.rs:9: first assignment to `x`
+        untitled:Untitled-1:1:1: notice:  42: Unknown word
+        "#);
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(STREAM), @r#"
+        /app/src/main.py:1:8: error:   Pyright(reportMissingImports): Import "requests" could not be resolved\nDocumentation: https://github.com/microsoft/pyright/blob/main/docs/configuration.md#reportMissingImports
+        /app/src/main.py:5:5: warning: Pyright: Variable "unused" is not accessed
+        /app/src/lib.rs:10:9: notice:  rustc(E0384): cannot assign twice to immutable variable `x`\n/app/src/lib.rs:9: first assignment to `x`
+        untitled:Untitled-1:1:1: notice:  42: Unknown word
+        "#);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let messages = parse(STREAM, 17);
+        assert_eq!(messages, parse(STREAM, STREAM.len()));
+        let diagnostics: Vec<_> = messages
+            .iter()
+            .map(|msg| match msg {
+                LspMessage::Diagnostic(diagnostic) => diagnostic,
+                LspMessage::OverlongLine(_)
+                | LspMessage::TruncatedLine(_)
+                | LspMessage::TextOutput(_) => panic!("expected only diagnostics"),
+            })
+            .collect();
+        // The diagnostic published again is skipped
+        assert_eq!(diagnostics.len(), 4);
+        let [missing, unused, assigned, word] = diagnostics.as_slice() else {
+            panic!("expected four diagnostics");
+        };
+        assert_eq!((missing.line, missing.column), (1, 8));
+        assert_eq!((missing.end_line, missing.end_column), (1, 16));
+        assert_eq!(unused.severity, LspSeverity::Warning);
+        assert_eq!(unused.code(), None);
+        assert_eq!(assigned.severity, LspSeverity::Hint);
+        assert_eq!(
+            assigned.related,
+            ["/app/src/lib.rs:9: first assignment to `x`"]
+        );
+        assert_eq!(word.file, "untitled:Untitled-1");
+        assert_eq!(word.code(), Some("42"));
+    }
+
+    #[test]
+    fn uris() {
+        assert_eq!(uri_to_file("file:///app/my%20file.py"), "/app/my file.py");
+        assert_eq!(uri_to_file("file:///c%3A/app/main.py"), "c:/app/main.py");
+        assert_eq!(uri_to_file("file:///C:/app/main.py"), "C:/app/main.py");
+        assert_eq!(uri_to_file("untitled:Untitled-1"), "untitled:Untitled-1");
+    }
+
+    #[test]
+    fn text() {
+        let output = "Starting the server\n{\"uri\":\"file:///app/a.py\",\"diagnostics\":[]}\n";
+        let messages = parse(output, output.len());
+        assert!(
+            messages
+                .iter()
+                .all(|msg| matches!(msg, LspMessage::TextOutput(_)))
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = STREAM.get(..120).expect("valid index");
+        insta::assert_snapshot!(format::<Plain>(cut), @"warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 120 bytes of an incomplete message: EOF while parsing an object at line 1 column 120");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(LspDiagnostics::detect(STREAM.as_bytes()).is_some());
+        assert!(LspDiagnostics::detect(b"{\"uri\": \"file:///a\"}\n").is_none());
+        assert!(CargoCheck::detect(STREAM.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = LspDiagnostics::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let line = STREAM.lines().next().expect("a line");
+        let captures = regex.captures(line).expect("notification not matched");
+        assert_eq!(
+            captures.get(1).map(|m| m.as_str()),
+            Some("/app/src/main.py")
+        );
+        assert_eq!(
+            captures.get(2).map(|m| m.as_str()),
+            Some(r#"Import \"requests\" could not be resolved"#)
+        );
+    }
+}
@@ -0,0 +1,1094 @@
+//! Miri output format.
+//!
+//! Support for parsing the output of `cargo miri test` (or `cargo miri run`),
+//! which interprets the tests of a crate to detect undefined behavior. With
+//! `--message-format json`, the messages of cargo are those of `cargo check`
+//! (see [`CargoCheck`]); the reports of the interpreted program are printed
+//! by Miri itself, either as the human-readable diagnostics of rustc (when
+//! stderr is redirected to stdout), or as bare JSON diagnostics of rustc
+//! (when Miri is passed `--error-format json`), without the envelope of
+//! cargo.
+//!
+//! A report of undefined behavior (`error: Undefined Behavior: ...`) is
+//! annotated as an error, titled after the kind of undefined behavior (e.g.,
+//! `Undefined Behavior: Data Race`), on the first location of its backtrace
+//! within the checkout (or failing that, the location reported by Miri). The
+//! report itself follows the annotation in a group. The diagnostics of
+//! undefined behavior within the JSON messages of cargo are titled the same
+//! way.
+
+use core::mem;
+use std::io::BufRead as _;
+
+use serde::Deserialize;
+
+use crate::{
+    capture::{Captured, OutputPolicy},
+    catalog::Title,
+    ci::{Platform, is_in_checkout},
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    sarif::Fix,
+    timeline::{Timed, Timing},
+    tool::{
+        Detect, DynTool, Tool, ToolOption, ToolOptions,
+        cargo_check::{CargoCheck, CargoMessage},
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::Passthrough,
+    },
+};
+
+/// The prefix of the message of a diagnostic reporting undefined behavior.
+const UB_PREFIX: &str = "Undefined Behavior: ";
+
+/// The start of a human-readable report of undefined behavior.
+const UB_MARKER: &str = "error: Undefined Behavior: ";
+
+/// A message parsed from the output of Miri.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MiriMessage {
+    /// A message of cargo.
+    Cargo(Box<CargoMessage>),
+
+    /// Undefined behavior detected while interpreting the program.
+    UndefinedBehavior(UndefinedBehavior),
+}
+
+/// The kind of undefined behavior reported by Miri, inferred from its
+/// message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UbKind {
+    /// Conflicting accesses to the same memory from several threads.
+    DataRace,
+    /// An access violating the aliasing model (Stacked or Tree Borrows).
+    Aliasing,
+    /// An access through a pointer to memory which has been freed.
+    Dangling,
+    /// An access beyond the bounds of an allocation.
+    OutOfBounds,
+    /// An access through a pointer which is not sufficiently aligned.
+    Misaligned,
+    /// A value which is not valid for its type (e.g., a `bool` of `2`).
+    InvalidValue,
+    /// A read of memory which was never initialized.
+    Uninitialized,
+    /// Code which was declared unreachable was reached.
+    Unreachable,
+    /// Any other undefined behavior.
+    Other,
+}
+
+impl UbKind {
+    /// Infer the kind of undefined behavior from the message of Miri.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message, without the `Undefined Behavior: ` prefix.
+    fn of(message: &str) -> Self {
+        let kinds = [
+            (Self::DataRace, &["Data race"][..]),
+            (
+                Self::Aliasing,
+                &[
+                    "borrow stack",
+                    "Stacked Borrows",
+                    "Tree Borrows",
+                    "is forbidden",
+                ],
+            ),
+            (Self::Dangling, &["dangling", "has been freed"]),
+            (
+                Self::OutOfBounds,
+                &["out-of-bounds", "beyond the end of the allocation"],
+            ),
+            (Self::Misaligned, &["alignment"]),
+            (Self::InvalidValue, &["invalid value"]),
+            (Self::Uninitialized, &["uninitialized"]),
+            (Self::Unreachable, &["unreachable"]),
+        ];
+        kinds
+            .into_iter()
+            .find(|(_, needles)| needles.iter().any(|needle| message.contains(needle)))
+            .map_or(Self::Other, |(kind, _)| kind)
+    }
+
+    /// The name of the kind, as it appears in the title of the annotation,
+    /// or `None` for [`UbKind::Other`].
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> Option<&'static str> {
+        match self {
+            Self::DataRace => Some("Data Race"),
+            Self::Aliasing => Some("Aliasing Violation"),
+            Self::Dangling => Some("Dangling Pointer"),
+            Self::OutOfBounds => Some("Out-of-Bounds Access"),
+            Self::Misaligned => Some("Misaligned Pointer"),
+            Self::InvalidValue => Some("Invalid Value"),
+            Self::Uninitialized => Some("Uninitialized Memory"),
+            Self::Unreachable => Some("Unreachable Code"),
+            Self::Other => None,
+        }
+    }
+
+    /// The title of the annotation of undefined behavior of this kind (e.g.,
+    /// `Undefined Behavior: Data Race`).
+    #[must_use]
+    #[inline]
+    pub fn title(self) -> String {
+        match self.name() {
+            Some(name) => format!("{}: {name}", Title::UndefinedBehavior.text()),
+            None => Title::UndefinedBehavior.text().to_owned(),
+        }
+    }
+}
+
+/// The kind of undefined behavior reported by the message of a diagnostic.
+///
+/// # Returns
+///
+/// The kind, or `None` if the diagnostic does not report undefined behavior.
+pub(crate) fn undefined_behavior(message: &str) -> Option<UbKind> {
+    message.strip_prefix(UB_PREFIX).map(UbKind::of)
+}
+
+/// Undefined behavior detected by Miri.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UndefinedBehavior {
+    /// The kind of undefined behavior.
+    pub kind: UbKind,
+    /// The message of Miri, without the `Undefined Behavior: ` prefix.
+    pub message: String,
+    /// The file in which the undefined behavior occurred, if known.
+    pub file: Option<String>,
+    /// The line at which the undefined behavior occurred.
+    pub line: u32,
+    /// The frames of the backtrace, innermost first (e.g., ``inside `main`
+    /// at src/main.rs:5:14``).
+    pub backtrace: Vec<String>,
+    /// The lines of the report, as printed by Miri.
+    pub report: Vec<String>,
+}
+
+impl UndefinedBehavior {
+    /// Start a report of undefined behavior.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message of Miri, without the `Undefined Behavior: `
+    ///   prefix.
+    fn new(message: &str) -> Self {
+        Self {
+            kind: UbKind::of(message),
+            message: message.to_owned(),
+            file: None,
+            line: 1,
+            backtrace: Vec::new(),
+            report: Vec::new(),
+        }
+    }
+
+    /// Record a location of the report, which is annotated if it is the first
+    /// one, or the first one within the checkout.
+    fn locate(&mut self, file: &str, line: u32) {
+        let replace = self
+            .file
+            .as_deref()
+            .is_none_or(|current| !is_in_checkout(current) && is_in_checkout(file));
+        if replace {
+            self.file = Some(file.to_owned());
+            self.line = line;
+        }
+    }
+
+    /// Record a frame of the backtrace, annotated at its location if it lies
+    /// within the checkout and no earlier location does.
+    fn push_frame(&mut self, frame: String) {
+        if let Some((file, line)) = frame
+            .rsplit_once(" at ")
+            .and_then(|(_, location)| parse_location(location))
+        {
+            self.locate(&file, line);
+        }
+        self.backtrace.push(frame);
+    }
+}
+
+impl<P: Platform> CiMessage<P> for UndefinedBehavior {
+    #[inline]
+    fn format(&self) -> String {
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            &self.message,
+            self.file.as_deref().map(|file| (file, self.line, None)),
+            Some(&self.kind.title()),
+        )];
+        if !self.report.is_empty() {
+            lines.push(P::group("Miri report"));
+            lines.extend(self.report.iter().cloned());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for UndefinedBehavior {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for MiriMessage
+where
+    CargoMessage: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Cargo(msg) => <CargoMessage as CiMessage<P>>::format(msg),
+            Self::UndefinedBehavior(msg) => <UndefinedBehavior as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for MiriMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Cargo(msg) => msg.severity(),
+            Self::UndefinedBehavior(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Cargo(msg) => msg.kind(),
+            Self::UndefinedBehavior(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::Cargo(msg) => msg.is_incomplete(),
+            Self::UndefinedBehavior(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Cargo(msg) => msg.sort_key(),
+            Self::UndefinedBehavior(msg) => msg.file(),
+        }
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Cargo(msg) => msg.code(),
+            Self::UndefinedBehavior(_) => None,
+        }
+    }
+
+    #[inline]
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        match self {
+            Self::Cargo(msg) => msg.fixes(),
+            Self::UndefinedBehavior(_) => Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn snippet(&self) -> Option<String> {
+        match self {
+            Self::Cargo(msg) => msg.snippet(),
+            Self::UndefinedBehavior(_) => None,
+        }
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        match self {
+            Self::Cargo(msg) => msg.package(),
+            Self::UndefinedBehavior(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Cargo(msg) => msg.file(),
+            Self::UndefinedBehavior(msg) => msg.file(),
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Cargo(msg) => msg.message(),
+            Self::UndefinedBehavior(msg) => msg.message(),
+        }
+    }
+
+    #[inline]
+    fn build_result(&self) -> Option<bool> {
+        match self {
+            Self::Cargo(msg) => msg.build_result(),
+            Self::UndefinedBehavior(_) => None,
+        }
+    }
+
+    #[inline]
+    fn binary(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::Cargo(msg) => msg.binary(),
+            Self::UndefinedBehavior(_) => None,
+        }
+    }
+}
+
+impl Timed for MiriMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::Cargo(msg) => msg.timing(),
+            Self::UndefinedBehavior(_) => None,
+        }
+    }
+}
+
+impl Captured for MiriMessage {}
+
+/// A diagnostic of rustc printed by Miri as JSON, without the envelope of
+/// cargo.
+#[derive(Debug, Deserialize)]
+struct BareDiagnostic {
+    /// The type of the message, which is `diagnostic`.
+    #[serde(rename = "$message_type")]
+    message_type: String,
+    /// The primary message.
+    message: String,
+    /// The source code spans.
+    #[serde(default)]
+    spans: Vec<BareSpan>,
+    /// The child diagnostics, among which the frames of the backtrace.
+    #[serde(default)]
+    children: Vec<BareChild>,
+    /// The rendered version of the diagnostic.
+    rendered: Option<String>,
+}
+
+/// A child diagnostic of a [`BareDiagnostic`] (e.g., a note).
+#[derive(Debug, Deserialize)]
+struct BareChild {
+    /// The message of the child.
+    message: String,
+    /// The source code spans.
+    #[serde(default)]
+    spans: Vec<BareSpan>,
+}
+
+/// A source code span of a [`BareDiagnostic`].
+#[derive(Debug, Deserialize)]
+struct BareSpan {
+    /// The file of the span.
+    file_name: String,
+    /// The first line of the span.
+    line_start: u32,
+    /// The first column of the span.
+    column_start: u32,
+    /// Whether this is the primary span.
+    is_primary: bool,
+}
+
+impl BareSpan {
+    /// The location of the span, as printed by rustc (e.g., `src/lib.rs:4:14`).
+    fn location(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.file_name, self.line_start, self.column_start
+        )
+    }
+}
+
+impl BareDiagnostic {
+    /// Parse a line as a bare diagnostic of rustc.
+    fn parse(line: &[u8]) -> Option<Self> {
+        if !line.starts_with(b"{") {
+            return None;
+        }
+        serde_json::from_slice::<Self>(line)
+            .ok()
+            .filter(|diagnostic| diagnostic.message_type == "diagnostic")
+    }
+
+    /// The undefined behavior reported by the diagnostic, if any.
+    fn undefined_behavior(&self) -> Option<UndefinedBehavior> {
+        let message = self.message.strip_prefix(UB_PREFIX)?;
+        let mut report = UndefinedBehavior::new(message);
+        if let Some(span) = self.spans.iter().find(|span| span.is_primary) {
+            report.locate(&span.file_name, span.line_start);
+        }
+        for child in &self.children {
+            if child.message.starts_with("inside ") {
+                let frame = match child.spans.iter().find(|span| span.is_primary) {
+                    Some(span) => format!("{} at {}", child.message, span.location()),
+                    None => child.message.clone(),
+                };
+                report.push_frame(frame);
+            }
+        }
+        report.report = self.rendered_lines();
+        Some(report)
+    }
+
+    /// The lines of the rendered diagnostic, without the trailing empty
+    /// lines.
+    fn rendered_lines(&self) -> Vec<String> {
+        let rendered = self.rendered.as_deref().unwrap_or(&self.message);
+        rendered.trim_end().lines().map(str::to_owned).collect()
+    }
+}
+
+/// Parse a location as printed by rustc (e.g., `src/lib.rs:4:14`, possibly
+/// followed by the end of the span, as in `src/lib.rs:4:14: 4:22`).
+///
+/// # Returns
+///
+/// The file and line, or `None` if the text is not a location.
+fn parse_location(location: &str) -> Option<(String, u32)> {
+    let start = location.split(": ").next()?.trim();
+    let mut parts = start.rsplitn(3, ':');
+    let _column: u32 = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next().filter(|file| !file.is_empty())?;
+    Some((file.to_owned(), line))
+}
+
+/// Whether a line of the output shows that it is that of Miri: a report of
+/// undefined behavior (human-readable, or as a diagnostic with or without
+/// the envelope of cargo), or an artifact built for Miri.
+fn is_miri_line(line: &str) -> bool {
+    if line.contains(UB_MARKER) {
+        return true;
+    }
+    if BareDiagnostic::parse(line.as_bytes())
+        .is_some_and(|diagnostic| diagnostic.message.starts_with(UB_PREFIX))
+    {
+        return true;
+    }
+    match serde_json::from_str::<CargoMessage>(line) {
+        Ok(CargoMessage::CompilerMessage(msg)) => msg
+            .message()
+            .is_some_and(|message| message.starts_with(UB_PREFIX)),
+        Ok(CargoMessage::CompilerArtifact(artifact)) => artifact
+            .filenames
+            .iter()
+            .any(|file| file.contains("/miri/")),
+        _ => false,
+    }
+}
+
+/// The state of the parser of Miri's output, apart from the framing of its
+/// lines.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The parser of cargo's messages, to which the lines which are not
+    /// part of a report of undefined behavior are passed.
+    check: CargoCheck,
+    /// The lines to be passed to the parser of cargo's messages.
+    pending: Vec<u8>,
+    /// The human-readable report of undefined behavior being read.
+    report: Option<UndefinedBehavior>,
+    /// A frame of the backtrace whose location is on the next line.
+    frame: Option<String>,
+    /// Whether any message of cargo was parsed, without which the output
+    /// is that of Miri alone, rather than of a build.
+    built: bool,
+}
+
+impl Reader {
+    /// Pass the pending lines to the parser of cargo's messages, marking the
+    /// diagnostics which report undefined behavior.
+    fn flush(&mut self) -> Vec<Result<MiriMessage, serde_json::Error>> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        let pending = mem::take(&mut self.pending);
+        let results = self.check.parse(&pending);
+        self.wrap(results)
+    }
+
+    /// Wrap the messages of cargo, marking the diagnostics which report
+    /// undefined behavior.
+    fn wrap(
+        &mut self,
+        results: Vec<Result<CargoMessage, serde_json::Error>>,
+    ) -> Vec<Result<MiriMessage, serde_json::Error>> {
+        results
+            .into_iter()
+            .map(|result| {
+                result.map(|mut msg| {
+                    match &mut msg {
+                        CargoMessage::CompilerMessage(compiler_msg) => {
+                            compiler_msg.undefined_behavior();
+                            self.built = true;
+                        }
+                        CargoMessage::TextOutput(_)
+                        | CargoMessage::OverlongLine(_)
+                        | CargoMessage::TruncatedLine(_)
+                        | CargoMessage::IncompleteBuild(_) => {}
+                        CargoMessage::CompilerArtifact(_)
+                        | CargoMessage::BuildScriptExecuted(_)
+                        | CargoMessage::BuildFinished(_)
+                        | CargoMessage::TimingInfo(_)
+                        | CargoMessage::BuildScriptWarning(_) => self.built = true,
+                    }
+                    MiriMessage::Cargo(Box::new(msg))
+                })
+            })
+            .collect()
+    }
+
+    /// End the human-readable report being read, if any.
+    fn end_report(&mut self) -> Option<Result<MiriMessage, serde_json::Error>> {
+        self.frame = None;
+        self.report
+            .take()
+            .map(|report| Ok(MiriMessage::UndefinedBehavior(report)))
+    }
+
+    /// Read a line of the human-readable report being read.
+    fn read_report_line(&mut self, line: &str) {
+        let Some(report) = &mut self.report else {
+            return;
+        };
+        report.report.push(line.to_owned());
+        let content = line.trim_start();
+        let note = content.strip_prefix("= ").unwrap_or(content);
+        if let Some(frame) = note.strip_prefix("note: ")
+            && frame.starts_with("inside ")
+        {
+            if frame.contains(" at ") {
+                report.push_frame(frame.to_owned());
+            } else {
+                self.frame = Some(frame.to_owned());
+            }
+        } else if let Some(location) = content.strip_prefix("--> ") {
+            match self.frame.take() {
+                Some(frame) => report.push_frame(format!("{frame} at {location}")),
+                None if report.file.is_none() => {
+                    if let Some((file, line_number)) = parse_location(location) {
+                        report.locate(&file, line_number);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Parse a line of the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - The line.
+    /// * `max_length` - The maximum line length, for reporting overlong
+    ///   lines.
+    /// * `newline` - Whether the line is terminated by a newline, which is
+    ///   not the case of the final line of the output.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        newline: bool,
+    ) -> Vec<Result<MiriMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                let mut results = self.flush();
+                results.push(Ok(MiriMessage::Cargo(Box::new(
+                    CargoMessage::OverlongLine(OverlongLine { max_length }),
+                ))));
+                results.extend(self.parse_line(Line::Complete(line), max_length, newline));
+                return results;
+            }
+        };
+        let text = String::from_utf8_lossy(line);
+
+        let mut results = Vec::new();
+        if self.report.is_some() {
+            if text.trim().is_empty() {
+                results.extend(self.end_report());
+                return results;
+            }
+            if !text.contains(UB_MARKER) || line.starts_with(b"{") {
+                self.read_report_line(&text);
+                return results;
+            }
+            results.extend(self.end_report());
+        }
+
+        if let Some(diagnostic) = BareDiagnostic::parse(line) {
+            if let Some(report) = diagnostic.undefined_behavior() {
+                results.extend(self.flush());
+                results.push(Ok(MiriMessage::UndefinedBehavior(report)));
+            } else {
+                for rendered in diagnostic.rendered_lines() {
+                    self.pending.extend_from_slice(rendered.as_bytes());
+                    self.pending.push(b'\n');
+                }
+            }
+            return results;
+        }
+
+        if let Some((prefix, message)) = text.split_once(UB_MARKER)
+            && !line.starts_with(b"{")
+        {
+            // The report may follow the output of a test on the same line.
+            if !prefix.trim().is_empty() {
+                self.pending.extend_from_slice(prefix.as_bytes());
+                self.pending.push(b'\n');
+            }
+            results.extend(self.flush());
+            let mut report = UndefinedBehavior::new(message);
+            report.report.push(format!("error: {UB_PREFIX}{message}"));
+            self.report = Some(report);
+            return results;
+        }
+
+        self.pending.extend_from_slice(line);
+        if newline {
+            self.pending.push(b'\n');
+        }
+        results
+    }
+}
+
+/// Tool implementation for parsing the output of Miri.
+#[derive(Debug, Clone, Default)]
+pub struct Miri {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl Miri {
+    /// A problem matcher for the human-readable reports of undefined
+    /// behavior printed by Miri.
+    ///
+    /// Each report has the form of a diagnostic of rustc (see
+    /// [`CargoCheck::problem_matcher`]), whose message starts with
+    /// `Undefined Behavior:`, and is reported as an error.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-miri",
+            vec![
+                Pattern::new("error: (Undefined Behavior: .+)$").message(1),
+                Pattern::new(r"^\s*--> (.+?):(\d+):(\d+)$")
+                    .file(1)
+                    .line(2)
+                    .column(3),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Detect for Miri {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| is_miri_line(&line))
+            .then(Self::default)
+    }
+}
+
+impl Tool for Miri {
+    type Message = MiriMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "miri"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, true));
+        }
+        results.extend(self.reader.flush());
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.reader.parse_line(line, max_length, false));
+        }
+        results.extend(self.reader.end_report());
+        results.extend(self.reader.flush());
+        let built = self.reader.built;
+        let finished = self
+            .reader
+            .check
+            .finish()
+            .into_iter()
+            // The output of Miri alone is not that of an incomplete build.
+            .filter(|result| built || !matches!(result, Ok(CargoMessage::IncompleteBuild(_))))
+            .collect();
+        results.extend(self.reader.wrap(finished));
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+        self.reader.check.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.reader.check.set_passthrough(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.reader.check.set_output_policy(policy);
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        self.reader.check.supported_options()
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        self.reader.check.set_options(options);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Miri
+where
+    MiriMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{Miri, MiriMessage, UbKind, parse_location};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{CargoCheck, CargoLibtest, Detect as _, Tool as _};
+
+    const REPORT: &str = "\
+running 1 test
+test tests::dangling ... error: Undefined Behavior: memory access failed: alloc115 has been freed, so this pointer is dangling
+ --> src/lib.rs:4:14
+  |
+4 |     unsafe { *pointer }
+  |              ^^^^^^^^ Undefined Behavior occurred here
+  |
+  = help: this indicates a bug in the program: it performed an invalid operation, and caused Undefined Behavior
+help: alloc115 was deallocated here:
+ --> src/lib.rs:3:5
+  |
+3 |     drop(boxed);
+  |     ^^^^^^^^^^^
+  = note: BACKTRACE (of the first span) on thread `tests::dangling`:
+  = note: inside `read_freed` at src/lib.rs:4:14: 4:22
+note: inside `tests::dangling`
+ --> src/lib.rs:12:9
+   |
+12 |         read_freed();
+   |         ^^^^^^^^^^^^
+
+error: aborting due to 1 previous error
+";
+
+    const STD_REPORT: &str = "\
+error: Undefined Behavior: Data race detected between (1) non-atomic write on thread `unnamed-1` and (2) non-atomic read on thread `main` at alloc1
+ --> /rustc/0123456789abcdef/library/core/src/ptr/mod.rs:1234:9
+  = note: inside `std::ptr::read::<u32>` at /rustc/0123456789abcdef/library/core/src/ptr/mod.rs:1234:9: 1234:30
+  = note: inside `race` at src/lib.rs:8:5: 8:28
+";
+
+    const BARE: &str = r#"{"$message_type":"diagnostic","message":"Undefined Behavior: using uninitialized data, but this operation requires initialized memory","code":null,"level":"error","spans":[{"file_name":"src/main.rs","byte_start":80,"byte_end":86,"line_start":5,"line_end":5,"column_start":13,"column_end":19,"is_primary":true,"text":[],"label":"Undefined Behavior occurred here","suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[{"message":"inside `main`","code":null,"level":"note","spans":[{"file_name":"src/main.rs","byte_start":80,"byte_end":86,"line_start":5,"line_end":5,"column_start":13,"column_end":19,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[],"rendered":null}],"rendered":"error: Undefined Behavior: using uninitialized data, but this operation requires initialized memory\n --> src/main.rs:5:13\n\n"}"#;
+
+    const ABORTING: &str = r#"{"$message_type":"diagnostic","message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting due to 1 previous error\n\n"}"#;
+
+    const COMPILER_MESSAGE: &str = r#"{"reason":"compiler-message","package_id":"path+file:///w/project#0.1.0","manifest_path":"/w/project/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/project/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"rendered":"error[E0080]: evaluation of constant value failed\n","$message_type":"diagnostic","children":[],"level":"error","message":"Undefined Behavior: constructing invalid value: encountered 0x02, but expected a boolean","spans":[{"byte_end":70,"byte_start":63,"column_end":28,"column_start":21,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"E0080","explanation":null}}}"#;
+
+    const ARTIFACT: &str = r#"{"reason":"compiler-artifact","package_id":"path+file:///w/project#0.1.0","manifest_path":"/w/project/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"project","src_path":"/w/project/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":true},"features":[],"filenames":["/w/project/target/miri/x86_64-unknown-linux-gnu/debug/deps/project-0123456789abcdef"],"executable":"/w/project/target/miri/x86_64-unknown-linux-gnu/debug/deps/project-0123456789abcdef","fresh":false}"#;
+
+    const BUILD_FINISHED: &str = r#"{"reason":"build-finished","success":true}"#;
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        MiriMessage: CiMessage<P>,
+    {
+        let mut tool = Miri::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <MiriMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[rstest]
+    #[case(
+        "Data race detected between (1) non-atomic write on thread `unnamed-1` and (2) non-atomic read on thread `main` at alloc1",
+        UbKind::DataRace
+    )]
+    #[case(
+        "attempting a read access using <2063> at alloc1[0x0], but that tag does not exist in the borrow stack for this location",
+        UbKind::Aliasing
+    )]
+    #[case(
+        "read access through <1> at alloc1[0x0] is forbidden",
+        UbKind::Aliasing
+    )]
+    #[case(
+        "memory access failed: alloc115 has been freed, so this pointer is dangling",
+        UbKind::Dangling
+    )]
+    #[case(
+        "out-of-bounds pointer use: expected a pointer to 4 bytes of memory, but got alloc1+0x8 which is at or beyond the end of the allocation of size 8 bytes",
+        UbKind::OutOfBounds
+    )]
+    #[case(
+        "accessing memory based on pointer with alignment 1, but alignment 4 is required",
+        UbKind::Misaligned
+    )]
+    #[case(
+        "constructing invalid value: encountered 0x02, but expected a boolean",
+        UbKind::InvalidValue
+    )]
+    #[case(
+        "using uninitialized data, but this operation requires initialized memory",
+        UbKind::Uninitialized
+    )]
+    #[case("entering unreachable code", UbKind::Unreachable)]
+    #[case(
+        "calling a function with calling convention \"C\" using caller calling convention \"Rust\"",
+        UbKind::Other
+    )]
+    fn kinds(#[case] message: &str, #[case] kind: UbKind) {
+        assert_eq!(UbKind::of(message), kind);
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(UbKind::DataRace.title(), "Undefined Behavior: Data Race");
+        assert_eq!(UbKind::Other.title(), "Undefined Behavior");
+    }
+
+    #[test]
+    fn locations() {
+        assert_eq!(
+            parse_location("src/lib.rs:4:14: 4:22"),
+            Some(("src/lib.rs".to_owned(), 4))
+        );
+        assert_eq!(
+            parse_location("C:\\w\\src\\lib.rs:12:9"),
+            Some(("C:\\w\\src\\lib.rs".to_owned(), 12))
+        );
+        assert_eq!(parse_location("src/lib.rs"), None);
+    }
+
+    #[test]
+    fn report() {
+        insta::assert_snapshot!(format::<GitHub>(REPORT), @"
+        ::error file=src/lib.rs,line=4,title=Undefined Behavior%3A Dangling Pointer::memory access failed: alloc115 has been freed, so this pointer is dangling
+        ::group::Miri report
+        error: Undefined Behavior: memory access failed: alloc115 has been freed, so this pointer is dangling
+         --> src/lib.rs:4:14
+          |
+        4 |     unsafe { *pointer }
+          |              ^^^^^^^^ Undefined Behavior occurred here
+          |
+          = help: this indicates a bug in the program: it performed an invalid operation, and caused Undefined Behavior
+        help: alloc115 was deallocated here:
+         --> src/lib.rs:3:5
+          |
+        3 |     drop(boxed);
+          |     ^^^^^^^^^^^
+          = note: BACKTRACE (of the first span) on thread `tests::dangling`:
+          = note: inside `read_freed` at src/lib.rs:4:14: 4:22
+        note: inside `tests::dangling`
+         --> src/lib.rs:12:9
+           |
+        12 |         read_freed();
+           |         ^^^^^^^^^^^^
+        ::endgroup::
+        ");
+    }
+
+    #[test]
+    fn backtrace() {
+        let mut tool = Miri::default();
+        let mut messages = tool.parse(REPORT.as_bytes());
+        messages.extend(tool.finish());
+        let reports: Vec<_> = messages
+            .into_iter()
+            .filter_map(|msg| match msg.expect("valid message") {
+                MiriMessage::UndefinedBehavior(report) => Some(report),
+                MiriMessage::Cargo(_) => None,
+            })
+            .collect();
+        let [report] = reports.as_slice() else {
+            panic!("expected one report, got {reports:?}");
+        };
+        assert_eq!(report.kind, UbKind::Dangling);
+        assert_eq!(
+            report.backtrace,
+            [
+                "inside `read_freed` at src/lib.rs:4:14: 4:22",
+                "inside `tests::dangling` at src/lib.rs:12:9",
+            ]
+        );
+    }
+
+    #[test]
+    fn standard_library() {
+        insta::assert_snapshot!(format::<Plain>(STD_REPORT), @"
+        src/lib.rs:8: error:   Undefined Behavior: Data Race: Data race detected between (1) non-atomic write on thread `unnamed-1` and (2) non-atomic read on thread `main` at alloc1
+        Miri report
+        error: Undefined Behavior: Data race detected between (1) non-atomic write on thread `unnamed-1` and (2) non-atomic read on thread `main` at alloc1
+         --> /rustc/0123456789abcdef/library/core/src/ptr/mod.rs:1234:9
+          = note: inside `std::ptr::read::<u32>` at /rustc/0123456789abcdef/library/core/src/ptr/mod.rs:1234:9: 1234:30
+          = note: inside `race` at src/lib.rs:8:5: 8:28
+        ");
+    }
+
+    #[test]
+    fn bare() {
+        let output = [BARE, ABORTING].join("\n");
+        insta::assert_snapshot!(format::<Plain>(&output), @"
+        src/main.rs:5: error:   Undefined Behavior: Uninitialized Memory: using uninitialized data, but this operation requires initialized memory
+        Miri report
+        error: Undefined Behavior: using uninitialized data, but this operation requires initialized memory
+         --> src/main.rs:5:13
+        ");
+    }
+
+    #[test]
+    fn compiler_message() {
+        let output = [COMPILER_MESSAGE, BUILD_FINISHED].join("\n");
+        insta::assert_snapshot!(format::<GitHub>(&output), @"
+        ::error file=src/lib.rs,line=2,col=21,endLine=2,endColumn=28,title=Undefined Behavior%3A Invalid Value::Undefined Behavior: constructing invalid value: encountered 0x02, but expected a boolean
+
+        ::notice title=Build Complete::Build finished successfully
+        ");
+    }
+
+    #[test]
+    fn truncated() {
+        let output = format!(
+            "{ARTIFACT}\n{}",
+            BUILD_FINISHED.get(..20).expect("in bounds")
+        );
+        insta::assert_snapshot!(format::<Plain>(&output), @"
+        Built artifact: project (lib)
+        warning: Truncated Output: Output ended within line 2 (at byte offset 598), discarding 20 bytes of an incomplete message: EOF while parsing a string at line 1 column 20
+        warning: Incomplete Build: Cargo's output ended without reporting the result of the build
+        ");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Miri::detect(REPORT.as_bytes()).is_some());
+        assert!(Miri::detect(BARE.as_bytes()).is_some());
+        assert!(Miri::detect(COMPILER_MESSAGE.as_bytes()).is_some());
+        let build = [ARTIFACT, BUILD_FINISHED].join("\n");
+        assert!(Miri::detect(build.as_bytes()).is_some());
+        assert!(Miri::detect(BUILD_FINISHED.as_bytes()).is_none());
+        assert!(Miri::detect(b"running 1 test\ntest tests::it_works ... ok\n").is_none());
+        assert!(CargoCheck::detect(REPORT.as_bytes()).is_none());
+        assert!(CargoLibtest::detect(BARE.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Miri::problem_matcher();
+        let [report, location] = matcher.pattern.as_slice() else {
+            panic!("expected two patterns");
+        };
+        let message = regex::Regex::new(&report.regexp).expect("invalid regexp");
+        let span = regex::Regex::new(&location.regexp).expect("invalid regexp");
+
+        let captures = message
+            .captures(
+                "test tests::dangling ... error: Undefined Behavior: entering unreachable code",
+            )
+            .expect("report not matched");
+        assert_eq!(
+            captures.get(1).map(|m| m.as_str()),
+            Some("Undefined Behavior: entering unreachable code")
+        );
+        assert!(!message.is_match("error[E0308]: mismatched types"));
+        assert!(span.is_match(" --> src/lib.rs:4:14"));
+    }
+}
@@ -0,0 +1,218 @@
+//! Options tuning the messages of a tool.
+//!
+//! Besides the settings which apply to the output of any tool (e.g., the
+//! [`Passthrough`](crate::tool::Passthrough) policy), some settings only make
+//! sense for some tools (e.g., the lints denied by policy only apply to
+//! compilers reporting lints). These are gathered in [`ToolOptions`], and each
+//! tool lists the [`ToolOption`]s it supports, such that a caller can reject
+//! an option which the tool would otherwise ignore.
+
+use core::fmt;
+
+use crate::timeline::TimeBudget;
+
+/// An option of [`ToolOptions`], which only some tools support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ToolOption {
+    /// See [`ToolOptions::fold_children`].
+    FoldChildren,
+    /// See [`ToolOptions::denied_lints`].
+    DeniedLints,
+    /// See [`ToolOptions::read_sources`].
+    ReadSources,
+    /// See [`ToolOptions::time_budget`].
+    TimeBudget,
+    /// See [`ToolOptions::coverage_threshold`].
+    CoverageThreshold,
+}
+
+impl fmt::Display for ToolOption {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FoldChildren => "folding of child diagnostics",
+            Self::DeniedLints => "denied lints",
+            Self::ReadSources => "reading of sources",
+            Self::TimeBudget => "time budget",
+            Self::CoverageThreshold => "coverage threshold",
+        })
+    }
+}
+
+/// Options tuning the messages of a tool, which only some tools support (see
+/// [`Tool::supported_options`](crate::tool::Tool::supported_options)).
+///
+/// The default options change nothing, such that tools which do not support
+/// an option report the same messages as if it was left at its default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ToolOptions {
+    /// Whether child diagnostics (e.g., notes and help) are folded into the
+    /// annotation of their parent, rather than annotated separately.
+    ///
+    /// Folding keeps a single annotation per root diagnostic, and places the
+    /// full diagnostic in a collapsed group of the log.
+    pub fold_children: bool,
+    /// The codes of the lints which are denied by policy (e.g.,
+    /// `unsafe_code` or `clippy::unwrap_used`).
+    ///
+    /// Their diagnostics are reported as errors, whichever level the tool
+    /// reported, and their annotation's title is prefixed with `denied`.
+    pub denied_lints: Vec<String>,
+    /// Whether the source text of a diagnostic is read from disk when the
+    /// tool does not include it, so that a snippet of the source can be shown
+    /// (see [`Classify::snippet`](crate::ci_message::Classify::snippet)).
+    pub read_sources: bool,
+    /// The budgets for the duration of test suites.
+    ///
+    /// Suites which pass but exceed a budget are reported with a warning
+    /// rather than a notice, so that a slow increase in their duration is
+    /// noticed.
+    pub time_budget: TimeBudget,
+    /// The line coverage, in percent, below which a file is reported.
+    ///
+    /// Files whose share of executed lines is below the threshold are
+    /// reported with a warning rather than a notice, as is the total.
+    pub coverage_threshold: Option<u8>,
+}
+
+impl ToolOptions {
+    /// Set whether child diagnostics are folded into the annotation of their
+    /// parent (see [`ToolOptions::fold_children`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `fold` - Whether to fold the children.
+    #[must_use]
+    #[inline]
+    pub fn with_fold_children(mut self, fold: bool) -> Self {
+        self.fold_children = fold;
+        self
+    }
+
+    /// Set the lints which are denied by policy (see
+    /// [`ToolOptions::denied_lints`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `lints` - The codes of the denied lints.
+    #[must_use]
+    #[inline]
+    pub fn with_denied_lints(mut self, lints: Vec<String>) -> Self {
+        self.denied_lints = lints;
+        self
+    }
+
+    /// Set whether the source text of a diagnostic is read from disk (see
+    /// [`ToolOptions::read_sources`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `read` - Whether to read the source text from disk.
+    #[must_use]
+    #[inline]
+    pub fn with_read_sources(mut self, read: bool) -> Self {
+        self.read_sources = read;
+        self
+    }
+
+    /// Set the budgets for the duration of test suites (see
+    /// [`ToolOptions::time_budget`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - The budgets.
+    #[must_use]
+    #[inline]
+    pub fn with_time_budget(mut self, budget: TimeBudget) -> Self {
+        self.time_budget = budget;
+        self
+    }
+
+    /// Set the line coverage below which a file is reported (see
+    /// [`ToolOptions::coverage_threshold`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `percent` - The threshold in percent, if any.
+    #[must_use]
+    #[inline]
+    pub fn with_coverage_threshold(mut self, percent: Option<u8>) -> Self {
+        self.coverage_threshold = percent;
+        self
+    }
+
+    /// The options which are set, rather than left at their default.
+    ///
+    /// # Returns
+    ///
+    /// The options which are set, in the order of their declaration.
+    #[must_use]
+    #[inline]
+    pub fn set(&self) -> Vec<ToolOption> {
+        [
+            (ToolOption::FoldChildren, self.fold_children),
+            (ToolOption::DeniedLints, !self.denied_lints.is_empty()),
+            (ToolOption::ReadSources, self.read_sources),
+            (
+                ToolOption::TimeBudget,
+                self.time_budget != TimeBudget::default(),
+            ),
+            (
+                ToolOption::CoverageThreshold,
+                self.coverage_threshold.is_some(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(option, set)| set.then_some(option))
+        .collect()
+    }
+
+    /// The options which are set, but not supported by a tool.
+    ///
+    /// # Arguments
+    ///
+    /// * `supported` - The options supported by the tool (see
+    ///   [`Tool::supported_options`](crate::tool::Tool::supported_options)).
+    ///
+    /// # Returns
+    ///
+    /// The options which are set but not supported, which the tool would
+    /// ignore.
+    #[must_use]
+    #[inline]
+    pub fn unsupported(&self, supported: &[ToolOption]) -> Vec<ToolOption> {
+        self.set()
+            .into_iter()
+            .filter(|option| !supported.contains(option))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{ToolOption, ToolOptions};
+    use crate::timeline::TimeBudget;
+
+    #[test]
+    fn set() {
+        assert_eq!(ToolOptions::default().set(), []);
+
+        let options = ToolOptions::default()
+            .with_denied_lints(vec!["unsafe_code".to_owned()])
+            .with_time_budget(TimeBudget::new(Some(Duration::from_secs(10)), None));
+        assert_eq!(
+            options.set(),
+            [ToolOption::DeniedLints, ToolOption::TimeBudget]
+        );
+        assert_eq!(
+            options.unsupported(&[ToolOption::TimeBudget]),
+            [ToolOption::DeniedLints]
+        );
+    }
+}
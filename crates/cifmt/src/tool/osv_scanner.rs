@@ -0,0 +1,1202 @@
+//! OSV-Scanner JSON report.
+//!
+//! Support for parsing the report which OSV-Scanner prints with `--format
+//! json`, which lists the known vulnerabilities of the packages found in each
+//! of the scanned sources (e.g., a `Cargo.lock` or `package-lock.json`). The
+//! report is a JSON object printed over many lines (or on a single line, once
+//! compacted by another tool), which is parsed once its closing brace is
+//! read; a report cut off before its end is reported as truncated.
+//!
+//! The vulnerabilities which OSV-Scanner groups as the same (e.g., an
+//! advisory of GitHub and one of the RustSec database) are reported once,
+//! under the first of their IDs, with the others as aliases. Each is
+//! annotated on the line of the lockfile which declares the affected version
+//! of the package, with its summary, its severity and the versions in which
+//! it is fixed. The lockfile is read from disk; if it cannot be read, or the
+//! package is not found in it, the first line of the lockfile is annotated
+//! instead. Vulnerabilities of a high or critical severity are errors, while
+//! the others are warnings. A summary of the number of vulnerabilities
+//! follows at the end of the output.
+//!
+//! Any text outside of the report (e.g., the progress of the scan, when
+//! printed to the same stream) is passed through as plain text output.
+
+use core::{fmt, mem};
+use std::fs;
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the JSON report of OSV-Scanner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OsvMessage {
+    /// A known vulnerability of a package.
+    Vulnerability(Box<Vulnerability>),
+
+    /// The number of vulnerabilities, reported at the end of the output.
+    Summary(OsvSummary),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// A report cut off before its end.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of the report.
+    TextOutput(TextOutput),
+}
+
+/// The severity of a vulnerability, from its CVSS score or, without one, as
+/// given by its database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum VulnerabilityLevel {
+    /// A severity which is not known.
+    Unknown,
+    /// A low severity (a score below 4.0).
+    Low,
+    /// A medium severity (a score from 4.0 to 6.9).
+    Medium,
+    /// A high severity (a score from 7.0 to 8.9).
+    High,
+    /// A critical severity (a score of 9.0 or above).
+    Critical,
+}
+
+impl VulnerabilityLevel {
+    /// The level of a CVSS score (e.g., `6.2`).
+    fn from_score(score: &str) -> Option<Self> {
+        let value = score.parse::<f64>().ok()?;
+        Some(if value >= 9.0 {
+            Self::Critical
+        } else if value >= 7.0 {
+            Self::High
+        } else if value >= 4.0 {
+            Self::Medium
+        } else {
+            Self::Low
+        })
+    }
+
+    /// The level named by a database (e.g., `MODERATE` for GitHub advisories).
+    fn from_label(label: &str) -> Self {
+        match label.to_ascii_uppercase().as_str() {
+            "CRITICAL" => Self::Critical,
+            "HIGH" => Self::High,
+            "MODERATE" | "MEDIUM" => Self::Medium,
+            "LOW" => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for VulnerabilityLevel {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unknown => "unknown",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// A known vulnerability of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Vulnerability {
+    /// The ID of the vulnerability (e.g., `GHSA-wcg3-cvx6-7396`).
+    pub id: String,
+    /// The other IDs of the vulnerability (e.g., `CVE-2020-26235`).
+    pub aliases: Vec<String>,
+    /// The summary of the vulnerability, if any.
+    pub summary: Option<String>,
+    /// The name of the affected package.
+    pub package: String,
+    /// The affected version of the package.
+    pub version: String,
+    /// The ecosystem of the package (e.g., `crates.io`).
+    pub ecosystem: String,
+    /// The highest CVSS score of the vulnerability (e.g., `6.2`), if any.
+    pub score: Option<String>,
+    /// The severity of the vulnerability.
+    pub level: VulnerabilityLevel,
+    /// The versions of the package in which the vulnerability is fixed.
+    pub fixed: Vec<String>,
+    /// The path of the scanned source (e.g., the lockfile).
+    pub source: String,
+    /// The lockfile which declares the package, if the source is one.
+    pub file: Option<String>,
+    /// The line of the lockfile which declares the package, if found.
+    pub line: Option<u32>,
+    /// The message of the diagnostic (e.g., `time 0.1.45 is affected by
+    /// GHSA-wcg3-cvx6-7396`), from which its fingerprint is computed.
+    pub message: String,
+}
+
+impl Vulnerability {
+    /// The text of the annotation.
+    fn text(&self) -> String {
+        let summary = self.summary.as_deref().unwrap_or("Known vulnerability");
+        let mut lines = vec![match (&self.score, self.level) {
+            (Some(score), level) => format!("{summary} ({level} severity, CVSS {score})"),
+            (None, VulnerabilityLevel::Unknown) => summary.to_owned(),
+            (None, level) => format!("{summary} ({level} severity)"),
+        }];
+        lines.push(format!(
+            "Package: {} {} ({})",
+            self.package, self.version, self.ecosystem
+        ));
+        if self.file.is_none() {
+            lines.push(format!("Source: {}", self.source));
+        }
+        if !self.aliases.is_empty() {
+            lines.push(format!("Aliases: {}", self.aliases.join(", ")));
+        }
+        lines.push(if self.fixed.is_empty() {
+            String::from("No fixed version is known")
+        } else {
+            format!("Fixed in: {}", self.fixed.join(", "))
+        });
+        lines.push(format!("Details: https://osv.dev/{}", self.id));
+        lines.join("\n")
+    }
+}
+
+impl<P: Platform> CiMessage<P> for Vulnerability {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            self.text(),
+            self.file
+                .as_deref()
+                .map(|file| (file, self.line.unwrap_or(1), None)),
+            Some(&self.id),
+        )
+    }
+}
+
+impl Classify for Vulnerability {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.level {
+            VulnerabilityLevel::Critical | VulnerabilityLevel::High => Severity::Error,
+            VulnerabilityLevel::Medium | VulnerabilityLevel::Low | VulnerabilityLevel::Unknown => {
+                Severity::Warning
+            }
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.source)
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        Some(&self.package)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// The number of vulnerabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OsvSummary {
+    /// Whether a report of OSV-Scanner was found in the output.
+    pub reported: bool,
+    /// The number of vulnerabilities.
+    pub vulnerabilities: usize,
+    /// The number of packages with vulnerabilities.
+    pub packages: usize,
+}
+
+impl<P: Platform> CiMessage<P> for OsvSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let message = if !self.reported {
+            "No report of OSV-Scanner found".to_owned()
+        } else if self.vulnerabilities == 0 {
+            "No known vulnerabilities found".to_owned()
+        } else {
+            format!(
+                "{} known vulnerability(ies) in {} package(s)",
+                self.vulnerabilities, self.packages
+            )
+        };
+        P::annotate(
+            self.severity(),
+            message,
+            None,
+            Some(Title::VulnerabilityScan.text()),
+        )
+    }
+}
+
+impl Classify for OsvSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl<P: Platform> CiMessage<P> for OsvMessage
+where
+    OverlongLine: CiMessage<P>,
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Vulnerability(msg) => <Vulnerability as CiMessage<P>>::format(msg),
+            Self::Summary(msg) => <OsvSummary as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for OsvMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Vulnerability(msg) => msg.severity(),
+            Self::Summary(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Vulnerability(msg) => msg.kind(),
+            Self::Summary(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::Vulnerability(_)
+            | Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Vulnerability(msg) => msg.sort_key(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Vulnerability(msg) => msg.code(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        match self {
+            Self::Vulnerability(msg) => msg.package(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Vulnerability(msg) => msg.file(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Vulnerability(msg) => msg.message(),
+            Self::Summary(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for OsvMessage {}
+
+impl Captured for OsvMessage {}
+
+/// The report of `osv-scanner --format json`.
+#[derive(Debug, Deserialize)]
+struct Report {
+    /// The packages found in each scanned source.
+    #[serde(default)]
+    results: Vec<SourceResult>,
+}
+
+/// The packages with vulnerabilities found in a scanned source.
+#[derive(Debug, Deserialize)]
+struct SourceResult {
+    /// The scanned source.
+    source: Source,
+    /// The packages with vulnerabilities.
+    #[serde(default)]
+    packages: Vec<PackageResult>,
+}
+
+/// A scanned source.
+#[derive(Debug, Deserialize)]
+struct Source {
+    /// The path of the source.
+    path: String,
+    /// The kind of the source (e.g., `lockfile`, `sbom` or `git`).
+    #[serde(rename = "type", default)]
+    kind: String,
+}
+
+/// A package with vulnerabilities.
+#[derive(Debug, Deserialize)]
+struct PackageResult {
+    /// The package.
+    package: PackageInfo,
+    /// The vulnerabilities of the package.
+    #[serde(default)]
+    vulnerabilities: Vec<Osv>,
+    /// The vulnerabilities of the package grouped as the same, if given.
+    #[serde(default)]
+    groups: Vec<Group>,
+}
+
+/// A package.
+#[derive(Debug, Deserialize)]
+struct PackageInfo {
+    /// The name of the package.
+    name: String,
+    /// The version of the package.
+    #[serde(default)]
+    version: String,
+    /// The ecosystem of the package.
+    #[serde(default)]
+    ecosystem: String,
+}
+
+/// A vulnerability, in the OSV format.
+#[derive(Debug, Deserialize)]
+struct Osv {
+    /// The ID of the vulnerability.
+    id: String,
+    /// The other IDs of the vulnerability.
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// The summary of the vulnerability.
+    summary: Option<String>,
+    /// The packages and versions affected by the vulnerability.
+    #[serde(default)]
+    affected: Vec<Affected>,
+    /// The fields specific to the database of the vulnerability (e.g., the
+    /// `severity` of GitHub advisories).
+    #[serde(default)]
+    database_specific: serde_json::Value,
+}
+
+/// The versions of a package affected by a vulnerability.
+#[derive(Debug, Deserialize)]
+struct Affected {
+    /// The affected package, if given.
+    package: Option<AffectedPackage>,
+    /// The ranges of affected versions.
+    #[serde(default)]
+    ranges: Vec<Range>,
+}
+
+/// A package affected by a vulnerability.
+#[derive(Debug, Deserialize)]
+struct AffectedPackage {
+    /// The name of the package.
+    name: String,
+}
+
+/// A range of affected versions.
+#[derive(Debug, Deserialize)]
+struct Range {
+    /// The versions at which the range starts and ends.
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+/// A version at which a range of affected versions starts or ends.
+#[derive(Debug, Deserialize)]
+struct Event {
+    /// The version in which the vulnerability is fixed, if the range ends
+    /// there.
+    fixed: Option<String>,
+}
+
+/// The vulnerabilities of a package grouped as the same.
+#[derive(Debug, Deserialize)]
+struct Group {
+    /// The IDs of the vulnerabilities.
+    ids: Vec<String>,
+    /// The other IDs of the vulnerabilities.
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// The highest CVSS score of the vulnerabilities (e.g., `6.2`), or empty.
+    #[serde(default)]
+    max_severity: String,
+}
+
+impl Osv {
+    /// The versions of a package in which the vulnerability is fixed.
+    fn fixed_versions<'a>(&'a self, package: &'a str) -> impl Iterator<Item = &'a str> {
+        self.affected
+            .iter()
+            .filter(move |affected| {
+                affected
+                    .package
+                    .as_ref()
+                    .is_none_or(|affected_package| affected_package.name == package)
+            })
+            .flat_map(|affected| &affected.ranges)
+            .flat_map(|range| &range.events)
+            .filter_map(|event| event.fixed.as_deref())
+    }
+
+    /// The level of the vulnerability given by its database, if any.
+    fn label(&self) -> Option<&str> {
+        self.database_specific.get("severity")?.as_str()
+    }
+}
+
+/// Whether a line mentions a word (e.g., the name of a package), rather than
+/// a longer word containing it.
+fn mentions(line: &str, word: &str) -> bool {
+    let is_part = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.');
+    !word.is_empty()
+        && line.match_indices(word).any(|(start, _)| {
+            let before = line.get(..start).and_then(|text| text.chars().next_back());
+            let after = line
+                .get(start.saturating_add(word.len())..)
+                .and_then(|text| text.chars().next());
+            !before.is_some_and(is_part) && !after.is_some_and(is_part)
+        })
+}
+
+/// Find the line of a lockfile which declares a version of a package.
+///
+/// The package is looked for as a word of a line whose version is given on
+/// the same line or on the next two (e.g., `name = "time"` followed by
+/// `version = "0.1.45"` in a `Cargo.lock`, or `"node_modules/lodash": {`
+/// followed by `"version": "4.17.20"` in a `package-lock.json`), or else as
+/// a word of any line.
+///
+/// # Arguments
+///
+/// * `lockfile` - The text of the lockfile.
+/// * `name` - The name of the package.
+/// * `version` - The version of the package.
+///
+/// # Returns
+///
+/// The line of the declaration (1-based), or `None` if it is not found.
+fn find_package(lockfile: &str, name: &str, version: &str) -> Option<u32> {
+    let lines: Vec<_> = lockfile.lines().collect();
+    let declares = |index: usize| {
+        lines
+            .iter()
+            .skip(index)
+            .take(3)
+            .any(|line| mentions(line, version))
+    };
+    let found = lines
+        .iter()
+        .enumerate()
+        .position(|(index, line)| mentions(line, name) && declares(index))
+        .or_else(|| lines.iter().position(|line| mentions(line, name)))?;
+    u32::try_from(found.saturating_add(1)).ok()
+}
+
+impl PackageResult {
+    /// The vulnerabilities of the package, located in the scanned source.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The scanned source.
+    /// * `lockfile` - The text of the lockfile, if the source is one which
+    ///   could be read.
+    fn into_vulnerabilities(self, source: &Source, lockfile: Option<&str>) -> Vec<Vulnerability> {
+        let Self {
+            package,
+            vulnerabilities,
+            groups: given,
+        } = self;
+        // Without the groups, each vulnerability is reported on its own
+        let groups = if given.is_empty() {
+            vulnerabilities
+                .iter()
+                .map(|osv| Group {
+                    ids: vec![osv.id.clone()],
+                    aliases: osv.aliases.clone(),
+                    max_severity: String::new(),
+                })
+                .collect()
+        } else {
+            given
+        };
+        let is_file = matches!(source.kind.as_str(), "lockfile" | "sbom" | "");
+        let line = lockfile.and_then(|text| find_package(text, &package.name, &package.version));
+
+        groups
+            .into_iter()
+            .filter_map(|group| {
+                let mut ids = group.ids.into_iter();
+                let id = ids.next()?;
+                let mut aliases: Vec<String> = Vec::new();
+                for alias in ids.chain(group.aliases) {
+                    if alias != id && !aliases.contains(&alias) {
+                        aliases.push(alias);
+                    }
+                }
+                let members: Vec<&Osv> = vulnerabilities
+                    .iter()
+                    .filter(|osv| osv.id == id || aliases.contains(&osv.id))
+                    .collect();
+
+                let mut fixed: Vec<String> = Vec::new();
+                for version in members
+                    .iter()
+                    .flat_map(|osv| osv.fixed_versions(&package.name))
+                {
+                    if !fixed.iter().any(|known| known == version) {
+                        fixed.push(version.to_owned());
+                    }
+                }
+                let score = Some(group.max_severity).filter(|score| !score.is_empty());
+                let level = score
+                    .as_deref()
+                    .and_then(VulnerabilityLevel::from_score)
+                    .or_else(|| {
+                        members
+                            .iter()
+                            .filter_map(|osv| osv.label())
+                            .map(VulnerabilityLevel::from_label)
+                            .max()
+                    })
+                    .unwrap_or(VulnerabilityLevel::Unknown);
+
+                let message = format!("{} {} is affected by {id}", package.name, package.version);
+                Some(Vulnerability {
+                    summary: members
+                        .iter()
+                        .find_map(|osv| osv.summary.clone())
+                        .filter(|summary| !summary.is_empty()),
+                    id,
+                    aliases,
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    ecosystem: package.ecosystem.clone(),
+                    score,
+                    level,
+                    fixed,
+                    source: source.path.clone(),
+                    file: is_file.then(|| source.path.clone()),
+                    line: line.filter(|_| is_file),
+                    message,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The report being read, and the vulnerabilities reported so far.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The lines of the report read so far.
+    document: Vec<u8>,
+    /// The line number and offset of the first line of the report, if one is
+    /// being read.
+    start: Option<(usize, u64)>,
+    /// Whether a report was found.
+    reported: bool,
+    /// The number of vulnerabilities reported so far.
+    vulnerabilities: usize,
+    /// The number of packages with vulnerabilities reported so far.
+    packages: usize,
+}
+
+impl Reader {
+    /// Parse the report read so far.
+    fn parse(&mut self) -> Vec<Result<OsvMessage, serde_json::Error>> {
+        self.start = None;
+        let report = match serde_json::from_slice::<Report>(&mem::take(&mut self.document)) {
+            Ok(report) => report,
+            Err(e) => return vec![Err(e)],
+        };
+        self.reported = true;
+        let mut results = Vec::new();
+        for result in report.results {
+            let lockfile = fs::read_to_string(&result.source.path).ok();
+            for package in result.packages {
+                let found = package.into_vulnerabilities(&result.source, lockfile.as_deref());
+                if !found.is_empty() {
+                    self.packages = self.packages.saturating_add(1);
+                    self.vulnerabilities = self.vulnerabilities.saturating_add(found.len());
+                }
+                results.extend(
+                    found.into_iter().map(|vulnerability| {
+                        Ok(OsvMessage::Vulnerability(Box::new(vulnerability)))
+                    }),
+                );
+            }
+        }
+        results
+    }
+
+    /// Parse a single line of the output of OSV-Scanner.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the line.
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the report.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        position: (usize, u64),
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<OsvMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                // The report cannot be parsed without the whole line
+                self.start = None;
+                self.document.clear();
+                let mut results = vec![Ok(OsvMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(position, Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        if self.start.is_none() {
+            if line.first() != Some(&b'{') {
+                return text
+                    .handle(line)
+                    .map(|output| Ok(OsvMessage::TextOutput(output)))
+                    .into_iter()
+                    .collect();
+            }
+            self.start = Some(position);
+        }
+        self.document.extend_from_slice(line);
+        self.document.push(b'\n');
+
+        // OSV-Scanner prints the report over many lines, closing it on a line
+        // of its own, which may also be compacted onto a single line
+        let single = self.document.len() == line.len().saturating_add(1);
+        if line == b"}" || (single && line.ends_with(b"}")) {
+            let results = self.parse();
+            if single
+                && let [Err(e)] = results.as_slice()
+                && e.is_eof()
+            {
+                // The report continues on the next lines
+                self.start = Some(position);
+                self.document.extend_from_slice(line);
+                self.document.push(b'\n');
+                return Vec::new();
+            }
+            return results;
+        }
+        Vec::new()
+    }
+
+    /// Report the report being read at the end of the output, if any, and
+    /// the number of vulnerabilities.
+    fn finish(&mut self) -> Vec<Result<OsvMessage, serde_json::Error>> {
+        let mut results = Vec::new();
+        if let Some((line_number, offset)) = self.start {
+            // The last line may end within a string, which cannot contain the
+            // newline which ends it
+            if self.document.last() == Some(&b'\n') {
+                self.document.pop();
+            }
+            let length = self.document.len();
+            results.extend(self.parse().into_iter().map(|result| {
+                result.or_else(|error| {
+                    if error.is_eof() {
+                        Ok(OsvMessage::TruncatedLine(TruncatedLine {
+                            line_number,
+                            offset,
+                            length,
+                            error: error.to_string(),
+                        }))
+                    } else {
+                        Err(error)
+                    }
+                })
+            }));
+        }
+
+        let totals = mem::take(self);
+        results.push(Ok(OsvMessage::Summary(OsvSummary {
+            reported: totals.reported,
+            vulnerabilities: totals.vulnerabilities,
+            packages: totals.packages,
+        })));
+        results
+    }
+}
+
+/// Tool implementation for parsing the JSON report of OSV-Scanner.
+#[derive(Debug, Clone, Default)]
+pub struct OsvScanner {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of the report.
+    text: TextLines,
+    /// The report being read.
+    reader: Reader,
+}
+
+impl Detect for OsvScanner {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        // The fields of the scanned sources and of their packages, whether
+        // the report is printed over many lines or compacted
+        let field = |name: &[u8]| memchr::memmem::find(sample, name).is_some();
+        (field(b"\"results\":") && field(b"\"source\":") && field(b"\"ecosystem\":"))
+            .then(Self::default)
+    }
+}
+
+impl OsvScanner {
+    /// A problem matcher for the table printed by OSV-Scanner.
+    ///
+    /// Each vulnerability is a row of the table (e.g., `│
+    /// https://osv.dev/GHSA-wcg3-cvx6-7396 │ 6.2 │ crates.io │ time │ 0.1.45
+    /// │ Cargo.lock │`), which gives its ID, the affected package and, in
+    /// its last column, the scanned source. As the line of the package is
+    /// not given, each match is reported as an error on the source, with the
+    /// name of the package as its message.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-osv-scanner",
+            vec![
+                Pattern::new(
+                    r"^[│|] https://osv\.dev/(\S+) +[│|][^│|]*[│|] *\S+ +[│|] *(\S+) +[│|].*[│|] *(\S+) +[│|]$",
+                )
+                .code(1)
+                .message(2)
+                .file(3),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for OsvScanner {
+    type Message = OsvMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "osv-scanner"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        loop {
+            let position = self.framer.position();
+            let Some(line) = self.framer.next_line() else {
+                break;
+            };
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+
+        results.extend(self.reader.finish());
+        results.extend(
+            self.text
+                .finish()
+                .map(|output| Ok(OsvMessage::TextOutput(output))),
+        );
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for OsvScanner
+where
+    OsvMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    use super::{OsvMessage, OsvScanner, VulnerabilityLevel, find_package};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{Bandit, Detect as _, Psalm, Tool as _};
+
+    const REPORT: &str = r#"{
+  "results": [
+    {
+      "source": {
+        "path": "/w/app/Cargo.lock",
+        "type": "lockfile"
+      },
+      "packages": [
+        {
+          "package": {
+            "name": "time",
+            "version": "0.1.45",
+            "ecosystem": "crates.io"
+          },
+          "vulnerabilities": [
+            {
+              "id": "GHSA-wcg3-cvx6-7396",
+              "aliases": ["CVE-2020-26235"],
+              "summary": "Segmentation fault in time",
+              "affected": [
+                {
+                  "package": {"ecosystem": "crates.io", "name": "time"},
+                  "ranges": [{"type": "SEMVER", "events": [{"introduced": "0.0.0-0"}, {"fixed": "0.2.23"}]}]
+                }
+              ],
+              "database_specific": {"severity": "MODERATE"}
+            },
+            {
+              "id": "RUSTSEC-2020-0071",
+              "aliases": ["CVE-2020-26235", "GHSA-wcg3-cvx6-7396"],
+              "summary": "Potential segfault in the time crate",
+              "affected": [
+                {
+                  "package": {"ecosystem": "crates.io", "name": "time"},
+                  "ranges": [{"type": "SEMVER", "events": [{"introduced": "0.0.0-0"}, {"fixed": "0.2.23"}]}]
+                }
+              ],
+              "database_specific": {"informational": null}
+            }
+          ],
+          "groups": [
+            {
+              "ids": ["GHSA-wcg3-cvx6-7396", "RUSTSEC-2020-0071"],
+              "aliases": ["CVE-2020-26235", "GHSA-wcg3-cvx6-7396", "RUSTSEC-2020-0071"],
+              "max_severity": "6.2"
+            }
+          ]
+        },
+        {
+          "package": {
+            "name": "smallvec",
+            "version": "1.6.0",
+            "ecosystem": "crates.io"
+          },
+          "vulnerabilities": [
+            {
+              "id": "RUSTSEC-2021-0003",
+              "summary": "Buffer overflow in SmallVec::insert_many",
+              "affected": [
+                {
+                  "package": {"ecosystem": "crates.io", "name": "smallvec"},
+                  "ranges": [{"type": "SEMVER", "events": [{"introduced": "0.3.0"}, {"fixed": "0.6.14"}, {"introduced": "1.0.0"}, {"fixed": "1.6.1"}]}]
+                }
+              ]
+            }
+          ],
+          "groups": [
+            {
+              "ids": ["RUSTSEC-2021-0003"],
+              "aliases": ["CVE-2021-25900"],
+              "max_severity": "9.8"
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+"#;
+
+    const LOCKFILE: &str = r#"# This file is automatically @generated by Cargo.
+version = 4
+
+[[package]]
+name = "smallvec"
+version = "1.6.0"
+
+[[package]]
+name = "time"
+version = "0.1.44"
+
+[[package]]
+name = "time"
+version = "0.1.45"
+dependencies = [
+ "libc",
+]
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<OsvMessage> {
+        let mut tool = OsvScanner::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        OsvMessage: CiMessage<P>,
+    {
+        let mut tool = OsvScanner::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <OsvMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .map(|formatted| formatted.trim_end().to_owned())
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(REPORT), @r#"
+        /w/app/Cargo.lock:1: warning: GHSA-wcg3-cvx6-7396: Segmentation fault in time (medium severity, CVSS 6.2)\nPackage: time 0.1.45 (crates.io)\nAliases: RUSTSEC-2020-0071, CVE-2020-26235\nFixed in: 0.2.23\nDetails: https://osv.dev/GHSA-wcg3-cvx6-7396
+        /w/app/Cargo.lock:1: error:   RUSTSEC-2021-0003: Buffer overflow in SmallVec::insert_many (critical severity, CVSS 9.8)\nPackage: smallvec 1.6.0 (crates.io)\nAliases: CVE-2021-25900\nFixed in: 0.6.14, 1.6.1\nDetails: https://osv.dev/RUSTSEC-2021-0003
+        notice:  Vulnerability Scan: 2 known vulnerability(ies) in 2 package(s)
+        "#);
+    }
+
+    #[test]
+    fn lockfile() {
+        let dir = assert_fs::TempDir::new().expect("failed to create temp dir");
+        let lockfile = dir.child("Cargo.lock");
+        lockfile
+            .write_str(LOCKFILE)
+            .expect("failed to write lockfile");
+        let path = lockfile.path().display().to_string();
+        let report = REPORT.replace("/w/app/Cargo.lock", &path);
+
+        let output = format::<GitHub>(&report).replace(&path, "/w/app/Cargo.lock");
+        insta::assert_snapshot!(output, @"
+        ::warning file=/w/app/Cargo.lock,line=13,title=GHSA-wcg3-cvx6-7396::Segmentation fault in time (medium severity, CVSS 6.2)%0APackage: time 0.1.45 (crates.io)%0AAliases: RUSTSEC-2020-0071, CVE-2020-26235%0AFixed in: 0.2.23%0ADetails: https://osv.dev/GHSA-wcg3-cvx6-7396
+        ::error file=/w/app/Cargo.lock,line=5,title=RUSTSEC-2021-0003::Buffer overflow in SmallVec::insert_many (critical severity, CVSS 9.8)%0APackage: smallvec 1.6.0 (crates.io)%0AAliases: CVE-2021-25900%0AFixed in: 0.6.14, 1.6.1%0ADetails: https://osv.dev/RUSTSEC-2021-0003
+        ::notice title=Vulnerability Scan::2 known vulnerability(ies) in 2 package(s)
+        ");
+    }
+
+    #[test]
+    fn vulnerabilities() {
+        let messages = parse(REPORT, 13);
+        assert_eq!(messages, parse(REPORT, REPORT.len()));
+        let [
+            OsvMessage::Vulnerability(time),
+            OsvMessage::Vulnerability(smallvec),
+            OsvMessage::Summary(summary),
+        ] = messages.as_slice()
+        else {
+            panic!("expected two vulnerabilities and a summary");
+        };
+        assert_eq!(time.id, "GHSA-wcg3-cvx6-7396");
+        assert_eq!(time.aliases, ["RUSTSEC-2020-0071", "CVE-2020-26235"]);
+        assert_eq!(time.level, VulnerabilityLevel::Medium);
+        assert_eq!(time.fixed, ["0.2.23"]);
+        assert_eq!(smallvec.level, VulnerabilityLevel::Critical);
+        assert_eq!(smallvec.fixed, ["0.6.14", "1.6.1"]);
+        assert_eq!(smallvec.code(), Some("RUSTSEC-2021-0003"));
+        assert_eq!((summary.vulnerabilities, summary.packages), (2, 2));
+    }
+
+    #[test]
+    fn packages() {
+        assert_eq!(find_package(LOCKFILE, "time", "0.1.45"), Some(13));
+        assert_eq!(find_package(LOCKFILE, "time", "0.2.0"), Some(9));
+        assert_eq!(find_package(LOCKFILE, "smallvec", "1.6.0"), Some(5));
+        assert_eq!(find_package(LOCKFILE, "libc", ""), Some(16));
+        assert_eq!(find_package(LOCKFILE, "small", "1.6.0"), None);
+    }
+
+    #[test]
+    fn ungrouped() {
+        let output = r#"{"results":[{"source":{"path":"/w/web/package-lock.json","type":"lockfile"},"packages":[{"package":{"name":"lodash","version":"4.17.20","ecosystem":"npm"},"vulnerabilities":[{"id":"GHSA-35jh-r3h4-6jhm","aliases":["CVE-2021-23337"],"summary":"Command Injection in lodash","affected":[{"package":{"ecosystem":"npm","name":"lodash"},"ranges":[{"type":"SEMVER","events":[{"introduced":"0"},{"fixed":"4.17.21"}]}]}],"database_specific":{"severity":"HIGH"}}]}]}]}"#;
+        insta::assert_snapshot!(format::<Plain>(output), @r#"
+        /w/web/package-lock.json:1: error:   GHSA-35jh-r3h4-6jhm: Command Injection in lodash (high severity)\nPackage: lodash 4.17.20 (npm)\nAliases: CVE-2021-23337\nFixed in: 4.17.21\nDetails: https://osv.dev/GHSA-35jh-r3h4-6jhm
+        notice:  Vulnerability Scan: 1 known vulnerability(ies) in 1 package(s)
+        "#);
+    }
+
+    #[test]
+    fn clean() {
+        let output = "Scanning dir .\nNo issues found\n{\n  \"results\": []\n}\n";
+        insta::assert_snapshot!(format::<GitHub>(output), @"::notice title=Vulnerability Scan::No known vulnerabilities found");
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = REPORT.get(..300).expect("valid index");
+        insta::assert_snapshot!(format::<Plain>(cut), @"
+        warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 300 bytes of an incomplete message: EOF while parsing a string at line 15 column 22
+        notice:  Vulnerability Scan: No report of OSV-Scanner found
+        ");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(OsvScanner::detect(REPORT.as_bytes()).is_some());
+        assert!(OsvScanner::detect(b"{\"results\": []}\n").is_none());
+        assert!(Bandit::detect(REPORT.as_bytes()).is_none());
+        assert!(Psalm::detect(REPORT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = OsvScanner::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regex
+            .captures("│ https://osv.dev/GHSA-wcg3-cvx6-7396 │ 6.2  │ crates.io │ time     │ 0.1.45  │ Cargo.lock │")
+            .expect("vulnerability not matched");
+        assert_eq!(
+            captures.get(1).map(|m| m.as_str()),
+            Some("GHSA-wcg3-cvx6-7396")
+        );
+        assert_eq!(captures.get(2).map(|m| m.as_str()), Some("time"));
+        assert_eq!(captures.get(3).map(|m| m.as_str()), Some("Cargo.lock"));
+        assert!(!regex.is_match("│ OSV URL                             │ CVSS │ ECOSYSTEM │ PACKAGE  │ VERSION │ SOURCE     │"));
+    }
+}
@@ -0,0 +1,72 @@
+//! Overlong line messages.
+//!
+//! Tools which split their output into lines cap the length of a single line
+//! to bound their memory usage. Lines exceeding this cap (e.g., a gigantic
+//! JSON message, or binary output without any newlines) are cut to the cap,
+//! and reported through an [`OverlongLine`] message before what was kept of
+//! them is parsed.
+
+use crate::{
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// A line of the tool's output which exceeded the maximum line length.
+///
+/// The content of the line beyond the maximum length is discarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OverlongLine {
+    /// The maximum line length, in bytes.
+    pub max_length: usize,
+}
+
+impl<P: Platform> CiMessage<P> for OverlongLine {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            format!(
+                "Truncated line exceeding the maximum length of {} bytes",
+                self.max_length
+            ),
+            None,
+            Some(Title::LineTooLong.text()),
+        )
+    }
+}
+
+impl Classify for OverlongLine {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+}
+
+impl Timed for OverlongLine {}
+
+#[cfg(test)]
+mod tests {
+    use super::OverlongLine;
+    use crate::ci::{AzureDevOps, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[test]
+    fn format() {
+        let message = OverlongLine { max_length: 1024 };
+        insta::assert_snapshot!(
+            <OverlongLine as CiMessage<Plain>>::format(&message),
+            @"warning: Line Too Long: Truncated line exceeding the maximum length of 1024 bytes"
+        );
+        insta::assert_snapshot!(
+            <OverlongLine as CiMessage<GitHub>>::format(&message),
+            @"::warning title=Line Too Long::Truncated line exceeding the maximum length of 1024 bytes"
+        );
+        insta::assert_snapshot!(
+            <OverlongLine as CiMessage<AzureDevOps>>::format(&message),
+            @"##vso[task.logissue type=warning]Line Too Long: Truncated line exceeding the maximum length of 1024 bytes"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Passthrough of human-readable output.
+//!
+//! Tools emitting structured output may still interleave it with lines of
+//! human-readable text (e.g., cargo printing `Blocking waiting for file lock`
+//! to the same stream). Such lines are dropped by default, and can otherwise
+//! be passed through according to a [`Passthrough`] policy, which determines
+//! the [`TextOutput`] messages reported for them.
+
+use core::fmt;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Platform, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// Policy for lines of the tool's output which are not structured messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Passthrough {
+    /// Drop the lines.
+    #[default]
+    Drop,
+    /// Print each line as is.
+    Raw,
+    /// Print each line as a debug message, which is typically hidden.
+    Debug,
+    /// Collect the lines, and print them in a group at the end of the output.
+    Collect,
+}
+
+/// Human-readable output of the tool, passed through according to the
+/// [`Passthrough`] policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TextOutput {
+    /// A line printed as is.
+    Raw(String),
+    /// A line printed as a debug message.
+    Debug(String),
+    /// All lines collected until the end of the output.
+    Collected(Vec<String>),
+}
+
+impl TextOutput {
+    /// Display the collected lines, each followed by a newline.
+    fn lines(lines: &[String]) -> impl fmt::Display {
+        fmt::from_fn(move |f| lines.iter().try_for_each(|line| writeln!(f, "{line}")))
+    }
+}
+
+impl CiMessage<Plain> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Raw(line) => line.clone(),
+            Self::Debug(line) => Plain::line(Severity::Debug, line, None, None),
+            Self::Collected(lines) => format!("OTHER OUTPUT:\n{}", lines.join("\n")),
+        }
+    }
+}
+
+impl CiMessage<GitHub> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Raw(line) => line.clone(),
+            Self::Debug(line) => GitHub::debug(line),
+            Self::Collected(lines) => format!(
+                "{}\n{}{}",
+                GitHub::group(Title::OtherOutput.text()),
+                Self::lines(lines),
+                GitHub::endgroup()
+            ),
+        }
+    }
+}
+
+impl CiMessage<AzureDevOps> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Raw(line) => line.clone(),
+            Self::Debug(line) => AzureDevOps::debug(line),
+            Self::Collected(lines) => format!(
+                "{}\n{}{}",
+                AzureDevOps::group(Title::OtherOutput.text()),
+                Self::lines(lines),
+                AzureDevOps::endgroup()
+            ),
+        }
+    }
+}
+
+impl CiMessage<Buildkite> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Raw(_) | Self::Debug(_) => <Self as CiMessage<Plain>>::format(self),
+            Self::Collected(lines) => format!(
+                "{}\n{}",
+                Buildkite::group(Title::OtherOutput.text()),
+                lines.join("\n")
+            ),
+        }
+    }
+}
+
+impl CiMessage<CodeBuild> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for TextOutput {
+    #[inline]
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl Classify for TextOutput {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+}
+
+impl Timed for TextOutput {}
+
+impl Captured for TextOutput {}
+
+/// Applies a [`Passthrough`] policy to the lines of a tool's output.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TextLines {
+    /// The policy applied to each line.
+    policy: Passthrough,
+    /// Lines collected under [`Passthrough::Collect`].
+    collected: Vec<String>,
+}
+
+impl TextLines {
+    /// Set the policy applied to subsequent lines.
+    pub(crate) fn set_policy(&mut self, policy: Passthrough) {
+        self.policy = policy;
+    }
+
+    /// Handle a line which is not a structured message.
+    ///
+    /// # Returns
+    ///
+    /// The message to report for the line, if any.
+    pub(crate) fn handle(&mut self, line: &[u8]) -> Option<TextOutput> {
+        let text = String::from_utf8_lossy(line).into_owned();
+        match self.policy {
+            Passthrough::Drop => None,
+            Passthrough::Raw => Some(TextOutput::Raw(text)),
+            Passthrough::Debug => Some(TextOutput::Debug(text)),
+            Passthrough::Collect => {
+                self.collected.push(text);
+                None
+            }
+        }
+    }
+
+    /// Finish at the end of the tool's output.
+    ///
+    /// # Returns
+    ///
+    /// The collected lines, if any.
+    pub(crate) fn finish(&mut self) -> Option<TextOutput> {
+        let collected = core::mem::take(&mut self.collected);
+        (!collected.is_empty()).then_some(TextOutput::Collected(collected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{Passthrough, TextLines, TextOutput};
+    use crate::ci::{AzureDevOps, Buildkite, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[rstest]
+    #[case::drop(Passthrough::Drop, None)]
+    #[case::raw(Passthrough::Raw, Some(TextOutput::Raw("note: waiting".to_owned())))]
+    #[case::debug(Passthrough::Debug, Some(TextOutput::Debug("note: waiting".to_owned())))]
+    #[case::collect(Passthrough::Collect, None)]
+    fn handle(#[case] policy: Passthrough, #[case] expected: Option<TextOutput>) {
+        let mut lines = TextLines::default();
+        lines.set_policy(policy);
+        assert_eq!(lines.handle(b"note: waiting"), expected);
+    }
+
+    #[test]
+    fn collect() {
+        let mut lines = TextLines::default();
+        lines.set_policy(Passthrough::Collect);
+        assert_eq!(lines.handle(b"first"), None);
+        assert_eq!(lines.handle(b"second"), None);
+        assert_eq!(
+            lines.finish(),
+            Some(TextOutput::Collected(vec![
+                "first".to_owned(),
+                "second".to_owned()
+            ]))
+        );
+        assert_eq!(lines.finish(), None);
+    }
+
+    #[test]
+    fn format() {
+        let debug = TextOutput::Debug("Blocking waiting for file lock".to_owned());
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<Plain>>::format(&debug),
+            @"debug:   Blocking waiting for file lock"
+        );
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<GitHub>>::format(&debug),
+            @"::debug::Blocking waiting for file lock"
+        );
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<AzureDevOps>>::format(&debug),
+            @"##[debug]Blocking waiting for file lock"
+        );
+
+        let collected = TextOutput::Collected(vec!["first".to_owned(), "second".to_owned()]);
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<Plain>>::format(&collected),
+            @"
+        OTHER OUTPUT:
+        first
+        second
+        "
+        );
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<GitHub>>::format(&collected),
+            @"
+        ::group::Other Output
+        first
+        second
+        ::endgroup::
+        "
+        );
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<AzureDevOps>>::format(&collected),
+            @"
+        ##[group]Other Output
+        first
+        second
+        ##[endgroup]
+        "
+        );
+        insta::assert_snapshot!(
+            <TextOutput as CiMessage<Buildkite>>::format(&collected),
+            @"
+        --- Other Output
+        first
+        second
+        "
+        );
+    }
+}
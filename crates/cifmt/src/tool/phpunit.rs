@@ -0,0 +1,955 @@
+//! PHPUnit TeamCity output and JUnit XML reports.
+//!
+//! Support for parsing the results of PHPUnit, either as printed with
+//! `--teamcity` (a `##teamcity[...]` service message for each event of the
+//! run) or as written with `--log-junit`. Both are reported as JUnit XML
+//! reports are by the [`Junit`](crate::tool::Junit) tool: each test with its
+//! outcome as it finishes, and each test class (or suite) with the tally of
+//! the tests it contains directly as it finishes.
+//!
+//! PHPUnit locates a test at the line which declares its method, while its
+//! failure (or error) is best located at the line of the test which failed.
+//! A failed test is therefore annotated at the frame of its stack trace (e.g.,
+//! `/app/tests/CalculatorTest.php:20`) within the file of the test, or
+//! failing this, at the first frame of the stack trace, which is where an
+//! error was thrown.
+//!
+//! Any text outside of the service messages or of the report (e.g., the
+//! version of PHPUnit) is passed through as plain text output.
+
+use core::{convert::Infallible, time::Duration};
+use std::path::Path;
+
+use crate::{
+    capture::{Captured, OutputPolicy},
+    ci::{Phase, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    junit::{TestOutcome, TestResult},
+    matcher::{Pattern, ProblemMatcher},
+    suites::SuiteResult,
+    timeline::{Timed, Timing},
+    tool::{
+        Detect, DynTool, Junit, Tool,
+        junit::{IncompleteReport, JunitMessage, TestCase, TestSuite},
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines},
+    },
+};
+
+/// A message parsed from the results of PHPUnit.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PhpunitMessage {
+    /// The result of a test or of a test suite, or output outside of the
+    /// results, as read from a JUnit XML report.
+    Junit(JunitMessage),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+}
+
+impl<P: Platform> CiMessage<P> for PhpunitMessage
+where
+    JunitMessage: CiMessage<P>,
+    OverlongLine: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Junit(msg) => <JunitMessage as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for PhpunitMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Junit(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Junit(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        match self {
+            Self::Junit(msg) => msg.is_test_failure(),
+            Self::OverlongLine(_) => false,
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::Junit(msg) => msg.is_incomplete(),
+            Self::OverlongLine(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Junit(msg) => msg.sort_key(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Junit(msg) => msg.file(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Junit(msg) => msg.message(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        match self {
+            Self::Junit(msg) => msg.test_result(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            Self::Junit(msg) => msg.suite_result(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+}
+
+impl Timed for PhpunitMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::Junit(msg) => msg.timing(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+}
+
+impl Captured for PhpunitMessage {
+    #[inline]
+    fn captured_output_mut(&mut self) -> Option<(&str, &mut String)> {
+        match self {
+            Self::Junit(msg) => msg.captured_output_mut(),
+            Self::OverlongLine(_) => None,
+        }
+    }
+}
+
+/// Parse a frame of a stack trace of PHPUnit (e.g.,
+/// `/app/tests/CalculatorTest.php:20`) into its file and line.
+fn parse_frame(line: &str) -> Option<(&str, u32)> {
+    let (file, number) = line.trim().rsplit_once(':')?;
+    if !Path::new(file)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("php"))
+    {
+        return None;
+    }
+    Some((file, number.parse().ok()?))
+}
+
+/// Locate a failed test at the frame of its stack trace within its file, or
+/// failing this, at the first frame of its stack trace.
+fn relocate(case: &mut TestCase) {
+    if case.outcome != TestOutcome::Failed {
+        return;
+    }
+    let Some(details) = case.details.as_deref() else {
+        return;
+    };
+    let mut frames = details.lines().filter_map(parse_frame);
+    let first = frames.clone().next();
+    let frame = match case.file.as_deref() {
+        Some(test_file) => frames.find(|&(file, _)| file == test_file),
+        None => None,
+    };
+    if let Some((file, line)) = frame.or(first) {
+        case.file = Some(file.to_owned());
+        case.line = Some(line);
+    }
+}
+
+/// Replace the message of a failed test of a JUnit XML report, which
+/// PHPUnit does not give, with the first line of the text of its failure.
+///
+/// The text starts with the name of the test (e.g.,
+/// `Tests\CalculatorTest::testSubtract`), followed by the message of the
+/// failure and its stack trace.
+fn read_message(case: &mut TestCase) {
+    if case.outcome != TestOutcome::Failed {
+        return;
+    }
+    let mut lines = case.details.as_deref().unwrap_or_default().lines();
+    if !lines.next().is_some_and(|first| first.contains("::")) {
+        return;
+    }
+    if let Some(message) = lines.map(str::trim).find(|line| !line.is_empty()) {
+        case.message = Some(message.to_owned());
+    }
+}
+
+/// Decode the value of an attribute of a TeamCity service message, in which
+/// `|` escapes the quote, the brackets, line breaks and itself.
+fn unescape(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '|' {
+            decoded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => decoded.push('\n'),
+            Some('r') => decoded.push('\r'),
+            Some('x') => decoded.push('\u{85}'),
+            Some('l') => decoded.push('\u{2028}'),
+            Some('p') => decoded.push('\u{2029}'),
+            Some('0') => {
+                // A character given by its code (e.g., `|0x00e9`)
+                let code = chars.as_str().strip_prefix('x').and_then(|rest| {
+                    let digits = rest.get(..4)?;
+                    char::from_u32(u32::from_str_radix(digits, 16).ok()?)
+                });
+                if let Some(decoded_char) = code {
+                    decoded.push(decoded_char);
+                    chars = chars.as_str().get(5..).unwrap_or_default().chars();
+                } else {
+                    decoded.push('0');
+                }
+            }
+            Some(escaped) => decoded.push(escaped),
+            None => decoded.push('|'),
+        }
+    }
+    decoded
+}
+
+/// A TeamCity service message (e.g., `##teamcity[testStarted
+/// name='testAdd']`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ServiceMessage {
+    /// The name of the message (e.g., `testStarted`).
+    name: String,
+    /// The attributes of the message, decoded.
+    attributes: Vec<(String, String)>,
+}
+
+impl ServiceMessage {
+    /// Parse a line holding a service message.
+    ///
+    /// # Returns
+    ///
+    /// The message, or `None` if the line does not hold one.
+    fn parse(line: &str) -> Option<Self> {
+        let body = line.trim().strip_prefix("##teamcity[")?.strip_suffix(']')?;
+        let (name, mut rest) = body.split_once(' ').unwrap_or((body, ""));
+        let mut attributes = Vec::new();
+        while let Some((key, value)) = rest.trim_start().split_once("='") {
+            // The value ends at the first quote which is not escaped
+            let mut closing = None;
+            let mut escaped = false;
+            for (index, c) in value.char_indices() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '|' => escaped = true,
+                    '\'' => {
+                        closing = Some(index);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let end = closing?;
+            attributes.push((key.to_owned(), unescape(value.get(..end)?)));
+            rest = value.get(end.saturating_add(1)..)?;
+        }
+        Some(Self {
+            name: name.to_owned(),
+            attributes,
+        })
+    }
+
+    /// The value of an attribute, if given.
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// The file and the qualified name given by the location hint of a
+/// service message (e.g.,
+/// `php_qn:///app/tests/CalculatorTest.php::\Tests\CalculatorTest::testAdd`).
+///
+/// The name is qualified as in the JUnit XML report of PHPUnit (e.g.,
+/// `Tests.CalculatorTest.testAdd`).
+fn parse_location(hint: &str) -> Option<(&str, String)> {
+    let (file, name) = hint.strip_prefix("php_qn://")?.split_once("::")?;
+    let qualified = name
+        .trim_start_matches('\\')
+        .replace("::", ".")
+        .replace('\\', ".");
+    Some((file, qualified))
+}
+
+/// A test suite of the TeamCity output being read.
+#[derive(Debug, Clone)]
+struct OpenSuite {
+    /// The name of the suite.
+    name: String,
+    /// The file of the suite, if known.
+    file: Option<String>,
+    /// The tally of the tests of the suite read so far.
+    result: SuiteResult,
+}
+
+/// The state of the parser of the TeamCity output.
+#[derive(Debug, Clone, Default)]
+struct TeamCity {
+    /// The suites being read, innermost last.
+    suites: Vec<OpenSuite>,
+    /// The test being read, if any.
+    case: Option<TestCase>,
+}
+
+impl TeamCity {
+    /// Handle a service message.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the service message.
+    fn handle(&mut self, message: &ServiceMessage) -> Vec<JunitMessage> {
+        let name = message.attribute("name").unwrap_or_default();
+        match message.name.as_str() {
+            "testSuiteStarted" => {
+                self.suites.push(OpenSuite {
+                    name: name.to_owned(),
+                    file: message
+                        .attribute("locationHint")
+                        .and_then(parse_location)
+                        .map(|(file, _)| file.to_owned()),
+                    result: SuiteResult::new(0, 0, 0),
+                });
+                Vec::new()
+            }
+            "testSuiteFinished" => {
+                let mut results = self.finish_test();
+                if let Some(suite) = self.suites.pop() {
+                    results.push(JunitMessage::TestSuite(TestSuite {
+                        name: suite.name,
+                        result: suite.result,
+                    }));
+                }
+                results
+            }
+            "testStarted" => {
+                let results = self.finish_test();
+                self.case = Some(self.start_test(message));
+                results
+            }
+            "testFailed" | "testIgnored" => {
+                let case = self.case.get_or_insert_with(|| {
+                    // A class which fails before its tests (e.g., in
+                    // `setUpBeforeClass`) has no test started
+                    let suite = self.suites.last();
+                    TestCase {
+                        name: name.to_owned(),
+                        outcome: TestOutcome::Passed,
+                        message: None,
+                        details: None,
+                        output: None,
+                        duration: None,
+                        file: suite.and_then(|open| open.file.clone()),
+                        line: None,
+                    }
+                });
+                case.message = message
+                    .attribute("message")
+                    .map(str::trim)
+                    .filter(|text| !text.is_empty())
+                    .map(str::to_owned);
+                if message.name == "testIgnored" {
+                    case.outcome = TestOutcome::Skipped;
+                    return Vec::new();
+                }
+                case.outcome = TestOutcome::Failed;
+                let mut details = Vec::new();
+                if let (Some(expected), Some(actual)) =
+                    (message.attribute("expected"), message.attribute("actual"))
+                {
+                    details.push(format!("Expected: {expected}\nActual:   {actual}"));
+                }
+                details.extend(
+                    message
+                        .attribute("details")
+                        .map(str::trim)
+                        .filter(|trace| !trace.is_empty())
+                        .map(str::to_owned),
+                );
+                case.details = (!details.is_empty()).then(|| details.join("\n\n"));
+                relocate(case);
+                Vec::new()
+            }
+            "testFinished" => {
+                if let Some(case) = &mut self.case {
+                    case.duration = message
+                        .attribute("duration")
+                        .and_then(|millis| millis.parse().ok())
+                        .map(Duration::from_millis);
+                }
+                self.finish_test()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Create a test from its `testStarted` message.
+    fn start_test(&self, message: &ServiceMessage) -> TestCase {
+        let name = message.attribute("name").unwrap_or_default();
+        let location = message.attribute("locationHint").and_then(parse_location);
+        TestCase {
+            name: location
+                .as_ref()
+                .map_or_else(|| name.to_owned(), |(_, qualified)| qualified.clone()),
+            outcome: TestOutcome::Passed,
+            message: None,
+            details: None,
+            output: None,
+            duration: None,
+            file: location
+                .map(|(file, _)| file.to_owned())
+                .or_else(|| self.suites.last()?.file.clone()),
+            line: None,
+        }
+    }
+
+    /// Report the test being read, if any, tallying it in its suite.
+    fn finish_test(&mut self) -> Vec<JunitMessage> {
+        let Some(case) = self.case.take() else {
+            return Vec::new();
+        };
+        if let Some(suite) = self.suites.last_mut() {
+            let tally = match case.outcome {
+                TestOutcome::Passed => &mut suite.result.passed,
+                TestOutcome::Failed => &mut suite.result.failed,
+                TestOutcome::Skipped => &mut suite.result.ignored,
+            };
+            *tally = tally.saturating_add(1);
+        }
+        vec![JunitMessage::TestCase(case)]
+    }
+
+    /// Report an output which ended before all of its suites, if any.
+    fn finish(&mut self) -> Option<JunitMessage> {
+        let pending = self.case.take().map(|case| case.name);
+        let suite = self.suites.pop().map(|open| open.name);
+        self.suites.clear();
+        if suite.is_none() && pending.is_none() {
+            return None;
+        }
+        Some(JunitMessage::IncompleteReport(IncompleteReport {
+            suite,
+            pending,
+        }))
+    }
+}
+
+/// Read the messages of a JUnit XML report, locating its failed tests from
+/// their stack traces.
+fn read_report(messages: Vec<Result<JunitMessage, Infallible>>) -> Vec<PhpunitMessage> {
+    messages
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(|mut msg| {
+            if let JunitMessage::TestCase(case) = &mut msg {
+                read_message(case);
+                relocate(case);
+            }
+            PhpunitMessage::Junit(msg)
+        })
+        .collect()
+}
+
+/// The state of the parser.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The state of the parser of the TeamCity output.
+    teamcity: TeamCity,
+    /// The reader of the JUnit XML report being read, if any.
+    junit: Option<Junit>,
+    /// The policy for the inclusion of the output of tests.
+    policy: OutputPolicy,
+}
+
+impl Reader {
+    /// Parse a single line of the output of PHPUnit.
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the results.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<PhpunitMessage> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                let mut results = vec![PhpunitMessage::OverlongLine(OverlongLine { max_length })];
+                results.extend(self.parse_line(Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        let trimmed = line.trim_ascii();
+        if self.junit.is_none()
+            && (trimmed.starts_with(b"<?xml") || trimmed.starts_with(b"<testsuites"))
+        {
+            let mut junit = Junit::default();
+            junit.set_output_policy(self.policy);
+            self.junit = Some(junit);
+        }
+        if let Some(junit) = &mut self.junit {
+            let mut messages = junit.parse(line);
+            messages.extend(junit.parse(b"\n"));
+            if trimmed == b"</testsuites>" {
+                messages.extend(junit.finish());
+                self.junit = None;
+            }
+            return read_report(messages);
+        }
+
+        if let Some(message) = ServiceMessage::parse(&String::from_utf8_lossy(line)) {
+            return self
+                .teamcity
+                .handle(&message)
+                .into_iter()
+                .map(PhpunitMessage::Junit)
+                .collect();
+        }
+        text.handle(line)
+            .map(|output| PhpunitMessage::Junit(JunitMessage::TextOutput(output)))
+            .into_iter()
+            .collect()
+    }
+
+    /// Report the results which did not end at the end of the output, if
+    /// any.
+    fn finish(&mut self) -> Vec<PhpunitMessage> {
+        let mut results = Vec::new();
+        if let Some(mut junit) = self.junit.take() {
+            results.extend(read_report(junit.finish()));
+        }
+        results.extend(self.teamcity.finish().map(PhpunitMessage::Junit));
+        results
+    }
+}
+
+/// Tool implementation for parsing the results of PHPUnit.
+#[derive(Debug, Clone, Default)]
+pub struct Phpunit {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of the results.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl Detect for Phpunit {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        let find = |needle: &[u8]| memchr::memmem::find(sample, needle).is_some();
+        // The location hints of the service messages, or the attributes which
+        // PHPUnit gives each test of its JUnit XML report
+        ((find(b"##teamcity[") && find(b"php_qn://"))
+            || (find(b"<testcase ") && find(b" assertions=\"")))
+        .then(Self::default)
+    }
+}
+
+impl Phpunit {
+    /// A problem matcher for the TeamCity output of PHPUnit.
+    ///
+    /// Each `testFailed` service message gives the name of the test and the
+    /// message of its failure, which is reported as an error. As the stack
+    /// trace is in an attribute of its own, the error is not located.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-phpunit",
+            vec![
+                Pattern::new(
+                    r"^##teamcity\[testFailed name='((?:[^'|]|\|.)*)' message='((?:[^'|]|\|.)*)'",
+                )
+                .code(1)
+                .message(2),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for Phpunit {
+    type Message = PhpunitMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "phpunit"
+    }
+
+    #[inline]
+    fn phase(&self) -> Phase {
+        Phase::Test
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        results.extend(self.reader.finish());
+        results.extend(
+            self.text
+                .finish()
+                .map(|output| PhpunitMessage::Junit(JunitMessage::TextOutput(output))),
+        );
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.reader.policy = policy;
+    }
+}
+
+impl<P: Platform> DynTool<P> for Phpunit
+where
+    PhpunitMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Phpunit, PhpunitMessage, ServiceMessage, unescape};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify};
+    use crate::junit::TestOutcome;
+    use crate::suites::SuiteResult;
+    use crate::tool::junit::JunitMessage;
+    use crate::tool::{Detect as _, Junit, Tool as _};
+
+    const TEAMCITY: &str = r"PHPUnit 10.5.20 by Sebastian Bergmann and contributors.
+
+##teamcity[testCount count='4' flowId='4242']
+##teamcity[testSuiteStarted name='Tests\CalculatorTest' locationHint='php_qn:///app/tests/CalculatorTest.php::\Tests\CalculatorTest' flowId='4242']
+##teamcity[testStarted name='testAdd' locationHint='php_qn:///app/tests/CalculatorTest.php::\Tests\CalculatorTest::testAdd' flowId='4242']
+##teamcity[testFinished name='testAdd' duration='3' flowId='4242']
+##teamcity[testStarted name='testSubtract' locationHint='php_qn:///app/tests/CalculatorTest.php::\Tests\CalculatorTest::testSubtract' flowId='4242']
+##teamcity[testFailed name='testSubtract' message='Failed asserting that 1 matches expected 2.' details='/app/tests/CalculatorTest.php:20|n' duration='1' type='comparisonFailure' actual='1' expected='2' flowId='4242']
+##teamcity[testFinished name='testSubtract' duration='1' flowId='4242']
+##teamcity[testStarted name='testDivide' locationHint='php_qn:///app/tests/CalculatorTest.php::\Tests\CalculatorTest::testDivide' flowId='4242']
+##teamcity[testFailed name='testDivide' message='DivisionByZeroError: Division by zero' details='/app/src/Calculator.php:17|n/app/tests/CalculatorTest.php:27|n' duration='0' flowId='4242']
+##teamcity[testFinished name='testDivide' duration='0' flowId='4242']
+##teamcity[testStarted name='testMultiply' locationHint='php_qn:///app/tests/CalculatorTest.php::\Tests\CalculatorTest::testMultiply' flowId='4242']
+##teamcity[testIgnored name='testMultiply' message='Not implemented yet' duration='0' flowId='4242']
+##teamcity[testFinished name='testMultiply' duration='0' flowId='4242']
+##teamcity[testSuiteFinished name='Tests\CalculatorTest' flowId='4242']
+";
+
+    const JUNIT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="Tests\CalculatorTest" file="/app/tests/CalculatorTest.php" tests="2" assertions="2" errors="1" failures="0" skipped="0" time="0.004">
+    <testcase name="testAdd" file="/app/tests/CalculatorTest.php" line="12" class="Tests\CalculatorTest" classname="Tests.CalculatorTest" assertions="1" time="0.003"/>
+    <testcase name="testDivide" file="/app/tests/CalculatorTest.php" line="24" class="Tests\CalculatorTest" classname="Tests.CalculatorTest" assertions="1" time="0.001">
+      <error type="DivisionByZeroError">Tests\CalculatorTest::testDivide
+DivisionByZeroError: Division by zero
+
+/app/src/Calculator.php:17
+/app/tests/CalculatorTest.php:27</error>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<PhpunitMessage> {
+        let mut tool = Phpunit::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        PhpunitMessage: CiMessage<P>,
+    {
+        parse(output, output.len().max(1))
+            .iter()
+            .map(<PhpunitMessage as CiMessage<P>>::format)
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn teamcity() {
+        insta::assert_snapshot!(format::<Plain>(TEAMCITY), @r#"
+        notice:  Test Passed: Tests.CalculatorTest.testAdd: Executed in 0.00s
+        /app/tests/CalculatorTest.php:20: error:   Test Failed: Tests.CalculatorTest.testSubtract: Failed asserting that 1 matches expected 2.
+        Test: Tests.CalculatorTest.testSubtract
+        Expected: 2
+        Actual:   1
+
+        /app/tests/CalculatorTest.php:20
+        /app/tests/CalculatorTest.php:27: error:   Test Failed: Tests.CalculatorTest.testDivide: DivisionByZeroError: Division by zero
+        Test: Tests.CalculatorTest.testDivide
+        /app/src/Calculator.php:17
+        /app/tests/CalculatorTest.php:27
+        debug:   Test Ignored: Tests.CalculatorTest.testMultiply: Not implemented yet
+        error:   Test Suite Failed: Tests\CalculatorTest: 1 passed, 2 failed, 1 ignored
+        "#);
+    }
+
+    #[test]
+    fn teamcity_github() {
+        insta::assert_snapshot!(format::<GitHub>(TEAMCITY), @r#"
+        ::notice title=Test Passed%3A Tests.CalculatorTest.testAdd::Executed in 0.00s
+        ::error file=/app/tests/CalculatorTest.php,line=20,title=Test Failed%3A Tests.CalculatorTest.testSubtract::Failed asserting that 1 matches expected 2.
+        ::group::Test: Tests.CalculatorTest.testSubtract
+        Expected: 2
+        Actual:   1
+
+        /app/tests/CalculatorTest.php:20
+        ::endgroup::
+        ::error file=/app/tests/CalculatorTest.php,line=27,title=Test Failed%3A Tests.CalculatorTest.testDivide::DivisionByZeroError: Division by zero
+        ::group::Test: Tests.CalculatorTest.testDivide
+        /app/src/Calculator.php:17
+        /app/tests/CalculatorTest.php:27
+        ::endgroup::
+        ::debug::Test Ignored: Tests.CalculatorTest.testMultiply: Not implemented yet
+        ::error title=Test Suite Failed%3A Tests\CalculatorTest::1 passed, 2 failed, 1 ignored
+        "#);
+    }
+
+    #[test]
+    fn teamcity_results() {
+        let messages = parse(TEAMCITY, 11);
+        assert_eq!(messages, parse(TEAMCITY, TEAMCITY.len()));
+        let outcomes = messages
+            .iter()
+            .filter_map(Classify::test_result)
+            .map(|result| (result.name.to_owned(), result.outcome, result.duration))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [
+                (
+                    "Tests.CalculatorTest.testAdd".to_owned(),
+                    TestOutcome::Passed,
+                    Some(Duration::from_millis(3))
+                ),
+                (
+                    "Tests.CalculatorTest.testSubtract".to_owned(),
+                    TestOutcome::Failed,
+                    Some(Duration::from_millis(1))
+                ),
+                (
+                    "Tests.CalculatorTest.testDivide".to_owned(),
+                    TestOutcome::Failed,
+                    Some(Duration::ZERO)
+                ),
+                (
+                    "Tests.CalculatorTest.testMultiply".to_owned(),
+                    TestOutcome::Skipped,
+                    Some(Duration::ZERO)
+                ),
+            ]
+        );
+        let suites = messages
+            .iter()
+            .filter_map(Classify::suite_result)
+            .collect::<Vec<_>>();
+        assert_eq!(suites, [SuiteResult::new(1, 2, 1)]);
+    }
+
+    #[test]
+    fn junit() {
+        insta::assert_snapshot!(format::<Plain>(JUNIT), @r#"
+        notice:  Test Passed: Tests.CalculatorTest.testAdd: Executed in 0.00s
+        /app/tests/CalculatorTest.php:27: error:   Test Failed: Tests.CalculatorTest.testDivide: DivisionByZeroError: Division by zero
+        Test: Tests.CalculatorTest.testDivide
+        Tests\CalculatorTest::testDivide
+        DivisionByZeroError: Division by zero
+
+        /app/src/Calculator.php:17
+        /app/tests/CalculatorTest.php:27
+        error:   Test Suite Failed: Tests\CalculatorTest: 1 passed, 1 failed, 0 ignored in 0.00s
+        "#);
+    }
+
+    #[test]
+    fn junit_location() {
+        let failed = parse(JUNIT, 5)
+            .into_iter()
+            .find_map(|msg| match msg {
+                PhpunitMessage::Junit(JunitMessage::TestCase(case))
+                    if case.outcome == TestOutcome::Failed =>
+                {
+                    Some(case)
+                }
+                PhpunitMessage::Junit(_) | PhpunitMessage::OverlongLine(_) => None,
+            })
+            .expect("a failed test");
+        assert_eq!(
+            failed.file.as_deref(),
+            Some("/app/tests/CalculatorTest.php")
+        );
+        assert_eq!(failed.line, Some(27));
+        assert_eq!(
+            failed.message.as_deref(),
+            Some("DivisionByZeroError: Division by zero")
+        );
+    }
+
+    #[test]
+    fn incomplete() {
+        let cut = TEAMCITY.split_inclusive('\n').take(7).collect::<String>();
+        insta::assert_snapshot!(format::<Plain>(&cut), @r#"
+        notice:  Test Passed: Tests.CalculatorTest.testAdd: Executed in 0.00s
+        warning: Incomplete Test Suite: Report ended before test suite Tests\CalculatorTest; test Tests.CalculatorTest.testSubtract did not finish
+        "#);
+    }
+
+    #[test]
+    fn service_message() {
+        let message = ServiceMessage::parse(
+            "##teamcity[testFailed name='it|'s' message='a |[b|] |0x00e9|||n' flowId='1']",
+        )
+        .expect("a service message");
+        assert_eq!(message.name, "testFailed");
+        assert_eq!(message.attribute("name"), Some("it's"));
+        assert_eq!(message.attribute("message"), Some("a [b] é|\n"));
+        assert_eq!(message.attribute("flowId"), Some("1"));
+        assert!(ServiceMessage::parse("##teamcity[testFailed name='open").is_none());
+        assert_eq!(unescape("trailing|"), "trailing|");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Phpunit::detect(TEAMCITY.as_bytes()).is_some());
+        assert!(Phpunit::detect(JUNIT.as_bytes()).is_some());
+        assert!(Phpunit::detect(b"##teamcity[testStarted name='a']\n").is_none());
+        assert!(Phpunit::detect(b"<testsuite><testcase name=\"a\"/></testsuite>\n").is_none());
+        assert!(Junit::detect(JUNIT.as_bytes()).is_some());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Phpunit::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+        let captures = regex
+            .captures("##teamcity[testFailed name='testSubtract' message='Failed asserting that |'1|' is |'2|'.' details='' flowId='1']")
+            .expect("failure not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("testSubtract"));
+        assert_eq!(
+            captures.get(2).map(|m| m.as_str()),
+            Some("Failed asserting that |'1|' is |'2|'.")
+        );
+        assert!(!regex.is_match("##teamcity[testFinished name='testAdd' duration='3']"));
+    }
+}
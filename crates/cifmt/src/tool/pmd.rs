@@ -0,0 +1,1130 @@
+//! PMD reports.
+//!
+//! Support for parsing the reports which PMD writes with `--format xml` or
+//! `--format json` (e.g., `pmd check -d src -R rulesets/java/quickstart.xml
+//! -f xml`), so that the violations of its rules are annotated at their
+//! location. The format of each report is recognised from its first line:
+//! an XML report is read as it is received, with each `<violation>` reported
+//! as it ends, while a JSON report is a single object, printed over many
+//! lines, which is parsed once its closing brace is read (a JSON report cut
+//! off before its end is reported as truncated).
+//!
+//! Each violation is annotated on its first line, titled after the ruleset
+//! and the rule which it breaks (e.g., `Best Practices: UnusedPrivateField`),
+//! with the link to the documentation of the rule following its message. The
+//! priority of the rule is mapped to the level of the annotation: rules of
+//! high (1) and medium high (2) priority are errors, those of medium (3)
+//! priority are warnings, and those of medium low (4) and low (5) priority
+//! are notices.
+//!
+//! A file which PMD could not analyse (e.g., as it is not valid Java) is
+//! reported as a warning on its first line, with the stack trace of the
+//! error in a group, and a rule which PMD could not configure is reported as
+//! a warning. As PMD logs to stderr (e.g., `[WARN] This analysis could be
+//! faster`), any text outside of the reports is passed through as plain text
+//! output.
+
+use core::{fmt, mem};
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+        xml::{Element, Event, XmlReader},
+    },
+};
+
+/// A message parsed from a report of PMD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PmdMessage {
+    /// A violation of a rule.
+    Violation(Box<PmdViolation>),
+
+    /// A file which PMD could not analyse.
+    ProcessingError(ProcessingError),
+
+    /// A rule which PMD could not configure.
+    ConfigurationError(ConfigurationError),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// A JSON report cut off before its end.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of a report.
+    TextOutput(TextOutput),
+}
+
+/// The priority of a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PmdPriority {
+    /// The highest priority (1).
+    High,
+    /// A medium high priority (2).
+    MediumHigh,
+    /// A medium priority (3), the default of a rule.
+    Medium,
+    /// A medium low priority (4).
+    MediumLow,
+    /// The lowest priority (5), or any priority which is not known.
+    Low,
+}
+
+impl PmdPriority {
+    /// The priority with the given number.
+    const fn new(priority: u8) -> Self {
+        match priority {
+            1 => Self::High,
+            2 => Self::MediumHigh,
+            3 => Self::Medium,
+            4 => Self::MediumLow,
+            _ => Self::Low,
+        }
+    }
+}
+
+impl fmt::Display for PmdPriority {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::High => "high",
+            Self::MediumHigh => "medium high",
+            Self::Medium => "medium",
+            Self::MediumLow => "medium low",
+            Self::Low => "low",
+        })
+    }
+}
+
+/// A violation of a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PmdViolation {
+    /// The name of the rule (e.g., `UnusedPrivateField`).
+    pub rule: String,
+    /// The name of the ruleset of the rule (e.g., `Best Practices`), if given.
+    pub ruleset: Option<String>,
+    /// The priority of the rule.
+    pub priority: PmdPriority,
+    /// The message of the violation.
+    pub message: String,
+    /// The link to the documentation of the rule, if given.
+    pub external_info_url: Option<String>,
+    /// The file of the violation.
+    pub file: String,
+    /// The first line of the violation.
+    pub line: u32,
+    /// The first column of the violation, if known.
+    pub column: Option<u32>,
+    /// The last line of the violation, if known.
+    pub end_line: Option<u32>,
+    /// The last column of the violation, if known.
+    pub end_column: Option<u32>,
+}
+
+impl PmdViolation {
+    /// Read a violation from the attributes of its element, in the given
+    /// file.
+    ///
+    /// # Returns
+    ///
+    /// The violation, whose message is the text of the element, or `None` if
+    /// the element has no rule or line.
+    fn new(element: &Element, file: &str) -> Option<Self> {
+        let number = |name: &str| element.attribute(name)?.trim().parse().ok();
+        Some(Self {
+            rule: element
+                .attribute("rule")
+                .filter(|rule| !rule.is_empty())?
+                .to_owned(),
+            ruleset: element
+                .attribute("ruleset")
+                .filter(|ruleset| !ruleset.is_empty())
+                .map(str::to_owned),
+            priority: PmdPriority::new(
+                element
+                    .attribute("priority")
+                    .and_then(|priority| priority.trim().parse().ok())
+                    .unwrap_or(3),
+            ),
+            message: String::new(),
+            external_info_url: element
+                .attribute("externalInfoUrl")
+                .filter(|link| !link.is_empty())
+                .map(str::to_owned),
+            file: file.to_owned(),
+            line: number("beginline")?,
+            column: number("begincolumn"),
+            end_line: number("endline"),
+            end_column: number("endcolumn"),
+        })
+    }
+
+    /// The title of the annotation (e.g., `Best Practices:
+    /// UnusedPrivateField`).
+    fn title(&self) -> String {
+        match &self.ruleset {
+            Some(ruleset) => format!("{ruleset}: {}", self.rule),
+            None => self.rule.clone(),
+        }
+    }
+}
+
+impl<P: Platform> CiMessage<P> for PmdViolation {
+    #[inline]
+    fn format(&self) -> String {
+        let message = match &self.external_info_url {
+            Some(link) => format!("{}\n{}: {link}", self.message, Title::Documentation),
+            None => self.message.clone(),
+        };
+        P::annotate(
+            self.severity(),
+            message,
+            Some((&self.file, self.line, self.column)),
+            Some(&self.title()),
+        )
+    }
+}
+
+impl Classify for PmdViolation {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.priority {
+            PmdPriority::High | PmdPriority::MediumHigh => Severity::Error,
+            PmdPriority::Medium => Severity::Warning,
+            PmdPriority::MediumLow | PmdPriority::Low => Severity::Notice,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        Some(&self.rule)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// A file which PMD could not analyse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProcessingError {
+    /// The file.
+    pub file: String,
+    /// The reason the file could not be analysed (e.g., `ParseException:
+    /// ...`).
+    pub message: String,
+    /// The stack trace of the error, if given.
+    pub detail: Option<String>,
+}
+
+impl<P: Platform> CiMessage<P> for ProcessingError {
+    #[inline]
+    fn format(&self) -> String {
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            format!("The file was not analysed: {}", self.message),
+            Some((&self.file, 1, None)),
+            Some(Title::ScanError.text()),
+        )];
+        if let Some(detail) = &self.detail {
+            lines.push(P::group(format!("{}: {}", Title::ScanError, self.file)));
+            lines.push(detail.clone());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for ProcessingError {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+/// A rule which PMD could not configure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConfigurationError {
+    /// The name of the rule.
+    pub rule: String,
+    /// The reason the rule could not be configured.
+    pub message: String,
+}
+
+impl<P: Platform> CiMessage<P> for ConfigurationError {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            &self.message,
+            None,
+            Some(&format!("{}: {}", Title::ConfigError, self.rule)),
+        )
+    }
+}
+
+impl Classify for ConfigurationError {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        Some(&self.rule)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for PmdMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Violation(msg) => <PmdViolation as CiMessage<P>>::format(msg),
+            Self::ProcessingError(msg) => <ProcessingError as CiMessage<P>>::format(msg),
+            Self::ConfigurationError(msg) => <ConfigurationError as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for PmdMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Violation(msg) => msg.severity(),
+            Self::ProcessingError(msg) => msg.severity(),
+            Self::ConfigurationError(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Violation(msg) => msg.kind(),
+            Self::ProcessingError(msg) => msg.kind(),
+            Self::ConfigurationError(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Violation(msg) => msg.code(),
+            Self::ConfigurationError(msg) => msg.code(),
+            Self::ProcessingError(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Violation(msg) => msg.file(),
+            Self::ProcessingError(msg) => msg.file(),
+            Self::ConfigurationError(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Violation(msg) => msg.message(),
+            Self::ProcessingError(msg) => msg.message(),
+            Self::ConfigurationError(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for PmdMessage {}
+
+impl Captured for PmdMessage {}
+
+/// The JSON report of PMD.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+    /// The files with violations.
+    files: Vec<JsonFile>,
+    /// The files which could not be analysed.
+    #[serde(default)]
+    processing_errors: Vec<JsonProcessingError>,
+    /// The rules which could not be configured.
+    #[serde(default)]
+    configuration_errors: Vec<JsonConfigurationError>,
+}
+
+/// A file listed in the `files` of the report.
+#[derive(Debug, Deserialize)]
+struct JsonFile {
+    /// The path of the file.
+    filename: String,
+    /// The violations in the file.
+    #[serde(default)]
+    violations: Vec<JsonViolation>,
+}
+
+/// A violation of a file.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonViolation {
+    /// The first line of the violation.
+    beginline: u32,
+    /// The first column of the violation.
+    #[serde(default)]
+    begincolumn: Option<u32>,
+    /// The last line of the violation.
+    #[serde(default)]
+    endline: Option<u32>,
+    /// The last column of the violation.
+    #[serde(default)]
+    endcolumn: Option<u32>,
+    /// The message of the violation.
+    description: String,
+    /// The name of the rule.
+    rule: String,
+    /// The name of the ruleset of the rule.
+    #[serde(default)]
+    ruleset: Option<String>,
+    /// The priority of the rule.
+    priority: u8,
+    /// The link to the documentation of the rule.
+    #[serde(default)]
+    external_info_url: Option<String>,
+}
+
+/// A file listed in the `processingErrors` of the report.
+#[derive(Debug, Deserialize)]
+struct JsonProcessingError {
+    /// The path of the file.
+    filename: String,
+    /// The reason the file could not be analysed.
+    message: String,
+    /// The stack trace of the error.
+    #[serde(default)]
+    detail: Option<String>,
+}
+
+/// A rule listed in the `configurationErrors` of the report.
+#[derive(Debug, Deserialize)]
+struct JsonConfigurationError {
+    /// The name of the rule.
+    rule: String,
+    /// The reason the rule could not be configured.
+    message: String,
+}
+
+impl Report {
+    /// The messages of the report, in the order of its files.
+    fn into_messages(self) -> Vec<PmdMessage> {
+        let violations = self.files.into_iter().flat_map(|file| {
+            let filename = file.filename;
+            file.violations.into_iter().map(move |violation| {
+                PmdMessage::Violation(Box::new(PmdViolation {
+                    rule: violation.rule,
+                    ruleset: violation.ruleset.filter(|ruleset| !ruleset.is_empty()),
+                    priority: PmdPriority::new(violation.priority),
+                    message: violation.description,
+                    external_info_url: violation.external_info_url.filter(|link| !link.is_empty()),
+                    file: filename.clone(),
+                    line: violation.beginline,
+                    column: violation.begincolumn,
+                    end_line: violation.endline,
+                    end_column: violation.endcolumn,
+                }))
+            })
+        });
+        let processing = self.processing_errors.into_iter().map(|error| {
+            PmdMessage::ProcessingError(ProcessingError {
+                file: error.filename,
+                message: error.message,
+                detail: error.detail.filter(|detail| !detail.trim().is_empty()),
+            })
+        });
+        let configuration = self.configuration_errors.into_iter().map(|error| {
+            PmdMessage::ConfigurationError(ConfigurationError {
+                rule: error.rule,
+                message: error.message,
+            })
+        });
+        violations.chain(processing).chain(configuration).collect()
+    }
+}
+
+/// The format of the report being read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// An XML report, read as it is received.
+    Xml,
+    /// A JSON report, read until its end.
+    Json,
+}
+
+/// The state of the parser.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The format of the report being read, if any.
+    format: Option<Format>,
+    /// Reader of the events of an XML report.
+    xml: XmlReader,
+    /// The file of the XML report being read, if any.
+    file: Option<String>,
+    /// The violation of the XML report being read, if any.
+    violation: Option<PmdViolation>,
+    /// The processing error of the XML report being read, if any.
+    error: Option<ProcessingError>,
+    /// The lines of the JSON report read so far.
+    document: Vec<u8>,
+    /// The line number and offset of the first line of the JSON report.
+    start: (usize, u64),
+}
+
+impl Reader {
+    /// Report the violation or processing error of the XML report being
+    /// read, if any.
+    fn flush(&mut self) -> Option<PmdMessage> {
+        if let Some(mut violation) = self.violation.take() {
+            violation.message = String::from(violation.message.trim());
+            return Some(PmdMessage::Violation(Box::new(violation)));
+        }
+        let mut error = self.error.take()?;
+        error.detail = error
+            .detail
+            .map(|detail| detail.trim().to_owned())
+            .filter(|detail| !detail.is_empty());
+        Some(PmdMessage::ProcessingError(error))
+    }
+
+    /// Handle an event of an XML report.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the event.
+    fn handle(&mut self, event: Event, text: &mut TextLines) -> Vec<PmdMessage> {
+        match event {
+            Event::Start(element) if element.name == "file" => {
+                self.file = element.attribute("name").map(str::to_owned);
+                Vec::new()
+            }
+            Event::Start(element) if element.name == "violation" => {
+                let mut results: Vec<_> = self.flush().into_iter().collect();
+                self.violation = self
+                    .file
+                    .as_deref()
+                    .and_then(|file| PmdViolation::new(&element, file));
+                if element.empty {
+                    results.extend(self.flush());
+                }
+                results
+            }
+            Event::Start(element) if element.name == "error" => {
+                let mut results: Vec<_> = self.flush().into_iter().collect();
+                self.error = element.attribute("filename").map(|file| ProcessingError {
+                    file: file.to_owned(),
+                    message: element.attribute("msg").unwrap_or_default().to_owned(),
+                    detail: None,
+                });
+                if element.empty {
+                    results.extend(self.flush());
+                }
+                results
+            }
+            Event::Start(element) if element.name == "configerror" => element
+                .attribute("rule")
+                .map(|rule| {
+                    PmdMessage::ConfigurationError(ConfigurationError {
+                        rule: rule.to_owned(),
+                        message: element.attribute("msg").unwrap_or_default().to_owned(),
+                    })
+                })
+                .into_iter()
+                .collect(),
+            Event::End(name) if name == "violation" || name == "error" => {
+                self.flush().into_iter().collect()
+            }
+            Event::End(name) if name == "file" => {
+                self.file = None;
+                Vec::new()
+            }
+            Event::End(name) if name == "pmd" => {
+                let results = self.flush().into_iter().collect();
+                self.format = None;
+                results
+            }
+            Event::Text(content) => {
+                if let Some(violation) = &mut self.violation {
+                    violation.message.push_str(&content);
+                } else if let Some(error) = &mut self.error {
+                    error.detail.get_or_insert_default().push_str(&content);
+                } else {
+                    return content
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .filter_map(|line| text.handle(line.as_bytes()))
+                        .map(PmdMessage::TextOutput)
+                        .collect();
+                }
+                Vec::new()
+            }
+            Event::Start(_) | Event::End(_) => Vec::new(),
+        }
+    }
+
+    /// Parse the JSON report read so far.
+    fn parse(&mut self) -> Vec<Result<PmdMessage, serde_json::Error>> {
+        self.format = None;
+        match serde_json::from_slice::<Report>(&mem::take(&mut self.document)) {
+            Ok(report) => report.into_messages().into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        }
+    }
+
+    /// Parse a single line of the output of PMD.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the line.
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the reports.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        position: (usize, u64),
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<PmdMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                // A JSON report cannot be parsed without the whole line
+                if self.format == Some(Format::Json) {
+                    self.format = None;
+                    self.document.clear();
+                }
+                let mut results = vec![Ok(PmdMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(position, Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        if self.format.is_none() {
+            let trimmed = line.trim_ascii_start();
+            if trimmed.starts_with(b"<?xml") || trimmed.starts_with(b"<pmd") {
+                self.format = Some(Format::Xml);
+                self.xml = XmlReader::default();
+            } else if line.first() == Some(&b'{') {
+                self.format = Some(Format::Json);
+                self.start = position;
+            } else {
+                return text
+                    .handle(line)
+                    .map(|output| Ok(PmdMessage::TextOutput(output)))
+                    .into_iter()
+                    .collect();
+            }
+        }
+
+        if self.format == Some(Format::Xml) {
+            self.xml.push(line);
+            self.xml.push(b"\n");
+            let mut results = Vec::new();
+            while let Some(event) = self.xml.next_event() {
+                results.extend(self.handle(event, text));
+            }
+            return results.into_iter().map(Ok).collect();
+        }
+
+        self.document.extend_from_slice(line);
+        self.document.push(b'\n');
+        // PMD closes the report on a line of its own, unless it is printed
+        // on a single line (e.g., reformatted by another tool)
+        let single = self.document.len() == line.len().saturating_add(1);
+        if line == b"}" || (single && line.ends_with(b"}")) {
+            let results = self.parse();
+            if single
+                && let [Err(e)] = results.as_slice()
+                && e.is_eof()
+            {
+                // The report continues on the next lines
+                self.format = Some(Format::Json);
+                self.start = position;
+                self.document.extend_from_slice(line);
+                self.document.push(b'\n');
+                return Vec::new();
+            }
+            return results;
+        }
+        Vec::new()
+    }
+
+    /// Report the report being read at the end of the output, if any.
+    fn finish(&mut self, text: &mut TextLines) -> Vec<Result<PmdMessage, serde_json::Error>> {
+        match self.format {
+            None => Vec::new(),
+            Some(Format::Xml) => {
+                let mut results = Vec::new();
+                if let Some(event) = self.xml.finish() {
+                    results.extend(self.handle(event, text));
+                }
+                // An element cut off before its end is reported as far as it
+                // was read
+                results.extend(self.flush());
+                results.into_iter().map(Ok).collect()
+            }
+            Some(Format::Json) => {
+                let (line_number, offset) = self.start;
+                // The last line may end within a string, which cannot contain
+                // the newline which ends it
+                if self.document.last() == Some(&b'\n') {
+                    self.document.pop();
+                }
+                let length = self.document.len();
+                self.parse()
+                    .into_iter()
+                    .map(|result| {
+                        result.or_else(|error| {
+                            if error.is_eof() {
+                                Ok(PmdMessage::TruncatedLine(TruncatedLine {
+                                    line_number,
+                                    offset,
+                                    length,
+                                    error: error.to_string(),
+                                }))
+                            } else {
+                                Err(error)
+                            }
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Tool implementation for parsing the reports of PMD.
+#[derive(Debug, Clone, Default)]
+pub struct Pmd {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a report.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl Detect for Pmd {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        (memchr::memmem::find(sample, b"<pmd ").is_some()
+            || memchr::memmem::find(sample, b"\"pmdVersion\":").is_some())
+        .then(Self::default)
+    }
+}
+
+impl Pmd {
+    /// A problem matcher for the text report of PMD.
+    ///
+    /// Each violation is printed on a line of its own (e.g.,
+    /// `src/App.java:5:\tUnusedPrivateField:\tAvoid unused private fields
+    /// such as 'count'.`), with the rule omitted before PMD 7. Each match is
+    /// reported as a warning.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-pmd",
+            vec![
+                Pattern::new(r"^(.+?):(\d+):\t(?:(\w+):\t)?(.+)$")
+                    .file(1)
+                    .line(2)
+                    .code(3)
+                    .message(4),
+            ],
+        )
+        .with_severity("warning")
+    }
+}
+
+impl Tool for Pmd {
+    type Message = PmdMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "pmd"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        loop {
+            let position = self.framer.position();
+            let Some(line) = self.framer.next_line() else {
+                break;
+            };
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+
+        results.extend(self.reader.finish(&mut self.text));
+        results.extend(
+            self.text
+                .finish()
+                .map(|output| Ok(PmdMessage::TextOutput(output))),
+        );
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Pmd
+where
+    PmdMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Pmd, PmdMessage, PmdPriority};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{Cppcheck, Detect as _, Junit, Passthrough, Tool as _};
+
+    const XML: &str = r#"[WARN] This analysis could be faster, please consider using Incremental Analysis
+<?xml version="1.0" encoding="UTF-8"?>
+<pmd xmlns="http://pmd.sourceforge.net/report/2.0.0"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+    xsi:schemaLocation="http://pmd.sourceforge.net/report/2.0.0 http://pmd.sourceforge.net/report_2_0_0.xsd"
+    version="7.0.0" timestamp="2024-05-01T12:00:00.000">
+<file name="src/main/java/com/example/App.java">
+<violation beginline="5" endline="5" begincolumn="17" endcolumn="22" rule="UnusedPrivateField" ruleset="Best Practices" package="com.example" class="App" variable="count" externalInfoUrl="https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_bestpractices.html#unusedprivatefield" priority="3">
+Avoid unused private fields such as &apos;count&apos;.
+</violation>
+<violation beginline="9" endline="11" begincolumn="9" endcolumn="9" rule="EmptyCatchBlock" ruleset="Error Prone" package="com.example" class="App" method="run" externalInfoUrl="https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_errorprone.html#emptycatchblock" priority="1">
+Avoid empty catch blocks
+</violation>
+</file>
+<error filename="src/main/java/com/example/Broken.java" msg="ParseException: Parse exception in file &apos;src/main/java/com/example/Broken.java&apos; at line 3, column 20: Encountered &quot;;&quot;">
+<![CDATA[net.sourceforge.pmd.lang.ast.ParseException: Encountered ";"
+	at net.sourceforge.pmd.lang.java.ast.JavaParser.parse(JavaParser.java:42)]]>
+</error>
+<configerror rule="LoosePackageCoupling" msg="No packages or classes specified"/>
+</pmd>
+"#;
+
+    const JSON: &str = r#"{
+  "formatVersion": 0,
+  "pmdVersion": "7.0.0",
+  "timestamp": "2024-05-01T12:00:00.000Z",
+  "files": [
+    {
+      "filename": "src/main/java/com/example/App.java",
+      "violations": [
+        {
+          "beginline": 5,
+          "begincolumn": 17,
+          "endline": 5,
+          "endcolumn": 22,
+          "description": "Avoid unused private fields such as 'count'.",
+          "rule": "UnusedPrivateField",
+          "ruleset": "Best Practices",
+          "priority": 3,
+          "externalInfoUrl": "https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_bestpractices.html#unusedprivatefield"
+        },
+        {
+          "beginline": 14,
+          "begincolumn": 5,
+          "endline": 14,
+          "endcolumn": 30,
+          "description": "Comment is too large: Line too long",
+          "rule": "CommentSize",
+          "ruleset": "Documentation",
+          "priority": 5,
+          "externalInfoUrl": ""
+        }
+      ]
+    }
+  ],
+  "suppressedViolations": [],
+  "processingErrors": [
+    {
+      "filename": "src/main/java/com/example/Broken.java",
+      "message": "ParseException: Encountered \";\"",
+      "detail": ""
+    }
+  ],
+  "configurationErrors": []
+}
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<PmdMessage> {
+        let mut tool = Pmd::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut Pmd, output: &str) -> String
+    where
+        PmdMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <PmdMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn xml() {
+        insta::assert_snapshot!(format::<GitHub>(&mut Pmd::default(), XML), @r#"
+        ::warning file=src/main/java/com/example/App.java,line=5,col=17,title=Best Practices%3A UnusedPrivateField::Avoid unused private fields such as 'count'.%0ADocumentation: https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_bestpractices.html#unusedprivatefield
+        ::error file=src/main/java/com/example/App.java,line=9,col=9,title=Error Prone%3A EmptyCatchBlock::Avoid empty catch blocks%0ADocumentation: https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_errorprone.html#emptycatchblock
+        ::warning file=src/main/java/com/example/Broken.java,line=1,title=Scan Error::The file was not analysed: ParseException: Parse exception in file 'src/main/java/com/example/Broken.java' at line 3, column 20: Encountered ";"
+        ::group::Scan Error: src/main/java/com/example/Broken.java
+        net.sourceforge.pmd.lang.ast.ParseException: Encountered ";"
+        	at net.sourceforge.pmd.lang.java.ast.JavaParser.parse(JavaParser.java:42)
+        ::endgroup::
+        ::warning title=Configuration Error%3A LoosePackageCoupling::No packages or classes specified
+        "#);
+    }
+
+    #[test]
+    fn json() {
+        insta::assert_snapshot!(format::<Plain>(&mut Pmd::default(), JSON), @r#"
+        src/main/java/com/example/App.java:5:17: warning: Best Practices: UnusedPrivateField: Avoid unused private fields such as 'count'.\nDocumentation: https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_bestpractices.html#unusedprivatefield
+        src/main/java/com/example/App.java:14:5: notice:  Documentation: CommentSize: Comment is too large: Line too long
+        src/main/java/com/example/Broken.java:1: warning: Scan Error: The file was not analysed: ParseException: Encountered ";"
+        "#);
+    }
+
+    #[test]
+    fn chunked() {
+        for output in [XML, JSON] {
+            let whole = parse(output, output.len());
+            for chunk in [1, 7, 64] {
+                assert_eq!(parse(output, chunk), whole, "chunks of {chunk}");
+            }
+        }
+    }
+
+    #[test]
+    fn violations() {
+        let violations: Vec<_> = parse(XML, XML.len())
+            .into_iter()
+            .filter_map(|msg| match msg {
+                PmdMessage::Violation(violation) => Some(violation),
+                PmdMessage::ProcessingError(_)
+                | PmdMessage::ConfigurationError(_)
+                | PmdMessage::OverlongLine(_)
+                | PmdMessage::TruncatedLine(_)
+                | PmdMessage::TextOutput(_) => None,
+            })
+            .collect();
+        let [field, catch] = violations.as_slice() else {
+            panic!("expected two violations");
+        };
+        assert_eq!(
+            field.message,
+            "Avoid unused private fields such as 'count'."
+        );
+        assert_eq!(field.priority, PmdPriority::Medium);
+        assert_eq!(field.code(), Some("UnusedPrivateField"));
+        assert_eq!(field.column, Some(17));
+        assert_eq!(catch.priority, PmdPriority::High);
+        assert_eq!((catch.line, catch.end_line), (9, Some(11)));
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = JSON.get(..400).expect("valid index");
+        insta::assert_snapshot!(format::<Plain>(&mut Pmd::default(), cut), @"warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 400 bytes of an incomplete message: EOF while parsing a string at line 15 column 22");
+    }
+
+    #[test]
+    fn passthrough() {
+        let mut tool = Pmd::default();
+        tool.set_passthrough(Passthrough::Raw);
+        let output = format!("{JSON}[INFO] Done\n");
+        insta::assert_snapshot!(format::<Plain>(&mut tool, &output), @r#"
+        src/main/java/com/example/App.java:5:17: warning: Best Practices: UnusedPrivateField: Avoid unused private fields such as 'count'.\nDocumentation: https://docs.pmd-code.org/pmd-doc-7.0.0/pmd_rules_java_bestpractices.html#unusedprivatefield
+        src/main/java/com/example/App.java:14:5: notice:  Documentation: CommentSize: Comment is too large: Line too long
+        src/main/java/com/example/Broken.java:1: warning: Scan Error: The file was not analysed: ParseException: Encountered ";"
+        [INFO] Done
+        "#);
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Pmd::detect(XML.as_bytes()).is_some());
+        assert!(Pmd::detect(JSON.as_bytes()).is_some());
+        assert!(Pmd::detect(b"[WARN] This analysis could be faster\n").is_none());
+        assert!(Junit::detect(XML.as_bytes()).is_none());
+        assert!(Cppcheck::detect(XML.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Pmd::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regex
+            .captures("src/App.java:5:\tUnusedPrivateField:\tAvoid unused private fields such as 'count'.")
+            .expect("violation not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("src/App.java"));
+        assert_eq!(
+            captures.get(3).map(|m| m.as_str()),
+            Some("UnusedPrivateField")
+        );
+        let legacy = regex
+            .captures("src/App.java:9:\tAvoid empty catch blocks")
+            .expect("violation not matched");
+        assert_eq!(legacy.get(3), None);
+        assert_eq!(
+            legacy.get(4).map(|m| m.as_str()),
+            Some("Avoid empty catch blocks")
+        );
+    }
+}
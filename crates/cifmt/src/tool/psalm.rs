@@ -0,0 +1,637 @@
+//! Psalm JSON report.
+//!
+//! Support for parsing the report which Psalm prints with `--output-format
+//! json`, so that the issues found in PHP code are annotated at their
+//! location. The report is a JSON array of the issues, printed on a single
+//! line (or over many lines, once reformatted by another tool), which is
+//! parsed once its closing bracket is read; a report cut off before its end
+//! is reported as truncated.
+//!
+//! Each issue is annotated from the line and column at which it starts to
+//! those at which it ends, where the platform supports it (e.g., GitHub
+//! Actions), titled after its type (e.g., `InvalidReturnType`), which is also
+//! its code, with the link to the documentation of the type following its
+//! message. Issues of the `error` severity are errors, while those of the
+//! `info` severity (i.e., below the error level of the project) are notices.
+//!
+//! Any text outside of the report (e.g., Psalm's progress, when printed to
+//! the same stream) is passed through as plain text output.
+
+use core::mem;
+
+use serde::Deserialize;
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the JSON report of Psalm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PsalmMessage {
+    /// An issue found by Psalm.
+    Issue(Box<PsalmIssue>),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// A report cut off before its end.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of the report.
+    TextOutput(TextOutput),
+}
+
+/// The severity of an issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PsalmSeverity {
+    /// An issue at or above the error level of the project.
+    Error,
+    /// An issue below the error level of the project, or any severity which
+    /// is not known.
+    #[serde(other)]
+    Info,
+}
+
+/// An issue found by Psalm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PsalmIssue {
+    /// The type of the issue (e.g., `InvalidReturnType`).
+    pub issue_type: String,
+    /// The severity of the issue.
+    pub severity: PsalmSeverity,
+    /// The message of the issue.
+    pub message: String,
+    /// The link to the documentation of the type of the issue, if given.
+    pub link: Option<String>,
+    /// The file of the issue, relative to the root of the project.
+    pub file: String,
+    /// The line at which the issue starts.
+    pub line: u32,
+    /// The column at which the issue starts.
+    pub column: u32,
+    /// The line at which the issue ends.
+    pub end_line: u32,
+    /// The column at which the issue ends.
+    pub end_column: u32,
+}
+
+impl PsalmIssue {
+    /// The message of the annotation, followed by the link to the
+    /// documentation of the type of the issue.
+    fn text(&self) -> String {
+        match &self.link {
+            Some(link) => format!("{}\n{}: {link}", self.message, Title::Documentation),
+            None => self.message.clone(),
+        }
+    }
+}
+
+/// The issue is annotated over the columns which it spans, where the platform
+/// supports it.
+impl<P: Platform> CiMessage<P> for PsalmIssue {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate_span(
+            self.severity(),
+            self.text(),
+            &self.file,
+            ((self.line, self.column), (self.end_line, self.end_column)),
+            Some(&self.issue_type),
+        )
+    }
+}
+
+impl Classify for PsalmIssue {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.severity {
+            PsalmSeverity::Error => Severity::Error,
+            PsalmSeverity::Info => Severity::Notice,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        Some(&self.issue_type)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for PsalmMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Issue(msg) => <PsalmIssue as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for PsalmMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Issue(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Issue(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.code(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.file(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Issue(msg) => msg.message(),
+            Self::OverlongLine(_) | Self::TruncatedLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for PsalmMessage {}
+
+impl Captured for PsalmMessage {}
+
+/// An issue listed in the report.
+#[derive(Debug, Deserialize)]
+struct JsonIssue {
+    /// The severity of the issue.
+    severity: PsalmSeverity,
+    /// The line at which the issue starts.
+    line_from: u32,
+    /// The line at which the issue ends.
+    line_to: u32,
+    /// The type of the issue.
+    #[serde(rename = "type")]
+    issue_type: String,
+    /// The message of the issue.
+    message: String,
+    /// The path of the file, relative to the root of the project.
+    file_name: String,
+    /// The column at which the issue starts.
+    column_from: u32,
+    /// The column at which the issue ends.
+    column_to: u32,
+    /// The link to the documentation of the type of the issue.
+    #[serde(default)]
+    link: Option<String>,
+}
+
+impl From<JsonIssue> for PsalmMessage {
+    #[inline]
+    fn from(issue: JsonIssue) -> Self {
+        Self::Issue(Box::new(PsalmIssue {
+            issue_type: issue.issue_type,
+            severity: issue.severity,
+            message: issue.message,
+            link: issue.link.filter(|link| !link.is_empty()),
+            file: issue.file_name,
+            line: issue.line_from,
+            column: issue.column_from,
+            end_line: issue.line_to,
+            end_column: issue.column_to,
+        }))
+    }
+}
+
+/// The report being read.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The lines of the report read so far.
+    document: Vec<u8>,
+    /// The line number and offset of the first line of the report, if one is
+    /// being read.
+    start: Option<(usize, u64)>,
+}
+
+impl Reader {
+    /// Parse the report read so far.
+    fn parse(&mut self) -> Vec<Result<PsalmMessage, serde_json::Error>> {
+        self.start = None;
+        match serde_json::from_slice::<Vec<JsonIssue>>(&mem::take(&mut self.document)) {
+            Ok(issues) => issues.into_iter().map(|issue| Ok(issue.into())).collect(),
+            Err(e) => vec![Err(e)],
+        }
+    }
+
+    /// Parse a single line of the output of Psalm.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the line.
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the report.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        position: (usize, u64),
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<PsalmMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                // The report cannot be parsed without the whole line
+                self.start = None;
+                self.document.clear();
+                let mut results = vec![Ok(PsalmMessage::OverlongLine(OverlongLine { max_length }))];
+                results.extend(self.parse_line(position, Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        if self.start.is_none() {
+            if line.first() != Some(&b'[') {
+                return text
+                    .handle(line)
+                    .map(|output| Ok(PsalmMessage::TextOutput(output)))
+                    .into_iter()
+                    .collect();
+            }
+            self.start = Some(position);
+        }
+        self.document.extend_from_slice(line);
+        self.document.push(b'\n');
+
+        // Psalm prints the report on a single line, which may also be
+        // reformatted to close the report on a line of its own
+        let single = self.document.len() == line.len().saturating_add(1);
+        if line == b"]" || (single && line.ends_with(b"]")) {
+            let results = self.parse();
+            if single
+                && let [Err(e)] = results.as_slice()
+                && e.is_eof()
+            {
+                // The report continues on the next lines
+                self.start = Some(position);
+                self.document.extend_from_slice(line);
+                self.document.push(b'\n');
+                return Vec::new();
+            }
+            return results;
+        }
+        Vec::new()
+    }
+
+    /// Report the report being read at the end of the output, if any.
+    fn finish(&mut self) -> Vec<Result<PsalmMessage, serde_json::Error>> {
+        let Some((line_number, offset)) = self.start else {
+            return Vec::new();
+        };
+        // The last line may end within a string, which cannot contain the
+        // newline which ends it
+        if self.document.last() == Some(&b'\n') {
+            self.document.pop();
+        }
+        let length = self.document.len();
+        self.parse()
+            .into_iter()
+            .map(|result| {
+                result.or_else(|error| {
+                    if error.is_eof() {
+                        Ok(PsalmMessage::TruncatedLine(TruncatedLine {
+                            line_number,
+                            offset,
+                            length,
+                            error: error.to_string(),
+                        }))
+                    } else {
+                        Err(error)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Tool implementation for parsing the JSON report of Psalm.
+#[derive(Debug, Clone, Default)]
+pub struct Psalm {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of the report.
+    text: TextLines,
+    /// The report being read.
+    reader: Reader,
+}
+
+impl Detect for Psalm {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        // The fields which locate an issue, whether the report is printed on
+        // a single line or reformatted
+        let field = |name: &[u8]| memchr::memmem::find(sample, name).is_some();
+        (field(b"\"line_from\":") && field(b"\"column_from\":") && field(b"\"file_name\":"))
+            .then(Self::default)
+    }
+}
+
+impl Psalm {
+    /// A problem matcher for the console output of Psalm.
+    ///
+    /// Each issue is printed on a line of its own (e.g., `ERROR:
+    /// InvalidReturnType - src/Foo.php:10:25 - The declared return type 'int'
+    /// for Foo::bar is incorrect, got 'string' (see https://psalm.dev/011)`),
+    /// which gives its type, location and message. Only errors are matched,
+    /// as the issues of the `info` severity do not fail the analysis.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-psalm",
+            vec![
+                Pattern::new(r"^ERROR: (\w+) - (.+?):(\d+):(\d+) - (.+)$")
+                    .code(1)
+                    .file(2)
+                    .line(3)
+                    .column(4)
+                    .message(5),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for Psalm {
+    type Message = PsalmMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "psalm"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        loop {
+            let position = self.framer.position();
+            let Some(line) = self.framer.next_line() else {
+                break;
+            };
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+
+        results.extend(self.reader.finish());
+        results.extend(
+            self.text
+                .finish()
+                .map(|output| Ok(PsalmMessage::TextOutput(output))),
+        );
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Psalm
+where
+    PsalmMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Psalm, PsalmMessage, PsalmSeverity};
+    use crate::ci::{AzureDevOps, GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify as _};
+    use crate::tool::{Bandit, Detect as _, Tool as _};
+
+    const REPORT: &str = r#"[{"severity":"error","line_from":10,"line_to":10,"type":"InvalidReturnType","message":"The declared return type 'int' for App\\Calculator::divide is incorrect, got 'float'","file_name":"src/Calculator.php","file_path":"/home/user/app/src/Calculator.php","snippet":"    public function divide(int $a, int $b): int","selected_text":"int","from":187,"to":190,"snippet_from":145,"snippet_to":189,"column_from":45,"column_to":48,"error_level":-1,"shortcode":11,"link":"https://psalm.dev/011","taint_trace":null,"other_references":null},{"severity":"info","line_from":4,"line_to":6,"type":"MissingReturnType","message":"Method App\\Greeter::greet does not have a return type","file_name":"src/Greeter.php","file_path":"/home/user/app/src/Greeter.php","snippet":"    public function greet($name)","selected_text":"greet","from":71,"to":76,"snippet_from":55,"snippet_to":84,"column_from":21,"column_to":26,"error_level":2,"shortcode":50,"link":"https://psalm.dev/050","taint_trace":null,"other_references":null}]
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<PsalmMessage> {
+        let mut tool = Psalm::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        PsalmMessage: CiMessage<P>,
+    {
+        let mut tool = Psalm::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <PsalmMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(REPORT), @r#"
+        ::error file=src/Calculator.php,line=10,col=45,endLine=10,endColumn=48,title=InvalidReturnType::The declared return type 'int' for App\Calculator::divide is incorrect, got 'float'%0ADocumentation: https://psalm.dev/011
+        ::notice file=src/Greeter.php,line=4,col=21,endLine=6,endColumn=26,title=MissingReturnType::Method App\Greeter::greet does not have a return type%0ADocumentation: https://psalm.dev/050
+        "#);
+    }
+
+    #[test]
+    fn azure() {
+        insta::assert_snapshot!(format::<AzureDevOps>(REPORT), @r"
+        ##vso[task.logissue type=error;sourcepath=src/Calculator.php;linenumber=10;columnnumber=45]InvalidReturnType: The declared return type 'int' for App\Calculator::divide is incorrect, got 'float'%0ADocumentation: https://psalm.dev/011
+        src/Greeter.php:4:21: notice:  MissingReturnType: Method App\Greeter::greet does not have a return type\nDocumentation: https://psalm.dev/050
+        ");
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(REPORT), @r"
+        src/Calculator.php:10:45: error:   InvalidReturnType: The declared return type 'int' for App\Calculator::divide is incorrect, got 'float'\nDocumentation: https://psalm.dev/011
+        src/Greeter.php:4:21: notice:  MissingReturnType: Method App\Greeter::greet does not have a return type\nDocumentation: https://psalm.dev/050
+        ");
+    }
+
+    #[test]
+    fn issues() {
+        let messages = parse(REPORT, 7);
+        assert_eq!(messages, parse(REPORT, REPORT.len()));
+        let [PsalmMessage::Issue(error), PsalmMessage::Issue(info)] = messages.as_slice() else {
+            panic!("expected two issues");
+        };
+        assert_eq!(error.severity, PsalmSeverity::Error);
+        assert_eq!(error.code(), Some("InvalidReturnType"));
+        assert_eq!((error.column, error.end_column), (45, 48));
+        assert_eq!(info.severity, PsalmSeverity::Info);
+        assert_eq!((info.line, info.end_line), (4, 6));
+    }
+
+    #[test]
+    fn reformatted() {
+        let output = "Scanning files...\n[\n  {\"severity\": \"error\", \"line_from\": 3, \"line_to\": 3, \"type\": \"UndefinedVariable\",\n   \"message\": \"Cannot find referenced variable $x\", \"file_name\": \"index.php\", \"column_from\": 6, \"column_to\": 8}\n]\n";
+        insta::assert_snapshot!(format::<Plain>(output), @"index.php:3:6: error:   UndefinedVariable: Cannot find referenced variable $x");
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = REPORT.get(..200).expect("valid index");
+        insta::assert_snapshot!(format::<Plain>(cut), @"warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 200 bytes of an incomplete message: EOF while parsing a string at line 1 column 200");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Psalm::detect(REPORT.as_bytes()).is_some());
+        assert!(Psalm::detect(b"[]\n").is_none());
+        assert!(Bandit::detect(REPORT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Psalm::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regex
+            .captures("ERROR: InvalidReturnType - src/Calculator.php:10:45 - The declared return type 'int' for App\\Calculator::divide is incorrect, got 'float' (see https://psalm.dev/011)")
+            .expect("issue not matched");
+        assert_eq!(
+            captures.get(1).map(|m| m.as_str()),
+            Some("InvalidReturnType")
+        );
+        assert_eq!(
+            captures.get(2).map(|m| m.as_str()),
+            Some("src/Calculator.php")
+        );
+        assert_eq!(captures.get(4).map(|m| m.as_str()), Some("45"));
+        assert!(!regex.is_match("INFO: MissingReturnType - src/Greeter.php:4:21 - Method App\\Greeter::greet does not have a return type (see https://psalm.dev/050)"));
+    }
+}
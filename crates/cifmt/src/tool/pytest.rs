@@ -0,0 +1,1400 @@
+//! pytest report logs.
+//!
+//! Support for parsing the report log written by the `pytest-reportlog`
+//! plugin (`pytest --report-log=report.jsonl`), in which each line is a
+//! report of the session, of the collection of a module, or of a phase of a
+//! test (its setup, call and teardown).
+//!
+//! The phases of a test are combined into its result, which is reported
+//! when the test's teardown ends: a test which failed in any phase failed
+//! (an error in a fixture being reported as such), a test skipped in its
+//! setup or which failed as expected (`xfail`) was ignored, and any other
+//! test passed. A failed test is annotated on the line of the test's file at
+//! which it failed, as found in its traceback, and the traceback (with the
+//! output of pytest's assertion rewriting) and the test's captured output
+//! follow the annotation in a group. A module which could not be collected
+//! is reported as a failed test. The end of the session is reported with the
+//! tally of its tests.
+//!
+//! The JUnit XML report of pytest (`--junitxml`) is parsed by
+//! [`Junit`](super::Junit) instead. Any text outside of the report log is
+//! passed through as plain text output.
+
+use core::{fmt, mem, time::Duration};
+use std::io::BufRead as _;
+
+use serde::{Deserialize, de::IgnoredAny};
+
+use crate::{
+    capture::{Captured, OutputPolicy},
+    catalog::Title,
+    ci::{Phase, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    junit::{TestOutcome, TestResult},
+    matcher::{Pattern, ProblemMatcher},
+    suites::SuiteResult,
+    timeline::{Timed, Timing},
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from a pytest report log.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PytestMessage {
+    /// The result of a test, or of the collection of a module which failed.
+    TestCase(TestCase),
+
+    /// The end of the session.
+    Session(Session),
+
+    /// A report log which ended before its session.
+    IncompleteSession(IncompleteSession),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// An incomplete report at the end of the log.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of the report log.
+    TextOutput(TextOutput),
+}
+
+/// The result of a test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TestCase {
+    /// The node ID of the test (e.g., `tests/test_math.py::test_add`).
+    pub name: String,
+    /// The outcome of the test.
+    pub outcome: TestOutcome,
+    /// The message of the failure (e.g., `assert 3 == 4`), or the reason the
+    /// test was skipped, if any.
+    pub message: Option<String>,
+    /// The traceback of the failure, as printed by pytest, if any.
+    pub details: Option<String>,
+    /// The output captured while the test ran, under the headers of its
+    /// sections (e.g., `Captured stdout call`), if any.
+    pub output: Option<String>,
+    /// How long the phases of the test took to run, if known.
+    pub duration: Option<Duration>,
+    /// The file in which the test is defined, if known.
+    pub file: Option<String>,
+    /// The line at which the test failed, or failing this, at which it is
+    /// defined, if known.
+    pub line: Option<u32>,
+}
+
+/// The end of a session, with the tally of its tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Session {
+    /// The tally of the tests of the session.
+    pub result: SuiteResult,
+    /// The exit status of pytest (e.g., `1` if tests failed).
+    pub exit_status: i32,
+}
+
+/// A report log which ended before its session, which typically means that
+/// pytest crashed or was killed while running the tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IncompleteSession {
+    /// The node IDs of the tests which did not finish.
+    pub pending: Vec<String>,
+}
+
+/// Display a title followed by a name (e.g., `Test Failed: tests/test_a.py::test_b`).
+fn titled(title: Title, name: &str) -> String {
+    format!("{title}: {name}")
+}
+
+/// Display a duration in seconds (e.g., ` in 0.12s`), if known.
+fn time_info(duration: Option<Duration>, prefix: &'static str) -> impl fmt::Display {
+    fmt::from_fn(move |f| match duration {
+        Some(time) => write!(f, "{prefix}{:.2}s", time.as_secs_f64()),
+        None => Ok(()),
+    })
+}
+
+/// Convert a number of seconds (e.g., a timestamp) to a duration.
+fn seconds(value: Option<f64>) -> Option<Duration> {
+    Duration::try_from_secs_f64(value?).ok()
+}
+
+impl TestCase {
+    /// The location at which the test is annotated, which is only known
+    /// for a failed test whose report has its file.
+    fn location(&self) -> Option<(&str, u32, Option<u32>)> {
+        if self.outcome != TestOutcome::Failed {
+            return None;
+        }
+        Some((self.file.as_deref()?, self.line.unwrap_or(1), None))
+    }
+
+    /// The details of the test shown in its group: those of a failure, and
+    /// the output of the test.
+    fn shown_details(&self) -> impl Iterator<Item = &String> {
+        self.details
+            .iter()
+            .filter(|_| self.outcome == TestOutcome::Failed)
+            .chain(&self.output)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for TestCase {
+    #[inline]
+    fn format(&self) -> String {
+        let (title, message) = match self.outcome {
+            TestOutcome::Passed => (
+                Title::TestPassed,
+                match self.duration {
+                    Some(_) => time_info(self.duration, "Executed in ").to_string(),
+                    None => "Passed".to_owned(),
+                },
+            ),
+            TestOutcome::Failed => (
+                Title::TestFailed,
+                self.message.clone().unwrap_or_else(|| "Failed".to_owned()),
+            ),
+            TestOutcome::Skipped => (
+                Title::TestIgnored,
+                self.message.clone().unwrap_or_else(|| "Skipped".to_owned()),
+            ),
+        };
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            message,
+            self.location(),
+            Some(&titled(title, &self.name)),
+        )];
+        if self.shown_details().next().is_some() {
+            lines.push(P::group(titled(Title::Test, &self.name)));
+            lines.extend(self.shown_details().cloned());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for TestCase {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.outcome {
+            TestOutcome::Passed => Severity::Notice,
+            TestOutcome::Failed => Severity::Error,
+            TestOutcome::Skipped => Severity::Debug,
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self.outcome {
+            TestOutcome::Passed => "TestPassed",
+            TestOutcome::Failed => "TestFailed",
+            TestOutcome::Skipped => "TestIgnored",
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        self.outcome == TestOutcome::Failed
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        Some(TestResult {
+            duration: self.duration,
+            message: self.message.as_deref(),
+            output: self.output.as_deref(),
+            ..TestResult::new(&self.name, self.outcome)
+        })
+    }
+}
+
+impl<P: Platform> CiMessage<P> for Session {
+    #[inline]
+    fn format(&self) -> String {
+        let SuiteResult {
+            passed,
+            failed,
+            ignored,
+            duration,
+        } = self.result;
+        let title = if self.severity() == Severity::Error {
+            Title::TestSuiteFailed
+        } else {
+            Title::TestSuitePassed
+        };
+        let mut message = format!(
+            "{passed} passed, {failed} failed, {ignored} ignored{}",
+            time_info(duration, " in ")
+        );
+        // A session may fail without any failed test (e.g., when interrupted)
+        if failed == 0 && self.exit_status != 0_i32 {
+            message = format!("{message}; pytest exited with status {}", self.exit_status);
+        }
+        P::annotate(self.severity(), message, None, Some(title.text()))
+    }
+}
+
+impl Classify for Session {
+    #[inline]
+    fn severity(&self) -> Severity {
+        if self.result.failed > 0 || self.exit_status != 0 {
+            Severity::Error
+        } else {
+            Severity::Notice
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        if self.result.failed > 0 || self.exit_status != 0 {
+            "SuiteFailed"
+        } else {
+            "SuitePassed"
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        Some(self.result)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for IncompleteSession {
+    #[inline]
+    fn format(&self) -> String {
+        let mut message = "Report log ended before the end of the session".to_owned();
+        if !self.pending.is_empty() {
+            message = format!(
+                "{message}; tests {} did not finish",
+                self.pending.join(", ")
+            );
+        }
+        P::annotate(
+            self.severity(),
+            message,
+            None,
+            Some(Title::IncompleteTestSuite.text()),
+        )
+    }
+}
+
+impl Classify for IncompleteSession {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
+
+impl<P: Platform> CiMessage<P> for PytestMessage
+where
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::TestCase(msg) => <TestCase as CiMessage<P>>::format(msg),
+            Self::Session(msg) => <Session as CiMessage<P>>::format(msg),
+            Self::IncompleteSession(msg) => <IncompleteSession as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for PytestMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::TestCase(msg) => msg.severity(),
+            Self::Session(msg) => msg.severity(),
+            Self::IncompleteSession(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TestCase(msg) => msg.kind(),
+            Self::Session(msg) => msg.kind(),
+            Self::IncompleteSession(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        match self {
+            Self::TestCase(msg) => msg.is_test_failure(),
+            Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::IncompleteSession(msg) => msg.is_incomplete(),
+            Self::TruncatedLine(msg) => msg.is_incomplete(),
+            Self::TestCase(_) | Self::Session(_) | Self::OverlongLine(_) | Self::TextOutput(_) => {
+                false
+            }
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::TestCase(msg) => msg.sort_key(),
+            Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::TestCase(msg) => msg.file(),
+            Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::TestCase(msg) => msg.message(),
+            Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        match self {
+            Self::TestCase(msg) => msg.test_result(),
+            Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            Self::Session(msg) => msg.suite_result(),
+            Self::TestCase(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for PytestMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::TestCase(TestCase {
+                name,
+                duration: Some(duration),
+                ..
+            }) => Some(Timing::Elapsed {
+                track: name,
+                name,
+                category: "test",
+                duration: *duration,
+            }),
+            Self::TestCase(_)
+            | Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Captured for PytestMessage {
+    #[inline]
+    fn captured_output_mut(&mut self) -> Option<(&str, &mut String)> {
+        match self {
+            Self::TestCase(TestCase {
+                name,
+                output: Some(output),
+                ..
+            }) => Some((name, output)),
+            Self::TestCase(_)
+            | Self::Session(_)
+            | Self::IncompleteSession(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+/// A line of the report log.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "$report_type")]
+enum Report {
+    /// The start of the session.
+    SessionStart,
+    /// The collection of a node (e.g., a module).
+    #[serde(rename = "CollectReport")]
+    Collect(CollectReport),
+    /// A phase of a test.
+    #[serde(rename = "TestReport")]
+    Test(Box<TestReport>),
+    /// The end of the session.
+    SessionFinish(SessionFinish),
+    /// Any other report (e.g., a warning).
+    #[serde(other)]
+    Other,
+}
+
+/// The end of the session.
+#[derive(Debug, Clone, Deserialize)]
+struct SessionFinish {
+    /// The exit status of pytest.
+    exitstatus: i32,
+}
+
+/// The outcome of a phase of a test, or of the collection of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    /// The phase passed.
+    Passed,
+    /// The phase failed.
+    Failed,
+    /// The phase was skipped.
+    Skipped,
+}
+
+/// The collection of a node.
+#[derive(Debug, Clone, Deserialize)]
+struct CollectReport {
+    /// The node ID (e.g., `tests/test_math.py`).
+    nodeid: String,
+    /// The outcome of the collection.
+    outcome: Outcome,
+    /// The representation of the failure, if any.
+    #[serde(default)]
+    longrepr: Option<LongRepr>,
+}
+
+/// A phase of a test.
+#[derive(Debug, Clone, Deserialize)]
+struct TestReport {
+    /// The node ID of the test.
+    nodeid: String,
+    /// The file, zero-based line and name of the test.
+    #[serde(default)]
+    location: Option<(String, Option<u32>, String)>,
+    /// The outcome of the phase.
+    outcome: Outcome,
+    /// The representation of the failure, or of the reason for skipping the
+    /// test, if any.
+    #[serde(default)]
+    longrepr: Option<LongRepr>,
+    /// The phase (i.e., `setup`, `call` or `teardown`).
+    when: String,
+    /// The output captured while the test ran, by section.
+    #[serde(default)]
+    sections: Vec<(String, String)>,
+    /// The duration of the phase, in seconds.
+    #[serde(default)]
+    duration: Option<f64>,
+    /// The timestamp at which the phase started.
+    #[serde(default)]
+    start: Option<f64>,
+    /// The timestamp at which the phase stopped.
+    #[serde(default)]
+    stop: Option<f64>,
+    /// The reason the test was expected to fail, if it was.
+    #[serde(default)]
+    wasxfail: Option<String>,
+}
+
+/// The representation of a failure, or of the reason a test was skipped.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum LongRepr {
+    /// An exception, with its traceback.
+    Exception(ExceptionRepr),
+    /// The file, line and reason of a skipped test, of which only the
+    /// reason is kept.
+    Skip(IgnoredAny, IgnoredAny, String),
+    /// A failure already rendered as text (e.g., a syntax error found while
+    /// collecting a module).
+    Text(String),
+}
+
+/// An exception, with its traceback.
+#[derive(Debug, Clone, Deserialize)]
+struct ExceptionRepr {
+    /// Where the exception was raised, and its message.
+    #[serde(default)]
+    reprcrash: Option<FileLocation>,
+    /// The traceback of the exception.
+    reprtraceback: Traceback,
+}
+
+/// The traceback of an exception.
+#[derive(Debug, Clone, Deserialize)]
+struct Traceback {
+    /// The entries of the traceback, outermost first.
+    reprentries: Vec<TracebackEntry>,
+}
+
+/// An entry of a traceback.
+#[derive(Debug, Clone, Deserialize)]
+struct TracebackEntry {
+    /// The contents of the entry.
+    data: EntryData,
+}
+
+/// The contents of an entry of a traceback.
+#[derive(Debug, Clone, Deserialize)]
+struct EntryData {
+    /// The lines of source and of explanation (e.g., `E       assert 3 == 4`).
+    lines: Vec<String>,
+    /// The location of the entry, which is absent from native tracebacks.
+    #[serde(default)]
+    reprfileloc: Option<FileLocation>,
+}
+
+/// A location in a file, with a message.
+#[derive(Debug, Clone, Deserialize)]
+struct FileLocation {
+    /// The path of the file.
+    path: String,
+    /// The one-based line.
+    lineno: u32,
+    /// The message (e.g., the type of the exception).
+    #[serde(default)]
+    message: String,
+}
+
+impl LongRepr {
+    /// The message of the failure (e.g., `assert 3 == 4`), or the reason the
+    /// test was skipped, if any.
+    fn message(&self) -> Option<String> {
+        let message = match self {
+            Self::Exception(exception) => exception.reprcrash.as_ref()?.message.as_str(),
+            Self::Skip(_, _, reason) => reason.strip_prefix("Skipped: ").unwrap_or(reason),
+            // The exception ends the text, as in `E   ModuleNotFoundError: ...`
+            Self::Text(text) => {
+                let last = text.lines().rfind(|line| !line.trim().is_empty())?;
+                last.strip_prefix('E').unwrap_or(last)
+            }
+        };
+        Some(message.trim().to_owned()).filter(|trimmed| !trimmed.is_empty())
+    }
+
+    /// The traceback of the failure, as printed by pytest.
+    fn details(&self) -> Option<String> {
+        let details = match self {
+            Self::Exception(exception) => exception
+                .reprtraceback
+                .reprentries
+                .iter()
+                .map(|entry| match &entry.data.reprfileloc {
+                    Some(loc) => format!(
+                        "{}\n\n{}:{}: {}",
+                        entry.data.lines.join("\n"),
+                        loc.path,
+                        loc.lineno,
+                        loc.message
+                    )
+                    .trim_end()
+                    .to_owned(),
+                    None => entry.data.lines.join("\n"),
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            Self::Skip(..) => return None,
+            Self::Text(text) => text.clone(),
+        };
+        Some(details.trim_end().to_owned()).filter(|trimmed| !trimmed.is_empty())
+    }
+
+    /// The innermost line of the traceback in the file, which is where a
+    /// failure in a test is annotated.
+    fn line_in(&self, file: &str) -> Option<u32> {
+        let Self::Exception(exception) = self else {
+            return None;
+        };
+        exception
+            .reprtraceback
+            .reprentries
+            .iter()
+            .rev()
+            .filter_map(|entry| entry.data.reprfileloc.as_ref())
+            .find(|loc| loc.path == file)
+            .map(|loc| loc.lineno)
+    }
+}
+
+/// A test whose teardown has not yet been reported.
+#[derive(Debug, Clone)]
+struct OpenTest {
+    /// The result of the test so far.
+    case: TestCase,
+    /// The output captured so far, by section.
+    sections: Vec<(String, String)>,
+}
+
+impl OpenTest {
+    /// Create a passed test from the report of its first phase.
+    fn new(report: &TestReport) -> Self {
+        let (file, line) = match &report.location {
+            Some((file, line, _)) => (Some(file.clone()), line.map(|zero| zero.saturating_add(1))),
+            None => (None, None),
+        };
+        Self {
+            case: TestCase {
+                name: report.nodeid.clone(),
+                outcome: TestOutcome::Passed,
+                message: None,
+                details: None,
+                output: None,
+                duration: None,
+                file,
+                line,
+            },
+            sections: Vec::new(),
+        }
+    }
+
+    /// Record a phase of the test.
+    fn record(&mut self, report: &TestReport) {
+        let case = &mut self.case;
+        if let Some(duration) = seconds(report.duration) {
+            case.duration = Some(case.duration.unwrap_or_default().saturating_add(duration));
+        }
+        // Each report has the output captured up to its phase
+        for (title, content) in &report.sections {
+            if !self.sections.iter().any(|(seen, _)| seen == title) {
+                self.sections.push((title.clone(), content.clone()));
+            }
+        }
+
+        // The first failure is the cause of any later one
+        let longrepr = report.longrepr.as_ref();
+        match report.outcome {
+            Outcome::Failed if case.outcome != TestOutcome::Failed => {
+                case.outcome = TestOutcome::Failed;
+                let message = longrepr.and_then(LongRepr::message);
+                case.message = if report.when == "call" {
+                    message
+                } else {
+                    let reason = message.unwrap_or_else(|| "Failed".to_owned());
+                    Some(format!("Error at {}: {reason}", report.when))
+                };
+                case.details = longrepr.and_then(LongRepr::details);
+                if let Some(line) = case
+                    .file
+                    .as_deref()
+                    .and_then(|file| longrepr?.line_in(file))
+                {
+                    case.line = Some(line);
+                }
+            }
+            Outcome::Skipped if case.outcome == TestOutcome::Passed => {
+                case.outcome = TestOutcome::Skipped;
+                case.message = match &report.wasxfail {
+                    Some(reason) if reason.is_empty() => Some("Expected failure".to_owned()),
+                    Some(reason) => Some(format!("Expected failure: {reason}")),
+                    None => longrepr.and_then(LongRepr::message),
+                };
+            }
+            Outcome::Passed | Outcome::Failed | Outcome::Skipped => {}
+        }
+    }
+
+    /// Finish the test, applying the policy for the inclusion of its output.
+    fn finish(self, policy: OutputPolicy) -> TestCase {
+        let mut case = self.case;
+        let output = self
+            .sections
+            .iter()
+            .filter(|(_, content)| !content.trim().is_empty())
+            .map(|(title, content)| format!("----- {title} -----\n{}", content.trim_end()))
+            .collect::<Vec<_>>();
+        case.output = (!output.is_empty()).then(|| output.join("\n"));
+        policy.apply(&mut case.output, case.outcome == TestOutcome::Failed);
+        case
+    }
+}
+
+/// The tally of a session being read.
+#[derive(Debug, Clone)]
+struct OpenSession {
+    /// The tally of the tests reported so far.
+    result: SuiteResult,
+    /// The earliest start of a phase of a test.
+    start: Option<Duration>,
+    /// The latest end of a phase of a test.
+    stop: Option<Duration>,
+}
+
+impl Default for OpenSession {
+    fn default() -> Self {
+        Self {
+            result: SuiteResult::new(0, 0, 0),
+            start: None,
+            stop: None,
+        }
+    }
+}
+
+impl OpenSession {
+    /// Count a test in the tally.
+    fn count(&mut self, outcome: TestOutcome) {
+        let tally = match outcome {
+            TestOutcome::Passed => &mut self.result.passed,
+            TestOutcome::Failed => &mut self.result.failed,
+            TestOutcome::Skipped => &mut self.result.ignored,
+        };
+        *tally = tally.saturating_add(1);
+    }
+
+    /// Extend the span of the session to a phase of a test.
+    fn span(&mut self, report: &TestReport) {
+        if let Some(start) = seconds(report.start) {
+            self.start = Some(self.start.map_or(start, |earliest| earliest.min(start)));
+        }
+        if let Some(stop) = seconds(report.stop) {
+            self.stop = Some(self.stop.map_or(stop, |latest| latest.max(stop)));
+        }
+    }
+}
+
+/// The state of the parser, apart from the framing of its lines.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The session being read, if any.
+    session: Option<OpenSession>,
+    /// The tests whose teardown has not yet been reported, in the order in
+    /// which they started (tests run in parallel, e.g., by `pytest-xdist`,
+    /// being interleaved).
+    tests: Vec<OpenTest>,
+}
+
+impl Reader {
+    /// Handle a report of the log.
+    ///
+    /// # Returns
+    ///
+    /// The message completed by the report, if any.
+    fn handle(&mut self, report: Report, policy: OutputPolicy) -> Option<PytestMessage> {
+        match report {
+            Report::SessionStart => {
+                self.session = Some(OpenSession::default());
+                None
+            }
+            Report::Collect(collect) => self.collected(collect),
+            Report::Test(test) => self.phase(&test, policy),
+            Report::SessionFinish(finish) => {
+                let session = self.session.take().unwrap_or_default();
+                let mut result = session.result;
+                result.duration = session
+                    .start
+                    .zip(session.stop)
+                    .and_then(|(start, stop)| stop.checked_sub(start));
+                Some(PytestMessage::Session(Session {
+                    result,
+                    exit_status: finish.exitstatus,
+                }))
+            }
+            Report::Other => None,
+        }
+    }
+
+    /// Handle the collection of a node, reporting a failure as a failed test.
+    fn collected(&mut self, collect: CollectReport) -> Option<PytestMessage> {
+        if collect.outcome != Outcome::Failed {
+            return None;
+        }
+        let file = collect
+            .nodeid
+            .split("::")
+            .next()
+            .filter(|file| !file.is_empty())
+            .map(str::to_owned);
+        let longrepr = collect.longrepr.as_ref();
+        let reason = longrepr
+            .and_then(LongRepr::message)
+            .unwrap_or_else(|| "Failed".to_owned());
+        let case = TestCase {
+            message: Some(format!("Error collecting: {reason}")),
+            details: longrepr.and_then(LongRepr::details),
+            output: None,
+            duration: None,
+            line: file.as_deref().and_then(|path| longrepr?.line_in(path)),
+            file,
+            name: collect.nodeid,
+            outcome: TestOutcome::Failed,
+        };
+        if let Some(session) = &mut self.session {
+            session.count(case.outcome);
+        }
+        Some(PytestMessage::TestCase(case))
+    }
+
+    /// Handle a phase of a test, reporting the test at the end of its
+    /// teardown.
+    fn phase(&mut self, report: &TestReport, policy: OutputPolicy) -> Option<PytestMessage> {
+        if let Some(session) = &mut self.session {
+            session.span(report);
+        }
+        let index = if let Some(index) = self
+            .tests
+            .iter()
+            .position(|open| open.case.name == report.nodeid)
+        {
+            index
+        } else {
+            self.tests.push(OpenTest::new(report));
+            self.tests.len().saturating_sub(1)
+        };
+        let open = self.tests.get_mut(index)?;
+        open.record(report);
+        if report.when != "teardown" {
+            return None;
+        }
+
+        let case = self.tests.remove(index).finish(policy);
+        if let Some(session) = &mut self.session {
+            session.count(case.outcome);
+        }
+        Some(PytestMessage::TestCase(case))
+    }
+
+    /// Report a log which ended before its session, if any.
+    fn finish(&mut self) -> Option<PytestMessage> {
+        let session = self.session.take();
+        let pending: Vec<_> = mem::take(&mut self.tests)
+            .into_iter()
+            .map(|open| open.case.name)
+            .collect();
+        if session.is_none() && pending.is_empty() {
+            return None;
+        }
+        Some(PytestMessage::IncompleteSession(IncompleteSession {
+            pending,
+        }))
+    }
+}
+
+/// A line of the log, once parsed.
+#[derive(Debug, Clone)]
+enum Parsed {
+    /// A report, to be combined with the others.
+    Report(Report),
+    /// A message standing on its own (e.g., text output).
+    Message(PytestMessage),
+}
+
+/// Parse a single line of the report log.
+///
+/// # Returns
+///
+/// The parsed lines, which are empty if the line should be skipped.
+fn parse_line(
+    next: Line<'_>,
+    max_length: usize,
+    text: &mut TextLines,
+) -> Vec<Result<Parsed, serde_json::Error>> {
+    let line = match next {
+        Line::Complete(line) => line,
+        Line::Overlong(line) => {
+            let mut results = vec![Ok(Parsed::Message(PytestMessage::OverlongLine(
+                OverlongLine { max_length },
+            )))];
+            results.extend(parse_line(Line::Complete(line), max_length, text));
+            return results;
+        }
+    };
+    if line.trim_ascii().is_empty() {
+        return Vec::new();
+    }
+
+    let result = match serde_json::from_slice::<Report>(line) {
+        Ok(report) => Some(Ok(Parsed::Report(report))),
+        // Only report an error if the line looks like JSON, otherwise pass
+        // it through (e.g., the output of pytest itself)
+        Err(error) if line.first() == Some(&b'{') => Some(Err(error)),
+        Err(_) => text
+            .handle(line)
+            .map(|output| Ok(Parsed::Message(PytestMessage::TextOutput(output)))),
+    };
+    result.into_iter().collect()
+}
+
+/// Tool implementation for parsing the report logs of pytest.
+#[derive(Debug, Clone, Default)]
+pub struct Pytest {
+    /// Framer for splitting the log into JSON lines.
+    framer: LineFramer,
+    /// Handling of lines which are not JSON.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+    /// The policy for the inclusion of the output of tests.
+    policy: OutputPolicy,
+}
+
+impl Pytest {
+    /// A problem matcher for the short test summary of pytest (e.g., `FAILED
+    /// tests/test_math.py::test_add - assert 3 == 4`).
+    ///
+    /// Each failed test, and each error in a fixture or while collecting a
+    /// module, is reported as an error in its file, with the message of its
+    /// failure when pytest gives one.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-pytest",
+            vec![
+                Pattern::new(r"^(?:FAILED|ERROR) ([^\s:]+)(?:::\S+)?(?: - (.+))?$")
+                    .file(1)
+                    .message(2),
+            ],
+        )
+        .with_severity("error")
+    }
+
+    /// Handle the parsed lines of the log.
+    fn handle(
+        &mut self,
+        results: Vec<Result<Parsed, serde_json::Error>>,
+    ) -> Vec<Result<PytestMessage, serde_json::Error>> {
+        results
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(Parsed::Report(report)) => self.reader.handle(report, self.policy).map(Ok),
+                Ok(Parsed::Message(msg)) => Some(Ok(msg)),
+                Err(error) => Some(Err(error)),
+            })
+            .collect()
+    }
+}
+
+impl Detect for Pytest {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<Report>(&line).ok())
+            .any(|report| !matches!(report, Report::Other))
+            .then(Self::default)
+    }
+}
+
+impl Tool for Pytest {
+    type Message = PytestMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "pytest"
+    }
+
+    #[inline]
+    fn phase(&self) -> Phase {
+        Phase::Test
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(parse_line(line, max_length, &mut self.text));
+        }
+        self.handle(results)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let results: Vec<_> = self
+            .framer
+            .finish()
+            .into_iter()
+            .flat_map(|line| {
+                parse_line(line, max_length, &mut self.text)
+                    .into_iter()
+                    .map(move |result| {
+                        result.or_else(|error| {
+                            TruncatedLine::from_final_line(position, line, error).map(|truncated| {
+                                Parsed::Message(PytestMessage::TruncatedLine(truncated))
+                            })
+                        })
+                    })
+            })
+            .collect();
+
+        let mut messages = self.handle(results);
+        messages.extend(self.reader.finish().map(Ok));
+        messages.extend(
+            self.text
+                .finish()
+                .map(|output| Ok(PytestMessage::TextOutput(output))),
+        );
+        messages
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.policy = policy;
+    }
+}
+
+impl<P: Platform> DynTool<P> for Pytest
+where
+    PytestMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Pytest, PytestMessage};
+    use crate::capture::OutputPolicy;
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify};
+    use crate::tool::{CargoLibtest, Detect as _, Passthrough, Tool as _};
+
+    const LOG: &str = r#"{"pytest_version":"8.0.0","$report_type":"SessionStart"}
+{"nodeid":"tests/test_a.py::test_ok","location":["tests/test_a.py",0,"test_ok"],"outcome":"passed","longrepr":null,"when":"setup","sections":[],"duration":0.001,"start":100.0,"stop":100.001,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_ok","location":["tests/test_a.py",0,"test_ok"],"outcome":"passed","longrepr":null,"when":"call","sections":[],"duration":0.002,"start":100.001,"stop":100.003,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_ok","location":["tests/test_a.py",0,"test_ok"],"outcome":"passed","longrepr":null,"when":"teardown","sections":[],"duration":0.001,"start":100.003,"stop":100.004,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_add","location":["tests/test_a.py",3,"test_add"],"outcome":"passed","longrepr":null,"when":"setup","sections":[],"duration":0.001,"start":100.004,"stop":100.005,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_add","location":["tests/test_a.py",3,"test_add"],"outcome":"failed","longrepr":{"reprcrash":{"path":"/src/tests/test_a.py","lineno":5,"message":"assert 3 == 4"},"reprtraceback":{"reprentries":[{"type":"ReprEntry","data":{"lines":["    def test_add():",">       assert add(1, 2) == 4","E       assert 3 == 4"],"reprfileloc":{"path":"tests/test_a.py","lineno":5,"message":"AssertionError"}}}]}},"when":"call","sections":[["Captured stdout call","adding\n"]],"duration":0.01,"start":100.005,"stop":100.015,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_add","location":["tests/test_a.py",3,"test_add"],"outcome":"passed","longrepr":null,"when":"teardown","sections":[["Captured stdout call","adding\n"]],"duration":0.001,"start":100.015,"stop":100.016,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_skip","location":["tests/test_a.py",7,"test_skip"],"outcome":"skipped","longrepr":["/src/tests/test_a.py",7,"Skipped: not ready"],"when":"setup","sections":[],"duration":0.001,"start":100.016,"stop":100.017,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_skip","location":["tests/test_a.py",7,"test_skip"],"outcome":"passed","longrepr":null,"when":"teardown","sections":[],"duration":0.001,"start":100.017,"stop":100.018,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_db","location":["tests/test_a.py",10,"test_db"],"outcome":"failed","longrepr":{"reprcrash":{"path":"/src/tests/test_a.py","lineno":9,"message":"ConnectionError: down"},"reprtraceback":{"reprentries":[{"type":"ReprEntry","data":{"lines":["    def db():",">       raise ConnectionError(\"down\")","E       ConnectionError: down"],"reprfileloc":{"path":"tests/test_a.py","lineno":9,"message":"ConnectionError"}}}]}},"when":"setup","sections":[],"duration":0.001,"start":100.018,"stop":100.019,"$report_type":"TestReport"}
+{"nodeid":"tests/test_a.py::test_db","location":["tests/test_a.py",10,"test_db"],"outcome":"passed","longrepr":null,"when":"teardown","sections":[],"duration":0.002,"start":100.019,"stop":100.021,"$report_type":"TestReport"}
+{"exitstatus":1,"$report_type":"SessionFinish"}
+"#;
+
+    /// Parse the whole log, fed in chunks of the given size.
+    fn parse(log: &str, chunk: usize) -> Vec<PytestMessage> {
+        let mut tool = Pytest::default();
+        let mut messages = Vec::new();
+        for bytes in log.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole log, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut Pytest, log: &str) -> String
+    where
+        PytestMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(log.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <PytestMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(&mut Pytest::default(), LOG), @r#"
+        ::notice title=Test Passed%3A tests/test_a.py%3A%3Atest_ok::Executed in 0.00s
+        ::error file=tests/test_a.py,line=5,title=Test Failed%3A tests/test_a.py%3A%3Atest_add::assert 3 == 4
+        ::group::Test: tests/test_a.py::test_add
+            def test_add():
+        >       assert add(1, 2) == 4
+        E       assert 3 == 4
+
+        tests/test_a.py:5: AssertionError
+        ----- Captured stdout call -----
+        adding
+        ::endgroup::
+        ::debug::Test Ignored: tests/test_a.py::test_skip: not ready
+        ::error file=tests/test_a.py,line=9,title=Test Failed%3A tests/test_a.py%3A%3Atest_db::Error at setup: ConnectionError: down
+        ::group::Test: tests/test_a.py::test_db
+            def db():
+        >       raise ConnectionError("down")
+        E       ConnectionError: down
+
+        tests/test_a.py:9: ConnectionError
+        ::endgroup::
+        ::error title=Test Suite Failed::1 passed, 2 failed, 1 ignored in 0.02s
+        "#);
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(&mut Pytest::default(), LOG), @r#"
+        notice:  Test Passed: tests/test_a.py::test_ok: Executed in 0.00s
+        tests/test_a.py:5: error:   Test Failed: tests/test_a.py::test_add: assert 3 == 4
+        Test: tests/test_a.py::test_add
+            def test_add():
+        >       assert add(1, 2) == 4
+        E       assert 3 == 4
+
+        tests/test_a.py:5: AssertionError
+        ----- Captured stdout call -----
+        adding
+        debug:   Test Ignored: tests/test_a.py::test_skip: not ready
+        tests/test_a.py:9: error:   Test Failed: tests/test_a.py::test_db: Error at setup: ConnectionError: down
+        Test: tests/test_a.py::test_db
+            def db():
+        >       raise ConnectionError("down")
+        E       ConnectionError: down
+
+        tests/test_a.py:9: ConnectionError
+        error:   Test Suite Failed: 1 passed, 2 failed, 1 ignored in 0.02s
+        "#);
+    }
+
+    #[test]
+    fn chunked() {
+        assert_eq!(parse(LOG, 7), parse(LOG, LOG.len()));
+    }
+
+    #[test]
+    fn results() {
+        let messages = parse(LOG, LOG.len());
+
+        let failures: Vec<_> = messages
+            .iter()
+            .filter(|msg| msg.is_test_failure())
+            .collect();
+        let [failure, error] = failures.as_slice() else {
+            panic!("expected two failures");
+        };
+        assert_eq!(failure.file(), Some("tests/test_a.py"));
+        assert_eq!(failure.message(), Some("assert 3 == 4"));
+        let result = failure.test_result().expect("no test result");
+        assert_eq!(result.name, "tests/test_a.py::test_add");
+        assert_eq!(
+            result.output,
+            Some("----- Captured stdout call -----\nadding")
+        );
+        assert_eq!(result.duration, Some(Duration::from_millis(12)));
+        assert_eq!(
+            error.message(),
+            Some("Error at setup: ConnectionError: down")
+        );
+
+        let sessions: Vec<_> = messages.iter().filter_map(Classify::suite_result).collect();
+        let [session] = sessions.as_slice() else {
+            panic!("expected one session");
+        };
+        assert_eq!((session.passed, session.failed, session.ignored), (1, 2, 1));
+        assert_eq!(
+            session.duration.map(|duration| duration.as_millis()),
+            Some(21)
+        );
+    }
+
+    #[test]
+    fn expected_failure() {
+        let log = r#"{"nodeid":"t.py::test_x","location":["t.py",0,"test_x"],"outcome":"skipped","longrepr":null,"when":"call","sections":[],"duration":0.1,"wasxfail":"broken","$report_type":"TestReport"}
+{"nodeid":"t.py::test_x","location":["t.py",0,"test_x"],"outcome":"passed","longrepr":null,"when":"teardown","sections":[],"duration":0.1,"$report_type":"TestReport"}"#;
+        insta::assert_snapshot!(format::<Plain>(&mut Pytest::default(), log), @"debug:   Test Ignored: t.py::test_x: Expected failure: broken");
+    }
+
+    #[test]
+    fn collection_error() {
+        let log = r#"{"pytest_version":"8.0.0","$report_type":"SessionStart"}
+{"nodeid":"tests/test_broken.py","outcome":"failed","longrepr":"tests/test_broken.py:3: in <module>\n    import missing\nE   ModuleNotFoundError: No module named 'missing'","result":null,"sections":[],"$report_type":"CollectReport"}
+{"exitstatus":2,"$report_type":"SessionFinish"}"#;
+        insta::assert_snapshot!(format::<GitHub>(&mut Pytest::default(), log), @"
+        ::error file=tests/test_broken.py,line=1,title=Test Failed%3A tests/test_broken.py::Error collecting: ModuleNotFoundError: No module named 'missing'
+        ::group::Test: tests/test_broken.py
+        tests/test_broken.py:3: in <module>
+            import missing
+        E   ModuleNotFoundError: No module named 'missing'
+        ::endgroup::
+        ::error title=Test Suite Failed::0 passed, 1 failed, 0 ignored
+        ");
+    }
+
+    #[test]
+    fn interrupted() {
+        let log = r#"{"pytest_version":"8.0.0","$report_type":"SessionStart"}
+{"exitstatus":2,"$report_type":"SessionFinish"}"#;
+        insta::assert_snapshot!(format::<GitHub>(&mut Pytest::default(), log), @"::error title=Test Suite Failed::0 passed, 0 failed, 0 ignored; pytest exited with status 2");
+    }
+
+    #[test]
+    fn incomplete() {
+        let log = LOG.lines().take(5).collect::<Vec<_>>().join("\n");
+        let mut tool = Pytest::default();
+        insta::assert_snapshot!(format::<GitHub>(&mut tool, &log), @"
+        ::notice title=Test Passed%3A tests/test_a.py%3A%3Atest_ok::Executed in 0.00s
+        ::warning title=Incomplete Test Suite::Report log ended before the end of the session; tests tests/test_a.py::test_add did not finish
+        ");
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = LOG
+            .trim_end()
+            .strip_suffix(r#""$report_type":"SessionFinish"}"#)
+            .expect("log ends with the end of the session");
+        let messages = parse(cut, LOG.len());
+        assert!(
+            messages
+                .iter()
+                .any(|msg| matches!(msg, PytestMessage::TruncatedLine(_)))
+        );
+        assert!(messages.iter().any(Classify::is_incomplete));
+    }
+
+    #[test]
+    fn output_policy() {
+        let mut tool = Pytest::default();
+        tool.set_output_policy(OutputPolicy::Never);
+        let captured = format::<Plain>(&mut tool, LOG);
+        assert!(!captured.contains("Captured stdout call"));
+    }
+
+    #[test]
+    fn passthrough() {
+        let mut tool = Pytest::default();
+        tool.set_passthrough(Passthrough::Raw);
+        let log = format!("============ test session starts ============\n{LOG}");
+        let formatted = format::<Plain>(&mut tool, &log);
+        assert!(formatted.starts_with("============ test session starts"));
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Pytest::detect(LOG.as_bytes()).is_some());
+        assert!(Pytest::detect(br#"{"$report_type":"WarningMessage"}"#).is_none());
+        assert!(Pytest::detect(b"tests/test_a.py::test_ok PASSED\n").is_none());
+        assert!(CargoLibtest::detect(LOG.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Pytest::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regexp = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let captures = regexp
+            .captures("FAILED tests/test_a.py::test_add - assert 3 == 4")
+            .expect("failure not matched");
+        assert_eq!(captures.get(1).map(|m| m.as_str()), Some("tests/test_a.py"));
+        assert_eq!(captures.get(2).map(|m| m.as_str()), Some("assert 3 == 4"));
+        assert!(regexp.is_match("ERROR tests/test_broken.py"));
+        assert!(!regexp.is_match("PASSED tests/test_a.py::test_ok"));
+    }
+}
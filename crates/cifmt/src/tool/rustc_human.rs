@@ -0,0 +1,940 @@
+//! Human-readable rustc diagnostics.
+//!
+//! Support for parsing the diagnostics of rustc in their default,
+//! human-readable format, as printed by `cargo check` or `cargo build`
+//! without `--message-format json` (or by `rustc` itself), for builds whose
+//! output cannot be switched to JSON (e.g., a build script which wraps
+//! cargo).
+//!
+//! Each diagnostic, from its header (e.g., `error[E0308]: mismatched types`)
+//! to the next empty line, is parsed into the diagnostic which rustc would
+//! have printed as JSON, so that it is annotated the same way as by
+//! [`CargoCheck`](crate::tool::CargoCheck): at the location of its primary
+//! span (` --> src/lib.rs:4:9`), with its notes (`= note: ...`) and help as
+//! children. The code of a lint is taken from the note which enables it
+//! (e.g., ``= note: `#[warn(unused_variables)]` on by default``), and the end
+//! of the span from the carets under the source line. The suggestions of
+//! rustc are kept, but none is known to be machine applicable, so no fix is
+//! suggested in the annotations.
+//!
+//! The summaries of rustc and cargo (e.g., `error: aborting due to 2
+//! previous errors`) are skipped; cargo's `error: could not compile` and
+//! `Finished` report the result of the build instead. As the diagnostics of
+//! a library are printed again for its tests, each diagnostic is only
+//! reported once.
+
+use core::{convert::Infallible, mem};
+use std::{collections::HashSet, io::BufRead as _};
+
+use crate::{
+    ansi,
+    capture::Captured,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    matcher::{Pattern, ProblemMatcher},
+    sarif::Fix,
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool, ToolOption, ToolOptions,
+        cargo_check::{
+            BuildFinished, Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan,
+            DiagnosticSpanLine,
+        },
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+    },
+};
+
+/// The beginnings of the notes which rustc prints after its diagnostics,
+/// reported as failure notes, as in its JSON output.
+const FAILURE_NOTES: &[&str] = &[
+    "Some errors have detailed explanations: ",
+    "For more information about this error, try ",
+    "For more information about an error, try ",
+];
+
+/// A message parsed from the human-readable output of rustc.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum RustcHumanMessage {
+    /// A diagnostic of rustc.
+    Diagnostic(Box<Diagnostic>),
+
+    /// The result of the build, as reported by cargo.
+    BuildFinished(BuildFinished),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// Output which is not part of a diagnostic.
+    TextOutput(TextOutput),
+}
+
+impl<P: Platform> CiMessage<P> for RustcHumanMessage
+where
+    Diagnostic: CiMessage<P>,
+    BuildFinished: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::Diagnostic(msg) => <Diagnostic as CiMessage<P>>::format(msg),
+            Self::BuildFinished(msg) => <BuildFinished as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for RustcHumanMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Diagnostic(msg) => msg.severity(),
+            Self::BuildFinished(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Diagnostic(msg) => msg.kind(),
+            Self::BuildFinished(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn code(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.code(),
+            Self::BuildFinished(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn lint(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.lint(),
+            Self::BuildFinished(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn fixes(&self) -> Vec<Fix<'_>> {
+        match self {
+            Self::Diagnostic(msg) => msg.fixes(),
+            Self::BuildFinished(_) | Self::OverlongLine(_) | Self::TextOutput(_) => Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn snippet(&self) -> Option<String> {
+        match self {
+            Self::Diagnostic(msg) => msg.snippet(),
+            Self::BuildFinished(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.file(),
+            Self::BuildFinished(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.message(),
+            Self::BuildFinished(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn build_result(&self) -> Option<bool> {
+        match self {
+            Self::BuildFinished(msg) => msg.build_result(),
+            Self::Diagnostic(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for RustcHumanMessage {}
+
+impl Captured for RustcHumanMessage {}
+
+/// The header of a diagnostic (e.g., `error[E0308]: mismatched types`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Header<'a> {
+    /// The level of the diagnostic.
+    level: DiagnosticLevel,
+    /// The code of the diagnostic, if any.
+    code: Option<&'a str>,
+    /// The message of the diagnostic.
+    message: &'a str,
+}
+
+impl<'a> Header<'a> {
+    /// Parse the header of a diagnostic.
+    ///
+    /// # Returns
+    ///
+    /// The header, or `None` if the line is not the header of an error or a
+    /// warning.
+    fn parse(line: &'a str) -> Option<Self> {
+        let (head, message) = line.split_once(": ")?;
+        let (name, code) = match head.split_once('[') {
+            Some((name, code)) => (name, Some(code.strip_suffix(']')?)),
+            None => (head, None),
+        };
+        let level = match name {
+            "error" => DiagnosticLevel::Error,
+            "warning" => DiagnosticLevel::Warning,
+            _ => return None,
+        };
+        if level == DiagnosticLevel::Error
+            && let Some(ice) = message.strip_prefix("internal compiler error: ")
+        {
+            return Some(Self {
+                level: DiagnosticLevel::InternalCompilerError,
+                code,
+                message: ice,
+            });
+        }
+        Some(Self {
+            level,
+            code,
+            message,
+        })
+    }
+
+    /// Whether the header is that of a summary of rustc or cargo (e.g.,
+    /// `warning: 1 warning emitted`), rather than of a diagnostic.
+    fn is_summary(&self) -> bool {
+        self.code.is_none()
+            && (self.message.starts_with("aborting due to ")
+                || self.message.starts_with("build failed, waiting")
+                || self.message.ends_with(" emitted")
+                || (self.message.starts_with('`') && self.message.contains(" generated ")))
+    }
+
+    /// Whether the header is that of cargo's report of a failed
+    /// compilation (e.g., ``error: could not compile `foo` (lib) due to 2
+    /// previous errors``).
+    fn is_failure(&self) -> bool {
+        self.level == DiagnosticLevel::Error
+            && self.code.is_none()
+            && self.message.starts_with("could not compile ")
+    }
+}
+
+/// Create a diagnostic without spans or children.
+fn diagnostic(level: DiagnosticLevel, code: Option<&str>, message: &str) -> Diagnostic {
+    Diagnostic {
+        message: message.to_owned(),
+        code: code.map(|id| DiagnosticCode {
+            code: id.to_owned(),
+            explanation: None,
+        }),
+        level,
+        spans: Vec::new(),
+        children: Vec::new(),
+        rendered: None,
+        fold_children: false,
+        denied: false,
+        documentation: None,
+        undefined_behavior: None,
+    }
+}
+
+/// Parse a location as printed by rustc (e.g., `src/lib.rs:4:9`).
+///
+/// # Returns
+///
+/// The file, line and column, or `None` if the text is not a location.
+fn parse_location(location: &str) -> Option<(&str, u32, u32)> {
+    let mut parts = location.trim().rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next().filter(|file| !file.is_empty())?;
+    Some((file, line, column))
+}
+
+/// Parse the name of the lint which a note reports as enabled (e.g.,
+/// ``"`#[warn(unused_variables)]` on by default"``, or ``"`-D
+/// clippy::shadow-reuse` implied by `-D warnings`"``), which rustc only
+/// prints as the code of the diagnostic in its JSON output.
+fn parse_lint(note: &str) -> Option<String> {
+    if let Some(attribute) = note.strip_prefix("`#[") {
+        let (_level, rest) = attribute.split_once('(')?;
+        let (name, _) = rest.split_once(")]`")?;
+        return Some(name.to_owned());
+    }
+    let flag = ["`-W ", "`-D ", "`-F "]
+        .iter()
+        .find_map(|prefix| note.strip_prefix(prefix))?;
+    let (name, rest) = flag.split_once('`')?;
+    rest.starts_with(" implied by ")
+        .then(|| name.replace('-', "_"))
+}
+
+/// Split a help message from the suggestion which follows it (e.g.,
+/// ``prefix it with an underscore: `_x` ``).
+fn split_suggestion(help: &str) -> (&str, Option<&str>) {
+    help.strip_suffix('`')
+        .and_then(|rest| rest.rsplit_once(": `"))
+        .map_or((help, None), |(message, suggestion)| {
+            (message, Some(suggestion))
+        })
+}
+
+/// A diagnostic being read, up to the empty line which ends it.
+#[derive(Debug, Clone)]
+struct Block {
+    /// The diagnostic read so far.
+    diagnostic: Diagnostic,
+    /// The lines of the diagnostic, as rendered by rustc.
+    lines: Vec<String>,
+    /// The number and text of the last source line shown.
+    source: Option<(u32, String)>,
+    /// Whether the last line was a note, which may continue on the next
+    /// line.
+    continued: bool,
+}
+
+impl Block {
+    /// Start reading a diagnostic.
+    fn new(header: Header<'_>, line: &str) -> Self {
+        Self {
+            diagnostic: diagnostic(header.level, header.code, header.message),
+            lines: vec![line.to_owned()],
+            source: None,
+            continued: false,
+        }
+    }
+
+    /// Read a line of the diagnostic.
+    fn read(&mut self, line: &str) {
+        self.lines.push(line.to_owned());
+        let continued = mem::take(&mut self.continued);
+        let content = line.trim_start();
+        if let Some(location) = content.strip_prefix("--> ") {
+            self.locate(location);
+        } else if content.starts_with("::: ") {
+            // Secondary locations are not annotated
+        } else if let Some(note) = content.strip_prefix("= ") {
+            self.continued = self.child(note);
+        } else if self.excerpt(line) {
+            // The source excerpt of the diagnostic
+        } else if !line.starts_with(char::is_whitespace) {
+            self.continued = self.child(line);
+        } else if continued && let Some(child) = self.diagnostic.children.last_mut() {
+            child.message.push('\n');
+            child.message.push_str(content);
+            self.continued = true;
+        }
+    }
+
+    /// Read a line of the source excerpt (e.g., `4 |     let x = 1;`, or
+    /// the markers under it).
+    ///
+    /// # Returns
+    ///
+    /// Whether the line is part of the excerpt.
+    fn excerpt(&mut self, line: &str) -> bool {
+        let Some((gutter, rest)) = line.split_once('|') else {
+            return false;
+        };
+        let text = rest.strip_prefix(' ').unwrap_or(rest);
+        let number = gutter.trim();
+        if number.is_empty() {
+            self.mark(text);
+        } else if let Ok(parsed) = number.parse() {
+            self.source = Some((parsed, text.to_owned()));
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Read a note or help (e.g., `note: ...`) as a child of the
+    /// diagnostic.
+    ///
+    /// # Returns
+    ///
+    /// Whether the text is a note or help.
+    fn child(&mut self, text: &str) -> bool {
+        let (level, message) = if let Some(note) = text.strip_prefix("note: ") {
+            (DiagnosticLevel::Note, note)
+        } else if let Some(help) = text.strip_prefix("help: ") {
+            (DiagnosticLevel::Help, help)
+        } else {
+            return false;
+        };
+        if self.diagnostic.code.is_none()
+            && let Some(lint) = parse_lint(message)
+        {
+            self.diagnostic.code = Some(DiagnosticCode {
+                code: lint,
+                explanation: None,
+            });
+        }
+        self.diagnostic
+            .children
+            .push(diagnostic(level, None, message));
+        true
+    }
+
+    /// Read a location (e.g., `src/lib.rs:4:9`), the primary span of the
+    /// diagnostic, or of its last child if the diagnostic already has one.
+    fn locate(&mut self, location: &str) {
+        let Some((file, line, column)) = parse_location(location) else {
+            return;
+        };
+        let span = DiagnosticSpan {
+            file_name: file.to_owned(),
+            byte_start: 0,
+            byte_end: 0,
+            line_start: line,
+            line_end: line,
+            column_start: column,
+            column_end: column,
+            is_primary: true,
+            text: Vec::new(),
+            label: None,
+            suggested_replacement: None,
+            suggestion_applicability: None,
+            expansion: None,
+        };
+        if self.diagnostic.spans.is_empty() {
+            self.diagnostic.spans.push(span);
+        } else if let Some(child) = self.diagnostic.children.last_mut()
+            && child.spans.is_empty()
+        {
+            child.spans.push(span);
+        }
+    }
+
+    /// Read the markers under a source line (e.g., `^^^^ expected due to this`),
+    /// which give the end and label of the primary span if they are under
+    /// it.
+    ///
+    /// A label starting with `help: ` is read as a child of the diagnostic
+    /// on the same span, as in the JSON output of rustc.
+    fn mark(&mut self, markers: &str) {
+        let Some((number, text)) = self.source.as_ref() else {
+            return;
+        };
+        let Some(span) = self
+            .diagnostic
+            .spans
+            .first_mut()
+            .filter(|span| span.line_start == *number && span.text.is_empty())
+        else {
+            return;
+        };
+        let Some(offset) = markers.find('^') else {
+            return;
+        };
+        let (before, after) = markers.split_at(offset);
+        let carets = after.chars().take_while(|&marker| marker == '^').count();
+        let start = u32::try_from(before.chars().count())
+            .unwrap_or(u32::MAX)
+            .saturating_add(1);
+        if start != span.column_start {
+            return;
+        }
+        let end = start.saturating_add(u32::try_from(carets).unwrap_or(u32::MAX));
+        span.column_end = end;
+        span.text.push(DiagnosticSpanLine {
+            text: text.clone(),
+            highlight_start: start,
+            highlight_end: end,
+        });
+
+        let label = after.get(carets..).unwrap_or_default().trim();
+        if let Some(help) = label.strip_prefix("help: ") {
+            let (message, suggestion) = split_suggestion(help);
+            let mut child = diagnostic(DiagnosticLevel::Help, None, message);
+            child.spans.push(DiagnosticSpan {
+                suggested_replacement: suggestion.map(str::to_owned),
+                ..span.clone()
+            });
+            self.diagnostic.children.push(child);
+        } else if !label.is_empty() {
+            span.label = Some(label.to_owned());
+        }
+    }
+
+    /// Finish reading the diagnostic.
+    fn finish(self) -> Diagnostic {
+        let mut diagnostic = self.diagnostic;
+        diagnostic.rendered = Some(format!("{}\n", self.lines.join("\n")));
+        diagnostic
+    }
+}
+
+/// The state of the parser, apart from the framing of its lines.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The diagnostic being read.
+    block: Option<Block>,
+    /// Whether cargo reported that the build failed.
+    failed: bool,
+}
+
+impl Reader {
+    /// Report the diagnostic being read, if any.
+    fn flush(&mut self) -> Option<RustcHumanMessage> {
+        let block = self.block.take()?;
+        Some(RustcHumanMessage::Diagnostic(Box::new(block.finish())))
+    }
+
+    /// Parse a single line of the output.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<RustcHumanMessage> {
+        let raw = match next {
+            Line::Complete(raw) => raw,
+            Line::Overlong(raw) => {
+                let mut results: Vec<_> = self.flush().into_iter().collect();
+                results.push(RustcHumanMessage::OverlongLine(OverlongLine { max_length }));
+                results.extend(self.parse_line(Line::Complete(raw), max_length, text));
+                return results;
+            }
+        };
+
+        // rustc colours its diagnostics when forced to (e.g., with
+        // `CARGO_TERM_COLOR=always`)
+        let stripped;
+        let bytes = if raw.contains(&b'\x1b') {
+            stripped = ansi::strip(raw);
+            stripped.as_slice()
+        } else {
+            raw
+        };
+        let line = String::from_utf8_lossy(bytes);
+
+        // An empty line ends a diagnostic
+        if line.trim().is_empty() {
+            return self.flush().into_iter().collect();
+        }
+
+        if let Some(block) = &mut self.block
+            && (line.starts_with(|first: char| first.is_whitespace() || first.is_ascii_digit())
+                || line.starts_with("note: ")
+                || line.starts_with("help: "))
+        {
+            block.read(&line);
+            return Vec::new();
+        }
+
+        let mut results: Vec<_> = self.flush().into_iter().collect();
+        match Header::parse(&line) {
+            Some(header) if header.is_summary() => {}
+            Some(header) if header.is_failure() => {
+                if !mem::replace(&mut self.failed, true) {
+                    results.push(RustcHumanMessage::BuildFinished(BuildFinished {
+                        success: false,
+                    }));
+                }
+            }
+            Some(header) => self.block = Some(Block::new(header, &line)),
+            None if FAILURE_NOTES.iter().any(|note| line.starts_with(note)) => {
+                let note = diagnostic(DiagnosticLevel::FailureNote, None, &line);
+                results.push(RustcHumanMessage::Diagnostic(Box::new(note)));
+            }
+            None if line.starts_with(' ') && line.trim_start().starts_with("Finished ") => {
+                results.push(RustcHumanMessage::BuildFinished(BuildFinished {
+                    success: true,
+                }));
+            }
+            None => results.extend(text.handle(bytes).map(RustcHumanMessage::TextOutput)),
+        }
+        results
+    }
+}
+
+/// Tool implementation for parsing the human-readable diagnostics of rustc.
+#[derive(Debug, Clone, Default)]
+pub struct RustcHuman {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a diagnostic.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+    /// Whether child diagnostics are folded into their parent's annotation.
+    fold_children: bool,
+    /// The codes of the lints denied by policy.
+    denied: HashSet<String>,
+    /// Whether the source text of diagnostics is read from disk when absent.
+    read_sources: bool,
+    /// The fingerprints of the diagnostics reported so far.
+    reported: HashSet<String>,
+}
+
+impl RustcHuman {
+    /// A problem matcher for the human-readable diagnostics of rustc.
+    ///
+    /// This is the problem matcher of
+    /// [`CargoCheck`](crate::tool::CargoCheck::problem_matcher), under the
+    /// owner of this tool.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-rustc-human",
+            vec![
+                Pattern::new(r"^(warning|error)(?:\[(\S+)\])?: (.+)$")
+                    .severity(1)
+                    .code(2)
+                    .message(3),
+                Pattern::new(r"^\s*--> (.+?):(\d+):(\d+)$")
+                    .file(1)
+                    .line(2)
+                    .column(3),
+            ],
+        )
+    }
+
+    /// Apply the settings of the tool to the parsed diagnostics, dropping
+    /// those which were already reported.
+    fn prepare(&mut self, messages: Vec<RustcHumanMessage>) -> Vec<RustcHumanMessage> {
+        messages
+            .into_iter()
+            .filter_map(|mut msg| {
+                if let RustcHumanMessage::Diagnostic(diagnostic) = &mut msg {
+                    if !self.reported.insert(diagnostic.fingerprint()) {
+                        return None;
+                    }
+                    diagnostic.fold_children = self.fold_children;
+                    if let Some(code) = &diagnostic.code {
+                        diagnostic.denied = self.denied.contains(&code.code);
+                    }
+                    if self.read_sources {
+                        diagnostic.load_sources();
+                    }
+                }
+                Some(msg)
+            })
+            .collect()
+    }
+}
+
+impl Detect for RustcHuman {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        let mut header = false;
+        for line in sample.lines().map_while(Result::ok) {
+            if header && line.trim_start().starts_with("--> ") {
+                return Some(Self::default());
+            }
+            header = Header::parse(&line).is_some();
+        }
+        None
+    }
+}
+
+impl Tool for RustcHuman {
+    type Message = RustcHumanMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "rustc-human"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        self.prepare(results).into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+
+        // A diagnostic cut off before its end is reported as far as it was
+        // read
+        results.extend(self.reader.flush());
+        results.extend(self.text.finish().map(RustcHumanMessage::TextOutput));
+        self.prepare(results).into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn supported_options(&self) -> &'static [ToolOption] {
+        &[
+            ToolOption::FoldChildren,
+            ToolOption::DeniedLints,
+            ToolOption::ReadSources,
+        ]
+    }
+
+    #[inline]
+    fn set_options(&mut self, options: &ToolOptions) {
+        self.fold_children = options.fold_children;
+        self.denied = options.denied_lints.iter().cloned().collect();
+        self.read_sources = options.read_sources;
+    }
+}
+
+impl<P: Platform> DynTool<P> for RustcHuman
+where
+    RustcHumanMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        Header, RustcHuman, RustcHumanMessage, parse_lint, parse_location, split_suggestion,
+    };
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::tool::{CargoCheck, Detect as _, Miri, Tool as _, ToolOptions};
+
+    const OUTPUT: &str = "    Checking test-project v0.1.0 (/example/project)
+warning: unused variable: `unused`
+ --> src/lib.rs:3:9
+  |
+3 |     let unused = 1;
+  |         ^^^^^^ help: if this is intentional, prefix it with an underscore: `_unused`
+  |
+  = note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default
+
+warning: `test-project` (lib) generated 1 warning (run `cargo fix --lib -p test-project` to apply 1 suggestion)
+error[E0308]: mismatched types
+ --> src/bin/tool.rs:2:22
+  |
+2 |     let value: i32 = \"string\";
+  |                ---   ^^^^^^^^ expected `i32`, found `&str`
+  |                |
+  |                expected due to this
+
+For more information about this error, try `rustc --explain E0308`.
+error: could not compile `test-project` (bin \"tool\") due to 1 previous error
+";
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(tool: &mut RustcHuman, output: &str) -> String
+    where
+        RustcHumanMessage: CiMessage<P>,
+    {
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <RustcHumanMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn headers() {
+        let header = Header::parse("error[E0308]: mismatched types").expect("header");
+        assert_eq!(header.code, Some("E0308"));
+        assert_eq!(header.message, "mismatched types");
+        let ice = Header::parse("error: internal compiler error: unexpected panic");
+        assert_eq!(ice.map(|h| h.message), Some("unexpected panic"));
+        assert!(Header::parse("warning: 1 warning emitted").is_some_and(|h| h.is_summary()));
+        assert!(
+            Header::parse("error: could not compile `foo` (lib) due to 1 previous error")
+                .is_some_and(|h| h.is_failure())
+        );
+        assert_eq!(Header::parse("note: a note"), None);
+        assert_eq!(Header::parse("    Checking foo v0.1.0"), None);
+    }
+
+    #[test]
+    fn locations() {
+        assert_eq!(parse_location("src/lib.rs:4:9"), Some(("src/lib.rs", 4, 9)));
+        assert_eq!(
+            parse_location("C:\\src\\lib.rs:4:9"),
+            Some(("C:\\src\\lib.rs", 4, 9))
+        );
+        assert_eq!(parse_location("src/lib.rs"), None);
+        assert_eq!(
+            split_suggestion("prefix it with an underscore: `_x`"),
+            ("prefix it with an underscore", Some("_x"))
+        );
+        assert_eq!(split_suggestion("remove this"), ("remove this", None));
+        assert_eq!(
+            parse_lint("`#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default")
+                .as_deref(),
+            Some("unused_variables")
+        );
+        assert_eq!(
+            parse_lint("`-D clippy::shadow-reuse` implied by `-D warnings`").as_deref(),
+            Some("clippy::shadow_reuse")
+        );
+        assert_eq!(parse_lint("expected due to this"), None);
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(&mut RustcHuman::default(), OUTPUT), @"
+        ::warning file=src/lib.rs,line=3,col=9,endLine=3,endColumn=15,title=warning%3A unused_variables::unused variable: `unused`
+        ::notice file=src/lib.rs,line=3,col=9,title=help::if this is intentional, prefix it with an underscore
+        ::notice title=note::`#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default
+
+        ::error file=src/bin/tool.rs,line=2,col=22,endLine=2,endColumn=30,title=error%3A E0308::mismatched types
+
+        ::notice title=failure-note::For more information about this error, try `rustc --explain E0308`.
+
+        ::error title=Build Failed::Build failed
+        ");
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(&mut RustcHuman::default(), OUTPUT), @"
+        src/lib.rs:3:9: warning: unused_variables: unused variable: `unused`
+        src/lib.rs:3:9: notice:  help: if this is intentional, prefix it with an underscore
+        notice:  note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default
+
+        src/bin/tool.rs:2:22: error:   E0308: mismatched types
+
+        notice:  failure-note: For more information about this error, try `rustc --explain E0308`.
+
+        Build failed
+        ");
+    }
+
+    #[test]
+    fn duplicates() {
+        let output = format!("{OUTPUT}{OUTPUT}");
+        let mut tool = RustcHuman::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        let diagnostics = messages
+            .iter()
+            .flatten()
+            .filter(|msg| matches!(msg, RustcHumanMessage::Diagnostic(_)))
+            .count();
+        assert_eq!(diagnostics, 3);
+    }
+
+    #[test]
+    fn denied_lints() {
+        let mut tool = RustcHuman::default();
+        tool.set_options(
+            &ToolOptions::default()
+                .with_denied_lints(vec!["unused_variables".to_owned()])
+                .with_fold_children(true),
+        );
+        let output = OUTPUT
+            .get(..OUTPUT.find("error[").expect("error"))
+            .expect("in bounds");
+        insta::assert_snapshot!(format::<GitHub>(&mut tool, output), @"
+        ::group::warning: unused variable: `unused`
+        warning: unused variable: `unused`
+         --> src/lib.rs:3:9
+          |
+        3 |     let unused = 1;
+          |         ^^^^^^ help: if this is intentional, prefix it with an underscore: `_unused`
+          |
+          = note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default
+        ::endgroup::
+        ::error file=src/lib.rs,line=3,col=9,endLine=3,endColumn=15,title=denied warning%3A unused_variables::unused variable: `unused` - help: if this is intentional, prefix it with an underscore - note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default
+        ");
+    }
+
+    #[test]
+    fn colored() {
+        let output = "\x1b[1m\x1b[91merror[E0425]\x1b[0m\x1b[1m: cannot find value `y` in this scope\x1b[0m\n \x1b[1m\x1b[94m--> \x1b[0msrc/lib.rs:3:5\n";
+        insta::assert_snapshot!(format::<GitHub>(&mut RustcHuman::default(), output), @"::error file=src/lib.rs,line=3,col=5,endLine=3,endColumn=5,title=error%3A E0425::cannot find value `y` in this scope");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(RustcHuman::detect(OUTPUT.as_bytes()).is_some());
+        assert!(RustcHuman::detect(b"error: could not compile `foo`\n").is_none());
+        assert!(CargoCheck::detect(OUTPUT.as_bytes()).is_none());
+        assert!(Miri::detect(OUTPUT.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = RustcHuman::problem_matcher();
+        let [header, location] = matcher.pattern.as_slice() else {
+            panic!("expected two patterns");
+        };
+        let message = regex::Regex::new(&header.regexp).expect("invalid regexp");
+        let span = regex::Regex::new(&location.regexp).expect("invalid regexp");
+
+        let captures = message
+            .captures("error[E0308]: mismatched types")
+            .expect("header not matched");
+        assert_eq!(captures.get(2).map(|m| m.as_str()), Some("E0308"));
+        let located = span
+            .captures(" --> src/bin/tool.rs:2:22")
+            .expect("location not matched");
+        assert_eq!(located.get(1).map(|m| m.as_str()), Some("src/bin/tool.rs"));
+    }
+}
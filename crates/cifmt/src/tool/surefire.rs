@@ -0,0 +1,1037 @@
+//! Maven Surefire and Failsafe console output.
+//!
+//! Support for parsing the output of `mvn test` (or `mvn verify`), as
+//! printed by the Surefire plugin (or by the Failsafe plugin, for the
+//! integration tests) while it runs the tests of each module. The XML
+//! reports which these plugins write (`target/surefire-reports/TEST-*.xml`)
+//! are JUnit XML reports, read by the [`Junit`](crate::tool::Junit) tool.
+//!
+//! Each failed test is reported from the block which Surefire prints for it,
+//! headed by `[ERROR] com.example.AppTest.adds -- Time elapsed: 0.01 s <<<
+//! FAILURE!` (or `[ERROR] adds(com.example.AppTest)  Time elapsed: 0.01 s
+//! <<< FAILURE!` for Surefire 2) and followed by the stack trace of the
+//! failure. The test is annotated at the frame of the stack trace within its
+//! class, in the file at which the Maven layout places the class (e.g.,
+//! `core/src/test/java/com/example/AppTest.java`, with the directory of the
+//! module given by the `from core/pom.xml` line of Maven 3.9), with its
+//! stack trace in a group.
+//!
+//! The tally of the tests of each module (`Tests run: 5, Failures: 1, Errors:
+//! 0, Skipped: 1` under `Results:`) is reported as the summary of the module,
+//! named after its artifact and the plugin which ran the tests (e.g., `core
+//! (surefire)`), with the time taken by its test classes. Should the output
+//! end before the tally of a module, the tallies of its test classes read so
+//! far are reported instead.
+//!
+//! The colour codes of Maven are removed, and any other output is passed
+//! through as plain text output.
+
+use core::{convert::Infallible, fmt, time::Duration};
+use std::io::BufRead as _;
+
+use crate::{
+    ansi,
+    capture::Captured,
+    catalog::Title,
+    ci::{Phase, Platform},
+    ci_message::{CiMessage, Classify, Severity},
+    junit::{TestOutcome, TestResult},
+    matcher::{Pattern, ProblemMatcher},
+    suites::SuiteResult,
+    timeline::Timed,
+    tool::{
+        Detect, DynTool, Tool,
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+    },
+};
+
+/// A message parsed from the console output of Maven Surefire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SurefireMessage {
+    /// A test which failed.
+    TestFailure(TestFailure),
+
+    /// The tally of the tests of a module.
+    Summary(ModuleSummary),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// Output which is not part of a test result.
+    TextOutput(TextOutput),
+}
+
+/// A test which failed, with the stack trace of its failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TestFailure {
+    /// The full name of the test (e.g., `com.example.AppTest.adds`).
+    pub name: String,
+    /// The class of the test (e.g., `com.example.AppTest`).
+    pub class: String,
+    /// Whether the test raised an unexpected exception (`<<< ERROR!`),
+    /// rather than failing an assertion (`<<< FAILURE!`).
+    pub error: bool,
+    /// The message of the failure (i.e., the first line of its stack trace),
+    /// if any.
+    pub message: Option<String>,
+    /// The stack trace of the failure, if any.
+    pub details: Option<String>,
+    /// How long the test took to run, if known.
+    pub duration: Option<Duration>,
+    /// The file of the test, if found in the stack trace.
+    pub file: Option<String>,
+    /// The line of the test at which it failed, if found in the stack trace.
+    pub line: Option<u32>,
+}
+
+/// The tally of the tests of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ModuleSummary {
+    /// The name of the module and of the plugin which ran its tests (e.g.,
+    /// `core (surefire)`).
+    pub name: String,
+    /// The tally of the tests.
+    pub result: SuiteResult,
+}
+
+/// Display a title followed by a name (e.g., `Test Failed: com.example.A.b`).
+fn titled(title: Title, name: &str) -> String {
+    format!("{title}: {name}")
+}
+
+/// Display a duration in seconds (e.g., ` in 0.12s`), if known.
+fn time_info(duration: Option<Duration>, prefix: &'static str) -> impl fmt::Display {
+    fmt::from_fn(move |f| match duration {
+        Some(time) => write!(f, "{prefix}{:.2}s", time.as_secs_f64()),
+        None => Ok(()),
+    })
+}
+
+/// Parse a duration printed by Surefire (e.g., `0.045 s`, or `0.045 sec`
+/// for Surefire 2).
+fn parse_duration(elapsed: &str) -> Option<Duration> {
+    let trimmed = elapsed.trim();
+    let seconds = trimmed
+        .strip_suffix(" sec")
+        .or_else(|| trimmed.strip_suffix(" s"))
+        .unwrap_or(trimmed);
+    Duration::try_from_secs_f64(seconds.parse().ok()?).ok()
+}
+
+impl TestFailure {
+    /// The location at which the test is annotated, if known.
+    fn location(&self) -> Option<(&str, u32, Option<u32>)> {
+        Some((self.file.as_deref()?, self.line.unwrap_or(1), None))
+    }
+}
+
+impl<P: Platform> CiMessage<P> for TestFailure {
+    #[inline]
+    fn format(&self) -> String {
+        let mut lines = vec![P::annotate(
+            self.severity(),
+            self.message
+                .as_deref()
+                .unwrap_or(if self.error { "Error" } else { "Failed" }),
+            self.location(),
+            Some(&titled(Title::TestFailed, &self.name)),
+        )];
+        if let Some(details) = &self.details {
+            lines.push(P::group(titled(Title::Test, &self.name)));
+            lines.push(details.clone());
+            lines.push(P::endgroup());
+        }
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Classify for TestFailure {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "TestFailed"
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        Some(TestResult {
+            duration: self.duration,
+            message: self.message.as_deref(),
+            output: self.details.as_deref(),
+            ..TestResult::new(&self.name, TestOutcome::Failed)
+        })
+    }
+}
+
+impl<P: Platform> CiMessage<P> for ModuleSummary {
+    #[inline]
+    fn format(&self) -> String {
+        let SuiteResult {
+            passed,
+            failed,
+            ignored,
+            duration,
+        } = self.result;
+        let title = if failed > 0 {
+            Title::TestSuiteFailed
+        } else {
+            Title::TestSuitePassed
+        };
+        P::annotate(
+            self.severity(),
+            format!(
+                "{passed} passed, {failed} failed, {ignored} ignored{}",
+                time_info(duration, " in ")
+            ),
+            None,
+            Some(&titled(title, &self.name)),
+        )
+    }
+}
+
+impl Classify for ModuleSummary {
+    #[inline]
+    fn severity(&self) -> Severity {
+        if self.result.failed > 0 {
+            Severity::Error
+        } else {
+            Severity::Notice
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        if self.result.failed > 0 {
+            "SuiteFailed"
+        } else {
+            "SuitePassed"
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        Some(self.result)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for SurefireMessage
+where
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::TestFailure(msg) => <TestFailure as CiMessage<P>>::format(msg),
+            Self::Summary(msg) => <ModuleSummary as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for SurefireMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::TestFailure(msg) => msg.severity(),
+            Self::Summary(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TestFailure(msg) => msg.kind(),
+            Self::Summary(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        match self {
+            Self::TestFailure(msg) => msg.is_test_failure(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::TestFailure(msg) => msg.sort_key(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::TestFailure(msg) => msg.file(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::TestFailure(msg) => msg.message(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        match self {
+            Self::TestFailure(msg) => msg.test_result(),
+            Self::Summary(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            Self::Summary(msg) => msg.suite_result(),
+            Self::TestFailure(_) | Self::OverlongLine(_) | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for SurefireMessage {}
+
+impl Captured for SurefireMessage {}
+
+/// Split a line of Maven's output into its level (e.g., `ERROR`) and the
+/// rest of the line.
+fn split_level(line: &str) -> Option<(&str, &str)> {
+    let (level, rest) = line.strip_prefix('[')?.split_once(']')?;
+    if level.is_empty() || !level.bytes().all(|byte| byte.is_ascii_uppercase()) {
+        return None;
+    }
+    Some((level, rest.strip_prefix(' ').unwrap_or(rest)))
+}
+
+/// A tally of tests (e.g., `Tests run: 3, Failures: 1, Errors: 0, Skipped:
+/// 0, Time elapsed: 0.045 s <<< FAILURE! -- in com.example.AppTest`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tally<'a> {
+    /// The tally of the tests.
+    result: SuiteResult,
+    /// The test class which was run, or `None` for the tally of a module.
+    class: Option<&'a str>,
+}
+
+impl<'a> Tally<'a> {
+    /// Parse a tally.
+    ///
+    /// # Returns
+    ///
+    /// The tally, or `None` if the line is not a tally.
+    fn parse(line: &'a str) -> Option<Self> {
+        let tally = line.strip_prefix("Tests run: ")?;
+        let (counts, class) = match tally
+            .split_once(" -- in ")
+            .or_else(|| tally.split_once(" - in "))
+        {
+            Some((counts, class)) => (counts, Some(class.trim())),
+            None => (tally, None),
+        };
+        let mut fields = counts
+            .split_once(" <<< ")
+            .map_or(counts, |(head, _)| head)
+            .split(", ");
+        let run: usize = fields.next()?.trim().parse().ok()?;
+        let mut failed = 0_usize;
+        let mut ignored = 0_usize;
+        let mut duration = None;
+        for field in fields {
+            let (key, value) = field.split_once(": ")?;
+            match key {
+                "Failures" | "Errors" => {
+                    failed = failed.saturating_add(value.trim().parse().ok()?);
+                }
+                "Skipped" => ignored = value.trim().parse().ok()?,
+                "Time elapsed" => duration = parse_duration(value),
+                // e.g., `Flakes` when failing tests are rerun
+                _ => {}
+            }
+        }
+        let mut result = SuiteResult::new(
+            run.saturating_sub(failed).saturating_sub(ignored),
+            failed,
+            ignored,
+        );
+        result.duration = duration;
+        Some(Self { result, class })
+    }
+}
+
+/// Parse the header of the block of a failed test.
+///
+/// # Returns
+///
+/// The name of the test, its class if given, how long it took and whether
+/// it raised an unexpected exception, or `None` if the line is not a header.
+fn parse_failure(line: &str) -> Option<(String, Option<&str>, Option<Duration>, bool)> {
+    let (head, error) = if let Some(head) = line.strip_suffix(" <<< FAILURE!") {
+        (head, false)
+    } else {
+        (line.strip_suffix(" <<< ERROR!")?, true)
+    };
+    // Surefire 3: `com.example.AppTest.adds -- Time elapsed: 0.01 s`
+    if let Some((name, elapsed)) = head.split_once(" -- Time elapsed: ") {
+        return Some((name.to_owned(), None, parse_duration(elapsed), error));
+    }
+    // Surefire 2: `adds(com.example.AppTest)  Time elapsed: 0.01 s`
+    let (test, elapsed) = head.split_once("  Time elapsed: ")?;
+    let (method, class) = test.strip_suffix(')')?.split_once('(')?;
+    Some((
+        format!("{class}.{method}"),
+        Some(class),
+        parse_duration(elapsed),
+        error,
+    ))
+}
+
+/// Parse a frame of a stack trace (e.g., `\tat
+/// com.example.AppTest.adds(AppTest.java:14)`).
+///
+/// # Returns
+///
+/// The class, file and line of the frame, or `None` if the line is not a
+/// frame with a location.
+fn parse_frame(line: &str) -> Option<(&str, &str, u32)> {
+    let (qualified, location) = line.trim().strip_prefix("at ")?.split_once('(')?;
+    // The class loader and module of the frame (e.g., `app//`)
+    let (class, _method) = qualified
+        .rsplit_once('/')
+        .map_or(qualified, |(_, name)| name)
+        .rsplit_once('.')?;
+    let (file, number) = location.strip_suffix(')')?.rsplit_once(':')?;
+    Some((class, file, number.parse().ok()?))
+}
+
+/// The path of the source file of a test class within its module, following
+/// the Maven layout (e.g., `src/test/java/com/example/AppTest.java`).
+fn source_path(class: &str, file: &str) -> String {
+    let language = match file.rsplit_once('.') {
+        Some((_, "kt")) => "kotlin",
+        Some((_, "groovy")) => "groovy",
+        Some((_, "scala")) => "scala",
+        _ => "java",
+    };
+    match class.rsplit_once('.') {
+        Some((package, _)) => format!("src/test/{language}/{}/{file}", package.replace('.', "/")),
+        None => format!("src/test/{language}/{file}"),
+    }
+}
+
+/// A failed test whose stack trace is being read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenFailure {
+    /// The failure read so far.
+    failure: TestFailure,
+    /// The lines of the stack trace.
+    trace: Vec<String>,
+}
+
+impl OpenFailure {
+    /// Finish reading the failure, locating it in its stack trace.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory of the module, if known (e.g., `core/`).
+    fn finish(self, directory: &str) -> TestFailure {
+        let Self { mut failure, trace } = self;
+        // The outermost class, as nested classes are in the same file
+        let outer = |class: &str| class.split('$').next().unwrap_or_default().to_owned();
+        let test_class = outer(&failure.class);
+        if let Some((class, file, line)) = trace
+            .iter()
+            .filter_map(|frame| parse_frame(frame))
+            .find(|&(class, _, _)| outer(class) == test_class)
+        {
+            failure.file = Some(format!("{directory}{}", source_path(class, file)));
+            failure.line = Some(line);
+        }
+        failure.message = trace
+            .first()
+            .map(|message| message.trim().to_owned())
+            .filter(|message| !message.is_empty());
+        failure.details = Some(trace.join("\n"))
+            .map(|details| details.trim_end().to_owned())
+            .filter(|details| !details.is_empty());
+        failure
+    }
+}
+
+/// The state of the parser.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The artifact of the module whose tests are run, if known.
+    module: Option<String>,
+    /// The directory of the module (e.g., `core/`), empty if not known.
+    directory: String,
+    /// The plugin which runs the tests (e.g., `surefire`), if known.
+    plugin: Option<String>,
+    /// The test class being run, if any.
+    class: Option<String>,
+    /// The failed test whose stack trace is being read, if any.
+    failure: Option<OpenFailure>,
+    /// The tallies of the test classes of the module read so far, if any.
+    classes: Option<SuiteResult>,
+}
+
+impl Reader {
+    /// Report the failed test being read, if any.
+    fn flush(&mut self) -> Option<SurefireMessage> {
+        let failure = self.failure.take()?;
+        Some(SurefireMessage::TestFailure(
+            failure.finish(&self.directory),
+        ))
+    }
+
+    /// Report the tally of the module.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - The tally printed by Surefire, or `None` to report the
+    ///   tallies of the test classes read so far, if any.
+    fn summarize(&mut self, total: Option<SuiteResult>) -> Option<SurefireMessage> {
+        let classes = self.classes.take();
+        let mut result = total.or(classes)?;
+        result.duration = classes.and_then(|tally| tally.duration);
+        let name = match (&self.module, &self.plugin) {
+            (Some(module), Some(plugin)) => format!("{module} ({plugin})"),
+            (Some(name), None) | (None, Some(name)) => name.clone(),
+            (None, None) => "tests".to_owned(),
+        };
+        Some(SurefireMessage::Summary(ModuleSummary { name, result }))
+    }
+
+    /// Record the tally of a test class.
+    fn record(&mut self, tally: SuiteResult) {
+        let classes = self
+            .classes
+            .get_or_insert_with(|| SuiteResult::new(0, 0, 0));
+        classes.passed = classes.passed.saturating_add(tally.passed);
+        classes.failed = classes.failed.saturating_add(tally.failed);
+        classes.ignored = classes.ignored.saturating_add(tally.ignored);
+        classes.duration = match (classes.duration, tally.duration) {
+            (Some(total), Some(time)) => Some(total.saturating_add(time)),
+            (total, time) => total.or(time),
+        };
+    }
+
+    /// Update the module from a line of Maven's output.
+    ///
+    /// # Returns
+    ///
+    /// The summary of the previous module, if its tally was not read.
+    fn update_module(&mut self, line: &str) -> Option<SurefireMessage> {
+        let trimmed = line.trim();
+        if let Some(coordinates) = trimmed
+            .trim_matches('-')
+            .strip_prefix("< ")
+            .and_then(|rest| rest.strip_suffix(" >"))
+        {
+            // The header of a module (e.g., `----< com.example:core >----`)
+            let summary = self.summarize(None);
+            let artifact = coordinates.rsplit(':').next().unwrap_or(coordinates);
+            self.module = Some(artifact.to_owned());
+            self.directory = String::new();
+            self.plugin = None;
+            return summary;
+        }
+        if let Some(manifest) = trimmed.strip_prefix("from ")
+            && let Some(directory) = manifest.strip_suffix("pom.xml")
+        {
+            directory.clone_into(&mut self.directory);
+        } else if let Some(execution) = trimmed
+            .strip_prefix("--- ")
+            .and_then(|rest| rest.strip_suffix(" ---"))
+            && let Some((goal, module)) = execution.split_once(" @ ")
+        {
+            // The execution of a goal (e.g., `--- surefire:3.2.5:test
+            // (default-test) @ core ---`)
+            let prefix = goal.split(':').next().unwrap_or(goal);
+            let artifact = prefix.strip_prefix("maven-").unwrap_or(prefix);
+            let plugin = artifact.strip_suffix("-plugin").unwrap_or(artifact);
+            if matches!(plugin, "surefire" | "failsafe") {
+                self.plugin = Some(plugin.to_owned());
+            }
+            self.module = Some(module.trim().to_owned());
+        } else if let Some(class) = trimmed.strip_prefix("Running ") {
+            self.class = Some(class.to_owned());
+        }
+        None
+    }
+
+    /// Parse a single line of the output of Maven.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<SurefireMessage> {
+        let raw = match next {
+            Line::Complete(raw) => raw,
+            Line::Overlong(raw) => {
+                let mut results = vec![SurefireMessage::OverlongLine(OverlongLine { max_length })];
+                results.extend(self.parse_line(Line::Complete(raw), max_length, text));
+                return results;
+            }
+        };
+
+        // Maven colours its output unless run in batch mode
+        let stripped;
+        let bytes = if raw.contains(&b'\x1b') {
+            stripped = ansi::strip(raw);
+            stripped.as_slice()
+        } else {
+            raw
+        };
+        let line = String::from_utf8_lossy(bytes);
+        let level = split_level(&line);
+
+        // The stack trace of a failed test continues until an empty line or
+        // the next line logged by Maven
+        if let Some(open) = &mut self.failure
+            && level.is_none()
+            && !line.trim().is_empty()
+        {
+            open.trace.push(line.into_owned());
+            return Vec::new();
+        }
+        let mut results: Vec<_> = self.flush().into_iter().collect();
+
+        let Some((_, rest)) = level else {
+            if !line.trim().is_empty() {
+                results.extend(text.handle(bytes).map(SurefireMessage::TextOutput));
+            }
+            return results;
+        };
+
+        if let Some(tally) = Tally::parse(rest) {
+            if tally.class.is_some() {
+                self.record(tally.result);
+            } else {
+                results.extend(self.summarize(Some(tally.result)));
+                return results;
+            }
+        } else if let Some((name, given, duration, error)) = parse_failure(rest) {
+            let class = given.map_or_else(
+                || {
+                    // The class being run, unless the test is not one of it
+                    self.class
+                        .clone()
+                        .filter(|running| name.starts_with(&format!("{running}.")))
+                        .unwrap_or_else(|| {
+                            name.rsplit_once('.')
+                                .map_or(name.as_str(), |(outer, _)| outer)
+                                .to_owned()
+                        })
+                },
+                str::to_owned,
+            );
+            self.failure = Some(OpenFailure {
+                failure: TestFailure {
+                    name,
+                    class,
+                    error,
+                    message: None,
+                    details: None,
+                    duration,
+                    file: None,
+                    line: None,
+                },
+                trace: Vec::new(),
+            });
+            return results;
+        } else {
+            results.extend(self.update_module(rest));
+        }
+        results.extend(text.handle(bytes).map(SurefireMessage::TextOutput));
+        results
+    }
+}
+
+/// Tool implementation for parsing the console output of Maven Surefire.
+#[derive(Debug, Clone, Default)]
+pub struct Surefire {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a test result.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl Detect for Surefire {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        sample
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| {
+                split_level(&line)
+                    .is_some_and(|(_, rest)| rest == " T E S T S" || Tally::parse(rest).is_some())
+            })
+            .then(Self::default)
+    }
+}
+
+impl Surefire {
+    /// A problem matcher for the tests which fail in the output of `mvn
+    /// test`.
+    ///
+    /// The header of the block of a failed test (e.g., `[ERROR]
+    /// com.example.AppTest.adds -- Time elapsed: 0.01 s <<< FAILURE!`) gives
+    /// its name as the message, but not its location. Each match is reported as an error.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-surefire",
+            vec![
+                Pattern::new(
+                    r"^\[ERROR\] (\S+)(?: --|\s+) Time elapsed: .+ <<< (?:FAILURE|ERROR)!$",
+                )
+                .message(1),
+            ],
+        )
+        .with_severity("error")
+    }
+}
+
+impl Tool for Surefire {
+    type Message = SurefireMessage;
+    type Error = Infallible;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "surefire"
+    }
+
+    #[inline]
+    fn phase(&self) -> Phase {
+        Phase::Test
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        while let Some(line) = self.framer.next_line() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(self.reader.parse_line(line, max_length, &mut self.text));
+        }
+
+        results.extend(self.reader.flush());
+        results.extend(self.reader.summarize(None));
+        results.extend(self.text.finish().map(SurefireMessage::TextOutput));
+        results.into_iter().map(Ok).collect()
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+}
+
+impl<P: Platform> DynTool<P> for Surefire
+where
+    SurefireMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Surefire, SurefireMessage, Tally, parse_failure, parse_frame, source_path};
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::CiMessage;
+    use crate::suites::SuiteResult;
+    use crate::tool::{Detect as _, Tool as _};
+
+    const OUTPUT: &str = "\
+[INFO] -------------------------< com.example:core >-------------------------
+[INFO] Building core 1.0-SNAPSHOT                                        [2/3]
+[INFO]   from core/pom.xml
+[INFO] --------------------------------[ jar ]---------------------------------
+[INFO] --- surefire:3.2.5:test (default-test) @ core ---
+[INFO] -------------------------------------------------------
+[INFO]  T E S T S
+[INFO] -------------------------------------------------------
+[INFO] Running com.example.CalculatorTest
+[ERROR] Tests run: 3, Failures: 1, Errors: 1, Skipped: 0, Time elapsed: 0.045 s <<< FAILURE! -- in com.example.CalculatorTest
+[ERROR] com.example.CalculatorTest.adds -- Time elapsed: 0.005 s <<< FAILURE!
+org.opentest4j.AssertionFailedError: expected: <4> but was: <3>
+\tat org.junit.jupiter.api.AssertionUtils.fail(AssertionUtils.java:151)
+\tat org.junit.jupiter.api.Assertions.assertEquals(Assertions.java:150)
+\tat com.example.CalculatorTest.adds(CalculatorTest.java:14)
+\tat java.base/java.lang.reflect.Method.invoke(Method.java:580)
+
+[ERROR] com.example.CalculatorTest.divides -- Time elapsed: 0.002 s <<< ERROR!
+java.lang.ArithmeticException: / by zero
+\tat com.example.Calculator.divide(Calculator.java:9)
+\tat com.example.CalculatorTest.divides(CalculatorTest.java:20)
+
+[INFO] Running com.example.StringsTest
+[WARNING] Tests run: 2, Failures: 0, Errors: 0, Skipped: 1, Time elapsed: 0.010 s -- in com.example.StringsTest
+[INFO]
+[INFO] Results:
+[INFO]
+[ERROR] Failures:
+[ERROR]   CalculatorTest.adds:14 expected: <4> but was: <3>
+[ERROR] Errors:
+[ERROR]   CalculatorTest.divides:20 » Arithmetic / by zero
+[INFO]
+[ERROR] Tests run: 5, Failures: 1, Errors: 1, Skipped: 1
+[INFO]
+[INFO] BUILD FAILURE
+";
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        SurefireMessage: CiMessage<P>,
+    {
+        let mut tool = Surefire::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <SurefireMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn tallies() {
+        let tally = Tally::parse("Tests run: 3, Failures: 1, Errors: 1, Skipped: 0, Time elapsed: 0.045 s <<< FAILURE! -- in com.example.CalculatorTest")
+            .expect("valid tally");
+        assert_eq!(tally.class, Some("com.example.CalculatorTest"));
+        assert_eq!(tally.result.failed, 2);
+        assert_eq!(tally.result.duration, Some(Duration::from_millis(45)));
+
+        let total =
+            Tally::parse("Tests run: 5, Failures: 1, Errors: 0, Skipped: 2").expect("valid tally");
+        assert_eq!(total.class, None);
+        assert_eq!(total.result, SuiteResult::new(2, 1, 2));
+
+        assert_eq!(Tally::parse("Tests run: lots"), None);
+    }
+
+    #[test]
+    fn headers() {
+        assert_eq!(
+            parse_failure("com.example.AppTest.adds -- Time elapsed: 0.01 s <<< FAILURE!"),
+            Some((
+                "com.example.AppTest.adds".to_owned(),
+                None,
+                Some(Duration::from_millis(10)),
+                false
+            ))
+        );
+        assert_eq!(
+            parse_failure("adds(com.example.AppTest)  Time elapsed: 0.01 sec  <<< ERROR!"),
+            Some((
+                "com.example.AppTest.adds".to_owned(),
+                Some("com.example.AppTest"),
+                Some(Duration::from_millis(10)),
+                true
+            ))
+        );
+        assert_eq!(parse_failure("Tests run: 1 <<< FAILURE!"), None);
+    }
+
+    #[test]
+    fn frames() {
+        assert_eq!(
+            parse_frame("\tat com.example.AppTest.adds(AppTest.java:14)"),
+            Some(("com.example.AppTest", "AppTest.java", 14))
+        );
+        assert_eq!(
+            parse_frame("\tat app//com.example.AppTest$Nested.adds(AppTest.java:30)"),
+            Some(("com.example.AppTest$Nested", "AppTest.java", 30))
+        );
+        assert_eq!(
+            parse_frame("\tat java.base/jdk.internal.Foo.bar(Native Method)"),
+            None
+        );
+        assert_eq!(
+            source_path("com.example.AppTest", "AppTest.kt"),
+            "src/test/kotlin/com/example/AppTest.kt"
+        );
+        assert_eq!(
+            source_path("AppTest", "AppTest.java"),
+            "src/test/java/AppTest.java"
+        );
+    }
+
+    #[test]
+    fn failures() {
+        insta::assert_snapshot!(format::<GitHub>(OUTPUT), @"
+        ::error file=core/src/test/java/com/example/CalculatorTest.java,line=14,title=Test Failed%3A com.example.CalculatorTest.adds::org.opentest4j.AssertionFailedError: expected: <4> but was: <3>
+        ::group::Test: com.example.CalculatorTest.adds
+        org.opentest4j.AssertionFailedError: expected: <4> but was: <3>
+        	at org.junit.jupiter.api.AssertionUtils.fail(AssertionUtils.java:151)
+        	at org.junit.jupiter.api.Assertions.assertEquals(Assertions.java:150)
+        	at com.example.CalculatorTest.adds(CalculatorTest.java:14)
+        	at java.base/java.lang.reflect.Method.invoke(Method.java:580)
+        ::endgroup::
+        ::error file=core/src/test/java/com/example/CalculatorTest.java,line=20,title=Test Failed%3A com.example.CalculatorTest.divides::java.lang.ArithmeticException: / by zero
+        ::group::Test: com.example.CalculatorTest.divides
+        java.lang.ArithmeticException: / by zero
+        	at com.example.Calculator.divide(Calculator.java:9)
+        	at com.example.CalculatorTest.divides(CalculatorTest.java:20)
+        ::endgroup::
+        ::error title=Test Suite Failed%3A core (surefire)::2 passed, 2 failed, 1 ignored in 0.06s
+        ");
+    }
+
+    #[test]
+    fn surefire_2() {
+        let output = "\
+[INFO] --- maven-surefire-plugin:2.22.2:test (default-test) @ app ---
+[INFO] Running com.example.AppTest
+[ERROR] Tests run: 1, Failures: 1, Errors: 0, Skipped: 0, Time elapsed: 0.01 s <<< FAILURE! - in com.example.AppTest
+[ERROR] adds(com.example.AppTest)  Time elapsed: 0.01 s  <<< FAILURE!
+java.lang.AssertionError: expected:<4> but was:<3>
+\tat com.example.AppTest.adds(AppTest.java:12)
+
+[ERROR] Tests run: 1, Failures: 1, Errors: 0, Skipped: 0
+";
+        insta::assert_snapshot!(format::<Plain>(output), @"
+        src/test/java/com/example/AppTest.java:12: error:   Test Failed: com.example.AppTest.adds: java.lang.AssertionError: expected:<4> but was:<3>
+        Test: com.example.AppTest.adds
+        java.lang.AssertionError: expected:<4> but was:<3>
+        	at com.example.AppTest.adds(AppTest.java:12)
+        error:   Test Suite Failed: app (surefire): 0 passed, 1 failed, 0 ignored in 0.01s
+        ");
+    }
+
+    #[test]
+    fn interrupted() {
+        let output = "\
+[INFO] --- failsafe:3.2.5:integration-test (default) @ app ---
+[INFO] Running com.example.AppIT
+[INFO] Tests run: 4, Failures: 0, Errors: 0, Skipped: 0, Time elapsed: 1.5 s -- in com.example.AppIT
+[INFO] Running com.example.OtherIT
+";
+        insta::assert_snapshot!(format::<Plain>(output), @"notice:  Test Suite Passed: app (failsafe): 4 passed, 0 failed, 0 ignored in 1.50s");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Surefire::detect(OUTPUT.as_bytes()).is_some());
+        assert!(Surefire::detect(b"[INFO] BUILD SUCCESS\n").is_none());
+        assert!(Surefire::detect(b"Tests run: 1, Failures: 0, Errors: 0, Skipped: 0\n").is_none());
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Surefire::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+        for (line, test) in [
+            (
+                "[ERROR] com.example.AppTest.adds -- Time elapsed: 0.01 s <<< FAILURE!",
+                "com.example.AppTest.adds",
+            ),
+            (
+                "[ERROR] adds(com.example.AppTest)  Time elapsed: 0.01 s  <<< ERROR!",
+                "adds(com.example.AppTest)",
+            ),
+        ] {
+            let captures = regex.captures(line).expect("line not matched");
+            assert_eq!(captures.get(1).map(|m| m.as_str()), Some(test));
+        }
+        assert!(!regex.is_match("[ERROR] Tests run: 1, Failures: 1, Errors: 0, Skipped: 0"));
+    }
+}
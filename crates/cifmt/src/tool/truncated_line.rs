@@ -0,0 +1,187 @@
+//! Truncated line messages.
+//!
+//! If the tool's output is cut off (e.g., because the job was killed or ran
+//! out of memory), its final line may be incomplete. Rather than silently
+//! discarding such a line, tools report it through a [`TruncatedLine`]
+//! message, which describes where the output ended.
+
+use crate::{
+    catalog::Title,
+    ci::{AzureDevOps, Buildkite, CloudBuild, CodeBuild, GitHub, Plain, Terminal},
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+    tool::line_framer::Line,
+};
+
+/// An incomplete message at the end of the tool's output.
+///
+/// The content of the line is discarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TruncatedLine {
+    /// The number of the line within the output (1-indexed).
+    pub line_number: usize,
+    /// The offset of the start of the line within the output, in bytes.
+    pub offset: u64,
+    /// The length of the incomplete line, in bytes.
+    pub length: usize,
+    /// The reason the line could not be parsed.
+    pub error: String,
+}
+
+impl TruncatedLine {
+    /// Report the final line of the output if it could not be parsed because
+    /// it ended prematurely.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the final line, as
+    ///   returned by [`LineFramer::position`](super::line_framer::LineFramer::position).
+    /// * `line` - The final line.
+    /// * `error` - The error encountered while parsing the final line.
+    ///
+    /// # Returns
+    ///
+    /// The truncated line, or the original error if the line is not
+    /// truncated (i.e., it is complete but otherwise invalid).
+    pub(crate) fn from_final_line(
+        position: (usize, u64),
+        line: Line<'_>,
+        error: serde_json::Error,
+    ) -> Result<Self, serde_json::Error> {
+        match line {
+            Line::Complete(content) if error.is_eof() => {
+                let (line_number, offset) = position;
+                Ok(Self {
+                    line_number,
+                    offset,
+                    length: content.len(),
+                    error: error.to_string(),
+                })
+            }
+            Line::Complete(_) | Line::Overlong(_) => Err(error),
+        }
+    }
+
+    /// The description of where the output ended.
+    fn description(&self) -> String {
+        format!(
+            "Output ended within line {} (at byte offset {}), discarding {} bytes of an incomplete message: {}",
+            self.line_number, self.offset, self.length, self.error
+        )
+    }
+}
+
+impl CiMessage<Plain> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        Plain::line(
+            Severity::Warning,
+            &self.description(),
+            None,
+            Some(Title::TruncatedOutput.text()),
+        )
+    }
+}
+
+impl CiMessage<GitHub> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        GitHub::warning(self.description())
+            .title(Title::TruncatedOutput.text())
+            .format()
+    }
+}
+
+impl CiMessage<AzureDevOps> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        AzureDevOps::warning(self.description()).format()
+    }
+}
+
+impl CiMessage<Buildkite> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        format!(
+            "{}{}",
+            Buildkite::expand_previous(),
+            <Self as CiMessage<Plain>>::format(self)
+        )
+    }
+}
+
+impl CiMessage<CodeBuild> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        <Self as CiMessage<Plain>>::format(self)
+    }
+}
+
+impl CiMessage<CloudBuild> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        CloudBuild::entry(
+            self.severity(),
+            <Self as CiMessage<Plain>>::format(self),
+            None,
+        )
+    }
+}
+
+impl CiMessage<Terminal> for TruncatedLine {
+    #[inline]
+    fn format(&self) -> String {
+        Terminal::message(self.severity(), <Self as CiMessage<Plain>>::format(self))
+    }
+}
+
+impl Classify for TruncatedLine {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        true
+    }
+}
+
+impl Timed for TruncatedLine {}
+
+#[cfg(test)]
+mod tests {
+    use super::TruncatedLine;
+    use crate::ci::{AzureDevOps, Buildkite, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[test]
+    fn format() {
+        let message = TruncatedLine {
+            line_number: 42,
+            offset: 4096,
+            length: 17,
+            error: "EOF while parsing a string at line 1 column 17".to_owned(),
+        };
+        insta::assert_snapshot!(
+            <TruncatedLine as CiMessage<Plain>>::format(&message),
+            @"warning: Truncated Output: Output ended within line 42 (at byte offset 4096), discarding 17 bytes of an incomplete message: EOF while parsing a string at line 1 column 17"
+        );
+        insta::assert_snapshot!(
+            <TruncatedLine as CiMessage<GitHub>>::format(&message),
+            @"::warning title=Truncated Output::Output ended within line 42 (at byte offset 4096), discarding 17 bytes of an incomplete message: EOF while parsing a string at line 1 column 17"
+        );
+        insta::assert_snapshot!(
+            <TruncatedLine as CiMessage<AzureDevOps>>::format(&message),
+            @"##vso[task.logissue type=warning]Output ended within line 42 (at byte offset 4096), discarding 17 bytes of an incomplete message: EOF while parsing a string at line 1 column 17"
+        );
+        insta::assert_snapshot!(
+            <TruncatedLine as CiMessage<Buildkite>>::format(&message),
+            @"
+        ^^^ +++
+        warning: Truncated Output: Output ended within line 42 (at byte offset 4096), discarding 17 bytes of an incomplete message: EOF while parsing a string at line 1 column 17
+        "
+        );
+    }
+}
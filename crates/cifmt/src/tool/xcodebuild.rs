@@ -0,0 +1,1576 @@
+//! xcodebuild output and xcresult summaries.
+//!
+//! Support for parsing the output of `xcodebuild build` and `xcodebuild
+//! test`, so that the diagnostics of the compilers and the failures of XCTest
+//! are annotated at their location, and for parsing the summary of a result
+//! bundle printed by `xcrun xcresulttool get --legacy --format json --path
+//! App.xcresult`, for the runs whose output is not kept.
+//!
+//! Each error and warning (e.g., `/app/App/Model.swift:12:5: error: cannot
+//! find 'total' in scope`) is annotated at its file and line. The steps of
+//! the build name the target which they build (e.g., `CompileSwift normal
+//! arm64 /app/App/Model.swift (in target 'App' from project 'App')`), by
+//! which the diagnostics are grouped per target, a group being opened
+//! whenever the build moves to another target.
+//!
+//! The tests run by XCTest are reported as the tests of JUnit XML reports
+//! are by the [`Junit`](crate::tool::Junit) tool: each test with its outcome
+//! as it finishes (e.g., `Test Case '-[AppTests.ModelTests testTotal]'
+//! failed (0.002 seconds).`), annotated at the first of its failures, and
+//! each test class with the tally of its tests. The output of a test is kept
+//! as its captured output.
+//!
+//! The summary of a result bundle is a JSON document, parsed once its closing
+//! brace is read, whose errors, warnings and test failures are annotated in
+//! the same way; a summary cut off before its end is reported as truncated.
+//!
+//! Any other output (e.g., the commands run by each step) is passed through
+//! as plain text output.
+
+use core::{mem, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    capture::{Captured, OutputPolicy},
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    junit::{TestOutcome, TestResult},
+    matcher::{Pattern, ProblemMatcher},
+    suites::SuiteResult,
+    timeline::{Timed, Timing},
+    tool::{
+        Detect, DynTool, Tool,
+        junit::{IncompleteReport, JunitMessage, TestCase, TestSuite},
+        line_framer::{Line, LineFramer},
+        overlong_line::OverlongLine,
+        passthrough::{Passthrough, TextLines, TextOutput},
+        truncated_line::TruncatedLine,
+    },
+};
+
+/// A message parsed from the output of xcodebuild.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum XcodebuildMessage {
+    /// The build moved to a target, whose group is opened.
+    TargetStarted(TargetStarted),
+
+    /// The build moved away from a target, whose group is closed.
+    TargetFinished(TargetFinished),
+
+    /// An error or warning of the build.
+    Diagnostic(Box<XcodeDiagnostic>),
+
+    /// The result of a test or of a test class.
+    Test(JunitMessage),
+
+    /// A line which exceeded the maximum line length.
+    OverlongLine(OverlongLine),
+
+    /// A summary of a result bundle cut off before its end.
+    TruncatedLine(TruncatedLine),
+
+    /// Output which is not part of a diagnostic or of the tests.
+    TextOutput(TextOutput),
+}
+
+/// The start of the steps building a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TargetStarted {
+    /// The name of the target (e.g., `App`).
+    pub target: String,
+}
+
+/// The end of the steps building a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TargetFinished {
+    /// The name of the target (e.g., `App`).
+    pub target: String,
+}
+
+/// The level of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XcodeLevel {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+}
+
+/// An error or warning of the build (e.g., of the Swift compiler).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct XcodeDiagnostic {
+    /// The level of the diagnostic.
+    pub level: XcodeLevel,
+    /// The message of the diagnostic.
+    pub message: String,
+    /// The file of the diagnostic, if any.
+    pub file: Option<String>,
+    /// The line of the diagnostic, if any.
+    pub line: Option<u32>,
+    /// The column of the diagnostic, if any.
+    pub column: Option<u32>,
+    /// The kind of the diagnostic given by a result bundle (e.g., `Swift
+    /// Compiler Error`), or the program which reported a diagnostic without
+    /// a location (e.g., `ld`), if any.
+    pub origin: Option<String>,
+    /// The target whose build reported the diagnostic, if known.
+    pub target: Option<String>,
+}
+
+impl<P: Platform> CiMessage<P> for TargetStarted {
+    #[inline]
+    fn format(&self) -> String {
+        P::group(format!("{}: {}", Title::Target, self.target))
+    }
+}
+
+impl Classify for TargetStarted {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "TargetStarted"
+    }
+}
+
+impl<P: Platform> CiMessage<P> for TargetFinished {
+    #[inline]
+    fn format(&self) -> String {
+        P::endgroup()
+    }
+}
+
+impl Classify for TargetFinished {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Debug
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "TargetFinished"
+    }
+}
+
+impl<P: Platform> CiMessage<P> for XcodeDiagnostic {
+    #[inline]
+    fn format(&self) -> String {
+        let location = self
+            .file
+            .as_deref()
+            .map(|file| (file, self.line.unwrap_or(1), self.column));
+        P::annotate(
+            self.severity(),
+            &self.message,
+            location,
+            self.origin.as_deref(),
+        )
+    }
+}
+
+impl Classify for XcodeDiagnostic {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self.level {
+            XcodeLevel::Error => Severity::Error,
+            XcodeLevel::Warning => Severity::Warning,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        self.file()
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+}
+
+impl<P: Platform> CiMessage<P> for XcodebuildMessage
+where
+    JunitMessage: CiMessage<P>,
+    OverlongLine: CiMessage<P>,
+    TruncatedLine: CiMessage<P>,
+    TextOutput: CiMessage<P>,
+{
+    #[inline]
+    fn format(&self) -> String {
+        match self {
+            Self::TargetStarted(msg) => <TargetStarted as CiMessage<P>>::format(msg),
+            Self::TargetFinished(msg) => <TargetFinished as CiMessage<P>>::format(msg),
+            Self::Diagnostic(msg) => <XcodeDiagnostic as CiMessage<P>>::format(msg),
+            Self::Test(msg) => <JunitMessage as CiMessage<P>>::format(msg),
+            Self::OverlongLine(msg) => <OverlongLine as CiMessage<P>>::format(msg),
+            Self::TruncatedLine(msg) => <TruncatedLine as CiMessage<P>>::format(msg),
+            Self::TextOutput(msg) => <TextOutput as CiMessage<P>>::format(msg),
+        }
+    }
+}
+
+impl Classify for XcodebuildMessage {
+    #[inline]
+    fn severity(&self) -> Severity {
+        match self {
+            Self::TargetStarted(msg) => msg.severity(),
+            Self::TargetFinished(msg) => msg.severity(),
+            Self::Diagnostic(msg) => msg.severity(),
+            Self::Test(msg) => msg.severity(),
+            Self::OverlongLine(msg) => msg.severity(),
+            Self::TruncatedLine(msg) => msg.severity(),
+            Self::TextOutput(msg) => msg.severity(),
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TargetStarted(msg) => msg.kind(),
+            Self::TargetFinished(msg) => msg.kind(),
+            Self::Diagnostic(msg) => msg.kind(),
+            Self::Test(msg) => msg.kind(),
+            Self::OverlongLine(msg) => msg.kind(),
+            Self::TruncatedLine(msg) => msg.kind(),
+            Self::TextOutput(msg) => msg.kind(),
+        }
+    }
+
+    #[inline]
+    fn is_test_failure(&self) -> bool {
+        match self {
+            Self::Test(msg) => msg.is_test_failure(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Diagnostic(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn is_incomplete(&self) -> bool {
+        match self {
+            Self::Test(msg) => msg.is_incomplete(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Diagnostic(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => false,
+        }
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.sort_key(),
+            Self::Test(msg) => msg.sort_key(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn package(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.package(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Test(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn file(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.file(),
+            Self::Test(msg) => msg.file(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Diagnostic(msg) => msg.message(),
+            Self::Test(msg) => msg.message(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn test_result(&self) -> Option<TestResult<'_>> {
+        match self {
+            Self::Test(msg) => msg.test_result(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Diagnostic(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+
+    #[inline]
+    fn suite_result(&self) -> Option<SuiteResult> {
+        match self {
+            Self::Test(msg) => msg.suite_result(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Diagnostic(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Timed for XcodebuildMessage {
+    #[inline]
+    fn timing(&self) -> Option<Timing<'_>> {
+        match self {
+            Self::Test(msg) => msg.timing(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Diagnostic(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+impl Captured for XcodebuildMessage {
+    #[inline]
+    fn captured_output_mut(&mut self) -> Option<(&str, &mut String)> {
+        match self {
+            Self::Test(msg) => msg.captured_output_mut(),
+            Self::TargetStarted(_)
+            | Self::TargetFinished(_)
+            | Self::Diagnostic(_)
+            | Self::OverlongLine(_)
+            | Self::TruncatedLine(_)
+            | Self::TextOutput(_) => None,
+        }
+    }
+}
+
+/// Parse a location (e.g., `/app/App/Model.swift:12:5` or
+/// `/app/AppTests/ModelTests.swift:18`) into its file, line and column.
+fn parse_location(location: &str) -> Option<(&str, u32, Option<u32>)> {
+    let (rest, last) = location.rsplit_once(':')?;
+    let number = last.parse().ok()?;
+    let (file, line, column) = match rest.rsplit_once(':') {
+        Some((file, line)) if line.parse::<u32>().is_ok() => {
+            (file, line.parse().ok()?, Some(number))
+        }
+        Some(_) | None => (rest, number, None),
+    };
+    (!file.is_empty() && file != "<unknown>").then_some((file, line, column))
+}
+
+/// Split the target which reported a diagnostic or which a step builds from
+/// the end of a line (e.g., `CompileSwift normal arm64 (in target 'App' from
+/// project 'App')`).
+///
+/// # Returns
+///
+/// The rest of the line and the target, if given.
+fn split_target(line: &str) -> (&str, Option<&str>) {
+    let Some((rest, suffix)) = line.trim_end().rsplit_once(" (in target '") else {
+        return (line, None);
+    };
+    match suffix.split_once("' from project '") {
+        Some((target, project)) if project.ends_with("')") => (rest, Some(target)),
+        Some(_) | None => (line, None),
+    }
+}
+
+/// Parse the target of a banner of the legacy build system (e.g., `=== BUILD
+/// TARGET App OF PROJECT App WITH CONFIGURATION Debug ===`).
+fn parse_banner(line: &str) -> Option<&str> {
+    let (_, rest) = line.strip_prefix("=== ")?.split_once(" TARGET ")?;
+    Some(rest.split_once(" OF PROJECT ")?.0)
+}
+
+/// Parse a diagnostic of the build (e.g., `/app/App/Model.swift:12:5: error:
+/// cannot find 'total' in scope`, `ld: warning: ...` or `error: Signing for
+/// "App" requires a development team.`).
+fn parse_diagnostic(line: &str) -> Option<XcodeDiagnostic> {
+    let (level, prefix, message) = [
+        (XcodeLevel::Error, "error: "),
+        (XcodeLevel::Warning, "warning: "),
+    ]
+    .into_iter()
+    .filter_map(|(level, marker)| {
+        if let Some(message) = line.strip_prefix(marker) {
+            return Some((level, "", message));
+        }
+        let (prefix, message) = line.split_once(&format!(": {marker}"))?;
+        Some((level, prefix, message))
+    })
+    .min_by_key(|(_, prefix, _)| prefix.len())?;
+
+    let (text, target) = split_target(message);
+    let mut diagnostic = XcodeDiagnostic {
+        level,
+        message: text.trim().to_owned(),
+        file: None,
+        line: None,
+        column: None,
+        origin: None,
+        target: target.map(str::to_owned),
+    };
+    if let Some((file, line_number, column)) = parse_location(prefix) {
+        diagnostic.file = Some(file.to_owned());
+        diagnostic.line = Some(line_number);
+        diagnostic.column = column;
+    } else if !prefix.is_empty() {
+        // A program, rather than a sentence which contains the marker
+        if prefix.contains([' ', '/']) {
+            return None;
+        }
+        diagnostic.origin = Some(prefix.to_owned());
+    }
+    (!diagnostic.message.is_empty()).then_some(diagnostic)
+}
+
+/// The name of a test as reported, qualified by its class and module (e.g.,
+/// `AppTests.ModelTests.testTotal` for `-[AppTests.ModelTests testTotal]`).
+fn qualified_name(reference: &str) -> String {
+    reference
+        .strip_prefix("-[")
+        .and_then(|name| name.strip_suffix(']'))
+        .map_or_else(|| reference.to_owned(), |name| name.replacen(' ', ".", 1))
+}
+
+/// Parse a duration in seconds (e.g., `(0.002 seconds).`) at the end of a
+/// line.
+fn parse_seconds(text: &str) -> Option<Duration> {
+    let (_, rest) = text.split_once('(')?;
+    let (seconds, _) = rest.split_once(" seconds)")?;
+    Duration::try_from_secs_f64(seconds.parse().ok()?).ok()
+}
+
+/// An event of XCTest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum XctestEvent<'a> {
+    /// A test class (or bundle) started.
+    SuiteStarted(&'a str),
+    /// A test class (or bundle) ended.
+    SuiteFinished,
+    /// A test started.
+    CaseStarted(&'a str),
+    /// A test ended, with its outcome and duration.
+    CaseFinished(TestOutcome, Option<Duration>),
+}
+
+impl<'a> XctestEvent<'a> {
+    /// Parse a line of XCTest (e.g., `Test Case '-[AppTests.ModelTests
+    /// testTotal]' passed (0.001 seconds).`).
+    fn parse(line: &'a str) -> Option<Self> {
+        if let Some(rest) = line.strip_prefix("Test Suite '") {
+            let (name, status) = rest.split_once("' ")?;
+            return if status.starts_with("started") {
+                Some(Self::SuiteStarted(name))
+            } else if status.starts_with("passed") || status.starts_with("failed") {
+                Some(Self::SuiteFinished)
+            } else {
+                None
+            };
+        }
+        let (reference, status) = line.strip_prefix("Test Case '")?.split_once("' ")?;
+        let outcome = if status.starts_with("started") {
+            return Some(Self::CaseStarted(reference));
+        } else if status.starts_with("passed") {
+            TestOutcome::Passed
+        } else if status.starts_with("failed") {
+            TestOutcome::Failed
+        } else if status.starts_with("skipped") {
+            TestOutcome::Skipped
+        } else {
+            return None;
+        };
+        Some(Self::CaseFinished(outcome, parse_seconds(status)))
+    }
+}
+
+/// A test being run.
+#[derive(Debug, Clone)]
+struct OpenCase {
+    /// The name of the test as printed by XCTest (e.g.,
+    /// `-[AppTests.ModelTests testTotal]`), which prefixes its failures.
+    reference: String,
+    /// The result of the test read so far.
+    case: TestCase,
+    /// The failures of the test, with their locations.
+    failures: Vec<String>,
+}
+
+impl OpenCase {
+    /// Record a line printed while the test runs: a failure or the reason it
+    /// was skipped (e.g., `/app/AppTests/ModelTests.swift:18: error:
+    /// -[AppTests.ModelTests testTotal] : XCTAssertEqual failed`), or output
+    /// of the test.
+    fn record(&mut self, line: &str) {
+        let Some((head, message)) = line.split_once(&format!("{} : ", self.reference)) else {
+            let output = self.case.output.get_or_insert_default();
+            output.push_str(line);
+            output.push('\n');
+            return;
+        };
+        let is_failure = head.trim_end().ends_with("error:");
+        let location = head
+            .trim_end()
+            .trim_end_matches("error:")
+            .trim_end()
+            .strip_suffix(':')
+            .and_then(parse_location);
+        if !is_failure {
+            // The reason the test was skipped
+            let reason = message
+                .strip_prefix("Test skipped")
+                .unwrap_or(message)
+                .trim_start_matches([' ', '-'])
+                .trim();
+            self.case.message = (!reason.is_empty()).then(|| reason.to_owned());
+            return;
+        }
+        if self.failures.is_empty() {
+            self.case.message = Some(message.trim().to_owned());
+            self.case.file = location.map(|(file, _, _)| file.to_owned());
+            self.case.line = location.map(|(_, line_number, _)| line_number);
+        }
+        self.failures.push(match location {
+            Some((file, line_number, _)) => format!("{file}:{line_number}: {}", message.trim()),
+            None => message.trim().to_owned(),
+        });
+    }
+
+    /// Finish the test with its outcome.
+    fn finish(
+        mut self,
+        outcome: TestOutcome,
+        duration: Option<Duration>,
+        policy: OutputPolicy,
+    ) -> TestCase {
+        self.case.outcome = outcome;
+        self.case.duration = duration;
+        // The details only add to the message when the test failed more than
+        // once
+        if self.failures.len() > 1 {
+            self.case.details = Some(self.failures.join("\n"));
+        }
+        self.case.output = self
+            .case
+            .output
+            .map(|output| output.trim_end().to_owned())
+            .filter(|output| !output.is_empty());
+        policy.apply(&mut self.case.output, outcome == TestOutcome::Failed);
+        self.case
+    }
+}
+
+/// A test class (or bundle) being run.
+#[derive(Debug, Clone)]
+struct OpenSuite {
+    /// The name of the suite.
+    name: String,
+    /// The tally of the tests which the suite contains directly.
+    result: SuiteResult,
+}
+
+/// A value of a result bundle (e.g., `{"_value": "App"}`).
+#[derive(Debug, Deserialize)]
+struct Value {
+    /// The value.
+    #[serde(rename = "_value")]
+    value: String,
+}
+
+/// An array of a result bundle (e.g., `{"_values": [...]}`).
+#[derive(Debug, Deserialize)]
+struct Values<T> {
+    /// The values.
+    #[serde(rename = "_values", default = "Vec::new")]
+    values: Vec<T>,
+}
+
+/// The location of an issue of a result bundle.
+#[derive(Debug, Deserialize)]
+struct DocumentLocation {
+    /// The URL of the file, whose fragment gives the lines and columns (e.g.,
+    /// `file:///app/App/Model.swift#EndingLineNumber=11&StartingLineNumber=11`).
+    url: Value,
+}
+
+/// An issue of a result bundle.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueSummary {
+    /// The kind of the issue (e.g., `Swift Compiler Error`).
+    issue_type: Option<Value>,
+    /// The message of the issue.
+    message: Value,
+    /// The location of the issue, if known.
+    document_location_in_creating_workspace: Option<DocumentLocation>,
+    /// The target whose build or tests reported the issue, if known.
+    producing_target: Option<Value>,
+    /// The test which failed (e.g., `ModelTests.testTotal()`), for a test
+    /// failure.
+    test_case_name: Option<Value>,
+}
+
+/// The issues of a result bundle.
+#[derive(Debug, Default, Deserialize)]
+struct ResultIssues {
+    /// The errors of the build.
+    #[serde(rename = "errorSummaries")]
+    errors: Option<Values<IssueSummary>>,
+    /// The warnings of the build.
+    #[serde(rename = "warningSummaries")]
+    warnings: Option<Values<IssueSummary>>,
+    /// The failures of the tests.
+    #[serde(rename = "testFailureSummaries")]
+    test_failures: Option<Values<IssueSummary>>,
+}
+
+/// The summary of a result bundle.
+#[derive(Debug, Deserialize)]
+struct ActionsInvocationRecord {
+    /// The issues of the actions.
+    #[serde(default)]
+    issues: ResultIssues,
+}
+
+/// Decode the percent-encoded characters of a path (e.g., `%20`).
+pub(super) fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0_usize;
+    while let Some(&byte) = bytes.get(index) {
+        let code = bytes
+            .get(index.saturating_add(1)..index.saturating_add(3))
+            .filter(|_| byte == b'%')
+            .and_then(|hex| u8::from_str_radix(core::str::from_utf8(hex).ok()?, 16).ok());
+        decoded.push(code.unwrap_or(byte));
+        index = index.saturating_add(if code.is_some() { 3 } else { 1 });
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parse the URL of the location of an issue into its file, line and
+/// column, which are counted from zero in the fragment.
+fn parse_document_url(url: &str) -> Option<(String, Option<u32>, Option<u32>)> {
+    let (path, fragment) = url
+        .strip_prefix("file://")?
+        .split_once('#')
+        .unwrap_or((url.strip_prefix("file://")?, ""));
+    let parameter = |name: &str| {
+        fragment
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .and_then(|value| value.parse::<u32>().ok())
+            .map(|value| value.saturating_add(1))
+    };
+    Some((
+        percent_decode(path),
+        parameter("StartingLineNumber"),
+        parameter("StartingColumnNumber"),
+    ))
+}
+
+impl IssueSummary {
+    /// Convert an error or warning of the build.
+    fn into_diagnostic(self, level: XcodeLevel) -> XcodeDiagnostic {
+        let location = self
+            .document_location_in_creating_workspace
+            .and_then(|location| parse_document_url(&location.url.value));
+        let (file, line, column) = match location {
+            Some((file, line, column)) => (Some(file), line, column),
+            None => (None, None, None),
+        };
+        XcodeDiagnostic {
+            level,
+            message: self.message.value,
+            file,
+            line,
+            column,
+            origin: self.issue_type.map(|issue_type| issue_type.value),
+            target: self.producing_target.map(|target| target.value),
+        }
+    }
+
+    /// Convert a failure of a test.
+    fn into_test_case(self) -> TestCase {
+        let test = self
+            .test_case_name
+            .map(|name| name.value.trim_end_matches("()").to_owned())
+            .unwrap_or_default();
+        let name = match &self.producing_target {
+            Some(target) if !test.starts_with(&format!("{}.", target.value)) => {
+                format!("{}.{test}", target.value)
+            }
+            Some(_) | None => test,
+        };
+        let location = self
+            .document_location_in_creating_workspace
+            .and_then(|location| parse_document_url(&location.url.value));
+        TestCase {
+            name,
+            outcome: TestOutcome::Failed,
+            message: Some(self.message.value),
+            details: None,
+            output: None,
+            duration: None,
+            file: location.as_ref().map(|(file, _, _)| file.clone()),
+            line: location.and_then(|(_, line, _)| line),
+        }
+    }
+}
+
+impl From<ActionsInvocationRecord> for Vec<XcodebuildMessage> {
+    #[inline]
+    fn from(record: ActionsInvocationRecord) -> Self {
+        let ResultIssues {
+            errors,
+            warnings,
+            test_failures,
+        } = record.issues;
+        let issues = |summaries: Option<Values<IssueSummary>>| {
+            summaries.map(|list| list.values).unwrap_or_default()
+        };
+        issues(errors)
+            .into_iter()
+            .map(|issue| {
+                XcodebuildMessage::Diagnostic(Box::new(issue.into_diagnostic(XcodeLevel::Error)))
+            })
+            .chain(issues(warnings).into_iter().map(|issue| {
+                XcodebuildMessage::Diagnostic(Box::new(issue.into_diagnostic(XcodeLevel::Warning)))
+            }))
+            .chain(issues(test_failures).into_iter().map(|issue| {
+                XcodebuildMessage::Test(JunitMessage::TestCase(issue.into_test_case()))
+            }))
+            .collect()
+    }
+}
+
+/// The state of the parser.
+#[derive(Debug, Clone, Default)]
+struct Reader {
+    /// The target whose group is open, if any.
+    target: Option<String>,
+    /// The test classes (and bundles) being run, innermost last.
+    suites: Vec<OpenSuite>,
+    /// The test being run, if any.
+    case: Option<OpenCase>,
+    /// The test class which ended, reported with the time of the class given
+    /// by the next line (e.g., `Executed 3 tests, with 1 failure (0
+    /// unexpected) in 0.005 (0.007) seconds`).
+    ended: Option<TestSuite>,
+    /// The lines of the summary of a result bundle read so far.
+    document: Vec<u8>,
+    /// The line number and offset of the first line of the summary, if one
+    /// is being read.
+    start: Option<(usize, u64)>,
+    /// The policy for the inclusion of the output of tests.
+    policy: OutputPolicy,
+}
+
+impl Reader {
+    /// Close the group of the open target, if any.
+    fn close_target(&mut self) -> Option<XcodebuildMessage> {
+        self.target
+            .take()
+            .map(|target| XcodebuildMessage::TargetFinished(TargetFinished { target }))
+    }
+
+    /// Move to a target, closing the group of the previous target and opening
+    /// the group of the new one, unless the target is already open.
+    fn switch_target(&mut self, target: &str) -> Vec<XcodebuildMessage> {
+        if self.target.as_deref() == Some(target) {
+            return Vec::new();
+        }
+        let mut results: Vec<_> = self.close_target().into_iter().collect();
+        self.target = Some(target.to_owned());
+        results.push(XcodebuildMessage::TargetStarted(TargetStarted {
+            target: target.to_owned(),
+        }));
+        results
+    }
+
+    /// Handle an event of XCTest.
+    fn handle_test(&mut self, event: XctestEvent<'_>) -> Vec<XcodebuildMessage> {
+        let mut results = Vec::new();
+        match event {
+            XctestEvent::SuiteStarted(name) => self.suites.push(OpenSuite {
+                name: name.to_owned(),
+                result: SuiteResult::new(0, 0, 0),
+            }),
+            XctestEvent::SuiteFinished => {
+                results.extend(self.finish_case(TestOutcome::Failed, None));
+                // Only the classes, which contain tests directly, are
+                // reported, rather than the bundles and the whole run
+                self.ended = self
+                    .suites
+                    .pop()
+                    .filter(|suite| {
+                        let result = suite.result;
+                        result
+                            .passed
+                            .saturating_add(result.failed)
+                            .saturating_add(result.ignored)
+                            > 0
+                    })
+                    .map(|suite| TestSuite {
+                        name: suite.name,
+                        result: suite.result,
+                    });
+            }
+            XctestEvent::CaseStarted(reference) => {
+                results.extend(self.finish_case(TestOutcome::Failed, None));
+                self.case = Some(OpenCase {
+                    reference: reference.to_owned(),
+                    case: TestCase {
+                        name: qualified_name(reference),
+                        outcome: TestOutcome::Passed,
+                        message: None,
+                        details: None,
+                        output: None,
+                        duration: None,
+                        file: None,
+                        line: None,
+                    },
+                    failures: Vec::new(),
+                });
+            }
+            XctestEvent::CaseFinished(outcome, duration) => {
+                results.extend(self.finish_case(outcome, duration));
+            }
+        }
+        results
+    }
+
+    /// Report the test being run, if any, tallying it in its class.
+    fn finish_case(
+        &mut self,
+        outcome: TestOutcome,
+        duration: Option<Duration>,
+    ) -> Option<XcodebuildMessage> {
+        let case = self.case.take()?.finish(outcome, duration, self.policy);
+        if let Some(suite) = self.suites.last_mut() {
+            let tally = match case.outcome {
+                TestOutcome::Passed => &mut suite.result.passed,
+                TestOutcome::Failed => &mut suite.result.failed,
+                TestOutcome::Skipped => &mut suite.result.ignored,
+            };
+            *tally = tally.saturating_add(1);
+        }
+        Some(XcodebuildMessage::Test(JunitMessage::TestCase(case)))
+    }
+
+    /// Parse the summary of a result bundle read so far.
+    fn parse_document(&mut self) -> Result<Vec<XcodebuildMessage>, serde_json::Error> {
+        self.start = None;
+        serde_json::from_slice::<ActionsInvocationRecord>(&mem::take(&mut self.document))
+            .map(Vec::from)
+    }
+
+    /// Parse a single line of the output of xcodebuild.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The line number and offset of the line.
+    /// * `next` - The line.
+    /// * `max_length` - The maximum length of a line.
+    /// * `text` - The handling of the lines outside of the diagnostics and
+    ///   tests.
+    ///
+    /// # Returns
+    ///
+    /// The messages completed by the line.
+    fn parse_line(
+        &mut self,
+        position: (usize, u64),
+        next: Line<'_>,
+        max_length: usize,
+        text: &mut TextLines,
+    ) -> Vec<Result<XcodebuildMessage, serde_json::Error>> {
+        let line = match next {
+            Line::Complete(line) => line,
+            Line::Overlong(line) => {
+                // A summary cannot be parsed without the whole line
+                self.start = None;
+                self.document.clear();
+                let mut results = vec![Ok(XcodebuildMessage::OverlongLine(OverlongLine {
+                    max_length,
+                }))];
+                results.extend(self.parse_line(position, Line::Complete(line), max_length, text));
+                return results;
+            }
+        };
+
+        if self.start.is_some() || (line == b"{" && self.case.is_none()) {
+            self.start.get_or_insert(position);
+            self.document.extend_from_slice(line);
+            self.document.push(b'\n');
+            if line == b"}" {
+                return match self.parse_document() {
+                    Ok(messages) => messages.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+            }
+            return Vec::new();
+        }
+
+        let content = String::from_utf8_lossy(line);
+        let mut results = Vec::new();
+        if let Some(mut ended) = self.ended.take() {
+            // e.g., `Executed 3 tests, with 1 failure (0 unexpected) in 0.005
+            // (0.007) seconds`
+            if let Some((_, time)) = content
+                .trim()
+                .strip_prefix("Executed ")
+                .and_then(|rest| rest.rsplit_once(" in "))
+            {
+                ended.result.duration = time.split_once(' ').and_then(|(seconds, _)| {
+                    Duration::try_from_secs_f64(seconds.parse().ok()?).ok()
+                });
+                return vec![Ok(XcodebuildMessage::Test(JunitMessage::TestSuite(ended)))];
+            }
+            results.push(XcodebuildMessage::Test(JunitMessage::TestSuite(ended)));
+        }
+        results.extend(self.parse_content(&content, text));
+        results.into_iter().map(Ok).collect()
+    }
+
+    /// Parse a line which is not part of the summary of a result bundle.
+    fn parse_content(&mut self, content: &str, text: &mut TextLines) -> Vec<XcodebuildMessage> {
+        if let Some(event) = XctestEvent::parse(content) {
+            let mut results: Vec<_> = self.close_target().into_iter().collect();
+            results.extend(self.handle_test(event));
+            return results;
+        }
+        if let Some(open) = &mut self.case {
+            open.record(content);
+            return Vec::new();
+        }
+        if let Some(diagnostic) = parse_diagnostic(content) {
+            return vec![XcodebuildMessage::Diagnostic(Box::new(XcodeDiagnostic {
+                target: diagnostic.target.or_else(|| self.target.clone()),
+                ..diagnostic
+            }))];
+        }
+
+        let mut results = Vec::new();
+        if let Some(target) = split_target(content).1.or_else(|| parse_banner(content)) {
+            results.extend(self.switch_target(target));
+        } else if content.starts_with("** ") || content.starts_with("Testing started") {
+            // The end of the build (e.g., `** BUILD FAILED **`)
+            results.extend(self.close_target());
+        }
+        results.extend(
+            text.handle(content.as_bytes())
+                .map(XcodebuildMessage::TextOutput),
+        );
+        results
+    }
+
+    /// Report the summary being read, the test being run and the open
+    /// group at the end of the output, if any.
+    fn finish(&mut self) -> Vec<Result<XcodebuildMessage, serde_json::Error>> {
+        let mut results = Vec::new();
+        if let Some((line_number, offset)) = self.start {
+            // The last line may end within a string, which cannot contain the
+            // newline which ends it
+            if self.document.last() == Some(&b'\n') {
+                self.document.pop();
+            }
+            let length = self.document.len();
+            match self.parse_document() {
+                Ok(messages) => results.extend(messages.into_iter().map(Ok)),
+                Err(error) if error.is_eof() => {
+                    results.push(Ok(XcodebuildMessage::TruncatedLine(TruncatedLine {
+                        line_number,
+                        offset,
+                        length,
+                        error: error.to_string(),
+                    })));
+                }
+                Err(error) => results.push(Err(error)),
+            }
+        }
+        if let Some(suite) = self.ended.take() {
+            results.push(Ok(XcodebuildMessage::Test(JunitMessage::TestSuite(suite))));
+        }
+        let pending = self.case.take().map(|open| open.case.name);
+        let suite = self.suites.pop().map(|open| open.name);
+        self.suites.clear();
+        if suite.is_some() || pending.is_some() {
+            results.push(Ok(XcodebuildMessage::Test(JunitMessage::IncompleteReport(
+                IncompleteReport { suite, pending },
+            ))));
+        }
+        results.extend(self.close_target().map(Ok));
+        results
+    }
+}
+
+/// Tool implementation for parsing the output of xcodebuild.
+#[derive(Debug, Clone, Default)]
+pub struct Xcodebuild {
+    /// Framer for splitting the output into lines.
+    framer: LineFramer,
+    /// Handling of lines which are not part of a diagnostic or of the tests.
+    text: TextLines,
+    /// The state of the parser.
+    reader: Reader,
+}
+
+impl Detect for Xcodebuild {
+    type Tool = Self;
+
+    #[inline]
+    fn detect(sample: &[u8]) -> Option<Self::Tool> {
+        let find = |needle: &[u8]| memchr::memmem::find(sample, needle).is_some();
+        // The targets named by the steps of the build, the summaries of the
+        // actions, the tests of XCTest or the summary of a result bundle
+        (find(b" (in target '")
+            || find(b"=== BUILD TARGET ")
+            || find(b"** BUILD SUCCEEDED **")
+            || find(b"** BUILD FAILED **")
+            || find(b"** TEST SUCCEEDED **")
+            || find(b"** TEST FAILED **")
+            || find(b"Test Case '-[")
+            || find(b"\"ActionsInvocationRecord\""))
+        .then(Self::default)
+    }
+}
+
+impl Xcodebuild {
+    /// A problem matcher for the output of xcodebuild.
+    ///
+    /// Each diagnostic of the compilers (e.g., `/app/App/Model.swift:12:5:
+    /// error: cannot find 'total' in scope`) and each failure of XCTest
+    /// (e.g., `/app/AppTests/ModelTests.swift:18: error:
+    /// -[AppTests.ModelTests testTotal] : XCTAssertEqual failed`) gives its
+    /// location, level and message.
+    #[must_use]
+    #[inline]
+    pub fn problem_matcher() -> ProblemMatcher {
+        ProblemMatcher::new(
+            "cifmt-xcodebuild",
+            vec![
+                Pattern::new(r"^(.+?):(\d+):(?:(\d+):)? (error|warning): (.+)$")
+                    .file(1)
+                    .line(2)
+                    .column(3)
+                    .severity(4)
+                    .message(5),
+            ],
+        )
+    }
+}
+
+impl Tool for Xcodebuild {
+    type Message = XcodebuildMessage;
+    type Error = serde_json::Error;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "xcodebuild"
+    }
+
+    #[inline]
+    fn parse(&mut self, buf: &[u8]) -> Vec<Result<Self::Message, Self::Error>> {
+        self.framer.push(buf);
+
+        let max_length = self.framer.max_line_length();
+        let mut results = Vec::new();
+        loop {
+            let position = self.framer.position();
+            let Some(line) = self.framer.next_line() else {
+                break;
+            };
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+        results
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Vec<Result<Self::Message, Self::Error>> {
+        let max_length = self.framer.max_line_length();
+        let position = self.framer.position();
+        let mut results = Vec::new();
+        if let Some(line) = self.framer.finish() {
+            results.extend(
+                self.reader
+                    .parse_line(position, line, max_length, &mut self.text),
+            );
+        }
+
+        results.extend(self.reader.finish());
+        results.extend(
+            self.text
+                .finish()
+                .map(|output| Ok(XcodebuildMessage::TextOutput(output))),
+        );
+        results
+    }
+
+    #[inline]
+    fn set_max_line_length(&mut self, max: usize) {
+        self.framer.set_max_line_length(max);
+    }
+
+    #[inline]
+    fn set_passthrough(&mut self, policy: Passthrough) {
+        self.text.set_policy(policy);
+    }
+
+    #[inline]
+    fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.reader.policy = policy;
+    }
+}
+
+impl<P: Platform> DynTool<P> for Xcodebuild
+where
+    XcodebuildMessage: CiMessage<P>,
+{
+    #[inline]
+    fn name(&self) -> &'static str {
+        Tool::name(self)
+    }
+
+    #[inline]
+    fn parse_and_format(&mut self, buf: &[u8]) -> Vec<String> {
+        self.parse(buf)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+
+    #[inline]
+    fn finish_and_format(&mut self) -> Vec<String> {
+        self.finish()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|msg| msg.format())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Xcodebuild, XcodebuildMessage, parse_diagnostic, parse_document_url};
+    use crate::capture::OutputPolicy;
+    use crate::ci::{GitHub, Plain, Platform};
+    use crate::ci_message::{CiMessage, Classify};
+    use crate::junit::TestOutcome;
+    use crate::suites::SuiteResult;
+    use crate::tool::junit::JunitMessage;
+    use crate::tool::{self, Detect as _, Tool as _};
+
+    const OUTPUT: &str = "Command line invocation:
+    /Applications/Xcode.app/Contents/Developer/usr/bin/xcodebuild test -scheme App
+
+CompileSwift normal arm64 /app/App/Model.swift (in target 'App' from project 'App')
+    cd /app
+/app/App/Model.swift:12:13: warning: initialization of immutable value 'unused' was never used; consider replacing with assignment to '_' or removing it
+        let unused = 42
+            ^~~~~~
+Ld /build/Debug/App.app/App normal (in target 'App' from project 'App')
+    cd /app
+CompileSwift normal arm64 /app/AppTests/ModelTests.swift (in target 'AppTests' from project 'App')
+    cd /app
+Testing started
+Test Suite 'All tests' started at 2024-05-01 10:00:00.000.
+Test Suite 'AppTests.xctest' started at 2024-05-01 10:00:00.001.
+Test Suite 'ModelTests' started at 2024-05-01 10:00:00.001.
+Test Case '-[AppTests.ModelTests testAdd]' started.
+Test Case '-[AppTests.ModelTests testAdd]' passed (0.001 seconds).
+Test Case '-[AppTests.ModelTests testTotal]' started.
+Computing the total of 2 items
+/app/AppTests/ModelTests.swift:18: error: -[AppTests.ModelTests testTotal] : XCTAssertEqual failed: (\"3\") is not equal to (\"4\")
+/app/AppTests/ModelTests.swift:19: error: -[AppTests.ModelTests testTotal] : XCTAssertTrue failed
+Test Case '-[AppTests.ModelTests testTotal]' failed (0.002 seconds).
+Test Case '-[AppTests.ModelTests testRemote]' started.
+/app/AppTests/ModelTests.swift:25: -[AppTests.ModelTests testRemote] : Test skipped - Requires network access
+Test Case '-[AppTests.ModelTests testRemote]' skipped (0.000 seconds).
+Test Suite 'ModelTests' failed at 2024-05-01 10:00:00.005.
+\t Executed 3 tests, with 1 test skipped and 2 failures (0 unexpected) in 0.003 (0.004) seconds
+Test Suite 'AppTests.xctest' failed at 2024-05-01 10:00:00.005.
+\t Executed 3 tests, with 1 test skipped and 2 failures (0 unexpected) in 0.003 (0.004) seconds
+Test Suite 'All tests' failed at 2024-05-01 10:00:00.006.
+\t Executed 3 tests, with 1 test skipped and 2 failures (0 unexpected) in 0.003 (0.005) seconds
+
+** TEST FAILED **
+";
+
+    const RESULT: &str = r#"{
+  "_type" : {
+    "_name" : "ActionsInvocationRecord"
+  },
+  "issues" : {
+    "_type" : {
+      "_name" : "ResultIssueSummaries"
+    },
+    "errorSummaries" : {
+      "_type" : {
+        "_name" : "Array"
+      },
+      "_values" : [
+        {
+          "_type" : {
+            "_name" : "IssueSummary"
+          },
+          "documentLocationInCreatingWorkspace" : {
+            "_type" : {
+              "_name" : "DocumentLocation"
+            },
+            "concreteTypeName" : {
+              "_type" : {
+                "_name" : "String"
+              },
+              "_value" : "DVTTextDocumentLocation"
+            },
+            "url" : {
+              "_type" : {
+                "_name" : "String"
+              },
+              "_value" : "file:///app/App/Shopping%20Cart.swift#EndingColumnNumber=10&EndingLineNumber=7&StartingColumnNumber=4&StartingLineNumber=7&Timestamp=736243200.0"
+            }
+          },
+          "issueType" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "Swift Compiler Error"
+          },
+          "message" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "Cannot find 'total' in scope"
+          },
+          "producingTarget" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "App"
+          }
+        }
+      ]
+    },
+    "testFailureSummaries" : {
+      "_type" : {
+        "_name" : "Array"
+      },
+      "_values" : [
+        {
+          "_type" : {
+            "_name" : "TestFailureIssueSummary"
+          },
+          "documentLocationInCreatingWorkspace" : {
+            "_type" : {
+              "_name" : "DocumentLocation"
+            },
+            "url" : {
+              "_type" : {
+                "_name" : "String"
+              },
+              "_value" : "file:///app/AppTests/ModelTests.swift#EndingLineNumber=17&StartingLineNumber=17"
+            }
+          },
+          "issueType" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "Uncategorized"
+          },
+          "message" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "XCTAssertEqual failed: (\"3\") is not equal to (\"4\")"
+          },
+          "producingTarget" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "AppTests"
+          },
+          "testCaseName" : {
+            "_type" : {
+              "_name" : "String"
+            },
+            "_value" : "ModelTests.testTotal()"
+          }
+        }
+      ]
+    }
+  }
+}
+"#;
+
+    /// Parse the whole output, fed in chunks of the given size.
+    fn parse(output: &str, chunk: usize) -> Vec<XcodebuildMessage> {
+        let mut tool = Xcodebuild::default();
+        let mut messages = Vec::new();
+        for bytes in output.as_bytes().chunks(chunk) {
+            messages.extend(tool.parse(bytes));
+        }
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| msg.expect("valid message"))
+            .collect()
+    }
+
+    /// Parse the whole output, formatting each message for the platform.
+    fn format<P: Platform>(output: &str) -> String
+    where
+        XcodebuildMessage: CiMessage<P>,
+    {
+        let mut tool = Xcodebuild::default();
+        let mut messages = tool.parse(output.as_bytes());
+        messages.extend(tool.finish());
+        messages
+            .into_iter()
+            .map(|msg| <XcodebuildMessage as CiMessage<P>>::format(&msg.expect("valid message")))
+            .filter(|formatted| !formatted.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(format::<GitHub>(OUTPUT), @r#"
+        ::group::Target: App
+        ::warning file=/app/App/Model.swift,line=12,col=13::initialization of immutable value 'unused' was never used; consider replacing with assignment to '_' or removing it
+        ::endgroup::
+        ::group::Target: AppTests
+        ::endgroup::
+        ::notice title=Test Passed%3A AppTests.ModelTests.testAdd::Executed in 0.00s
+        ::error file=/app/AppTests/ModelTests.swift,line=18,title=Test Failed%3A AppTests.ModelTests.testTotal::XCTAssertEqual failed: ("3") is not equal to ("4")
+        ::group::Test: AppTests.ModelTests.testTotal
+        /app/AppTests/ModelTests.swift:18: XCTAssertEqual failed: ("3") is not equal to ("4")
+        /app/AppTests/ModelTests.swift:19: XCTAssertTrue failed
+        Computing the total of 2 items
+        ::endgroup::
+        ::debug::Test Ignored: AppTests.ModelTests.testRemote: Requires network access
+        ::error title=Test Suite Failed%3A ModelTests::1 passed, 1 failed, 1 ignored in 0.00s
+        "#);
+    }
+
+    #[test]
+    fn plain() {
+        insta::assert_snapshot!(format::<Plain>(OUTPUT), @r#"
+        Target: App
+        /app/App/Model.swift:12:13: warning: initialization of immutable value 'unused' was never used; consider replacing with assignment to '_' or removing it
+        Target: AppTests
+        notice:  Test Passed: AppTests.ModelTests.testAdd: Executed in 0.00s
+        /app/AppTests/ModelTests.swift:18: error:   Test Failed: AppTests.ModelTests.testTotal: XCTAssertEqual failed: ("3") is not equal to ("4")
+        Test: AppTests.ModelTests.testTotal
+        /app/AppTests/ModelTests.swift:18: XCTAssertEqual failed: ("3") is not equal to ("4")
+        /app/AppTests/ModelTests.swift:19: XCTAssertTrue failed
+        Computing the total of 2 items
+        debug:   Test Ignored: AppTests.ModelTests.testRemote: Requires network access
+        error:   Test Suite Failed: ModelTests: 1 passed, 1 failed, 1 ignored in 0.00s
+        "#);
+    }
+
+    #[test]
+    fn tests() {
+        let messages = parse(OUTPUT, 11);
+        assert_eq!(messages, parse(OUTPUT, OUTPUT.len()));
+        let results: Vec<_> = messages
+            .iter()
+            .filter_map(Classify::test_result)
+            .map(|result| (result.name, result.outcome))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                ("AppTests.ModelTests.testAdd", TestOutcome::Passed),
+                ("AppTests.ModelTests.testTotal", TestOutcome::Failed),
+                ("AppTests.ModelTests.testRemote", TestOutcome::Skipped),
+            ]
+        );
+        let suites: Vec<_> = messages.iter().filter_map(Classify::suite_result).collect();
+        let mut expected = SuiteResult::new(1, 1, 1);
+        expected.duration = Some(Duration::from_millis(3));
+        assert_eq!(suites, [expected]);
+
+        let Some(XcodebuildMessage::Test(JunitMessage::TestCase(failed))) =
+            messages.iter().find(|msg| msg.is_test_failure())
+        else {
+            panic!("expected a failed test");
+        };
+        assert_eq!(
+            failed.file.as_deref(),
+            Some("/app/AppTests/ModelTests.swift")
+        );
+        assert_eq!(failed.line, Some(18));
+        assert_eq!(
+            failed.output.as_deref(),
+            Some("Computing the total of 2 items")
+        );
+        assert_eq!(
+            failed.details.as_deref(),
+            Some(
+                "/app/AppTests/ModelTests.swift:18: XCTAssertEqual failed: (\"3\") is not equal to (\"4\")\n/app/AppTests/ModelTests.swift:19: XCTAssertTrue failed"
+            )
+        );
+    }
+
+    #[test]
+    fn output_policy() {
+        let mut tool = Xcodebuild::default();
+        tool.set_output_policy(OutputPolicy::Never);
+        let mut results = tool.parse(OUTPUT.as_bytes());
+        results.extend(tool.finish());
+        let messages: Vec<_> = results.into_iter().filter_map(Result::ok).collect();
+        let outputs: Vec<_> = messages
+            .iter()
+            .filter_map(Classify::test_result)
+            .map(|result| result.output)
+            .collect();
+        assert_eq!(outputs, [None, None, None]);
+    }
+
+    #[test]
+    fn targets() {
+        let output = "=== BUILD TARGET Core OF PROJECT App WITH CONFIGURATION Debug ===
+/app/Core/Core.m:3:1: error: expected identifier or '('
+=== BUILD TARGET App OF PROJECT App WITH CONFIGURATION Debug ===
+ld: warning: directory not found for option '-L/app/Vendor'
+error: Signing for \"App\" requires a development team. (in target 'App' from project 'App')
+** BUILD FAILED **
+";
+        let messages = parse(output, output.len());
+        let packages: Vec<_> = messages
+            .iter()
+            .filter(|msg| matches!(msg, XcodebuildMessage::Diagnostic(_)))
+            .map(Classify::package)
+            .collect();
+        assert_eq!(packages, [Some("Core"), Some("App"), Some("App")]);
+        insta::assert_snapshot!(format::<GitHub>(output), @r#"
+        ::group::Target: Core
+        ::error file=/app/Core/Core.m,line=3,col=1::expected identifier or '('
+        ::endgroup::
+        ::group::Target: App
+        ::warning title=ld::directory not found for option '-L/app/Vendor'
+        ::error ::Signing for "App" requires a development team.
+        ::endgroup::
+        "#);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let diagnostic = parse_diagnostic("/app/App/View.swift:4:1: error: expected '}' in struct")
+            .expect("diagnostic not parsed");
+        assert_eq!(diagnostic.file.as_deref(), Some("/app/App/View.swift"));
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(4), Some(1)));
+        assert_eq!(diagnostic.message, "expected '}' in struct");
+        assert!(parse_diagnostic("Running the tests: no error: none").is_none());
+        assert!(parse_diagnostic("    cd /app").is_none());
+    }
+
+    #[test]
+    fn result_bundle() {
+        insta::assert_snapshot!(format::<GitHub>(&format!("Fetching the results\n{RESULT}")), @r#"
+        ::error file=/app/App/Shopping Cart.swift,line=8,col=5,title=Swift Compiler Error::Cannot find 'total' in scope
+        ::error file=/app/AppTests/ModelTests.swift,line=18,title=Test Failed%3A AppTests.ModelTests.testTotal::XCTAssertEqual failed: ("3") is not equal to ("4")
+        "#);
+        assert_eq!(
+            parse_document_url("file:///app/A%20B.swift#StartingLineNumber=0"),
+            Some((String::from("/app/A B.swift"), Some(1), None))
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let cut = RESULT.get(..400).expect("valid index");
+        insta::assert_snapshot!(format::<Plain>(cut), @"warning: Truncated Output: Output ended within line 1 (at byte offset 0), discarding 400 bytes of an incomplete message: EOF while parsing an object at line 20 column 6");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Xcodebuild::detect(OUTPUT.as_bytes()).is_some());
+        assert!(Xcodebuild::detect(RESULT.as_bytes()).is_some());
+        assert!(Xcodebuild::detect(b"src/main.c:3:1: error: expected ';'\n").is_none());
+        // The diagnostics of the compilers are also those of GCC and Clang
+        let detected = tool::detect_any(OUTPUT.as_bytes()).expect("tool not detected");
+        assert_eq!(detected.name(), "xcodebuild");
+    }
+
+    #[test]
+    fn problem_matcher() {
+        let matcher = Xcodebuild::problem_matcher();
+        let [pattern] = matcher.pattern.as_slice() else {
+            panic!("expected one pattern");
+        };
+        let regex = regex::Regex::new(&pattern.regexp).expect("invalid regexp");
+
+        let diagnostic = regex
+            .captures("/app/App/Model.swift:12:13: warning: initialization of immutable value 'unused' was never used")
+            .expect("diagnostic not matched");
+        assert_eq!(
+            diagnostic.get(1).map(|m| m.as_str()),
+            Some("/app/App/Model.swift")
+        );
+        assert_eq!(diagnostic.get(3).map(|m| m.as_str()), Some("13"));
+        assert_eq!(diagnostic.get(4).map(|m| m.as_str()), Some("warning"));
+        let failure = regex
+            .captures("/app/AppTests/ModelTests.swift:18: error: -[AppTests.ModelTests testTotal] : XCTAssertEqual failed")
+            .expect("failure not matched");
+        assert_eq!(failure.get(2).map(|m| m.as_str()), Some("18"));
+        assert_eq!(failure.get(3), None);
+    }
+}
@@ -0,0 +1,301 @@
+//! A minimal, incremental XML reader.
+//!
+//! A report is read as it is received, so the reader is fed chunks of the
+//! document and yields the events (start tags, end tags and text) which they
+//! complete. Only what the reports of the tools use (e.g., JUnit reports, or
+//! the results of cppcheck) is supported: elements, attributes, text, CDATA
+//! sections, and the predefined and numeric character references.
+//! Comments, processing instructions (e.g., `<?xml version="1.0"?>`) and
+//! document type declarations are skipped, and the document is not validated.
+
+use memchr::{memchr, memmem};
+
+/// An event of an XML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The start tag of an element (e.g., `<testcase name="a">`), which is
+    /// also its end if the element is empty (e.g., `<skipped/>`).
+    Start(Element),
+    /// The end tag of an element (e.g., `</testcase>`), with its name.
+    End(String),
+    /// Text, with its character references resolved.
+    Text(String),
+}
+
+/// The start tag of an element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    /// The name of the element.
+    pub name: String,
+    /// The attributes of the element, in order, with their character
+    /// references resolved.
+    pub attributes: Vec<(String, String)>,
+    /// Whether the element is empty (e.g., `<skipped/>`), in which case no
+    /// end tag follows.
+    pub empty: bool,
+}
+
+impl Element {
+    /// The value of an attribute, if present.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reader of the events of an XML document, fed in chunks.
+#[derive(Debug, Clone, Default)]
+pub struct XmlReader {
+    /// The bytes received so far.
+    buffer: Vec<u8>,
+    /// The position in the buffer up to which events were read.
+    position: usize,
+}
+
+impl XmlReader {
+    /// Add a chunk of the document.
+    pub fn push(&mut self, buf: &[u8]) {
+        self.buffer.drain(..self.position);
+        self.position = 0;
+        self.buffer.extend_from_slice(buf);
+    }
+
+    /// Read the next event completed by the chunks received so far.
+    ///
+    /// # Returns
+    ///
+    /// The event, or `None` if more of the document is needed.
+    pub fn next_event(&mut self) -> Option<Event> {
+        loop {
+            let rest = self.buffer.get(self.position..).unwrap_or_default();
+            if rest.first() != Some(&b'<') {
+                let end = memchr(b'<', rest)?;
+                let text = rest.get(..end).unwrap_or_default();
+                self.position = self.position.saturating_add(end);
+                return Some(Event::Text(unescape(&String::from_utf8_lossy(text))));
+            }
+            let (length, event) = parse_markup(rest)?;
+            self.position = self.position.saturating_add(length);
+            if event.is_some() {
+                return event;
+            }
+        }
+    }
+
+    /// Finish reading the document.
+    ///
+    /// # Returns
+    ///
+    /// The text which followed the last tag, if any. A tag cut off before
+    /// its end is dropped.
+    pub fn finish(&mut self) -> Option<Event> {
+        let rest = self.buffer.get(self.position..).unwrap_or_default();
+        let event = (!rest.is_empty() && rest.first() != Some(&b'<'))
+            .then(|| Event::Text(unescape(&String::from_utf8_lossy(rest))));
+        self.buffer.clear();
+        self.position = 0;
+        event
+    }
+}
+
+/// Parse the markup at the start of the input (e.g., a tag or a comment).
+///
+/// # Returns
+///
+/// The length of the markup and the event it holds (`None` for skipped
+/// markup), or `None` if the markup is cut off.
+fn parse_markup(input: &[u8]) -> Option<(usize, Option<Event>)> {
+    if let Some(comment) = input.strip_prefix(b"<!--") {
+        let end = memmem::find(comment, b"-->")?;
+        return Some((end.saturating_add(7), None));
+    }
+    if let Some(cdata) = input.strip_prefix(b"<![CDATA[") {
+        let end = memmem::find(cdata, b"]]>")?;
+        let text = String::from_utf8_lossy(cdata.get(..end).unwrap_or_default()).into_owned();
+        return Some((end.saturating_add(12), Some(Event::Text(text))));
+    }
+    if input.starts_with(b"<?") {
+        let end = memmem::find(input, b"?>")?;
+        return Some((end.saturating_add(2), None));
+    }
+    if input.starts_with(b"<!") {
+        let end = memchr(b'>', input)?;
+        return Some((end.saturating_add(1), None));
+    }
+
+    let end = tag_end(input)?;
+    let tag = String::from_utf8_lossy(input.get(1..end).unwrap_or_default()).into_owned();
+    let event = match tag.strip_prefix('/') {
+        Some(name) => Event::End(name.trim().to_owned()),
+        None => Event::Start(parse_element(&tag)),
+    };
+    Some((end.saturating_add(1), Some(event)))
+}
+
+/// Find the end of the tag at the start of the input, ignoring a `>` within
+/// the value of an attribute.
+///
+/// # Returns
+///
+/// The position of the `>` ending the tag, or `None` if the tag is cut off.
+fn tag_end(input: &[u8]) -> Option<usize> {
+    let mut quote = None;
+    for (position, &byte) in input.iter().enumerate().skip(1) {
+        match quote {
+            Some(open) if byte == open => quote = None,
+            None if byte == b'"' || byte == b'\'' => quote = Some(byte),
+            None if byte == b'>' => return Some(position),
+            Some(_) | None => {}
+        }
+    }
+    None
+}
+
+/// Parse the content of a start tag (e.g., `testcase name="a"`).
+fn parse_element(tag: &str) -> Element {
+    let trimmed = tag.trim_end();
+    let (body, empty) = match trimmed.strip_suffix('/') {
+        Some(body) => (body, true),
+        None => (trimmed, false),
+    };
+    let (name, mut rest) = body.split_at(body.find(char::is_whitespace).unwrap_or(body.len()));
+
+    let mut attributes = Vec::new();
+    while let Some((key, assigned)) = rest.split_once('=') {
+        let value = assigned.trim_start();
+        let Some(quote) = value.chars().next().filter(|&c| c == '"' || c == '\'') else {
+            break;
+        };
+        let Some((quoted, after)) = value.get(1..).and_then(|inner| inner.split_once(quote)) else {
+            break;
+        };
+        attributes.push((key.trim().to_owned(), unescape(quoted)));
+        rest = after;
+    }
+
+    Element {
+        name: name.to_owned(),
+        attributes,
+        empty,
+    }
+}
+
+/// Resolve the character references of a text (e.g., `&lt;` or `&#10;`).
+///
+/// References which are not valid are left as they are.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        let (before, reference) = rest.split_at(start);
+        unescaped.push_str(before);
+        let resolved = reference.find(';').and_then(|end| {
+            let character = resolve(reference.get(1..end)?)?;
+            Some((character, end.saturating_add(1)))
+        });
+        let consumed = if let Some((character, length)) = resolved {
+            unescaped.push(character);
+            length
+        } else {
+            unescaped.push('&');
+            1
+        };
+        rest = reference.get(consumed..).unwrap_or_default();
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Resolve the name of a character reference (e.g., `lt` or `#x41`).
+fn resolve(name: &str) -> Option<char> {
+    match name {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Element, Event, XmlReader, unescape};
+
+    /// Read every event of a document, fed in chunks of the given size.
+    fn events(document: &str, chunk: usize) -> Vec<Event> {
+        let mut reader = XmlReader::default();
+        let mut events = Vec::new();
+        for bytes in document.as_bytes().chunks(chunk) {
+            reader.push(bytes);
+            while let Some(event) = reader.next_event() {
+                events.push(event);
+            }
+        }
+        events.extend(reader.finish());
+        events
+    }
+
+    #[test]
+    fn document() {
+        let document = "<?xml version=\"1.0\"?>\n<!-- a > b --><a x=\"1 > 0\" y='&lt;&#65;'><b/>t&amp;u<![CDATA[<c>]]></a>";
+        let expected = vec![
+            Event::Text("\n".to_owned()),
+            Event::Start(Element {
+                name: "a".to_owned(),
+                attributes: vec![
+                    ("x".to_owned(), "1 > 0".to_owned()),
+                    ("y".to_owned(), "<A".to_owned()),
+                ],
+                empty: false,
+            }),
+            Event::Start(Element {
+                name: "b".to_owned(),
+                attributes: Vec::new(),
+                empty: true,
+            }),
+            Event::Text("t&u".to_owned()),
+            Event::Text("<c>".to_owned()),
+            Event::End("a".to_owned()),
+        ];
+        for chunk in [1, 3, 7, document.len()] {
+            assert_eq!(events(document, chunk), expected, "chunks of {chunk}");
+        }
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(unescape("a &lt; b &amp;&amp; c &#x3E; d"), "a < b && c > d");
+        assert_eq!(unescape("line&#10;break"), "line\nbreak");
+        assert_eq!(
+            unescape("&unknown; & &#xFFFFFFFF;"),
+            "&unknown; & &#xFFFFFFFF;"
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            events("<a>text<b x=\"", 2),
+            vec![
+                Event::Start(Element {
+                    name: "a".to_owned(),
+                    attributes: Vec::new(),
+                    empty: false,
+                }),
+                Event::Text("text".to_owned()),
+            ]
+        );
+    }
+}
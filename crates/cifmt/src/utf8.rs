@@ -0,0 +1,240 @@
+//! Replacement of invalid UTF-8.
+//!
+//! Tools which relay the output of other programs (e.g., the captured stdout
+//! of a test) may emit bytes which are not valid UTF-8. Since JSON must be
+//! valid UTF-8, every line containing such bytes fails to parse, and enough
+//! of them cause detection to fail as well. The [`Utf8Replacer`] replaces
+//! each invalid sequence with U+FFFD REPLACEMENT CHARACTER before the tool's
+//! output is detected and parsed, and keeps count of the replacements so that
+//! they can be reported through an [`InvalidUtf8`] message.
+
+use crate::{
+    capture::Captured,
+    catalog::Title,
+    ci::Platform,
+    ci_message::{CiMessage, Classify, Severity},
+    timeline::Timed,
+};
+
+/// The UTF-8 encoding of U+FFFD REPLACEMENT CHARACTER.
+const REPLACEMENT: &[u8] = "\u{FFFD}".as_bytes();
+
+/// Replaces invalid UTF-8 sequences in a stream of bytes.
+///
+/// The input may be provided in arbitrary chunks, as the replacer retains an
+/// incomplete sequence at the end of one chunk until the next. A character
+/// split across two chunks is therefore preserved. Each maximal invalid
+/// sequence is replaced by a single replacement character, as for
+/// [`String::from_utf8_lossy`].
+///
+/// # Example
+///
+/// ```
+/// use cifmt::utf8::Utf8Replacer;
+///
+/// let mut replacer = Utf8Replacer::default();
+/// assert_eq!(replacer.replace(b"caf\xC3"), b"caf");
+/// assert_eq!(replacer.replace(b"\xA9 \xFF\n"), "é \u{FFFD}\n".as_bytes());
+/// assert_eq!(replacer.replaced(), 1);
+/// ```
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "Utf8Replacer reads more clearly at call sites than Replacer"
+)]
+#[derive(Debug, Clone, Default)]
+pub struct Utf8Replacer {
+    /// An incomplete sequence at the end of the previous chunk.
+    pending: Vec<u8>,
+    /// Buffer holding the replaced output, reused across calls.
+    buffer: Vec<u8>,
+    /// Number of invalid sequences replaced so far.
+    replaced: usize,
+}
+
+impl Utf8Replacer {
+    /// Replace invalid sequences in the next chunk of input.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The next chunk of the tool's output.
+    ///
+    /// # Returns
+    ///
+    /// The chunk with all invalid sequences replaced, excluding an incomplete
+    /// sequence at its end. This borrows the input directly if it is valid
+    /// UTF-8 in its entirety.
+    #[inline]
+    pub fn replace<'a>(&'a mut self, input: &'a [u8]) -> &'a [u8] {
+        if self.pending.is_empty() && core::str::from_utf8(input).is_ok() {
+            return input;
+        }
+
+        self.buffer.clear();
+        let joined;
+        let mut rest = if self.pending.is_empty() {
+            input
+        } else {
+            self.pending.extend_from_slice(input);
+            joined = core::mem::take(&mut self.pending);
+            &joined
+        };
+        while let Err(error) = core::str::from_utf8(rest) {
+            let (valid, invalid) = rest.split_at(error.valid_up_to());
+            self.buffer.extend_from_slice(valid);
+            let Some(len) = error.error_len() else {
+                // The sequence may be completed by the next chunk.
+                self.pending.extend_from_slice(invalid);
+                return &self.buffer;
+            };
+            self.buffer.extend_from_slice(REPLACEMENT);
+            self.replaced = self.replaced.saturating_add(1);
+            rest = invalid.get(len..).unwrap_or_default();
+        }
+        self.buffer.extend_from_slice(rest);
+        &self.buffer
+    }
+
+    /// Finish replacing at the end of the input.
+    ///
+    /// # Returns
+    ///
+    /// A replacement character if the input ended with an incomplete
+    /// sequence, and nothing otherwise.
+    #[inline]
+    pub fn finish(&mut self) -> &'static [u8] {
+        if self.pending.is_empty() {
+            return &[];
+        }
+        self.pending.clear();
+        self.replaced = self.replaced.saturating_add(1);
+        REPLACEMENT
+    }
+
+    /// Number of invalid sequences replaced so far.
+    #[must_use]
+    #[inline]
+    pub fn replaced(&self) -> usize {
+        self.replaced
+    }
+}
+
+/// Replace all invalid sequences in the given input.
+///
+/// This is a convenience for input which is available in its entirety; use
+/// a [`Utf8Replacer`] for streamed input.
+///
+/// # Arguments
+///
+/// * `input` - The input in which to replace invalid sequences.
+#[must_use]
+#[inline]
+pub fn replace_invalid(input: &[u8]) -> Vec<u8> {
+    String::from_utf8_lossy(input).into_owned().into_bytes()
+}
+
+/// A notice that invalid UTF-8 sequences were replaced in the tool's output.
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "InvalidUtf8 describes the input, not the module"
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InvalidUtf8 {
+    /// The number of invalid sequences which were replaced.
+    pub replaced: usize,
+}
+
+impl InvalidUtf8 {
+    /// The description of the replacement.
+    fn description(&self) -> String {
+        format!(
+            "Replaced {} invalid UTF-8 sequence(s) in the input with U+FFFD",
+            self.replaced
+        )
+    }
+}
+
+impl<P: Platform> CiMessage<P> for InvalidUtf8 {
+    #[inline]
+    fn format(&self) -> String {
+        P::annotate(
+            self.severity(),
+            self.description(),
+            None,
+            Some(Title::InvalidUtf8.text()),
+        )
+    }
+}
+
+impl Classify for InvalidUtf8 {
+    #[inline]
+    fn severity(&self) -> Severity {
+        Severity::Notice
+    }
+}
+
+impl Timed for InvalidUtf8 {}
+
+impl Captured for InvalidUtf8 {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{InvalidUtf8, Utf8Replacer, replace_invalid};
+    use crate::ci::{AzureDevOps, GitHub, Plain};
+    use crate::ci_message::CiMessage;
+
+    #[rstest]
+    #[case::valid("café → ok\n".as_bytes(), "café → ok\n", 0)]
+    #[case::invalid_byte(b"a\xFFb\n", "a\u{FFFD}b\n", 1)]
+    #[case::truncated_sequence(b"a\xE2\x86b\n", "a\u{FFFD}b\n", 1)]
+    #[case::incomplete_at_end(b"a\xE2\x86", "a\u{FFFD}", 1)]
+    #[case::json(
+        b"{\"stdout\":\"\xC0\xAF\"}\n",
+        "{\"stdout\":\"\u{FFFD}\u{FFFD}\"}\n",
+        2
+    )]
+    fn replaces(#[case] input: &[u8], #[case] expected: &str, #[case] replaced: usize) {
+        let mut replacer = Utf8Replacer::default();
+        let mut output = replacer.replace(input).to_vec();
+        output.extend_from_slice(replacer.finish());
+        assert_eq!(String::from_utf8_lossy(&output), expected);
+        assert_eq!(replacer.replaced(), replaced);
+        assert_eq!(replace_invalid(input), expected.as_bytes());
+    }
+
+    #[test]
+    fn split_across_chunks() {
+        let input = "a→b\u{1F600}c\n".as_bytes();
+
+        // Every possible split point must yield the same output.
+        for split in 0..=input.len() {
+            let (first, second) = input.split_at(split);
+            let mut replacer = Utf8Replacer::default();
+            let mut output = replacer.replace(first).to_vec();
+            output.extend_from_slice(replacer.replace(second));
+            output.extend_from_slice(replacer.finish());
+            assert_eq!(output, input, "split at {split}");
+            assert_eq!(replacer.replaced(), 0, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn format() {
+        let message = InvalidUtf8 { replaced: 3 };
+        insta::assert_snapshot!(
+            <InvalidUtf8 as CiMessage<Plain>>::format(&message),
+            @"notice:  Invalid UTF-8: Replaced 3 invalid UTF-8 sequence(s) in the input with U+FFFD"
+        );
+        insta::assert_snapshot!(
+            <InvalidUtf8 as CiMessage<GitHub>>::format(&message),
+            @"::notice title=Invalid UTF-8::Replaced 3 invalid UTF-8 sequence(s) in the input with U+FFFD"
+        );
+        insta::assert_snapshot!(
+            <InvalidUtf8 as CiMessage<AzureDevOps>>::format(&message),
+            @"notice:  Invalid UTF-8: Replaced 3 invalid UTF-8 sequence(s) in the input with U+FFFD"
+        );
+    }
+}
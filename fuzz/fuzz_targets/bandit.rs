@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Bandit`.
+
+#![no_main]
+
+use cifmt::tool::Bandit;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Bandit::default()), data);
+});
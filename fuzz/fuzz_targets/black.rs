@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Black`.
+
+#![no_main]
+
+use cifmt::tool::Black;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Black::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `CargoCheck`.
+
+#![no_main]
+
+use cifmt::tool::CargoCheck;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(CargoCheck::default()), data);
+});
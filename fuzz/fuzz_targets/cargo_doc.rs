@@ -0,0 +1,10 @@
+//! Fuzz the parser of `CargoDoc`.
+
+#![no_main]
+
+use cifmt::tool::CargoDoc;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(CargoDoc::default()), data);
+});
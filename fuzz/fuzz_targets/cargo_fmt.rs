@@ -0,0 +1,10 @@
+//! Fuzz the parser of `CargoFmt`.
+
+#![no_main]
+
+use cifmt::tool::CargoFmt;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(CargoFmt::default()), data);
+});
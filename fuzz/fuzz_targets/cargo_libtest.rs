@@ -0,0 +1,10 @@
+//! Fuzz the parser of `CargoLibtest`.
+
+#![no_main]
+
+use cifmt::tool::CargoLibtest;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(CargoLibtest::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `CargoUdeps`.
+
+#![no_main]
+
+use cifmt::tool::CargoUdeps;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(CargoUdeps::default()), data);
+});
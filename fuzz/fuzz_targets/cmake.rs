@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Cmake`.
+
+#![no_main]
+
+use cifmt::tool::Cmake;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Cmake::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Cppcheck`.
+
+#![no_main]
+
+use cifmt::tool::Cppcheck;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Cppcheck::default()), data);
+});
@@ -0,0 +1,11 @@
+//! Fuzz the detection of the tool, and the parser of the tool detected.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tool) = cifmt::tool::detect_any(data) {
+        cifmt_fuzz::exercise(tool, data);
+    }
+});
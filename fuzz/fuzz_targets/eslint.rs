@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Eslint`.
+
+#![no_main]
+
+use cifmt::tool::Eslint;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Eslint::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `GccDiagnostics`.
+
+#![no_main]
+
+use cifmt::tool::GccDiagnostics;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(GccDiagnostics::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Junit`.
+
+#![no_main]
+
+use cifmt::tool::Junit;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Junit::default()), data);
+});
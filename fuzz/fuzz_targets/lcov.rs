@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Lcov`.
+
+#![no_main]
+
+use cifmt::tool::Lcov;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Lcov::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `LspDiagnostics`.
+
+#![no_main]
+
+use cifmt::tool::LspDiagnostics;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(LspDiagnostics::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Miri`.
+
+#![no_main]
+
+use cifmt::tool::Miri;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Miri::default()), data);
+});
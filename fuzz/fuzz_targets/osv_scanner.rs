@@ -0,0 +1,10 @@
+//! Fuzz the parser of `OsvScanner`.
+
+#![no_main]
+
+use cifmt::tool::OsvScanner;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(OsvScanner::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Phpunit`.
+
+#![no_main]
+
+use cifmt::tool::Phpunit;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Phpunit::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Pmd`.
+
+#![no_main]
+
+use cifmt::tool::Pmd;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Pmd::default()), data);
+});
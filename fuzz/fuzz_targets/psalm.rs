@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Psalm`.
+
+#![no_main]
+
+use cifmt::tool::Psalm;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Psalm::default()), data);
+});
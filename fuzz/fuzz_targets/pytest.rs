@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Pytest`.
+
+#![no_main]
+
+use cifmt::tool::Pytest;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Pytest::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `RustcHuman`.
+
+#![no_main]
+
+use cifmt::tool::RustcHuman;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(RustcHuman::default()), data);
+});
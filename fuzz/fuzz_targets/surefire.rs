@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Surefire`.
+
+#![no_main]
+
+use cifmt::tool::Surefire;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Surefire::default()), data);
+});
@@ -0,0 +1,10 @@
+//! Fuzz the parser of `Xcodebuild`.
+
+#![no_main]
+
+use cifmt::tool::Xcodebuild;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cifmt_fuzz::exercise(Box::new(Xcodebuild::default()), data);
+});
@@ -0,0 +1,56 @@
+//! Fuzz targets for the parsers of cifmt.
+//!
+//! Each target feeds arbitrary input to a parser, which must never panic (see
+//! `cifmt::resilience`). The targets require a nightly toolchain and
+//! `cargo-fuzz`:
+//!
+//! ```bash
+//! cargo install cargo-fuzz
+//! cargo +nightly fuzz run cargo_check
+//! ```
+//!
+//! The recordings of `crates/cifmt/corpora` make a good seed corpus:
+//!
+//! ```bash
+//! mkdir -p fuzz/corpus/cargo_check
+//! cp crates/cifmt/corpora/cargo-check.in fuzz/corpus/cargo_check/
+//! ```
+
+use cifmt::{ci::PlatformKind, tool::AnyTool};
+
+/// The platforms for which each message is formatted.
+const PLATFORMS: [PlatformKind; 7] = [
+    PlatformKind::Plain,
+    PlatformKind::GitHub,
+    PlatformKind::AzureDevOps,
+    PlatformKind::Buildkite,
+    PlatformKind::CodeBuild,
+    PlatformKind::CloudBuild,
+    PlatformKind::Terminal,
+];
+
+/// Parse the input with a tool, and format each message for every platform.
+///
+/// The first byte of the input selects where the rest is split into two
+/// chunks, so that messages split across reads are exercised too.
+///
+/// # Arguments
+///
+/// * `tool` - The tool whose parser is fuzzed.
+/// * `data` - The input.
+#[inline]
+pub fn exercise(mut tool: Box<dyn AnyTool>, data: &[u8]) {
+    let Some((&split, rest)) = data.split_first() else {
+        return;
+    };
+    let (head, tail) = rest.split_at(usize::from(split).min(rest.len()));
+    let mut messages = tool.parse_dyn(head);
+    messages.extend(tool.parse_dyn(tail));
+    messages.extend(tool.finish_dyn());
+    for message in &messages {
+        for platform in PLATFORMS {
+            let _formatted = message.format_for(platform);
+        }
+        let _snippet = message.snippet();
+    }
+}